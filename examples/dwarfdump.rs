@@ -401,7 +401,7 @@ fn dump_eh_frame<R: Reader, W: Write>(w: &mut W, eh_frame: &gimli::EhFrame<R>) -
                 // TODO: augmentation
                 writeln!(w, "    code_align: {}", cie.code_alignment_factor())?;
                 writeln!(w, "    data_align: {}", cie.data_alignment_factor())?;
-                writeln!(w, "   ra_register: {:#x}", cie.return_address_register())?;
+                writeln!(w, "   ra_register: {:#x}", cie.return_address_register().0)?;
                 // TODO: aug_arg
                 dump_cfi_instructions(w, cie.instructions(), true)?;
                 writeln!(w)?;
@@ -474,7 +474,7 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                     writeln!(w, "                DW_CFA_advance_loc ({})", delta)?;
                 }
                 DefCfa { register, offset } => {
-                    writeln!(w, "                DW_CFA_def_cfa ({}, {})", register, offset)?;
+                    writeln!(w, "                DW_CFA_def_cfa ({}, {})", register.0, offset)?;
                 }
                 DefCfaSf {
                     register,
@@ -482,12 +482,12 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_def_cfa_sf ({}, {})",
-                        register,
+                        register.0,
                         factored_offset
                     )?;
                 }
                 DefCfaRegister { register } => {
-                    writeln!(w, "                DW_CFA_def_cfa_register ({})", register)?;
+                    writeln!(w, "                DW_CFA_def_cfa_register ({})", register.0)?;
                 }
                 DefCfaOffset { offset } => {
                     writeln!(w, "                DW_CFA_def_cfa_offset ({})", offset)?;
@@ -502,10 +502,10 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                     writeln!(w, "                DW_CFA_def_cfa_expression (...)")?;
                 }
                 Undefined { register } => {
-                    writeln!(w, "                DW_CFA_undefined ({})", register)?;
+                    writeln!(w, "                DW_CFA_undefined ({})", register.0)?;
                 }
                 SameValue { register } => {
-                    writeln!(w, "                DW_CFA_same_value ({})", register)?;
+                    writeln!(w, "                DW_CFA_same_value ({})", register.0)?;
                 }
                 Offset {
                     register,
@@ -513,7 +513,7 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_offset ({}, {})",
-                        register,
+                        register.0,
                         factored_offset
                     )?;
                 }
@@ -523,7 +523,7 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_offset_extended_sf ({}, {})",
-                        register,
+                        register.0,
                         factored_offset
                     )?;
                 }
@@ -533,7 +533,7 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_val_offset ({}, {})",
-                        register,
+                        register.0,
                         factored_offset
                     )?;
                 }
@@ -543,7 +543,7 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_val_offset_sf ({}, {})",
-                        register,
+                        register.0,
                         factored_offset
                     )?;
                 }
@@ -553,24 +553,24 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 } => {
                     writeln!(w,
                         "                DW_CFA_register ({}, {})",
-                        dest_register,
-                        src_register
+                        dest_register.0,
+                        src_register.0
                     )?;
                 }
                 Expression {
                     register,
                     expression: _,
                 } => {
-                    writeln!(w, "                DW_CFA_expression ({}, ...)", register)?;
+                    writeln!(w, "                DW_CFA_expression ({}, ...)", register.0)?;
                 }
                 ValExpression {
                     register,
                     expression: _,
                 } => {
-                    writeln!(w, "                DW_CFA_val_expression ({}, ...)", register)?;
+                    writeln!(w, "                DW_CFA_val_expression ({}, ...)", register.0)?;
                 }
                 Restore { register } => {
-                    writeln!(w, "                DW_CFA_restore ({})", register)?;
+                    writeln!(w, "                DW_CFA_restore ({})", register.0)?;
                 }
                 RememberState => {
                     writeln!(w, "                DW_CFA_remember_state")?;
@@ -578,6 +578,9 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 RestoreState => {
                     writeln!(w, "                DW_CFA_restore_state")?;
                 }
+                ArgsSize { size } => {
+                    writeln!(w, "                DW_CFA_GNU_args_size ({})", size)?;
+                }
                 Nop => {
                     writeln!(w, "                DW_CFA_nop")?;
                 }
@@ -607,7 +610,10 @@ fn dump_info<R: Reader>(
             Err(err) => {
                 writeln!(buf,
                     "Failed to parse abbreviations: {}",
-                    error::Error::description(&err)
+                    err.with_context(
+                        gimli::SectionId::DebugAbbrev,
+                        unit.debug_abbrev_offset().0 as u64,
+                    )
                 )?;
                 return Ok(());
             }
@@ -664,7 +670,10 @@ fn dump_types<R: Reader, W: Write>(
             Err(err) => {
                 writeln!(w,
                     "Failed to parse abbreviations: {}",
-                    error::Error::description(&err)
+                    err.with_context(
+                        gimli::SectionId::DebugAbbrev,
+                        unit.debug_abbrev_offset().0 as u64,
+                    )
                 )?;
                 continue;
             }
@@ -835,6 +844,9 @@ fn dump_attr_value<R: Reader, W: Write>(
         gimli::AttributeValue::Addr(address) => {
             writeln!(w, "0x{:08x}", address)?;
         }
+        gimli::AttributeValue::DebugAddrIndex(gimli::AddressIndex(index)) => {
+            writeln!(w, "<addr index 0x{:x}>", index)?;
+        }
         gimli::AttributeValue::Block(data) => {
             for byte in data.to_slice()?.iter() {
                 write!(w, "{:02x}", byte)?;
@@ -855,6 +867,23 @@ fn dump_attr_value<R: Reader, W: Write>(
                 writeln!(w, "{:?}", value)?;
             }
         }
+        gimli::AttributeValue::Data16(_) => {
+            if let (Some(udata), Some(sdata)) = (attr.udata128_value(), attr.sdata128_value()) {
+                if sdata >= 0 {
+                    writeln!(w, "{}", udata)?;
+                } else {
+                    writeln!(w, "{} ({})", udata, sdata)?;
+                }
+            } else {
+                writeln!(w, "{:?}", value)?;
+            }
+        }
+        gimli::AttributeValue::Sdata128(_) => {
+            writeln!(w, "{}", attr.sdata128_value().unwrap())?;
+        }
+        gimli::AttributeValue::Udata128(_) => {
+            writeln!(w, "{}", attr.udata128_value().unwrap())?;
+        }
         gimli::AttributeValue::Sdata(data) => {
             match attr.name() {
                 gimli::DW_AT_data_member_location => {
@@ -929,13 +958,34 @@ fn dump_attr_value<R: Reader, W: Write>(
         gimli::AttributeValue::LocationListsRef(offset) => {
             dump_loc_list(w, loclists, offset, unit)?;
         }
+        gimli::AttributeValue::DebugLocListsIndex(gimli::DebugLocListsIndex(index)) => {
+            writeln!(w, "<loclist index 0x{:x}>", index)?;
+        }
         gimli::AttributeValue::DebugMacinfoRef(gimli::DebugMacinfoOffset(offset)) => {
             writeln!(w, "{}", offset)?;
         }
+        gimli::AttributeValue::DebugMacroRef(gimli::DebugMacroOffset(offset)) => {
+            writeln!(w, "{}", offset)?;
+        }
+        gimli::AttributeValue::DebugStrOffsetsBase(gimli::DebugStrOffsetsBase(offset)) => {
+            writeln!(w, "0x{:08x}", offset)?;
+        }
+        gimli::AttributeValue::DebugAddrBase(gimli::DebugAddrBase(offset)) => {
+            writeln!(w, "0x{:08x}", offset)?;
+        }
+        gimli::AttributeValue::DebugLocListsBase(gimli::DebugLocListsBase(offset)) => {
+            writeln!(w, "0x{:08x}", offset)?;
+        }
+        gimli::AttributeValue::DebugRngListsBase(gimli::DebugRngListsBase(offset)) => {
+            writeln!(w, "0x{:08x}", offset)?;
+        }
         gimli::AttributeValue::RangeListsRef(offset) => {
             writeln!(w, "0x{:08x}", offset.0)?;
             dump_range_list(w, rnglists, offset, unit)?;
         }
+        gimli::AttributeValue::DebugRngListsIndex(gimli::DebugRngListsIndex(index)) => {
+            writeln!(w, "<rnglist index 0x{:x}>", index)?;
+        }
         gimli::AttributeValue::DebugTypesRef(signature) => {
             dump_type_signature(w, signature, unit.endian)?;
             writeln!(w, " <type signature>")?;
@@ -945,9 +995,15 @@ fn dump_attr_value<R: Reader, W: Write>(
         } else {
             writeln!(w, "<GOFF=0x{:08x}>", offset.0)?;
         },
+        gimli::AttributeValue::DebugStrOffsetsIndex(gimli::DebugStrOffsetsIndex(index)) => {
+            writeln!(w, "<str index 0x{:x}>", index)?;
+        }
         gimli::AttributeValue::DebugStrRefSup(offset) => {
             writeln!(w, "<SUP_GOFF=0x{:08x}>", offset.0)?;
         },
+        gimli::AttributeValue::DebugLineStrRef(offset) => {
+            writeln!(w, "<line_strp 0x{:08x}>", offset.0)?;
+        },
         gimli::AttributeValue::String(s) => {
             writeln!(w, "{}", s.to_string_lossy()?)?;
         }
@@ -972,6 +1028,9 @@ fn dump_attr_value<R: Reader, W: Write>(
         gimli::AttributeValue::Language(value) => {
             writeln!(w, "{}", value)?;
         }
+        gimli::AttributeValue::LanguageName(value) => {
+            writeln!(w, "{}", value)?;
+        }
         gimli::AttributeValue::AddressClass(value) => {
             writeln!(w, "{}", value)?;
         }
@@ -1128,7 +1187,7 @@ fn dump_op<R: Reader, W: Write>(
         },
         gimli::Operation::Register { base_type, register } => {
             if dwop == gimli::DW_OP_regx {
-                write!(w, " {}", register)?;
+                write!(w, " {}", register.0)?;
             }
             if base_type != UnitOffset(0) {
                 write!(w, " type 0x{:08x}", base_type.0)?;
@@ -1182,6 +1241,21 @@ fn dump_op<R: Reader, W: Write>(
         gimli::Operation::TextRelativeOffset { offset } => {
             write!(w, " 0x{:08x}", offset)?;
         }
+        gimli::Operation::AddressIndex { index } => {
+            write!(w, " 0x{:08x}", index.0)?;
+        }
+        gimli::Operation::ConstantIndex { index } => {
+            write!(w, " 0x{:08x}", index.0)?;
+        }
+        gimli::Operation::WasmLocal { index } => {
+            write!(w, " 0x0 {}", index)?;
+        }
+        gimli::Operation::WasmGlobal { index } => {
+            write!(w, " 0x1 {}", index)?;
+        }
+        gimli::Operation::WasmStack { index } => {
+            write!(w, " 0x2 {}", index)?;
+        }
         gimli::Operation::TypedLiteral { base_type, value } => {
             write!(w, " type 0x{:08x} contents 0x", base_type.0)?;
             for byte in value.to_slice()?.iter() {
@@ -1470,7 +1544,7 @@ fn dump_line<R: Reader, W: Write>(
                     writeln!(w,
                         "  {}\t{}\t{}\t{}\t{}",
                         i + 1,
-                        file.directory_index(),
+                        file.directory_index().0,
                         file.last_modification(),
                         file.length(),
                         file.path_name().to_string_lossy()?
@@ -1489,7 +1563,7 @@ fn dump_line<R: Reader, W: Write>(
                 writeln!(w, "<pc>        [lno,col]")?;
             }
             let mut rows = program.rows();
-            let mut file_index = 0;
+            let mut file_index = gimli::FileIndex(0);
             while let Some((header, row)) = rows.next_row()? {
                 let line = row.line().unwrap_or(0);
                 let column = match row.column() {
@@ -1,16 +1,20 @@
 //! Functions for parsing DWARF debugging abbreviations.
 
 use constants;
-use endianity::{EndianBuf, Endianity};
-use parser::{Error, Result};
-use reader::Reader;
+use endianity::{EndianBuf, Endianity, LittleEndian};
+use parser::{Error, ReaderLimits, Result};
+use reader::{Reader, ReaderOffset};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+use sync::Arc;
 use unit::UnitHeader;
 use vec::Vec;
 use btree_map;
-use Section;
+use {Section, SectionId};
 
 /// An offset into the `.debug_abbrev` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugAbbrevOffset<T = usize>(pub T);
 
 /// The `DebugAbbrev` struct represents the abbreviations describing
@@ -52,16 +56,31 @@ impl<R: Reader> DebugAbbrev<R> {
     pub fn abbreviations(
         &self,
         debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
+    ) -> Result<Abbreviations> {
+        self.abbreviations_with_limits(debug_abbrev_offset, &ReaderLimits::default())
+    }
+
+    /// Parse the abbreviations at the given `offset` within this
+    /// `.debug_abbrev` section, failing with `Error::LimitExceeded` if there
+    /// are more than `limits.max_abbreviations` of them.
+    pub fn abbreviations_with_limits(
+        &self,
+        debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
+        limits: &ReaderLimits,
     ) -> Result<Abbreviations> {
         let input = &mut self.debug_abbrev_section.clone();
         input.skip(debug_abbrev_offset.0)?;
-        Abbreviations::parse(input)
+        Abbreviations::parse(input, limits.max_abbreviations)
     }
 }
 
 impl<R: Reader> Section<R> for DebugAbbrev<R> {
-    fn section_name() -> &'static str {
-        ".debug_abbrev"
+    fn id() -> SectionId {
+        SectionId::DebugAbbrev
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_abbrev_section
     }
 }
 
@@ -78,10 +97,18 @@ impl<R: Reader> From<R> for DebugAbbrev<R> {
 /// Construct an `Abbreviations` instance with the
 /// [`abbreviations()`](struct.UnitHeader.html#method.abbreviations)
 /// method.
+///
+/// The abbreviations are checked when the set is parsed, but the attribute
+/// specifications of each abbreviation are only decoded the first time that
+/// the abbreviation is used. Large units can have thousands of abbreviations,
+/// and many uses only need a few of them.
 #[derive(Debug, Default, Clone)]
 pub struct Abbreviations {
-    vec: Vec<Abbreviation>,
-    map: btree_map::BTreeMap<u64, Abbreviation>,
+    vec: Vec<LazyAbbreviation>,
+    map: btree_map::BTreeMap<u64, LazyAbbreviation>,
+    // The encoded abbreviations that the offsets in `LazyAbbreviation` refer to.
+    // This is shared by clones of the set.
+    data: Arc<Vec<u8>>,
 }
 
 impl Abbreviations {
@@ -90,6 +117,7 @@ impl Abbreviations {
         Abbreviations {
             vec: Vec::new(),
             map: btree_map::BTreeMap::new(),
+            data: Arc::default(),
         }
     }
 
@@ -98,9 +126,19 @@ impl Abbreviations {
     /// Returns `Ok` if it is the first abbreviation in the set with its code,
     /// `Err` if the code is a duplicate and there already exists an
     /// abbreviation in the set with the given abbreviation's code.
+    #[cfg(test)]
     fn insert(&mut self, abbrev: Abbreviation) -> ::std::result::Result<(), ()> {
-        let code_usize = abbrev.code as usize;
-        if code_usize as u64 == abbrev.code {
+        let code = abbrev.code;
+        self.insert_lazy(code, LazyAbbreviation::from(abbrev))
+    }
+
+    fn insert_lazy(
+        &mut self,
+        code: u64,
+        abbrev: LazyAbbreviation,
+    ) -> ::std::result::Result<(), ()> {
+        let code_usize = code as usize;
+        if code_usize as u64 == code {
             // Optimize for sequential abbreviation codes by storing them
             // in a Vec, as long as the map doesn't already contain them.
             // A potential further optimization would be to allow some
@@ -108,7 +146,7 @@ impl Abbreviations {
             if code_usize - 1 < self.vec.len() {
                 return Err(());
             } else if code_usize - 1 == self.vec.len() {
-                if !self.map.is_empty() && self.map.contains_key(&abbrev.code) {
+                if !self.map.is_empty() && self.map.contains_key(&code) {
                     return Err(());
                 } else {
                     self.vec.push(abbrev);
@@ -116,7 +154,7 @@ impl Abbreviations {
                 }
             }
         }
-        match self.map.entry(abbrev.code) {
+        match self.map.entry(code) {
             btree_map::Entry::Occupied(_) => Err(()),
             btree_map::Entry::Vacant(entry) => {
                 entry.insert(abbrev);
@@ -125,31 +163,168 @@ impl Abbreviations {
         }
     }
 
+    /// Return the number of abbreviations in the set.
+    pub fn len(&self) -> usize {
+        self.vec.len() + self.map.len()
+    }
+
+    /// Return true if the set contains no abbreviations.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty() && self.map.is_empty()
+    }
+
     /// Get the abbreviation associated with the given code.
+    ///
+    /// Returns `None` if there is no abbreviation with the code, or if the
+    /// abbreviation could not be decoded.
     #[inline]
     pub fn get(&self, code: u64) -> Option<&Abbreviation> {
+        self.lookup(code).ok()
+    }
+
+    /// Get the abbreviation associated with the given code, decoding it if
+    /// this is its first use.
+    ///
+    /// Returns `Error::UnknownAbbreviation` if there is no abbreviation with
+    /// the code.
+    #[inline]
+    pub(crate) fn lookup(&self, code: u64) -> Result<&Abbreviation> {
         let code_usize = code as usize;
-        if code_usize as u64 == code && code_usize - 1 < self.vec.len() {
-            Some(&self.vec[code_usize - 1])
+        let abbrev = if code_usize as u64 == code && code_usize - 1 < self.vec.len() {
+            &self.vec[code_usize - 1]
         } else {
-            self.map.get(&code)
-        }
+            self.map.get(&code).ok_or(Error::UnknownAbbreviation)?
+        };
+        abbrev.get(&self.data)
     }
 
     /// Parse a series of abbreviations, terminated by a null abbreviation.
-    fn parse<R: Reader>(input: &mut R) -> Result<Abbreviations> {
+    fn parse<R: Reader>(input: &mut R, max_count: Option<usize>) -> Result<Abbreviations> {
+        let start = input.clone();
         let mut abbrevs = Abbreviations::empty();
+        let mut count = 0;
 
-        while let Some(abbrev) = Abbreviation::parse(input)? {
-            if abbrevs.insert(abbrev).is_err() {
+        loop {
+            let offset = input.offset_from(&start)?.into_u64() as usize;
+            let (code, abbrev) = match LazyAbbreviation::parse(input, offset)? {
+                Some(abbrev) => abbrev,
+                None => break,
+            };
+            count += 1;
+            if let Some(max_count) = max_count {
+                if count > max_count {
+                    return Err(Error::LimitExceeded);
+                }
+            }
+            if abbrevs.insert_lazy(code, abbrev).is_err() {
                 return Err(Error::DuplicateAbbreviationCode);
             }
         }
 
+        if cfg!(feature = "std") {
+            let mut data = start;
+            let len = input.offset_from(&data)?;
+            data.truncate(len)?;
+            abbrevs.data = Arc::new(data.to_slice()?.into_owned());
+        }
         Ok(abbrevs)
     }
 }
 
+/// An abbreviation in an `Abbreviations` set, which is decoded on first use.
+#[derive(Debug, Clone)]
+struct LazyAbbreviation {
+    // The offset of the abbreviation within `Abbreviations::data`.
+    offset: usize,
+    abbrev: OnceLock<Abbreviation>,
+}
+
+impl LazyAbbreviation {
+    /// Check the abbreviation at the start of `input`, and skip over it.
+    ///
+    /// Return `None` for the null abbreviation, and the code and lazy
+    /// abbreviation for an actual abbreviation.
+    #[cfg(feature = "std")]
+    fn parse<R: Reader>(input: &mut R, offset: usize) -> Result<Option<(u64, LazyAbbreviation)>> {
+        let code = input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+
+        Abbreviation::parse_tag(input)?;
+        Abbreviation::parse_has_children(input)?;
+        while AttributeSpecification::parse(input)?.is_some() {}
+        let abbrev = LazyAbbreviation {
+            offset,
+            abbrev: OnceLock::new(),
+        };
+        Ok(Some((code, abbrev)))
+    }
+
+    /// Without `std` there is no thread safe lazy initialization, so the
+    /// abbreviation is decoded immediately.
+    #[cfg(not(feature = "std"))]
+    fn parse<R: Reader>(input: &mut R, offset: usize) -> Result<Option<(u64, LazyAbbreviation)>> {
+        match Abbreviation::parse(input)? {
+            Some(abbrev) => {
+                let code = abbrev.code;
+                let mut abbrev = LazyAbbreviation::from(abbrev);
+                abbrev.offset = offset;
+                Ok(Some((code, abbrev)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Return the abbreviation, decoding it from `data` if this is its first
+    /// use.
+    #[inline]
+    fn get(&self, data: &[u8]) -> Result<&Abbreviation> {
+        if let Some(abbrev) = self.abbrev.get() {
+            return Ok(abbrev);
+        }
+        let data = data.get(self.offset..).ok_or(Error::UnknownAbbreviation)?;
+        let input = &mut EndianBuf::new(data, LittleEndian);
+        let abbrev = Abbreviation::parse(input)?.ok_or(Error::UnknownAbbreviation)?;
+        // Another thread may have decoded it first, but the result is the same.
+        Ok(self.abbrev.get_or_init(|| abbrev))
+    }
+}
+
+impl From<Abbreviation> for LazyAbbreviation {
+    fn from(abbrev: Abbreviation) -> Self {
+        LazyAbbreviation {
+            offset: 0,
+            abbrev: OnceLock::from(abbrev),
+        }
+    }
+}
+
+/// A replacement for `std::sync::OnceLock` that is always initialized.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Clone)]
+struct OnceLock<T>(T);
+
+#[cfg(not(feature = "std"))]
+impl<T> OnceLock<T> {
+    #[inline]
+    fn get(&self) -> Option<&T> {
+        Some(&self.0)
+    }
+
+    #[inline]
+    fn get_or_init<F: FnOnce() -> T>(&self, _f: F) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> Self {
+        OnceLock(value)
+    }
+}
+
 /// An abbreviation describes the shape of a `DebuggingInformationEntry`'s type:
 /// its code, tag type, whether it has children, and its set of attributes.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -307,24 +482,48 @@ impl AttributeSpecification {
 
             constants::DW_FORM_implicit_const => Some(0),
 
+            constants::DW_FORM_flag_present => Some(0),
+
             constants::DW_FORM_flag |
-            constants::DW_FORM_flag_present |
             constants::DW_FORM_data1 |
-            constants::DW_FORM_ref1 => Some(1),
+            constants::DW_FORM_ref1 |
+            constants::DW_FORM_strx1 |
+            constants::DW_FORM_addrx1 => Some(1),
 
             constants::DW_FORM_data2 |
-            constants::DW_FORM_ref2 => Some(2),
+            constants::DW_FORM_ref2 |
+            constants::DW_FORM_strx2 |
+            constants::DW_FORM_addrx2 => Some(2),
+
+            constants::DW_FORM_strx3 |
+            constants::DW_FORM_addrx3 => Some(3),
 
             constants::DW_FORM_data4 |
-            constants::DW_FORM_ref4 => Some(4),
+            constants::DW_FORM_ref4 |
+            constants::DW_FORM_ref_sup4 |
+            constants::DW_FORM_strx4 |
+            constants::DW_FORM_addrx4 => Some(4),
 
             constants::DW_FORM_data8 |
-            constants::DW_FORM_ref8 => Some(8),
+            constants::DW_FORM_ref8 |
+            constants::DW_FORM_ref_sig8 |
+            constants::DW_FORM_ref_sup8 => Some(8),
+
+            constants::DW_FORM_data16 => Some(16),
+
+            // DWARF version 2 specifies that DW_FORM_ref_addr has the same
+            // size as an address on the target system.
+            constants::DW_FORM_ref_addr if header.version() == 2 => {
+                Some(header.address_size() as usize)
+            }
 
             constants::DW_FORM_sec_offset |
             constants::DW_FORM_ref_addr |
-            constants::DW_FORM_ref_sig8 |
-            constants::DW_FORM_strp => Some(header.format().word_size() as usize),
+            constants::DW_FORM_strp |
+            constants::DW_FORM_line_strp |
+            constants::DW_FORM_strp_sup |
+            constants::DW_FORM_GNU_ref_alt |
+            constants::DW_FORM_GNU_strp_alt => Some(header.format().word_size() as usize),
 
             // Variably sized forms.
             constants::DW_FORM_block |
@@ -386,7 +585,7 @@ pub mod tests {
     use super::*;
     use constants;
     use endianity::{EndianBuf, LittleEndian};
-    use parser::Error;
+    use parser::{Error, Format};
     use self::test_assembler::Section;
     #[cfg(target_pointer_width = "32")]
     use std::u32;
@@ -514,6 +713,9 @@ pub mod tests {
         assert_abbrev(&abbrevs, 1);
         assert_abbrev(&abbrevs, 2);
         assert_abbrev(&abbrevs, 3);
+        assert_eq!(abbrevs.len(), 3);
+        assert!(!abbrevs.is_empty());
+        assert!(Abbreviations::empty().is_empty());
 
         // Duplicate code in vec.
         let mut abbrevs = Abbreviations::empty();
@@ -607,12 +809,61 @@ pub mod tests {
             ],
         );
 
-        let abbrevs = Abbreviations::parse(rest).expect("Should parse abbreviations");
+        let abbrevs = Abbreviations::parse(rest, None).expect("Should parse abbreviations");
         assert_eq!(abbrevs.get(1), Some(&abbrev1));
         assert_eq!(abbrevs.get(2), Some(&abbrev2));
         assert_eq!(*rest, EndianBuf::new(&expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_abbreviations_lazy() {
+        let buf = Section::new()
+            .abbrev(1, constants::DW_TAG_subprogram, constants::DW_CHILDREN_no)
+            .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+            .abbrev_attr_null()
+            .abbrev(3, constants::DW_TAG_variable, constants::DW_CHILDREN_no)
+            .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_strp)
+            .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let rest = &mut EndianBuf::new(&*buf, LittleEndian);
+
+        let abbrevs = Abbreviations::parse(rest, None).expect("Should parse abbreviations");
+        assert_eq!(abbrevs.len(), 2);
+        assert!(abbrevs.vec[0].abbrev.get().is_none());
+        assert!(abbrevs.map[&3].abbrev.get().is_none());
+
+        let abbrev3 = Abbreviation::new(
+            3,
+            constants::DW_TAG_variable,
+            constants::DW_CHILDREN_no,
+            vec![
+                AttributeSpecification::new(constants::DW_AT_name, constants::DW_FORM_strp, None),
+            ],
+        );
+        assert_eq!(abbrevs.get(3), Some(&abbrev3));
+        assert!(abbrevs.vec[0].abbrev.get().is_none());
+        assert!(abbrevs.map[&3].abbrev.get().is_some());
+        assert_eq!(abbrevs.clone().get(3), Some(&abbrev3));
+        assert_eq!(abbrevs.get(1).map(Abbreviation::tag), Some(constants::DW_TAG_subprogram));
+        assert_eq!(abbrevs.get(2), None);
+        assert_eq!(abbrevs.lookup(2), Err(Error::UnknownAbbreviation));
+
+        // Clones share the encoded abbreviations.
+        let mut abbrevs = Abbreviations::parse(&mut EndianBuf::new(&*buf, LittleEndian), None)
+            .expect("Should parse abbreviations");
+        assert!(Arc::ptr_eq(&abbrevs.data, &abbrevs.clone().data));
+
+        // Decoding errors are returned instead of panicking.
+        abbrevs.data = Arc::new(buf[..2].to_vec());
+        match abbrevs.lookup(1) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+        assert_eq!(abbrevs.get(1), None);
+    }
+
     #[test]
     fn test_parse_abbreviations_duplicate() {
         let expected_rest = [1, 2, 3, 4];
@@ -634,12 +885,40 @@ pub mod tests {
             .unwrap();
         let buf = &mut EndianBuf::new(&*buf, LittleEndian);
 
-        match Abbreviations::parse(buf) {
+        match Abbreviations::parse(buf, None) {
             Err(Error::DuplicateAbbreviationCode) => {}
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
 
+    #[test]
+    fn test_abbreviations_with_limits() {
+        let buf = Section::new()
+            .abbrev(1, constants::DW_TAG_subprogram, constants::DW_CHILDREN_no)
+            .abbrev_attr_null()
+            .abbrev(2, constants::DW_TAG_variable, constants::DW_CHILDREN_no)
+            .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+
+        let mut limits = ReaderLimits::default();
+        limits.max_abbreviations = Some(2);
+        let abbrevs = debug_abbrev
+            .abbreviations_with_limits(DebugAbbrevOffset(0), &limits)
+            .expect("Should parse abbreviations");
+        assert!(abbrevs.get(2).is_some());
+
+        limits.max_abbreviations = Some(1);
+        assert_eq!(
+            debug_abbrev
+                .abbreviations_with_limits(DebugAbbrevOffset(0), &limits)
+                .map(|_| ()),
+            Err(Error::LimitExceeded)
+        );
+    }
+
     #[test]
     fn test_parse_abbreviation_tag_ok() {
         let buf = [0x01, 0x02];
@@ -734,7 +1013,7 @@ pub mod tests {
         let buf = &mut EndianBuf::new(&*buf, LittleEndian);
 
         match Abbreviation::parse(buf) {
-            Err(Error::UnexpectedEof) => {},
+            Err(Error::UnexpectedEof(_)) => {},
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         }
     }
@@ -802,4 +1081,35 @@ pub mod tests {
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
+
+    #[test]
+    fn test_attribute_specification_size() {
+        let buf = [];
+        let header = |version, format| {
+            UnitHeader::new(
+                0,
+                version,
+                DebugAbbrevOffset(0),
+                4,
+                format,
+                EndianBuf::new(&buf, LittleEndian),
+            )
+        };
+        let size = |form, version, format| {
+            AttributeSpecification::new(constants::DW_AT_name, form, None)
+                .size(&header(version, format))
+        };
+
+        assert_eq!(size(constants::DW_FORM_strp, 4, Format::Dwarf32), Some(4));
+        assert_eq!(size(constants::DW_FORM_strp, 4, Format::Dwarf64), Some(8));
+        assert_eq!(size(constants::DW_FORM_sec_offset, 4, Format::Dwarf64), Some(8));
+        assert_eq!(size(constants::DW_FORM_GNU_strp_alt, 4, Format::Dwarf64), Some(8));
+        assert_eq!(size(constants::DW_FORM_ref_addr, 2, Format::Dwarf64), Some(4));
+        assert_eq!(size(constants::DW_FORM_ref_addr, 3, Format::Dwarf64), Some(8));
+        assert_eq!(size(constants::DW_FORM_ref_sig8, 4, Format::Dwarf32), Some(8));
+        assert_eq!(size(constants::DW_FORM_flag_present, 4, Format::Dwarf32), Some(0));
+        assert_eq!(size(constants::DW_FORM_strx3, 5, Format::Dwarf32), Some(3));
+        assert_eq!(size(constants::DW_FORM_addrx4, 5, Format::Dwarf32), Some(4));
+        assert_eq!(size(constants::DW_FORM_udata, 4, Format::Dwarf32), None);
+    }
 }
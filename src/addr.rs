@@ -0,0 +1,306 @@
+use endianity::{EndianBuf, Endianity};
+use fallible_iterator::FallibleIterator;
+use parser::{self, Error, Format, Result};
+use reader::{Reader, ReaderOffset};
+use rnglists::{AddressIndex, DebugAddrBase};
+use {Section, SectionId};
+
+/// The `DebugAddr` struct represents the address table found in the
+/// `.debug_addr` section.
+///
+/// Each unit that uses the `DW_FORM_addrx` forms has a set of entries in this
+/// section, and its `DW_AT_addr_base` attribute gives the offset of the first
+/// entry. In DWARF version 5, each set of entries is preceded by a header. The
+/// pre-standard GNU split DWARF extension to DWARF version 4 uses the
+/// `DW_AT_GNU_addr_base` attribute instead, and the section doesn't contain
+/// any headers.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugAddr<R: Reader> {
+    debug_addr_section: R,
+}
+
+impl<'input, Endian> DebugAddr<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugAddr` instance from the data in the `.debug_addr`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_addr` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugAddr, LittleEndian};
+    ///
+    /// # let buf = [0x00, 0x01, 0x02, 0x03];
+    /// # let read_debug_addr_section_somehow = || &buf;
+    /// let debug_addr = DebugAddr::new(read_debug_addr_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_addr_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(debug_addr_section, endian))
+    }
+}
+
+impl<R: Reader> DebugAddr<R> {
+    /// Return the address at the given index of a unit's entries.
+    ///
+    /// `address_size` must be the address size of the unit, and `base` must be
+    /// the unit's `DW_AT_addr_base` or `DW_AT_GNU_addr_base` attribute. For
+    /// split units, this attribute is found in the skeleton unit.
+    ///
+    /// ```
+    /// use gimli::{AddressIndex, DebugAddr, DebugAddrBase, LittleEndian};
+    ///
+    /// let buf = [
+    ///     // Header: unit length, version, address size, segment selector size.
+    ///     0x0c, 0x00, 0x00, 0x00, 0x05, 0x00, 0x04, 0x00,
+    ///     // Entries.
+    ///     0x00, 0x10, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00,
+    /// ];
+    /// let debug_addr = DebugAddr::new(&buf, LittleEndian);
+    /// let address = debug_addr.get_address(4, DebugAddrBase(8), AddressIndex(1));
+    /// assert_eq!(address, Ok(0x2000));
+    /// ```
+    pub fn get_address(
+        &self,
+        address_size: u8,
+        base: DebugAddrBase<R::Offset>,
+        index: AddressIndex,
+    ) -> Result<u64> {
+        let input = &mut self.debug_addr_section.clone();
+        input.skip(base.0)?;
+        let offset = index
+            .0
+            .checked_mul(u64::from(address_size))
+            .ok_or(Error::OffsetOutOfBounds)?;
+        input.skip(R::Offset::from_u64(offset)?)?;
+        input.read_address(address_size)
+    }
+
+    /// Iterate the headers of the sets of entries in the section.
+    ///
+    /// This requires every set of entries to have a DWARF version 5 header,
+    /// so it can't be used for the pre-standard GNU split DWARF extension.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn headers(&self) -> DebugAddrHeadersIter<R> {
+        DebugAddrHeadersIter {
+            input: self.debug_addr_section.clone(),
+            offset: R::Offset::from_u8(0),
+        }
+    }
+}
+
+impl<R: Reader> Section<R> for DebugAddr<R> {
+    fn id() -> SectionId {
+        SectionId::DebugAddr
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_addr_section
+    }
+}
+
+impl<R: Reader> From<R> for DebugAddr<R> {
+    fn from(debug_addr_section: R) -> Self {
+        DebugAddr { debug_addr_section }
+    }
+}
+
+/// The header of a set of entries in the `.debug_addr` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugAddrHeader<T = usize> {
+    offset: T,
+    length: T,
+    format: Format,
+    version: u16,
+    address_size: u8,
+    segment_selector_size: u8,
+}
+
+impl<T: ReaderOffset> DebugAddrHeader<T> {
+    /// Return the serialized size of the header for the given format.
+    pub fn size_of_header(format: Format) -> u8 {
+        // initial_length + version + address_size + segment_selector_size
+        format.initial_length_size() + 2 + 1 + 1
+    }
+
+    /// Return the offset of this header within the section.
+    pub fn offset(&self) -> T {
+        self.offset
+    }
+
+    /// Return the length of the entries, including the header fields that
+    /// follow the initial length field.
+    pub fn length(&self) -> T {
+        self.length
+    }
+
+    /// Return the format of the header.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Return the version of the header.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the size in bytes of each address.
+    pub fn address_size(&self) -> u8 {
+        self.address_size
+    }
+
+    /// Return the size in bytes of the segment selectors.
+    pub fn segment_selector_size(&self) -> u8 {
+        self.segment_selector_size
+    }
+
+    /// Return the offset of the first entry, which is the value that units
+    /// use for their `DW_AT_addr_base` attribute.
+    pub fn base(&self) -> DebugAddrBase<T> {
+        DebugAddrBase(self.offset + T::from_u8(Self::size_of_header(self.format)))
+    }
+
+    /// Return the number of entries.
+    pub fn count(&self) -> u64 {
+        let entry_size = u64::from(self.address_size) + u64::from(self.segment_selector_size);
+        if entry_size == 0 {
+            return 0;
+        }
+        (self.length.into_u64() - 4) / entry_size
+    }
+}
+
+/// An iterator over the headers in the `.debug_addr` section.
+#[derive(Debug, Clone)]
+pub struct DebugAddrHeadersIter<R: Reader> {
+    input: R,
+    offset: R::Offset,
+}
+
+impl<R: Reader> DebugAddrHeadersIter<R> {
+    /// Advance the iterator to the next header.
+    pub fn next(&mut self) -> Result<Option<DebugAddrHeader<R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse_header() {
+            Ok(header) => {
+                self.offset += R::Offset::from_u8(header.format.initial_length_size()) +
+                    header.length;
+                Ok(Some(header))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse_header(&mut self) -> Result<DebugAddrHeader<R::Offset>> {
+        let (length, format) = parser::parse_initial_length(&mut self.input)?;
+        let length = R::Offset::from_u64(length)?;
+        let mut rest = self.input.split(length)?;
+
+        let version = rest.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+        let address_size = rest.read_u8()?;
+        let segment_selector_size = rest.read_u8()?;
+
+        Ok(DebugAddrHeader {
+            offset: self.offset,
+            length,
+            format,
+            version,
+            address_size,
+            segment_selector_size,
+        })
+    }
+}
+
+impl<R: Reader> FallibleIterator for DebugAddrHeadersIter<R> {
+    type Item = DebugAddrHeader<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        DebugAddrHeadersIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Section};
+    use super::*;
+    use endianity::LittleEndian;
+
+    #[test]
+    fn test_debug_addr_headers() {
+        let section = Section::with_endian(Endian::Little)
+            // A DWARF 32 set of entries.
+            .L32(0x0c)
+            .L16(5)
+            .D8(4)
+            .D8(0)
+            .L32(0x1000)
+            .L32(0x2000)
+            // A DWARF 64 set of entries.
+            .L32(0xffff_ffff)
+            .L64(0x0c)
+            .L16(5)
+            .D8(8)
+            .D8(0)
+            .L64(0x3000);
+        let buf = section.get_contents().unwrap();
+        let debug_addr = DebugAddr::new(&buf, LittleEndian);
+
+        let mut headers = debug_addr.headers();
+        let first = headers.next().unwrap().unwrap();
+        assert_eq!(first.offset(), 0);
+        assert_eq!(first.format(), Format::Dwarf32);
+        assert_eq!(first.address_size(), 4);
+        assert_eq!(first.count(), 2);
+        assert_eq!(first.base(), DebugAddrBase(8));
+        assert_eq!(debug_addr.get_address(4, first.base(), AddressIndex(1)), Ok(0x2000));
+
+        let second = headers.next().unwrap().unwrap();
+        assert_eq!(second.offset(), 0x10);
+        assert_eq!(second.format(), Format::Dwarf64);
+        assert_eq!(second.count(), 1);
+        assert_eq!(second.base(), DebugAddrBase(0x20));
+        assert_eq!(debug_addr.get_address(8, second.base(), AddressIndex(0)), Ok(0x3000));
+
+        assert_eq!(headers.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_debug_addr_headers_bad_version() {
+        let buf = [0x04, 0x00, 0x00, 0x00, 0x04, 0x00, 0x08, 0x00];
+        let debug_addr = DebugAddr::new(&buf, LittleEndian);
+        let mut headers = debug_addr.headers();
+        assert_eq!(headers.next(), Err(Error::UnknownVersion(4)));
+        assert_eq!(headers.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_debug_addr_gnu_split() {
+        // The pre-standard GNU extension has no headers, and the skeleton
+        // unit's `DW_AT_GNU_addr_base` is an offset of the first entry.
+        let buf = [
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00,
+        ];
+        let debug_addr = DebugAddr::new(&buf, LittleEndian);
+        assert_eq!(debug_addr.get_address(4, DebugAddrBase(4), AddressIndex(1)), Ok(0x3000));
+        match debug_addr.get_address(4, DebugAddrBase(4), AddressIndex(2)) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+}
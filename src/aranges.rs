@@ -6,7 +6,7 @@ use reader::{Reader, ReaderOffset};
 use unit::{parse_debug_info_offset, DebugInfoOffset};
 use std::cmp::Ordering;
 use std::marker::PhantomData;
-use Section;
+use {Section, SectionId};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct ArangeHeader<T = usize> {
@@ -110,7 +110,13 @@ impl<R: Reader> LookupParser<R> for ArangeParser<R> {
         // The first tuple following the header in each set begins at an offset that is
         // a multiple of the size of a single tuple (that is, the size of a segment selector
         // plus twice the size of an address).
-        let tuple_length = 2 * address_size + segment_size;
+        let tuple_length = address_size
+            .checked_mul(2)
+            .and_then(|x| x.checked_add(segment_size))
+            .ok_or(Error::UnsupportedAddressSize(address_size))?;
+        if tuple_length == 0 {
+            return Err(Error::UnsupportedAddressSize(address_size));
+        }
         let padding = if header_length % tuple_length == 0 {
             0
         } else {
@@ -219,8 +225,12 @@ impl<R: Reader> DebugAranges<R> {
 }
 
 impl<R: Reader> Section<R> for DebugAranges<R> {
-    fn section_name() -> &'static str {
-        ".debug_aranges"
+    fn id() -> SectionId {
+        SectionId::DebugAranges
+    }
+
+    fn reader(&self) -> &R {
+        self.0.reader()
     }
 }
 
@@ -238,6 +248,15 @@ impl<R: Reader> From<R> for DebugAranges<R> {
 pub struct ArangeEntryIter<R: Reader>(LookupEntryIter<R, ArangeParser<R>>);
 
 impl<R: Reader> ArangeEntryIter<R> {
+    /// Return the section offset of the next arange, or of the set header
+    /// before it, that the iterator will parse.
+    ///
+    /// If parsing fails, this is the offset of the arange or header that failed.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.0.offset()
+    }
+
     /// Advance the iterator and return the next arange.
     ///
     /// Returns the newly parsed arange as `Ok(Some(arange))`. Returns `Ok(None)`
@@ -316,6 +335,77 @@ mod tests {
                    });
     }
 
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_parse_header_dwarf64() {
+        let buf = [
+            // 64-bit length = 36.
+            0xff, 0xff, 0xff, 0xff,
+            0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Version.
+            0x02, 0x00,
+            // Offset.
+            0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x00,
+            // Address size.
+            0x08,
+            // Segment size.
+            0x00,
+            // Length to here = 12 + 2 + 8 + 1 + 1 = 24, so 8 bytes of padding
+            // are needed to align to the tuple length of 16.
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+
+            // Dummy arange.
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+
+            // Dummy next arange.
+            0x30, 0x00, 0x00, 0x00,
+        ];
+
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+
+        let (tuples, header) = ArangeParser::parse_header(rest)
+            .expect("should parse header ok");
+
+        assert_eq!(*rest, EndianBuf::new(&buf[buf.len() - 4..], LittleEndian));
+        assert_eq!(tuples, EndianBuf::new(&buf[buf.len() - 20..buf.len() - 4], LittleEndian));
+        assert_eq!(header,
+                   ArangeHeader {
+                       format: Format::Dwarf64,
+                       length: 0x24,
+                       version: 2,
+                       offset: DebugInfoOffset(0x04030201),
+                       address_size: 8,
+                       segment_size: 0,
+                   });
+    }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_parse_header_bad_tuple_length() {
+        for &(address_size, segment_size) in &[(0, 0), (0x80, 0), (0x70, 0x20)] {
+            let buf = [
+                // 32-bit length = 8.
+                0x08, 0x00, 0x00, 0x00,
+                // Version.
+                0x02, 0x00,
+                // Offset.
+                0x01, 0x02, 0x03, 0x04,
+                // Address size.
+                address_size,
+                // Segment size.
+                segment_size,
+            ];
+
+            let rest = &mut EndianBuf::new(&buf, LittleEndian);
+            match ArangeParser::parse_header(rest) {
+                Err(Error::UnsupportedAddressSize(size)) => assert_eq!(size, address_size),
+                otherwise => panic!("Unexpected result: {:?}", otherwise),
+            }
+        }
+    }
+
     #[test]
     fn test_parse_entry_ok() {
         let header = ArangeHeader {
@@ -0,0 +1,987 @@
+//! DWARF register numbers, and the mapping between register numbers and
+//! register names for common architectures.
+//!
+//! The mapping of DWARF register numbers to machine registers is defined by
+//! each architecture's ABI. The register numbers are used by call frame
+//! information, and by the `DW_OP_reg*` and `DW_OP_breg*` operations of
+//! DWARF expressions.
+
+use constants::{self, DwAddr};
+
+/// A DWARF register number.
+///
+/// The meaning of a register number is architecture dependent. The
+/// architecture types in this module, such as `X86_64`, define constants for
+/// the register numbers, and map them to and from register names.
+///
+/// ```
+/// use gimli::{Register, X86_64};
+///
+/// assert_eq!(X86_64::RSP, Register(7));
+/// assert_eq!(X86_64::name(Register(7)), Some("rsp"));
+/// assert_eq!(X86_64::from_name("rsp"), Some(X86_64::RSP));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Register(pub u16);
+
+macro_rules! registers {
+    ($arch:ident, { $($register:ident = ($value:expr, $name:expr),)+ }) => {
+        #[allow(missing_docs)]
+        impl $arch {
+            $(
+                pub const $register: Register = Register($value);
+            )+
+        }
+
+        impl $arch {
+            /// Return the name of the given register, or `None` if the
+            /// register number is not known for this architecture.
+            pub fn name(register: Register) -> Option<&'static str> {
+                match register {
+                    $(
+                        $arch::$register => Some($name),
+                    )+
+                    _ => None,
+                }
+            }
+
+            /// Return the register with the given name, or `None` if the name
+            /// is not known for this architecture.
+            pub fn from_name(name: &str) -> Option<Register> {
+                match name {
+                    $(
+                        $name => Some($arch::$register),
+                    )+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+macro_rules! address_classes {
+    ($arch:ident, { $($class:ident,)+ }) => {
+        impl $arch {
+            /// Return the name of the given `DW_AT_address_class` value, or
+            /// `None` if the address class is not known for this architecture.
+            pub fn address_class_name(class: DwAddr) -> Option<&'static str> {
+                match class {
+                    $(
+                        constants::$class => Some(stringify!($class)),
+                    )+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+/// The 32-bit x86 architecture, as described by the i386 System V psABI.
+#[derive(Debug, Clone, Copy)]
+pub struct X86;
+
+registers!(X86, {
+    EAX = (0, "eax"),
+    ECX = (1, "ecx"),
+    EDX = (2, "edx"),
+    EBX = (3, "ebx"),
+    ESP = (4, "esp"),
+    EBP = (5, "ebp"),
+    ESI = (6, "esi"),
+    EDI = (7, "edi"),
+    RA = (8, "RA"),
+    EFLAGS = (9, "eflags"),
+    ST0 = (11, "st0"),
+    ST1 = (12, "st1"),
+    ST2 = (13, "st2"),
+    ST3 = (14, "st3"),
+    ST4 = (15, "st4"),
+    ST5 = (16, "st5"),
+    ST6 = (17, "st6"),
+    ST7 = (18, "st7"),
+    XMM0 = (21, "xmm0"),
+    XMM1 = (22, "xmm1"),
+    XMM2 = (23, "xmm2"),
+    XMM3 = (24, "xmm3"),
+    XMM4 = (25, "xmm4"),
+    XMM5 = (26, "xmm5"),
+    XMM6 = (27, "xmm6"),
+    XMM7 = (28, "xmm7"),
+    MM0 = (29, "mm0"),
+    MM1 = (30, "mm1"),
+    MM2 = (31, "mm2"),
+    MM3 = (32, "mm3"),
+    MM4 = (33, "mm4"),
+    MM5 = (34, "mm5"),
+    MM6 = (35, "mm6"),
+    MM7 = (36, "mm7"),
+    MXCSR = (39, "mxcsr"),
+    ES = (40, "es"),
+    CS = (41, "cs"),
+    SS = (42, "ss"),
+    DS = (43, "ds"),
+    FS = (44, "fs"),
+    GS = (45, "gs"),
+    TR = (48, "tr"),
+    LDTR = (49, "ldtr"),
+    FS_BASE = (93, "fs.base"),
+    GS_BASE = (94, "gs.base"),
+});
+
+address_classes!(X86, {
+    DW_ADDR_none,
+    DW_ADDR_x86_near16,
+    DW_ADDR_x86_far16,
+    DW_ADDR_x86_huge16,
+    DW_ADDR_x86_near32,
+    DW_ADDR_x86_far32,
+});
+
+/// The x86-64 architecture, as described by the AMD64 System V psABI.
+#[derive(Debug, Clone, Copy)]
+pub struct X86_64;
+
+registers!(X86_64, {
+    RAX = (0, "rax"),
+    RDX = (1, "rdx"),
+    RCX = (2, "rcx"),
+    RBX = (3, "rbx"),
+    RSI = (4, "rsi"),
+    RDI = (5, "rdi"),
+    RBP = (6, "rbp"),
+    RSP = (7, "rsp"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+    RA = (16, "RA"),
+    XMM0 = (17, "xmm0"),
+    XMM1 = (18, "xmm1"),
+    XMM2 = (19, "xmm2"),
+    XMM3 = (20, "xmm3"),
+    XMM4 = (21, "xmm4"),
+    XMM5 = (22, "xmm5"),
+    XMM6 = (23, "xmm6"),
+    XMM7 = (24, "xmm7"),
+    XMM8 = (25, "xmm8"),
+    XMM9 = (26, "xmm9"),
+    XMM10 = (27, "xmm10"),
+    XMM11 = (28, "xmm11"),
+    XMM12 = (29, "xmm12"),
+    XMM13 = (30, "xmm13"),
+    XMM14 = (31, "xmm14"),
+    XMM15 = (32, "xmm15"),
+    ST0 = (33, "st0"),
+    ST1 = (34, "st1"),
+    ST2 = (35, "st2"),
+    ST3 = (36, "st3"),
+    ST4 = (37, "st4"),
+    ST5 = (38, "st5"),
+    ST6 = (39, "st6"),
+    ST7 = (40, "st7"),
+    MM0 = (41, "mm0"),
+    MM1 = (42, "mm1"),
+    MM2 = (43, "mm2"),
+    MM3 = (44, "mm3"),
+    MM4 = (45, "mm4"),
+    MM5 = (46, "mm5"),
+    MM6 = (47, "mm6"),
+    MM7 = (48, "mm7"),
+    RFLAGS = (49, "rFLAGS"),
+    ES = (50, "es"),
+    CS = (51, "cs"),
+    SS = (52, "ss"),
+    DS = (53, "ds"),
+    FS = (54, "fs"),
+    GS = (55, "gs"),
+    FS_BASE = (58, "fs.base"),
+    GS_BASE = (59, "gs.base"),
+    TR = (62, "tr"),
+    LDTR = (63, "ldtr"),
+    MXCSR = (64, "mxcsr"),
+    FCW = (65, "fcw"),
+    FSW = (66, "fsw"),
+    XMM16 = (67, "xmm16"),
+    XMM17 = (68, "xmm17"),
+    XMM18 = (69, "xmm18"),
+    XMM19 = (70, "xmm19"),
+    XMM20 = (71, "xmm20"),
+    XMM21 = (72, "xmm21"),
+    XMM22 = (73, "xmm22"),
+    XMM23 = (74, "xmm23"),
+    XMM24 = (75, "xmm24"),
+    XMM25 = (76, "xmm25"),
+    XMM26 = (77, "xmm26"),
+    XMM27 = (78, "xmm27"),
+    XMM28 = (79, "xmm28"),
+    XMM29 = (80, "xmm29"),
+    XMM30 = (81, "xmm30"),
+    XMM31 = (82, "xmm31"),
+    K0 = (118, "k0"),
+    K1 = (119, "k1"),
+    K2 = (120, "k2"),
+    K3 = (121, "k3"),
+    K4 = (122, "k4"),
+    K5 = (123, "k5"),
+    K6 = (124, "k6"),
+    K7 = (125, "k7"),
+});
+
+/// The 32-bit ARM architecture, as described by the DWARF for the ARM Architecture ABI.
+#[derive(Debug, Clone, Copy)]
+pub struct Arm;
+
+registers!(Arm, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    SP = (13, "sp"),
+    LR = (14, "lr"),
+    PC = (15, "pc"),
+    S0 = (64, "s0"),
+    S1 = (65, "s1"),
+    S2 = (66, "s2"),
+    S3 = (67, "s3"),
+    S4 = (68, "s4"),
+    S5 = (69, "s5"),
+    S6 = (70, "s6"),
+    S7 = (71, "s7"),
+    S8 = (72, "s8"),
+    S9 = (73, "s9"),
+    S10 = (74, "s10"),
+    S11 = (75, "s11"),
+    S12 = (76, "s12"),
+    S13 = (77, "s13"),
+    S14 = (78, "s14"),
+    S15 = (79, "s15"),
+    S16 = (80, "s16"),
+    S17 = (81, "s17"),
+    S18 = (82, "s18"),
+    S19 = (83, "s19"),
+    S20 = (84, "s20"),
+    S21 = (85, "s21"),
+    S22 = (86, "s22"),
+    S23 = (87, "s23"),
+    S24 = (88, "s24"),
+    S25 = (89, "s25"),
+    S26 = (90, "s26"),
+    S27 = (91, "s27"),
+    S28 = (92, "s28"),
+    S29 = (93, "s29"),
+    S30 = (94, "s30"),
+    S31 = (95, "s31"),
+    WCGR0 = (104, "wcgr0"),
+    WCGR1 = (105, "wcgr1"),
+    WCGR2 = (106, "wcgr2"),
+    WCGR3 = (107, "wcgr3"),
+    WCGR4 = (108, "wcgr4"),
+    WCGR5 = (109, "wcgr5"),
+    WCGR6 = (110, "wcgr6"),
+    WCGR7 = (111, "wcgr7"),
+    WR0 = (112, "wr0"),
+    WR1 = (113, "wr1"),
+    WR2 = (114, "wr2"),
+    WR3 = (115, "wr3"),
+    WR4 = (116, "wr4"),
+    WR5 = (117, "wr5"),
+    WR6 = (118, "wr6"),
+    WR7 = (119, "wr7"),
+    WR8 = (120, "wr8"),
+    WR9 = (121, "wr9"),
+    WR10 = (122, "wr10"),
+    WR11 = (123, "wr11"),
+    WR12 = (124, "wr12"),
+    WR13 = (125, "wr13"),
+    WR14 = (126, "wr14"),
+    WR15 = (127, "wr15"),
+    SPSR = (128, "spsr"),
+    SPSR_FIQ = (129, "spsr_fiq"),
+    SPSR_IRQ = (130, "spsr_irq"),
+    SPSR_ABT = (131, "spsr_abt"),
+    SPSR_UND = (132, "spsr_und"),
+    SPSR_SVC = (133, "spsr_svc"),
+    D0 = (256, "d0"),
+    D1 = (257, "d1"),
+    D2 = (258, "d2"),
+    D3 = (259, "d3"),
+    D4 = (260, "d4"),
+    D5 = (261, "d5"),
+    D6 = (262, "d6"),
+    D7 = (263, "d7"),
+    D8 = (264, "d8"),
+    D9 = (265, "d9"),
+    D10 = (266, "d10"),
+    D11 = (267, "d11"),
+    D12 = (268, "d12"),
+    D13 = (269, "d13"),
+    D14 = (270, "d14"),
+    D15 = (271, "d15"),
+    D16 = (272, "d16"),
+    D17 = (273, "d17"),
+    D18 = (274, "d18"),
+    D19 = (275, "d19"),
+    D20 = (276, "d20"),
+    D21 = (277, "d21"),
+    D22 = (278, "d22"),
+    D23 = (279, "d23"),
+    D24 = (280, "d24"),
+    D25 = (281, "d25"),
+    D26 = (282, "d26"),
+    D27 = (283, "d27"),
+    D28 = (284, "d28"),
+    D29 = (285, "d29"),
+    D30 = (286, "d30"),
+    D31 = (287, "d31"),
+});
+
+/// The 64-bit ARM architecture, as described by the DWARF for the ARM 64-bit Architecture ABI.
+#[derive(Debug, Clone, Copy)]
+pub struct AArch64;
+
+registers!(AArch64, {
+    X0 = (0, "x0"),
+    X1 = (1, "x1"),
+    X2 = (2, "x2"),
+    X3 = (3, "x3"),
+    X4 = (4, "x4"),
+    X5 = (5, "x5"),
+    X6 = (6, "x6"),
+    X7 = (7, "x7"),
+    X8 = (8, "x8"),
+    X9 = (9, "x9"),
+    X10 = (10, "x10"),
+    X11 = (11, "x11"),
+    X12 = (12, "x12"),
+    X13 = (13, "x13"),
+    X14 = (14, "x14"),
+    X15 = (15, "x15"),
+    X16 = (16, "x16"),
+    X17 = (17, "x17"),
+    X18 = (18, "x18"),
+    X19 = (19, "x19"),
+    X20 = (20, "x20"),
+    X21 = (21, "x21"),
+    X22 = (22, "x22"),
+    X23 = (23, "x23"),
+    X24 = (24, "x24"),
+    X25 = (25, "x25"),
+    X26 = (26, "x26"),
+    X27 = (27, "x27"),
+    X28 = (28, "x28"),
+    FP = (29, "fp"),
+    LR = (30, "lr"),
+    SP = (31, "sp"),
+    PC = (32, "pc"),
+    ELR_MODE = (33, "elr_mode"),
+    RA_SIGN_STATE = (34, "ra_sign_state"),
+    TPIDRRO_EL0 = (35, "tpidrro_el0"),
+    TPIDR_EL0 = (36, "tpidr_el0"),
+    TPIDR_EL1 = (37, "tpidr_el1"),
+    TPIDR_EL2 = (38, "tpidr_el2"),
+    TPIDR_EL3 = (39, "tpidr_el3"),
+    VG = (46, "vg"),
+    FFR = (47, "ffr"),
+    P0 = (48, "p0"),
+    P1 = (49, "p1"),
+    P2 = (50, "p2"),
+    P3 = (51, "p3"),
+    P4 = (52, "p4"),
+    P5 = (53, "p5"),
+    P6 = (54, "p6"),
+    P7 = (55, "p7"),
+    P8 = (56, "p8"),
+    P9 = (57, "p9"),
+    P10 = (58, "p10"),
+    P11 = (59, "p11"),
+    P12 = (60, "p12"),
+    P13 = (61, "p13"),
+    P14 = (62, "p14"),
+    P15 = (63, "p15"),
+    V0 = (64, "v0"),
+    V1 = (65, "v1"),
+    V2 = (66, "v2"),
+    V3 = (67, "v3"),
+    V4 = (68, "v4"),
+    V5 = (69, "v5"),
+    V6 = (70, "v6"),
+    V7 = (71, "v7"),
+    V8 = (72, "v8"),
+    V9 = (73, "v9"),
+    V10 = (74, "v10"),
+    V11 = (75, "v11"),
+    V12 = (76, "v12"),
+    V13 = (77, "v13"),
+    V14 = (78, "v14"),
+    V15 = (79, "v15"),
+    V16 = (80, "v16"),
+    V17 = (81, "v17"),
+    V18 = (82, "v18"),
+    V19 = (83, "v19"),
+    V20 = (84, "v20"),
+    V21 = (85, "v21"),
+    V22 = (86, "v22"),
+    V23 = (87, "v23"),
+    V24 = (88, "v24"),
+    V25 = (89, "v25"),
+    V26 = (90, "v26"),
+    V27 = (91, "v27"),
+    V28 = (92, "v28"),
+    V29 = (93, "v29"),
+    V30 = (94, "v30"),
+    V31 = (95, "v31"),
+    Z0 = (96, "z0"),
+    Z1 = (97, "z1"),
+    Z2 = (98, "z2"),
+    Z3 = (99, "z3"),
+    Z4 = (100, "z4"),
+    Z5 = (101, "z5"),
+    Z6 = (102, "z6"),
+    Z7 = (103, "z7"),
+    Z8 = (104, "z8"),
+    Z9 = (105, "z9"),
+    Z10 = (106, "z10"),
+    Z11 = (107, "z11"),
+    Z12 = (108, "z12"),
+    Z13 = (109, "z13"),
+    Z14 = (110, "z14"),
+    Z15 = (111, "z15"),
+    Z16 = (112, "z16"),
+    Z17 = (113, "z17"),
+    Z18 = (114, "z18"),
+    Z19 = (115, "z19"),
+    Z20 = (116, "z20"),
+    Z21 = (117, "z21"),
+    Z22 = (118, "z22"),
+    Z23 = (119, "z23"),
+    Z24 = (120, "z24"),
+    Z25 = (121, "z25"),
+    Z26 = (122, "z26"),
+    Z27 = (123, "z27"),
+    Z28 = (124, "z28"),
+    Z29 = (125, "z29"),
+    Z30 = (126, "z30"),
+    Z31 = (127, "z31"),
+});
+
+/// The RISC-V architecture, using the register names from the RISC-V ELF psABI.
+#[derive(Debug, Clone, Copy)]
+pub struct RiscV;
+
+registers!(RiscV, {
+    ZERO = (0, "zero"),
+    RA = (1, "ra"),
+    SP = (2, "sp"),
+    GP = (3, "gp"),
+    TP = (4, "tp"),
+    T0 = (5, "t0"),
+    T1 = (6, "t1"),
+    T2 = (7, "t2"),
+    S0 = (8, "s0"),
+    S1 = (9, "s1"),
+    A0 = (10, "a0"),
+    A1 = (11, "a1"),
+    A2 = (12, "a2"),
+    A3 = (13, "a3"),
+    A4 = (14, "a4"),
+    A5 = (15, "a5"),
+    A6 = (16, "a6"),
+    A7 = (17, "a7"),
+    S2 = (18, "s2"),
+    S3 = (19, "s3"),
+    S4 = (20, "s4"),
+    S5 = (21, "s5"),
+    S6 = (22, "s6"),
+    S7 = (23, "s7"),
+    S8 = (24, "s8"),
+    S9 = (25, "s9"),
+    S10 = (26, "s10"),
+    S11 = (27, "s11"),
+    T3 = (28, "t3"),
+    T4 = (29, "t4"),
+    T5 = (30, "t5"),
+    T6 = (31, "t6"),
+    FT0 = (32, "ft0"),
+    FT1 = (33, "ft1"),
+    FT2 = (34, "ft2"),
+    FT3 = (35, "ft3"),
+    FT4 = (36, "ft4"),
+    FT5 = (37, "ft5"),
+    FT6 = (38, "ft6"),
+    FT7 = (39, "ft7"),
+    FS0 = (40, "fs0"),
+    FS1 = (41, "fs1"),
+    FA0 = (42, "fa0"),
+    FA1 = (43, "fa1"),
+    FA2 = (44, "fa2"),
+    FA3 = (45, "fa3"),
+    FA4 = (46, "fa4"),
+    FA5 = (47, "fa5"),
+    FA6 = (48, "fa6"),
+    FA7 = (49, "fa7"),
+    FS2 = (50, "fs2"),
+    FS3 = (51, "fs3"),
+    FS4 = (52, "fs4"),
+    FS5 = (53, "fs5"),
+    FS6 = (54, "fs6"),
+    FS7 = (55, "fs7"),
+    FS8 = (56, "fs8"),
+    FS9 = (57, "fs9"),
+    FS10 = (58, "fs10"),
+    FS11 = (59, "fs11"),
+    FT8 = (60, "ft8"),
+    FT9 = (61, "ft9"),
+    FT10 = (62, "ft10"),
+    FT11 = (63, "ft11"),
+});
+
+/// The 64-bit PowerPC architecture, as described by the 64-bit ELF ABI.
+#[derive(Debug, Clone, Copy)]
+pub struct PowerPc64;
+
+registers!(PowerPc64, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+    R16 = (16, "r16"),
+    R17 = (17, "r17"),
+    R18 = (18, "r18"),
+    R19 = (19, "r19"),
+    R20 = (20, "r20"),
+    R21 = (21, "r21"),
+    R22 = (22, "r22"),
+    R23 = (23, "r23"),
+    R24 = (24, "r24"),
+    R25 = (25, "r25"),
+    R26 = (26, "r26"),
+    R27 = (27, "r27"),
+    R28 = (28, "r28"),
+    R29 = (29, "r29"),
+    R30 = (30, "r30"),
+    R31 = (31, "r31"),
+    F0 = (32, "f0"),
+    F1 = (33, "f1"),
+    F2 = (34, "f2"),
+    F3 = (35, "f3"),
+    F4 = (36, "f4"),
+    F5 = (37, "f5"),
+    F6 = (38, "f6"),
+    F7 = (39, "f7"),
+    F8 = (40, "f8"),
+    F9 = (41, "f9"),
+    F10 = (42, "f10"),
+    F11 = (43, "f11"),
+    F12 = (44, "f12"),
+    F13 = (45, "f13"),
+    F14 = (46, "f14"),
+    F15 = (47, "f15"),
+    F16 = (48, "f16"),
+    F17 = (49, "f17"),
+    F18 = (50, "f18"),
+    F19 = (51, "f19"),
+    F20 = (52, "f20"),
+    F21 = (53, "f21"),
+    F22 = (54, "f22"),
+    F23 = (55, "f23"),
+    F24 = (56, "f24"),
+    F25 = (57, "f25"),
+    F26 = (58, "f26"),
+    F27 = (59, "f27"),
+    F28 = (60, "f28"),
+    F29 = (61, "f29"),
+    F30 = (62, "f30"),
+    F31 = (63, "f31"),
+    LR = (65, "lr"),
+    CTR = (66, "ctr"),
+    CR0 = (68, "cr0"),
+    CR1 = (69, "cr1"),
+    CR2 = (70, "cr2"),
+    CR3 = (71, "cr3"),
+    CR4 = (72, "cr4"),
+    CR5 = (73, "cr5"),
+    CR6 = (74, "cr6"),
+    CR7 = (75, "cr7"),
+    XER = (76, "xer"),
+    VR0 = (77, "vr0"),
+    VR1 = (78, "vr1"),
+    VR2 = (79, "vr2"),
+    VR3 = (80, "vr3"),
+    VR4 = (81, "vr4"),
+    VR5 = (82, "vr5"),
+    VR6 = (83, "vr6"),
+    VR7 = (84, "vr7"),
+    VR8 = (85, "vr8"),
+    VR9 = (86, "vr9"),
+    VR10 = (87, "vr10"),
+    VR11 = (88, "vr11"),
+    VR12 = (89, "vr12"),
+    VR13 = (90, "vr13"),
+    VR14 = (91, "vr14"),
+    VR15 = (92, "vr15"),
+    VR16 = (93, "vr16"),
+    VR17 = (94, "vr17"),
+    VR18 = (95, "vr18"),
+    VR19 = (96, "vr19"),
+    VR20 = (97, "vr20"),
+    VR21 = (98, "vr21"),
+    VR22 = (99, "vr22"),
+    VR23 = (100, "vr23"),
+    VR24 = (101, "vr24"),
+    VR25 = (102, "vr25"),
+    VR26 = (103, "vr26"),
+    VR27 = (104, "vr27"),
+    VR28 = (105, "vr28"),
+    VR29 = (106, "vr29"),
+    VR30 = (107, "vr30"),
+    VR31 = (108, "vr31"),
+    VRSAVE = (109, "vrsave"),
+    VSCR = (110, "vscr"),
+});
+
+/// The MIPS architecture, using the register names from the o32 and n64 ABIs.
+#[derive(Debug, Clone, Copy)]
+pub struct Mips;
+
+registers!(Mips, {
+    ZERO = (0, "zero"),
+    AT = (1, "at"),
+    V0 = (2, "v0"),
+    V1 = (3, "v1"),
+    A0 = (4, "a0"),
+    A1 = (5, "a1"),
+    A2 = (6, "a2"),
+    A3 = (7, "a3"),
+    T0 = (8, "t0"),
+    T1 = (9, "t1"),
+    T2 = (10, "t2"),
+    T3 = (11, "t3"),
+    T4 = (12, "t4"),
+    T5 = (13, "t5"),
+    T6 = (14, "t6"),
+    T7 = (15, "t7"),
+    S0 = (16, "s0"),
+    S1 = (17, "s1"),
+    S2 = (18, "s2"),
+    S3 = (19, "s3"),
+    S4 = (20, "s4"),
+    S5 = (21, "s5"),
+    S6 = (22, "s6"),
+    S7 = (23, "s7"),
+    T8 = (24, "t8"),
+    T9 = (25, "t9"),
+    K0 = (26, "k0"),
+    K1 = (27, "k1"),
+    GP = (28, "gp"),
+    SP = (29, "sp"),
+    FP = (30, "fp"),
+    RA = (31, "ra"),
+    F0 = (32, "f0"),
+    F1 = (33, "f1"),
+    F2 = (34, "f2"),
+    F3 = (35, "f3"),
+    F4 = (36, "f4"),
+    F5 = (37, "f5"),
+    F6 = (38, "f6"),
+    F7 = (39, "f7"),
+    F8 = (40, "f8"),
+    F9 = (41, "f9"),
+    F10 = (42, "f10"),
+    F11 = (43, "f11"),
+    F12 = (44, "f12"),
+    F13 = (45, "f13"),
+    F14 = (46, "f14"),
+    F15 = (47, "f15"),
+    F16 = (48, "f16"),
+    F17 = (49, "f17"),
+    F18 = (50, "f18"),
+    F19 = (51, "f19"),
+    F20 = (52, "f20"),
+    F21 = (53, "f21"),
+    F22 = (54, "f22"),
+    F23 = (55, "f23"),
+    F24 = (56, "f24"),
+    F25 = (57, "f25"),
+    F26 = (58, "f26"),
+    F27 = (59, "f27"),
+    F28 = (60, "f28"),
+    F29 = (61, "f29"),
+    F30 = (62, "f30"),
+    F31 = (63, "f31"),
+    HI = (64, "hi"),
+    LO = (65, "lo"),
+});
+
+/// The IBM z/Architecture, as described by the s390x ELF ABI.
+#[derive(Debug, Clone, Copy)]
+pub struct S390x;
+
+registers!(S390x, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+    F0 = (16, "f0"),
+    F2 = (17, "f2"),
+    F4 = (18, "f4"),
+    F6 = (19, "f6"),
+    F1 = (20, "f1"),
+    F3 = (21, "f3"),
+    F5 = (22, "f5"),
+    F7 = (23, "f7"),
+    F8 = (24, "f8"),
+    F10 = (25, "f10"),
+    F12 = (26, "f12"),
+    F14 = (27, "f14"),
+    F9 = (28, "f9"),
+    F11 = (29, "f11"),
+    F13 = (30, "f13"),
+    F15 = (31, "f15"),
+    CR0 = (32, "cr0"),
+    CR1 = (33, "cr1"),
+    CR2 = (34, "cr2"),
+    CR3 = (35, "cr3"),
+    CR4 = (36, "cr4"),
+    CR5 = (37, "cr5"),
+    CR6 = (38, "cr6"),
+    CR7 = (39, "cr7"),
+    CR8 = (40, "cr8"),
+    CR9 = (41, "cr9"),
+    CR10 = (42, "cr10"),
+    CR11 = (43, "cr11"),
+    CR12 = (44, "cr12"),
+    CR13 = (45, "cr13"),
+    CR14 = (46, "cr14"),
+    CR15 = (47, "cr15"),
+    A0 = (48, "a0"),
+    A1 = (49, "a1"),
+    A2 = (50, "a2"),
+    A3 = (51, "a3"),
+    A4 = (52, "a4"),
+    A5 = (53, "a5"),
+    A6 = (54, "a6"),
+    A7 = (55, "a7"),
+    A8 = (56, "a8"),
+    A9 = (57, "a9"),
+    A10 = (58, "a10"),
+    A11 = (59, "a11"),
+    A12 = (60, "a12"),
+    A13 = (61, "a13"),
+    A14 = (62, "a14"),
+    A15 = (63, "a15"),
+    PSWM = (64, "pswm"),
+    PSWA = (65, "pswa"),
+    V16 = (68, "v16"),
+    V18 = (69, "v18"),
+    V20 = (70, "v20"),
+    V22 = (71, "v22"),
+    V17 = (72, "v17"),
+    V19 = (73, "v19"),
+    V21 = (74, "v21"),
+    V23 = (75, "v23"),
+    V24 = (76, "v24"),
+    V26 = (77, "v26"),
+    V28 = (78, "v28"),
+    V30 = (79, "v30"),
+    V25 = (80, "v25"),
+    V27 = (81, "v27"),
+    V29 = (82, "v29"),
+    V31 = (83, "v31"),
+});
+
+/// The AVR architecture, using the register numbers emitted by GCC and LLVM.
+///
+/// AVR has separate address spaces for program memory and data memory, so
+/// pointer types may have a `DW_AT_address_class` attribute.
+#[derive(Debug, Clone, Copy)]
+pub struct Avr;
+
+registers!(Avr, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+    R16 = (16, "r16"),
+    R17 = (17, "r17"),
+    R18 = (18, "r18"),
+    R19 = (19, "r19"),
+    R20 = (20, "r20"),
+    R21 = (21, "r21"),
+    R22 = (22, "r22"),
+    R23 = (23, "r23"),
+    R24 = (24, "r24"),
+    R25 = (25, "r25"),
+    R26 = (26, "r26"),
+    R27 = (27, "r27"),
+    R28 = (28, "r28"),
+    R29 = (29, "r29"),
+    R30 = (30, "r30"),
+    R31 = (31, "r31"),
+    SP = (32, "sp"),
+});
+
+address_classes!(Avr, {
+    DW_ADDR_none,
+    DW_ADDR_AVR_flash,
+});
+
+/// The LoongArch architecture, using the register names from the LoongArch ELF psABI.
+#[derive(Debug, Clone, Copy)]
+pub struct LoongArch;
+
+registers!(LoongArch, {
+    ZERO = (0, "zero"),
+    RA = (1, "ra"),
+    TP = (2, "tp"),
+    SP = (3, "sp"),
+    A0 = (4, "a0"),
+    A1 = (5, "a1"),
+    A2 = (6, "a2"),
+    A3 = (7, "a3"),
+    A4 = (8, "a4"),
+    A5 = (9, "a5"),
+    A6 = (10, "a6"),
+    A7 = (11, "a7"),
+    T0 = (12, "t0"),
+    T1 = (13, "t1"),
+    T2 = (14, "t2"),
+    T3 = (15, "t3"),
+    T4 = (16, "t4"),
+    T5 = (17, "t5"),
+    T6 = (18, "t6"),
+    T7 = (19, "t7"),
+    T8 = (20, "t8"),
+    R21 = (21, "r21"),
+    FP = (22, "fp"),
+    S0 = (23, "s0"),
+    S1 = (24, "s1"),
+    S2 = (25, "s2"),
+    S3 = (26, "s3"),
+    S4 = (27, "s4"),
+    S5 = (28, "s5"),
+    S6 = (29, "s6"),
+    S7 = (30, "s7"),
+    S8 = (31, "s8"),
+    FA0 = (32, "fa0"),
+    FA1 = (33, "fa1"),
+    FA2 = (34, "fa2"),
+    FA3 = (35, "fa3"),
+    FA4 = (36, "fa4"),
+    FA5 = (37, "fa5"),
+    FA6 = (38, "fa6"),
+    FA7 = (39, "fa7"),
+    FT0 = (40, "ft0"),
+    FT1 = (41, "ft1"),
+    FT2 = (42, "ft2"),
+    FT3 = (43, "ft3"),
+    FT4 = (44, "ft4"),
+    FT5 = (45, "ft5"),
+    FT6 = (46, "ft6"),
+    FT7 = (47, "ft7"),
+    FT8 = (48, "ft8"),
+    FT9 = (49, "ft9"),
+    FT10 = (50, "ft10"),
+    FT11 = (51, "ft11"),
+    FT12 = (52, "ft12"),
+    FT13 = (53, "ft13"),
+    FT14 = (54, "ft14"),
+    FT15 = (55, "ft15"),
+    FS0 = (56, "fs0"),
+    FS1 = (57, "fs1"),
+    FS2 = (58, "fs2"),
+    FS3 = (59, "fs3"),
+    FS4 = (60, "fs4"),
+    FS5 = (61, "fs5"),
+    FS6 = (62, "fs6"),
+    FS7 = (63, "fs7"),
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_names() {
+        assert_eq!(X86::name(X86::ESP), Some("esp"));
+        assert_eq!(X86_64::name(Register(16)), Some("RA"));
+        assert_eq!(X86_64::name(Register(200)), None);
+        assert_eq!(Arm::name(Register(270)), Some("d14"));
+        assert_eq!(AArch64::name(AArch64::SP), Some("sp"));
+        assert_eq!(RiscV::name(Register(8)), Some("s0"));
+        assert_eq!(PowerPc64::name(Register(65)), Some("lr"));
+        assert_eq!(Mips::name(Register(29)), Some("sp"));
+        assert_eq!(S390x::name(Register(17)), Some("f2"));
+        assert_eq!(LoongArch::name(Register(3)), Some("sp"));
+    }
+
+    #[test]
+    fn test_register_from_name() {
+        assert_eq!(X86_64::from_name("rbp"), Some(Register(6)));
+        assert_eq!(X86_64::from_name("xmm31"), Some(Register(82)));
+        assert_eq!(X86_64::from_name("eax"), None);
+        assert_eq!(Arm::from_name("pc"), Some(Arm::PC));
+        assert_eq!(RiscV::from_name("fa0"), Some(Register(42)));
+        assert_eq!(S390x::from_name("f1"), Some(Register(20)));
+        assert_eq!(LoongArch::from_name("fs7"), Some(Register(63)));
+    }
+
+    #[test]
+    fn test_address_class_name() {
+        assert_eq!(X86::address_class_name(DwAddr(0)), Some("DW_ADDR_none"));
+        assert_eq!(X86::address_class_name(DwAddr(2)), Some("DW_ADDR_x86_far16"));
+        assert_eq!(X86::address_class_name(DwAddr(6)), None);
+        assert_eq!(Avr::address_class_name(DwAddr(1)), Some("DW_ADDR_AVR_flash"));
+        assert_eq!(Avr::address_class_name(DwAddr(2)), None);
+        assert_eq!(Avr::name(Avr::SP), Some("sp"));
+    }
+}
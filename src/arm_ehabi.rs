@@ -0,0 +1,1034 @@
+//! Parsing of the ARM exception handling ABI (EHABI) unwind tables, found in
+//! the `.ARM.exidx` and `.ARM.extab` sections.
+//!
+//! 32-bit ARM binaries typically use these sections instead of `.eh_frame`.
+//! The unwind instructions can be converted to an `UnwindTableRow`, so that
+//! the same unwinder can be used for both formats.
+
+use arch::{Arm, Register};
+use cfi::{CfaRule, RegisterRule, UnwindTableRow};
+use endianity::{EndianBuf, Endianity};
+use parser::{Error, Result};
+use reader::{Reader, ReaderOffset};
+
+/// The number of DWARF register numbers that unwind instructions can save.
+const SAVED_REGISTERS: usize = 288;
+
+/// The value of the second word of an index table entry for a function that
+/// cannot be unwound.
+const EXIDX_CANTUNWIND: u32 = 1;
+
+/// Decode a place-relative 31-bit signed offset.
+fn prel31(word: u32, place: u64) -> u64 {
+    let offset = ((word << 1) as i32) >> 1;
+    place.wrapping_add(offset as i64 as u64)
+}
+
+/// The `.ARM.exidx` section, which maps function addresses to their unwind
+/// instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmExidx<R: Reader>(R);
+
+impl<'input, Endian> ArmExidx<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `ArmExidx` instance from the data in the `.ARM.exidx`
+    /// section.
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(section, endian))
+    }
+}
+
+impl<R: Reader> From<R> for ArmExidx<R> {
+    fn from(section: R) -> Self {
+        ArmExidx(section)
+    }
+}
+
+impl<R: Reader> ArmExidx<R> {
+    /// Iterate over the entries in this section.
+    ///
+    /// `address` is the address of the `.ARM.exidx` section, which is needed
+    /// to resolve the place-relative offsets in the entries.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn entries(&self, address: u64) -> ArmExidxIter<R> {
+        ArmExidxIter {
+            section: self.0.clone(),
+            input: self.0.clone(),
+            address,
+        }
+    }
+
+    /// Find the entry for the function containing the given address.
+    ///
+    /// The entries are sorted by function address, so this performs a binary
+    /// search. `section_address` is the address of the `.ARM.exidx` section.
+    ///
+    /// ```
+    /// use gimli::{ArmExidx, ArmExtab, LittleEndian};
+    ///
+    /// # fn foo() -> gimli::Result<()> {
+    /// # let read_section = |_| unimplemented!();
+    /// # let (exidx_address, extab_address, pc) = (0, 0, 0);
+    /// let exidx = ArmExidx::new(read_section(".ARM.exidx"), LittleEndian);
+    /// let extab = ArmExtab::new(read_section(".ARM.extab"), LittleEndian);
+    /// if let Some(entry) = exidx.entry_for_address(exidx_address, pc)? {
+    ///     let row = entry.unwind_row(&extab, extab_address)?;
+    ///     println!("CFA rule: {:?}", row.cfa());
+    /// }
+    /// # unreachable!()
+    /// # }
+    /// ```
+    pub fn entry_for_address(
+        &self,
+        section_address: u64,
+        address: u64,
+    ) -> Result<Option<ArmExidxEntry>> {
+        let entry_size = R::Offset::from_u8(8);
+        let function_address = |index: u64| -> Result<u64> {
+            let offset = R::Offset::from_u64(index * 8)?;
+            let mut input = self.0.clone();
+            input.skip(offset)?;
+            let place = section_address.wrapping_add(offset.into_u64());
+            Ok(prel31(input.read_u32()?, place))
+        };
+
+        // Find the last entry with a function address that is not greater
+        // than `address`.
+        let mut low = 0;
+        let mut high = self.0.len().into_u64() / entry_size.into_u64();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if function_address(mid)? <= address {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            return Ok(None);
+        }
+
+        let mut iter = self.entries(section_address);
+        iter.input.skip(R::Offset::from_u64((low - 1) * 8)?)?;
+        iter.next()
+    }
+}
+
+/// An iterator over the entries in an `.ARM.exidx` section.
+#[derive(Debug, Clone)]
+pub struct ArmExidxIter<R: Reader> {
+    section: R,
+    input: R,
+    address: u64,
+}
+
+impl<R: Reader> ArmExidxIter<R> {
+    /// Advance the iterator to the next entry.
+    pub fn next(&mut self) -> Result<Option<ArmExidxEntry>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse() {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<ArmExidxEntry> {
+        let place = self.address
+            .wrapping_add(self.input.offset_from(&self.section)?.into_u64());
+        let function_address = prel31(self.input.read_u32()?, place);
+        let word = self.input.read_u32()?;
+        let data = if word == EXIDX_CANTUNWIND {
+            ArmExidxData::CantUnwind
+        } else if word & 0x8000_0000 != 0 {
+            ArmExidxData::Inline(word)
+        } else {
+            ArmExidxData::Extab(prel31(word, place.wrapping_add(4)))
+        };
+
+        // Each function extends until the start of the next entry's function.
+        let end_address = if self.input.is_empty() {
+            None
+        } else {
+            let mut next = self.input.clone();
+            Some(prel31(next.read_u32()?, place.wrapping_add(8)))
+        };
+
+        Ok(ArmExidxEntry {
+            function_address,
+            end_address,
+            data,
+        })
+    }
+}
+
+impl<R: Reader> ::fallible_iterator::FallibleIterator for ArmExidxIter<R> {
+    type Item = ArmExidxEntry;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        ArmExidxIter::next(self)
+    }
+}
+
+/// An entry in the `.ARM.exidx` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmExidxEntry {
+    function_address: u64,
+    end_address: Option<u64>,
+    data: ArmExidxData,
+}
+
+/// The unwind data of an entry in the `.ARM.exidx` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmExidxData {
+    /// The function cannot be unwound.
+    CantUnwind,
+
+    /// The unwind instructions are stored inline, in this word.
+    Inline(u32),
+
+    /// The unwind instructions are stored at this address in the
+    /// `.ARM.extab` section.
+    Extab(u64),
+}
+
+impl ArmExidxEntry {
+    /// The start address of the function that this entry applies to.
+    pub fn function_address(&self) -> u64 {
+        self.function_address
+    }
+
+    /// The start address of the next entry's function, or `None` if this is
+    /// the last entry.
+    pub fn end_address(&self) -> Option<u64> {
+        self.end_address
+    }
+
+    /// The unwind data for this entry.
+    pub fn data(&self) -> ArmExidxData {
+        self.data
+    }
+
+    /// Return the unwind instructions for this entry.
+    ///
+    /// `extab_address` is the address of the `.ARM.extab` section, which is
+    /// only used if the instructions are not stored inline.
+    pub fn instructions<R: Reader>(
+        &self,
+        extab: &ArmExtab<R>,
+        extab_address: u64,
+    ) -> Result<ArmUnwindInstructions<R>> {
+        match self.data {
+            ArmExidxData::CantUnwind => Err(Error::ArmCannotUnwind),
+            ArmExidxData::Inline(word) => {
+                let mut input = extab.0.clone();
+                input.empty();
+                ArmUnwindInstructions::compact(word, &mut input)
+            }
+            ArmExidxData::Extab(address) => {
+                Ok(extab.entry(extab_address, address)?.instructions)
+            }
+        }
+    }
+
+    /// Evaluate the unwind instructions for this entry, and return the
+    /// equivalent row of a DWARF unwind table.
+    ///
+    /// The row applies from the start of this entry's function until the
+    /// start of the next entry's function.
+    pub fn unwind_row<R: Reader>(
+        &self,
+        extab: &ArmExtab<R>,
+        extab_address: u64,
+    ) -> Result<UnwindTableRow<R>> {
+        let end_address = self.end_address.unwrap_or(!0);
+        self.instructions(extab, extab_address)?
+            .unwind_row(self.function_address, end_address)
+    }
+}
+
+/// The `.ARM.extab` section, which contains the unwind instructions and
+/// language-specific data that do not fit in an `.ARM.exidx` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmExtab<R: Reader>(R);
+
+impl<'input, Endian> ArmExtab<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `ArmExtab` instance from the data in the `.ARM.extab`
+    /// section.
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(section, endian))
+    }
+}
+
+impl<R: Reader> From<R> for ArmExtab<R> {
+    fn from(section: R) -> Self {
+        ArmExtab(section)
+    }
+}
+
+impl<R: Reader> ArmExtab<R> {
+    /// Parse the entry at the given address.
+    ///
+    /// `section_address` is the address of the `.ARM.extab` section.
+    pub fn entry(&self, section_address: u64, address: u64) -> Result<ArmExtabEntry<R>> {
+        let offset = address
+            .checked_sub(section_address)
+            .ok_or(Error::OffsetOutOfBounds)?;
+        let mut input = self.0.clone();
+        input.skip(R::Offset::from_u64(offset)?)?;
+
+        let word = input.read_u32()?;
+        if word & 0x8000_0000 != 0 {
+            let personality = match (word >> 24) & 0xf {
+                0 => ArmPersonality::Su16,
+                1 => ArmPersonality::Lu16,
+                _ => ArmPersonality::Lu32,
+            };
+            let instructions = ArmUnwindInstructions::compact(word, &mut input)?;
+            Ok(ArmExtabEntry {
+                personality,
+                instructions,
+                data: input,
+            })
+        } else {
+            // The generic model does not specify the format of the data, but
+            // this is the format used by the GCC personality routines.
+            let personality = ArmPersonality::Generic(prel31(word, address));
+            let word = input.read_u32()?;
+            let len = R::Offset::from_u32((word >> 24) * 4);
+            Ok(ArmExtabEntry {
+                personality,
+                instructions: ArmUnwindInstructions {
+                    word,
+                    bytes: 3,
+                    words: input.split(len)?,
+                },
+                data: input,
+            })
+        }
+    }
+}
+
+/// The personality routine of an `.ARM.extab` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmPersonality {
+    /// `__aeabi_unwind_cpp_pr0`, which has up to 3 unwind instructions.
+    Su16,
+
+    /// `__aeabi_unwind_cpp_pr1`, which has a variable number of unwind
+    /// instructions and 16-bit scope descriptors.
+    Lu16,
+
+    /// `__aeabi_unwind_cpp_pr2`, which has a variable number of unwind
+    /// instructions and 32-bit scope descriptors.
+    Lu32,
+
+    /// The personality routine at this address.
+    Generic(u64),
+}
+
+/// An entry in the `.ARM.extab` section.
+#[derive(Debug, Clone)]
+pub struct ArmExtabEntry<R: Reader> {
+    personality: ArmPersonality,
+    instructions: ArmUnwindInstructions<R>,
+    data: R,
+}
+
+impl<R: Reader> ArmExtabEntry<R> {
+    /// The personality routine for this entry.
+    pub fn personality(&self) -> ArmPersonality {
+        self.personality
+    }
+
+    /// The unwind instructions for this entry.
+    pub fn instructions(&self) -> &ArmUnwindInstructions<R> {
+        &self.instructions
+    }
+
+    /// The language-specific data that follows the unwind instructions.
+    ///
+    /// This extends to the end of the section, since its length is only known
+    /// to the personality routine.
+    pub fn data(&self) -> &R {
+        &self.data
+    }
+}
+
+/// A sequence of ARM EHABI unwind instructions.
+#[derive(Debug, Clone)]
+pub struct ArmUnwindInstructions<R: Reader> {
+    // The current word, and the number of its bytes that are unread.
+    word: u32,
+    bytes: u8,
+    // The remaining words.
+    words: R,
+}
+
+impl<R: Reader> ArmUnwindInstructions<R> {
+    /// Parse the instructions for a compact model entry, which starts with
+    /// `word`. Any additional words are read from `input`.
+    fn compact(word: u32, input: &mut R) -> Result<Self> {
+        match (word >> 24) & 0xf {
+            0 => Ok(ArmUnwindInstructions {
+                word,
+                bytes: 3,
+                words: {
+                    let mut words = input.clone();
+                    words.empty();
+                    words
+                },
+            }),
+            1 | 2 => {
+                let len = R::Offset::from_u32(((word >> 16) & 0xff) * 4);
+                Ok(ArmUnwindInstructions {
+                    word,
+                    bytes: 2,
+                    words: input.split(len)?,
+                })
+            }
+            index => Err(Error::UnknownArmPersonality(index as u8)),
+        }
+    }
+
+    /// Iterate over the instructions.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn iter(&self) -> ArmUnwindInstructionIter<R> {
+        ArmUnwindInstructionIter {
+            instructions: self.clone(),
+        }
+    }
+
+    /// Evaluate the instructions, and return the equivalent row of a DWARF
+    /// unwind table for the given address range.
+    ///
+    /// The CFA is the value of the virtual stack pointer after all
+    /// instructions have been evaluated, which is also the value of the stack
+    /// pointer in the previous frame. If the program counter is not popped,
+    /// then its value is the value of the link register.
+    pub fn unwind_row(&self, start_address: u64, end_address: u64) -> Result<UnwindTableRow<R>> {
+        // The offset of each saved register from the initial virtual stack
+        // pointer, indexed by DWARF register number.
+        let mut saved = [None; SAVED_REGISTERS];
+        let mut vsp_register = Arm::SP;
+        let mut vsp = 0i64;
+
+        fn save(saved: &mut [Option<i64>; SAVED_REGISTERS], register: Register, vsp: i64) {
+            saved[register.0 as usize] = Some(vsp);
+        }
+
+        fn adjust(vsp: i64, offset: i64) -> Result<i64> {
+            vsp.checked_add(offset).ok_or(Error::OffsetOverflow)
+        }
+
+        let mut iter = self.iter();
+        while let Some(instruction) = iter.next()? {
+            match instruction {
+                ArmUnwindInstruction::AdjustVsp(offset) => vsp = adjust(vsp, offset)?,
+                ArmUnwindInstruction::RefuseToUnwind => return Err(Error::ArmCannotUnwind),
+                ArmUnwindInstruction::PopCore(mask) => {
+                    if mask & (1 << Arm::SP.0) != 0 {
+                        // The new stack pointer is loaded from the stack, so
+                        // there is no fixed CFA. Only the `0x8X` instruction
+                        // can pop it, and its opcode holds the mask of r12-r15.
+                        let opcode = 0x80 | (mask >> 12) as u8;
+                        return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                    }
+                    for register in 0..16 {
+                        if mask & (1 << register) != 0 {
+                            save(&mut saved, Register(register), vsp);
+                            vsp = adjust(vsp, 4)?;
+                        }
+                    }
+                }
+                ArmUnwindInstruction::SetVsp(register) => {
+                    if vsp != 0 || saved.iter().any(Option::is_some) {
+                        return Err(Error::UnsupportedArmUnwindInstruction(0x90 | register));
+                    }
+                    vsp_register = Register(u16::from(register));
+                }
+                ArmUnwindInstruction::PopVfp {
+                    first,
+                    count,
+                    fstmx,
+                } => {
+                    for register in first..first + count {
+                        let register = Register(Arm::D0.0 + u16::from(register));
+                        save(&mut saved, register, vsp);
+                        vsp = adjust(vsp, 8)?;
+                    }
+                    if fstmx {
+                        vsp = adjust(vsp, 4)?;
+                    }
+                }
+                ArmUnwindInstruction::PopWmmx { first, count } => {
+                    for register in first..first + count {
+                        let register = Register(Arm::WR0.0 + u16::from(register));
+                        save(&mut saved, register, vsp);
+                        vsp = adjust(vsp, 8)?;
+                    }
+                }
+                ArmUnwindInstruction::PopWmmxControl(mask) => for register in 0..4 {
+                    if mask & (1 << register) != 0 {
+                        let register = Register(Arm::WCGR0.0 + register);
+                        save(&mut saved, register, vsp);
+                        vsp = adjust(vsp, 4)?;
+                    }
+                },
+                ArmUnwindInstruction::Finish => break,
+            }
+        }
+
+        let cfa = CfaRule::RegisterAndOffset {
+            register: vsp_register,
+            offset: vsp,
+        };
+        let mut row = UnwindTableRow::new(start_address, end_address, cfa);
+        for (register, offset) in saved.iter().enumerate() {
+            if let Some(offset) = *offset {
+                let register = Register(register as u16);
+                row.set_register(register, RegisterRule::Offset(offset - vsp))?;
+            }
+        }
+        row.set_register(Arm::SP, RegisterRule::ValOffset(0))?;
+        if saved[Arm::PC.0 as usize].is_none() {
+            let rule = match saved[Arm::LR.0 as usize] {
+                Some(offset) => RegisterRule::Offset(offset - vsp),
+                None => RegisterRule::Register(Arm::LR),
+            };
+            row.set_register(Arm::PC, rule)?;
+        }
+        Ok(row)
+    }
+}
+
+/// A decoded ARM EHABI unwind instruction.
+///
+/// `vsp` is the virtual stack pointer, which is initially the value of the
+/// stack pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmUnwindInstruction {
+    /// `vsp = vsp + offset`.
+    AdjustVsp(i64),
+
+    /// The function cannot be unwound.
+    RefuseToUnwind,
+
+    /// Pop the core registers `r0` to `r15` that are set in this mask, in
+    /// increasing order, with `r0` in the least significant bit.
+    PopCore(u16),
+
+    /// `vsp = r[register]`.
+    SetVsp(u8),
+
+    /// Pop the VFP double-precision registers `D[first]` to
+    /// `D[first + count - 1]`.
+    PopVfp {
+        /// The first register.
+        first: u8,
+        /// The number of registers.
+        count: u8,
+        /// True if the registers were saved by `FSTMFDX`, which stores an
+        /// additional word.
+        fstmx: bool,
+    },
+
+    /// Pop the iWMMXt data registers `wR[first]` to `wR[first + count - 1]`.
+    PopWmmx {
+        /// The first register.
+        first: u8,
+        /// The number of registers.
+        count: u8,
+    },
+
+    /// Pop the iWMMXt control registers `wCGR0` to `wCGR3` that are set in
+    /// this mask, with `wCGR0` in the least significant bit.
+    PopWmmxControl(u8),
+
+    /// There are no further instructions.
+    Finish,
+}
+
+/// An iterator over ARM EHABI unwind instructions.
+#[derive(Debug, Clone)]
+pub struct ArmUnwindInstructionIter<R: Reader> {
+    instructions: ArmUnwindInstructions<R>,
+}
+
+impl<R: Reader> ArmUnwindInstructionIter<R> {
+    fn next_byte(&mut self) -> Result<Option<u8>> {
+        let instructions = &mut self.instructions;
+        if instructions.bytes == 0 {
+            if instructions.words.is_empty() {
+                return Ok(None);
+            }
+            instructions.word = instructions.words.read_u32()?;
+            instructions.bytes = 4;
+        }
+        instructions.bytes -= 1;
+        Ok(Some((instructions.word >> (8 * instructions.bytes)) as u8))
+    }
+
+    fn operand(&mut self, opcode: u8) -> Result<u8> {
+        match self.next_byte()? {
+            Some(byte) => Ok(byte),
+            None => Err(Error::UnsupportedArmUnwindInstruction(opcode)),
+        }
+    }
+
+    /// Advance the iterator to the next instruction.
+    ///
+    /// Returns `Ok(None)` when there are no more instructions. `Finish` is
+    /// returned for any explicit finish instructions, including padding.
+    pub fn next(&mut self) -> Result<Option<ArmUnwindInstruction>> {
+        match self.parse() {
+            Err(e) => {
+                self.instructions.bytes = 0;
+                self.instructions.words.empty();
+                Err(e)
+            }
+            result => result,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Option<ArmUnwindInstruction>> {
+        let opcode = match self.next_byte()? {
+            Some(opcode) => opcode,
+            None => return Ok(None),
+        };
+        let count = |byte: u8| (byte & 0xf) + 1;
+
+        let instruction = match opcode {
+            _ if opcode & 0xc0 == 0x00 => {
+                ArmUnwindInstruction::AdjustVsp(((opcode & 0x3f) as i64) * 4 + 4)
+            }
+            _ if opcode & 0xc0 == 0x40 => {
+                ArmUnwindInstruction::AdjustVsp(-(((opcode & 0x3f) as i64) * 4 + 4))
+            }
+            _ if opcode & 0xf0 == 0x80 => {
+                let mask = ((opcode as u16 & 0xf) << 8) | self.operand(opcode)? as u16;
+                if mask == 0 {
+                    ArmUnwindInstruction::RefuseToUnwind
+                } else {
+                    ArmUnwindInstruction::PopCore(mask << 4)
+                }
+            }
+            _ if opcode & 0xf0 == 0x90 => {
+                let register = opcode & 0xf;
+                if u16::from(register) == Arm::SP.0 || u16::from(register) == Arm::PC.0 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::SetVsp(register)
+            }
+            _ if opcode & 0xf0 == 0xa0 => {
+                // Pop r4 to r[4 + nnn], and optionally r14.
+                let mut mask = ((1u16 << ((opcode & 0x7) + 1)) - 1) << 4;
+                if opcode & 0x8 != 0 {
+                    mask |= 1 << Arm::LR.0;
+                }
+                ArmUnwindInstruction::PopCore(mask)
+            }
+            0xb0 => ArmUnwindInstruction::Finish,
+            0xb1 => {
+                let mask = self.operand(opcode)?;
+                if mask == 0 || mask & 0xf0 != 0 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::PopCore(mask as u16)
+            }
+            0xb2 => {
+                let mut value = 0u64;
+                let mut shift = 0;
+                loop {
+                    let byte = self.operand(opcode)?;
+                    if shift == 63 && byte > 1 {
+                        return Err(Error::BadUnsignedLeb128);
+                    }
+                    value |= ((byte & 0x7f) as u64) << shift;
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                    shift += 7;
+                    if shift > 63 {
+                        return Err(Error::BadUnsignedLeb128);
+                    }
+                }
+                let offset = value
+                    .checked_mul(4)
+                    .and_then(|offset| offset.checked_add(0x204))
+                    .filter(|&offset| offset <= i64::max_value() as u64)
+                    .ok_or(Error::OffsetOverflow)?;
+                ArmUnwindInstruction::AdjustVsp(offset as i64)
+            }
+            0xb3 => {
+                let byte = self.operand(opcode)?;
+                let (first, count) = (byte >> 4, count(byte));
+                if first + count > 16 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::PopVfp {
+                    first,
+                    count,
+                    fstmx: true,
+                }
+            }
+            _ if opcode & 0xf8 == 0xb8 => ArmUnwindInstruction::PopVfp {
+                first: 8,
+                count: (opcode & 0x7) + 1,
+                fstmx: true,
+            },
+            _ if opcode & 0xf8 == 0xc0 && opcode & 0x7 < 6 => ArmUnwindInstruction::PopWmmx {
+                first: 10,
+                count: (opcode & 0x7) + 1,
+            },
+            0xc6 => {
+                let byte = self.operand(opcode)?;
+                let (first, count) = (byte >> 4, count(byte));
+                if first + count > 16 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::PopWmmx { first, count }
+            }
+            0xc7 => {
+                let mask = self.operand(opcode)?;
+                if mask == 0 || mask & 0xf0 != 0 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::PopWmmxControl(mask)
+            }
+            0xc8 | 0xc9 => {
+                let byte = self.operand(opcode)?;
+                // 0xc8 pops registers from D16-D31, and 0xc9 from D0-D15.
+                let base = if opcode == 0xc8 { 16 } else { 0 };
+                let (first, count) = (base + (byte >> 4), count(byte));
+                if first + count > base + 16 {
+                    return Err(Error::UnsupportedArmUnwindInstruction(opcode));
+                }
+                ArmUnwindInstruction::PopVfp {
+                    first,
+                    count,
+                    fstmx: false,
+                }
+            }
+            _ if opcode & 0xf8 == 0xd0 => ArmUnwindInstruction::PopVfp {
+                first: 8,
+                count: (opcode & 0x7) + 1,
+                fstmx: false,
+            },
+            _ => return Err(Error::UnsupportedArmUnwindInstruction(opcode)),
+        };
+        Ok(Some(instruction))
+    }
+}
+
+impl<R: Reader> ::fallible_iterator::FallibleIterator for ArmUnwindInstructionIter<R> {
+    type Item = ArmUnwindInstruction;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        ArmUnwindInstructionIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+    use vec::Vec;
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const EXIDX: [u8; 24] = [
+        // 0x2000: pop {r4, r14}.
+        0x00, 0x10, 0x00, 0x00,
+        0xb0, 0xb0, 0xa8, 0x80,
+        // 0x2100: cannot unwind.
+        0xf8, 0x10, 0x00, 0x00,
+        0x01, 0x00, 0x00, 0x00,
+        // 0x2200: extab entry at 0x3000.
+        0xf0, 0x11, 0x00, 0x00,
+        0xec, 0x1f, 0x00, 0x00,
+    ];
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const EXTAB: [u8; 24] = [
+        // Lu16 with one additional word: vsp = r11, vsp += 12, pop {r14}.
+        0x02, 0x9b, 0x01, 0x81,
+        0xb0, 0xb0, 0x00, 0x84,
+        // Personality routine at 0x4000, vsp += 8, and LSDA.
+        0xf8, 0x0f, 0x00, 0x00,
+        0xb0, 0xb0, 0x01, 0x00,
+        0xaa, 0xbb, 0xcc, 0xdd,
+        // Lu32 with no additional words: refuse to unwind.
+        0x00, 0x80, 0x00, 0x82,
+    ];
+
+    #[test]
+    fn test_exidx_entries() {
+        let exidx = ArmExidx::new(&EXIDX, LittleEndian);
+        let mut entries = exidx.entries(0x1000);
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2000);
+        assert_eq!(entry.end_address(), Some(0x2100));
+        assert_eq!(entry.data(), ArmExidxData::Inline(0x80a8b0b0));
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2100);
+        assert_eq!(entry.data(), ArmExidxData::CantUnwind);
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2200);
+        assert_eq!(entry.end_address(), None);
+        assert_eq!(entry.data(), ArmExidxData::Extab(0x3000));
+        assert_eq!(entries.next(), Ok(None));
+
+        assert_eq!(exidx.entry_for_address(0x1000, 0x1fff), Ok(None));
+        let entry = exidx.entry_for_address(0x1000, 0x2000).unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2000);
+        let entry = exidx.entry_for_address(0x1000, 0x21ff).unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2100);
+        let entry = exidx.entry_for_address(0x1000, 0x9000).unwrap().unwrap();
+        assert_eq!(entry.function_address(), 0x2200);
+    }
+
+    #[test]
+    fn test_exidx_unwind_row() {
+        let exidx = ArmExidx::new(&EXIDX, LittleEndian);
+        let extab = ArmExtab::new(&EXTAB, LittleEndian);
+
+        let entry = exidx.entry_for_address(0x1000, 0x2000).unwrap().unwrap();
+        let row = entry.unwind_row(&extab, 0x3000).unwrap();
+        assert_eq!(row.start_address(), 0x2000);
+        assert_eq!(row.end_address(), 0x2100);
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Arm::SP,
+                offset: 8,
+            }
+        );
+        assert_eq!(row.register(Register(4)), RegisterRule::Offset(-8));
+        assert_eq!(row.register(Arm::LR), RegisterRule::Offset(-4));
+        assert_eq!(row.register(Arm::PC), RegisterRule::Offset(-4));
+        assert_eq!(row.register(Arm::SP), RegisterRule::ValOffset(0));
+        assert_eq!(row.register(Register(5)), RegisterRule::Undefined);
+
+        let entry = exidx.entry_for_address(0x1000, 0x2100).unwrap().unwrap();
+        assert_eq!(
+            entry.unwind_row(&extab, 0x3000).map(|_| ()),
+            Err(Error::ArmCannotUnwind)
+        );
+
+        let entry = exidx.entry_for_address(0x1000, 0x2200).unwrap().unwrap();
+        let row = entry.unwind_row(&extab, 0x3000).unwrap();
+        assert_eq!(row.end_address(), !0);
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(11),
+                offset: 16,
+            }
+        );
+        assert_eq!(row.register(Arm::LR), RegisterRule::Offset(-4));
+        assert_eq!(row.register(Arm::PC), RegisterRule::Offset(-4));
+    }
+
+    #[test]
+    fn test_extab_entries() {
+        let extab = ArmExtab::new(&EXTAB, LittleEndian);
+
+        let entry = extab.entry(0x3000, 0x3000).unwrap();
+        assert_eq!(entry.personality(), ArmPersonality::Lu16);
+        let mut iter = entry.instructions().iter();
+        assert_eq!(iter.next(), Ok(Some(ArmUnwindInstruction::SetVsp(11))));
+        assert_eq!(iter.next(), Ok(Some(ArmUnwindInstruction::AdjustVsp(12))));
+        assert_eq!(
+            iter.next(),
+            Ok(Some(ArmUnwindInstruction::PopCore(1 << Arm::LR.0)))
+        );
+        assert_eq!(iter.next(), Ok(Some(ArmUnwindInstruction::Finish)));
+        assert_eq!(iter.next(), Ok(Some(ArmUnwindInstruction::Finish)));
+        assert_eq!(iter.next(), Ok(None));
+
+        let entry = extab.entry(0x3000, 0x3008).unwrap();
+        assert_eq!(entry.personality(), ArmPersonality::Generic(0x4000));
+        assert_eq!(entry.data().buf(), &[0xaa, 0xbb, 0xcc, 0xdd, 0x00, 0x80, 0x00, 0x82]);
+        let row = entry.instructions().unwind_row(0, 4).unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Arm::SP,
+                offset: 8,
+            }
+        );
+        assert_eq!(row.register(Arm::PC), RegisterRule::Register(Arm::LR));
+
+        let entry = extab.entry(0x3000, 0x3014).unwrap();
+        assert_eq!(entry.personality(), ArmPersonality::Lu32);
+        assert_eq!(
+            entry.instructions().iter().next(),
+            Ok(Some(ArmUnwindInstruction::RefuseToUnwind))
+        );
+        assert_eq!(
+            entry.instructions().unwind_row(0, 4).map(|_| ()),
+            Err(Error::ArmCannotUnwind)
+        );
+    }
+
+    #[test]
+    fn test_unwind_row_vfp() {
+        // Pop d0-d1 saved by FSTMFDX, then finish.
+        let buf = [0xb0, 0x01, 0xb3, 0x80];
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        let row = entry.instructions().unwind_row(0, 4).unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Arm::SP,
+                offset: 20,
+            }
+        );
+        assert_eq!(row.register(Arm::D0), RegisterRule::Offset(-20));
+        assert_eq!(row.register(Arm::D1), RegisterRule::Offset(-12));
+        assert_eq!(row.register(Arm::PC), RegisterRule::Register(Arm::LR));
+    }
+
+    /// Encode `instructions` as an `.ARM.extab` entry for the Lu16
+    /// personality routine, padding them with "finish" instructions.
+    fn lu16(instructions: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x81, 0];
+        bytes.extend_from_slice(instructions);
+        while bytes.len() % 4 != 0 {
+            bytes.push(0xb0);
+        }
+        bytes[1] = (bytes.len() / 4 - 1) as u8;
+        // Each word is stored in little endian, but its instructions are
+        // read from its most significant byte first.
+        bytes.chunks(4).flat_map(|word| word.iter().rev().cloned()).collect()
+    }
+
+    #[test]
+    fn test_unwind_row_vfp_out_of_range() {
+        // Pop d31-d46.
+        let buf = [0xff, 0xc8, 0x00, 0x81];
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        assert_eq!(
+            entry.instructions().unwind_row(0, 4).map(|_| ()),
+            Err(Error::UnsupportedArmUnwindInstruction(0xc8))
+        );
+
+        // Pop d15-d16 with FSTMFDX, d15-d16 with VPUSH, and wr15-wr16.
+        for &(opcode, operand) in &[(0xb3, 0xf1), (0xc9, 0xf1), (0xc6, 0xf1)] {
+            let buf = lu16(&[opcode, operand]);
+            let extab = ArmExtab::new(&buf, LittleEndian);
+            let entry = extab.entry(0, 0).unwrap();
+            assert_eq!(
+                entry.instructions().iter().next(),
+                Err(Error::UnsupportedArmUnwindInstruction(opcode))
+            );
+        }
+
+        // Pop d16-d31, which is the largest valid range.
+        let buf = lu16(&[0xc8, 0x0f]);
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        let row = entry.instructions().unwind_row(0, 4).unwrap();
+        assert_eq!(row.register(Register(Arm::D0.0 + 31)), RegisterRule::Offset(-8));
+    }
+
+    #[test]
+    fn test_unwind_row_vsp_overflow() {
+        // vsp += 0x204 + (2^62 - 1) * 4
+        let buf = lu16(&[0xb2, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f]);
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        assert_eq!(entry.instructions().iter().next(), Err(Error::OffsetOverflow));
+
+        // vsp += 0x204 + (2^60 - 1) * 4, twice.
+        let adjust = [0xb2, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f];
+        let buf = lu16(&[&adjust[..], &adjust[..]].concat());
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        assert_eq!(
+            entry.instructions().unwind_row(0, 4).map(|_| ()),
+            Err(Error::OffsetOverflow)
+        );
+    }
+
+    #[test]
+    fn test_unwind_row_pop_sp() {
+        // Pop r13, then finish.
+        let buf = [0xb0, 0x00, 0x82, 0x80];
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        assert_eq!(
+            entry.instructions().unwind_row(0, 4).map(|_| ()),
+            Err(Error::UnsupportedArmUnwindInstruction(0x82))
+        );
+    }
+
+    #[test]
+    fn test_unwind_instructions() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            0x0f, 0xb1, 0x04, 0x82,
+            0xb3, 0x01, 0xb2, 0x3f,
+            0x41, 0x03, 0xc7, 0x12,
+            0xd1, 0x21, 0xc8, 0xc1,
+            0xb0, 0xb0, 0xb0, 0x9f,
+        ];
+        let extab = ArmExtab::new(&buf, LittleEndian);
+        let entry = extab.entry(0, 0).unwrap();
+        assert_eq!(entry.personality(), ArmPersonality::Lu32);
+
+        let mut iter = entry.instructions().iter();
+        let expected = [
+            ArmUnwindInstruction::PopCore(0xf),
+            ArmUnwindInstruction::AdjustVsp(0x3f * 4 + 4),
+            ArmUnwindInstruction::AdjustVsp(0x208),
+            ArmUnwindInstruction::PopVfp {
+                first: 1,
+                count: 3,
+                fstmx: true,
+            },
+            ArmUnwindInstruction::PopWmmxControl(0x3),
+            ArmUnwindInstruction::AdjustVsp(-8),
+            ArmUnwindInstruction::PopWmmx {
+                first: 10,
+                count: 2,
+            },
+            ArmUnwindInstruction::PopVfp {
+                first: 18,
+                count: 2,
+                fstmx: false,
+            },
+            ArmUnwindInstruction::PopVfp {
+                first: 8,
+                count: 2,
+                fstmx: false,
+            },
+        ];
+        for instruction in &expected {
+            assert_eq!(iter.next(), Ok(Some(*instruction)));
+        }
+        assert_eq!(
+            iter.next(),
+            Err(Error::UnsupportedArmUnwindInstruction(0x9f))
+        );
+        assert_eq!(iter.next(), Ok(None));
+    }
+}
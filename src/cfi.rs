@@ -1,20 +1,21 @@
-use arrayvec::ArrayVec;
+use arrayvec::{Array, ArrayVec};
 use constants::{self, DwEhPe};
 use endianity::{EndianBuf, Endianity};
 use fallible_iterator::FallibleIterator;
+use arch::Register;
 use op::Expression;
-use parser::{parse_encoded_pointer, parse_initial_length, parse_pointer_encoding, Error, Format,
-             Pointer, Result, u64_to_u8};
+use parser::{padding_length, parse_encoded_pointer, parse_initial_length, parse_pointer_encoding,
+             parse_register, EntryOrPadding, Error, Format, Pointer, Result};
 use reader::{Reader, ReaderOffset};
-use std::cell::RefCell;
 use std::fmt::Debug;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::cmp::{Ord, Ordering};
 use std::mem;
-use std::str;
 use boxed::Box;
-use Section;
+use btree_map::BTreeMap;
+use vec::Vec;
+use {Section, SectionId};
 
 /// An offset into the `.debug_frame` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -80,8 +81,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugFrame<R> {
-    fn section_name() -> &'static str {
-        ".debug_frame"
+    fn id() -> SectionId {
+        SectionId::DebugFrame
+    }
+
+    fn reader(&self) -> &R {
+        &self.0
     }
 }
 
@@ -158,8 +163,12 @@ impl<R: Reader> EhFrameHdr<R> {
 }
 
 impl<R: Reader> Section<R> for EhFrameHdr<R> {
-    fn section_name() -> &'static str {
-        ".eh_frame_hdr"
+    fn id() -> SectionId {
+        SectionId::EhFrameHdr
+    }
+
+    fn reader(&self) -> &R {
+        &self.0
     }
 }
 
@@ -224,7 +233,8 @@ impl<'a, R: Reader + 'a> EhHdrTable<'a, R> {
         let mut reader = self.hdr.table.clone();
 
         while len > 1 {
-            let head = reader.split(R::Offset::from_u64((len / 2) * row_size)?)?;
+            let head_size = (len / 2).checked_mul(row_size).ok_or(Error::OffsetOverflow)?;
+            let head = reader.split(R::Offset::from_u64(head_size)?)?;
             let tail = reader.clone();
 
             let pivot = parse_encoded_pointer(self.hdr.table_enc, bases, self.hdr.addr_size,
@@ -295,8 +305,12 @@ where
 }
 
 impl<R: Reader> Section<R> for EhFrame<R> {
-    fn section_name() -> &'static str {
-        ".eh_frame"
+    fn id() -> SectionId {
+        SectionId::EhFrame
+    }
+
+    fn reader(&self) -> &R {
+        &self.0
     }
 }
 
@@ -412,10 +426,48 @@ pub trait UnwindSection<R: Reader>: Clone + Debug + _UnwindSectionPrivate<R> {
             section: self.clone(),
             bases: bases,
             input: self.section().clone(),
+            offset: R::Offset::from_u8(0),
             phantom: PhantomData,
         }
     }
 
+    /// Iterate over the `CommonInformationEntry`s and fully parsed
+    /// `FrameDescriptionEntry`s in this section.
+    ///
+    /// Each FDE's CIE is looked up in `cache`, and is only parsed if it is not
+    /// already present. Every CIE that is parsed, either because it was
+    /// encountered during iteration or because an FDE refers to it, is added
+    /// to `cache`.
+    ///
+    /// ```
+    /// use gimli::{BaseAddresses, CfiEntry, CieCache, EhFrame, NativeEndian, UnwindSection};
+    ///
+    /// # fn foo() -> gimli::Result<()> {
+    /// # let read_eh_frame_section = || unimplemented!();
+    /// let eh_frame = EhFrame::new(read_eh_frame_section(), NativeEndian);
+    /// let bases = BaseAddresses::default();
+    /// let mut cache = CieCache::new();
+    ///
+    /// let mut entries = eh_frame.entries_with_cache(&bases, &mut cache);
+    /// while let Some(entry) = entries.next()? {
+    ///     if let CfiEntry::Fde(fde) = entry {
+    ///         println!("FDE for {:#x}", fde.initial_address());
+    ///     }
+    /// }
+    /// # unreachable!()
+    /// # }
+    /// ```
+    fn entries_with_cache<'bases, 'cache>(
+        &self,
+        bases: &'bases BaseAddresses,
+        cache: &'cache mut CieCache<Self, R>,
+    ) -> CachedCfiEntriesIter<'bases, 'cache, Self, R> {
+        CachedCfiEntriesIter {
+            entries: self.entries(bases),
+            cache: cache,
+        }
+    }
+
     /// Parse the `CommonInformationEntry` at the given offset.
     fn cie_from_offset<'bases>(
         &self,
@@ -480,15 +532,18 @@ pub trait UnwindSection<R: Reader>: Clone + Debug + _UnwindSectionPrivate<R> {
     /// # unreachable!()
     /// # }
     /// ```
-    fn unwind_info_for_address<'bases>(
+    fn unwind_info_for_address<'bases, S>(
         &self,
         bases: &'bases BaseAddresses,
-        ctx: UninitializedUnwindContext<Self, R>,
+        ctx: UninitializedUnwindContext<Self, R, S>,
         address: u64,
     ) -> UnwindResult<
-        (UnwindTableRow<R>, UninitializedUnwindContext<Self, R>),
-        UninitializedUnwindContext<Self, R>,
-    > {
+        (UnwindTableRow<R, S>, UninitializedUnwindContext<Self, R, S>),
+        UninitializedUnwindContext<Self, R, S>,
+    >
+    where
+        S: UnwindContextStorage<R>,
+    {
         let mut entries = self.entries(bases);
         let fde_result = loop {
             match entries.next() {
@@ -622,7 +677,7 @@ impl<R: Reader> _UnwindSectionPrivate<R> for EhFrame<R> {
         debug_assert!(!section.is_empty());
         debug_assert!(!input_before_offset.is_empty());
 
-        let input_offset = input_before_offset.offset_from(section);
+        let input_offset = input_before_offset.offset_from(section).ok()?;
         input_offset.checked_sub(input_relative_offset)
     }
 
@@ -679,7 +734,7 @@ pub struct BaseAddresses {
     // as we enter and exit FDE parsing.
     #[doc(hidden)]
     #[allow(missing_docs)]
-    pub func: RefCell<Option<u64>>,
+    pub func: Option<u64>,
 }
 
 impl BaseAddresses {
@@ -751,6 +806,7 @@ where
     section: Section,
     bases: &'bases BaseAddresses,
     input: R,
+    offset: R::Offset,
     phantom: PhantomData<Section>,
 }
 
@@ -759,26 +815,67 @@ where
     R: Reader,
     Section: UnwindSection<R>,
 {
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    ///
+    /// Entries are returned in the order that they occur in the section. If
+    /// parsing an entry fails, this is the offset of that entry.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.offset
+    }
+
     /// Advance the iterator to the next entry.
+    ///
+    /// Zero padding between entries in `.debug_frame` is skipped. In
+    /// `.eh_frame`, a zero length entry terminates the section instead.
     pub fn next(&mut self) -> Result<Option<CieOrFde<'bases, Section, R>>> {
+        loop {
+            match self.next_or_padding()? {
+                Some(EntryOrPadding::Entry(entry)) => return Ok(Some(entry)),
+                Some(EntryOrPadding::Padding { .. }) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Advance the iterator to the next entry, or to the zero padding before
+    /// it.
+    pub fn next_or_padding(
+        &mut self,
+    ) -> Result<Option<EntryOrPadding<CieOrFde<'bases, Section, R>, R::Offset>>> {
         if self.input.is_empty() {
             return Ok(None);
         }
 
-        // Clear any function relative base address, if one was set when parsing
-        // the last entry.
-        self.bases.func.borrow_mut().take();
+        if !Section::length_value_is_end_of_entries(0) {
+            let padding = padding_length(&self.input)?;
+            if padding > R::Offset::from_u8(0) {
+                let offset = self.offset;
+                self.input.skip(padding)?;
+                self.offset += padding;
+                return Ok(Some(EntryOrPadding::Padding {
+                    offset,
+                    length: padding,
+                }));
+            }
+        }
 
+        let len = self.input.len();
         match parse_cfi_entry(self.bases, self.section.clone(), &mut self.input) {
             Err(e) => {
                 self.input.empty();
                 Err(e)
             }
             Ok(None) => {
+                self.offset += len - self.input.len();
                 self.input.empty();
                 Ok(None)
             }
-            Ok(Some(entry)) => Ok(Some(entry)),
+            Ok(Some(entry)) => {
+                self.offset += len - self.input.len();
+                Ok(Some(EntryOrPadding::Entry(entry)))
+            }
         }
     }
 }
@@ -796,6 +893,162 @@ where
     }
 }
 
+/// A cache of parsed `CommonInformationEntry`s, keyed by their offset within
+/// their section.
+///
+/// A single cache should only be used with one section and one set of
+/// `BaseAddresses`, since both affect the parsed CIEs.
+#[derive(Clone, Debug)]
+pub struct CieCache<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    cies: BTreeMap<R::Offset, CommonInformationEntry<Section, R, R::Offset>>,
+}
+
+impl<Section, R> Default for CieCache<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    fn default() -> Self {
+        CieCache {
+            cies: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Section, R> CieCache<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    /// Construct a new, empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached CIE at the given offset, if any.
+    pub fn get(
+        &self,
+        offset: Section::Offset,
+    ) -> Option<&CommonInformationEntry<Section, R, R::Offset>> {
+        self.cies.get(&UnwindOffset::into(offset))
+    }
+
+    /// Add a CIE to the cache, replacing any existing CIE at the same offset.
+    pub fn insert(&mut self, cie: CommonInformationEntry<Section, R, R::Offset>) {
+        self.cies.insert(cie.offset(), cie);
+    }
+
+    /// Return the CIE at the given offset, parsing it from `section` and
+    /// adding it to the cache if it is not already present.
+    pub fn get_or_parse(
+        &mut self,
+        section: &Section,
+        bases: &BaseAddresses,
+        offset: Section::Offset,
+    ) -> Result<&CommonInformationEntry<Section, R, R::Offset>> {
+        let key = UnwindOffset::into(offset);
+        if !self.cies.contains_key(&key) {
+            let cie = section.cie_from_offset(bases, offset)?;
+            self.cies.insert(key, cie);
+        }
+        Ok(&self.cies[&key])
+    }
+
+    /// Return the number of cached CIEs.
+    pub fn len(&self) -> usize {
+        self.cies.len()
+    }
+
+    /// Return true if there are no cached CIEs.
+    pub fn is_empty(&self) -> bool {
+        self.cies.is_empty()
+    }
+
+    /// Remove all CIEs from the cache.
+    pub fn clear(&mut self) {
+        self.cies.clear();
+    }
+}
+
+/// A fully parsed entry in an unwind section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CfiEntry<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    /// This CFI entry is a `CommonInformationEntry`.
+    Cie(CommonInformationEntry<Section, R, R::Offset>),
+    /// This CFI entry is a `FrameDescriptionEntry`.
+    Fde(FrameDescriptionEntry<Section, R, R::Offset>),
+}
+
+/// An iterator over CIE and FDE entries in a `.debug_frame` or `.eh_frame`
+/// section, which resolves each FDE's CIE through a `CieCache`.
+///
+/// See
+/// [`UnwindSection::entries_with_cache`](./trait.UnwindSection.html#method.entries_with_cache)
+/// for details.
+#[derive(Debug)]
+pub struct CachedCfiEntriesIter<'bases, 'cache, Section, R>
+where
+    R: 'cache + Reader,
+    Section: 'cache + UnwindSection<R>,
+{
+    entries: CfiEntriesIter<'bases, Section, R>,
+    cache: &'cache mut CieCache<Section, R>,
+}
+
+impl<'bases, 'cache, Section, R> CachedCfiEntriesIter<'bases, 'cache, Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.entries.offset()
+    }
+
+    /// Advance the iterator to the next entry.
+    pub fn next(&mut self) -> Result<Option<CfiEntry<Section, R>>> {
+        match self.entries.next()? {
+            None => Ok(None),
+            Some(CieOrFde::Cie(cie)) => {
+                self.cache.insert(cie.clone());
+                Ok(Some(CfiEntry::Cie(cie)))
+            }
+            Some(CieOrFde::Fde(partial)) => {
+                let cache = &mut *self.cache;
+                let fde = partial.parse(|offset| {
+                    cache
+                        .get_or_parse(&partial.section, partial.bases, offset)
+                        .map(Clone::clone)
+                })?;
+                Ok(Some(CfiEntry::Fde(fde)))
+            }
+        }
+    }
+}
+
+impl<'bases, 'cache, Section, R> FallibleIterator for CachedCfiEntriesIter<'bases, 'cache, Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    type Item = CfiEntry<Section, R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        CachedCfiEntriesIter::next(self)
+    }
+}
+
 struct CfiEntryCommon<R: Reader> {
     offset: R::Offset,
     length: R::Offset,
@@ -817,7 +1070,7 @@ where
     R: Reader,
     Section: UnwindSection<R>,
 {
-    let offset = input.offset_from(section.section());
+    let offset = input.offset_from(section.section())?;
     let (length, format) = parse_initial_length(input)?;
 
     if Section::length_value_is_end_of_entries(length) {
@@ -941,6 +1194,29 @@ pub struct Augmentation {
 }
 
 impl Augmentation {
+    /// The pointer encoding used for the LSDA pointer in the augmentation data
+    /// of this CIE's FDEs, if they have one.
+    pub fn lsda(&self) -> Option<constants::DwEhPe> {
+        self.lsda
+    }
+
+    /// The address of the personality routine handler, with its pointer
+    /// encoding applied, if there is one.
+    pub fn personality(&self) -> Option<Pointer> {
+        self.personality
+    }
+
+    /// The pointer encoding used for the addresses in this CIE's FDEs, if it
+    /// is not the default.
+    pub fn fde_address_encoding(&self) -> Option<constants::DwEhPe> {
+        self.fde_address_encoding
+    }
+
+    /// True if this CIE's FDEs are trampolines for signal handlers.
+    pub fn is_signal_trampoline(&self) -> bool {
+        self.is_signal_trampoline
+    }
+
     fn parse<'bases, Section, R>(
         augmentation_str: &mut R,
         bases: &'bases BaseAddresses,
@@ -1083,7 +1359,7 @@ where
     /// > An unsigned LEB128 constant that indicates which column in the rule
     /// > table represents the return address of the function. Note that this
     /// > column might not correspond to an actual machine register.
-    return_address_register: u64,
+    return_address_register: Register,
 
     /// > A sequence of rules that are interpreted to create the initial setting
     /// > of each column in the table.
@@ -1161,8 +1437,8 @@ where
         let data_alignment_factor = rest.read_sleb128()?;
 
         let return_address_register = match Section::return_address_register_encoding(version) {
-            ReturnAddressRegisterEncoding::U8 => rest.read_u8()? as u64,
-            ReturnAddressRegisterEncoding::Uleb => rest.read_uleb128()?,
+            ReturnAddressRegisterEncoding::U8 => Register(u16::from(rest.read_u8()?)),
+            ReturnAddressRegisterEncoding::Uleb => parse_register(&mut rest)?,
         };
 
         let augmentation = if augmentation_string.is_empty() {
@@ -1230,6 +1506,11 @@ where
         self.length
     }
 
+    /// Get whether this CIE uses the 32-bit or 64-bit DWARF format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     /// > A version number (see Section 7.23). This number is specific to the
     /// > call frame information and is independent of the DWARF version number.
     pub fn version(&self) -> u8 {
@@ -1244,6 +1525,32 @@ where
         self.augmentation.as_ref()
     }
 
+    /// Return the address of the personality routine handler, if this CIE
+    /// has one.
+    pub fn personality(&self) -> Option<Pointer> {
+        self.augmentation.as_ref().and_then(|a| a.personality)
+    }
+
+    /// Return the pointer encoding of the LSDA pointers in this CIE's FDEs, if
+    /// they have them.
+    pub fn lsda_encoding(&self) -> Option<constants::DwEhPe> {
+        self.augmentation.as_ref().and_then(|a| a.lsda)
+    }
+
+    /// > The size of a target address in this CIE and any FDEs that use it, in
+    /// > bytes.
+    pub fn address_size(&self) -> u8 {
+        self.address_size
+    }
+
+    /// > The size of a segment selector in this CIE and any FDEs that use it,
+    /// > in bytes.
+    ///
+    /// This is always zero for `.eh_frame`.
+    pub fn segment_size(&self) -> u8 {
+        self.segment_size
+    }
+
     /// > A constant that is factored out of all advance location instructions
     /// > (see Section 6.4.2.1).
     pub fn code_alignment_factor(&self) -> u64 {
@@ -1259,7 +1566,9 @@ where
     /// > An unsigned ... constant that indicates which column in the rule
     /// > table represents the return address of the function. Note that this
     /// > column might not correspond to an actual machine register.
-    pub fn return_address_register(&self) -> u64 {
+    ///
+    /// Use `UnwindTableRow::return_address` to get the rule for this column.
+    pub fn return_address_register(&self) -> Register {
         self.return_address_register
     }
 }
@@ -1376,11 +1685,12 @@ where
         F: FnMut(Section::Offset)
             -> Result<CommonInformationEntry<Section, R, R::Offset>>,
     {
-        {
-            let mut func = bases.func.borrow_mut();
-            let offset = rest.offset_from(section.section());
-            *func = Some(offset.into_u64());
-        }
+        // Function relative pointers within this FDE are relative to the
+        // start of the FDE's addresses.
+        let bases = &BaseAddresses {
+            func: Some(rest.offset_from(section.section())?.into_u64()),
+            ..bases.clone()
+        };
 
         let cie = get_cie(cie_pointer)?;
 
@@ -1392,6 +1702,9 @@ where
 
         let (initial_address, address_range) =
             Self::parse_addresses(&mut rest, &cie, bases, section)?;
+        if initial_address.checked_add(address_range).is_none() {
+            return Err(Error::AddressOverflow);
+        }
 
         let aug_data = if let Some(ref augmentation) = cie.augmentation {
             Some(AugmentationData::parse(
@@ -1481,6 +1794,11 @@ where
         self.length
     }
 
+    /// Get whether this FDE uses the 32-bit or 64-bit DWARF format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     /// Iterate over this FDE's instructions.
     ///
     /// Will not include the CIE's initial instructions, if you want those do
@@ -1494,6 +1812,16 @@ where
         }
     }
 
+    /// The segment selector for this entry's addresses, or `None` if the
+    /// CIE's segment size is zero.
+    pub fn initial_segment(&self) -> Option<u64> {
+        if self.cie.segment_size != 0 {
+            Some(self.initial_segment)
+        } else {
+            None
+        }
+    }
+
     /// The first address for which this entry has unwind information for.
     pub fn initial_address(&self) -> u64 {
         self.initial_address
@@ -1512,8 +1840,7 @@ where
     /// entry.initial_address() + entry.len()`.
     pub fn contains(&self, address: u64) -> bool {
         let start = self.initial_address();
-        let end = start + self.len();
-        start <= address && address < end
+        start <= address && address - start < self.len()
     }
 
     /// The address of this FDE's language-specific data area (LSDA), if it has
@@ -1702,11 +2029,19 @@ pub type UnwindResult<T, UnwindContext> = ::std::result::Result<T, (Error, Unwin
 ///          | End |
 ///          +-----+
 /// ```
+///
+/// By default, the context uses `StoreOnStack`, which limits the number of
+/// register rules and the depth of the `DW_CFA_remember_state` stack. Use
+/// `UninitializedUnwindContext::new_in` to select a different
+/// `UnwindContextStorage`, such as the growable `StoreOnHeap`.
 #[derive(Clone, Debug)]
-pub struct UninitializedUnwindContext<Section, R>(Box<UnwindContext<Section, R>>)
+pub struct UninitializedUnwindContext<Section, R, S = StoreOnStack>(
+    Box<UnwindContext<Section, R, S>>,
+)
 where
     R: Reader,
-    Section: UnwindSection<R>;
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>;
 
 impl<Section, R> UninitializedUnwindContext<Section, R>
 where
@@ -1715,35 +2050,57 @@ where
 {
     /// Construct a new call frame unwinding context.
     pub fn new() -> UninitializedUnwindContext<Section, R> {
-        UninitializedUnwindContext(Box::new(UnwindContext::new()))
+        Self::new_in()
     }
 }
 
-impl<Section, R> Default for UninitializedUnwindContext<Section, R>
+impl<Section, R, S> UninitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
+{
+    /// Construct a new call frame unwinding context that uses the storage `S`.
+    ///
+    /// ```
+    /// use gimli::{DebugFrame, EndianBuf, LittleEndian, StoreOnHeap, UninitializedUnwindContext};
+    ///
+    /// type Buf<'a> = EndianBuf<'a, LittleEndian>;
+    /// let ctx = UninitializedUnwindContext::<DebugFrame<Buf>, Buf, StoreOnHeap>::new_in();
+    /// # let _ = ctx;
+    /// ```
+    pub fn new_in() -> UninitializedUnwindContext<Section, R, S> {
+        UninitializedUnwindContext(Box::new(UnwindContext::new_in()))
+    }
+}
+
+impl<Section, R, S> Default for UninitializedUnwindContext<Section, R, S>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     fn default() -> Self {
-        Self::new()
+        Self::new_in()
     }
 }
 
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<Section, R> UninitializedUnwindContext<Section, R>
+/// other signal-unsafe operations, unless the storage `S` allocates.
+impl<Section, R, S> UninitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Run the CIE's initial instructions, creating an
     /// `InitializedUnwindContext`.
     pub fn initialize(
         mut self,
         cie: &CommonInformationEntry<Section, R, R::Offset>,
-    ) -> UnwindResult<InitializedUnwindContext<Section, R>, Self> {
+    ) -> UnwindResult<InitializedUnwindContext<Section, R, S>, Self> {
         self.0.assert_fully_uninitialized();
 
         let result = {
@@ -1776,40 +2133,183 @@ where
 /// [`UninitializedUnwindContext`](./struct.UninitializedUnwindContext.html) for
 /// more details.
 #[derive(Clone, Debug)]
-pub struct InitializedUnwindContext<Section, R>(Box<UnwindContext<Section, R>>)
+pub struct InitializedUnwindContext<Section, R, S = StoreOnStack>(
+    Box<UnwindContext<Section, R, S>>,
+)
 where
     R: Reader,
-    Section: UnwindSection<R>;
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>;
 
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
 /// other signal-unsafe operations.
-impl<Section, R> InitializedUnwindContext<Section, R>
+impl<Section, R, S> InitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Reset this context to the uninitialized state.
-    pub fn reset(mut self) -> UninitializedUnwindContext<Section, R> {
+    pub fn reset(mut self) -> UninitializedUnwindContext<Section, R, S> {
         self.0.reset();
         UninitializedUnwindContext(self.0)
     }
 }
 
+/// A growable or fixed capacity array, used as the backing storage for the
+/// register rules and row stack of an unwinding context.
+///
+/// This is implemented for `ArrayVec`, which has a fixed capacity and never
+/// allocates, and for `Vec`, which grows as needed.
+pub trait ArrayLike: Default + Clone + Debug {
+    /// The type of the elements.
+    type Item;
+
+    /// Return the elements as a slice.
+    fn as_slice(&self) -> &[Self::Item];
+
+    /// Return the elements as a mutable slice.
+    fn as_mut_slice(&mut self) -> &mut [Self::Item];
+
+    /// Append an element to the end of the array.
+    ///
+    /// If there is no remaining capacity, then the element is returned in
+    /// `Err`.
+    fn try_push(&mut self, value: Self::Item) -> ::std::result::Result<(), Self::Item>;
+
+    /// Remove the last element and return it, or `None` if the array is empty.
+    fn pop(&mut self) -> Option<Self::Item>;
+
+    /// Remove the element at `index` and return it, replacing it with the last
+    /// element.
+    fn swap_remove(&mut self, index: usize) -> Self::Item;
+
+    /// Remove all elements.
+    fn clear(&mut self);
+}
+
+impl<A> ArrayLike for ArrayVec<A>
+where
+    A: Array,
+    A::Item: Clone + Debug,
+{
+    type Item = A::Item;
+
+    fn as_slice(&self) -> &[Self::Item] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [Self::Item] {
+        self
+    }
+
+    fn try_push(&mut self, value: Self::Item) -> ::std::result::Result<(), Self::Item> {
+        ArrayVec::try_push(self, value).map_err(|e| e.element())
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        ArrayVec::pop(self)
+    }
+
+    fn swap_remove(&mut self, index: usize) -> Self::Item {
+        ArrayVec::swap_remove(self, index)
+    }
+
+    fn clear(&mut self) {
+        ArrayVec::clear(self)
+    }
+}
+
+impl<T> ArrayLike for Vec<T>
+where
+    T: Clone + Debug,
+{
+    type Item = T;
+
+    fn as_slice(&self) -> &[Self::Item] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [Self::Item] {
+        self
+    }
+
+    fn try_push(&mut self, value: Self::Item) -> ::std::result::Result<(), Self::Item> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        Vec::pop(self)
+    }
+
+    fn swap_remove(&mut self, index: usize) -> Self::Item {
+        Vec::swap_remove(self, index)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+}
+
+/// Specifies how an unwinding context stores its register rules and its
+/// stack of rows saved by `DW_CFA_remember_state`.
+///
+/// If the register rules are full, then evaluation fails with
+/// `Error::TooManyRegisterRules`. If the stack is full, then evaluation fails
+/// with `Error::CfiStackFull`.
+pub trait UnwindContextStorage<R: Reader>: Sized + Clone + Debug + PartialEq + Eq {
+    /// The storage for the `(register, rule)` pairs of a row.
+    type Rules: ArrayLike<Item = (Register, RegisterRule<R>)>;
+
+    /// The storage for the stack of rows.
+    type Stack: ArrayLike<Item = UnwindTableRow<R, Self>>;
+}
+
+const MAX_REGISTER_RULES: usize = 32;
 const MAX_UNWIND_STACK_DEPTH: usize = 4;
-type UnwindContextStack<R> = ArrayVec<[UnwindTableRow<R>; MAX_UNWIND_STACK_DEPTH]>;
+
+/// Fixed capacity `UnwindContextStorage` and `EvaluationStorage`, which
+/// never allocates.
+///
+/// A row may have rules for up to 32 registers, and up to 4 rows may be on the
+/// stack at once. This is the default storage for unwinding contexts, and is
+/// suitable for use in signal handlers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoreOnStack;
+
+impl<R: Reader> UnwindContextStorage<R> for StoreOnStack {
+    type Rules = ArrayVec<[(Register, RegisterRule<R>); MAX_REGISTER_RULES]>;
+    type Stack = ArrayVec<[UnwindTableRow<R, Self>; MAX_UNWIND_STACK_DEPTH]>;
+}
+
+/// Growable `UnwindContextStorage` and `EvaluationStorage`, which has no
+/// limit on the number of register rules or the depth of the stacks.
+///
+/// Evaluating a CFI program or a DWARF expression with this storage may
+/// allocate, so it must not be used in signal handlers. This is the default
+/// storage for `Evaluation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoreOnHeap;
+
+impl<R: Reader> UnwindContextStorage<R> for StoreOnHeap {
+    type Rules = Vec<(Register, RegisterRule<R>)>;
+    type Stack = Vec<UnwindTableRow<R, Self>>;
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-struct UnwindContext<Section, R>
+struct UnwindContext<Section, R, S = StoreOnStack>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     // Stack of rows. The last row is the row currently being built by the
     // program. There is always at least one row. The vast majority of CFI
     // programs will only ever have one row on the stack.
-    stack: UnwindContextStack<R>,
+    stack: S::Stack,
 
     // If we are evaluating an FDE's instructions, then `is_initialized` will be
     // `true` and `initial_rules` will contain the initial register rules
@@ -1817,22 +2317,34 @@ where
     // `DW_CFA_restore`. Otherwise, when we are currently evaluating a CIE's
     // initial instructions, `is_initialized` will be `false` and
     // `initial_rules` is not to be read from.
-    initial_rules: RegisterRuleMap<R>,
+    initial_rules: RegisterRuleMap<R, S>,
     is_initialized: bool,
 
     phantom: PhantomData<Section>,
 }
 
+impl<Section, R> UnwindContext<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    #[cfg(test)]
+    fn new() -> UnwindContext<Section, R> {
+        Self::new_in()
+    }
+}
+
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<Section, R> UnwindContext<Section, R>
+/// other signal-unsafe operations, unless the storage `S` allocates.
+impl<Section, R, S> UnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
-    fn new() -> UnwindContext<Section, R> {
+    fn new_in() -> UnwindContext<Section, R, S> {
         let mut ctx = UnwindContext {
             stack: Default::default(),
             is_initialized: false,
@@ -1859,23 +2371,23 @@ where
     #[inline]
     fn assert_fully_uninitialized(&self) {
         assert_eq!(self.is_initialized, false);
-        assert_eq!(self.initial_rules.rules.len(), 0);
-        assert_eq!(self.stack.len(), 1);
-        assert!(self.stack[0].is_default());
+        assert_eq!(self.initial_rules.rules.as_slice().len(), 0);
+        assert_eq!(self.stack.as_slice().len(), 1);
+        assert!(self.stack.as_slice()[0].is_default());
     }
 
-    fn row(&self) -> &UnwindTableRow<R> {
-        self.stack.last().unwrap()
+    fn row(&self) -> &UnwindTableRow<R, S> {
+        self.stack.as_slice().last().unwrap()
     }
 
-    fn row_mut(&mut self) -> &mut UnwindTableRow<R> {
-        self.stack.last_mut().unwrap()
+    fn row_mut(&mut self) -> &mut UnwindTableRow<R, S> {
+        self.stack.as_mut_slice().last_mut().unwrap()
     }
 
     fn save_initial_rules(&mut self) {
         assert_eq!(self.is_initialized, false);
         self.initial_rules
-            .clone_from(&self.stack.last().unwrap().registers);
+            .clone_from(&self.stack.as_slice().last().unwrap().registers);
         self.is_initialized = true;
     }
 
@@ -1888,14 +2400,14 @@ where
         row.start_address = start_address;
     }
 
-    fn set_register_rule(&mut self, register: u8, rule: RegisterRule<R>) -> Result<()> {
+    fn set_register_rule(&mut self, register: Register, rule: RegisterRule<R>) -> Result<()> {
         let row = self.row_mut();
         row.registers.set(register, rule)
     }
 
     /// Returns `None` if we have not completed evaluation of a CIE's initial
     /// instructions.
-    fn get_initial_rule(&self, register: u8) -> Option<RegisterRule<R>> {
+    fn get_initial_rule(&self, register: Register) -> Option<RegisterRule<R>> {
         if !self.is_initialized {
             return None;
         }
@@ -1918,7 +2430,7 @@ where
     }
 
     fn pop_row(&mut self) {
-        assert!(self.stack.len() > 1);
+        assert!(self.stack.as_slice().len() > 1);
         self.stack.pop();
     }
 }
@@ -1980,16 +2492,17 @@ where
 /// > recording just the differences starting at the beginning address of each
 /// > subroutine in the program.
 #[derive(Debug)]
-pub struct UnwindTable<'cie, 'fde, 'ctx, Section, R>
+pub struct UnwindTable<'cie, 'fde, 'ctx, Section, R, S = StoreOnStack>
 where
     R: 'cie + 'fde + 'ctx + Reader,
     Section: 'cie + 'fde + 'ctx + UnwindSection<R>,
+    S: 'ctx + UnwindContextStorage<R>,
 {
     cie: &'cie CommonInformationEntry<Section, R, R::Offset>,
     next_start_address: u64,
     returned_last_row: bool,
     instructions: CallFrameInstructionIter<R>,
-    ctx: &'ctx mut UnwindContext<Section, R>,
+    ctx: &'ctx mut UnwindContext<Section, R, S>,
     // If this is `None`, then we are executing a CIE's initial_instructions. If
     // this is `Some`, then we are executing an FDE's instructions.
     fde: Option<&'fde FrameDescriptionEntry<Section, R, R::Offset>>,
@@ -1999,17 +2512,18 @@ where
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
 /// other signal-unsafe operations.
-impl<'fde, 'ctx, Section, R> UnwindTable<'fde, 'fde, 'ctx, Section, R>
+impl<'fde, 'ctx, Section, R, S> UnwindTable<'fde, 'fde, 'ctx, Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Construct a new `UnwindTable` for the given
     /// `FrameDescriptionEntry`'s CFI unwinding program.
     pub fn new(
-        ctx: &'ctx mut InitializedUnwindContext<Section, R>,
+        ctx: &'ctx mut InitializedUnwindContext<Section, R, S>,
         fde: &'fde FrameDescriptionEntry<Section, R, R::Offset>,
-    ) -> UnwindTable<'fde, 'fde, 'ctx, Section, R> {
+    ) -> UnwindTable<'fde, 'fde, 'ctx, Section, R, S> {
         assert!(ctx.0.is_initialized);
         Self::new_internal(&mut ctx.0, fde.cie(), Some(fde))
     }
@@ -2020,17 +2534,18 @@ where
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
 /// other signal-unsafe operations.
-impl<'cie, 'fde, 'ctx, Section, R> UnwindTable<'cie, 'fde, 'ctx, Section, R>
+impl<'cie, 'fde, 'ctx, Section, R, S> UnwindTable<'cie, 'fde, 'ctx, Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     fn new_internal(
-        ctx: &'ctx mut UnwindContext<Section, R>,
+        ctx: &'ctx mut UnwindContext<Section, R, S>,
         cie: &'cie CommonInformationEntry<Section, R, R::Offset>,
         fde: Option<&'fde FrameDescriptionEntry<Section, R, R::Offset>>,
-    ) -> UnwindTable<'cie, 'fde, 'ctx, Section, R> {
-        assert!(ctx.stack.len() >= 1);
+    ) -> UnwindTable<'cie, 'fde, 'ctx, Section, R, S> {
+        assert!(ctx.stack.as_slice().len() >= 1);
         let next_start_address = fde.map_or(0, |fde| fde.initial_address());
         let instructions = fde.map_or_else(|| cie.instructions(), |fde| fde.instructions());
         UnwindTable {
@@ -2046,10 +2561,10 @@ where
     /// Evaluate call frame instructions until the next row of the table is
     /// completed, and return it.
     ///
-    /// Unfortunately, this cannot be used with `FallibleIterator` because of
-    /// the restricted lifetime of the yielded item.
-    pub fn next_row(&mut self) -> Result<Option<&UnwindTableRow<R>>> {
-        assert!(self.ctx.stack.len() >= 1);
+    /// The table also implements `FallibleIterator`, which yields clones of
+    /// the rows.
+    pub fn next_row(&mut self) -> Result<Option<&UnwindTableRow<R, S>>> {
+        assert!(self.ctx.stack.as_slice().len() >= 1);
         self.ctx.set_start_address(self.next_start_address);
 
         loop {
@@ -2097,7 +2612,10 @@ where
                 return Ok(true);
             }
             AdvanceLoc { delta } => {
-                self.next_start_address = self.ctx.start_address() + delta as u64;
+                self.next_start_address = self.ctx
+                    .start_address()
+                    .checked_add(delta as u64)
+                    .ok_or(Error::AddressOverflow)?;
                 self.ctx.row_mut().end_address = self.next_start_address;
                 return Ok(true);
             }
@@ -2114,9 +2632,12 @@ where
                 factored_offset,
             } => {
                 let data_align = self.cie.data_alignment_factor();
+                let offset = factored_offset
+                    .checked_mul(data_align)
+                    .ok_or(Error::OffsetOverflow)?;
                 self.ctx.set_cfa(CfaRule::RegisterAndOffset {
                     register: register,
-                    offset: factored_offset * data_align,
+                    offset: offset,
                 });
             }
             DefCfaRegister { register } => if let CfaRule::RegisterAndOffset {
@@ -2143,7 +2664,9 @@ where
             } = *self.ctx.cfa_mut()
             {
                 let data_align = self.cie.data_alignment_factor();
-                *off = factored_offset * data_align;
+                *off = factored_offset
+                    .checked_mul(data_align)
+                    .ok_or(Error::OffsetOverflow)?;
             } else {
                 return Err(Error::CfiInstructionInInvalidContext);
             },
@@ -2164,7 +2687,9 @@ where
                 register,
                 factored_offset,
             } => {
-                let offset = factored_offset as i64 * self.cie.data_alignment_factor;
+                let offset = (factored_offset as i64)
+                    .checked_mul(self.cie.data_alignment_factor)
+                    .ok_or(Error::OffsetOverflow)?;
                 self.ctx
                     .set_register_rule(register, RegisterRule::Offset(offset))?;
             }
@@ -2172,7 +2697,9 @@ where
                 register,
                 factored_offset,
             } => {
-                let offset = factored_offset * self.cie.data_alignment_factor();
+                let offset = factored_offset
+                    .checked_mul(self.cie.data_alignment_factor())
+                    .ok_or(Error::OffsetOverflow)?;
                 self.ctx
                     .set_register_rule(register, RegisterRule::Offset(offset))?;
             }
@@ -2180,7 +2707,9 @@ where
                 register,
                 factored_offset,
             } => {
-                let offset = factored_offset as i64 * self.cie.data_alignment_factor();
+                let offset = (factored_offset as i64)
+                    .checked_mul(self.cie.data_alignment_factor())
+                    .ok_or(Error::OffsetOverflow)?;
                 self.ctx
                     .set_register_rule(register, RegisterRule::ValOffset(offset))?;
             }
@@ -2188,7 +2717,9 @@ where
                 register,
                 factored_offset,
             } => {
-                let offset = factored_offset * self.cie.data_alignment_factor();
+                let offset = factored_offset
+                    .checked_mul(self.cie.data_alignment_factor())
+                    .ok_or(Error::OffsetOverflow)?;
                 self.ctx
                     .set_register_rule(register, RegisterRule::ValOffset(offset))?;
             }
@@ -2230,8 +2761,8 @@ where
                 self.ctx.push_row()?;
             }
             RestoreState => {
-                assert!(self.ctx.stack.len() > 0);
-                if self.ctx.stack.len() == 1 {
+                assert!(self.ctx.stack.as_slice().len() > 0);
+                if self.ctx.stack.as_slice().len() == 1 {
                     return Err(Error::PopWithEmptyStack);
                 }
                 // Pop state while preserving current location.
@@ -2242,12 +2773,29 @@ where
 
             // No operation.
             Nop => {}
+
+            // The argument size does not affect any register rules.
+            ArgsSize { .. } => {}
         };
 
         Ok(false)
     }
 }
 
+impl<'cie, 'fde, 'ctx, Section, R, S> FallibleIterator for UnwindTable<'cie, 'fde, 'ctx, Section, R, S>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
+{
+    type Item = UnwindTableRow<R, S>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        self.next_row().map(|row| row.cloned())
+    }
+}
+
 // We tend to have very few register rules: usually only a couple. Even if we
 // have a rule for every register, on x86-64 with SSE and everything we're
 // talking about ~100 rules. So rather than keeping the rules in a hash map, or
@@ -2260,11 +2808,11 @@ where
 // is undefined and save a little bit more space and do a little fewer
 // comparisons that way.
 #[derive(Clone, Debug)]
-struct RegisterRuleMap<R: Reader> {
-    rules: ArrayVec<[(u8, RegisterRule<R>); 32]>,
+struct RegisterRuleMap<R: Reader, S: UnwindContextStorage<R> = StoreOnStack> {
+    rules: S::Rules,
 }
 
-impl<R: Reader> Default for RegisterRuleMap<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> Default for RegisterRuleMap<R, S> {
     fn default() -> Self {
         RegisterRuleMap {
             rules: Default::default(),
@@ -2272,17 +2820,32 @@ impl<R: Reader> Default for RegisterRuleMap<R> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<R, S> ::serde::Serialize for RegisterRuleMap<R, S>
+where
+    R: Reader + ::serde::Serialize,
+    S: UnwindContextStorage<R>,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> ::std::result::Result<Ser::Ok, Ser::Error>
+    where
+        Ser: ::serde::Serializer,
+    {
+        serializer.collect_map(self.iter().map(|&(ref register, ref rule)| (register, rule)))
+    }
+}
+
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<R: Reader> RegisterRuleMap<R> {
+/// other signal-unsafe operations, unless the storage `S` allocates.
+impl<R: Reader, S: UnwindContextStorage<R>> RegisterRuleMap<R, S> {
     fn is_default(&self) -> bool {
-        self.rules.len() == 0
+        self.rules.as_slice().is_empty()
     }
 
-    fn get(&self, register: u8) -> RegisterRule<R> {
+    fn get(&self, register: Register) -> RegisterRule<R> {
         self.rules
+            .as_slice()
             .iter()
             .find(|rule| rule.0 == register)
             .map(|r| {
@@ -2292,9 +2855,10 @@ impl<R: Reader> RegisterRuleMap<R> {
             .unwrap_or(RegisterRule::Undefined)
     }
 
-    fn set(&mut self, register: u8, rule: RegisterRule<R>) -> Result<()> {
+    fn set(&mut self, register: Register, rule: RegisterRule<R>) -> Result<()> {
         if !rule.is_defined() {
             let idx = self.rules
+                .as_slice()
                 .iter()
                 .enumerate()
                 .find(|&(_, r)| r.0 == register)
@@ -2305,7 +2869,7 @@ impl<R: Reader> RegisterRuleMap<R> {
             return Ok(());
         }
 
-        for &mut (reg, ref mut old_rule) in &mut self.rules {
+        for &mut (reg, ref mut old_rule) in self.rules.as_mut_slice() {
             debug_assert!(old_rule.is_defined());
             if reg == register {
                 mem::replace(old_rule, rule);
@@ -2322,17 +2886,18 @@ impl<R: Reader> RegisterRuleMap<R> {
     }
 
     fn iter(&self) -> RegisterRuleIter<R> {
-        RegisterRuleIter(self.rules.iter())
+        RegisterRuleIter(self.rules.as_slice().iter())
     }
 }
 
-impl<'a, R> FromIterator<&'a (u8, RegisterRule<R>)> for RegisterRuleMap<R>
+impl<'a, R, S> FromIterator<&'a (Register, RegisterRule<R>)> for RegisterRuleMap<R, S>
 where
     R: 'a + Reader,
+    S: UnwindContextStorage<R>,
 {
-    fn from_iter<T>(iter: T) -> RegisterRuleMap<R>
+    fn from_iter<T>(iter: T) -> RegisterRuleMap<R, S>
     where
-        T: IntoIterator<Item = &'a (u8, RegisterRule<R>)>,
+        T: IntoIterator<Item = &'a (Register, RegisterRule<R>)>,
     {
         let iter = iter.into_iter();
         let mut rules = RegisterRuleMap::default();
@@ -2346,19 +2911,20 @@ where
     }
 }
 
-impl<R> PartialEq for RegisterRuleMap<R>
+impl<R, S> PartialEq for RegisterRuleMap<R, S>
 where
     R: Reader + PartialEq,
+    S: UnwindContextStorage<R>,
 {
     fn eq(&self, rhs: &Self) -> bool {
-        for &(reg, ref rule) in &self.rules {
+        for &(reg, ref rule) in self.rules.as_slice() {
             debug_assert!(rule.is_defined());
             if *rule != rhs.get(reg) {
                 return false;
             }
         }
 
-        for &(reg, ref rhs_rule) in &rhs.rules {
+        for &(reg, ref rhs_rule) in rhs.rules.as_slice() {
             debug_assert!(rhs_rule.is_defined());
             if *rhs_rule != self.get(reg) {
                 return false;
@@ -2369,20 +2935,21 @@ where
     }
 }
 
-impl<R> Eq for RegisterRuleMap<R>
+impl<R, S> Eq for RegisterRuleMap<R, S>
 where
     R: Reader + Eq,
+    S: UnwindContextStorage<R>,
 {
 }
 
 /// An unordered iterator for register rules.
 #[derive(Debug, Clone)]
-pub struct RegisterRuleIter<'iter, R>(::std::slice::Iter<'iter, (u8, RegisterRule<R>)>)
+pub struct RegisterRuleIter<'iter, R>(::std::slice::Iter<'iter, (Register, RegisterRule<R>)>)
 where
     R: 'iter + Reader;
 
 impl<'iter, R: Reader> Iterator for RegisterRuleIter<'iter, R> {
-    type Item = &'iter (u8, RegisterRule<R>);
+    type Item = &'iter (Register, RegisterRule<R>);
 
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next()
@@ -2392,14 +2959,14 @@ impl<'iter, R: Reader> Iterator for RegisterRuleIter<'iter, R> {
 /// A row in the virtual unwind table that describes how to find the values of
 /// the registers in the *previous* frame for a range of PC addresses.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct UnwindTableRow<R: Reader> {
+pub struct UnwindTableRow<R: Reader, S: UnwindContextStorage<R> = StoreOnStack> {
     start_address: u64,
     end_address: u64,
     cfa: CfaRule<R>,
-    registers: RegisterRuleMap<R>,
+    registers: RegisterRuleMap<R, S>,
 }
 
-impl<R: Reader> Default for UnwindTableRow<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> Default for UnwindTableRow<R, S> {
     fn default() -> Self {
         UnwindTableRow {
             start_address: 0,
@@ -2410,7 +2977,35 @@ impl<R: Reader> Default for UnwindTableRow<R> {
     }
 }
 
-impl<R: Reader> UnwindTableRow<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> UnwindTableRow<R, S> {
+    /// Construct a new row for the given address range and CFA rule, with
+    /// no defined register rules.
+    ///
+    /// This is useful for synthesizing rows that aren't described by call
+    /// frame information, such as a fallback that assumes a frame pointer.
+    pub fn new(start_address: u64, end_address: u64, cfa: CfaRule<R>) -> Self {
+        UnwindTableRow {
+            start_address,
+            end_address,
+            cfa,
+            registers: Default::default(),
+        }
+    }
+
+    /// Set the canonical frame address (CFA) recovery rule for this row.
+    pub fn set_cfa(&mut self, cfa: CfaRule<R>) {
+        self.cfa = cfa;
+    }
+
+    /// Set the recovery rule for the given register.
+    ///
+    /// Setting the rule to `RegisterRule::Undefined` removes any existing rule
+    /// for the register. Returns `Error::TooManyRegisterRules` if the storage
+    /// for register rules is full.
+    pub fn set_register(&mut self, register: Register, rule: RegisterRule<R>) -> Result<()> {
+        self.registers.set(register, rule)
+    }
+
     fn is_default(&self) -> bool {
         self.start_address == 0 && self.end_address == 0 && self.cfa.is_default() &&
             self.registers.is_default()
@@ -2445,8 +3040,10 @@ impl<R: Reader> UnwindTableRow<R> {
 
     /// Get the register recovery rule for the given register number.
     ///
-    /// The register number mapping is architecture dependent. For example, in
-    /// the x86-64 ABI the register number mapping is defined in Figure 3.36:
+    /// The register number mapping is architecture dependent, and the
+    /// architecture types such as `X86_64` define constants for the register
+    /// numbers. For example, in the x86-64 ABI the register number mapping is
+    /// defined in Figure 3.36:
     ///
     /// > Figure 3.36: DWARF Register Number Mapping
     /// >
@@ -2487,10 +3084,32 @@ impl<R: Reader> UnwindTableRow<R> {
     /// >   <tr><td>Vector Mask Registers 0–7</td>        <td>118-125</td> <td>%k0–%k7</td></tr>
     /// >   <tr><td>Reserved</td>                         <td>126-129</td> <td></td></tr>
     /// > </table>
-    pub fn register(&self, register: u8) -> RegisterRule<R> {
+    pub fn register(&self, register: Register) -> RegisterRule<R> {
         self.registers.get(register)
     }
 
+    /// Get the register recovery rule for the return address of the function
+    /// described by the given CIE.
+    ///
+    /// The CIE's return address column might not correspond to an actual
+    /// machine register (for example, it may be a column number beyond the
+    /// architecture's general purpose registers).
+    ///
+    /// If the returned rule is `RegisterRule::Undefined`, then there is no
+    /// return address and this is the outermost frame.
+    pub fn return_address<Section, Offset>(
+        &self,
+        cie: &CommonInformationEntry<Section, R, Offset>,
+    ) -> RegisterRule<R>
+    where
+        Section: UnwindSection<R>,
+        Section::Offset: UnwindOffset<R::Offset>,
+        R: Reader<Offset = Offset>,
+        Offset: ReaderOffset,
+    {
+        self.register(cie.return_address_register())
+    }
+
     /// Iterate over all defined register `(number, rule)` pairs.
     ///
     /// The rules are not iterated in any guaranteed order. Any register that
@@ -2511,13 +3130,38 @@ impl<R: Reader> UnwindTableRow<R> {
     }
 }
 
+/// Serializes the row as a struct with `start_address`, `end_address`, `cfa`
+/// and `registers` fields, where `registers` is a map from register number
+/// to rule that only contains the registers that have a defined rule.
+#[cfg(feature = "serde")]
+impl<R, S> ::serde::Serialize for UnwindTableRow<R, S>
+where
+    R: Reader + ::serde::Serialize,
+    S: UnwindContextStorage<R>,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> ::std::result::Result<Ser::Ok, Ser::Error>
+    where
+        Ser: ::serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("UnwindTableRow", 4)?;
+        state.serialize_field("start_address", &self.start_address)?;
+        state.serialize_field("end_address", &self.end_address)?;
+        state.serialize_field("cfa", &self.cfa)?;
+        state.serialize_field("registers", &self.registers)?;
+        state.end()
+    }
+}
+
 /// The canonical frame address (CFA) recovery rules.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum CfaRule<R: Reader> {
     /// The CFA is given offset from the given register's value.
     RegisterAndOffset {
         /// The register containing the base value.
-        register: u8,
+        register: Register,
         /// The offset from the register's base value.
         offset: i64,
     },
@@ -2529,7 +3173,7 @@ pub enum CfaRule<R: Reader> {
 impl<R: Reader> Default for CfaRule<R> {
     fn default() -> Self {
         CfaRule::RegisterAndOffset {
-            register: 0,
+            register: Register(0),
             offset: 0,
         }
     }
@@ -2538,7 +3182,7 @@ impl<R: Reader> Default for CfaRule<R> {
 impl<R: Reader> CfaRule<R> {
     fn is_default(&self) -> bool {
         match *self {
-            CfaRule::RegisterAndOffset { register, offset } => register == 0 && offset == 0,
+            CfaRule::RegisterAndOffset { register, offset } => register == Register(0) && offset == 0,
             _ => false,
         }
     }
@@ -2551,6 +3195,7 @@ impl<R: Reader> CfaRule<R> {
 /// has been saved and the rule to find the value for the register in the
 /// previous frame."
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum RegisterRule<R: Reader> {
     /// > A register that has this rule has no recoverable value in the previous
     /// > frame. (By convention, it is not preserved by a callee.)
@@ -2571,7 +3216,7 @@ pub enum RegisterRule<R: Reader> {
 
     /// "The previous value of this register is stored in another register
     /// numbered R."
-    Register(u8),
+    Register(Register),
 
     /// "The previous value of this register is located at the address produced
     /// by executing the DWARF expression."
@@ -2583,6 +3228,13 @@ pub enum RegisterRule<R: Reader> {
 
     /// "The rule is defined externally to this specification by the augmenter."
     Architectural,
+
+    /// The previous value of this register is the given constant.
+    ///
+    /// This rule is not produced by any DWARF 5 call frame instruction, but is
+    /// proposed for DWARF 6 and is used by some toolchains, e.g. to mark the
+    /// return address column of the outermost frame with a known value.
+    Constant(u64),
 }
 
 impl<R: Reader> RegisterRule<R> {
@@ -2637,7 +3289,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > register and offset.
     DefCfa {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The non-factored offset.
         offset: u64,
     },
@@ -2651,7 +3303,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > offset is factored_offset * data_alignment_factor.
     DefCfaSf {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The factored offset.
         factored_offset: i64,
     },
@@ -2665,7 +3317,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > is defined to use a register and offset.
     DefCfaRegister {
         /// The target register's number.
-        register: u8,
+        register: Register,
     },
 
     /// > 4. DW_CFA_def_cfa_offset
@@ -2712,7 +3364,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > set the rule for the specified register to “undefined.”
     Undefined {
         /// The target register's number.
-        register: u8,
+        register: Register,
     },
 
     /// > 2. DW_CFA_same_value
@@ -2722,7 +3374,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > set the rule for the specified register to “same value.”
     SameValue {
         /// The target register's number.
-        register: u8,
+        register: Register,
     },
 
     /// The `Offset` instruction represents both `DW_CFA_offset` and
@@ -2737,7 +3389,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > where the value of N is factored offset * data_alignment_factor.
     Offset {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The factored offset.
         factored_offset: u64,
     },
@@ -2752,7 +3404,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > data_alignment_factor.
     OffsetExtendedSf {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The factored offset.
         factored_offset: i64,
     },
@@ -2766,7 +3418,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > factored_offset * data_alignment_factor.
     ValOffset {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The factored offset.
         factored_offset: u64,
     },
@@ -2780,7 +3432,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > offset is factored_offset * data_alignment_factor.
     ValOffsetSf {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The factored offset.
         factored_offset: i64,
     },
@@ -2793,10 +3445,10 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > register.
     Register {
         /// The number of the register whose rule is being changed.
-        dest_register: u8,
+        dest_register: Register,
         /// The number of the register where the other register's value can be
         /// found.
-        src_register: u8,
+        src_register: Register,
     },
 
     /// > 9. DW_CFA_expression
@@ -2810,7 +3462,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > DWARF evaluation stack prior to execution of the DWARF expression.
     Expression {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The DWARF expression.
         expression: Expression<R>,
     },
@@ -2827,7 +3479,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > of the DWARF expression.
     ValExpression {
         /// The target register's number.
-        register: u8,
+        register: Register,
         /// The DWARF expression.
         expression: Expression<R>,
     },
@@ -2843,7 +3495,7 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > by the initial_instructions in the CIE.
     Restore {
         /// The register to be reset.
-        register: u8,
+        register: Register,
     },
 
     // 6.4.2.4 Row State Instructions
@@ -2867,6 +3519,20 @@ pub enum CallFrameInstruction<R: Reader> {
     /// > The DW_CFA_nop instruction has no operands and no required actions. It
     /// > is used as padding to make a CIE or FDE an appropriate size.
     Nop,
+
+    // GNU extensions
+    /// > DW_CFA_GNU_args_size
+    /// >
+    /// > The DW_CFA_GNU_args_size instruction takes an unsigned LEB128 operand
+    /// > representing an argument size. This instruction specifies the total of
+    /// > the size of the arguments which have been pushed onto the stack.
+    ///
+    /// This does not affect any register rules, but an unwinder that resumes
+    /// execution at a landing pad needs it to adjust the stack pointer.
+    ArgsSize {
+        /// The size of the arguments which have been pushed onto the stack.
+        size: u64,
+    },
 }
 
 const CFI_INSTRUCTION_HIGH_BITS_MASK: u8 = 0b11000000;
@@ -2885,7 +3551,7 @@ impl<R: Reader> CallFrameInstruction<R> {
         }
 
         if high_bits == constants::DW_CFA_offset.0 {
-            let register = instruction & CFI_INSTRUCTION_LOW_BITS_MASK;
+            let register = Register(u16::from(instruction & CFI_INSTRUCTION_LOW_BITS_MASK));
             let offset = input.read_uleb128()?;
             return Ok(CallFrameInstruction::Offset {
                 register: register,
@@ -2894,7 +3560,7 @@ impl<R: Reader> CallFrameInstruction<R> {
         }
 
         if high_bits == constants::DW_CFA_restore.0 {
-            let register = instruction & CFI_INSTRUCTION_LOW_BITS_MASK;
+            let register = Register(u16::from(instruction & CFI_INSTRUCTION_LOW_BITS_MASK));
             return Ok(CallFrameInstruction::Restore { register: register });
         }
 
@@ -2929,7 +3595,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_offset_extended => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_uleb128()?;
                 Ok(CallFrameInstruction::Offset {
                     register: register,
@@ -2937,24 +3603,51 @@ impl<R: Reader> CallFrameInstruction<R> {
                 })
             }
 
+            constants::DW_CFA_MIPS_advance_loc8 => {
+                let delta = input.read_u64()?;
+                if delta > u64::from(u32::MAX) {
+                    return Err(Error::AddressOverflow);
+                }
+                Ok(CallFrameInstruction::AdvanceLoc {
+                    delta: delta as u32,
+                })
+            }
+
+            constants::DW_CFA_GNU_args_size => {
+                let size = input.read_uleb128()?;
+                Ok(CallFrameInstruction::ArgsSize { size: size })
+            }
+
+            constants::DW_CFA_GNU_negative_offset_extended => {
+                let register = parse_register(input)?;
+                let offset = input.read_uleb128()?;
+                if offset > i64::MAX as u64 {
+                    return Err(Error::OffsetOverflow);
+                }
+                Ok(CallFrameInstruction::OffsetExtendedSf {
+                    register: register,
+                    factored_offset: -(offset as i64),
+                })
+            }
+
             constants::DW_CFA_restore_extended => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 Ok(CallFrameInstruction::Restore { register: register })
             }
 
             constants::DW_CFA_undefined => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 Ok(CallFrameInstruction::Undefined { register: register })
             }
 
             constants::DW_CFA_same_value => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 Ok(CallFrameInstruction::SameValue { register: register })
             }
 
             constants::DW_CFA_register => {
-                let dest = input.read_uleb128().and_then(u64_to_u8)?;
-                let src = input.read_uleb128().and_then(u64_to_u8)?;
+                let dest = parse_register(input)?;
+                let src = parse_register(input)?;
                 Ok(CallFrameInstruction::Register {
                     dest_register: dest,
                     src_register: src,
@@ -2966,7 +3659,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             constants::DW_CFA_restore_state => Ok(CallFrameInstruction::RestoreState),
 
             constants::DW_CFA_def_cfa => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_uleb128()?;
                 Ok(CallFrameInstruction::DefCfa {
                     register: register,
@@ -2975,7 +3668,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_def_cfa_register => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 Ok(CallFrameInstruction::DefCfaRegister { register: register })
             }
 
@@ -2993,7 +3686,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_expression => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let len = input.read_uleb128().and_then(R::Offset::from_u64)?;
                 let expression = input.split(len)?;
                 Ok(CallFrameInstruction::Expression {
@@ -3003,7 +3696,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_offset_extended_sf => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_sleb128()?;
                 Ok(CallFrameInstruction::OffsetExtendedSf {
                     register: register,
@@ -3012,7 +3705,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_def_cfa_sf => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_sleb128()?;
                 Ok(CallFrameInstruction::DefCfaSf {
                     register: register,
@@ -3028,7 +3721,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_val_offset => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_uleb128()?;
                 Ok(CallFrameInstruction::ValOffset {
                     register: register,
@@ -3037,7 +3730,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_val_offset_sf => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let offset = input.read_sleb128()?;
                 Ok(CallFrameInstruction::ValOffsetSf {
                     register: register,
@@ -3046,7 +3739,7 @@ impl<R: Reader> CallFrameInstruction<R> {
             }
 
             constants::DW_CFA_val_expression => {
-                let register = input.read_uleb128().and_then(u64_to_u8)?;
+                let register = parse_register(input)?;
                 let len = input.read_uleb128().and_then(R::Offset::from_u64)?;
                 let expression = input.split(len)?;
                 Ok(CallFrameInstruction::ValExpression {
@@ -3101,16 +3794,18 @@ mod tests {
 
     use super::*;
     use super::{parse_cfi_entry, AugmentationData, RegisterRuleMap, UnwindContext};
+    use arch::X86_64;
     use constants;
     use endianity::{BigEndian, EndianBuf, Endianity, LittleEndian, NativeEndian};
     use op::Expression;
-    use parser::{Error, Format, Pointer, Result};
+    use parser::{EntryOrPadding, Error, Format, Pointer, Result};
     use self::test_assembler::{Endian, Label, LabelMaker, LabelOrNum, Section, ToLabelOrNum};
     use std::marker::PhantomData;
     use std::mem;
-    use std::u64;
+    use std::{i64, u64};
     use vec::Vec;
     use test_util::GimliSectionMethods;
+    use Section as GimliSection;
 
     type DebugFrameCie<R, O = usize> = CommonInformationEntry<DebugFrame<R>, R, O>;
     type DebugFrameFde<R, O = usize> = FrameDescriptionEntry<DebugFrame<R>, R, O>;
@@ -3210,7 +3905,7 @@ mod tests {
             let section = section
                 .uleb(cie.code_alignment_factor)
                 .sleb(cie.data_alignment_factor)
-                .uleb(cie.return_address_register)
+                .uleb(cie.return_address_register.0.into())
                 .append_bytes(cie.initial_instructions.into())
                 .mark(&end);
 
@@ -3334,10 +4029,26 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    fn assert_parse_cie_eof<E>(section: Section)
+    where
+        E: Endianity,
+    {
+        let section = section.get_contents().unwrap();
+        let debug_frame = DebugFrame::new(&section, E::default());
+        let input = &mut EndianBuf::new(&section, E::default());
+        let bases = Default::default();
+        match DebugFrameCie::parse(&bases, debug_frame, input) {
+            Err(Error::UnexpectedEof(id)) => {
+                assert!(debug_frame.lookup_offset_id(id).is_some());
+            }
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+
     #[test]
     fn test_parse_cie_incomplete_length_32() {
         let section = Section::with_endian(Endian::Little).L16(5);
-        assert_parse_cie::<LittleEndian>(section, Err(Error::UnexpectedEof));
+        assert_parse_cie_eof::<LittleEndian>(section);
     }
 
     #[test]
@@ -3345,7 +4056,7 @@ mod tests {
         let section = Section::with_endian(Endian::Little)
             .L32(0xffffffff)
             .L32(12345);
-        assert_parse_cie::<LittleEndian>(section, Err(Error::UnexpectedEof));
+        assert_parse_cie_eof::<LittleEndian>(section);
     }
 
     #[test]
@@ -3354,7 +4065,7 @@ mod tests {
             // The length is not large enough to contain the ID.
             .B32(3)
             .B32(0xffffffff);
-        assert_parse_cie::<BigEndian>(section, Err(Error::UnexpectedEof));
+        assert_parse_cie_eof::<BigEndian>(section);
     }
 
     #[test]
@@ -3379,7 +4090,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 1,
             data_alignment_factor: 2,
-            return_address_register: 3,
+            return_address_register: Register(3),
             initial_instructions: EndianBuf::new(&[], LittleEndian),
             phantom: PhantomData,
         };
@@ -3440,7 +4151,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 16,
             data_alignment_factor: 32,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&expected_instrs, LittleEndian),
             phantom: PhantomData,
         };
@@ -3470,7 +4181,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 16,
             data_alignment_factor: 32,
-            return_address_register: 7,
+            return_address_register: Register(7),
             initial_instructions: EndianBuf::new(&expected_instrs, BigEndian),
             phantom: PhantomData,
         };
@@ -3499,7 +4210,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 0,
             data_alignment_factor: 0,
-            return_address_register: 3,
+            return_address_register: Register(3),
             initial_instructions: EndianBuf::new(&expected_instrs, LittleEndian),
             phantom: PhantomData,
         };
@@ -3515,14 +4226,14 @@ mod tests {
         contents[3] = 255;
 
         let bases = Default::default();
-        assert_eq!(
-            DebugFrameCie::parse(
-                &bases,
-                DebugFrame::new(&contents, LittleEndian),
-                &mut EndianBuf::new(&contents, LittleEndian)
-            ),
-            Err(Error::UnexpectedEof)
-        );
+        match DebugFrameCie::parse(
+            &bases,
+            DebugFrame::new(&contents, LittleEndian),
+            &mut EndianBuf::new(&contents, LittleEndian),
+        ) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
     }
 
     #[test]
@@ -3531,10 +4242,12 @@ mod tests {
         let section = section.get_contents().unwrap();
         let debug_frame = DebugFrame::new(&section, LittleEndian);
         let rest = &mut EndianBuf::new(&section, LittleEndian);
-        assert_eq!(
-            parse_fde(debug_frame, rest, |_| unreachable!()),
-            Err(Error::UnexpectedEof)
-        );
+        match parse_fde(debug_frame, rest, |_| unreachable!()) {
+            Err(Error::UnexpectedEof(id)) => {
+                assert!(debug_frame.lookup_offset_id(id).is_some());
+            }
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
     }
 
     #[test]
@@ -3545,10 +4258,12 @@ mod tests {
         let section = section.get_contents().unwrap();
         let debug_frame = DebugFrame::new(&section, LittleEndian);
         let rest = &mut EndianBuf::new(&section, LittleEndian);
-        assert_eq!(
-            parse_fde(debug_frame, rest, |_| unreachable!()),
-            Err(Error::UnexpectedEof)
-        );
+        match parse_fde(debug_frame, rest, |_| unreachable!()) {
+            Err(Error::UnexpectedEof(id)) => {
+                assert!(debug_frame.lookup_offset_id(id).is_some());
+            }
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
     }
 
     #[test]
@@ -3560,10 +4275,12 @@ mod tests {
         let section = section.get_contents().unwrap();
         let debug_frame = DebugFrame::new(&section, BigEndian);
         let rest = &mut EndianBuf::new(&section, BigEndian);
-        assert_eq!(
-            parse_fde(debug_frame, rest, |_| unreachable!()),
-            Err(Error::UnexpectedEof)
-        );
+        match parse_fde(debug_frame, rest, |_| unreachable!()) {
+            Err(Error::UnexpectedEof(id)) => {
+                assert!(debug_frame.lookup_offset_id(id).is_some());
+            }
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
     }
 
     #[test]
@@ -3578,12 +4295,61 @@ mod tests {
             format: Format::Dwarf32,
             version: 4,
             augmentation: None,
-            // DWARF32 with a 64 bit address size! Holy moly!
+            // DWARF32 with a 64 bit address size! Holy moly!
+            address_size: 8,
+            segment_size: 0,
+            code_alignment_factor: 3,
+            data_alignment_factor: 2,
+            return_address_register: Register(1),
+            initial_instructions: EndianBuf::new(&[], LittleEndian),
+            phantom: PhantomData,
+        };
+
+        let mut fde = DebugFrameFde {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            cie: cie.clone(),
+            initial_segment: 0,
+            initial_address: 0xfeedbeef,
+            address_range: 39,
+            augmentation: None,
+            instructions: EndianBuf::new(&expected_instrs, LittleEndian),
+        };
+
+        let section = Section::with_endian(Endian::Little)
+            .fde(Endian::Little, cie_offset, &mut fde)
+            .append_bytes(&expected_rest);
+
+        let section = section.get_contents().unwrap();
+        let rest = &mut EndianBuf::new(&section, LittleEndian);
+
+        let get_cie = |offset| {
+            assert_eq!(offset, DebugFrameOffset(cie_offset as usize));
+            Ok(cie.clone())
+        };
+
+        assert_eq!(
+            parse_fde(DebugFrame::new(&*section, LittleEndian), rest, get_cie),
+            Ok(fde)
+        );
+        assert_eq!(*rest, EndianBuf::new(&expected_rest, LittleEndian));
+    }
+
+    #[test]
+    fn test_parse_fde_address_overflow() {
+        let cie_offset = 0xbad0bad1;
+        let cie = DebugFrameCie {
+            offset: 0,
+            length: 100,
+            format: Format::Dwarf32,
+            version: 4,
+            augmentation: None,
             address_size: 8,
             segment_size: 0,
             code_alignment_factor: 3,
             data_alignment_factor: 2,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&[], LittleEndian),
             phantom: PhantomData,
         };
@@ -3594,29 +4360,23 @@ mod tests {
             format: Format::Dwarf32,
             cie: cie.clone(),
             initial_segment: 0,
-            initial_address: 0xfeedbeef,
-            address_range: 39,
+            initial_address: 0xffff_ffff_ffff_fff0,
+            address_range: 0x20,
             augmentation: None,
-            instructions: EndianBuf::new(&expected_instrs, LittleEndian),
+            instructions: EndianBuf::new(&[], LittleEndian),
         };
 
-        let section = Section::with_endian(Endian::Little)
-            .fde(Endian::Little, cie_offset, &mut fde)
-            .append_bytes(&expected_rest);
+        let section = Section::with_endian(Endian::Little).fde(Endian::Little, cie_offset, &mut fde);
 
         let section = section.get_contents().unwrap();
         let rest = &mut EndianBuf::new(&section, LittleEndian);
 
-        let get_cie = |offset| {
-            assert_eq!(offset, DebugFrameOffset(cie_offset as usize));
-            Ok(cie.clone())
-        };
+        let get_cie = |_| Ok(cie.clone());
 
         assert_eq!(
             parse_fde(DebugFrame::new(&*section, LittleEndian), rest, get_cie),
-            Ok(fde)
+            Err(Error::AddressOverflow)
         );
-        assert_eq!(*rest, EndianBuf::new(&expected_rest, LittleEndian));
     }
 
     #[test]
@@ -3635,7 +4395,7 @@ mod tests {
             segment_size: 4,
             code_alignment_factor: 3,
             data_alignment_factor: 2,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&[], LittleEndian),
             phantom: PhantomData,
         };
@@ -3664,11 +4424,14 @@ mod tests {
             Ok(cie.clone())
         };
 
-        assert_eq!(
-            parse_fde(DebugFrame::new(&*section, LittleEndian), rest, get_cie),
-            Ok(fde)
-        );
+        let parsed = parse_fde(DebugFrame::new(&*section, LittleEndian), rest, get_cie);
+        assert_eq!(parsed, Ok(fde));
         assert_eq!(*rest, EndianBuf::new(&expected_rest, LittleEndian));
+
+        let parsed = parsed.unwrap();
+        assert_eq!(parsed.cie().segment_size(), 4);
+        assert_eq!(parsed.initial_segment(), Some(0xbadbad11));
+        assert_eq!(parsed.initial_address(), 0xfeedbeef);
     }
 
     #[test]
@@ -3687,7 +4450,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 3,
             data_alignment_factor: 2,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&[], LittleEndian),
             phantom: PhantomData,
         };
@@ -3738,7 +4501,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 16,
             data_alignment_factor: 32,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&expected_instrs, BigEndian),
             phantom: PhantomData,
         };
@@ -3773,7 +4536,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 16,
             data_alignment_factor: 32,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&[], BigEndian),
             phantom: PhantomData,
         };
@@ -3837,7 +4600,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 1,
             data_alignment_factor: 2,
-            return_address_register: 3,
+            return_address_register: Register(3),
             initial_instructions: EndianBuf::new(&expected_instrs1, BigEndian),
             phantom: PhantomData,
         };
@@ -3852,7 +4615,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 3,
             data_alignment_factor: 2,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&expected_instrs2, BigEndian),
             phantom: PhantomData,
         };
@@ -3944,6 +4707,169 @@ mod tests {
         assert_eq!(entries.next(), Ok(None));
     }
 
+    #[test]
+    fn test_cfi_entries_iter_padding() {
+        let instrs: Vec<_> = (0..4).map(|_| constants::DW_CFA_nop.0).collect();
+
+        let mut cie = DebugFrameCie {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            version: 4,
+            augmentation: None,
+            address_size: 4,
+            segment_size: 0,
+            code_alignment_factor: 1,
+            data_alignment_factor: 2,
+            return_address_register: Register(3),
+            initial_instructions: EndianBuf::new(&instrs, BigEndian),
+            phantom: PhantomData,
+        };
+
+        let cie_location = Label::new();
+        let padding_location = Label::new();
+        let section = Section::with_endian(Endian::Big)
+            .mark(&cie_location)
+            .cie(Endian::Big, None, &mut cie)
+            .mark(&padding_location)
+            .append_repeated(0, 8);
+
+        let mut fde = DebugFrameFde {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            cie: cie.clone(),
+            initial_segment: 0,
+            initial_address: 0xfeedbeef,
+            address_range: 39,
+            augmentation: None,
+            instructions: EndianBuf::new(&instrs, BigEndian),
+        };
+
+        let section = section.fde(Endian::Big, &cie_location, &mut fde);
+        section.start().set_const(0);
+        let padding_offset = padding_location.value().unwrap() as usize;
+
+        let contents = section.get_contents().unwrap();
+        let debug_frame = DebugFrame::new(&contents, BigEndian);
+        let bases = Default::default();
+
+        let mut entries = debug_frame.entries(&bases);
+        assert_eq!(entries.next(), Ok(Some(CieOrFde::Cie(cie.clone()))));
+        match entries.next() {
+            Ok(Some(CieOrFde::Fde(partial))) => {
+                assert_eq!(partial.parse(|_| Ok(cie.clone())), Ok(fde.clone()));
+            }
+            otherwise => panic!("Unexpected result: {:#?}", otherwise),
+        }
+        assert_eq!(entries.next(), Ok(None));
+
+        let mut entries = debug_frame.entries(&bases);
+        assert_eq!(entries.offset(), 0);
+        assert_eq!(
+            entries.next_or_padding(),
+            Ok(Some(EntryOrPadding::Entry(CieOrFde::Cie(cie.clone()))))
+        );
+        assert_eq!(entries.offset(), padding_offset);
+        assert_eq!(
+            entries.next_or_padding(),
+            Ok(Some(EntryOrPadding::Padding {
+                offset: padding_offset,
+                length: 8,
+            }))
+        );
+        assert_eq!(entries.offset(), padding_offset + 8);
+        match entries.next_or_padding() {
+            Ok(Some(EntryOrPadding::Entry(CieOrFde::Fde(_)))) => {}
+            otherwise => panic!("Unexpected result: {:#?}", otherwise),
+        }
+        assert_eq!(entries.offset(), contents.len());
+        assert_eq!(entries.next_or_padding(), Ok(None));
+
+        // In `.eh_frame`, a zero length is a terminator rather than padding.
+        let eh_frame = EhFrame::new(&contents[padding_offset..], BigEndian);
+        let mut entries = eh_frame.entries(&bases);
+        assert_eq!(entries.next_or_padding(), Ok(None));
+    }
+
+    #[test]
+    fn test_cfi_entries_with_cache() {
+        let instrs: Vec<_> = (0..4).map(|_| constants::DW_CFA_nop.0).collect();
+
+        let mut cie = DebugFrameCie {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            version: 4,
+            augmentation: None,
+            address_size: 4,
+            segment_size: 0,
+            code_alignment_factor: 1,
+            data_alignment_factor: 2,
+            return_address_register: Register(3),
+            initial_instructions: EndianBuf::new(&instrs, BigEndian),
+            phantom: PhantomData,
+        };
+
+        let cie_location = Label::new();
+        let section = Section::with_endian(Endian::Big)
+            .mark(&cie_location)
+            .cie(Endian::Big, None, &mut cie);
+
+        let mut fde1 = DebugFrameFde {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            cie: cie.clone(),
+            initial_segment: 0,
+            initial_address: 0xfeedbeef,
+            address_range: 39,
+            augmentation: None,
+            instructions: EndianBuf::new(&instrs, BigEndian),
+        };
+
+        let mut fde2 = DebugFrameFde {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            cie: cie.clone(),
+            initial_segment: 0,
+            initial_address: 0xfeedface,
+            address_range: 9000,
+            augmentation: None,
+            instructions: EndianBuf::new(&instrs, BigEndian),
+        };
+
+        let section = section
+            .fde(Endian::Big, &cie_location, &mut fde1)
+            .fde(Endian::Big, &cie_location, &mut fde2);
+        section.start().set_const(0);
+
+        let cie_offset = DebugFrameOffset(cie_location.value().unwrap() as usize);
+        let contents = section.get_contents().unwrap();
+        let debug_frame = DebugFrame::new(&contents, BigEndian);
+        let bases = Default::default();
+
+        let mut cache = CieCache::new();
+        {
+            let mut entries = debug_frame.entries_with_cache(&bases, &mut cache);
+            assert_eq!(entries.next(), Ok(Some(CfiEntry::Cie(cie.clone()))));
+            assert_eq!(entries.next(), Ok(Some(CfiEntry::Fde(fde1.clone()))));
+            assert_eq!(entries.next(), Ok(Some(CfiEntry::Fde(fde2.clone()))));
+            assert_eq!(entries.next(), Ok(None));
+        }
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(cie_offset), Some(&cie));
+
+        // CIEs that are only referenced by FDEs are parsed on demand.
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get_or_parse(&debug_frame, &bases, cie_offset), Ok(&cie));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get_or_parse(&debug_frame, &bases, DebugFrameOffset(1)).is_err());
+        assert_eq!(cache.len(), 1);
+    }
+
     #[test]
     fn test_parse_cie_from_offset() {
         let filler = [1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -3959,7 +4885,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 4,
             data_alignment_factor: 8,
-            return_address_register: 12,
+            return_address_register: Register(12),
             initial_instructions: EndianBuf::new(&instrs, LittleEndian),
             phantom: PhantomData,
         };
@@ -4015,7 +4941,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Offset {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4034,7 +4960,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Restore {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4131,6 +5057,76 @@ mod tests {
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_cfi_instruction_mips_advance_loc8() {
+        let expected_rest = [1, 2, 3, 4];
+        let expected_delta = 1 << 20;
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_MIPS_advance_loc8.0)
+            .L64(expected_delta as u64)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianBuf::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input),
+            Ok(CallFrameInstruction::AdvanceLoc {
+                delta: expected_delta,
+            })
+        );
+        assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
+
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_MIPS_advance_loc8.0)
+            .L64(1 << 32);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianBuf::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input),
+            Err(Error::AddressOverflow)
+        );
+    }
+
+    #[test]
+    fn test_parse_cfi_instruction_gnu_args_size() {
+        let expected_rest = [1, 2, 3, 4];
+        let expected_size = 16;
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_GNU_args_size.0)
+            .uleb(expected_size)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianBuf::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input),
+            Ok(CallFrameInstruction::ArgsSize {
+                size: expected_size,
+            })
+        );
+        assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
+    }
+
+    #[test]
+    fn test_parse_cfi_instruction_gnu_negative_offset_extended() {
+        let expected_rest = [1, 2, 3, 4];
+        let expected_reg = 31;
+        let expected_offset = 8;
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_GNU_negative_offset_extended.0)
+            .uleb(expected_reg)
+            .uleb(expected_offset)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianBuf::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input),
+            Ok(CallFrameInstruction::OffsetExtendedSf {
+                register: Register(expected_reg as u16),
+                factored_offset: -(expected_offset as i64),
+            })
+        );
+        assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
+    }
+
     #[test]
     fn test_parse_cfi_instruction_offset_extended() {
         let expected_rest = [1, 2, 3, 4];
@@ -4146,7 +5142,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Offset {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4166,7 +5162,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Restore {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4185,7 +5181,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Undefined {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4204,7 +5200,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::SameValue {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4225,8 +5221,8 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Register {
-                dest_register: expected_dest_reg as u8,
-                src_register: expected_src_reg as u8,
+                dest_register: Register(expected_dest_reg as u16),
+                src_register: Register(expected_src_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4277,7 +5273,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::DefCfa {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 offset: expected_offset,
             })
         );
@@ -4297,7 +5293,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::DefCfaRegister {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
             })
         );
         assert_eq!(*input, EndianBuf::new(&expected_rest, LittleEndian));
@@ -4378,7 +5374,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::Expression {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 expression: Expression(EndianBuf::new(&expected_expr, LittleEndian)),
             })
         );
@@ -4400,7 +5396,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::OffsetExtendedSf {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4422,7 +5418,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::DefCfaSf {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4463,7 +5459,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::ValOffset {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4485,7 +5481,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::ValOffsetSf {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 factored_offset: expected_offset,
             })
         );
@@ -4518,7 +5514,7 @@ mod tests {
         assert_eq!(
             CallFrameInstruction::parse(input),
             Ok(CallFrameInstruction::ValExpression {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 expression: Expression(EndianBuf::new(&expected_expr, LittleEndian)),
             })
         );
@@ -4568,7 +5564,7 @@ mod tests {
         assert_eq!(
             iter.next(),
             Ok(Some(CallFrameInstruction::ValExpression {
-                register: expected_reg as u8,
+                register: Register(expected_reg as u16),
                 expression: Expression(EndianBuf::new(&expected_expr, BigEndian)),
             }))
         );
@@ -4592,7 +5588,10 @@ mod tests {
         let input = EndianBuf::new(&contents, BigEndian);
         let mut iter = CallFrameInstructionIter { input: input };
 
-        assert_eq!(iter.next(), Err(Error::UnexpectedEof));
+        assert_eq!(
+            iter.next(),
+            Err(Error::UnexpectedEof(EndianBuf::new(&contents[1..], BigEndian).offset_id()))
+        );
         assert_eq!(iter.next(), Ok(None));
     }
 
@@ -4631,7 +5630,7 @@ mod tests {
             offset: 0,
             format: Format::Dwarf64,
             length: 0,
-            return_address_register: 0,
+            return_address_register: Register(0),
             version: 4,
             address_size: mem::size_of::<usize>() as u8,
             initial_instructions: EndianBuf::new(&[], LittleEndian),
@@ -4679,20 +5678,59 @@ mod tests {
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
+    #[test]
+    fn test_eval_advance_loc_overflow() {
+        let cie: DebugFrameCie<_, _> = make_test_cie();
+        let mut ctx = UnwindContext::new();
+        ctx.row_mut().start_address = u64::MAX;
+        let expected = ctx.clone();
+        let instructions = [
+            (
+                Err(Error::AddressOverflow),
+                CallFrameInstruction::AdvanceLoc { delta: 1 },
+            ),
+        ];
+        assert_eval(ctx, expected, cie, None, instructions);
+    }
+
+    #[test]
+    fn test_eval_offset_overflow() {
+        let cie: DebugFrameCie<_, _> = make_test_cie();
+        let ctx = UnwindContext::new();
+        let expected = ctx.clone();
+        let instructions = [
+            (
+                Err(Error::OffsetOverflow),
+                CallFrameInstruction::DefCfaSf {
+                    register: Register(42),
+                    factored_offset: i64::MAX,
+                },
+            ),
+            (
+                Err(Error::OffsetOverflow),
+                CallFrameInstruction::Offset {
+                    register: Register(2),
+                    factored_offset: u64::MAX >> 1,
+                },
+            ),
+        ];
+        assert_eval(ctx, expected, cie, None, instructions);
+    }
+
     #[test]
     fn test_eval_def_cfa() {
         let cie: DebugFrameCie<_, _> = make_test_cie();
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected.set_cfa(CfaRule::RegisterAndOffset {
-            register: 42,
+            register: Register(42),
             offset: 36,
         });
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::DefCfa {
-                    register: 42,
+                    register: Register(42),
                     offset: 36,
                 },
             ),
@@ -4706,14 +5744,14 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected.set_cfa(CfaRule::RegisterAndOffset {
-            register: 42,
+            register: Register(42),
             offset: 36 * cie.data_alignment_factor as i64,
         });
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::DefCfaSf {
-                    register: 42,
+                    register: Register(42),
                     factored_offset: 36,
                 },
             ),
@@ -4726,18 +5764,18 @@ mod tests {
         let cie: DebugFrameCie<_, _> = make_test_cie();
         let mut ctx = UnwindContext::new();
         ctx.set_cfa(CfaRule::RegisterAndOffset {
-            register: 3,
+            register: Register(3),
             offset: 8,
         });
         let mut expected = ctx.clone();
         expected.set_cfa(CfaRule::RegisterAndOffset {
-            register: 42,
+            register: Register(42),
             offset: 8,
         });
         let instructions = [
             (
                 Ok(false),
-                CallFrameInstruction::DefCfaRegister { register: 42 },
+                CallFrameInstruction::DefCfaRegister { register: Register(42) },
             ),
         ];
         assert_eval(ctx, expected, cie, None, instructions);
@@ -4754,7 +5792,7 @@ mod tests {
         let instructions = [
             (
                 Err(Error::CfiInstructionInInvalidContext),
-                CallFrameInstruction::DefCfaRegister { register: 42 },
+                CallFrameInstruction::DefCfaRegister { register: Register(42) },
             ),
         ];
         assert_eval(ctx, expected, cie, None, instructions);
@@ -4765,12 +5803,12 @@ mod tests {
         let cie: DebugFrameCie<_, _> = make_test_cie();
         let mut ctx = UnwindContext::new();
         ctx.set_cfa(CfaRule::RegisterAndOffset {
-            register: 3,
+            register: Register(3),
             offset: 8,
         });
         let mut expected = ctx.clone();
         expected.set_cfa(CfaRule::RegisterAndOffset {
-            register: 3,
+            register: Register(3),
             offset: 42,
         });
         let instructions = [
@@ -4822,9 +5860,9 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(5, RegisterRule::Undefined)
+            .set_register_rule(Register(5), RegisterRule::Undefined)
             .unwrap();
-        let instructions = [(Ok(false), CallFrameInstruction::Undefined { register: 5 })];
+        let instructions = [(Ok(false), CallFrameInstruction::Undefined { register: Register(5) })];
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
@@ -4834,9 +5872,9 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(0, RegisterRule::SameValue)
+            .set_register_rule(Register(0), RegisterRule::SameValue)
             .unwrap();
-        let instructions = [(Ok(false), CallFrameInstruction::SameValue { register: 0 })];
+        let instructions = [(Ok(false), CallFrameInstruction::SameValue { register: Register(0) })];
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
@@ -4846,13 +5884,13 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(2, RegisterRule::Offset(3 * cie.data_alignment_factor))
+            .set_register_rule(Register(2), RegisterRule::Offset(3 * cie.data_alignment_factor))
             .unwrap();
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::Offset {
-                    register: 2,
+                    register: Register(2),
                     factored_offset: 3,
                 },
             ),
@@ -4866,13 +5904,13 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(4, RegisterRule::Offset(-3 * cie.data_alignment_factor))
+            .set_register_rule(Register(4), RegisterRule::Offset(-3 * cie.data_alignment_factor))
             .unwrap();
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::OffsetExtendedSf {
-                    register: 4,
+                    register: Register(4),
                     factored_offset: -3,
                 },
             ),
@@ -4886,13 +5924,13 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(5, RegisterRule::ValOffset(7 * cie.data_alignment_factor))
+            .set_register_rule(Register(5), RegisterRule::ValOffset(7 * cie.data_alignment_factor))
             .unwrap();
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::ValOffset {
-                    register: 5,
+                    register: Register(5),
                     factored_offset: 7,
                 },
             ),
@@ -4906,13 +5944,13 @@ mod tests {
         let ctx = UnwindContext::new();
         let mut expected = ctx.clone();
         expected
-            .set_register_rule(5, RegisterRule::ValOffset(-7 * cie.data_alignment_factor))
+            .set_register_rule(Register(5), RegisterRule::ValOffset(-7 * cie.data_alignment_factor))
             .unwrap();
         let instructions = [
             (
                 Ok(false),
                 CallFrameInstruction::ValOffsetSf {
-                    register: 5,
+                    register: Register(5),
                     factored_offset: -7,
                 },
             ),
@@ -4928,7 +5966,7 @@ mod tests {
         let mut expected = ctx.clone();
         expected
             .set_register_rule(
-                9,
+                Register(9),
                 RegisterRule::Expression(Expression(EndianBuf::new(&expr, LittleEndian))),
             )
             .unwrap();
@@ -4936,7 +5974,7 @@ mod tests {
             (
                 Ok(false),
                 CallFrameInstruction::Expression {
-                    register: 9,
+                    register: Register(9),
                     expression: Expression(EndianBuf::new(&expr, LittleEndian)),
                 },
             ),
@@ -4952,7 +5990,7 @@ mod tests {
         let mut expected = ctx.clone();
         expected
             .set_register_rule(
-                9,
+                Register(9),
                 RegisterRule::ValExpression(Expression(EndianBuf::new(&expr, LittleEndian))),
             )
             .unwrap();
@@ -4960,7 +5998,7 @@ mod tests {
             (
                 Ok(false),
                 CallFrameInstruction::ValExpression {
-                    register: 9,
+                    register: Register(9),
                     expression: Expression(EndianBuf::new(&expr, LittleEndian)),
                 },
             ),
@@ -4984,12 +6022,12 @@ mod tests {
         };
 
         let mut ctx = UnwindContext::new();
-        ctx.set_register_rule(0, RegisterRule::Offset(1)).unwrap();
+        ctx.set_register_rule(Register(0), RegisterRule::Offset(1)).unwrap();
         ctx.save_initial_rules();
         let expected = ctx.clone();
-        ctx.set_register_rule(0, RegisterRule::Offset(2)).unwrap();
+        ctx.set_register_rule(Register(0), RegisterRule::Offset(2)).unwrap();
 
-        let instructions = [(Ok(false), CallFrameInstruction::Restore { register: 0 })];
+        let instructions = [(Ok(false), CallFrameInstruction::Restore { register: Register(0) })];
         assert_eval(ctx, expected, cie, Some(fde), instructions);
     }
 
@@ -5001,7 +6039,7 @@ mod tests {
         let instructions = [
             (
                 Err(Error::CfiInstructionInInvalidContext),
-                CallFrameInstruction::Restore { register: 0 },
+                CallFrameInstruction::Restore { register: Register(0) },
             ),
         ];
         assert_eval(ctx, expected, cie, None, instructions);
@@ -5023,11 +6061,11 @@ mod tests {
 
         let mut ctx = UnwindContext::new();
         ctx.set_start_address(1);
-        ctx.set_register_rule(0, RegisterRule::SameValue).unwrap();
+        ctx.set_register_rule(Register(0), RegisterRule::SameValue).unwrap();
         let mut expected = ctx.clone();
         ctx.push_row().unwrap();
         ctx.set_start_address(2);
-        ctx.set_register_rule(0, RegisterRule::Offset(16)).unwrap();
+        ctx.set_register_rule(Register(0), RegisterRule::Offset(16)).unwrap();
 
         // Restore state should preserve current location.
         expected.set_start_address(2);
@@ -5045,6 +6083,45 @@ mod tests {
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
+    #[test]
+    fn test_unwind_context_store_on_stack_limits() {
+        let mut ctx = UnwindContext::<DebugFrame<EndianBuf<LittleEndian>>, _>::new();
+        for register in 0..32 {
+            ctx.set_register_rule(Register(register), RegisterRule::SameValue).unwrap();
+        }
+        assert_eq!(
+            ctx.set_register_rule(Register(32), RegisterRule::SameValue),
+            Err(Error::TooManyRegisterRules)
+        );
+
+        for _ in 1..4 {
+            ctx.push_row().unwrap();
+        }
+        assert_eq!(ctx.push_row(), Err(Error::CfiStackFull));
+    }
+
+    #[test]
+    fn test_unwind_context_store_on_heap() {
+        let mut ctx =
+            UnwindContext::<DebugFrame<EndianBuf<LittleEndian>>, _, StoreOnHeap>::new_in();
+        for register in 0..100 {
+            ctx.set_register_rule(Register(register), RegisterRule::Offset(register.into()))
+                .unwrap();
+        }
+        for _ in 0..10 {
+            ctx.push_row().unwrap();
+        }
+        ctx.set_register_rule(Register(0), RegisterRule::Undefined).unwrap();
+        assert_eq!(ctx.row().register(Register(0)), RegisterRule::Undefined);
+        assert_eq!(ctx.row().register(Register(99)), RegisterRule::Offset(99));
+        assert_eq!(ctx.row().registers().count(), 99);
+        ctx.pop_row();
+        assert_eq!(ctx.row().register(Register(0)), RegisterRule::Offset(0));
+
+        ctx.reset();
+        assert!(ctx.row().is_default());
+    }
+
     #[test]
     fn test_eval_nop() {
         let cie: DebugFrameCie<_, _> = make_test_cie();
@@ -5054,6 +6131,71 @@ mod tests {
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
+    #[test]
+    fn test_eval_args_size() {
+        let cie: DebugFrameCie<_, _> = make_test_cie();
+        let ctx = UnwindContext::new();
+        let expected = ctx.clone();
+        let instructions = [(Ok(false), CallFrameInstruction::ArgsSize { size: 8 })];
+        assert_eval(ctx, expected, cie, None, instructions);
+    }
+
+    #[test]
+    fn test_unwind_table_row_modify() {
+        let mut row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(
+            0x1000,
+            0x1010,
+            CfaRule::RegisterAndOffset {
+                register: X86_64::RSP,
+                offset: 8,
+            },
+        );
+        row.set_register(X86_64::RA, RegisterRule::Offset(-8)).unwrap();
+
+        // Merge in frame pointer rules for registers that CFI doesn't describe.
+        let cfa = CfaRule::RegisterAndOffset {
+            register: X86_64::RBP,
+            offset: 16,
+        };
+        row.set_cfa(cfa.clone());
+        for (register, rule) in vec![
+            (X86_64::RBP, RegisterRule::Offset(-16)),
+            (X86_64::RA, RegisterRule::Offset(-4)),
+        ] {
+            if row.register(register) == RegisterRule::Undefined {
+                row.set_register(register, rule).unwrap();
+            }
+        }
+
+        assert_eq!(*row.cfa(), cfa);
+        let mut registers: Vec<_> = row.registers().cloned().collect();
+        registers.sort_by_key(|&(register, _)| register);
+        assert_eq!(
+            registers,
+            vec![
+                (X86_64::RBP, RegisterRule::Offset(-16)),
+                (X86_64::RA, RegisterRule::Offset(-8)),
+            ]
+        );
+
+        row.set_register(X86_64::RBP, RegisterRule::Undefined).unwrap();
+        assert_eq!(row.registers().count(), 1);
+        assert_eq!(row.register(X86_64::RBP), RegisterRule::Undefined);
+    }
+
+    #[test]
+    fn test_unwind_table_row_return_address() {
+        let mut cie: DebugFrameCie<_, _> = make_test_cie();
+        let mut row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0, 1, Default::default());
+        row.set_register(Register(31), RegisterRule::Constant(0)).unwrap();
+
+        cie.return_address_register = Register(31);
+        assert_eq!(row.return_address(&cie), RegisterRule::Constant(0));
+
+        cie.return_address_register = Register(256);
+        assert_eq!(row.return_address(&cie), RegisterRule::Undefined);
+    }
+
     #[test]
     fn test_unwind_table_next_row() {
         let initial_instructions = Section::with_endian(Endian::Little)
@@ -5080,7 +6222,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 1,
             data_alignment_factor: 1,
-            return_address_register: 3,
+            return_address_register: Register(3),
             initial_instructions: EndianBuf::new(&initial_instructions, LittleEndian),
             phantom: PhantomData,
         };
@@ -5128,7 +6270,7 @@ mod tests {
 
         assert!(ctx.0.is_initialized);
         let expected_initial_rules: RegisterRuleMap<_> =
-            [(0, RegisterRule::Offset(8)), (3, RegisterRule::Offset(4))]
+            [(Register(0), RegisterRule::Offset(8)), (Register(3), RegisterRule::Offset(4))]
                 .into_iter()
                 .collect();
         assert_eq!(ctx.0.initial_rules, expected_initial_rules);
@@ -5141,10 +6283,10 @@ mod tests {
                 start_address: 0,
                 end_address: 1,
                 cfa: CfaRule::RegisterAndOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: -12,
                 },
-                registers: [(0, RegisterRule::Offset(8)), (3, RegisterRule::Offset(4))]
+                registers: [(Register(0), RegisterRule::Offset(8)), (Register(3), RegisterRule::Offset(4))]
                     .into_iter()
                     .collect(),
             };
@@ -5157,10 +6299,10 @@ mod tests {
                 start_address: 1,
                 end_address: 33,
                 cfa: CfaRule::RegisterAndOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: -12,
                 },
-                registers: [(0, RegisterRule::Offset(-16)), (3, RegisterRule::Offset(4))]
+                registers: [(Register(0), RegisterRule::Offset(-16)), (Register(3), RegisterRule::Offset(4))]
                     .into_iter()
                     .collect(),
             };
@@ -5173,12 +6315,12 @@ mod tests {
                 start_address: 33,
                 end_address: 97,
                 cfa: CfaRule::RegisterAndOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: -12,
                 },
                 registers: [
-                    (0, RegisterRule::Offset(-16)),
-                    (3, RegisterRule::Offset(-4)),
+                    (Register(0), RegisterRule::Offset(-16)),
+                    (Register(3), RegisterRule::Offset(-4)),
                 ].into_iter()
                     .collect(),
             };
@@ -5191,13 +6333,13 @@ mod tests {
                 start_address: 97,
                 end_address: 100,
                 cfa: CfaRule::RegisterAndOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: -12,
                 },
                 registers: [
-                    (0, RegisterRule::Offset(-16)),
-                    (3, RegisterRule::Offset(-4)),
-                    (5, RegisterRule::Offset(4)),
+                    (Register(0), RegisterRule::Offset(-16)),
+                    (Register(3), RegisterRule::Offset(-4)),
+                    (Register(5), RegisterRule::Offset(4)),
                 ].into_iter()
                     .collect(),
             };
@@ -5242,7 +6384,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 1,
             data_alignment_factor: 1,
-            return_address_register: 3,
+            return_address_register: Register(3),
             initial_instructions: EndianBuf::new(&instrs1, BigEndian),
             phantom: PhantomData,
         };
@@ -5257,7 +6399,7 @@ mod tests {
             segment_size: 0,
             code_alignment_factor: 1,
             data_alignment_factor: 1,
-            return_address_register: 1,
+            return_address_register: Register(1),
             initial_instructions: EndianBuf::new(&instrs2, BigEndian),
             phantom: PhantomData,
         };
@@ -5319,10 +6461,10 @@ mod tests {
                 start_address: fde1.initial_address() + 100,
                 end_address: fde1.initial_address() + fde1.len(),
                 cfa: CfaRule::RegisterAndOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: -12,
                 },
-                registers: [(0, RegisterRule::Offset(-16))].into_iter().collect(),
+                registers: [(Register(0), RegisterRule::Offset(-16))].into_iter().collect(),
             }
         );
     }
@@ -5972,15 +7114,23 @@ mod tests {
         assert_eq!(iter.next(), Err(Error::FuncRelativePointerInBadContext));
     }
 
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BaseAddresses>();
+        assert_send_sync::<EhFrame<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<DebugFrame<EndianBuf<LittleEndian>>>();
+    }
+
     #[test]
     fn register_rule_map_eq() {
         // Different order, but still equal.
         let map1: RegisterRuleMap<EndianBuf<LittleEndian>> =
-            [(0, RegisterRule::SameValue), (3, RegisterRule::Offset(1))]
+            [(Register(0), RegisterRule::SameValue), (Register(3), RegisterRule::Offset(1))]
                 .iter()
                 .collect();
         let map2: RegisterRuleMap<EndianBuf<LittleEndian>> =
-            [(3, RegisterRule::Offset(1)), (0, RegisterRule::SameValue)]
+            [(Register(3), RegisterRule::Offset(1)), (Register(0), RegisterRule::SameValue)]
                 .iter()
                 .collect();
         assert_eq!(map1, map2);
@@ -5988,11 +7138,11 @@ mod tests {
 
         // Not equal.
         let map3: RegisterRuleMap<EndianBuf<LittleEndian>> =
-            [(0, RegisterRule::SameValue), (2, RegisterRule::Offset(1))]
+            [(Register(0), RegisterRule::SameValue), (Register(2), RegisterRule::Offset(1))]
                 .iter()
                 .collect();
         let map4: RegisterRuleMap<EndianBuf<LittleEndian>> =
-            [(3, RegisterRule::Offset(1)), (0, RegisterRule::SameValue)]
+            [(Register(3), RegisterRule::Offset(1)), (Register(0), RegisterRule::SameValue)]
                 .iter()
                 .collect();
         assert!(map3 != map4);
@@ -6000,8 +7150,8 @@ mod tests {
 
         // One has undefined explicitly set, other implicitly has undefined.
         let mut map5 = RegisterRuleMap::<EndianBuf<LittleEndian>>::default();
-        map5.set(0, RegisterRule::SameValue).unwrap();
-        map5.set(0, RegisterRule::Undefined).unwrap();
+        map5.set(Register(0), RegisterRule::SameValue).unwrap();
+        map5.set(Register(0), RegisterRule::Undefined).unwrap();
         let map6 = RegisterRuleMap::<EndianBuf<LittleEndian>>::default();
         assert_eq!(map5, map6);
         assert_eq!(map6, map5);
@@ -6011,9 +7161,9 @@ mod tests {
     fn iter_register_rules() {
         let mut row = UnwindTableRow::<EndianBuf<LittleEndian>>::default();
         row.registers = [
-            (0, RegisterRule::SameValue),
-            (1, RegisterRule::Offset(1)),
-            (2, RegisterRule::ValOffset(2)),
+            (Register(0), RegisterRule::SameValue),
+            (Register(1), RegisterRule::Offset(1)),
+            (Register(2), RegisterRule::ValOffset(2)),
         ].iter()
             .collect();
 
@@ -6023,22 +7173,22 @@ mod tests {
 
         for &(register, ref rule) in row.registers() {
             match register {
-                0 => {
+                Register(0) => {
                     assert_eq!(found0, false);
                     found0 = true;
                     assert_eq!(*rule, RegisterRule::SameValue);
                 }
-                1 => {
+                Register(1) => {
                     assert_eq!(found1, false);
                     found1 = true;
                     assert_eq!(*rule, RegisterRule::Offset(1));
                 }
-                2 => {
+                Register(2) => {
                     assert_eq!(found2, false);
                     found2 = true;
                     assert_eq!(*rule, RegisterRule::ValOffset(2));
                 }
-                x => panic!("Unexpected register rule: ({}, {:?})", x, rule),
+                x => panic!("Unexpected register rule: ({:?}, {:?})", x, rule),
             }
         }
 
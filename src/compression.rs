@@ -0,0 +1,221 @@
+//! Decompression of compressed DWARF sections.
+//!
+//! Sections may be compressed in two ways:
+//!
+//! * ELF sections with the `SHF_COMPRESSED` flag begin with an ELF compression
+//! header (`Elf32_Chdr` or `Elf64_Chdr`), followed by zlib or zstd compressed
+//! data.
+//!
+//! * The older GNU `.zdebug_*` sections begin with the magic bytes `ZLIB`
+//! and a big endian 64 bit uncompressed size, followed by zlib compressed
+//! data.
+//!
+//! ```
+//! use gimli::compression::decompress_section;
+//! use gimli::{DebugStr, DebugStrOffset, LittleEndian};
+//!
+//! let data = b"hello\0";
+//! let section = decompress_section(".debug_str", 0, true, data, LittleEndian).unwrap();
+//! let debug_str = DebugStr::from(section);
+//! assert_eq!(debug_str.get_str(DebugStrOffset(0)).unwrap().bytes(), b"hello");
+//! ```
+
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+use std::sync::Arc;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+use endian_reader::EndianArcSlice;
+use endianity::{BigEndian, EndianBuf, Endianity};
+use parser::{Error, Result};
+use reader::Reader;
+use vec::Vec;
+
+/// The ELF section flag for sections containing compressed data.
+pub const SHF_COMPRESSED: u64 = 0x800;
+
+/// The ELF compression type for zlib compressed data.
+pub const ELFCOMPRESS_ZLIB: u32 = 1;
+
+/// The ELF compression type for zstd compressed data.
+pub const ELFCOMPRESS_ZSTD: u32 = 2;
+
+/// Return the contents of a section as an owned reader, decompressing them
+/// if necessary.
+///
+/// `name` is the name of the section, and `flags` are its ELF section header
+/// flags (`sh_flags`), or 0 for other object file formats. `is_64` is true if
+/// the object file is a 64 bit ELF file, which determines the layout of the
+/// compression header of `SHF_COMPRESSED` sections. `endian` is the endianity
+/// of both the object file and the returned reader.
+///
+/// Sections that are not compressed are copied into the returned reader.
+pub fn decompress_section<Endian>(
+    name: &str,
+    flags: u64,
+    is_64: bool,
+    data: &[u8],
+    endian: Endian,
+) -> Result<EndianArcSlice<Endian>>
+where
+    Endian: Endianity,
+{
+    let bytes = if flags & SHF_COMPRESSED != 0 {
+        decompress_elf(data, is_64, endian)?
+    } else if name.starts_with(".zdebug_") && data.starts_with(b"ZLIB") {
+        decompress_zdebug(data)?
+    } else {
+        data.to_vec()
+    };
+    Ok(EndianArcSlice::new(Arc::from(bytes), endian))
+}
+
+fn decompress_elf<Endian>(data: &[u8], is_64: bool, endian: Endian) -> Result<Vec<u8>>
+where
+    Endian: Endianity,
+{
+    let mut input = EndianBuf::new(data, endian);
+    let ch_type = input.read_u32()?;
+    let size = if is_64 {
+        let _ch_reserved = input.read_u32()?;
+        let size = input.read_u64()?;
+        let _ch_addralign = input.read_u64()?;
+        size
+    } else {
+        let size = input.read_u32()?;
+        let _ch_addralign = input.read_u32()?;
+        size as u64
+    };
+    match ch_type {
+        ELFCOMPRESS_ZLIB => decompress(ZlibDecoder::new(input.buf()), size),
+        ELFCOMPRESS_ZSTD => {
+            let decoder = ZstdDecoder::new(input.buf()).map_err(|_| Error::InvalidCompressedData)?;
+            decompress(decoder, size)
+        }
+        otherwise => Err(Error::UnsupportedCompressionType(otherwise)),
+    }
+}
+
+fn decompress_zdebug(data: &[u8]) -> Result<Vec<u8>> {
+    let mut input = EndianBuf::new(&data[4..], BigEndian);
+    let size = input.read_u64()?;
+    decompress(ZlibDecoder::new(input.buf()), size)
+}
+
+/// Decompress exactly `size` bytes from the given decoder.
+fn decompress<R: Read>(decoder: R, size: u64) -> Result<Vec<u8>> {
+    // Don't trust the size for the initial allocation, and don't read more
+    // than one byte past it, so that a bad header can't exhaust memory.
+    let mut bytes = Vec::with_capacity(size.min(1 << 20) as usize);
+    decoder
+        .take(size.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|_| Error::InvalidCompressedData)?;
+    if bytes.len() as u64 != size {
+        return Err(Error::InvalidCompressedData);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use super::*;
+    use self::test_assembler::{Endian, Section};
+    use endianity::LittleEndian;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const DATA: &[u8] = b"The quick brown fox jumps over the lazy dog.";
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let section = decompress_section(".debug_info", 0, true, DATA, LittleEndian).unwrap();
+        assert_eq!(section.bytes(), DATA);
+    }
+
+    #[test]
+    fn test_elf64_zlib() {
+        let buf = Section::with_endian(Endian::Little)
+            .L32(ELFCOMPRESS_ZLIB)
+            .L32(0)
+            .L64(DATA.len() as u64)
+            .L64(1)
+            .append_bytes(&zlib(DATA))
+            .get_contents()
+            .unwrap();
+        let section =
+            decompress_section(".debug_info", SHF_COMPRESSED, true, &buf, LittleEndian).unwrap();
+        assert_eq!(section.bytes(), DATA);
+
+        // A wrong uncompressed size is an error.
+        let buf = Section::with_endian(Endian::Little)
+            .L32(ELFCOMPRESS_ZLIB)
+            .L32(0)
+            .L64(DATA.len() as u64 - 1)
+            .L64(1)
+            .append_bytes(&zlib(DATA))
+            .get_contents()
+            .unwrap();
+        assert_eq!(
+            decompress_section(".debug_info", SHF_COMPRESSED, true, &buf, LittleEndian),
+            Err(Error::InvalidCompressedData)
+        );
+    }
+
+    #[test]
+    fn test_elf32_zstd() {
+        let buf = Section::with_endian(Endian::Big)
+            .B32(ELFCOMPRESS_ZSTD)
+            .B32(DATA.len() as u32)
+            .B32(1)
+            .append_bytes(&::zstd::encode_all(DATA, 0).unwrap())
+            .get_contents()
+            .unwrap();
+        let section =
+            decompress_section(".debug_str", SHF_COMPRESSED, false, &buf, BigEndian).unwrap();
+        assert_eq!(section.bytes(), DATA);
+    }
+
+    #[test]
+    fn test_elf_bad_type() {
+        let buf = Section::with_endian(Endian::Little)
+            .L32(3)
+            .L32(DATA.len() as u32)
+            .L32(1)
+            .get_contents()
+            .unwrap();
+        assert_eq!(
+            decompress_section(".debug_info", SHF_COMPRESSED, false, &buf, LittleEndian),
+            Err(Error::UnsupportedCompressionType(3))
+        );
+    }
+
+    #[test]
+    fn test_zdebug() {
+        let buf = Section::with_endian(Endian::Big)
+            .append_bytes(b"ZLIB")
+            .B64(DATA.len() as u64)
+            .append_bytes(&zlib(DATA))
+            .get_contents()
+            .unwrap();
+        let section = decompress_section(".zdebug_line", 0, true, &buf, LittleEndian).unwrap();
+        assert_eq!(section.bytes(), DATA);
+
+        // Corrupt data is an error.
+        let mut buf = buf;
+        buf.truncate(20);
+        assert_eq!(
+            decompress_section(".zdebug_line", 0, true, &buf, LittleEndian),
+            Err(Error::InvalidCompressedData)
+        );
+    }
+}
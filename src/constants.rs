@@ -37,7 +37,7 @@ use std::fmt;
 //
 // into this:
 //
-//     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 //     struct DwFoo(pub u32);
 //
 //     pub const DW_FOO_bar: DwFoo = DwFoo(0);
@@ -56,7 +56,8 @@ use std::fmt;
 //     }
 macro_rules! dw {
     ($struct_name:ident($struct_type:ty) { $($name:ident = $val:expr),+ }) => {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize))]
         pub struct $struct_name(pub $struct_type);
 
         $(
@@ -65,6 +66,9 @@ macro_rules! dw {
 
         impl $struct_name {
             pub fn static_string(&self) -> Option<&'static str> {
+                // Later names that share a value with an earlier name, such as
+                // the `lo_user` bounds, are never returned.
+                #[allow(unreachable_patterns)]
                 Some(match *self {
                     $(
                         $name => stringify!($name),
@@ -431,6 +435,10 @@ dw!(DwAt(u64) {
     DW_AT_defaulted = 0x8b,
     DW_AT_loclists_base = 0x8c,
 
+// DWARF 6 draft.
+    DW_AT_language_name = 0x90,
+    DW_AT_language_version = 0x91,
+
     DW_AT_lo_user = 0x2000,
     DW_AT_hi_user = 0x3fff,
 
@@ -848,6 +856,151 @@ impl DwLang {
             _ => None,
         }
     }
+
+    /// Get the equivalent `DW_AT_language_name` and `DW_AT_language_version`
+    /// values for this language.
+    ///
+    /// A version of 0 means that the language version is unspecified.
+    pub fn language_name(&self) -> Option<(DwLname, u32)> {
+        Some(match *self {
+            DW_LANG_C89 => (DW_LNAME_C, 198912),
+            DW_LANG_C => (DW_LNAME_C, 0),
+            DW_LANG_Ada83 => (DW_LNAME_Ada, 1983),
+            DW_LANG_C_plus_plus => (DW_LNAME_C_plus_plus, 199711),
+            DW_LANG_Cobol74 => (DW_LNAME_Cobol, 1974),
+            DW_LANG_Cobol85 => (DW_LNAME_Cobol, 1985),
+            DW_LANG_Fortran77 => (DW_LNAME_Fortran, 1977),
+            DW_LANG_Fortran90 => (DW_LNAME_Fortran, 1990),
+            DW_LANG_Pascal83 => (DW_LNAME_Pascal, 1983),
+            DW_LANG_Modula2 => (DW_LNAME_Modula2, 0),
+            DW_LANG_Java => (DW_LNAME_Java, 0),
+            DW_LANG_C99 => (DW_LNAME_C, 199901),
+            DW_LANG_Ada95 => (DW_LNAME_Ada, 1995),
+            DW_LANG_Fortran95 => (DW_LNAME_Fortran, 1995),
+            DW_LANG_PLI => (DW_LNAME_PLI, 0),
+            DW_LANG_ObjC => (DW_LNAME_ObjC, 0),
+            DW_LANG_ObjC_plus_plus => (DW_LNAME_ObjC_plus_plus, 0),
+            DW_LANG_UPC => (DW_LNAME_UPC, 0),
+            DW_LANG_D => (DW_LNAME_D, 0),
+            DW_LANG_Python => (DW_LNAME_Python, 0),
+            DW_LANG_OpenCL => (DW_LNAME_OpenCL_C, 0),
+            DW_LANG_Go => (DW_LNAME_Go, 0),
+            DW_LANG_Modula3 => (DW_LNAME_Modula3, 0),
+            DW_LANG_Haskell => (DW_LNAME_Haskell, 0),
+            DW_LANG_C_plus_plus_03 => (DW_LNAME_C_plus_plus, 200310),
+            DW_LANG_C_plus_plus_11 => (DW_LNAME_C_plus_plus, 201103),
+            DW_LANG_OCaml => (DW_LNAME_OCaml, 0),
+            DW_LANG_Rust => (DW_LNAME_Rust, 0),
+            DW_LANG_C11 => (DW_LNAME_C, 201112),
+            DW_LANG_Swift => (DW_LNAME_Swift, 0),
+            DW_LANG_Julia => (DW_LNAME_Julia, 0),
+            DW_LANG_Dylan => (DW_LNAME_Dylan, 0),
+            DW_LANG_C_plus_plus_14 => (DW_LNAME_C_plus_plus, 201402),
+            DW_LANG_Fortran03 => (DW_LNAME_Fortran, 2003),
+            DW_LANG_Fortran08 => (DW_LNAME_Fortran, 2008),
+            DW_LANG_RenderScript => (DW_LNAME_RenderScript, 0),
+            DW_LANG_BLISS => (DW_LNAME_BLISS, 0),
+            DW_LANG_Mips_Assembler => (DW_LNAME_Assembly, 0),
+            _ => return None,
+        })
+    }
+}
+
+// The encodings of the constants used in the `DW_AT_language_name` attribute.
+// These are defined by the DWARF 6 draft, and are used together with the
+// `DW_AT_language_version` attribute.
+dw!(DwLname(u16) {
+    DW_LNAME_Ada = 0x0001,
+    DW_LNAME_BLISS = 0x0002,
+    DW_LNAME_C = 0x0003,
+    DW_LNAME_C_plus_plus = 0x0004,
+    DW_LNAME_Cobol = 0x0005,
+    DW_LNAME_Crystal = 0x0006,
+    DW_LNAME_D = 0x0007,
+    DW_LNAME_Dylan = 0x0008,
+    DW_LNAME_Fortran = 0x0009,
+    DW_LNAME_Go = 0x000a,
+    DW_LNAME_Haskell = 0x000b,
+    DW_LNAME_Java = 0x000c,
+    DW_LNAME_Julia = 0x000d,
+    DW_LNAME_Kotlin = 0x000e,
+    DW_LNAME_Modula2 = 0x000f,
+    DW_LNAME_Modula3 = 0x0010,
+    DW_LNAME_ObjC = 0x0011,
+    DW_LNAME_ObjC_plus_plus = 0x0012,
+    DW_LNAME_OCaml = 0x0013,
+    DW_LNAME_OpenCL_C = 0x0014,
+    DW_LNAME_Pascal = 0x0015,
+    DW_LNAME_PLI = 0x0016,
+    DW_LNAME_Python = 0x0017,
+    DW_LNAME_RenderScript = 0x0018,
+    DW_LNAME_Rust = 0x0019,
+    DW_LNAME_Swift = 0x001a,
+    DW_LNAME_UPC = 0x001b,
+    DW_LNAME_Zig = 0x001c,
+    DW_LNAME_Assembly = 0x001d,
+    DW_LNAME_C_sharp = 0x001e,
+    DW_LNAME_Mojo = 0x001f,
+    DW_LNAME_GLSL = 0x0020,
+    DW_LNAME_GLSL_ES = 0x0021,
+    DW_LNAME_HLSL = 0x0022,
+    DW_LNAME_OpenCL_CPP = 0x0023,
+    DW_LNAME_CPP_for_OpenCL = 0x0024,
+    DW_LNAME_SYCL = 0x0025,
+    DW_LNAME_Ruby = 0x0026,
+    DW_LNAME_Move = 0x0027,
+    DW_LNAME_Hylo = 0x0028,
+
+    DW_LNAME_lo_user = 0x8000,
+    DW_LNAME_hi_user = 0xffff,
+});
+
+impl DwLname {
+    /// Get the default DW_AT_lower_bound for this language.
+    pub fn default_lower_bound(&self) -> Option<usize> {
+        match *self {
+            DW_LNAME_BLISS |
+            DW_LNAME_C |
+            DW_LNAME_C_plus_plus |
+            DW_LNAME_Crystal |
+            DW_LNAME_D |
+            DW_LNAME_Dylan |
+            DW_LNAME_Go |
+            DW_LNAME_Haskell |
+            DW_LNAME_Java |
+            DW_LNAME_Kotlin |
+            DW_LNAME_ObjC |
+            DW_LNAME_ObjC_plus_plus |
+            DW_LNAME_OCaml |
+            DW_LNAME_OpenCL_C |
+            DW_LNAME_Python |
+            DW_LNAME_RenderScript |
+            DW_LNAME_Rust |
+            DW_LNAME_Swift |
+            DW_LNAME_UPC |
+            DW_LNAME_Zig |
+            DW_LNAME_C_sharp |
+            DW_LNAME_Mojo |
+            DW_LNAME_GLSL |
+            DW_LNAME_GLSL_ES |
+            DW_LNAME_HLSL |
+            DW_LNAME_OpenCL_CPP |
+            DW_LNAME_CPP_for_OpenCL |
+            DW_LNAME_SYCL |
+            DW_LNAME_Ruby |
+            DW_LNAME_Move |
+            DW_LNAME_Hylo => Some(0),
+            DW_LNAME_Ada |
+            DW_LNAME_Cobol |
+            DW_LNAME_Fortran |
+            DW_LNAME_Julia |
+            DW_LNAME_Modula2 |
+            DW_LNAME_Modula3 |
+            DW_LNAME_Pascal |
+            DW_LNAME_PLI => Some(1),
+            _ => None,
+        }
+    }
 }
 
 /// The encodings of the constants used in the `DW_AT_address_class` attribute.
@@ -857,6 +1010,26 @@ dw!(DwAddr(u64) {
     DW_ADDR_none = 0x00,
 });
 
+// The remaining address classes are target specific, and the same value has
+// different meanings on different targets, so they are not included in the
+// table above. Use the `address_class_name` method of the architecture types,
+// such as `X86::address_class_name`, to get the name of these values.
+
+/// A 16-bit offset, no segment. Defined for Intel x86 by DWARF 2, Appendix 3.
+pub const DW_ADDR_x86_near16: DwAddr = DwAddr(0x01);
+/// A 16-bit offset, 16-bit segment. Defined for Intel x86 by DWARF 2, Appendix 3.
+pub const DW_ADDR_x86_far16: DwAddr = DwAddr(0x02);
+/// A 16-bit offset, 16-bit normalized segment. Defined for Intel x86 by DWARF 2, Appendix 3.
+pub const DW_ADDR_x86_huge16: DwAddr = DwAddr(0x03);
+/// A 32-bit offset, no segment. Defined for Intel x86 by DWARF 2, Appendix 3.
+pub const DW_ADDR_x86_near32: DwAddr = DwAddr(0x04);
+/// A 32-bit offset, 16-bit segment. Defined for Intel x86 by DWARF 2, Appendix 3.
+pub const DW_ADDR_x86_far32: DwAddr = DwAddr(0x05);
+
+/// A pointer into the AVR program memory (flash) address space, rather than
+/// the data memory (RAM) address space, as emitted by GCC for `__flash`.
+pub const DW_ADDR_AVR_flash: DwAddr = DwAddr(0x01);
+
 /// The encodings of the constants used in the `DW_AT_identifier_case` attribute.
 /// See Section 7.14, Table 7.18.
 dw!(DwId(u8) {
@@ -1164,6 +1337,8 @@ dw!(DwOp(u8) {
 
 // GNU extensions
     DW_OP_GNU_push_tls_address = 0xe0,
+// WebAssembly extension
+    DW_OP_WASM_location = 0xed,
     DW_OP_GNU_implicit_pointer = 0xf2,
     DW_OP_GNU_entry_value = 0xf3,
     DW_OP_GNU_const_type = 0xf4,
@@ -1172,6 +1347,13 @@ dw!(DwOp(u8) {
     DW_OP_GNU_convert = 0xf7,
     DW_OP_GNU_reinterpret = 0xf9,
     DW_OP_GNU_parameter_ref = 0xfa,
+    DW_OP_GNU_addr_index = 0xfb,
+    DW_OP_GNU_const_index = 0xfc,
+
+// The vendor extension range. These are listed last so that the names of the
+// extensions above are used when displaying their opcodes.
+    DW_OP_lo_user = 0xe0,
+    DW_OP_hi_user = 0xff,
 });
 
 /// Pointer encoding used by `.eh_frame`. The four lower bits describe the
@@ -1309,6 +1491,19 @@ mod tests {
         assert_eq!(encoding.application(), DW_EH_PE_pcrel);
     }
 
+    #[test]
+    fn test_dw_lang_language_name() {
+        assert_eq!(DW_LANG_C11.language_name(), Some((DW_LNAME_C, 201112)));
+        assert_eq!(
+            DW_LANG_C_plus_plus_03.language_name(),
+            Some((DW_LNAME_C_plus_plus, 200310))
+        );
+        assert_eq!(DW_LANG_Rust.language_name(), Some((DW_LNAME_Rust, 0)));
+        assert_eq!(DW_LANG_lo_user.language_name(), None);
+        assert_eq!(DW_LNAME_Fortran.default_lower_bound(), Some(1));
+        assert_eq!(format!("{}", DW_LNAME_C_plus_plus), "DW_LNAME_C_plus_plus");
+    }
+
     #[test]
     fn test_dw_eh_pe_is_absent() {
         assert_eq!(DW_EH_PE_absptr.is_absent(), false);
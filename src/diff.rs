@@ -0,0 +1,654 @@
+//! Comparison of the debugging information in two `Dwarf` objects.
+
+use constants;
+use dwarf::{Dwarf, Unit};
+use line::ColumnType;
+use loclists::LocationListEntry;
+use parser::Result;
+use reader::Reader;
+use rnglists::Range;
+use unit::{Attribute, AttributeValue, DebugInfoOffset, UnitOffset};
+use vec::Vec;
+
+/// An entry that is referred to by a `Difference`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry<R: Reader> {
+    /// The offset of the unit containing the entry.
+    pub unit: DebugInfoOffset<R::Offset>,
+
+    /// The offset of the entry within its unit.
+    pub offset: UnitOffset<R::Offset>,
+
+    /// The tag of the entry.
+    pub tag: constants::DwTag,
+
+    /// The `DW_AT_name` attribute of the entry.
+    pub name: Option<R>,
+}
+
+/// An attribute value that has been normalized so that it can be compared
+/// between different `Dwarf` objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffValue<R: Reader> {
+    /// A string, which may have been read from the `.debug_str` section.
+    String(R),
+
+    /// A reference to another entry, identified by the tag and name of that
+    /// entry.
+    Reference {
+        /// The tag of the referenced entry.
+        tag: constants::DwTag,
+        /// The `DW_AT_name` attribute of the referenced entry.
+        name: Option<R>,
+    },
+
+    /// The ranges of a range list.
+    Ranges(Vec<Range>),
+
+    /// The entries of a location list.
+    Locations(Vec<LocationListEntry<R>>),
+
+    /// Any other attribute value.
+    Value(AttributeValue<R>),
+}
+
+/// A row of a line number program, with the file index replaced by the
+/// file's path name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLineRow<R: Reader> {
+    /// The address of the row.
+    pub address: u64,
+
+    /// The path name of the row's file.
+    pub file: Option<R>,
+
+    /// The line number of the row.
+    pub line: Option<u64>,
+
+    /// The column number of the row.
+    pub column: ColumnType,
+
+    /// True if the row is the end of a sequence.
+    pub end_sequence: bool,
+}
+
+/// A difference between two `Dwarf` objects, as returned by `Dwarf::diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference<R: Reader> {
+    /// A unit only exists in the new `Dwarf`.
+    UnitAdded {
+        /// The offset of the unit in the new `Dwarf`.
+        unit: DebugInfoOffset<R::Offset>,
+        /// The `DW_AT_name` attribute of the unit.
+        name: Option<R>,
+    },
+
+    /// A unit only exists in the old `Dwarf`.
+    UnitRemoved {
+        /// The offset of the unit in the old `Dwarf`.
+        unit: DebugInfoOffset<R::Offset>,
+        /// The `DW_AT_name` attribute of the unit.
+        name: Option<R>,
+    },
+
+    /// An entry and its children only exist in the new `Dwarf`.
+    EntryAdded {
+        /// The entry in the new `Dwarf`.
+        entry: DiffEntry<R>,
+    },
+
+    /// An entry and its children only exist in the old `Dwarf`.
+    EntryRemoved {
+        /// The entry in the old `Dwarf`.
+        entry: DiffEntry<R>,
+    },
+
+    /// An attribute only exists in the new `Dwarf`.
+    AttributeAdded {
+        /// The entry in the old `Dwarf`.
+        old: DiffEntry<R>,
+        /// The entry in the new `Dwarf`.
+        new: DiffEntry<R>,
+        /// The name of the attribute.
+        name: constants::DwAt,
+        /// The value of the attribute in the new `Dwarf`.
+        value: DiffValue<R>,
+    },
+
+    /// An attribute only exists in the old `Dwarf`.
+    AttributeRemoved {
+        /// The entry in the old `Dwarf`.
+        old: DiffEntry<R>,
+        /// The entry in the new `Dwarf`.
+        new: DiffEntry<R>,
+        /// The name of the attribute.
+        name: constants::DwAt,
+        /// The value of the attribute in the old `Dwarf`.
+        value: DiffValue<R>,
+    },
+
+    /// An attribute has a different value in each `Dwarf`.
+    AttributeChanged {
+        /// The entry in the old `Dwarf`.
+        old: DiffEntry<R>,
+        /// The entry in the new `Dwarf`.
+        new: DiffEntry<R>,
+        /// The name of the attribute.
+        name: constants::DwAt,
+        /// The value of the attribute in the old `Dwarf`.
+        old_value: DiffValue<R>,
+        /// The value of the attribute in the new `Dwarf`.
+        new_value: DiffValue<R>,
+    },
+
+    /// A line number row only exists in the new `Dwarf`.
+    LineRowAdded {
+        /// The offset of the unit in the new `Dwarf`.
+        unit: DebugInfoOffset<R::Offset>,
+        /// The row.
+        row: DiffLineRow<R>,
+    },
+
+    /// A line number row only exists in the old `Dwarf`.
+    LineRowRemoved {
+        /// The offset of the unit in the old `Dwarf`.
+        unit: DebugInfoOffset<R::Offset>,
+        /// The row.
+        row: DiffLineRow<R>,
+    },
+}
+
+#[derive(Debug)]
+struct DiffNode<R: Reader> {
+    offset: UnitOffset<R::Offset>,
+    tag: constants::DwTag,
+    name: Option<R>,
+    children: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct DiffUnit<R: Reader> {
+    unit: Unit<R>,
+    // Sorted by offset.
+    nodes: Vec<DiffNode<R>>,
+}
+
+impl<R: Reader> DiffUnit<R> {
+    fn new(dwarf: &Dwarf<R>, unit: Unit<R>) -> Result<Self> {
+        let mut nodes: Vec<DiffNode<R>> = Vec::new();
+        {
+            let mut parents: Vec<usize> = Vec::new();
+            let mut depth = 0;
+            let mut cursor = unit.entries();
+            while let Some((delta, entry)) = cursor.next_dfs()? {
+                depth += delta;
+                parents.truncate(depth as usize);
+                let index = nodes.len();
+                if let Some(&parent) = parents.last() {
+                    nodes[parent].children.push(index);
+                }
+                let name = entry
+                    .attr_value(constants::DW_AT_name)?
+                    .and_then(|name| dwarf.attr_string(&name));
+                nodes.push(DiffNode {
+                    offset: entry.offset(),
+                    tag: entry.tag(),
+                    name,
+                    children: Vec::new(),
+                });
+                parents.push(index);
+            }
+        }
+        Ok(DiffUnit { unit, nodes })
+    }
+
+    fn contains(&self, offset: DebugInfoOffset<R::Offset>) -> bool {
+        let header = &self.unit.header;
+        offset.0 >= header.offset().0
+            && offset.0 - header.offset().0 < header.length_including_self()
+    }
+
+    fn node(&self, offset: UnitOffset<R::Offset>) -> Option<&DiffNode<R>> {
+        self.nodes
+            .binary_search_by(|node| node.offset.0.cmp(&offset.0))
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+
+    fn entry(&self, index: usize) -> DiffEntry<R> {
+        let node = &self.nodes[index];
+        DiffEntry {
+            unit: self.unit.header.offset(),
+            offset: node.offset,
+            tag: node.tag,
+            name: node.name.clone(),
+        }
+    }
+
+    fn line_rows(&self) -> Result<Vec<DiffLineRow<R>>> {
+        let mut result = Vec::new();
+        if let Some(ref program) = self.unit.line_program {
+            let mut rows = program.clone().rows();
+            while let Some((header, row)) = rows.next_row()? {
+                result.push(DiffLineRow {
+                    address: row.address(),
+                    file: row.file(header).map(|file| file.path_name()),
+                    line: row.line(),
+                    column: row.column(),
+                    end_sequence: row.end_sequence(),
+                });
+            }
+        }
+        result.sort_by_key(line_row_key);
+        Ok(result)
+    }
+}
+
+fn line_row_key<R: Reader>(row: &DiffLineRow<R>) -> (u64, bool, Option<u64>, ColumnType) {
+    (row.address, row.end_sequence, row.line, row.column)
+}
+
+fn load_units<R: Reader>(dwarf: &Dwarf<R>) -> Result<Vec<DiffUnit<R>>> {
+    let mut units = Vec::new();
+    let mut iter = dwarf.units();
+    while let Some(header) = iter.next()? {
+        let unit = dwarf.unit(header)?;
+        units.push(DiffUnit::new(dwarf, unit)?);
+    }
+    Ok(units)
+}
+
+/// Match each item in `new` with the first unmatched item in `old` that has
+/// an equal key.
+///
+/// Returns the index of the matching old item for each new item, and
+/// whether each old item was matched.
+fn match_items<T, F>(old: &[T], new: &[T], eq: F) -> (Vec<Option<usize>>, Vec<bool>)
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut old_matched = vec![false; old.len()];
+    let mut new_matches = Vec::with_capacity(new.len());
+    for n in new {
+        let index = old
+            .iter()
+            .enumerate()
+            .position(|(i, o)| !old_matched[i] && eq(o, n));
+        if let Some(index) = index {
+            old_matched[index] = true;
+        }
+        new_matches.push(index);
+    }
+    (new_matches, old_matched)
+}
+
+struct DiffContext<'a, R: Reader + 'a> {
+    old_dwarf: &'a Dwarf<R>,
+    new_dwarf: &'a Dwarf<R>,
+    old_units: &'a [DiffUnit<R>],
+    new_units: &'a [DiffUnit<R>],
+    differences: Vec<Difference<R>>,
+}
+
+impl<'a, R: Reader + PartialEq> DiffContext<'a, R> {
+    fn diff_unit(&mut self, old: &DiffUnit<R>, new: &DiffUnit<R>) -> Result<()> {
+        if !old.nodes.is_empty() && !new.nodes.is_empty() {
+            self.diff_entry(old, 0, new, 0)?;
+        }
+        self.diff_line_rows(old, new)
+    }
+
+    fn diff_entry(
+        &mut self,
+        old_unit: &DiffUnit<R>,
+        old_index: usize,
+        new_unit: &DiffUnit<R>,
+        new_index: usize,
+    ) -> Result<()> {
+        let old_attrs = entry_attributes(self.old_dwarf, self.old_units, old_unit, old_index)?;
+        let new_attrs = entry_attributes(self.new_dwarf, self.new_units, new_unit, new_index)?;
+        let old = old_unit.entry(old_index);
+        let new = new_unit.entry(new_index);
+        let (new_matches, old_matched) = match_items(&old_attrs, &new_attrs, |o, n| o.0 == n.0);
+        for (&(name, ref value), old_match) in new_attrs.iter().zip(new_matches) {
+            let difference = match old_match {
+                Some(i) => {
+                    if old_attrs[i].1 == *value {
+                        continue;
+                    }
+                    Difference::AttributeChanged {
+                        old: old.clone(),
+                        new: new.clone(),
+                        name,
+                        old_value: old_attrs[i].1.clone(),
+                        new_value: value.clone(),
+                    }
+                }
+                None => Difference::AttributeAdded {
+                    old: old.clone(),
+                    new: new.clone(),
+                    name,
+                    value: value.clone(),
+                },
+            };
+            self.differences.push(difference);
+        }
+        for (&(name, ref value), matched) in old_attrs.iter().zip(old_matched) {
+            if !matched {
+                self.differences.push(Difference::AttributeRemoved {
+                    old: old.clone(),
+                    new: new.clone(),
+                    name,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        let old_children = &old_unit.nodes[old_index].children;
+        let new_children = &new_unit.nodes[new_index].children;
+        let (new_matches, old_matched) = match_items(old_children, new_children, |&o, &n| {
+            let o = &old_unit.nodes[o];
+            let n = &new_unit.nodes[n];
+            o.tag == n.tag && o.name == n.name
+        });
+        for (&new_child, old_match) in new_children.iter().zip(new_matches) {
+            match old_match {
+                Some(i) => self.diff_entry(old_unit, old_children[i], new_unit, new_child)?,
+                None => self.differences.push(Difference::EntryAdded {
+                    entry: new_unit.entry(new_child),
+                }),
+            }
+        }
+        for (&old_child, matched) in old_children.iter().zip(old_matched) {
+            if !matched {
+                self.differences.push(Difference::EntryRemoved {
+                    entry: old_unit.entry(old_child),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn diff_line_rows(&mut self, old: &DiffUnit<R>, new: &DiffUnit<R>) -> Result<()> {
+        let old_rows = old.line_rows()?;
+        let new_rows = new.line_rows()?;
+        let mut old_iter = old_rows.into_iter().peekable();
+        let mut new_iter = new_rows.into_iter().peekable();
+        loop {
+            let ordering = match (old_iter.peek(), new_iter.peek()) {
+                (Some(o), Some(n)) => {
+                    if o == n {
+                        old_iter.next();
+                        new_iter.next();
+                        continue;
+                    }
+                    line_row_key(o).cmp(&line_row_key(n))
+                }
+                (Some(_), None) => ::std::cmp::Ordering::Less,
+                (None, Some(_)) => ::std::cmp::Ordering::Greater,
+                (None, None) => break,
+            };
+            // Rows with equal keys but different files are reported as both
+            // removed and added.
+            if ordering != ::std::cmp::Ordering::Greater {
+                self.differences.push(Difference::LineRowRemoved {
+                    unit: old.unit.header.offset(),
+                    row: old_iter.next().unwrap(),
+                });
+            }
+            if ordering != ::std::cmp::Ordering::Less {
+                self.differences.push(Difference::LineRowAdded {
+                    unit: new.unit.header.offset(),
+                    row: new_iter.next().unwrap(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the normalized attributes of an entry.
+fn entry_attributes<R: Reader>(
+    dwarf: &Dwarf<R>,
+    units: &[DiffUnit<R>],
+    unit: &DiffUnit<R>,
+    index: usize,
+) -> Result<Vec<(constants::DwAt, DiffValue<R>)>> {
+    let mut result = Vec::new();
+    let mut cursor = unit.unit.entries_at_offset(unit.nodes[index].offset)?;
+    cursor.next_entry()?;
+    let entry = match cursor.current() {
+        Some(entry) => entry,
+        None => return Ok(result),
+    };
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            // These are offsets that don't affect the meaning of the entry.
+            constants::DW_AT_sibling | constants::DW_AT_stmt_list => continue,
+            name => result.push((name, normalize(dwarf, units, unit, &attr)?)),
+        }
+    }
+    Ok(result)
+}
+
+/// Normalize an attribute value so that it doesn't depend on section offsets.
+fn normalize<R: Reader>(
+    dwarf: &Dwarf<R>,
+    units: &[DiffUnit<R>],
+    unit: &DiffUnit<R>,
+    attr: &Attribute<R>,
+) -> Result<DiffValue<R>> {
+    let value = attr.value();
+    let header = &unit.unit.header;
+    let node = match value {
+        AttributeValue::String(_) | AttributeValue::DebugStrRef(_) => {
+            if let Some(string) = dwarf.attr_string(&value) {
+                return Ok(DiffValue::String(string));
+            }
+            None
+        }
+        AttributeValue::UnitRef(offset) => unit.node(offset),
+        AttributeValue::DebugInfoRef(offset) => units
+            .iter()
+            .find(|unit| unit.contains(offset))
+            .and_then(|unit| unit.node(UnitOffset(offset.0 - unit.unit.header.offset().0))),
+        AttributeValue::RangeListsRef(offset) => {
+            let mut ranges = Vec::new();
+            let mut iter = dwarf.ranges.ranges(
+                offset,
+                header.version(),
+                header.address_size(),
+                unit.unit.low_pc,
+            )?;
+            while let Some(range) = iter.next()? {
+                ranges.push(range);
+            }
+            return Ok(DiffValue::Ranges(ranges));
+        }
+        AttributeValue::LocationListsRef(offset) => {
+            let mut locations = Vec::new();
+            let mut iter = dwarf.locations.locations(
+                offset,
+                header.version(),
+                header.address_size(),
+                unit.unit.low_pc,
+            )?;
+            while let Some(location) = iter.next()? {
+                locations.push(location);
+            }
+            return Ok(DiffValue::Locations(locations));
+        }
+        _ => None,
+    };
+    Ok(match node {
+        Some(node) => DiffValue::Reference {
+            tag: node.tag,
+            name: node.name.clone(),
+        },
+        None => DiffValue::Value(value),
+    })
+}
+
+impl<R: Reader + PartialEq> Dwarf<R> {
+    /// Compare the units in the `.debug_info` section of this `Dwarf` with
+    /// those of `new`, and return the differences.
+    ///
+    /// Units are matched by their `DW_AT_name`, and entries are matched by
+    /// their tag and `DW_AT_name` within matching parents. When several
+    /// items have the same key, they are matched in order. Only the topmost
+    /// entry of an added or removed subtree is reported.
+    ///
+    /// Attribute values are normalized before comparison so that they don't
+    /// depend on the layout of the sections: strings are read from
+    /// `.debug_str`, references are replaced by the tag and name of the
+    /// referenced entry, and range and location lists are replaced by their
+    /// contents. `DW_AT_sibling` and `DW_AT_stmt_list` are ignored, and the
+    /// rows of the line number programs of matching units are compared
+    /// instead.
+    pub fn diff(&self, new: &Dwarf<R>) -> Result<Vec<Difference<R>>> {
+        let old_units = load_units(self)?;
+        let new_units = load_units(new)?;
+        let mut context = DiffContext {
+            old_dwarf: self,
+            new_dwarf: new,
+            old_units: &old_units,
+            new_units: &new_units,
+            differences: Vec::new(),
+        };
+
+        let (new_matches, old_matched) =
+            match_items(&old_units, &new_units, |o, n| o.unit.name == n.unit.name);
+        for (new_unit, old_match) in new_units.iter().zip(new_matches) {
+            match old_match {
+                Some(i) => context.diff_unit(&old_units[i], new_unit)?,
+                None => context.differences.push(Difference::UnitAdded {
+                    unit: new_unit.unit.header.offset(),
+                    name: new_unit.unit.name.clone(),
+                }),
+            }
+        }
+        for (old_unit, matched) in old_units.iter().zip(old_matched) {
+            if !matched {
+                context.differences.push(Difference::UnitRemoved {
+                    unit: old_unit.unit.header.offset(),
+                    name: old_unit.unit.name.clone(),
+                });
+            }
+        }
+        Ok(context.differences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use SectionId;
+
+    fn dwarf<'input>(
+        debug_abbrev: &'input [u8],
+        debug_info: &'input [u8],
+    ) -> Dwarf<EndianBuf<'input, LittleEndian>> {
+        Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => debug_abbrev,
+                SectionId::DebugInfo => debug_info,
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            // DW_AT_decl_line, DW_FORM_data1.
+            0x3b, 0x0b,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x11, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let old_debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x15, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            0x01, b'a', b'.', b'c', 0x00,
+            0x02, b'f', 0x00, 0x01,
+            0x02, b'g', 0x00, 0x01,
+            0x00,
+
+            // Unit length, version, abbrev offset, address size.
+            0x0c, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            0x03, b'b', b'.', b'c', 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let new_debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x15, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            0x01, b'a', b'.', b'c', 0x00,
+            0x02, b'f', 0x00, 0x02,
+            0x02, b'h', 0x00, 0x01,
+            0x00,
+        ];
+
+        let old = dwarf(&debug_abbrev, &old_debug_info);
+        let new = dwarf(&debug_abbrev, &new_debug_info);
+        assert_eq!(old.diff(&old), Ok(Vec::new()));
+
+        let buf = |s| EndianBuf::new(s, LittleEndian);
+        let entry = |offset, name| DiffEntry {
+            unit: DebugInfoOffset(0),
+            offset: UnitOffset(offset),
+            tag: constants::DW_TAG_subprogram,
+            name: Some(buf(name)),
+        };
+        assert_eq!(
+            old.diff(&new),
+            Ok(vec![
+                Difference::AttributeChanged {
+                    old: entry(0x10, b"f"),
+                    new: entry(0x10, b"f"),
+                    name: constants::DW_AT_decl_line,
+                    old_value: DiffValue::Value(AttributeValue::Udata(1)),
+                    new_value: DiffValue::Value(AttributeValue::Udata(2)),
+                },
+                Difference::EntryAdded {
+                    entry: entry(0x14, b"h"),
+                },
+                Difference::EntryRemoved {
+                    entry: entry(0x14, b"g"),
+                },
+                Difference::UnitRemoved {
+                    unit: DebugInfoOffset(0x19),
+                    name: Some(buf(b"b.c")),
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,1857 @@
+//! A convenient interface to all of the DWARF sections, and to the units they
+//! contain.
+
+use std::result;
+
+use abbrev::{Abbreviations, DebugAbbrev};
+use addr::DebugAddr;
+use constants;
+use line::{DebugLine, IncompleteLineNumberProgram, StateMachine};
+use loclists::{DebugLoc, DebugLocLists, DebugLocListsBase, DebugLocListsIndex, LocListIter,
+               LocationLists, LocationListsOffset};
+use parser::{Error, ReaderLimits, Result};
+use reader::{Reader, ReaderOffset, ReaderOffsetId};
+use rnglists::{AddressAdjustment, AddressIndex, DebugAddrBase, DebugRanges, DebugRngLists,
+               DebugRngListsBase, DebugRngListsIndex, Range, RangeLists, RangeListsOffset,
+               RngListIter, TombstoneMode};
+use str::{DebugLineStr, DebugStr, DebugStrOffset, DebugStrOffsets, DebugStrOffsetsBase,
+          DebugStrOffsetsIndex};
+use string::String;
+use unit::{AttributeValue, CompilationUnitHeader, CompilationUnitHeadersIter, DebugInfo,
+           DebugInfoOffset, DebugTypes, DebuggingInformationEntry, EntriesCursor, EntriesTree,
+           TypeUnitHeadersIter, UnitOffset, UnitSectionOffset};
+use vec::Vec;
+use {Section, SectionId};
+
+/// The type of object file that a `Dwarf` was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwarfFileType {
+    /// A main object file, such as an executable or shared library.
+    Main,
+    /// A split DWARF object (a `.dwo` or `.dwp` file).
+    Dwo,
+}
+
+impl Default for DwarfFileType {
+    fn default() -> Self {
+        DwarfFileType::Main
+    }
+}
+
+/// All of the commonly used DWARF sections, and other common information.
+#[derive(Debug, Clone)]
+pub struct Dwarf<R: Reader> {
+    /// The `.debug_abbrev` section.
+    pub debug_abbrev: DebugAbbrev<R>,
+
+    /// The `.debug_addr` section.
+    pub debug_addr: DebugAddr<R>,
+
+    /// The `.debug_info` section.
+    pub debug_info: DebugInfo<R>,
+
+    /// The `.debug_line` section.
+    pub debug_line: DebugLine<R>,
+
+    /// The `.debug_line_str` section.
+    pub debug_line_str: DebugLineStr<R>,
+
+    /// The `.debug_str` section.
+    pub debug_str: DebugStr<R>,
+
+    /// The `.debug_str_offsets` section.
+    pub debug_str_offsets: DebugStrOffsets<R>,
+
+    /// The `.debug_types` section.
+    pub debug_types: DebugTypes<R>,
+
+    /// The location lists in the `.debug_loc` and `.debug_loclists` sections.
+    pub locations: LocationLists<R>,
+
+    /// The range lists in the `.debug_ranges` and `.debug_rnglists` sections.
+    pub ranges: RangeLists<R>,
+
+    /// The limits to apply when parsing abbreviations and navigating
+    /// entries.
+    pub limits: ReaderLimits,
+
+    /// How `die_ranges` treats ranges for code that was discarded by the
+    /// linker.
+    pub tombstones: TombstoneMode,
+
+    /// The type of object file that the sections were loaded from.
+    pub file_type: DwarfFileType,
+
+    /// Whether to accept the encodings of old DWARF 2 and 3 producers, which
+    /// are still common in embedded toolchains. Defaults to `false`.
+    ///
+    /// When enabled, `die_ranges` handles units before version 4 as follows:
+    ///
+    /// * `DW_AT_high_pc` is always an address, even if it has a constant form.
+    ///
+    /// * DWARF 2 has no `DW_AT_ranges`, so a compilation unit entry without
+    /// any address attributes is given the ranges of its children. This
+    /// includes the children of nested namespaces, modules, and class,
+    /// structure and union types.
+    ///
+    /// `DW_FORM_ref_addr` values are always parsed as address sized in
+    /// version 2 units, regardless of this option.
+    pub dwarf2_compat: bool,
+
+    /// The adjustment to apply to the addresses of code and data, such as
+    /// for the slide of a loaded image or the debug map of a Mach-O
+    /// executable. Defaults to `None`.
+    ///
+    /// This is copied to each `Unit` when it is constructed, and is applied
+    /// by `ranges`, `locations`, `die_ranges` and `Unit::line_rows`.
+    /// Addresses that have no adjusted address are omitted.
+    pub address_adjustment: Option<AddressAdjustment>,
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Try to load the DWARF sections using the given loader function.
+    ///
+    /// `section` loads a DWARF section from the object file.
+    /// It should return an empty section if the section does not exist.
+    ///
+    /// ```
+    /// use gimli::{Dwarf, EndianBuf, LittleEndian, SectionId};
+    ///
+    /// let load_section_somehow = |_: SectionId| -> gimli::Result<_> {
+    ///     Ok(EndianBuf::new(&[], LittleEndian))
+    /// };
+    ///
+    /// let dwarf = Dwarf::load(load_section_somehow).unwrap();
+    /// assert!(dwarf.units().next().unwrap().is_none());
+    /// ```
+    pub fn load<F, E>(mut section: F) -> result::Result<Self, E>
+    where
+        F: FnMut(SectionId) -> result::Result<R, E>,
+        E: From<Error>,
+    {
+        let debug_loc = DebugLoc::load(&mut section)?;
+        let debug_loclists = DebugLocLists::load(&mut section)?;
+        let debug_ranges = DebugRanges::load(&mut section)?;
+        let debug_rnglists = DebugRngLists::load(&mut section)?;
+        Ok(Dwarf {
+            debug_abbrev: Section::load(&mut section)?,
+            debug_addr: Section::load(&mut section)?,
+            debug_info: Section::load(&mut section)?,
+            debug_line: Section::load(&mut section)?,
+            debug_line_str: Section::load(&mut section)?,
+            debug_str: Section::load(&mut section)?,
+            debug_str_offsets: Section::load(&mut section)?,
+            debug_types: Section::load(&mut section)?,
+            locations: LocationLists::new(debug_loc, debug_loclists)?,
+            ranges: RangeLists::new(debug_ranges, debug_rnglists)?,
+            limits: ReaderLimits::default(),
+            tombstones: TombstoneMode::default(),
+            file_type: DwarfFileType::Main,
+            dwarf2_compat: false,
+            address_adjustment: None,
+        })
+    }
+
+    /// Try to load the DWARF sections of a split DWARF object (a `.dwo` or
+    /// `.dwp` file) using the given loader function.
+    ///
+    /// `section` loads the section named by `SectionId::dwo_name` from the
+    /// object file. It is only called for sections that can exist in a split
+    /// DWARF object, and should return an empty section if the section does
+    /// not exist.
+    ///
+    /// ```
+    /// use gimli::{Dwarf, EndianBuf, LittleEndian, SectionId};
+    ///
+    /// let load_section_somehow = |id: SectionId| -> gimli::Result<_> {
+    ///     assert!(id.dwo_name().is_some());
+    ///     Ok(EndianBuf::new(&[], LittleEndian))
+    /// };
+    ///
+    /// let dwarf = Dwarf::load_dwo(load_section_somehow).unwrap();
+    /// assert!(dwarf.units().next().unwrap().is_none());
+    /// ```
+    pub fn load_dwo<F, E>(mut section: F) -> result::Result<Self, E>
+    where
+        F: FnMut(SectionId) -> result::Result<R, E>,
+        E: From<Error>,
+    {
+        let debug_loc = DebugLoc::load(&mut section)?;
+        let debug_loclists = DebugLocLists::load(&mut section)?;
+        let debug_rnglists = section(SectionId::DebugRngLists)?;
+        // Split DWARF objects never contain a `.debug_ranges` section.
+        let mut debug_ranges = debug_rnglists.clone();
+        debug_ranges.empty();
+        // The addresses for split units are in the `.debug_addr` section of
+        // the executable, so callers must copy it from the skeleton's `Dwarf`
+        // using `set_skeleton_sections`.
+        let mut debug_addr = debug_rnglists.clone();
+        debug_addr.empty();
+        // Split DWARF objects never contain a `.debug_line_str` section.
+        let mut debug_line_str = debug_rnglists.clone();
+        debug_line_str.empty();
+        Ok(Dwarf {
+            debug_abbrev: Section::load(&mut section)?,
+            debug_addr: debug_addr.into(),
+            debug_info: Section::load(&mut section)?,
+            debug_line: Section::load(&mut section)?,
+            debug_line_str: debug_line_str.into(),
+            debug_str: Section::load(&mut section)?,
+            debug_str_offsets: Section::load(&mut section)?,
+            debug_types: Section::load(&mut section)?,
+            locations: LocationLists::new(debug_loc, debug_loclists)?,
+            ranges: RangeLists::new(debug_ranges.into(), debug_rnglists.into())?,
+            limits: ReaderLimits::default(),
+            tombstones: TombstoneMode::default(),
+            file_type: DwarfFileType::Dwo,
+            dwarf2_compat: false,
+            address_adjustment: None,
+        })
+    }
+
+    /// Copy the sections that split units use from the `Dwarf` of the main
+    /// object file that contains their skeleton units.
+    ///
+    /// Split units use the `.debug_addr` section of the main object file,
+    /// and in the pre-standard GNU split DWARF extension, they also use its
+    /// `.debug_ranges` section.
+    pub fn set_skeleton_sections(&mut self, skeleton: &Dwarf<R>) {
+        self.debug_addr = skeleton.debug_addr.clone();
+        self.ranges.debug_ranges = skeleton.ranges.debug_ranges.clone();
+    }
+
+    /// Iterate the compilation unit headers in the `.debug_info` section.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    #[inline]
+    pub fn units(&self) -> CompilationUnitHeadersIter<R> {
+        self.debug_info.units()
+    }
+
+    /// Iterate the type unit headers in the `.debug_types` section.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    #[inline]
+    pub fn type_units(&self) -> TypeUnitHeadersIter<R> {
+        self.debug_types.units()
+    }
+
+    /// Find the unit that contains the given section offset.
+    ///
+    /// Returns the offset of the header of the unit, and the offset of the
+    /// given section offset relative to the start of the unit. Returns `None`
+    /// if no unit contains the offset.
+    ///
+    /// This walks the unit headers in the section, so callers that need to
+    /// resolve many offsets should cache the headers instead.
+    pub fn find_unit_offset(
+        &self,
+        offset: UnitSectionOffset<R::Offset>,
+    ) -> Result<Option<(UnitSectionOffset<R::Offset>, UnitOffset<R::Offset>)>> {
+        match offset {
+            UnitSectionOffset::DebugInfoOffset(offset) => {
+                let mut units = self.units();
+                while let Some(header) = units.next()? {
+                    if let Some(unit_offset) = offset.to_unit_offset(&header) {
+                        return Ok(Some((header.offset().into(), unit_offset)));
+                    }
+                }
+            }
+            UnitSectionOffset::DebugTypesOffset(offset) => {
+                let mut units = self.type_units();
+                while let Some(header) = units.next()? {
+                    if let Some(unit_offset) = offset.to_unit_offset(&header) {
+                        return Ok(Some((header.offset().into(), unit_offset)));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Construct a new `Unit` from the given compilation unit header.
+    #[inline]
+    pub fn unit(&self, header: CompilationUnitHeader<R, R::Offset>) -> Result<Unit<R>> {
+        Unit::new(self, header)
+    }
+
+    /// Parse the abbreviations for a compilation unit.
+    #[inline]
+    pub fn abbreviations(
+        &self,
+        header: &CompilationUnitHeader<R, R::Offset>,
+    ) -> Result<Abbreviations> {
+        self.debug_abbrev
+            .abbreviations_with_limits(header.debug_abbrev_offset(), &self.limits)
+    }
+
+    /// Try to return an attribute value as a string slice.
+    ///
+    /// If the attribute value is either an inline `DW_FORM_string` string,
+    /// a `DW_FORM_strp` reference to an offset into the `.debug_str` section,
+    /// or a `DW_FORM_line_strp` reference to an offset into the
+    /// `.debug_line_str` section, return the string as `Some`. Other
+    /// attribute value forms are returned as `None`.
+    pub fn attr_string(&self, attr: &AttributeValue<R>) -> Option<R> {
+        match *attr {
+            AttributeValue::String(ref string) => Some(string.clone()),
+            AttributeValue::DebugStrRef(offset) => self.debug_str.get_str(offset).ok(),
+            AttributeValue::DebugLineStrRef(offset) => self.debug_line_str.get_str(offset).ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to return an attribute value of the given unit as a string slice.
+    ///
+    /// This accepts the same attribute value forms as `attr_string`, and also
+    /// accepts `DW_FORM_strx` indices, which are resolved using the unit's
+    /// `DW_AT_str_offsets_base`.
+    pub fn attr_unit_string(&self, unit: &Unit<R>, attr: &AttributeValue<R>) -> Option<R> {
+        match *attr {
+            AttributeValue::DebugStrOffsetsIndex(index) => {
+                let offset = self.string_offset(unit, index).ok()?;
+                self.debug_str.get_str(offset).ok()
+            }
+            _ => self.attr_string(attr),
+        }
+    }
+
+    /// Try to return an attribute value of the given unit as an address.
+    ///
+    /// If the attribute value is either a `DW_FORM_addr` address, or a
+    /// `DW_FORM_addrx` index into the `.debug_addr` section, return the
+    /// address as `Some`. Other attribute value forms are returned as `None`.
+    ///
+    /// The unit's address adjustment is applied to the address, and `None`
+    /// is returned if the address has no adjusted address.
+    pub fn attr_address(&self, unit: &Unit<R>, attr: &AttributeValue<R>) -> Option<u64> {
+        let address = self.attr_unadjusted_address(unit, attr)?;
+        match unit.address_adjustment {
+            Some(ref adjustment) => adjustment.adjust(address),
+            None => Some(address),
+        }
+    }
+
+    /// Try to return an attribute value of the given unit as an address,
+    /// without applying the unit's address adjustment.
+    fn attr_unadjusted_address(&self, unit: &Unit<R>, attr: &AttributeValue<R>) -> Option<u64> {
+        match *attr {
+            AttributeValue::Addr(address) => Some(address),
+            AttributeValue::DebugAddrIndex(index) => self.address(unit, index).ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to return a reference attribute value of the given unit as an
+    /// offset into the `.debug_info` section.
+    ///
+    /// This accepts both unit relative references, such as `DW_FORM_ref4`,
+    /// and `DW_FORM_ref_addr` references. The resulting offset can be used
+    /// with `find_unit_offset` to find the unit that contains the entry.
+    /// Other attribute value forms are returned as `None`.
+    pub fn attr_debug_info_offset(
+        &self,
+        unit: &Unit<R>,
+        attr: &AttributeValue<R>,
+    ) -> Option<DebugInfoOffset<R::Offset>> {
+        match *attr {
+            AttributeValue::UnitRef(offset) => Some(offset.to_debug_info_offset(&unit.header)),
+            AttributeValue::DebugInfoRef(offset) => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// Return the address at the given index of the unit's entries in the
+    /// `.debug_addr` section.
+    #[inline]
+    pub fn address(&self, unit: &Unit<R>, index: AddressIndex) -> Result<u64> {
+        self.debug_addr
+            .get_address(unit.header.address_size(), unit.addr_base, index)
+    }
+
+    /// Return the `.debug_str` offset at the given index of the unit's
+    /// entries in the `.debug_str_offsets` section.
+    #[inline]
+    pub fn string_offset(
+        &self,
+        unit: &Unit<R>,
+        index: DebugStrOffsetsIndex<R::Offset>,
+    ) -> Result<DebugStrOffset<R::Offset>> {
+        self.debug_str_offsets
+            .get_str_offset(unit.header.format(), unit.str_offsets_base, index)
+    }
+
+    /// Return the range list offset at the given index of the unit's set of
+    /// offsets in the `.debug_rnglists` section.
+    #[inline]
+    pub fn ranges_offset(
+        &self,
+        unit: &Unit<R>,
+        index: DebugRngListsIndex<R::Offset>,
+    ) -> Result<RangeListsOffset<R::Offset>> {
+        self.ranges
+            .get_offset(unit.header.format(), unit.rnglists_base, index)
+    }
+
+    /// Return the location list offset at the given index of the unit's set
+    /// of offsets in the `.debug_loclists` section.
+    #[inline]
+    pub fn locations_offset(
+        &self,
+        unit: &Unit<R>,
+        index: DebugLocListsIndex<R::Offset>,
+    ) -> Result<LocationListsOffset<R::Offset>> {
+        self.locations
+            .get_offset(unit.header.format(), unit.loclists_base, index)
+    }
+
+    /// Return the range list offset given by a `DW_FORM_sec_offset` value of
+    /// an attribute of the given unit.
+    ///
+    /// In split units of the pre-standard GNU split DWARF extension, these
+    /// offsets are relative to the skeleton unit's `DW_AT_GNU_ranges_base`.
+    /// Otherwise they are offsets from the start of the section, and
+    /// `DW_AT_rnglists_base` only applies to `DW_FORM_rnglistx` indices.
+    pub fn ranges_offset_from_raw(
+        &self,
+        unit: &Unit<R>,
+        offset: RangeListsOffset<R::Offset>,
+    ) -> RangeListsOffset<R::Offset> {
+        if self.file_type == DwarfFileType::Dwo && unit.header.version() < 5 {
+            RangeListsOffset(offset.0.wrapping_add(unit.rnglists_base.0))
+        } else {
+            offset
+        }
+    }
+
+    /// Try to return an attribute value of the given unit as a range list
+    /// offset.
+    ///
+    /// `DW_FORM_rnglistx` indices are resolved using the unit's
+    /// `DW_AT_rnglists_base`. Returns `None` for attribute values that are
+    /// not range list references, and for indices that are not valid.
+    pub fn attr_ranges_offset(
+        &self,
+        unit: &Unit<R>,
+        attr: &AttributeValue<R>,
+    ) -> Option<RangeListsOffset<R::Offset>> {
+        match *attr {
+            AttributeValue::RangeListsRef(offset) => {
+                Some(self.ranges_offset_from_raw(unit, offset))
+            }
+            AttributeValue::DebugRngListsIndex(index) => self.ranges_offset(unit, index).ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to return an attribute value of the given unit as a location list
+    /// offset.
+    ///
+    /// `DW_FORM_sec_offset` values are offsets from the start of the
+    /// section, including in split units, so `DW_AT_loclists_base` does not
+    /// apply to them. `DW_FORM_loclistx` indices are resolved using the
+    /// unit's `DW_AT_loclists_base`. Returns `None` for attribute values that
+    /// are not location list references, and for indices that are not valid.
+    pub fn attr_locations_offset(
+        &self,
+        unit: &Unit<R>,
+        attr: &AttributeValue<R>,
+    ) -> Option<LocationListsOffset<R::Offset>> {
+        match *attr {
+            AttributeValue::LocationListsRef(offset) => Some(offset),
+            AttributeValue::DebugLocListsIndex(index) => self.locations_offset(unit, index).ok(),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the range list of the given unit at the given offset.
+    ///
+    /// The offset must already be resolved, such as by `attr_ranges_offset`.
+    pub fn unit_ranges(
+        &self,
+        unit: &Unit<R>,
+        offset: RangeListsOffset<R::Offset>,
+    ) -> Result<RngListIter<R>> {
+        let mut iter = self.ranges.ranges(
+            offset,
+            unit.header.version(),
+            unit.header.address_size(),
+            unit.low_pc,
+        )?;
+        iter.set_tombstone_mode(self.tombstones);
+        iter.set_address_adjustment(unit.address_adjustment.clone());
+        Ok(iter)
+    }
+
+    /// Iterate over the location list of the given unit at the given offset.
+    ///
+    /// The offset must already be resolved, such as by
+    /// `attr_locations_offset`.
+    pub fn unit_locations(
+        &self,
+        unit: &Unit<R>,
+        offset: LocationListsOffset<R::Offset>,
+    ) -> Result<LocListIter<R>> {
+        let mut iter = self.locations.locations(
+            offset,
+            unit.header.version(),
+            unit.header.address_size(),
+            unit.low_pc,
+        )?;
+        iter.set_tombstone_mode(self.tombstones);
+        iter.set_address_adjustment(unit.address_adjustment.clone());
+        Ok(iter)
+    }
+
+    /// Try to return an attribute value as an owned string, replacing any
+    /// invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// This accepts the same attribute value forms as `attr_string`, but
+    /// doesn't require the string to be valid UTF-8, which is useful for
+    /// producers that emit Latin-1 file paths. Errors are only returned if
+    /// the reader fails to read the string.
+    pub fn attr_string_lossy(&self, attr: &AttributeValue<R>) -> Result<Option<String>> {
+        match self.attr_string(attr) {
+            Some(string) => Ok(Some(String::from(string.to_string_lossy()?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the attribute value with a `DW_FORM_strp` reference into the
+    /// `.debug_str` section replaced by the string that it refers to.
+    ///
+    /// This is useful before serializing an attribute value, since the
+    /// serialized value cannot be resolved later. Other attribute values, and
+    /// references that are not valid, are returned unchanged.
+    pub fn resolve_string(&self, attr: AttributeValue<R>) -> AttributeValue<R> {
+        match attr {
+            AttributeValue::DebugStrRef(offset) => match self.debug_str.get_str(offset) {
+                Ok(string) => AttributeValue::String(string),
+                Err(_) => attr,
+            },
+            _ => attr,
+        }
+    }
+
+    /// Return the address ranges of an entry, given by its `DW_AT_low_pc`,
+    /// `DW_AT_high_pc` and `DW_AT_ranges` attributes.
+    ///
+    /// `DW_AT_high_pc` may be either an address or an offset from
+    /// `DW_AT_low_pc`. Empty ranges are omitted, as are ranges for discarded
+    /// code unless `self.tombstones` is `TombstoneMode::Keep`. See
+    /// `self.dwarf2_compat` for the handling of older units.
+    pub fn die_ranges(
+        &self,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+    ) -> Result<Vec<Range>> {
+        let mut ranges = Vec::new();
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            if attr.name() != constants::DW_AT_ranges {
+                continue;
+            }
+            if let Some(offset) = self.attr_ranges_offset(unit, &attr.value()) {
+                let mut iter = self.unit_ranges(unit, offset)?;
+                while let Some(range) = iter.next()? {
+                    ranges.push(range);
+                }
+            }
+        }
+        let compat = self.dwarf2_compat && unit.header.version() < 4;
+        if let Some(range) = entry.pc_range_impl(compat)? {
+            let address_size = unit.header.address_size();
+            if !range.is_tombstone(self.tombstones, address_size) && range.begin < range.end {
+                match unit.address_adjustment {
+                    Some(ref adjustment) => ranges.extend(adjustment.adjust_range(range)),
+                    None => ranges.push(range),
+                }
+            }
+        }
+        let scope = match entry.tag() {
+            constants::DW_TAG_compile_unit |
+            constants::DW_TAG_namespace |
+            constants::DW_TAG_module |
+            constants::DW_TAG_class_type |
+            constants::DW_TAG_structure_type |
+            constants::DW_TAG_union_type => true,
+            _ => false,
+        };
+        if compat && ranges.is_empty() && scope {
+            let mut cursor = unit.entries_at_offset(entry.offset())?;
+            cursor.next_entry()?;
+            let mut children = cursor.children();
+            while let Some(child) = children.next()? {
+                ranges.extend(self.die_ranges(unit, child)?);
+            }
+        }
+        ranges.retain(|range| range.begin < range.end);
+        Ok(ranges)
+    }
+
+    /// Return the section and offset corresponding to the given reader
+    /// offset id, if it is associated with one of these sections.
+    ///
+    /// This can be used to report where an `Error::UnexpectedEof` occurred.
+    pub fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<(SectionId, R::Offset)> {
+        None.or_else(|| self.debug_abbrev.lookup_offset_id(id))
+            .or_else(|| self.debug_addr.lookup_offset_id(id))
+            .or_else(|| self.debug_info.lookup_offset_id(id))
+            .or_else(|| self.debug_line.lookup_offset_id(id))
+            .or_else(|| self.debug_line_str.lookup_offset_id(id))
+            .or_else(|| self.debug_str.lookup_offset_id(id))
+            .or_else(|| self.debug_str_offsets.lookup_offset_id(id))
+            .or_else(|| self.debug_types.lookup_offset_id(id))
+            .or_else(|| self.locations.lookup_offset_id(id))
+            .or_else(|| self.ranges.lookup_offset_id(id))
+    }
+}
+
+/// All of the commonly used information for a compilation unit.
+///
+/// This is built from a `CompilationUnitHeader` by parsing its abbreviations,
+/// and reading the attributes of the unit's root entry that are needed to
+/// interpret the rest of the unit.
+///
+/// ```rust,no_run
+/// # fn example(dwarf: &gimli::Dwarf<gimli::EndianBuf<gimli::LittleEndian>>) -> gimli::Result<()> {
+/// let mut iter = dwarf.units();
+/// while let Some(header) = iter.next()? {
+///     let unit = dwarf.unit(header)?;
+///     if let Some(ref name) = unit.name {
+///         println!("Unit: {}", name.to_string_lossy());
+///     }
+///
+///     let mut entries = unit.entries();
+///     while let Some((_, entry)) = entries.next_dfs()? {
+///         println!("{:?}", entry.tag());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Unit<R: Reader> {
+    /// The header of the unit.
+    pub header: CompilationUnitHeader<R, R::Offset>,
+
+    /// The parsed abbreviations for the unit.
+    pub abbreviations: Abbreviations,
+
+    /// The `DW_AT_name` attribute of the unit.
+    pub name: Option<R>,
+
+    /// The `DW_AT_comp_dir` attribute of the unit.
+    pub comp_dir: Option<R>,
+
+    /// The `DW_AT_low_pc` attribute of the unit. Defaults to 0.
+    pub low_pc: u64,
+
+    /// The `DW_AT_GNU_dwo_id` attribute of the unit, or the DWO id in the
+    /// header of a DWARF version 5 unit. This identifies the split unit that
+    /// corresponds to a skeleton unit.
+    pub dwo_id: Option<u64>,
+
+    /// The `DW_AT_str_offsets_base` attribute of the unit. Defaults to 0.
+    pub str_offsets_base: DebugStrOffsetsBase<R::Offset>,
+
+    /// The `DW_AT_addr_base` or `DW_AT_GNU_addr_base` attribute of the unit.
+    /// Defaults to 0.
+    pub addr_base: DebugAddrBase<R::Offset>,
+
+    /// The `DW_AT_loclists_base` attribute of the unit. Defaults to 0.
+    pub loclists_base: DebugLocListsBase<R::Offset>,
+
+    /// The `DW_AT_rnglists_base` or `DW_AT_GNU_ranges_base` attribute of the
+    /// unit. Defaults to 0.
+    pub rnglists_base: DebugRngListsBase<R::Offset>,
+
+    /// The line number program of the unit, if it has a `DW_AT_stmt_list`
+    /// attribute.
+    pub line_program: Option<IncompleteLineNumberProgram<R>>,
+
+    /// The limits to apply when navigating the unit's entries.
+    pub limits: ReaderLimits,
+
+    /// The adjustment to apply to the addresses of the unit's code and data.
+    ///
+    /// `low_pc` is not adjusted, since it is the base address for the
+    /// unit's range lists and location lists.
+    pub address_adjustment: Option<AddressAdjustment>,
+}
+
+impl<R: Reader> Unit<R> {
+    /// Construct a new `Unit` from the given compilation unit header.
+    pub fn new(dwarf: &Dwarf<R>, header: CompilationUnitHeader<R, R::Offset>) -> Result<Self> {
+        let abbreviations = dwarf.abbreviations(&header)?;
+        let zero = R::Offset::from_u8(0);
+        let mut name = None;
+        let mut comp_dir = None;
+        let mut low_pc = None;
+        let mut dwo_id = header.dwo_id();
+        let mut str_offsets_base = DebugStrOffsetsBase(zero);
+        let mut addr_base = DebugAddrBase(zero);
+        let mut loclists_base = DebugLocListsBase(zero);
+        let mut rnglists_base = DebugRngListsBase(zero);
+        let mut line_offset = None;
+        if dwarf.file_type == DwarfFileType::Dwo {
+            // Split units don't have these attributes. Their sections contain
+            // a single set of offsets, which follows the section header.
+            str_offsets_base =
+                DebugStrOffsetsBase::default_for_dwo(header.version(), header.format());
+            if header.version() >= 5 {
+                loclists_base =
+                    DebugLocListsBase(R::Offset::from_u8(dwarf.locations.header_size()));
+                rnglists_base = DebugRngListsBase(R::Offset::from_u8(dwarf.ranges.header_size()));
+            }
+        }
+        {
+            let mut cursor = header.entries(&abbreviations);
+            cursor.set_limits(&dwarf.limits);
+            cursor.next_dfs()?;
+            let root = cursor.current().ok_or(Error::MissingUnitDie)?;
+            let mut attrs = root.attrs();
+            while let Some(attr) = attrs.next()? {
+                match attr.name() {
+                    // These may be indices that are relative to the bases
+                    // given by other attributes, so resolve them later.
+                    constants::DW_AT_name => {
+                        name = Some(attr.value());
+                    }
+                    constants::DW_AT_comp_dir => {
+                        comp_dir = Some(attr.value());
+                    }
+                    constants::DW_AT_low_pc => {
+                        low_pc = Some(attr.value());
+                    }
+                    constants::DW_AT_GNU_dwo_id => {
+                        dwo_id = attr.udata_value();
+                    }
+                    constants::DW_AT_stmt_list => {
+                        if let AttributeValue::DebugLineRef(offset) = attr.value() {
+                            line_offset = Some(offset);
+                        }
+                    }
+                    constants::DW_AT_str_offsets_base => {
+                        if let AttributeValue::DebugStrOffsetsBase(base) = attr.value() {
+                            str_offsets_base = base;
+                        }
+                    }
+                    constants::DW_AT_addr_base | constants::DW_AT_GNU_addr_base => {
+                        if let AttributeValue::DebugAddrBase(base) = attr.value() {
+                            addr_base = base;
+                        }
+                    }
+                    constants::DW_AT_loclists_base => {
+                        if let AttributeValue::DebugLocListsBase(base) = attr.value() {
+                            loclists_base = base;
+                        }
+                    }
+                    constants::DW_AT_rnglists_base | constants::DW_AT_GNU_ranges_base => {
+                        if let AttributeValue::DebugRngListsBase(base) = attr.value() {
+                            rnglists_base = base;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut unit = Unit {
+            header,
+            abbreviations,
+            name: None,
+            comp_dir: None,
+            low_pc: 0,
+            dwo_id,
+            str_offsets_base,
+            addr_base,
+            loclists_base,
+            rnglists_base,
+            line_program: None,
+            limits: dwarf.limits,
+            address_adjustment: dwarf.address_adjustment.clone(),
+        };
+        unit.name = name.and_then(|attr| dwarf.attr_unit_string(&unit, &attr));
+        unit.comp_dir = comp_dir.and_then(|attr| dwarf.attr_unit_string(&unit, &attr));
+        unit.low_pc = low_pc
+            .and_then(|attr| dwarf.attr_unadjusted_address(&unit, &attr))
+            .unwrap_or(0);
+        if let Some(offset) = line_offset {
+            unit.line_program = Some(dwarf.debug_line.program_with_strings(
+                offset,
+                unit.header.address_size(),
+                unit.comp_dir.clone(),
+                unit.name.clone(),
+                &dwarf.debug_str,
+                &dwarf.debug_line_str,
+            )?);
+        }
+        Ok(unit)
+    }
+
+    /// Copy the attributes that a split unit inherits from its skeleton
+    /// unit.
+    ///
+    /// The skeleton unit provides the split unit's `DW_AT_low_pc` and
+    /// `DW_AT_addr_base`. For versions before 5, it also provides the
+    /// `DW_AT_GNU_ranges_base` that the split unit's range list offsets are
+    /// relative to. The split unit's addresses are in the main object file,
+    /// so it also uses the skeleton unit's address adjustment.
+    pub fn copy_skeleton_attributes(&mut self, skeleton: &Unit<R>) {
+        self.low_pc = skeleton.low_pc;
+        self.addr_base = skeleton.addr_base;
+        self.address_adjustment = skeleton.address_adjustment.clone();
+        if self.header.version() < 5 {
+            self.rnglists_base = skeleton.rnglists_base;
+        }
+    }
+
+    /// Return a state machine that computes the rows of the unit's line
+    /// number program, or `None` if the unit has no line number program.
+    ///
+    /// The state machine applies the unit's limits and address adjustment.
+    pub fn line_rows(&self) -> Option<StateMachine<R, IncompleteLineNumberProgram<R>>> {
+        self.line_program.as_ref().map(|program| {
+            let mut rows = program.clone().rows();
+            rows.set_limits(&self.limits);
+            rows.set_address_adjustment(self.address_adjustment.clone());
+            rows
+        })
+    }
+
+    /// Try to return a reference attribute value of this unit as an offset
+    /// within this unit.
+    ///
+    /// This accepts both unit relative references, such as `DW_FORM_ref4`,
+    /// and `DW_FORM_ref_addr` references to entries within this unit. Other
+    /// attribute value forms, and references to other units, are returned as
+    /// `None`.
+    pub fn attr_unit_offset(&self, attr: &AttributeValue<R>) -> Option<UnitOffset<R::Offset>> {
+        self.header.attr_unit_offset(attr)
+    }
+
+    /// Return the entry at the given offset within this unit.
+    ///
+    /// Returns `Error::NoEntryAtGivenOffset` if the offset is the offset of a
+    /// null entry.
+    pub fn entry<'me>(
+        &'me self,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<DebuggingInformationEntry<'me, 'me, R, R::Offset>> {
+        let mut cursor = self.entries_at_offset(offset)?;
+        cursor.next_entry()?;
+        cursor.current().cloned().ok_or(Error::NoEntryAtGivenOffset)
+    }
+
+    /// Return the entry that a reference attribute value of this unit refers
+    /// to.
+    ///
+    /// This follows references in the same way as `attr_unit_offset`, and
+    /// returns `None` for attribute values that it does not accept.
+    ///
+    /// ```rust,no_run
+    /// # fn example<R: gimli::Reader>(
+    /// #     unit: &gimli::Unit<R>,
+    /// #     entry: &gimli::DebuggingInformationEntry<R, R::Offset>,
+    /// # ) -> gimli::Result<()> {
+    /// if let Some(attr) = entry.attr_value(gimli::DW_AT_type)? {
+    ///     if let Some(ty) = unit.attr_entry(&attr)? {
+    ///         println!("Type: {}", ty.tag());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn attr_entry<'me>(
+        &'me self,
+        attr: &AttributeValue<R>,
+    ) -> Result<Option<DebuggingInformationEntry<'me, 'me, R, R::Offset>>> {
+        match self.attr_unit_offset(attr) {
+            Some(offset) => self.entry(offset).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Navigate this unit's `DebuggingInformationEntry`s.
+    #[inline]
+    pub fn entries<'me>(&'me self) -> EntriesCursor<'me, 'me, R> {
+        let mut cursor = self.header.entries(&self.abbreviations);
+        cursor.set_limits(&self.limits);
+        cursor
+    }
+
+    /// Navigate this unit's `DebuggingInformationEntry`s
+    /// starting at the given offset.
+    #[inline]
+    pub fn entries_at_offset<'me>(
+        &'me self,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<EntriesCursor<'me, 'me, R>> {
+        let mut cursor = self.header.entries_at_offset(&self.abbreviations, offset)?;
+        cursor.set_limits(&self.limits);
+        Ok(cursor)
+    }
+
+    /// Navigate this unit's `DebuggingInformationEntry`s
+    /// starting at the given offset, after checking that the offset is the
+    /// offset of an entry.
+    ///
+    /// This should be used for offsets from untrusted sources, such as
+    /// accelerator tables.
+    #[inline]
+    pub fn entries_at_offset_checked<'me>(
+        &'me self,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<EntriesCursor<'me, 'me, R>> {
+        let mut cursor = self.header.entries_at_offset_checked(&self.abbreviations, offset)?;
+        cursor.set_limits(&self.limits);
+        Ok(cursor)
+    }
+
+    /// Navigate this unit's `DebuggingInformationEntry`s as a tree
+    /// starting at the given offset.
+    #[inline]
+    pub fn entries_tree<'me>(
+        &'me self,
+        offset: Option<UnitOffset<R::Offset>>,
+    ) -> Result<EntriesTree<'me, 'me, R>> {
+        let mut tree = self.header.entries_tree(&self.abbreviations, offset)?;
+        tree.set_limits(&self.limits);
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use str::{DebugLineStrOffset, DebugStrOffset};
+    use unit::{DebugInfoOffset, DebugTypesOffset};
+    use vec::Vec;
+
+    fn dwarf<'input>(
+        debug_abbrev: &'input [u8],
+        debug_info: &'input [u8],
+        debug_str: &'input [u8],
+    ) -> Dwarf<EndianBuf<'input, LittleEndian>> {
+        Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => debug_abbrev,
+                SectionId::DebugInfo => debug_info,
+                SectionId::DebugStr => debug_str,
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_load_dwo() {
+        let buf = [0x01, 0x02, 0x00];
+        let mut loaded = Vec::new();
+        let dwarf = Dwarf::load_dwo(|id| -> Result<_> {
+            loaded.push(id.dwo_name().unwrap());
+            let data = match id {
+                SectionId::DebugStr => &buf[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        loaded.sort();
+        assert_eq!(
+            loaded,
+            [
+                ".debug_abbrev.dwo",
+                ".debug_info.dwo",
+                ".debug_line.dwo",
+                ".debug_loc.dwo",
+                ".debug_loclists.dwo",
+                ".debug_rnglists.dwo",
+                ".debug_str.dwo",
+                ".debug_str_offsets.dwo",
+                ".debug_types.dwo",
+            ]
+        );
+        assert_eq!(
+            dwarf.debug_str.get_str(DebugStrOffset(1)),
+            Ok(EndianBuf::new(&buf[1..2], LittleEndian))
+        );
+    }
+
+    #[test]
+    fn test_resolve_string() {
+        let debug_str = [0x01, 0x02, 0x00];
+        let dwarf = dwarf(&[], &[], &debug_str);
+
+        assert_eq!(
+            dwarf.resolve_string(AttributeValue::DebugStrRef(DebugStrOffset(1))),
+            AttributeValue::String(EndianBuf::new(&debug_str[1..2], LittleEndian))
+        );
+        assert_eq!(
+            dwarf.resolve_string(AttributeValue::DebugStrRef(DebugStrOffset(3))),
+            AttributeValue::DebugStrRef(DebugStrOffset(3))
+        );
+        assert_eq!(
+            dwarf.resolve_string(AttributeValue::Udata(1)),
+            AttributeValue::Udata(1)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        extern crate serde_json;
+
+        let debug_str = [0x01, 0x02, 0x00];
+        let dwarf = dwarf(&[], &[], &debug_str);
+
+        // Resolved strings are serialized as their bytes.
+        let value = dwarf.resolve_string(AttributeValue::DebugStrRef(DebugStrOffset(1)));
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"String":[2]}"#);
+        let value = AttributeValue::<EndianBuf<LittleEndian>>::DebugInfoRef(DebugInfoOffset(16));
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"DebugInfoRef":16}"#);
+
+        // Offsets and ranges can be deserialized again.
+        let json = serde_json::to_string(&DebugInfoOffset(16)).unwrap();
+        assert_eq!(json, "16");
+        assert_eq!(serde_json::from_str::<DebugInfoOffset>(&json).unwrap(), DebugInfoOffset(16));
+        let range = Range {
+            begin: 0x1000,
+            end: 0x1010,
+        };
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#"{"begin":4096,"end":4112}"#);
+        assert_eq!(serde_json::from_str::<Range>(&json).unwrap(), range);
+    }
+
+    #[test]
+    fn test_attr_string_line_str() {
+        let debug_line_str = [0x61, 0x00, 0x62, 0x63, 0x00];
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugLineStr => &debug_line_str[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        assert_eq!(
+            dwarf.attr_string(&AttributeValue::DebugLineStrRef(DebugLineStrOffset(2))),
+            Some(EndianBuf::new(&debug_line_str[2..4], LittleEndian))
+        );
+        assert_eq!(
+            dwarf.attr_string(&AttributeValue::DebugLineStrRef(DebugLineStrOffset(5))),
+            None
+        );
+        let id = EndianBuf::new(&debug_line_str[2..], LittleEndian).offset_id();
+        assert_eq!(dwarf.lookup_offset_id(id), Some((SectionId::DebugLineStr, 2)));
+    }
+
+    #[test]
+    fn test_attr_string_lossy() {
+        // "caf\xe9" in Latin-1, followed by a valid string.
+        let debug_str = [0x63, 0x61, 0x66, 0xe9, 0x00, 0x61, 0x62, 0x00];
+        let dwarf = dwarf(&[], &[], &debug_str);
+
+        assert_eq!(
+            dwarf.attr_string_lossy(&AttributeValue::DebugStrRef(DebugStrOffset(0))),
+            Ok(Some(String::from("caf\u{fffd}")))
+        );
+        assert_eq!(
+            dwarf.attr_string_lossy(&AttributeValue::DebugStrRef(DebugStrOffset(5))),
+            Ok(Some(String::from("ab")))
+        );
+        assert_eq!(
+            dwarf.attr_string_lossy(&AttributeValue::String(EndianBuf::new(
+                &debug_str[3..4],
+                LittleEndian
+            ))),
+            Ok(Some(String::from("\u{fffd}")))
+        );
+        assert_eq!(
+            dwarf.attr_string_lossy(&AttributeValue::DebugStrRef(DebugStrOffset(8))),
+            Ok(None)
+        );
+        assert_eq!(dwarf.attr_string_lossy(&AttributeValue::Udata(1)), Ok(None));
+    }
+
+    #[test]
+    fn test_lookup_offset_id() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, too long for the section.
+            0x08, 0x00, 0x00, 0x00,
+            // Version.
+            0x04, 0x00,
+            // Abbreviation offset.
+            0x00, 0x00, 0x00, 0x00,
+            // Address size.
+            0x08,
+        ];
+        let debug_str = [0x01, 0x02, 0x00];
+        let dwarf = dwarf(&[], &debug_info, &debug_str);
+
+        let id = match dwarf.units().next() {
+            Err(Error::UnexpectedEof(id)) => id,
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        };
+        assert_eq!(dwarf.lookup_offset_id(id), Some((SectionId::DebugInfo, 4)));
+
+        let id = EndianBuf::new(&debug_str[1..], LittleEndian).offset_id();
+        assert_eq!(dwarf.lookup_offset_id(id), Some((SectionId::DebugStr, 1)));
+
+        let other = [0];
+        let id = EndianBuf::new(&other, LittleEndian).offset_id();
+        assert_eq!(dwarf.lookup_offset_id(id), None);
+    }
+
+    #[test]
+    fn test_unit() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_name, DW_FORM_strp.
+            0x03, 0x0e,
+            // DW_AT_comp_dir, DW_FORM_string.
+            0x1b, 0x08,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_str_offsets_base, DW_FORM_sec_offset.
+            0x72, 0x17,
+            // DW_AT_addr_base, DW_FORM_sec_offset.
+            0x73, 0x17,
+            // DW_AT_rnglists_base, DW_FORM_sec_offset.
+            0x74, 0x17,
+            // DW_AT_loclists_base, DW_FORM_sec_offset.
+            0x8c, 0x01, 0x17,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x27, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x04, 0x00, 0x00, 0x00,
+            b'/', b't', b'm', b'p', 0x00,
+            0x00, 0x10, 0x00, 0x00,
+            0x08, 0x00, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0x0c, 0x00, 0x00, 0x00,
+            0x18, 0x00, 0x00, 0x00,
+            // Child entry.
+            0x02,
+            // End of children.
+            0x00,
+        ];
+
+        let debug_str = b"bar\0foo.c\0";
+
+        let mut dwarf = dwarf(&debug_abbrev, &debug_info, debug_str);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        assert_eq!(unit.name, Some(EndianBuf::new(b"foo.c", LittleEndian)));
+        assert_eq!(unit.comp_dir, Some(EndianBuf::new(b"/tmp", LittleEndian)));
+        assert_eq!(unit.low_pc, 0x1000);
+        assert_eq!(unit.str_offsets_base, DebugStrOffsetsBase(0x08));
+        assert_eq!(unit.addr_base, DebugAddrBase(0x10));
+        assert_eq!(unit.rnglists_base, DebugRngListsBase(0x0c));
+        assert_eq!(unit.loclists_base, DebugLocListsBase(0x18));
+        assert!(unit.line_program.is_none());
+
+        let mut entries = unit.entries();
+        let (_, entry) = entries.next_dfs().unwrap().unwrap();
+        assert_eq!(entry.tag(), constants::DW_TAG_compile_unit);
+        let (delta, entry) = entries.next_dfs().unwrap().unwrap();
+        assert_eq!(delta, 1);
+        assert_eq!(entry.tag(), constants::DW_TAG_subprogram);
+        let offset = entry.offset();
+        assert!(entries.next_dfs().unwrap().is_none());
+
+        let mut entries = unit.entries_at_offset(offset).unwrap();
+        let (_, entry) = entries.next_dfs().unwrap().unwrap();
+        assert_eq!(entry.tag(), constants::DW_TAG_subprogram);
+
+        dwarf.limits.max_die_depth = Some(0);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        let mut entries = unit.entries();
+        assert!(entries.next_dfs().unwrap().is_some());
+        assert_eq!(entries.next_dfs().map(|_| ()), Err(Error::LimitExceeded));
+
+        dwarf.limits.max_abbreviations = Some(1);
+        let header = dwarf.units().next().unwrap().unwrap();
+        assert_eq!(dwarf.unit(header).map(|_| ()), Err(Error::LimitExceeded));
+    }
+
+    #[test]
+    fn test_unit_refs() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_type, DW_FORM_ref4.
+            0x49, 0x13,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x24, 0x00,
+            // DW_AT_type, DW_FORM_ref_addr.
+            0x49, 0x10,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x13, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            // Subprogram entry.
+            0x02,
+            0x11, 0x00, 0x00, 0x00,
+            // Base type entry.
+            0x03,
+            0x0c, 0x00, 0x00, 0x00,
+            // End of children.
+            0x00,
+        ];
+
+        let dwarf = dwarf(&debug_abbrev, &debug_info, &[]);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        let subprogram = unit.entry(UnitOffset(12)).unwrap();
+        assert_eq!(subprogram.tag(), constants::DW_TAG_subprogram);
+        let attr = subprogram.attr_value(constants::DW_AT_type).unwrap().unwrap();
+        assert_eq!(attr, AttributeValue::UnitRef(UnitOffset(17)));
+        assert_eq!(unit.attr_unit_offset(&attr), Some(UnitOffset(17)));
+        assert_eq!(dwarf.attr_debug_info_offset(&unit, &attr), Some(DebugInfoOffset(17)));
+
+        let base_type = unit.attr_entry(&attr).unwrap().unwrap();
+        assert_eq!(base_type.tag(), constants::DW_TAG_base_type);
+        let attr = base_type.attr_value(constants::DW_AT_type).unwrap().unwrap();
+        assert_eq!(attr, AttributeValue::DebugInfoRef(DebugInfoOffset(12)));
+        assert_eq!(unit.attr_unit_offset(&attr), Some(UnitOffset(12)));
+        assert_eq!(dwarf.attr_debug_info_offset(&unit, &attr), Some(DebugInfoOffset(12)));
+        let entry = unit.attr_entry(&attr).unwrap().unwrap();
+        assert_eq!(entry.tag(), constants::DW_TAG_subprogram);
+
+        // References to other units, and values that are not references.
+        let attr = AttributeValue::DebugInfoRef(DebugInfoOffset(0x100));
+        assert_eq!(unit.attr_unit_offset(&attr), None);
+        assert_eq!(unit.attr_entry(&attr).map(|entry| entry.is_some()), Ok(false));
+        assert_eq!(unit.attr_unit_offset(&AttributeValue::Udata(17)), None);
+
+        // The null entry at the end of the children.
+        assert_eq!(unit.entry(UnitOffset(22)).map(|_| ()), Err(Error::NoEntryAtGivenOffset));
+    }
+
+    #[test]
+    fn test_die_ranges_dwarf2_compat() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x39, 0x01,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x1d, 0x00, 0x00, 0x00,
+            0x02, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            // Subprogram entry.
+            0x02,
+            0x00, 0x10, 0x00, 0x00,
+            0x00, 0x11, 0x00, 0x00,
+            // Namespace entry.
+            0x03,
+            // Subprogram entry.
+            0x02,
+            0x00, 0x30, 0x00, 0x00,
+            0x10, 0x30, 0x00, 0x00,
+            // End of namespace children.
+            0x00,
+            // End of children.
+            0x00,
+        ];
+
+        let mut dwarf = dwarf(&debug_abbrev, &debug_info, &[]);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        let mut entries = unit.entries();
+        let (_, root) = entries.next_dfs().unwrap().unwrap();
+        let root = root.clone();
+        let (_, subprogram) = entries.next_dfs().unwrap().unwrap();
+
+        assert_eq!(dwarf.die_ranges(&unit, &root), Ok(vec![]));
+        assert_eq!(
+            dwarf.die_ranges(&unit, subprogram),
+            Ok(vec![Range { begin: 0x1000, end: 0x2100 }])
+        );
+
+        dwarf.dwarf2_compat = true;
+        assert_eq!(
+            dwarf.die_ranges(&unit, &root),
+            Ok(vec![
+                Range { begin: 0x1000, end: 0x1100 },
+                Range { begin: 0x3000, end: 0x3010 },
+            ])
+        );
+        assert_eq!(
+            dwarf.die_ranges(&unit, subprogram),
+            Ok(vec![Range { begin: 0x1000, end: 0x1100 }])
+        );
+    }
+
+    #[test]
+    fn test_address_adjustment() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_ranges, DW_FORM_sec_offset.
+            0x55, 0x17,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x1a, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x00, 0x10, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            // Subprogram entry.
+            0x02,
+            0x00, 0x10, 0x00, 0x00,
+            0x20, 0x00, 0x00, 0x00,
+            // End of children.
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_ranges = [
+            0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x10, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugRanges => &debug_ranges[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        let root = unit.entry(UnitOffset(11)).unwrap();
+        let subprogram = unit.entry(UnitOffset(20)).unwrap();
+        assert_eq!(
+            dwarf.die_ranges(&unit, &root),
+            Ok(vec![
+                Range { begin: 0x1000, end: 0x1020 },
+                Range { begin: 0x1100, end: 0x1110 },
+            ])
+        );
+
+        // Only the first function is in the linked image.
+        dwarf.address_adjustment = Some(AddressAdjustment::new(|address| {
+            if address >= 0x1000 && address < 0x1020 {
+                Some(address + 0x5000)
+            } else {
+                None
+            }
+        }));
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        assert_eq!(unit.low_pc, 0x1000);
+        assert_eq!(dwarf.attr_address(&unit, &AttributeValue::Addr(0x1010)), Some(0x6010));
+        assert_eq!(dwarf.attr_address(&unit, &AttributeValue::Addr(0x1100)), None);
+        assert_eq!(
+            dwarf.die_ranges(&unit, &root),
+            Ok(vec![Range { begin: 0x6000, end: 0x6020 }])
+        );
+        assert_eq!(
+            dwarf.die_ranges(&unit, &subprogram),
+            Ok(vec![Range { begin: 0x6000, end: 0x6020 }])
+        );
+    }
+
+    #[test]
+    fn test_split_unit_ranges() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let skeleton_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_GNU_ranges_base, DW_FORM_sec_offset.
+            0xb2, 0x42, 0x17,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let skeleton_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x10, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x00, 0x10, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_ranges = [
+            // A range list for another unit.
+            0x20, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // The range list for the split unit.
+            0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_ranges, DW_FORM_sec_offset.
+            0x55, 0x17,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x0c, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let skeleton_dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &skeleton_abbrev[..],
+                SectionId::DebugInfo => &skeleton_info[..],
+                SectionId::DebugRanges => &debug_ranges[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        assert_eq!(skeleton_dwarf.file_type, DwarfFileType::Main);
+        let header = skeleton_dwarf.units().next().unwrap().unwrap();
+        let skeleton = skeleton_dwarf.unit(header).unwrap();
+        assert_eq!(skeleton.rnglists_base, DebugRngListsBase(0x10));
+
+        let mut split_dwarf = Dwarf::load_dwo(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &split_abbrev[..],
+                SectionId::DebugInfo => &split_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        assert_eq!(split_dwarf.file_type, DwarfFileType::Dwo);
+        split_dwarf.set_skeleton_sections(&skeleton_dwarf);
+        let header = split_dwarf.units().next().unwrap().unwrap();
+        let mut split = split_dwarf.unit(header).unwrap();
+
+        // Without the skeleton's attributes, the offset is used unchanged.
+        let offset = RangeListsOffset(0);
+        let attr = AttributeValue::RangeListsRef(offset);
+        assert_eq!(split_dwarf.attr_ranges_offset(&split, &attr), Some(offset));
+        assert_eq!(split_dwarf.attr_ranges_offset(&split, &AttributeValue::Udata(0)), None);
+
+        split.copy_skeleton_attributes(&skeleton);
+        assert_eq!(split.low_pc, 0x1000);
+        assert_eq!(
+            split_dwarf.attr_ranges_offset(&split, &attr),
+            Some(RangeListsOffset(0x10))
+        );
+        let mut entries = split.entries();
+        let (_, entry) = entries.next_dfs().unwrap().unwrap();
+        assert_eq!(
+            split_dwarf.die_ranges(&split, entry),
+            Ok(vec![Range { begin: 0x1000, end: 0x1010 }])
+        );
+
+        // The base only applies to split units.
+        assert_eq!(
+            skeleton_dwarf.attr_ranges_offset(&skeleton, &attr),
+            Some(offset)
+        );
+    }
+
+    #[test]
+    fn test_split_unit_v5() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let skeleton_abbrev = [
+            // Code, tag, children.
+            0x01, 0x4a, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let skeleton_info = [
+            // Unit length, version, unit type, address size, abbrev offset.
+            0x15, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x04,
+            0x04,
+            0x00, 0x00, 0x00, 0x00,
+            // DWO id.
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Root entry.
+            0x01,
+            0x00, 0x10, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_ranges, DW_FORM_rnglistx.
+            0x55, 0x23,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_info = [
+            // Unit length, version, unit type, address size, abbrev offset.
+            0x12, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x05,
+            0x04,
+            0x00, 0x00, 0x00, 0x00,
+            // DWO id.
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            // Root entry.
+            0x01,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_rnglists = [
+            // Unit length, version, address size, segment selector size,
+            // offset entry count.
+            0x10, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x04,
+            0x00,
+            0x01, 0x00, 0x00, 0x00,
+            // Offsets.
+            0x04, 0x00, 0x00, 0x00,
+            // DW_RLE_offset_pair, DW_RLE_end_of_list.
+            0x04, 0x00, 0x10,
+            0x00,
+        ];
+
+        let skeleton_dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &skeleton_abbrev[..],
+                SectionId::DebugInfo => &skeleton_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let header = skeleton_dwarf.units().next().unwrap().unwrap();
+        assert_eq!(header.unit_type(), constants::DW_UT_skeleton);
+        let skeleton = skeleton_dwarf.unit(header).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(0x0807060504030201));
+        assert_eq!(skeleton.low_pc, 0x1000);
+
+        let mut split_dwarf = Dwarf::load_dwo(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &split_abbrev[..],
+                SectionId::DebugInfo => &split_info[..],
+                SectionId::DebugRngLists => &split_rnglists[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        split_dwarf.set_skeleton_sections(&skeleton_dwarf);
+        let header = split_dwarf.units().next().unwrap().unwrap();
+        assert_eq!(header.unit_type(), constants::DW_UT_split_compile);
+        let mut split = split_dwarf.unit(header).unwrap();
+        assert_eq!(split.dwo_id, Some(0x0807060504030201));
+        assert_eq!(split.rnglists_base, DebugRngListsBase(12));
+        assert_eq!(split.loclists_base, DebugLocListsBase(12));
+        assert_eq!(split.str_offsets_base, DebugStrOffsetsBase(8));
+
+        split.copy_skeleton_attributes(&skeleton);
+        let mut entries = split.entries();
+        let (_, entry) = entries.next_dfs().unwrap().unwrap();
+        assert_eq!(
+            split_dwarf.die_ranges(&split, entry),
+            Ok(vec![Range { begin: 0x1000, end: 0x1010 }])
+        );
+    }
+
+    #[test]
+    fn test_unit_indexed_attributes() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_name, DW_FORM_strx1.
+            0x03, 0x25,
+            // DW_AT_low_pc, DW_FORM_addrx1.
+            0x11, 0x29,
+            // DW_AT_str_offsets_base, DW_FORM_sec_offset.
+            0x72, 0x17,
+            // DW_AT_addr_base, DW_FORM_sec_offset.
+            0x73, 0x17,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, unit type, address size, abbrev offset.
+            0x13, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x01,
+            0x04,
+            0x00, 0x00, 0x00, 0x00,
+            // Root entry.
+            0x01,
+            0x01,
+            0x01,
+            0x08, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_str_offsets = [
+            // Unit length, version, padding.
+            0x0c, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x00, 0x00,
+            // Entries.
+            0x00, 0x00, 0x00, 0x00,
+            0x04, 0x00, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_addr = [
+            // Unit length, version, address size, segment selector size.
+            0x0c, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x04,
+            0x00,
+            // Entries.
+            0x00, 0x10, 0x00, 0x00,
+            0x00, 0x20, 0x00, 0x00,
+        ];
+
+        let debug_str = b"bar\0foo.c\0";
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugAddr => &debug_addr[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugStr => &debug_str[..],
+                SectionId::DebugStrOffsets => &debug_str_offsets[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        assert_eq!(unit.name, Some(EndianBuf::new(b"foo.c", LittleEndian)));
+        assert_eq!(unit.low_pc, 0x2000);
+        assert_eq!(dwarf.address(&unit, AddressIndex(0)), Ok(0x1000));
+        assert_eq!(
+            dwarf.string_offset(&unit, DebugStrOffsetsIndex(0)),
+            Ok(DebugStrOffset(0))
+        );
+        assert_eq!(
+            dwarf.attr_unit_string(
+                &unit,
+                &AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(0))
+            ),
+            Some(EndianBuf::new(b"bar", LittleEndian))
+        );
+        assert_eq!(
+            dwarf.attr_unit_string(
+                &unit,
+                &AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(2))
+            ),
+            None
+        );
+        assert_eq!(
+            dwarf.attr_address(&unit, &AttributeValue::DebugAddrIndex(AddressIndex(2))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_unit_offset() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x08, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Root entry.
+            0x01,
+
+            // Unit length, version, abbrev offset, address size.
+            0x08, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x17: Root entry.
+            0x01,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_types = [
+            // Unit length, version, abbrev offset, address size.
+            0x14, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // Type signature, type offset.
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x17, 0x00, 0x00, 0x00,
+            // 0x17: Root entry.
+            0x01,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugTypes => &debug_types[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        let info = |offset| UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(offset));
+        let types = |offset| UnitSectionOffset::DebugTypesOffset(DebugTypesOffset(offset));
+        assert_eq!(
+            dwarf.find_unit_offset(info(0x0b)),
+            Ok(Some((info(0), UnitOffset(0x0b))))
+        );
+        assert_eq!(
+            dwarf.find_unit_offset(info(0x17)),
+            Ok(Some((info(0x0c), UnitOffset(0x0b))))
+        );
+        assert_eq!(dwarf.find_unit_offset(info(0x05)), Ok(None));
+        assert_eq!(dwarf.find_unit_offset(info(0x18)), Ok(None));
+        assert_eq!(
+            dwarf.find_unit_offset(types(0x17)),
+            Ok(Some((types(0), UnitOffset(0x17))))
+        );
+        assert_eq!(dwarf.find_unit_offset(types(0x0b)), Ok(None));
+
+        assert_eq!(info(0x0c).add_unit_offset(UnitOffset(0x0b)), Some(info(0x17)));
+        assert_eq!(info(0x0c).as_debug_info_offset(), Some(DebugInfoOffset(0x0c)));
+        assert_eq!(info(0x0c).as_debug_types_offset(), None);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Dwarf<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<Unit<EndianBuf<LittleEndian>>>();
+    }
+
+    #[test]
+    fn test_unit_missing_die() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x07, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+        ];
+
+        let dwarf = dwarf(&[0x00], &debug_info, &[]);
+        let header = dwarf.units().next().unwrap().unwrap();
+        match dwarf.unit(header) {
+            Err(Error::MissingUnitDie) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+}
@@ -0,0 +1,582 @@
+//! A `Reader` implementation for owned, shared buffers of bytes.
+
+use borrow::Cow;
+use rc::Rc;
+use std::fmt::{self, Debug};
+use std::mem;
+use std::ops::{Deref, Index, Range, RangeFrom, RangeTo};
+use std::str;
+use string::String;
+use sync::Arc;
+
+use endianity::Endianity;
+use leb128;
+use parser::{Error, Result};
+use reader::{find_byte, Reader, ReaderOffsetId};
+
+/// A reference counted, non-thread-safe slice of bytes and associated
+/// endianity.
+///
+/// ```
+/// use gimli::{EndianRcSlice, LittleEndian, Reader};
+/// use std::rc::Rc;
+///
+/// let buf: Rc<[u8]> = Rc::from(vec![0x01, 0x02, 0x03, 0x04]);
+/// let mut reader = EndianRcSlice::new(buf, LittleEndian);
+/// assert_eq!(reader.read_u16(), Ok(0x0201));
+/// assert_eq!(&*reader, &[0x03, 0x04]);
+/// ```
+pub type EndianRcSlice<Endian> = EndianReader<Endian, Rc<[u8]>>;
+
+/// An atomically reference counted, thread-safe slice of bytes and associated
+/// endianity.
+///
+/// ```
+/// use gimli::{EndianArcSlice, LittleEndian, Reader};
+/// use std::sync::Arc;
+///
+/// let buf: Arc<[u8]> = Arc::from(vec![0x01, 0x02, 0x03, 0x04]);
+/// let mut reader = EndianArcSlice::new(buf, LittleEndian);
+/// assert_eq!(reader.read_u16(), Ok(0x0201));
+/// assert_eq!(&*reader, &[0x03, 0x04]);
+/// ```
+pub type EndianArcSlice<Endian> = EndianReader<Endian, Arc<[u8]>>;
+
+/// A thread-safe reader that owns a shared reference to its data, so it has
+/// no lifetime parameter.
+///
+/// This can be used to keep many DWARF objects open for a long time, such as
+/// in a service that symbolicates addresses, without storing the section data
+/// and the `Dwarf` that borrows it in the same struct. The data is usually a
+/// whole object file, such as a memory map, and each section is a range of
+/// it.
+///
+/// `StaticReader`, and so `Dwarf<StaticReader<Endian>>`, `Unit` and
+/// `DwarfSession`, are `Send + Sync` whenever `Endian` is, so they can be
+/// shared between the threads of a thread pool.
+///
+/// ```
+/// use gimli::{Dwarf, LittleEndian, SectionId, SharedBytes, StaticReader};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// # let read_file_somehow = || vec![0; 16];
+/// # let find_section_somehow = |_: SectionId| 0..0;
+/// // This could be a memory map instead.
+/// let file = SharedBytes::new(read_file_somehow());
+/// let file = StaticReader::new(file, LittleEndian);
+/// let dwarf = Dwarf::load(|id| -> gimli::Result<_> {
+///     Ok(file.range(find_section_somehow(id)))
+/// }).unwrap();
+///
+/// let dwarf = Arc::new(dwarf);
+/// let handle = {
+///     let dwarf = dwarf.clone();
+///     thread::spawn(move || dwarf.units().next().unwrap().is_none())
+/// };
+/// assert!(handle.join().unwrap());
+/// ```
+pub type StaticReader<Endian> = EndianReader<Endian, SharedBytes>;
+
+/// A shared, thread-safe owner of a buffer of bytes.
+///
+/// The owner can be any type that dereferences to the same bytes for as long
+/// as it is alive, such as a `Vec<u8>` or a memory map. It is never mutated
+/// after it is given to `SharedBytes::new`, and it is dropped once the last
+/// reader that refers to it is dropped.
+#[derive(Clone)]
+pub struct SharedBytes(Arc<dyn AsRef<[u8]> + Send + Sync>);
+
+impl SharedBytes {
+    /// Construct a new `SharedBytes` that owns the given buffer.
+    pub fn new<T>(owner: T) -> Self
+    where
+        T: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        SharedBytes(Arc::new(owner))
+    }
+}
+
+impl From<Arc<[u8]>> for SharedBytes {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        SharedBytes(Arc::new(bytes))
+    }
+}
+
+impl Deref for SharedBytes {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        (*self.0).as_ref()
+    }
+}
+
+impl Debug for SharedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedBytes {{ len: {} }}", self.len())
+    }
+}
+
+/// A `Reader` over a range of an owned or shared buffer of bytes, with an
+/// associated endianity.
+///
+/// Unlike `EndianBuf`, this does not borrow the section data, so it can be
+/// used for data that the caller creates at run time, such as decompressed
+/// sections.
+///
+/// Every clone of `T` must dereference to the same slice of bytes, at the
+/// same address, for as long as any clone is alive. This is true for
+/// `Rc<[u8]>` and `Arc<[u8]>`, which are the usual choices for `T`.
+#[derive(Debug, Clone)]
+pub struct EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    bytes: T,
+    start: usize,
+    end: usize,
+    endian: Endian,
+}
+
+impl<Endian, T> PartialEq for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.endian == other.endian && self.bytes() == other.bytes()
+    }
+}
+
+impl<Endian, T> Eq for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+}
+
+impl<Endian, T> EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    /// Construct a new `EndianReader` with the given bytes.
+    #[inline]
+    pub fn new(bytes: T, endian: Endian) -> EndianReader<Endian, T> {
+        let end = bytes.len();
+        EndianReader {
+            bytes,
+            start: 0,
+            end,
+            endian,
+        }
+    }
+
+    /// Return a reference to the remaining bytes.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[self.start..self.end]
+    }
+
+    #[inline]
+    fn take_slice(&mut self, len: usize) -> Result<&[u8]> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            let start = self.start;
+            self.start += len;
+            Ok(&self.bytes[start..start + len])
+        }
+    }
+}
+
+/// # Range Methods
+///
+/// Unfortunately, `std::ops::Index` *must* return a reference, so we can't
+/// implement `Index<Range<usize>>` to return a new `EndianReader` the way we
+/// would like to. Instead, we abandon fancy indexing operators and have these
+/// plain old methods.
+impl<Endian, T> EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    /// Take the given `start..end` range of the remaining bytes and return a
+    /// new `EndianReader`.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn range(&self, idx: Range<usize>) -> EndianReader<Endian, T> {
+        assert!(idx.start <= idx.end);
+        assert!(idx.end <= self.len());
+        EndianReader {
+            bytes: self.bytes.clone(),
+            start: self.start + idx.start,
+            end: self.start + idx.end,
+            endian: self.endian,
+        }
+    }
+
+    /// Take the given `start..` range of the remaining bytes and return a new
+    /// `EndianReader`.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn range_from(&self, idx: RangeFrom<usize>) -> EndianReader<Endian, T> {
+        self.range(idx.start..self.len())
+    }
+
+    /// Take the given `..end` range of the remaining bytes and return a new
+    /// `EndianReader`.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn range_to(&self, idx: RangeTo<usize>) -> EndianReader<Endian, T> {
+        self.range(0..idx.end)
+    }
+}
+
+impl<Endian, T> Index<usize> for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    type Output = u8;
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.bytes()[idx]
+    }
+}
+
+impl<Endian, T> Index<RangeFrom<usize>> for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    type Output = [u8];
+    fn index(&self, idx: RangeFrom<usize>) -> &Self::Output {
+        &self.bytes()[idx]
+    }
+}
+
+impl<Endian, T> Deref for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    type Target = [u8];
+    fn deref(&self) -> &Self::Target {
+        self.bytes()
+    }
+}
+
+/// Serializes the remaining bytes. The endianity is not serialized.
+#[cfg(feature = "serde")]
+impl<Endian, T> ::serde::Serialize for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_bytes(self.bytes())
+    }
+}
+
+impl<Endian, T> Reader for EndianReader<Endian, T>
+where
+    Endian: Endianity,
+    T: Deref<Target = [u8]> + Clone + Debug,
+{
+    type Endian = Endian;
+    type Offset = usize;
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    #[inline]
+    fn empty(&mut self) {
+        self.start = self.end;
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) -> Result<()> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.end = self.start + len;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn offset_from(&self, base: &Self) -> Result<usize> {
+        let base_ptr = base.bytes().as_ptr() as *const u8 as usize;
+        let ptr = self.bytes().as_ptr() as *const u8 as usize;
+        let offset = ptr.checked_sub(base_ptr).ok_or(Error::OffsetOutOfBounds)?;
+        match offset.checked_add(self.len()) {
+            Some(end) if end <= base.len() => Ok(offset),
+            _ => Err(Error::OffsetOutOfBounds),
+        }
+    }
+
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        ReaderOffsetId(self.bytes().as_ptr() as u64)
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<usize> {
+        let id = id.0;
+        let self_id = self.bytes().as_ptr() as u64;
+        let self_len = self.bytes().len() as u64;
+        if id >= self_id && id <= self_id + self_len {
+            Some((id - self_id) as usize)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn find(&self, byte: u8) -> Result<usize> {
+        find_byte(self.bytes(), byte).ok_or_else(|| Error::UnexpectedEof(self.offset_id()))
+    }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<()> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.start += len;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn split(&mut self, len: usize) -> Result<Self> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            let head = self.range_to(..len);
+            self.start += len;
+            Ok(head)
+        }
+    }
+
+    #[inline]
+    fn to_slice(&self) -> Result<Cow<'_, [u8]>> {
+        Ok(self.bytes().into())
+    }
+
+    #[inline]
+    fn to_string(&self) -> Result<Cow<'_, str>> {
+        match str::from_utf8(self.bytes()) {
+            Ok(s) => Ok(s.into()),
+            _ => Err(Error::BadUtf8),
+        }
+    }
+
+    #[inline]
+    fn to_string_lossy(&self) -> Result<Cow<'_, str>> {
+        Ok(String::from_utf8_lossy(self.bytes()))
+    }
+
+    #[inline]
+    fn read_uleb128(&mut self) -> Result<u64> {
+        let (result, len) = leb128::read::unsigned_slice(self.bytes());
+        self.start += len;
+        result
+    }
+
+    #[inline]
+    fn read_sleb128(&mut self) -> Result<i64> {
+        let (result, len) = leb128::read::signed_slice(self.bytes());
+        self.start += len;
+        result
+    }
+
+    #[inline]
+    fn skip_leb128(&mut self) -> Result<()> {
+        let (result, len) = leb128::read::skip_slice(self.bytes());
+        self.start += len;
+        result
+    }
+
+    #[inline]
+    fn read_u8_array<A>(&mut self) -> Result<A>
+    where
+        A: Sized + Default + AsMut<[u8]>,
+    {
+        let len = mem::size_of::<A>();
+        let slice = self.take_slice(len)?;
+        let mut val = Default::default();
+        <A as AsMut<[u8]>>::as_mut(&mut val).clone_from_slice(slice);
+        Ok(val)
+    }
+
+    #[inline]
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        let slice = self.take_slice(buf.len())?;
+        buf.copy_from_slice(slice);
+        Ok(())
+    }
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8> {
+        let slice = self.take_slice(1)?;
+        Ok(slice[0])
+    }
+
+    #[inline]
+    fn read_i8(&mut self) -> Result<i8> {
+        let slice = self.take_slice(1)?;
+        Ok(slice[0] as i8)
+    }
+
+    #[inline]
+    fn read_u16(&mut self) -> Result<u16> {
+        let endian = self.endian;
+        let slice = self.take_slice(2)?;
+        Ok(endian.read_u16(slice))
+    }
+
+    #[inline]
+    fn read_i16(&mut self) -> Result<i16> {
+        let endian = self.endian;
+        let slice = self.take_slice(2)?;
+        Ok(endian.read_i16(slice))
+    }
+
+    #[inline]
+    fn read_u32(&mut self) -> Result<u32> {
+        let endian = self.endian;
+        let slice = self.take_slice(4)?;
+        Ok(endian.read_u32(slice))
+    }
+
+    #[inline]
+    fn read_i32(&mut self) -> Result<i32> {
+        let endian = self.endian;
+        let slice = self.take_slice(4)?;
+        Ok(endian.read_i32(slice))
+    }
+
+    #[inline]
+    fn read_u64(&mut self) -> Result<u64> {
+        let endian = self.endian;
+        let slice = self.take_slice(8)?;
+        Ok(endian.read_u64(slice))
+    }
+
+    #[inline]
+    fn read_i64(&mut self) -> Result<i64> {
+        let endian = self.endian;
+        let slice = self.take_slice(8)?;
+        Ok(endian.read_i64(slice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{BigEndian, LittleEndian};
+    use vec::Vec;
+
+    fn reader(bytes: Vec<u8>) -> EndianRcSlice<LittleEndian> {
+        EndianRcSlice::new(Rc::from(bytes), LittleEndian)
+    }
+
+    #[test]
+    fn test_endian_reader_read() {
+        let mut r = reader(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
+        assert_eq!(r.read_u8(), Ok(1));
+        assert_eq!(r.read_u16(), Ok(0x0302));
+        assert_eq!(r.read_u32(), Ok(0x0706_0504));
+        assert_eq!(r.len(), 3);
+        assert_eq!(r.read_u32(), Err(Error::UnexpectedEof(r.offset_id())));
+        assert_eq!(r.read_null_terminated_slice().map(|s| s.bytes().to_vec()), Ok(vec![8, 9]));
+        assert!(r.is_empty());
+
+        let mut r = EndianArcSlice::new(Arc::from(vec![1, 2]), BigEndian);
+        assert_eq!(r.read_u16(), Ok(0x0102));
+    }
+
+    #[test]
+    fn test_endian_reader_split() {
+        let base = reader(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
+        let mut r = base.clone();
+        r.skip(2).unwrap();
+        let mut head = r.split(3).unwrap();
+        assert_eq!(head.bytes(), &[3, 4, 5]);
+        assert_eq!(r.bytes(), &[6, 7, 8, 9, 0]);
+        assert_eq!(head.offset_from(&base), Ok(2));
+        assert_eq!(r.offset_from(&base), Ok(5));
+        assert_eq!(base.offset_from(&r), Err(Error::OffsetOutOfBounds));
+        assert_eq!(base.offset_from(&head), Err(Error::OffsetOutOfBounds));
+
+        head.truncate(1).unwrap();
+        assert_eq!(head.bytes(), &[3]);
+        assert_eq!(head.truncate(2), Err(Error::UnexpectedEof(head.offset_id())));
+        assert!(r.split(6).is_err());
+
+        assert_eq!(r.range(1..3).bytes(), &[7, 8]);
+        assert_eq!(r.range_from(3..).bytes(), &[9, 0]);
+        assert_eq!(r.range_to(..1).bytes(), &[6]);
+        assert_eq!(r[1], 7);
+        assert_eq!(r.find(0), Ok(4));
+
+        r.empty();
+        assert!(r.is_empty());
+        assert_eq!(r.skip(1), Err(Error::UnexpectedEof(r.offset_id())));
+    }
+
+    #[test]
+    fn test_static_reader() {
+        let file = SharedBytes::new(vec![1, 2, 3, 4, 5, 6]);
+        let reader = StaticReader::new(file.clone(), LittleEndian);
+        assert_eq!(format!("{:?}", file), "SharedBytes { len: 6 }");
+
+        let mut section = reader.range(2..6);
+        assert_eq!(section.read_u16(), Ok(0x0403));
+        assert_eq!(section.offset_from(&reader.range(2..6)), Ok(2));
+        assert_eq!(section, StaticReader::new(SharedBytes::new([5, 6]), LittleEndian));
+
+        let arc: Arc<[u8]> = Arc::from(vec![7, 8]);
+        let reader = StaticReader::new(SharedBytes::from(arc), LittleEndian);
+        assert_eq!(reader.bytes(), &[7, 8]);
+    }
+
+    #[test]
+    fn test_static_reader_send_sync() {
+        use dwarf::{Dwarf, Unit};
+        use endianity::RunTimeEndian;
+        use session::DwarfSession;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StaticReader<RunTimeEndian>>();
+        assert_send_sync::<EndianArcSlice<RunTimeEndian>>();
+        assert_send_sync::<Dwarf<StaticReader<RunTimeEndian>>>();
+        assert_send_sync::<Unit<StaticReader<RunTimeEndian>>>();
+        assert_send_sync::<DwarfSession<StaticReader<RunTimeEndian>>>();
+    }
+
+    #[test]
+    fn test_endian_reader_strings() {
+        let r = reader(b"hello\xff".to_vec());
+        assert_eq!(r.to_string(), Err(Error::BadUtf8));
+        assert_eq!(r.to_string_lossy().unwrap(), "hello\u{fffd}");
+        assert_eq!(r.range_to(..5).to_string().unwrap(), "hello");
+        assert_eq!(&*r.to_slice().unwrap(), b"hello\xff");
+    }
+}
@@ -8,8 +8,9 @@ use std::ops::{Deref, Index, Range, RangeFrom, RangeTo};
 use std::str;
 use string::String;
 use borrow::Cow;
+use leb128;
 use parser::{Error, Result};
-use reader::Reader;
+use reader::{find_byte, Reader, ReaderOffsetId};
 
 /// A trait describing the endianity of some buffer.
 pub trait Endianity: Debug + Default + Clone + Copy + PartialEq + Eq {
@@ -94,6 +95,60 @@ pub trait Endianity: Debug + Default + Clone + Copy + PartialEq + Eq {
         self.read_u64(buf) as i64
     }
 
+    /// Reads an unsigned 128 bit integer from `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `buf.len() < 16`.
+    #[inline]
+    fn read_u128(self, buf: &[u8]) -> u128 {
+        let first = self.read_u64(&buf[..8]) as u128;
+        let second = self.read_u64(&buf[8..16]) as u128;
+        if self.is_big_endian() {
+            (first << 64) | second
+        } else {
+            (second << 64) | first
+        }
+    }
+
+    /// Reads a signed 128 bit integer from `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `buf.len() < 16`.
+    #[inline]
+    fn read_i128(self, buf: &[u8]) -> i128 {
+        self.read_u128(buf) as i128
+    }
+
+    /// Writes an unsigned 16 bit integer `n` to `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `buf.len() < 2`.
+    #[inline]
+    fn write_u16(self, buf: &mut [u8], n: u16) {
+        if self.is_big_endian() {
+            byteorder::BigEndian::write_u16(buf, n)
+        } else {
+            byteorder::LittleEndian::write_u16(buf, n)
+        }
+    }
+
+    /// Writes an unsigned 32 bit integer `n` to `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `buf.len() < 4`.
+    #[inline]
+    fn write_u32(self, buf: &mut [u8], n: u32) {
+        if self.is_big_endian() {
+            byteorder::BigEndian::write_u32(buf, n)
+        } else {
+            byteorder::LittleEndian::write_u32(buf, n)
+        }
+    }
+
     /// Writes an unsigned 64 bit integer `n` to `buf`.
     ///
     /// # Panics
@@ -111,6 +166,7 @@ pub trait Endianity: Debug + Default + Clone + Copy + PartialEq + Eq {
 
 /// Byte order that is selectable at runtime.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum RunTimeEndian {
     /// Little endian byte order.
     Little,
@@ -141,6 +197,7 @@ impl Endianity for RunTimeEndian {
 
 /// Little endian byte order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LittleEndian;
 
 impl Default for LittleEndian {
@@ -159,6 +216,7 @@ impl Endianity for LittleEndian {
 
 /// Big endian byte order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct BigEndian;
 
 impl Default for BigEndian {
@@ -230,18 +288,22 @@ where
     /// Find the first occurence of a byte in the buffer, and return its index.
     #[inline]
     pub fn find(&self, byte: u8) -> Option<usize> {
-        self.buf.iter().position(|ch| *ch == byte)
+        find_byte(self.buf, byte)
     }
 
     /// Return the offset of the start of the buffer relative to the start
     /// of the given buffer.
+    ///
+    /// Returns an error if this buffer is not contained within the given buffer.
     #[inline]
-    pub fn offset_from(&self, base: EndianBuf<'input, Endian>) -> usize {
+    pub fn offset_from(&self, base: EndianBuf<'input, Endian>) -> Result<usize> {
         let base_ptr = base.buf.as_ptr() as *const u8 as usize;
         let ptr = self.buf.as_ptr() as *const u8 as usize;
-        debug_assert!(base_ptr <= ptr);
-        debug_assert!(ptr + self.buf.len() <= base_ptr + base.buf.len());
-        ptr - base_ptr
+        let offset = ptr.checked_sub(base_ptr).ok_or(Error::OffsetOutOfBounds)?;
+        match offset.checked_add(self.buf.len()) {
+            Some(end) if end <= base.buf.len() => Ok(offset),
+            _ => Err(Error::OffsetOutOfBounds),
+        }
     }
 
     /// Converts the buffer to a string using `str::from_utf8`.
@@ -260,9 +322,9 @@ where
     }
 
     #[inline]
-    fn read_slice(&mut self, len: usize) -> Result<&'input [u8]> {
+    fn take_slice(&mut self, len: usize) -> Result<&'input [u8]> {
         if self.buf.len() < len {
-            Err(Error::UnexpectedEof)
+            Err(Error::UnexpectedEof(self.offset_id()))
         } else {
             let val = &self.buf[..len];
             self.buf = &self.buf[len..];
@@ -375,6 +437,20 @@ where
     }
 }
 
+/// Serializes the bytes of the buffer. The endianity is not serialized.
+#[cfg(feature = "serde")]
+impl<'input, Endian> ::serde::Serialize for EndianBuf<'input, Endian>
+where
+    Endian: Endianity,
+{
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_bytes(self.buf)
+    }
+}
+
 impl<'input, Endian> Reader for EndianBuf<'input, Endian>
 where
     Endian: Endianity,
@@ -405,7 +481,7 @@ where
     #[inline]
     fn truncate(&mut self, len: usize) -> Result<()> {
         if self.buf.len() < len {
-            Err(Error::UnexpectedEof)
+            Err(Error::UnexpectedEof(self.offset_id()))
         } else {
             self.buf = &self.buf[..len];
             Ok(())
@@ -413,19 +489,36 @@ where
     }
 
     #[inline]
-    fn offset_from(&self, base: &Self) -> usize {
+    fn offset_from(&self, base: &Self) -> Result<usize> {
         self.offset_from(*base)
     }
 
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        ReaderOffsetId(self.buf.as_ptr() as u64)
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<usize> {
+        let id = id.0;
+        let self_id = self.buf.as_ptr() as u64;
+        let self_len = self.buf.len() as u64;
+        if id >= self_id && id <= self_id + self_len {
+            Some((id - self_id) as usize)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     fn find(&self, byte: u8) -> Result<usize> {
-        self.find(byte).ok_or(Error::UnexpectedEof)
+        self.find(byte).ok_or_else(|| Error::UnexpectedEof(self.offset_id()))
     }
 
     #[inline]
     fn skip(&mut self, len: usize) -> Result<()> {
         if self.buf.len() < len {
-            Err(Error::UnexpectedEof)
+            Err(Error::UnexpectedEof(self.offset_id()))
         } else {
             self.buf = &self.buf[len..];
             Ok(())
@@ -434,7 +527,7 @@ where
 
     #[inline]
     fn split(&mut self, len: usize) -> Result<Self> {
-        let slice = self.read_slice(len)?;
+        let slice = self.take_slice(len)?;
         Ok(EndianBuf::new(slice, self.endian))
     }
 
@@ -456,63 +549,91 @@ where
         Ok(String::from_utf8_lossy(self.buf))
     }
 
+    #[inline]
+    fn read_uleb128(&mut self) -> Result<u64> {
+        let (result, len) = leb128::read::unsigned_slice(self.buf);
+        self.buf = &self.buf[len..];
+        result
+    }
+
+    #[inline]
+    fn read_sleb128(&mut self) -> Result<i64> {
+        let (result, len) = leb128::read::signed_slice(self.buf);
+        self.buf = &self.buf[len..];
+        result
+    }
+
+    #[inline]
+    fn skip_leb128(&mut self) -> Result<()> {
+        let (result, len) = leb128::read::skip_slice(self.buf);
+        self.buf = &self.buf[len..];
+        result
+    }
+
     #[inline]
     fn read_u8_array<A>(&mut self) -> Result<A>
     where
         A: Sized + Default + AsMut<[u8]>,
     {
         let len = mem::size_of::<A>();
-        let slice = self.read_slice(len)?;
+        let slice = self.take_slice(len)?;
         let mut val = Default::default();
         <A as AsMut<[u8]>>::as_mut(&mut val).clone_from_slice(slice);
         Ok(val)
     }
 
+    #[inline]
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        let slice = self.take_slice(buf.len())?;
+        buf.copy_from_slice(slice);
+        Ok(())
+    }
+
     #[inline]
     fn read_u8(&mut self) -> Result<u8> {
-        let slice = self.read_slice(1)?;
+        let slice = self.take_slice(1)?;
         Ok(slice[0])
     }
 
     #[inline]
     fn read_i8(&mut self) -> Result<i8> {
-        let slice = self.read_slice(1)?;
+        let slice = self.take_slice(1)?;
         Ok(slice[0] as i8)
     }
 
     #[inline]
     fn read_u16(&mut self) -> Result<u16> {
-        let slice = self.read_slice(2)?;
+        let slice = self.take_slice(2)?;
         Ok(self.endian.read_u16(slice))
     }
 
     #[inline]
     fn read_i16(&mut self) -> Result<i16> {
-        let slice = self.read_slice(2)?;
+        let slice = self.take_slice(2)?;
         Ok(self.endian.read_i16(slice))
     }
 
     #[inline]
     fn read_u32(&mut self) -> Result<u32> {
-        let slice = self.read_slice(4)?;
+        let slice = self.take_slice(4)?;
         Ok(self.endian.read_u32(slice))
     }
 
     #[inline]
     fn read_i32(&mut self) -> Result<i32> {
-        let slice = self.read_slice(4)?;
+        let slice = self.take_slice(4)?;
         Ok(self.endian.read_i32(slice))
     }
 
     #[inline]
     fn read_u64(&mut self) -> Result<u64> {
-        let slice = self.read_slice(8)?;
+        let slice = self.take_slice(8)?;
         Ok(self.endian.read_u64(slice))
     }
 
     #[inline]
     fn read_i64(&mut self) -> Result<i64> {
-        let slice = self.read_slice(8)?;
+        let slice = self.take_slice(8)?;
         Ok(self.endian.read_i64(slice))
     }
 }
@@ -542,4 +663,83 @@ mod tests {
         let eb = EndianBuf::new(&buf, NativeEndian);
         eb.split_at(30);
     }
+
+    #[test]
+    fn test_endian_buf_offset_from() {
+        let buf = [1, 2, 3, 4, 5, 6, 7, 8, 9, 0];
+        let eb = EndianBuf::new(&buf, NativeEndian);
+        let (head, tail) = eb.split_at(3);
+        assert_eq!(head.offset_from(eb), Ok(0));
+        assert_eq!(tail.offset_from(eb), Ok(3));
+        assert_eq!(eb.offset_from(tail), Err(Error::OffsetOutOfBounds));
+        assert_eq!(tail.offset_from(head), Err(Error::OffsetOutOfBounds));
+    }
+
+    #[test]
+    fn test_endian_buf_read_slice() {
+        let buf = [1, 2, 3, 4, 5, 6, 7, 8, 9, 0];
+        let mut eb = EndianBuf::new(&buf, NativeEndian);
+        let mut data = [0; 4];
+        assert_eq!(eb.read_slice(&mut data), Ok(()));
+        assert_eq!(data, [1, 2, 3, 4]);
+        assert_eq!(eb.read_bytes(2), Ok(EndianBuf::new(&buf[4..6], NativeEndian)));
+        assert_eq!(eb.read_slice(&mut data), Ok(()));
+        assert_eq!(data, [7, 8, 9, 0]);
+        assert_eq!(
+            eb.read_slice(&mut data),
+            Err(Error::UnexpectedEof(eb.offset_id()))
+        );
+    }
+
+    #[test]
+    fn test_endian_buf_read_float() {
+        let buf = [
+            0x00, 0x00, 0xc0, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0xc0,
+        ];
+        let mut eb = EndianBuf::new(&buf, LittleEndian);
+        assert_eq!(eb.read_f32(), Ok(1.5));
+        assert_eq!(eb.read_f64(), Ok(-2.5));
+        assert_eq!(eb.read_f32(), Err(Error::UnexpectedEof(eb.offset_id())));
+
+        let buf = [0x3f, 0xc0, 0x00, 0x00];
+        let mut eb = EndianBuf::new(&buf, BigEndian);
+        assert_eq!(eb.read_f32(), Ok(1.5));
+    }
+
+    #[test]
+    fn test_endian_buf_find() {
+        let mut buf = [0xff; 40];
+        let eb = EndianBuf::new(&buf, NativeEndian);
+        assert_eq!(eb.find(0), None);
+        assert_eq!(Reader::find(&eb, 0), Err(Error::UnexpectedEof(eb.offset_id())));
+
+        // Check every position, including those in the trailing partial word.
+        for i in 0..buf.len() {
+            buf[i] = 0;
+            for start in 0..i + 1 {
+                let eb = EndianBuf::new(&buf[start..], NativeEndian);
+                assert_eq!(eb.find(0), Some(i - start));
+                assert_eq!(eb.find(0x80), None);
+            }
+            buf[i] = 0x80;
+            let eb = EndianBuf::new(&buf, NativeEndian);
+            assert_eq!(eb.find(0x80), Some(i));
+            assert_eq!(eb.find(0x7f), None);
+            buf[i] = 0xff;
+        }
+    }
+
+    #[test]
+    fn test_endian_buf_offset_id() {
+        let buf = [1, 2, 3, 4, 5, 6, 7, 8, 9, 0];
+        let eb = EndianBuf::new(&buf, NativeEndian);
+        let mut rest = eb;
+        rest.skip(3).unwrap();
+        assert_eq!(eb.lookup_offset_id(rest.offset_id()), Some(3));
+        assert_eq!(rest.lookup_offset_id(eb.offset_id()), None);
+        assert_eq!(
+            rest.read_u64(),
+            Err(Error::UnexpectedEof(ReaderOffsetId(eb.offset_id().0 + 3)))
+        );
+    }
 }
@@ -0,0 +1,960 @@
+//! Functions for parsing the `.gdb_index` section.
+//!
+//! This section is created by GDB and `gdb-add-index`, and also by linkers
+//! such as gold and lld when given `--gdb-index`. It is an alternative to the
+//! DWARF 5 `.debug_names` section.
+
+use fallible_iterator::FallibleIterator;
+
+use endianity::{EndianBuf, Endianity};
+use parser::{Error, Result};
+use reader::{Reader, ReaderOffset};
+use unit::{DebugInfoOffset, DebugTypeSignature, DebugTypesOffset, UnitOffset};
+use {Section, SectionId};
+
+/// The `GdbIndex` struct represents the index found in the `.gdb_index`
+/// section.
+///
+/// The contents of this section are always little endian, so the reader
+/// should use `LittleEndian` regardless of the endianity of the object file.
+#[derive(Debug, Clone, Copy)]
+pub struct GdbIndex<R: Reader> {
+    gdb_index_section: R,
+}
+
+impl<'input, Endian> GdbIndex<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `GdbIndex` instance from the data in the `.gdb_index`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.gdb_index` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{GdbIndex, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_gdb_index_section_somehow = || &buf;
+    /// let gdb_index = GdbIndex::new(read_gdb_index_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(gdb_index_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(gdb_index_section, endian))
+    }
+}
+
+impl<R: Reader> GdbIndex<R> {
+    /// Parse the header of the index, and return the tables that it contains.
+    ///
+    /// Only versions 7 and 8 of the index are supported. Earlier versions are
+    /// no longer produced by GDB, and use a different hash function.
+    pub fn table(&self) -> Result<GdbIndexTable<R>> {
+        GdbIndexTable::parse(self.gdb_index_section.clone())
+    }
+}
+
+impl<R: Reader> Section<R> for GdbIndex<R> {
+    fn id() -> SectionId {
+        SectionId::GdbIndex
+    }
+
+    fn reader(&self) -> &R {
+        &self.gdb_index_section
+    }
+}
+
+impl<R: Reader> From<R> for GdbIndex<R> {
+    fn from(gdb_index_section: R) -> Self {
+        GdbIndex { gdb_index_section }
+    }
+}
+
+/// An entry in the compilation unit list of a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexCompUnit<T = usize> {
+    offset: DebugInfoOffset<T>,
+    length: u64,
+}
+
+impl<T: ReaderOffset> GdbIndexCompUnit<T> {
+    /// Get the offset of the compilation unit in the `.debug_info` section.
+    #[inline]
+    pub fn offset(&self) -> DebugInfoOffset<T> {
+        self.offset
+    }
+
+    /// Get the length of the compilation unit, including its header.
+    #[inline]
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+}
+
+/// An entry in the type unit list of a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexTypeUnit<T = usize> {
+    offset: DebugTypesOffset<T>,
+    type_offset: UnitOffset<T>,
+    signature: DebugTypeSignature,
+}
+
+impl<T: ReaderOffset> GdbIndexTypeUnit<T> {
+    /// Get the offset of the type unit in the `.debug_types` section.
+    #[inline]
+    pub fn offset(&self) -> DebugTypesOffset<T> {
+        self.offset
+    }
+
+    /// Get the offset of the type's entry within the type unit.
+    #[inline]
+    pub fn type_offset(&self) -> UnitOffset<T> {
+        self.type_offset
+    }
+
+    /// Get the type signature of the type unit.
+    #[inline]
+    pub fn signature(&self) -> DebugTypeSignature {
+        self.signature
+    }
+}
+
+/// A unit that is referred to by a unit index in a `.gdb_index` section.
+///
+/// Unit indexes count the compilation units first, followed by the type
+/// units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbIndexUnit<T = usize> {
+    /// A unit in the compilation unit list.
+    Comp(GdbIndexCompUnit<T>),
+    /// A unit in the type unit list.
+    Type(GdbIndexTypeUnit<T>),
+}
+
+/// An entry in the address area of a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexAddress {
+    begin: u64,
+    end: u64,
+    comp_unit: u32,
+}
+
+impl GdbIndexAddress {
+    /// Get the first address in the range.
+    #[inline]
+    pub fn begin(&self) -> u64 {
+        self.begin
+    }
+
+    /// Get the first address after the range.
+    #[inline]
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Get the index in the compilation unit list of the compilation unit
+    /// that contains the range.
+    #[inline]
+    pub fn comp_unit(&self) -> u32 {
+        self.comp_unit
+    }
+}
+
+/// A name in the symbol table of a `.gdb_index` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GdbIndexSymbol<R: Reader> {
+    slot: u32,
+    name: R,
+    cu_vector: R,
+}
+
+impl<R: Reader> GdbIndexSymbol<R> {
+    /// Get the index of this name's slot in the symbol hash table.
+    #[inline]
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Get the name, without its null terminator.
+    #[inline]
+    pub fn name(&self) -> &R {
+        &self.name
+    }
+
+    /// Iterate over the units that define this name.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn units(&self) -> GdbIndexSymbolUnitIter<R> {
+        GdbIndexSymbolUnitIter {
+            input: self.cu_vector.clone(),
+        }
+    }
+}
+
+/// The kind of a symbol in a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbIndexSymbolKind {
+    /// The kind is not recorded. This is only used by older producers.
+    None,
+    /// A type, such as a typedef, struct or enum.
+    Type,
+    /// A variable or enumerator.
+    Variable,
+    /// A function.
+    Function,
+    /// Any other kind of symbol, such as a macro.
+    Other,
+    /// A kind that is reserved for future use.
+    Unknown(u8),
+}
+
+impl GdbIndexSymbolKind {
+    fn from_u8(kind: u8) -> Self {
+        match kind {
+            0 => GdbIndexSymbolKind::None,
+            1 => GdbIndexSymbolKind::Type,
+            2 => GdbIndexSymbolKind::Variable,
+            3 => GdbIndexSymbolKind::Function,
+            4 => GdbIndexSymbolKind::Other,
+            _ => GdbIndexSymbolKind::Unknown(kind),
+        }
+    }
+}
+
+/// A reference from a name in the symbol table to a unit that defines it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexSymbolUnit {
+    unit: u32,
+    kind: GdbIndexSymbolKind,
+    is_static: bool,
+}
+
+impl GdbIndexSymbolUnit {
+    fn from_u32(value: u32) -> Self {
+        GdbIndexSymbolUnit {
+            unit: value & 0x00ff_ffff,
+            kind: GdbIndexSymbolKind::from_u8(((value >> 28) & 0x7) as u8),
+            is_static: value & 0x8000_0000 != 0,
+        }
+    }
+
+    /// Get the unit index. This can be passed to `GdbIndexTable::unit`.
+    #[inline]
+    pub fn unit(&self) -> u32 {
+        self.unit
+    }
+
+    /// Get the kind of the symbol.
+    #[inline]
+    pub fn kind(&self) -> GdbIndexSymbolKind {
+        self.kind
+    }
+
+    /// Return true if the symbol is local to its unit, such as a `static`
+    /// function in C.
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+}
+
+const COMP_UNIT_SIZE: u32 = 16;
+const TYPE_UNIT_SIZE: u32 = 24;
+const ADDRESS_SIZE: u32 = 20;
+const SYMBOL_SIZE: u32 = 8;
+
+/// The tables in a `.gdb_index` section.
+///
+/// ```
+/// # fn foo() -> gimli::Result<()> {
+/// use gimli::{GdbIndex, GdbIndexUnit, LittleEndian};
+///
+/// # let buf = [];
+/// # let read_gdb_index_section_somehow = || &buf;
+/// let gdb_index = GdbIndex::new(read_gdb_index_section_somehow(), LittleEndian);
+///
+/// let table = gdb_index.table()?;
+/// if let Some(symbol) = table.find("main")? {
+///     let mut units = symbol.units();
+///     while let Some(unit) = units.next()? {
+///         if let GdbIndexUnit::Comp(unit) = table.unit(unit.unit())? {
+///             println!("main: {:?}", unit.offset());
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GdbIndexTable<R: Reader> {
+    version: u32,
+    comp_unit_count: u32,
+    type_unit_count: u32,
+    address_count: u32,
+    symbol_count: u32,
+    comp_unit_list: R,
+    type_unit_list: R,
+    address_area: R,
+    symbol_table: R,
+    constant_pool: R,
+}
+
+/// Get the section data between the offsets `start` and `end`.
+fn section_range<R: Reader>(section: &R, start: u32, end: u32) -> Result<R> {
+    if end < start {
+        return Err(Error::BadLength);
+    }
+    let mut input = section.clone();
+    input.skip(R::Offset::from_u32(start))?;
+    input.truncate(R::Offset::from_u32(end - start))?;
+    Ok(input)
+}
+
+/// Skip to the element at `index` in an array of elements of `size` bytes
+/// each.
+fn array_element<R: Reader>(array: &R, index: u32, size: u32) -> Result<R> {
+    let mut input = array.clone();
+    let offset = u64::from(index) * u64::from(size);
+    input.skip(R::Offset::from_u64(offset)?)?;
+    Ok(input)
+}
+
+impl<R: Reader> GdbIndexTable<R> {
+    fn parse(section: R) -> Result<GdbIndexTable<R>> {
+        let mut input = section.clone();
+        let version = input.read_u32()?;
+        if version != 7 && version != 8 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+        let comp_unit_list_offset = input.read_u32()?;
+        let type_unit_list_offset = input.read_u32()?;
+        let address_area_offset = input.read_u32()?;
+        let symbol_table_offset = input.read_u32()?;
+        let constant_pool_offset = input.read_u32()?;
+
+        let comp_unit_list =
+            section_range(&section, comp_unit_list_offset, type_unit_list_offset)?;
+        let type_unit_list =
+            section_range(&section, type_unit_list_offset, address_area_offset)?;
+        let address_area = section_range(&section, address_area_offset, symbol_table_offset)?;
+        let symbol_table = section_range(&section, symbol_table_offset, constant_pool_offset)?;
+        let mut constant_pool = section.clone();
+        constant_pool.skip(R::Offset::from_u32(constant_pool_offset))?;
+
+        let symbol_count = (constant_pool_offset - symbol_table_offset) / SYMBOL_SIZE;
+        // The hash table lookup relies on the size being a power of two.
+        if symbol_count != 0 && !symbol_count.is_power_of_two() {
+            return Err(Error::BadLength);
+        }
+
+        Ok(GdbIndexTable {
+            version,
+            comp_unit_count: (type_unit_list_offset - comp_unit_list_offset) / COMP_UNIT_SIZE,
+            type_unit_count: (address_area_offset - type_unit_list_offset) / TYPE_UNIT_SIZE,
+            address_count: (symbol_table_offset - address_area_offset) / ADDRESS_SIZE,
+            symbol_count,
+            comp_unit_list,
+            type_unit_list,
+            address_area,
+            symbol_table,
+            constant_pool,
+        })
+    }
+
+    /// Compute the hash of a name, as used by the symbol hash table.
+    ///
+    /// ASCII letters are folded to lowercase before hashing.
+    pub fn hash(name: &[u8]) -> u32 {
+        name.iter().fold(0u32, |hash, &byte| {
+            hash.wrapping_mul(67)
+                .wrapping_add(u32::from(byte.to_ascii_lowercase()))
+                .wrapping_sub(113)
+        })
+    }
+
+    /// Get the version of the index.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Get the number of compilation units in the compilation unit list.
+    #[inline]
+    pub fn comp_unit_count(&self) -> u32 {
+        self.comp_unit_count
+    }
+
+    /// Get the number of type units in the type unit list.
+    #[inline]
+    pub fn type_unit_count(&self) -> u32 {
+        self.type_unit_count
+    }
+
+    /// Get the number of entries in the address area.
+    #[inline]
+    pub fn address_count(&self) -> u32 {
+        self.address_count
+    }
+
+    /// Get the number of slots in the symbol hash table, including empty
+    /// slots.
+    #[inline]
+    pub fn symbol_count(&self) -> u32 {
+        self.symbol_count
+    }
+
+    /// Get the compilation unit at the given index in the compilation unit
+    /// list.
+    pub fn comp_unit(&self, index: u32) -> Result<GdbIndexCompUnit<R::Offset>> {
+        if index >= self.comp_unit_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let mut input = array_element(&self.comp_unit_list, index, COMP_UNIT_SIZE)?;
+        let offset = input.read_u64().and_then(R::Offset::from_u64)?;
+        let length = input.read_u64()?;
+        Ok(GdbIndexCompUnit {
+            offset: DebugInfoOffset(offset),
+            length,
+        })
+    }
+
+    /// Get the type unit at the given index in the type unit list.
+    pub fn type_unit(&self, index: u32) -> Result<GdbIndexTypeUnit<R::Offset>> {
+        if index >= self.type_unit_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let mut input = array_element(&self.type_unit_list, index, TYPE_UNIT_SIZE)?;
+        let offset = input.read_u64().and_then(R::Offset::from_u64)?;
+        let type_offset = input.read_u64().and_then(R::Offset::from_u64)?;
+        let signature = input.read_u64()?;
+        Ok(GdbIndexTypeUnit {
+            offset: DebugTypesOffset(offset),
+            type_offset: UnitOffset(type_offset),
+            signature: DebugTypeSignature(signature),
+        })
+    }
+
+    /// Get the unit for a unit index, such as from `GdbIndexSymbolUnit::unit`.
+    pub fn unit(&self, index: u32) -> Result<GdbIndexUnit<R::Offset>> {
+        if index < self.comp_unit_count {
+            self.comp_unit(index).map(GdbIndexUnit::Comp)
+        } else {
+            self.type_unit(index - self.comp_unit_count)
+                .map(GdbIndexUnit::Type)
+        }
+    }
+
+    /// Get the entry at the given index in the address area.
+    pub fn address(&self, index: u32) -> Result<GdbIndexAddress> {
+        if index >= self.address_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let mut input = array_element(&self.address_area, index, ADDRESS_SIZE)?;
+        let begin = input.read_u64()?;
+        let end = input.read_u64()?;
+        let comp_unit = input.read_u32()?;
+        Ok(GdbIndexAddress {
+            begin,
+            end,
+            comp_unit,
+        })
+    }
+
+    /// Get the name in the given slot of the symbol hash table, or `None` if
+    /// the slot is empty.
+    pub fn symbol(&self, slot: u32) -> Result<Option<GdbIndexSymbol<R>>> {
+        if slot >= self.symbol_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let mut input = array_element(&self.symbol_table, slot, SYMBOL_SIZE)?;
+        let name_offset = input.read_u32()?;
+        let cu_vector_offset = input.read_u32()?;
+        if name_offset == 0 && cu_vector_offset == 0 {
+            return Ok(None);
+        }
+
+        let mut name = self.constant_pool.clone();
+        name.skip(R::Offset::from_u32(name_offset))?;
+        let name = name.read_null_terminated_slice()?;
+
+        let mut cu_vector = self.constant_pool.clone();
+        cu_vector.skip(R::Offset::from_u32(cu_vector_offset))?;
+        let count = cu_vector.read_u32()?;
+        cu_vector.truncate(R::Offset::from_u64(u64::from(count) * 4)?)?;
+
+        Ok(Some(GdbIndexSymbol {
+            slot,
+            name,
+            cu_vector,
+        }))
+    }
+
+    /// Iterate over the compilation unit list.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn comp_units(&self) -> GdbIndexCompUnitIter<'_, R> {
+        GdbIndexCompUnitIter {
+            table: self,
+            next: 0,
+        }
+    }
+
+    /// Iterate over the type unit list.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn type_units(&self) -> GdbIndexTypeUnitIter<'_, R> {
+        GdbIndexTypeUnitIter {
+            table: self,
+            next: 0,
+        }
+    }
+
+    /// Iterate over the address area.
+    ///
+    /// The entries are sorted by address.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn addresses(&self) -> GdbIndexAddressIter<'_, R> {
+        GdbIndexAddressIter {
+            table: self,
+            next: 0,
+        }
+    }
+
+    /// Iterate over the names in the symbol hash table, skipping empty slots.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn symbols(&self) -> GdbIndexSymbolIter<'_, R> {
+        GdbIndexSymbolIter {
+            table: self,
+            next: 0,
+        }
+    }
+
+    /// Find the name in the symbol hash table that exactly matches the given
+    /// name.
+    pub fn find(&self, name: &str) -> Result<Option<GdbIndexSymbol<R>>> {
+        if self.symbol_count == 0 {
+            return Ok(None);
+        }
+        let mask = self.symbol_count - 1;
+        let hash = Self::hash(name.as_bytes());
+        let step = (hash.wrapping_mul(17) & mask) | 1;
+        let mut slot = hash & mask;
+        // The table is never full, but don't loop forever if it is.
+        for _ in 0..self.symbol_count {
+            match self.symbol(slot)? {
+                Some(symbol) => {
+                    if &*symbol.name.to_slice()? == name.as_bytes() {
+                        return Ok(Some(symbol));
+                    }
+                }
+                None => return Ok(None),
+            }
+            slot = (slot + step) & mask;
+        }
+        Ok(None)
+    }
+}
+
+/// An iterator over the compilation unit list of a `.gdb_index` section.
+///
+/// See the [documentation on
+/// `GdbIndexTable::comp_units`](./struct.GdbIndexTable.html#method.comp_units) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct GdbIndexCompUnitIter<'table, R: Reader + 'table> {
+    table: &'table GdbIndexTable<R>,
+    next: u32,
+}
+
+impl<'table, R: Reader> GdbIndexCompUnitIter<'table, R> {
+    /// Advance the iterator to the next compilation unit.
+    pub fn next(&mut self) -> Result<Option<GdbIndexCompUnit<R::Offset>>> {
+        if self.next >= self.table.comp_unit_count {
+            return Ok(None);
+        }
+        match self.table.comp_unit(self.next) {
+            Ok(item) => {
+                self.next += 1;
+                Ok(Some(item))
+            }
+            Err(e) => {
+                self.next = self.table.comp_unit_count;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'table, R: Reader> FallibleIterator for GdbIndexCompUnitIter<'table, R> {
+    type Item = GdbIndexCompUnit<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexCompUnitIter::next(self)
+    }
+}
+
+/// An iterator over the type unit list of a `.gdb_index` section.
+///
+/// See the [documentation on
+/// `GdbIndexTable::type_units`](./struct.GdbIndexTable.html#method.type_units) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct GdbIndexTypeUnitIter<'table, R: Reader + 'table> {
+    table: &'table GdbIndexTable<R>,
+    next: u32,
+}
+
+impl<'table, R: Reader> GdbIndexTypeUnitIter<'table, R> {
+    /// Advance the iterator to the next type unit.
+    pub fn next(&mut self) -> Result<Option<GdbIndexTypeUnit<R::Offset>>> {
+        if self.next >= self.table.type_unit_count {
+            return Ok(None);
+        }
+        match self.table.type_unit(self.next) {
+            Ok(item) => {
+                self.next += 1;
+                Ok(Some(item))
+            }
+            Err(e) => {
+                self.next = self.table.type_unit_count;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'table, R: Reader> FallibleIterator for GdbIndexTypeUnitIter<'table, R> {
+    type Item = GdbIndexTypeUnit<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexTypeUnitIter::next(self)
+    }
+}
+
+/// An iterator over the address area of a `.gdb_index` section.
+///
+/// See the [documentation on
+/// `GdbIndexTable::addresses`](./struct.GdbIndexTable.html#method.addresses) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct GdbIndexAddressIter<'table, R: Reader + 'table> {
+    table: &'table GdbIndexTable<R>,
+    next: u32,
+}
+
+impl<'table, R: Reader> GdbIndexAddressIter<'table, R> {
+    /// Advance the iterator to the next address range.
+    pub fn next(&mut self) -> Result<Option<GdbIndexAddress>> {
+        if self.next >= self.table.address_count {
+            return Ok(None);
+        }
+        match self.table.address(self.next) {
+            Ok(item) => {
+                self.next += 1;
+                Ok(Some(item))
+            }
+            Err(e) => {
+                self.next = self.table.address_count;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'table, R: Reader> FallibleIterator for GdbIndexAddressIter<'table, R> {
+    type Item = GdbIndexAddress;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexAddressIter::next(self)
+    }
+}
+
+/// An iterator over the names in the symbol hash table of a `.gdb_index`
+/// section.
+///
+/// See the [documentation on
+/// `GdbIndexTable::symbols`](./struct.GdbIndexTable.html#method.symbols) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct GdbIndexSymbolIter<'table, R: Reader + 'table> {
+    table: &'table GdbIndexTable<R>,
+    next: u32,
+}
+
+impl<'table, R: Reader> GdbIndexSymbolIter<'table, R> {
+    /// Advance the iterator to the next name.
+    pub fn next(&mut self) -> Result<Option<GdbIndexSymbol<R>>> {
+        while self.next < self.table.symbol_count {
+            match self.table.symbol(self.next) {
+                Ok(Some(symbol)) => {
+                    self.next += 1;
+                    return Ok(Some(symbol));
+                }
+                Ok(None) => self.next += 1,
+                Err(e) => {
+                    self.next = self.table.symbol_count;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'table, R: Reader> FallibleIterator for GdbIndexSymbolIter<'table, R> {
+    type Item = GdbIndexSymbol<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexSymbolIter::next(self)
+    }
+}
+
+/// An iterator over the units that define a name in a `.gdb_index` section.
+///
+/// See the [documentation on
+/// `GdbIndexSymbol::units`](./struct.GdbIndexSymbol.html#method.units) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct GdbIndexSymbolUnitIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> GdbIndexSymbolUnitIter<R> {
+    /// Advance the iterator to the next unit.
+    pub fn next(&mut self) -> Result<Option<GdbIndexSymbolUnit>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match self.input.read_u32() {
+            Ok(value) => Ok(Some(GdbIndexSymbolUnit::from_u32(value))),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<R: Reader> FallibleIterator for GdbIndexSymbolUnitIter<R> {
+    type Item = GdbIndexSymbolUnit;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexSymbolUnitIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use super::*;
+    use endianity::LittleEndian;
+    use vec::Vec;
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+
+    // Build an index containing the given names, and the unit vector values
+    // for each name.
+    fn gdb_index(version: u32, names: &[(&str, &[u32])], slot_count: u32) -> Vec<u8> {
+        // Insert the names into the hash table using the same probing as the
+        // lookup.
+        let mut slots = vec![None; slot_count as usize];
+        for (i, &(name, _)) in names.iter().enumerate() {
+            let mask = slot_count - 1;
+            let hash = GdbIndexTable::<EndianBuf<LittleEndian>>::hash(name.as_bytes());
+            let step = (hash.wrapping_mul(17) & mask) | 1;
+            let mut slot = hash & mask;
+            while slots[slot as usize].is_some() {
+                slot = (slot + step) & mask;
+            }
+            slots[slot as usize] = Some(i);
+        }
+
+        let cu_list = Label::new();
+        let tu_list = Label::new();
+        let address_area = Label::new();
+        let symbol_table = Label::new();
+        let constant_pool = Label::new();
+        let section = Section::with_endian(Endian::Little);
+        section.start().set_const(0);
+        let mut section = section
+            .L32(version)
+            .L32(&cu_list)
+            .L32(&tu_list)
+            .L32(&address_area)
+            .L32(&symbol_table)
+            .L32(&constant_pool)
+            .mark(&cu_list)
+            .L64(0)
+            .L64(0x40)
+            .L64(0x40)
+            .L64(0x60)
+            .mark(&tu_list)
+            .L64(0x10)
+            .L64(0x1d)
+            .L64(0x1234_5678_9abc_def0)
+            .mark(&address_area)
+            .L64(0x1000)
+            .L64(0x1100)
+            .L32(1)
+            .L64(0x2000)
+            .L64(0x2040)
+            .L32(0)
+            .mark(&symbol_table);
+
+        // The constant pool contains the unit vectors, followed by the names.
+        // Offsets are relative to the start of the constant pool.
+        let mut vector_offsets = Vec::new();
+        let mut name_offsets = Vec::new();
+        let mut pool_size = 0;
+        for &(_, units) in names {
+            vector_offsets.push(pool_size);
+            pool_size += 4 + 4 * units.len() as u32;
+        }
+        for &(name, _) in names {
+            name_offsets.push(pool_size);
+            pool_size += name.len() as u32 + 1;
+        }
+        for slot in &slots {
+            section = match *slot {
+                Some(i) => section.L32(name_offsets[i]).L32(vector_offsets[i]),
+                None => section.L32(0).L32(0),
+            };
+        }
+        section = section.mark(&constant_pool);
+        for &(_, units) in names {
+            section = section.L32(units.len() as u32);
+            for &unit in units {
+                section = section.L32(unit);
+            }
+        }
+        for &(name, _) in names {
+            section = section.append_bytes(name.as_bytes()).D8(0);
+        }
+        section.get_contents().unwrap()
+    }
+
+    const MAIN: &[u32] = &[0x3000_0000];
+    const FOO: &[u32] = &[0xb000_0001, 0x1000_0002];
+    const BAR: &[u32] = &[0x2000_0000];
+
+    #[test]
+    fn test_gdb_index_hash() {
+        // Values computed by GDB's `mapped_index_string_hash`.
+        assert_eq!(GdbIndexTable::<EndianBuf<LittleEndian>>::hash(b""), 0);
+        assert_eq!(GdbIndexTable::<EndianBuf<LittleEndian>>::hash(b"a"), 0xffff_fff0);
+        assert_eq!(
+            GdbIndexTable::<EndianBuf<LittleEndian>>::hash(b"Ab"),
+            GdbIndexTable::<EndianBuf<LittleEndian>>::hash(b"ab")
+        );
+        assert_eq!(
+            GdbIndexTable::<EndianBuf<LittleEndian>>::hash(b"ab"),
+            0xffff_fbc1
+        );
+    }
+
+    #[test]
+    fn test_gdb_index_units() {
+        let buf = gdb_index(7, &[("main", MAIN)], 4);
+        let section = GdbIndex::new(&buf, LittleEndian);
+        let table = section.table().unwrap();
+        assert_eq!(table.version(), 7);
+        assert_eq!(table.comp_unit_count(), 2);
+        assert_eq!(table.type_unit_count(), 1);
+        assert_eq!(table.address_count(), 2);
+
+        let comp_units: Vec<_> = table.comp_units().collect().unwrap();
+        assert_eq!(comp_units.len(), 2);
+        assert_eq!(comp_units[0].offset(), DebugInfoOffset(0));
+        assert_eq!(comp_units[0].length(), 0x40);
+        assert_eq!(comp_units[1].offset(), DebugInfoOffset(0x40));
+        assert_eq!(comp_units[1].length(), 0x60);
+
+        let type_units: Vec<_> = table.type_units().collect().unwrap();
+        assert_eq!(type_units.len(), 1);
+        assert_eq!(type_units[0].offset(), DebugTypesOffset(0x10));
+        assert_eq!(type_units[0].type_offset(), UnitOffset(0x1d));
+        assert_eq!(
+            type_units[0].signature(),
+            DebugTypeSignature(0x1234_5678_9abc_def0)
+        );
+
+        assert_eq!(table.unit(1), Ok(GdbIndexUnit::Comp(comp_units[1])));
+        assert_eq!(table.unit(2), Ok(GdbIndexUnit::Type(type_units[0])));
+        assert_eq!(table.unit(3), Err(Error::OffsetOutOfBounds));
+
+        let addresses: Vec<_> = table.addresses().collect().unwrap();
+        assert_eq!(addresses.len(), 2);
+        assert_eq!(addresses[0].begin(), 0x1000);
+        assert_eq!(addresses[0].end(), 0x1100);
+        assert_eq!(addresses[0].comp_unit(), 1);
+        assert_eq!(addresses[1].begin(), 0x2000);
+        assert_eq!(addresses[1].end(), 0x2040);
+        assert_eq!(addresses[1].comp_unit(), 0);
+    }
+
+    #[test]
+    fn test_gdb_index_symbols() {
+        let buf = gdb_index(8, &[("main", MAIN), ("foo", FOO), ("bar", BAR)], 8);
+        let section = GdbIndex::new(&buf, LittleEndian);
+        let table = section.table().unwrap();
+        assert_eq!(table.version(), 8);
+        assert_eq!(table.symbol_count(), 8);
+
+        let symbols: Vec<_> = table.symbols().collect().unwrap();
+        let mut names: Vec<_> = symbols
+            .iter()
+            .map(|symbol| symbol.name().to_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["bar", "foo", "main"]);
+
+        let foo = table.find("foo").unwrap().unwrap();
+        assert_eq!(foo.name().to_string(), Ok("foo".into()));
+        let units: Vec<_> = foo.units().collect().unwrap();
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].unit(), 1);
+        assert_eq!(units[0].kind(), GdbIndexSymbolKind::Function);
+        assert!(units[0].is_static());
+        assert_eq!(units[1].unit(), 2);
+        assert_eq!(units[1].kind(), GdbIndexSymbolKind::Type);
+        assert!(!units[1].is_static());
+
+        let main = table.find("main").unwrap().unwrap();
+        let units: Vec<_> = main.units().collect().unwrap();
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].kind(), GdbIndexSymbolKind::Function);
+        assert!(!units[0].is_static());
+
+        let bar = table.find("bar").unwrap().unwrap();
+        let units: Vec<_> = bar.units().collect().unwrap();
+        assert_eq!(units[0].kind(), GdbIndexSymbolKind::Variable);
+
+        assert_eq!(table.find("Foo"), Ok(None));
+        assert_eq!(table.find("baz"), Ok(None));
+    }
+
+    #[test]
+    fn test_gdb_index_version() {
+        let buf = gdb_index(6, &[], 4);
+        let section = GdbIndex::new(&buf, LittleEndian);
+        assert_eq!(section.table(), Err(Error::UnknownVersion(6)));
+
+        // The symbol table size must be a power of two.
+        let buf = gdb_index(7, &[], 3);
+        let section = GdbIndex::new(&buf, LittleEndian);
+        assert_eq!(section.table(), Err(Error::BadLength));
+    }
+}
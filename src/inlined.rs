@@ -0,0 +1,295 @@
+//! Finding the functions and inlined subroutines that contain an address.
+
+use constants;
+use dwarf::{Dwarf, Unit};
+use line::FileEntry;
+use parser::Result;
+use reader::Reader;
+use unit::{DebuggingInformationEntry, EntriesTreeNode, UnitOffset};
+use vec::Vec;
+
+/// A function, or an inlined instance of a function, that contains an
+/// address, as returned by `Dwarf::inlined_frames`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlinedFrame<R: Reader> {
+    /// The offset of the `DW_TAG_subprogram` or `DW_TAG_inlined_subroutine`
+    /// entry.
+    pub offset: UnitOffset<R::Offset>,
+
+    /// The tag of the entry, either `DW_TAG_subprogram` or
+    /// `DW_TAG_inlined_subroutine`.
+    pub tag: constants::DwTag,
+
+    /// The `DW_AT_name` of the function.
+    ///
+    /// This is found by following any `DW_AT_abstract_origin` and
+    /// `DW_AT_specification` references.
+    pub name: Option<R>,
+
+    /// The entry for the `DW_AT_call_file` attribute in the file names of the
+    /// unit's line number program.
+    ///
+    /// This is the file containing the call that was inlined, and is only
+    /// present for `DW_TAG_inlined_subroutine` entries.
+    pub call_file: Option<FileEntry<R>>,
+
+    /// The `DW_AT_call_line` attribute.
+    pub call_line: Option<u64>,
+
+    /// The `DW_AT_call_column` attribute.
+    pub call_column: Option<u64>,
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Find the function in the unit that contains the given address, and the
+    /// chain of inlined subroutines within that function that also contain
+    /// the address.
+    ///
+    /// The frames are returned with the outermost function first, and the
+    /// innermost inlined subroutine last. The location of the address within
+    /// the innermost frame can be found from the line number program, and
+    /// the location within each outer frame is given by the call location of
+    /// the frame that it contains.
+    ///
+    /// Returns an empty `Vec` if no function in the unit contains the
+    /// address.
+    pub fn inlined_frames(&self, unit: &Unit<R>, address: u64) -> Result<Vec<InlinedFrame<R>>> {
+        let mut frames = Vec::new();
+        let mut tree = unit.entries_tree(None)?;
+        let root = tree.root()?;
+        self.find_inlined_frames(unit, root, address, &mut frames)?;
+        Ok(frames)
+    }
+
+    /// Search the children of `node` for the entries that contain `address`.
+    fn find_inlined_frames(
+        &self,
+        unit: &Unit<R>,
+        node: EntriesTreeNode<R>,
+        address: u64,
+        frames: &mut Vec<InlinedFrame<R>>,
+    ) -> Result<()> {
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            let frame = {
+                let entry = child.entry();
+                match entry.tag() {
+                    constants::DW_TAG_subprogram | constants::DW_TAG_inlined_subroutine => {
+                        if !self.die_contains(unit, entry, address)? {
+                            continue;
+                        }
+                        let name = match entry
+                            .attr_value_followed(&unit.abbreviations, constants::DW_AT_name)?
+                        {
+                            Some(name) => self.attr_string(&name),
+                            None => None,
+                        };
+                        let call_file = match entry.attr(constants::DW_AT_call_file)? {
+                            Some(attr) => match (attr.udata_value(), unit.line_program.as_ref()) {
+                                (Some(file), Some(program)) => {
+                                    program.header().file(file).cloned()
+                                }
+                                _ => None,
+                            },
+                            None => None,
+                        };
+                        let call_line = match entry.attr(constants::DW_AT_call_line)? {
+                            Some(attr) => attr.udata_value(),
+                            None => None,
+                        };
+                        let call_column = match entry.attr(constants::DW_AT_call_column)? {
+                            Some(attr) => attr.udata_value(),
+                            None => None,
+                        };
+                        Some(InlinedFrame {
+                            offset: entry.offset(),
+                            tag: entry.tag(),
+                            name,
+                            call_file,
+                            call_line,
+                            call_column,
+                        })
+                    }
+                    // Skip lexical blocks that don't contain the address.
+                    // Other entries may contain nested functions, so always
+                    // search them.
+                    constants::DW_TAG_lexical_block => {
+                        let has_ranges = entry.attr(constants::DW_AT_low_pc)?.is_some()
+                            || entry.attr(constants::DW_AT_ranges)?.is_some();
+                        if has_ranges && !self.die_contains(unit, entry, address)? {
+                            continue;
+                        }
+                        None
+                    }
+                    _ => None,
+                }
+            };
+            match frame {
+                Some(frame) => {
+                    frames.push(frame);
+                    return self.find_inlined_frames(unit, child, address, frames);
+                }
+                None => {
+                    let len = frames.len();
+                    self.find_inlined_frames(unit, child, address, frames)?;
+                    if frames.len() != len {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return true if one of the address ranges of the entry contains
+    /// `address`.
+    fn die_contains(
+        &self,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+        address: u64,
+    ) -> Result<bool> {
+        Ok(self.die_ranges(unit, entry)?
+            .iter()
+            .any(|range| range.begin <= address && address < range.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use SectionId;
+
+    #[test]
+    fn test_inlined_frames() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_stmt_list, DW_FORM_sec_offset.
+            0x10, 0x17,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x2e, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x0b, 0x01,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x05, 0x1d, 0x00,
+            // DW_AT_abstract_origin, DW_FORM_ref4.
+            0x31, 0x13,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            // DW_AT_call_file, DW_FORM_data1.
+            0x58, 0x0b,
+            // DW_AT_call_line, DW_FORM_data1.
+            0x59, 0x0b,
+            // DW_AT_call_column, DW_FORM_data1.
+            0x57, 0x0b,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x47, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Root entry.
+            0x01, 0x00, 0x00, 0x00, 0x00,
+            // 0x10: Abstract instance of "inl".
+            0x03, b'i', b'n', b'l', 0x00,
+            // 0x15: Subprogram "main", [0x1000, 0x1100).
+            0x02, b'm', b'a', b'i', b'n', 0x00,
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+            // 0x27: Lexical block, [0x1008, 0x1088).
+            0x04,
+            0x08, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x80, 0x00, 0x00, 0x00,
+            // 0x34: Inlined "inl", [0x1010, 0x1030), called from 1:5:3.
+            0x05, 0x10, 0x00, 0x00, 0x00,
+            0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x20, 0x00, 0x00, 0x00,
+            0x01, 0x05, 0x03,
+            0x00,
+            0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_line = [
+            // Unit length, version, header length.
+            0x21, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x1b, 0x00, 0x00, 0x00,
+            // Minimum instruction length, maximum operations per instruction,
+            // default is_stmt, line base, line range, opcode base.
+            0x01, 0x01, 0x01, 0xfb, 0x0e, 0x0d,
+            // Standard opcode lengths.
+            0x00, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+            // No include directories.
+            0x00,
+            // File "a.c".
+            b'a', b'.', b'c', 0x00, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugLine => &debug_line[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        let frames = dwarf.inlined_frames(&unit, 0x1018).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].offset, UnitOffset(0x15));
+        assert_eq!(frames[0].tag, constants::DW_TAG_subprogram);
+        assert_eq!(frames[0].name, Some(EndianBuf::new(b"main", LittleEndian)));
+        assert_eq!(frames[0].call_file, None);
+        assert_eq!(frames[0].call_line, None);
+        assert_eq!(frames[1].offset, UnitOffset(0x34));
+        assert_eq!(frames[1].tag, constants::DW_TAG_inlined_subroutine);
+        assert_eq!(frames[1].name, Some(EndianBuf::new(b"inl", LittleEndian)));
+        assert_eq!(
+            frames[1].call_file.map(|file| file.path_name()),
+            Some(EndianBuf::new(b"a.c", LittleEndian))
+        );
+        assert_eq!(frames[1].call_line, Some(5));
+        assert_eq!(frames[1].call_column, Some(3));
+
+        let frames = dwarf.inlined_frames(&unit, 0x1004).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].offset, UnitOffset(0x15));
+
+        let frames = dwarf.inlined_frames(&unit, 0x2000).unwrap();
+        assert!(frames.is_empty());
+    }
+}
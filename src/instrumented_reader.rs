@@ -0,0 +1,403 @@
+//! A `Reader` decorator that records how much of each section is read.
+
+use borrow::Cow;
+use btree_map::{self, BTreeMap};
+use rc::Rc;
+use std::cell::RefCell;
+
+use parser::Result;
+use reader::{Reader, ReaderOffset, ReaderOffsetId};
+use SectionId;
+
+/// The number of bytes read from a section, by the kind of read operation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SectionStats {
+    /// Bytes read as fixed size integers, addresses, offsets, and arrays.
+    pub integers: u64,
+
+    /// Bytes read as LEB128 encoded integers.
+    pub leb128: u64,
+
+    /// Bytes read as null-terminated strings, or converted to slices or
+    /// strings.
+    pub strings: u64,
+
+    /// Bytes skipped without being read.
+    pub skipped: u64,
+}
+
+impl SectionStats {
+    /// The total number of bytes read or skipped.
+    pub fn total(&self) -> u64 {
+        self.integers + self.leb128 + self.strings + self.skipped
+    }
+
+    /// The number of bytes that were read, excluding those that were skipped.
+    pub fn read(&self) -> u64 {
+        self.integers + self.leb128 + self.strings
+    }
+}
+
+/// The statistics recorded by a `ReaderStatsCollector`, for each section.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReaderStats {
+    sections: BTreeMap<SectionId, SectionStats>,
+}
+
+impl ReaderStats {
+    /// Return the statistics for the given section.
+    ///
+    /// Sections that have not been read from have all counts equal to zero.
+    pub fn section(&self, id: SectionId) -> SectionStats {
+        self.sections.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Iterate over the statistics for each section that has been read from,
+    /// in order of `SectionId`.
+    pub fn iter(&self) -> btree_map::Iter<'_, SectionId, SectionStats> {
+        self.sections.iter()
+    }
+
+    fn section_mut(&mut self, id: SectionId) -> &mut SectionStats {
+        self.sections.entry(id).or_insert_with(Default::default)
+    }
+}
+
+/// Creates `InstrumentedReader`s and collects their statistics.
+///
+/// ```
+/// use gimli::{DebugStr, DebugStrOffset, EndianBuf, LittleEndian, ReaderStatsCollector,
+///             SectionId};
+///
+/// let buf = [0x01, 0x02, 0x03, 0x00];
+/// let collector = ReaderStatsCollector::new();
+/// let reader = collector.wrap(SectionId::DebugStr, EndianBuf::new(&buf, LittleEndian));
+/// let debug_str = DebugStr::from(reader);
+/// debug_str.get_str(DebugStrOffset(1)).unwrap();
+///
+/// let stats = collector.stats().section(SectionId::DebugStr);
+/// assert_eq!(stats.skipped, 1);
+/// assert_eq!(stats.strings, 3);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ReaderStatsCollector {
+    stats: Rc<RefCell<ReaderStats>>,
+}
+
+impl ReaderStatsCollector {
+    /// Construct a new collector with all counts equal to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap a reader for the given section, so that reads from it (and from
+    /// any readers split from it) are recorded by this collector.
+    ///
+    /// This can be used with `Dwarf::load` to instrument all sections.
+    pub fn wrap<R: Reader>(&self, id: SectionId, reader: R) -> InstrumentedReader<R> {
+        InstrumentedReader {
+            reader,
+            id,
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Return a snapshot of the statistics recorded so far.
+    pub fn stats(&self) -> ReaderStats {
+        self.stats.borrow().clone()
+    }
+
+    /// Reset all counts to zero.
+    pub fn reset(&self) {
+        *self.stats.borrow_mut() = ReaderStats::default();
+    }
+}
+
+/// A `Reader` that records the number of bytes read from the wrapped
+/// reader.
+///
+/// Created with `ReaderStatsCollector::wrap`.
+#[derive(Debug, Clone)]
+pub struct InstrumentedReader<R: Reader> {
+    reader: R,
+    id: SectionId,
+    stats: Rc<RefCell<ReaderStats>>,
+}
+
+impl<R: Reader> InstrumentedReader<R> {
+    /// Return the wrapped reader.
+    pub fn inner(&self) -> &R {
+        &self.reader
+    }
+
+    /// Return the section that this reader reads from.
+    pub fn section_id(&self) -> SectionId {
+        self.id
+    }
+
+    #[inline]
+    fn record<F>(&self, len: R::Offset, f: F)
+    where
+        F: FnOnce(&mut SectionStats) -> &mut u64,
+    {
+        let mut stats = self.stats.borrow_mut();
+        *f(stats.section_mut(self.id)) += len.into_u64();
+    }
+
+    /// Perform a read on the wrapped reader, and record the number of bytes
+    /// that it consumed.
+    #[inline]
+    fn consume<T, F, G>(&mut self, read: F, field: G) -> Result<T>
+    where
+        F: FnOnce(&mut R) -> Result<T>,
+        G: FnOnce(&mut SectionStats) -> &mut u64,
+    {
+        let before = self.reader.len();
+        let result = read(&mut self.reader);
+        let len = before - self.reader.len();
+        self.record(len, field);
+        result
+    }
+}
+
+impl<R: Reader> Reader for InstrumentedReader<R> {
+    type Endian = R::Endian;
+    type Offset = R::Offset;
+
+    #[inline]
+    fn endian(&self) -> Self::Endian {
+        self.reader.endian()
+    }
+
+    #[inline]
+    fn len(&self) -> Self::Offset {
+        self.reader.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.reader.is_empty()
+    }
+
+    #[inline]
+    fn empty(&mut self) {
+        self.reader.empty()
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: Self::Offset) -> Result<()> {
+        self.reader.truncate(len)
+    }
+
+    #[inline]
+    fn offset_from(&self, base: &Self) -> Result<Self::Offset> {
+        self.reader.offset_from(&base.reader)
+    }
+
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        self.reader.offset_id()
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<Self::Offset> {
+        self.reader.lookup_offset_id(id)
+    }
+
+    #[inline]
+    fn find(&self, byte: u8) -> Result<Self::Offset> {
+        self.reader.find(byte)
+    }
+
+    #[inline]
+    fn skip(&mut self, len: Self::Offset) -> Result<()> {
+        self.consume(|r| r.skip(len), |s| &mut s.skipped)
+    }
+
+    #[inline]
+    fn split(&mut self, len: Self::Offset) -> Result<Self> {
+        // The bytes are recorded when they are read from the new reader.
+        let reader = self.reader.split(len)?;
+        Ok(InstrumentedReader {
+            reader,
+            id: self.id,
+            stats: self.stats.clone(),
+        })
+    }
+
+    #[inline]
+    fn to_slice(&self) -> Result<Cow<'_, [u8]>> {
+        self.record(self.reader.len(), |s| &mut s.strings);
+        self.reader.to_slice()
+    }
+
+    #[inline]
+    fn to_string(&self) -> Result<Cow<'_, str>> {
+        self.record(self.reader.len(), |s| &mut s.strings);
+        self.reader.to_string()
+    }
+
+    #[inline]
+    fn to_string_lossy(&self) -> Result<Cow<'_, str>> {
+        self.record(self.reader.len(), |s| &mut s.strings);
+        self.reader.to_string_lossy()
+    }
+
+    #[inline]
+    fn read_u8_array<A>(&mut self) -> Result<A>
+    where
+        A: Sized + Default + AsMut<[u8]>,
+    {
+        self.consume(|r| r.read_u8_array(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.consume(|r| r.read_slice(buf), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8> {
+        self.consume(|r| r.read_u8(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_i8(&mut self) -> Result<i8> {
+        self.consume(|r| r.read_i8(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_u16(&mut self) -> Result<u16> {
+        self.consume(|r| r.read_u16(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_i16(&mut self) -> Result<i16> {
+        self.consume(|r| r.read_i16(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_u32(&mut self) -> Result<u32> {
+        self.consume(|r| r.read_u32(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_i32(&mut self) -> Result<i32> {
+        self.consume(|r| r.read_i32(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_u64(&mut self) -> Result<u64> {
+        self.consume(|r| r.read_u64(), |s| &mut s.integers)
+    }
+
+    #[inline]
+    fn read_i64(&mut self) -> Result<i64> {
+        self.consume(|r| r.read_i64(), |s| &mut s.integers)
+    }
+
+    fn read_null_terminated_slice(&mut self) -> Result<Self> {
+        let reader = self.consume(|r| r.read_null_terminated_slice(), |s| &mut s.strings)?;
+        Ok(InstrumentedReader {
+            reader,
+            id: self.id,
+            stats: self.stats.clone(),
+        })
+    }
+
+    fn read_uleb128(&mut self) -> Result<u64> {
+        self.consume(|r| r.read_uleb128(), |s| &mut s.leb128)
+    }
+
+    fn read_sleb128(&mut self) -> Result<i64> {
+        self.consume(|r| r.read_sleb128(), |s| &mut s.leb128)
+    }
+
+    fn skip_leb128(&mut self) -> Result<()> {
+        self.consume(|r| r.skip_leb128(), |s| &mut s.leb128)
+    }
+
+    fn read_uleb128_u128(&mut self) -> Result<u128> {
+        self.consume(|r| r.read_uleb128_u128(), |s| &mut s.leb128)
+    }
+
+    fn read_sleb128_i128(&mut self) -> Result<i128> {
+        self.consume(|r| r.read_sleb128_i128(), |s| &mut s.leb128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dwarf::Dwarf;
+    use endianity::{EndianBuf, LittleEndian};
+    use fallible_iterator::FallibleIterator;
+    use parser::Error;
+
+    #[test]
+    fn test_instrumented_reader() {
+        let buf = [0x01, 0x02, 0x03, 0x04, 0x85, 0x01, 0x61, 0x62, 0x00, 0x07];
+        let collector = ReaderStatsCollector::new();
+        let mut r = collector.wrap(SectionId::DebugLine, EndianBuf::new(&buf, LittleEndian));
+        assert_eq!(r.read_u16(), Ok(0x0201));
+        r.skip(2).unwrap();
+        assert_eq!(r.read_uleb128(), Ok(0x85));
+        let s = r.read_null_terminated_slice().unwrap();
+        assert_eq!(s.section_id(), SectionId::DebugLine);
+        assert_eq!(s.inner().buf(), b"ab");
+        let mut rest = r.split(1).unwrap();
+        assert_eq!(rest.read_u8(), Ok(7));
+        assert_eq!(rest.read_u8(), Err(Error::UnexpectedEof(rest.offset_id())));
+
+        let stats = collector.stats();
+        assert_eq!(
+            stats.section(SectionId::DebugLine),
+            SectionStats {
+                integers: 3,
+                leb128: 2,
+                strings: 3,
+                skipped: 2,
+            }
+        );
+        assert_eq!(stats.section(SectionId::DebugLine).total(), 10);
+        assert_eq!(stats.section(SectionId::DebugLine).read(), 8);
+        assert_eq!(stats.section(SectionId::DebugInfo), SectionStats::default());
+
+        collector.reset();
+        assert_eq!(collector.stats(), ReaderStats::default());
+    }
+
+    #[test]
+    fn test_instrumented_dwarf() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length.
+            0x07, 0x00, 0x00, 0x00,
+            // Version.
+            0x04, 0x00,
+            // Abbreviation offset.
+            0x00, 0x00, 0x00, 0x00,
+            // Address size.
+            0x08,
+        ];
+        let collector = ReaderStatsCollector::new();
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(collector.wrap(id, EndianBuf::new(data, LittleEndian)))
+        }).unwrap();
+        let units = dwarf.units().count().unwrap();
+        assert_eq!(units, 1);
+
+        let stats = collector.stats();
+        // The header is 11 bytes, and the unit length is also read when
+        // checking for padding.
+        assert_eq!(stats.section(SectionId::DebugInfo).integers, 15);
+        assert_eq!(
+            stats.iter().map(|(id, _)| *id).collect::<::vec::Vec<_>>(),
+            [SectionId::DebugInfo]
+        );
+    }
+}
@@ -62,7 +62,16 @@ fn low_bits_of_u64(val: u64) -> u8 {
 pub mod read {
     use super::{low_bits_of_byte, CONTINUATION_BIT, SIGN_BIT};
     use parser::{Error, Result};
-    use reader::Reader;
+    use reader::{Reader, ReaderOffsetId};
+
+    /// Read past a LEB128 number in the given `Reader` without decoding it.
+    ///
+    /// Both signed and unsigned numbers can be skipped, and there is no limit
+    /// on the number of bytes.
+    pub fn skip<R: Reader>(r: &mut R) -> Result<()> {
+        while r.read_u8()? & CONTINUATION_BIT != 0 {}
+        Ok(())
+    }
 
     /// Read an unsigned LEB128 number from the given `Reader` and
     /// return it or an error if reading failed.
@@ -117,6 +126,136 @@ pub mod read {
 
         Ok(result)
     }
+
+    /// Return the error for a LEB128 number that is truncated by the end of
+    /// `buf`.
+    #[cold]
+    fn slice_eof(buf: &[u8]) -> Error {
+        Error::UnexpectedEof(ReaderOffsetId(buf.as_ptr() as u64 + buf.len() as u64))
+    }
+
+    /// Decode an unsigned LEB128 number from the start of `buf`, and return
+    /// it together with the number of bytes that were consumed.
+    ///
+    /// This gives the same results as `unsigned` for slice-backed readers,
+    /// but isn't generic, so a single copy of the code is shared by all
+    /// endianities and reader types.
+    #[inline(never)]
+    pub(crate) fn unsigned_slice(buf: &[u8]) -> (Result<u64>, usize) {
+        let mut result = 0;
+        let mut shift = 0;
+
+        for (i, &byte) in buf.iter().enumerate() {
+            if shift == 63 && byte != 0x00 && byte != 0x01 {
+                return (Err(Error::BadUnsignedLeb128), i + 1);
+            }
+
+            let low_bits = low_bits_of_byte(byte) as u64;
+            result |= low_bits << shift;
+
+            if byte & CONTINUATION_BIT == 0 {
+                return (Ok(result), i + 1);
+            }
+
+            shift += 7;
+        }
+        (Err(slice_eof(buf)), buf.len())
+    }
+
+    /// Decode a signed LEB128 number from the start of `buf`, and return it
+    /// together with the number of bytes that were consumed.
+    ///
+    /// See `unsigned_slice` for why this exists.
+    #[inline(never)]
+    pub(crate) fn signed_slice(buf: &[u8]) -> (Result<i64>, usize) {
+        let mut result = 0;
+        let mut shift = 0;
+        let size = 64;
+
+        for (i, &byte) in buf.iter().enumerate() {
+            if shift == 63 && byte != 0x00 && byte != 0x7f {
+                return (Err(Error::BadSignedLeb128), i + 1);
+            }
+
+            let low_bits = low_bits_of_byte(byte) as i64;
+            result |= low_bits << shift;
+            shift += 7;
+
+            if byte & CONTINUATION_BIT == 0 {
+                if shift < size && (SIGN_BIT & byte) == SIGN_BIT {
+                    // Sign extend the result.
+                    result |= !0 << shift;
+                }
+                return (Ok(result), i + 1);
+            }
+        }
+        (Err(slice_eof(buf)), buf.len())
+    }
+
+    /// Find the length of the LEB128 number at the start of `buf`.
+    ///
+    /// See `unsigned_slice` for why this exists.
+    #[inline(never)]
+    pub(crate) fn skip_slice(buf: &[u8]) -> (Result<()>, usize) {
+        match buf.iter().position(|byte| byte & CONTINUATION_BIT == 0) {
+            Some(i) => (Ok(()), i + 1),
+            None => (Err(slice_eof(buf)), buf.len()),
+        }
+    }
+
+    /// Read an unsigned LEB128 number that may be up to 128 bits wide from
+    /// the given `Reader` and return it or an error if reading failed.
+    pub fn unsigned128<R: Reader>(r: &mut R) -> Result<u128> {
+        let mut result = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = r.read_u8()?;
+            if shift == 126 && byte > 0x03 {
+                return Err(Error::BadUnsignedLeb128);
+            }
+
+            let low_bits = low_bits_of_byte(byte) as u128;
+            result |= low_bits << shift;
+
+            if byte & CONTINUATION_BIT == 0 {
+                return Ok(result);
+            }
+
+            shift += 7;
+        }
+    }
+
+    /// Read a signed LEB128 number that may be up to 128 bits wide from the
+    /// given `Reader` and return it or an error if reading failed.
+    pub fn signed128<R: Reader>(r: &mut R) -> Result<i128> {
+        let mut result = 0;
+        let mut shift = 0;
+        let size = 128;
+        let mut byte;
+
+        loop {
+            byte = r.read_u8()?;
+            if shift == 126 && byte != 0x00 && byte != 0x01 && byte != 0x7e && byte != 0x7f {
+                return Err(Error::BadSignedLeb128);
+            }
+
+            let low_bits = low_bits_of_byte(byte) as i128;
+            result |= low_bits << shift;
+            shift += 7;
+
+            if byte & CONTINUATION_BIT == 0 {
+                break;
+            }
+        }
+
+        if shift < size && (SIGN_BIT & byte) == SIGN_BIT {
+            // Sign extend the result.
+            result |= !0 << shift;
+        }
+
+        Ok(result)
+    }
 }
 
 /// A module for writing integers encoded as LEB128.
@@ -189,6 +328,7 @@ mod tests {
     use super::{low_bits_of_byte, read, write, low_bits_of_u64, CONTINUATION_BIT};
     use endianity::{EndianBuf, NativeEndian};
     use parser::Error;
+    use reader::Reader;
     use std;
     use std::io;
 
@@ -211,6 +351,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_skip() {
+        let buf = [2u8, CONTINUATION_BIT, CONTINUATION_BIT, 1, 0x7f, CONTINUATION_BIT];
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        read::skip(&mut readable).expect("Should skip number");
+        assert_eq!(readable.len(), 5);
+        read::skip(&mut readable).expect("Should skip number");
+        assert_eq!(readable.len(), 2);
+        read::skip(&mut readable).expect("Should skip number");
+        assert_eq!(readable.len(), 1);
+        match read::skip(&mut readable) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected: {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_read_slice() {
+        // Check that the slice decoders match the generic decoders, including
+        // for errors and the number of bytes consumed.
+        let mut bufs = vec![
+            vec![],
+            vec![CONTINUATION_BIT],
+            vec![0x7f],
+            vec![0x40],
+            vec![CONTINUATION_BIT | 0x7f, 0x7e, 0xff],
+        ];
+        for last in &[0x00, 0x01, 0x02, 0x7f, 0x80] {
+            let mut buf = vec![0xff; 9];
+            buf.push(*last);
+            buf.push(0x55);
+            bufs.push(buf);
+        }
+        for buf in &bufs {
+            let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+            let expect = read::unsigned(&mut readable);
+            assert_eq!(read::unsigned_slice(buf), (expect, buf.len() - readable.len()));
+            let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+            assert_eq!(readable.read_uleb128(), expect);
+
+            let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+            let expect = read::signed(&mut readable);
+            assert_eq!(read::signed_slice(buf), (expect, buf.len() - readable.len()));
+
+            let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+            let expect = read::skip(&mut readable);
+            assert_eq!(read::skip_slice(buf), (expect, buf.len() - readable.len()));
+        }
+    }
+
     // Examples from the DWARF 4 standard, section 7.6, figure 22.
     #[test]
     fn test_read_unsigned() {
@@ -335,14 +525,20 @@ mod tests {
     fn test_read_unsigned_not_enough_data() {
         let buf = [CONTINUATION_BIT];
         let mut readable = EndianBuf::new(&buf[..], NativeEndian);
-        assert_eq!(read::unsigned(&mut readable), Err(Error::UnexpectedEof));
+        assert_eq!(
+            read::unsigned(&mut readable),
+            Err(Error::UnexpectedEof(readable.offset_id()))
+        );
     }
 
     #[test]
     fn test_read_signed_not_enough_data() {
         let buf = [CONTINUATION_BIT];
         let mut readable = EndianBuf::new(&buf[..], NativeEndian);
-        assert_eq!(read::signed(&mut readable), Err(Error::UnexpectedEof));
+        assert_eq!(
+            read::signed(&mut readable),
+            Err(Error::UnexpectedEof(readable.offset_id()))
+        );
     }
 
     #[test]
@@ -480,6 +676,53 @@ mod tests {
         assert!(read::signed(&mut readable).is_err());
     }
 
+    #[test]
+    fn test_read_128() {
+        // 2^64, which doesn't fit in a u64.
+        let buf = [
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02,
+        ];
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert!(read::unsigned(&mut readable).is_err());
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::unsigned128(&mut readable), Ok(1 << 64));
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::signed128(&mut readable), Ok(1 << 64));
+
+        // -2^64.
+        let buf = [
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7e,
+        ];
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert!(read::signed(&mut readable).is_err());
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::signed128(&mut readable), Ok(-1 << 64));
+
+        // u128::MAX, i128::MIN and i128::MAX.
+        let mut buf = [0xff; 19];
+        buf[18] = 0x03;
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::unsigned128(&mut readable), Ok(!0));
+        let mut buf = [0x80; 19];
+        buf[18] = 0x7e;
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::signed128(&mut readable), Ok(1 << 127));
+        let mut buf = [0xff; 19];
+        buf[18] = 0x01;
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::signed128(&mut readable), Ok(!(1 << 127)));
+
+        // Overflow.
+        let mut buf = [0xff; 19];
+        buf[18] = 0x04;
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::unsigned128(&mut readable), Err(Error::BadUnsignedLeb128));
+        let mut buf = [0x80; 19];
+        buf[18] = 0x7c;
+        let mut readable = EndianBuf::new(&buf[..], NativeEndian);
+        assert_eq!(read::signed128(&mut readable), Err(Error::BadSignedLeb128));
+    }
+
     #[test]
     fn test_read_multiple() {
         let buf = [2u8 | CONTINUATION_BIT, 1u8, 1u8];
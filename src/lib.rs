@@ -74,6 +74,9 @@
 //!
 //!   * [`DebugLine`](./struct.DebugLine.html): The `.debug_line` section.
 //!
+//!   * [`DebugLineStr`](./struct.DebugLineStr.html): The `.debug_line_str`
+//!   section.
+//!
 //!   * [`DebugLoc`](./struct.DebugLoc.html): The `.debug_loc` section.
 //!
 //!   * [`DebugPubNames`](./struct.DebugPubNames.html): The `.debug_pubnames`
@@ -92,6 +95,8 @@
 //!
 //!   * [`EhFrameHdr`](./struct.EhFrameHdr.html): The `.eh_frame_hdr` section.
 //!
+//!   * [`GdbIndex`](./struct.GdbIndex.html): The `.gdb_index` section.
+//!
 //! * Each section type exposes methods for accessing the debugging data encoded
 //! in that section. For example, the [`DebugInfo`](./struct.DebugInfo.html)
 //! struct has the [`units`](./struct.DebugInfo.html#method.units) method for
@@ -149,6 +154,40 @@
 //! # fn main() {}
 //! ```
 //!
+//! ## Code Size
+//!
+//! Most of `gimli` is generic over the `Reader` type, so each reader type
+//! that is used gets its own copy of the parsers. `EndianBuf<LittleEndian>`
+//! and `EndianBuf<BigEndian>` are different types, so supporting both
+//! endianities this way compiles everything twice. Instead, use
+//! [`RunTimeEndian`](./enum.RunTimeEndian.html) and choose the endianity
+//! when the file is loaded:
+//!
+//! ```
+//! use gimli::{DebugInfo, EndianBuf, RunTimeEndian};
+//!
+//! # let is_little_endian = true;
+//! # let debug_info_buf = [];
+//! let endian = if is_little_endian {
+//!     RunTimeEndian::Little
+//! } else {
+//!     RunTimeEndian::Big
+//! };
+//! let debug_info: DebugInfo<EndianBuf<RunTimeEndian>> =
+//!     DebugInfo::new(&debug_info_buf, endian);
+//! ```
+//!
+//! In a program that reads entries, line programs and CFI, adding a second
+//! endianity type nearly doubles the size of the code from `gimli`, while
+//! `RunTimeEndian` adds less than a tenth. The parsers mostly consist of
+//! reads from the `Reader`, so there is little endian independent code that
+//! could be shared between two endianity types instead.
+//!
+//! The slice-backed readers also share the non-generic implementations of
+//! their most frequently used operations, such as decoding LEB128 numbers
+//! and finding the end of strings. This matters when using several reader
+//! types, such as `EndianBuf` and `EndianReader`.
+//!
 //! ## Cargo Features
 //!
 //! Cargo features that can be enabled with `gimli`:
@@ -162,6 +201,17 @@
 //! * `alloc`: Nightly only. Enables usage of the unstable, nightly-only
 //! `#![feature(alloc)]` Rust feature that allows `gimli` to use boxes and
 //! collection types in a `#[no_std]` environment.
+//!
+//! * `compression`: Enables the `compression` module, which decompresses
+//! zlib and zstd compressed sections (`SHF_COMPRESSED` and `.zdebug_*`) into
+//! an `EndianArcSlice`. Requires `std`.
+//!
+//! * `memchr`: Use the `memchr` crate to search for the null terminators of
+//! strings in slice-backed readers. Without this feature, a portable word at a
+//! time search is used instead.
+//!
+//! * `write`: Enabled by default. Enables the `write` module, which provides
+//! builders for producing DWARF and unwind information sections.
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 // Allow clippy warnings when we aren't building with clippy.
@@ -190,6 +240,15 @@ extern crate alloc;
 extern crate arrayvec;
 extern crate byteorder;
 extern crate fallible_iterator;
+#[cfg(feature = "compression")]
+extern crate flate2;
+#[cfg(feature = "memchr")]
+extern crate memchr;
+#[cfg(feature = "compression")]
+extern crate zstd;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
 #[cfg(feature = "std")]
 mod imports {
@@ -198,6 +257,8 @@ mod imports {
     pub use std::string;
     pub use std::borrow;
     pub use std::collections::btree_map;
+    pub use std::rc;
+    pub use std::sync;
 }
 
 #[cfg(not(feature = "std"))]
@@ -206,33 +267,75 @@ mod imports {
     pub use alloc::vec;
     pub use alloc::string;
     pub use alloc::borrow;
-    pub use alloc::btree_map;
+    pub use alloc::collections::btree_map;
+    pub use alloc::rc;
+    pub use alloc::sync;
 }
 
 use imports::*;
 
+mod arch;
+pub use arch::*;
+
+mod arm_ehabi;
+pub use arm_ehabi::{ArmExidx, ArmExidxData, ArmExidxEntry, ArmExidxIter, ArmExtab, ArmExtabEntry,
+                    ArmPersonality, ArmUnwindInstruction, ArmUnwindInstructionIter,
+                    ArmUnwindInstructions};
+
 mod cfi;
 pub use cfi::*;
 
+mod dwarf;
+pub use dwarf::{Dwarf, DwarfFileType, Unit};
+
 mod constants;
 pub use constants::*;
 
+mod diff;
+pub use diff::{DiffEntry, DiffLineRow, DiffValue, Difference};
+
 mod endianity;
 pub use endianity::{BigEndian, EndianBuf, Endianity, LittleEndian, NativeEndian, RunTimeEndian};
 
+mod endian_reader;
+pub use endian_reader::{EndianArcSlice, EndianRcSlice, EndianReader, SharedBytes, StaticReader};
+
+#[cfg(feature = "std")]
+mod stream_reader;
+#[cfg(feature = "std")]
+pub use stream_reader::StreamReader;
+
+#[cfg(feature = "compression")]
+pub mod compression;
+
+mod instrumented_reader;
+pub use instrumented_reader::{InstrumentedReader, ReaderStats, ReaderStatsCollector, SectionStats};
+
+mod gdb_index;
+pub use gdb_index::{GdbIndex, GdbIndexAddress, GdbIndexAddressIter, GdbIndexCompUnit,
+                    GdbIndexCompUnitIter, GdbIndexSymbol, GdbIndexSymbolIter, GdbIndexSymbolKind,
+                    GdbIndexSymbolUnit, GdbIndexSymbolUnitIter, GdbIndexTable, GdbIndexTypeUnit,
+                    GdbIndexTypeUnitIter, GdbIndexUnit};
+
+mod inlined;
+pub use inlined::InlinedFrame;
+
 pub mod leb128;
 
 mod parser;
-pub use parser::{Error, Format, Result};
-pub use parser::{DebugMacinfoOffset, Pointer};
+pub use parser::{ContextError, EntryOrPadding, Error, Format, ReaderLimits, Result};
+pub use parser::{DebugMacinfoOffset, DebugMacroOffset, Pointer};
 
 mod reader;
-pub use reader::{Reader, ReaderOffset};
+pub use reader::{Reader, ReaderOffset, ReaderOffsetId};
 
 mod abbrev;
 pub use abbrev::{Abbreviation, Abbreviations, AttributeSpecification, DebugAbbrev,
                  DebugAbbrevOffset};
 
+mod addr;
+pub use addr::{DebugAddr, DebugAddrHeader, DebugAddrHeadersIter};
+
 mod aranges;
 pub use aranges::{ArangeEntry, ArangeEntryIter, DebugAranges};
 
@@ -240,14 +343,26 @@ mod line;
 pub use line::*;
 
 mod loclists;
-pub use loclists::{DebugLoc, DebugLocLists, LocationLists, LocationListsOffset,
-                   LocationListEntry, LocListIter, RawLocListEntry, RawLocListIter};
+pub use loclists::{DebugLoc, DebugLocLists, DebugLocListsBase, DebugLocListsIndex, LocationLists,
+                   LocationListsOffset, LocationListEntry, LocListIter, RawLocListEntry,
+                   RawLocListIter};
 
 mod lookup;
 
+mod lsda;
+pub use lsda::{CallSite, CallSiteIter, GccExceptTable, Lsda, LsdaAction, LsdaActionIter};
+
+mod names;
+pub use names::{DebugNames, DebugNamesOffset, IndexedName, IndexedNameIter, NameAbbreviation,
+                NameAttribute, NameAttributeSpecification, NameAttributeValue, NameEntry,
+                NameEntryIter, NameEntryOffset, NameIndex, NameIndexIter};
+
 mod op;
 pub use op::*;
 
+mod parents;
+pub use parents::UnitParents;
+
 mod pubnames;
 pub use pubnames::{DebugPubNames, PubNamesEntry, PubNamesEntryIter};
 
@@ -255,43 +370,363 @@ mod pubtypes;
 pub use pubtypes::{DebugPubTypes, PubTypesEntry, PubTypesEntryIter};
 
 mod rnglists;
-pub use rnglists::{DebugRanges, DebugRngLists, Range, RangeLists, RangeListsOffset,
-                   RawRngListEntry, RngListIter};
+pub use rnglists::{AddressAdjustment, AddressIndex, DebugAddrBase, DebugRanges, DebugRngLists,
+                   DebugRngListsBase, DebugRngListsIndex, MergedRangeIter,
+                   Range, RangeLists, RangeListsOffset, RawRngListEntry, RngListIter,
+                   TombstoneMode};
+
+mod session;
+pub use session::{DwarfFile, DwarfSession};
+
+mod statistics;
+pub use statistics::Statistics;
 
 mod str;
 pub use str::*;
 
-#[cfg(test)]
-mod test_util;
+#[cfg(any(test, feature = "test_util"))]
+pub mod test_util;
+
+mod unit_address_map;
+pub use unit_address_map::UnitAddressMap;
 
 mod unit;
-pub use unit::{CompilationUnitHeader, CompilationUnitHeadersIter, DebugInfo, DebugInfoOffset,
-               UnitOffset};
+pub use unit::{CompilationUnitHeader, CompilationUnitHeaders, CompilationUnitHeadersIter,
+               DebugInfo, DebugInfoOffset, UnitOffset};
 pub use unit::{DebugTypeSignature, DebugTypes, DebugTypesOffset, TypeUnitHeader,
-               TypeUnitHeadersIter};
-pub use unit::{DebuggingInformationEntry, EntriesCursor, EntriesTree, EntriesTreeIter,
-               EntriesTreeNode};
-pub use unit::{Attribute, AttributeValue, AttrsIter};
+               TypeUnitHeadersIter, UnitSectionOffset};
+pub use unit::{DebuggingInformationEntry, EntriesCursor, EntriesCursorCheckpoint,
+               EntriesCursorChildren, EntriesTree, EntriesTreeIter, EntriesTreeNode};
+pub use unit::{Attribute, AttributeClass, AttributeMetadata, AttributeValue, AttrsIter};
+pub use unit::{DiscriminantListEntry, DiscriminantListIter, DiscriminantValue};
+
+mod unwind;
+pub use unwind::UnwindRowEvaluator;
+
+mod value;
+pub use value::{Value, ValueType};
+
+mod verify;
+pub use verify::{VerifyIssue, VerifyIssueKind};
+
+#[cfg(feature = "write")]
+pub mod write;
+
+/// An identifier for a DWARF section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SectionId {
+    /// The `.debug_abbrev` section.
+    DebugAbbrev,
+    /// The `.debug_addr` section.
+    DebugAddr,
+    /// The `.debug_aranges` section.
+    DebugAranges,
+    /// The `.debug_frame` section.
+    DebugFrame,
+    /// The `.eh_frame` section.
+    EhFrame,
+    /// The `.eh_frame_hdr` section.
+    EhFrameHdr,
+    /// The `.debug_info` section.
+    DebugInfo,
+    /// The `.debug_line` section.
+    DebugLine,
+    /// The `.debug_line_str` section.
+    DebugLineStr,
+    /// The `.debug_loc` section.
+    DebugLoc,
+    /// The `.debug_loclists` section.
+    DebugLocLists,
+    /// The `.debug_names` section.
+    DebugNames,
+    /// The `.debug_pubnames` section.
+    DebugPubNames,
+    /// The `.debug_pubtypes` section.
+    DebugPubTypes,
+    /// The `.debug_ranges` section.
+    DebugRanges,
+    /// The `.debug_rnglists` section.
+    DebugRngLists,
+    /// The `.debug_str` section.
+    DebugStr,
+    /// The `.debug_str_offsets` section.
+    DebugStrOffsets,
+    /// The `.debug_types` section.
+    DebugTypes,
+    /// The `.gdb_index` section.
+    GdbIndex,
+}
+
+impl SectionId {
+    /// Returns the ELF section name for this kind of section.
+    pub fn name(self) -> &'static str {
+        match self {
+            SectionId::DebugAbbrev => ".debug_abbrev",
+            SectionId::DebugAddr => ".debug_addr",
+            SectionId::DebugAranges => ".debug_aranges",
+            SectionId::DebugFrame => ".debug_frame",
+            SectionId::EhFrame => ".eh_frame",
+            SectionId::EhFrameHdr => ".eh_frame_hdr",
+            SectionId::DebugInfo => ".debug_info",
+            SectionId::DebugLine => ".debug_line",
+            SectionId::DebugLineStr => ".debug_line_str",
+            SectionId::DebugLoc => ".debug_loc",
+            SectionId::DebugLocLists => ".debug_loclists",
+            SectionId::DebugNames => ".debug_names",
+            SectionId::DebugPubNames => ".debug_pubnames",
+            SectionId::DebugPubTypes => ".debug_pubtypes",
+            SectionId::DebugRanges => ".debug_ranges",
+            SectionId::DebugRngLists => ".debug_rnglists",
+            SectionId::DebugStr => ".debug_str",
+            SectionId::DebugStrOffsets => ".debug_str_offsets",
+            SectionId::DebugTypes => ".debug_types",
+            SectionId::GdbIndex => ".gdb_index",
+        }
+    }
+
+    /// Returns the ELF section name for this kind of section in a split DWARF
+    /// object (`.dwo` or `.dwp` file), if such a section can exist there.
+    pub fn dwo_name(self) -> Option<&'static str> {
+        Some(match self {
+            SectionId::DebugAbbrev => ".debug_abbrev.dwo",
+            SectionId::DebugInfo => ".debug_info.dwo",
+            SectionId::DebugLine => ".debug_line.dwo",
+            SectionId::DebugLoc => ".debug_loc.dwo",
+            SectionId::DebugLocLists => ".debug_loclists.dwo",
+            SectionId::DebugRngLists => ".debug_rnglists.dwo",
+            SectionId::DebugStr => ".debug_str.dwo",
+            SectionId::DebugStrOffsets => ".debug_str_offsets.dwo",
+            SectionId::DebugTypes => ".debug_types.dwo",
+            _ => return None,
+        })
+    }
+
+    /// Returns the Mach-O section name for this kind of section, if it can
+    /// exist in a Mach-O file.
+    ///
+    /// Mach-O section names are limited to 16 bytes, so some names are
+    /// truncated.
+    pub fn macho_name(self) -> Option<&'static str> {
+        Some(match self {
+            SectionId::DebugAbbrev => "__debug_abbrev",
+            SectionId::DebugAddr => "__debug_addr",
+            SectionId::DebugAranges => "__debug_aranges",
+            SectionId::DebugFrame => "__debug_frame",
+            SectionId::EhFrame => "__eh_frame",
+            SectionId::DebugInfo => "__debug_info",
+            SectionId::DebugLine => "__debug_line",
+            SectionId::DebugLineStr => "__debug_line_str",
+            SectionId::DebugLoc => "__debug_loc",
+            SectionId::DebugLocLists => "__debug_loclists",
+            SectionId::DebugNames => "__debug_names",
+            SectionId::DebugPubNames => "__debug_pubnames",
+            SectionId::DebugPubTypes => "__debug_pubtypes",
+            SectionId::DebugRanges => "__debug_ranges",
+            SectionId::DebugRngLists => "__debug_rnglists",
+            SectionId::DebugStr => "__debug_str",
+            SectionId::DebugStrOffsets => "__debug_str_offs",
+            SectionId::DebugTypes => "__debug_types",
+            SectionId::EhFrameHdr | SectionId::GdbIndex => return None,
+        })
+    }
+
+    /// Identify a section from its name in an object file.
+    ///
+    /// This recognizes the following names, so that loaders don't need to
+    /// know the conventions of each object file format:
+    ///
+    /// * ELF and PE/COFF names, such as `.debug_info`. PE/COFF loaders must
+    /// first resolve long names from the string table, since names longer
+    /// than 8 bytes are stored as `/n` offsets.
+    ///
+    /// * Mach-O names, such as `__debug_info`, including names that are
+    /// truncated to 16 bytes.
+    ///
+    /// * Names of sections that are compressed using the GNU convention, such
+    /// as `.zdebug_info`.
+    ///
+    /// * Names of sections in split DWARF objects, such as `.debug_info.dwo`.
+    ///
+    /// Trailing null bytes, which are present when reading fixed size name
+    /// fields, are ignored. Returns `None` for other names.
+    ///
+    /// ```
+    /// use gimli::SectionId;
+    ///
+    /// let name = SectionId::from_name("__debug_str_offs").unwrap();
+    /// assert_eq!(name.id, SectionId::DebugStrOffsets);
+    ///
+    /// let name = SectionId::from_name(".zdebug_info.dwo").unwrap();
+    /// assert_eq!(name.id, SectionId::DebugInfo);
+    /// assert!(name.compressed);
+    /// assert!(name.dwo);
+    /// ```
+    pub fn from_name(name: &str) -> Option<ParsedSectionName> {
+        let name = name.trim_end_matches('\0');
+        let (name, macho) = if name.starts_with("__") {
+            (&name[2..], true)
+        } else if name.starts_with('.') {
+            (&name[1..], false)
+        } else {
+            return None;
+        };
+        let (name, dwo) = if !macho && name.ends_with(".dwo") {
+            (&name[..name.len() - 4], true)
+        } else {
+            (name, false)
+        };
+        let (name, compressed) = if name.starts_with("zdebug_") {
+            (&name[1..], true)
+        } else {
+            (name, false)
+        };
+
+        for &id in &ALL_SECTION_IDS {
+            // Strip the leading '.' from the ELF name.
+            let id_name = &id.name()[1..];
+            let matched = if macho {
+                id.macho_name().map_or(false, |macho_name| {
+                    id_name == name || (!compressed && &macho_name[2..] == name)
+                })
+            } else {
+                id_name == name
+            };
+            if matched {
+                if dwo && id.dwo_name().is_none() {
+                    return None;
+                }
+                return Some(ParsedSectionName {
+                    id,
+                    dwo,
+                    compressed,
+                });
+            }
+        }
+        None
+    }
+}
+
+const ALL_SECTION_IDS: [SectionId; 20] = [
+    SectionId::DebugAbbrev,
+    SectionId::DebugAddr,
+    SectionId::DebugAranges,
+    SectionId::DebugFrame,
+    SectionId::EhFrame,
+    SectionId::EhFrameHdr,
+    SectionId::DebugInfo,
+    SectionId::DebugLine,
+    SectionId::DebugLineStr,
+    SectionId::DebugLoc,
+    SectionId::DebugLocLists,
+    SectionId::DebugNames,
+    SectionId::DebugPubNames,
+    SectionId::DebugPubTypes,
+    SectionId::DebugRanges,
+    SectionId::DebugRngLists,
+    SectionId::DebugStr,
+    SectionId::DebugStrOffsets,
+    SectionId::DebugTypes,
+    SectionId::GdbIndex,
+];
+
+/// A section name in an object file that was identified by
+/// `SectionId::from_name`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedSectionName {
+    /// The kind of section.
+    pub id: SectionId,
+
+    /// Whether the name is for a section in a split DWARF object.
+    pub dwo: bool,
+
+    /// Whether the name indicates that the section data is compressed using
+    /// the GNU `.zdebug` convention, which prefixes the data with `ZLIB` and
+    /// its uncompressed size.
+    pub compressed: bool,
+}
 
 /// A convenience trait for loading DWARF sections from object files.  To be
 /// used like:
 ///
 /// ```
-/// use gimli::{DebugInfo, EndianBuf, LittleEndian, Reader, Section};
-///
-/// fn load_section<R, S, F>(loader: F) -> S
-///   where R: Reader, S: Section<R>, F: FnOnce(&'static str) -> R
-/// {
-///   let data = loader(S::section_name());
-///   S::from(data)
-/// }
+/// use gimli::{DebugInfo, EndianBuf, LittleEndian, Section, SectionId};
 ///
 /// let buf = [0x00, 0x01, 0x02, 0x03];
-/// let reader = EndianBuf::new(&buf, LittleEndian);
+/// let load_section_somehow = |id: SectionId| -> Result<_, ()> {
+///     assert_eq!(id.name(), ".debug_info");
+///     Ok(EndianBuf::new(&buf, LittleEndian))
+/// };
 ///
-/// let debug_info: DebugInfo<_> = load_section(|_: &'static str| reader);
+/// let debug_info: DebugInfo<_> = Section::load(load_section_somehow).unwrap();
 /// ```
 pub trait Section<R: Reader>: From<R> {
+    /// Returns the `SectionId` for this type.
+    fn id() -> SectionId;
+
     /// Returns the ELF section name for this type.
-    fn section_name() -> &'static str;
+    fn section_name() -> &'static str {
+        Self::id().name()
+    }
+
+    /// Returns the ELF section name for this type in a split DWARF object,
+    /// if it can exist there.
+    fn dwo_section_name() -> Option<&'static str> {
+        Self::id().dwo_name()
+    }
+
+    /// Try to load the section using the given loader function.
+    fn load<F, E>(f: F) -> ::std::result::Result<Self, E>
+    where
+        F: FnOnce(SectionId) -> ::std::result::Result<R, E>,
+    {
+        f(Self::id()).map(From::from)
+    }
+
+    /// Returns the `Reader` for this section.
+    fn reader(&self) -> &R;
+
+    /// Returns the `SectionId` and section offset corresponding to the given
+    /// `id`, if it is associated with this section.
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<(SectionId, R::Offset)> {
+        self.reader()
+            .lookup_offset_id(id)
+            .map(|offset| (Self::id(), offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_id_from_name() {
+        for &id in &ALL_SECTION_IDS {
+            let name = SectionId::from_name(id.name()).unwrap();
+            assert_eq!(name, ParsedSectionName { id, dwo: false, compressed: false });
+            if let Some(dwo_name) = id.dwo_name() {
+                let name = SectionId::from_name(dwo_name).unwrap();
+                assert_eq!(name, ParsedSectionName { id, dwo: true, compressed: false });
+            }
+            if let Some(macho_name) = id.macho_name() {
+                assert!(macho_name.len() <= 16);
+                let name = SectionId::from_name(macho_name).unwrap();
+                assert_eq!(name, ParsedSectionName { id, dwo: false, compressed: false });
+            }
+        }
+
+        let name = SectionId::from_name(".zdebug_line").unwrap();
+        assert_eq!(name.id, SectionId::DebugLine);
+        assert!(name.compressed);
+        let name = SectionId::from_name("__zdebug_info").unwrap();
+        assert_eq!(name.id, SectionId::DebugInfo);
+        assert!(name.compressed);
+        let name = SectionId::from_name(".debug_info\0\0\0\0\0").unwrap();
+        assert_eq!(name.id, SectionId::DebugInfo);
+
+        assert_eq!(SectionId::from_name(".text"), None);
+        assert_eq!(SectionId::from_name("debug_info"), None);
+        assert_eq!(SectionId::from_name(".debug_aranges.dwo"), None);
+        assert_eq!(SectionId::from_name("__debug_info.dwo"), None);
+        assert_eq!(SectionId::from_name("__debug_str_of"), None);
+    }
 }
@@ -1,13 +1,17 @@
 use constants;
 use endianity::{EndianBuf, Endianity};
+use fallible_iterator::FallibleIterator;
 use parser;
 use reader::{Reader, ReaderOffset};
+use rnglists::AddressAdjustment;
 use std::fmt;
+use str::{DebugLineStr, DebugLineStrOffset, DebugStr, DebugStrOffset};
 use vec::Vec;
-use Section;
+use {Section, SectionId};
 
 /// An offset into the `.debug_line` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugLineOffset<T = usize>(pub T);
 
 /// The `DebugLine` struct contains the source location to instruction mapping
@@ -65,24 +69,66 @@ impl<R: Reader> DebugLine<R> {
     /// let program = debug_line.program(offset, address_size, None, None)
     ///     .expect("should have found a header at that offset, and parsed it OK");
     /// ```
+    ///
+    /// Version 5 headers may refer to strings in the `.debug_str` and
+    /// `.debug_line_str` sections, which this method can't resolve. Use
+    /// `program_with_strings` for these headers.
     pub fn program(
         &self,
         offset: DebugLineOffset<R::Offset>,
         address_size: u8,
         comp_dir: Option<R>,
         comp_name: Option<R>,
+    ) -> parser::Result<IncompleteLineNumberProgram<R>> {
+        let mut empty = self.debug_line_section.clone();
+        empty.empty();
+        self.program_with_strings(
+            offset,
+            address_size,
+            comp_dir,
+            comp_name,
+            &empty.clone().into(),
+            &empty.into(),
+        )
+    }
+
+    /// Parse the line number program whose header is at the given `offset` in the
+    /// `.debug_line` section, resolving the `DW_FORM_strp` and `DW_FORM_line_strp`
+    /// strings of version 5 headers.
+    ///
+    /// See `program` for the other parameters.
+    pub fn program_with_strings(
+        &self,
+        offset: DebugLineOffset<R::Offset>,
+        address_size: u8,
+        comp_dir: Option<R>,
+        comp_name: Option<R>,
+        debug_str: &DebugStr<R>,
+        debug_line_str: &DebugLineStr<R>,
     ) -> parser::Result<IncompleteLineNumberProgram<R>> {
         let input = &mut self.debug_line_section.clone();
         input.skip(offset.0)?;
-        let header = LineNumberProgramHeader::parse(input, address_size, comp_dir, comp_name)?;
+        let header = LineNumberProgramHeader::parse(
+            input,
+            offset,
+            address_size,
+            comp_dir,
+            comp_name,
+            debug_str,
+            debug_line_str,
+        )?;
         let program = IncompleteLineNumberProgram { header: header };
         Ok(program)
     }
 }
 
 impl<R: Reader> Section<R> for DebugLine<R> {
-    fn section_name() -> &'static str {
-        ".debug_line"
+    fn id() -> SectionId {
+        SectionId::DebugLine
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_line_section
     }
 }
 
@@ -135,6 +181,13 @@ where
     program: Program,
     row: LineNumberRow,
     opcodes: OpcodesIter<R>,
+    instructions: u64,
+    max_instructions: Option<u64>,
+    adjustment: Option<AddressAdjustment>,
+    adjusted_row: LineNumberRow,
+    // The unadjusted and adjusted addresses of the previous row in the
+    // current sequence.
+    previous_address: Option<(u64, u64)>,
 }
 
 
@@ -151,13 +204,16 @@ where
     fn new(program: IncompleteLineNumberProgram<R>) -> OneShotStateMachine<R> {
         let mut row = LineNumberRow::default();
         row.registers.reset(program.header().default_is_stmt());
-        let opcodes = OpcodesIter {
-            input: program.header().program_buf.clone(),
-        };
+        let opcodes = program.header().opcodes();
         StateMachine {
             program: program,
             row: row,
             opcodes: opcodes,
+            instructions: 0,
+            max_instructions: None,
+            adjustment: None,
+            adjusted_row: LineNumberRow::default(),
+            previous_address: None,
         }
     }
 
@@ -172,6 +228,11 @@ where
             program: program,
             row: row,
             opcodes: opcodes,
+            instructions: 0,
+            max_instructions: None,
+            adjustment: None,
+            adjusted_row: LineNumberRow::default(),
+            previous_address: None,
         }
     }
 
@@ -196,35 +257,31 @@ where
             self.header().maximum_operations_per_instruction as u64;
 
         if maximum_operations_per_instruction == 1 {
-            self.row.registers.address += minimum_instruction_length * operation_advance;
+            self.row.registers.address = self.row.registers
+                .address
+                .wrapping_add(minimum_instruction_length.wrapping_mul(operation_advance));
             self.row.registers.op_index = 0;
         } else {
-            let op_index_with_advance = self.row.registers.op_index + operation_advance;
-            self.row.registers.address += minimum_instruction_length
-                * (op_index_with_advance / maximum_operations_per_instruction);
+            let op_index_with_advance =
+                self.row.registers.op_index.wrapping_add(operation_advance);
+            self.row.registers.address = self.row.registers.address.wrapping_add(
+                minimum_instruction_length
+                    .wrapping_mul(op_index_with_advance / maximum_operations_per_instruction),
+            );
             self.row.registers.op_index =
                 op_index_with_advance % maximum_operations_per_instruction;
         }
     }
 
-    fn adjust_opcode(&self, opcode: u8) -> u8 {
-        opcode - self.header().opcode_base
-    }
-
     /// Section 6.2.5.1
     fn exec_special_opcode(&mut self, opcode: u8) {
-        let adjusted_opcode = self.adjust_opcode(opcode);
-
-        let line_range = self.header().line_range;
-        let line_advance = adjusted_opcode % line_range;
-        let operation_advance = adjusted_opcode / line_range;
+        let (line_advance, operation_advance) = self.header().special_opcode_advances(opcode);
 
         // Step 1
-        let line_base = self.header().line_base as i64;
-        self.apply_line_advance(line_base + line_advance as i64);
+        self.apply_line_advance(line_advance);
 
         // Step 2
-        self.apply_operation_advance(operation_advance as u64);
+        self.apply_operation_advance(operation_advance);
     }
 
     /// Execute the given opcode, and return true if a new row in the
@@ -271,14 +328,14 @@ where
             }
 
             Opcode::ConstAddPc => {
-                let adjusted = self.adjust_opcode(255);
-                let operation_advance = adjusted / self.header().line_range;
-                self.apply_operation_advance(operation_advance as u64);
+                let (_, operation_advance) = self.header().special_opcode_advances(255);
+                self.apply_operation_advance(operation_advance);
                 false
             }
 
             Opcode::FixedAddPc(operand) => {
-                self.row.registers.address += operand as u64;
+                self.row.registers.address =
+                    self.row.registers.address.wrapping_add(operand as u64);
                 self.row.registers.op_index = 0;
                 false
             }
@@ -333,6 +390,66 @@ where
         self.program.header()
     }
 
+    /// Return the offset within the `.debug_line` section of the next opcode
+    /// that the state machine will execute.
+    ///
+    /// Rows are returned in the order that they occur in the line number
+    /// program. If executing the program fails, this is the offset of the
+    /// opcode that could not be parsed.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.opcodes.offset()
+    }
+
+    /// Apply the `max_line_instructions` limit of the given `ReaderLimits`
+    /// to this state machine.
+    ///
+    /// Executing more instructions than the limit fails with
+    /// `Error::LimitExceeded`.
+    pub fn set_limits(&mut self, limits: &parser::ReaderLimits) {
+        self.max_instructions = limits.max_line_instructions;
+    }
+
+    /// Set the adjustment to apply to the addresses of the returned rows.
+    ///
+    /// Rows that have no adjusted address are omitted. The row that ends a
+    /// sequence is kept at the same offset from the previous row in the
+    /// sequence, since its address is after the end of the code. The default
+    /// is no adjustment.
+    pub fn set_address_adjustment(&mut self, adjustment: Option<AddressAdjustment>) {
+        self.adjustment = adjustment;
+    }
+
+    /// Compute the adjusted copy of the current row, and return false if it
+    /// has no adjusted address.
+    fn adjust_row(&mut self) -> bool {
+        let address = self.row.registers.address;
+        let adjusted = match self.adjustment {
+            None => return true,
+            Some(_) if self.row.registers.end_sequence => {
+                self.previous_address.and_then(|(previous, adjusted)| {
+                    address
+                        .checked_sub(previous)
+                        .and_then(|offset| adjusted.checked_add(offset))
+                })
+            }
+            Some(ref adjustment) => adjustment.adjust(address),
+        };
+        if self.row.registers.end_sequence {
+            self.previous_address = None;
+        } else if let Some(adjusted) = adjusted {
+            self.previous_address = Some((address, adjusted));
+        }
+        match adjusted {
+            Some(adjusted) => {
+                self.adjusted_row = self.row;
+                self.adjusted_row.registers.address = adjusted;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Parse and execute the next opcodes in the line number program until
     /// another row in the line number matrix is computed.
     ///
@@ -341,11 +458,30 @@ where
     /// number matrix, then `Ok(None)` is returned. If there was an error parsing
     /// an opcode, then `Err(e)` is returned.
     ///
-    /// Unfortunately, the references mean that this cannot be a
-    /// `FallibleIterator`.
+    /// The state machine also implements `FallibleIterator`, which yields
+    /// copies of the rows without the header.
     pub fn next_row(
         &mut self,
     ) -> parser::Result<Option<(&LineNumberProgramHeader<R>, &LineNumberRow)>> {
+        loop {
+            if !self.next_unadjusted_row()? {
+                return Ok(None);
+            }
+            if self.adjust_row() {
+                break;
+            }
+        }
+        let row = if self.adjustment.is_some() {
+            &self.adjusted_row
+        } else {
+            &self.row
+        };
+        Ok(Some((self.program.header(), row)))
+    }
+
+    /// Execute opcodes until another row is computed, and return false if
+    /// there are no more rows.
+    fn next_unadjusted_row(&mut self) -> parser::Result<bool> {
         // Perform any reset that was required after copying the previous row.
         if self.row.registers.end_sequence {
             // Previous opcode was EndSequence, so reset everything
@@ -370,10 +506,17 @@ where
             // Split the borrow here, rather than calling `self.header()`.
             match self.opcodes.next_opcode(self.program.header()) {
                 Err(err) => return Err(err),
-                Ok(None) => return Ok(None),
+                Ok(None) => return Ok(false),
                 Ok(Some(opcode)) => {
+                    self.instructions += 1;
+                    if let Some(max_instructions) = self.max_instructions {
+                        if self.instructions > max_instructions {
+                            self.opcodes.input.empty();
+                            return Err(parser::Error::LimitExceeded);
+                        }
+                    }
                     if self.execute(opcode) {
-                        return Ok(Some((self.header(), &self.row)));
+                        return Ok(true);
                     }
                     // Fall through, parse the next opcode, and see if that
                     // yields a row.
@@ -405,6 +548,19 @@ where
     }
 }
 
+impl<R, Program> FallibleIterator for StateMachine<R, Program>
+where
+    Program: LineNumberProgram<R>,
+    R: Reader,
+{
+    type Item = LineNumberRow;
+    type Error = parser::Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        self.next_row().map(|row| row.map(|(_, row)| *row))
+    }
+}
+
 /// A parsed line number program opcode.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Opcode<R: Reader> {
@@ -575,7 +731,20 @@ impl<R: Reader> Opcode<R> {
         } else if opcode >= header.opcode_base {
             Ok(Opcode::Special(opcode))
         } else {
-            match constants::DwLns(opcode) {
+            let mut opcode_lengths = header.standard_opcode_lengths().clone();
+            opcode_lengths.skip(R::Offset::from_u8(opcode - 1))?;
+            let num_args = opcode_lengths.read_u8()?;
+
+            let opcode = constants::DwLns(opcode);
+            if let Some(expected_num_args) = standard_opcode_length(opcode) {
+                if num_args != expected_num_args {
+                    // The producer has given this standard opcode a
+                    // nonstandard meaning, so we can only skip its operands.
+                    return Opcode::parse_unknown_standard(opcode, num_args, input);
+                }
+            }
+
+            match opcode {
                 constants::DW_LNS_copy => Ok(Opcode::Copy),
 
                 constants::DW_LNS_advance_pc => {
@@ -618,32 +787,57 @@ impl<R: Reader> Opcode<R> {
                     Ok(Opcode::SetIsa(isa))
                 }
 
-                otherwise => {
-                    let mut opcode_lengths = header.standard_opcode_lengths().clone();
-                    opcode_lengths.skip(R::Offset::from_u8(opcode - 1))?;
-                    let num_args = opcode_lengths.read_u8()? as usize;
-                    match num_args {
-                        0 => Ok(Opcode::UnknownStandard0(otherwise)),
-                        1 => {
-                            let arg = input.read_uleb128()?;
-                            Ok(Opcode::UnknownStandard1(otherwise, arg))
-                        }
-                        _ => {
-                            let mut args = input.clone();
-                            for _ in 0..num_args {
-                                input.read_uleb128()?;
-                            }
-                            let len = input.offset_from(&args);
-                            args.truncate(len)?;
-                            Ok(Opcode::UnknownStandardN(otherwise, args))
-                        }
-                    }
+                otherwise => Opcode::parse_unknown_standard(otherwise, num_args, input),
+            }
+        }
+    }
+
+    /// Parse the operands of a standard opcode that we don't understand, using
+    /// the number of operands given by the header's `standard_opcode_lengths`.
+    fn parse_unknown_standard(
+        opcode: constants::DwLns,
+        num_args: u8,
+        input: &mut R,
+    ) -> parser::Result<Opcode<R>> {
+        match num_args {
+            0 => Ok(Opcode::UnknownStandard0(opcode)),
+            1 => {
+                let arg = input.read_uleb128()?;
+                Ok(Opcode::UnknownStandard1(opcode, arg))
+            }
+            _ => {
+                let mut args = input.clone();
+                for _ in 0..num_args {
+                    input.read_uleb128()?;
                 }
+                let len = input.offset_from(&args)?;
+                args.truncate(len)?;
+                Ok(Opcode::UnknownStandardN(opcode, args))
             }
         }
     }
 }
 
+/// The number of operands that the DWARF standard defines for the given
+/// standard opcode, or `None` if the opcode is not defined by the standard.
+fn standard_opcode_length(opcode: constants::DwLns) -> Option<u8> {
+    match opcode {
+        constants::DW_LNS_copy |
+        constants::DW_LNS_negate_stmt |
+        constants::DW_LNS_set_basic_block |
+        constants::DW_LNS_const_add_pc |
+        constants::DW_LNS_set_prologue_end |
+        constants::DW_LNS_set_epilogue_begin => Some(0),
+        constants::DW_LNS_advance_pc |
+        constants::DW_LNS_advance_line |
+        constants::DW_LNS_set_file |
+        constants::DW_LNS_set_column |
+        constants::DW_LNS_fixed_advance_pc |
+        constants::DW_LNS_set_isa => Some(1),
+        _ => None,
+    }
+}
+
 impl<R: Reader> fmt::Display for Opcode<R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
@@ -696,18 +890,31 @@ impl<R: Reader> fmt::Display for Opcode<R> {
 #[derive(Clone, Debug)]
 pub struct OpcodesIter<R: Reader> {
     input: R,
+    offset: R::Offset,
 }
 
 impl<R: Reader> OpcodesIter<R> {
     fn remove_trailing(&self, other: &OpcodesIter<R>) -> parser::Result<OpcodesIter<R>> {
-        let offset = other.input.offset_from(&self.input);
+        let offset = other.input.offset_from(&self.input)?;
         let mut input = self.input.clone();
         input.truncate(offset)?;
-        Ok(OpcodesIter { input })
+        Ok(OpcodesIter {
+            input,
+            offset: self.offset,
+        })
     }
 }
 
 impl<R: Reader> OpcodesIter<R> {
+    /// Return the offset within the `.debug_line` section of the next opcode
+    /// that the iterator will parse.
+    ///
+    /// If parsing an opcode fails, this is the offset of that opcode.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.offset
+    }
+
     /// Advance the iterator and return the next opcode.
     ///
     /// Returns the newly parsed opcode as `Ok(Some(opcode))`. Returns
@@ -728,8 +935,12 @@ impl<R: Reader> OpcodesIter<R> {
             return Ok(None);
         }
 
+        let len = self.input.len();
         match Opcode::parse(header, &mut self.input) {
-            Ok(opcode) => Ok(Some(opcode)),
+            Ok(opcode) => {
+                self.offset += len - self.input.len();
+                Ok(Some(opcode))
+            }
             Err(e) => {
                 self.input.empty();
                 Err(e)
@@ -738,8 +949,152 @@ impl<R: Reader> OpcodesIter<R> {
     }
 }
 
+/// A line number program instruction, decoded without running the state
+/// machine.
+///
+/// Unlike `Opcode`, special opcodes and `DW_LNS_const_add_pc` are expanded
+/// into the advances that they apply to the state machine registers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineInstruction<R: Reader> {
+    /// A special opcode, which adds `line_advance` to the line register,
+    /// advances the address and op_index registers by `operation_advance`,
+    /// and then appends a row to the matrix.
+    Special {
+        /// The special opcode.
+        opcode: u8,
+        /// The signed value added to the line register.
+        line_advance: i64,
+        /// The operation advance applied to the address and op_index
+        /// registers.
+        operation_advance: u64,
+    },
+
+    /// A `DW_LNS_const_add_pc` opcode, which advances the address and
+    /// op_index registers by the operation advance of special opcode 255.
+    ConstAddPc {
+        /// The operation advance applied to the address and op_index
+        /// registers.
+        operation_advance: u64,
+    },
+
+    /// Any other opcode. This is never `Opcode::Special` or
+    /// `Opcode::ConstAddPc`.
+    Opcode(Opcode<R>),
+}
+
+/// An iterator yielding decoded line number program instructions.
+///
+/// See
+/// [`LineNumberProgramHeader::instructions`](./struct.LineNumberProgramHeader.html#method.instructions)
+/// for more details.
+///
+/// Can be [used with
+/// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+#[derive(Clone, Debug)]
+pub struct LineInstructions<'header, R: 'header + Reader> {
+    header: &'header LineNumberProgramHeader<R>,
+    opcodes: OpcodesIter<R>,
+}
+
+impl<'header, R: Reader> LineInstructions<'header, R> {
+    /// Return the offset within the `.debug_line` section of the next
+    /// instruction that the iterator will parse.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.opcodes.offset()
+    }
+
+    /// Advance the iterator and return the next instruction.
+    ///
+    /// Returns the newly decoded instruction as `Ok(Some(instruction))`.
+    /// Returns `Ok(None)` when iteration is complete. If an error occurs while
+    /// parsing the next instruction, then this error is returned as `Err(e)`,
+    /// and all subsequent calls return `Ok(None)`.
+    pub fn next(&mut self) -> parser::Result<Option<LineInstruction<R>>> {
+        let instruction = match self.opcodes.next_opcode(self.header)? {
+            None => return Ok(None),
+            Some(Opcode::Special(opcode)) => {
+                let (line_advance, operation_advance) =
+                    self.header.special_opcode_advances(opcode);
+                LineInstruction::Special {
+                    opcode: opcode,
+                    line_advance: line_advance,
+                    operation_advance: operation_advance,
+                }
+            }
+            Some(Opcode::ConstAddPc) => {
+                let (_, operation_advance) = self.header.special_opcode_advances(255);
+                LineInstruction::ConstAddPc {
+                    operation_advance: operation_advance,
+                }
+            }
+            Some(opcode) => LineInstruction::Opcode(opcode),
+        };
+        Ok(Some(instruction))
+    }
+}
+
+impl<'header, R: Reader> FallibleIterator for LineInstructions<'header, R> {
+    type Item = LineInstruction<R>;
+    type Error = parser::Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        LineInstructions::next(self)
+    }
+}
+
+/// An index into the file names of a line number program header, as used by
+/// the `file` register of the line number program and by the
+/// `DW_AT_decl_file` and `DW_AT_call_file` attributes.
+///
+/// The base of the index depends on the version of the line number program.
+/// Before version 5, the first entry in `file_names` has index 1, and index 0
+/// is the primary source file of the unit. In version 5, the first entry has
+/// index 0. Use `FileIndex::file` instead of indexing `file_names` directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FileIndex(
+    /// The raw index, as encoded in the line number program.
+    pub u64,
+);
+
+impl FileIndex {
+    /// Return the file entry that this index refers to in the given header.
+    #[inline]
+    pub fn file<'header, R: Reader>(
+        self,
+        header: &'header LineNumberProgramHeader<R>,
+    ) -> Option<&'header FileEntry<R>> {
+        header.file(self.0)
+    }
+}
+
+/// An index into the include directories of a line number program header.
+///
+/// Before version 5 of the line number program, the first entry in
+/// `include_directories` has index 1, and index 0 is the compilation
+/// directory of the unit. In version 5, the first entry has index 0. Use
+/// `DirectoryIndex::directory` instead of indexing `include_directories`
+/// directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DirectoryIndex(
+    /// The raw index, as encoded in the file entry.
+    pub u64,
+);
+
+impl DirectoryIndex {
+    /// Return the directory that this index refers to in the given header.
+    #[inline]
+    pub fn directory<R: Reader>(self, header: &LineNumberProgramHeader<R>) -> Option<R> {
+        header.directory(self.0)
+    }
+}
+
 /// A row in the line number program's resulting matrix.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct LineNumberRow {
     registers: StateMachineRegisters,
 }
@@ -766,9 +1121,11 @@ impl LineNumberRow {
 
     /// "An unsigned integer indicating the identity of the source file
     /// corresponding to a machine instruction."
+    ///
+    /// See `FileIndex` for how this index is interpreted.
     #[inline]
-    pub fn file_index(&self) -> u64 {
-        self.registers.file
+    pub fn file_index(&self) -> FileIndex {
+        FileIndex(self.registers.file)
     }
 
     /// The source file corresponding to the current machine instruction.
@@ -882,6 +1239,7 @@ pub enum ColumnType {
 
 /// The registers for a state machine, as defined in section 6.2.2.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 struct StateMachineRegisters {
     address: u64,
     op_index: u64,
@@ -935,10 +1293,20 @@ pub struct LineNumberSequence<R: Reader> {
     opcodes: OpcodesIter<R>,
 }
 
+impl<R: Reader> LineNumberSequence<R> {
+    /// Return `true` if the given `address` is within this sequence's address
+    /// range, `false` otherwise.
+    pub fn contains(&self, address: u64) -> bool {
+        self.start <= address && address < self.end
+    }
+}
+
 /// A header for a line number program in the `.debug_line` section, as defined
 /// in section 6.2.4 of the standard.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LineNumberProgramHeader<R: Reader> {
+    offset: DebugLineOffset<R::Offset>,
+
     unit_length: R::Offset,
 
     /// "A version number. This number is specific to the line number
@@ -1001,6 +1369,9 @@ pub struct LineNumberProgramHeader<R: Reader> {
     /// The encoded line program instructions.
     program_buf: R,
 
+    /// The section offset of `program_buf`.
+    program_offset: R::Offset,
+
     /// The size of an address on the debuggee architecture, in bytes.
     address_size: u8,
 
@@ -1012,12 +1383,22 @@ pub struct LineNumberProgramHeader<R: Reader> {
 }
 
 impl<R: Reader> LineNumberProgramHeader<R> {
+    /// Return the offset of this header within the `.debug_line` section.
+    pub fn offset(&self) -> DebugLineOffset<R::Offset> {
+        self.offset
+    }
+
     /// Return the length of the line number program and header, not including
     /// the length of the encoded length itself.
     pub fn unit_length(&self) -> R::Offset {
         self.unit_length
     }
 
+    /// Get whether this line program uses the 32-bit or 64-bit DWARF format.
+    pub fn format(&self) -> parser::Format {
+        self.format
+    }
+
     /// Get the version of this header's line program.
     pub fn version(&self) -> u16 {
         self.version
@@ -1075,11 +1456,14 @@ impl<R: Reader> LineNumberProgramHeader<R> {
         &self.include_directories[..]
     }
 
-    /// The include directory with the given directory index.
+    /// The include directory with the given raw directory index.
     ///
-    /// A directory index of 0 corresponds to the compilation unit directory.
+    /// Before version 5, a directory index of 0 corresponds to the
+    /// compilation unit directory. See `DirectoryIndex`.
     pub fn directory(&self, directory: u64) -> Option<R> {
-        if directory == 0 {
+        if self.version >= 5 {
+            self.include_directories.get(directory as usize).cloned()
+        } else if directory == 0 {
             self.comp_dir.clone()
         } else {
             let directory = directory as usize - 1;
@@ -1092,11 +1476,14 @@ impl<R: Reader> LineNumberProgramHeader<R> {
         &self.file_names[..]
     }
 
-    /// The source file with the given file index.
+    /// The source file with the given raw file index.
     ///
-    /// A file index of 0 corresponds to the compilation unit file.
+    /// Before version 5, a file index of 0 corresponds to the compilation unit
+    /// file. See `FileIndex`.
     pub fn file(&self, file: u64) -> Option<&FileEntry<R>> {
-        if file == 0 {
+        if self.version >= 5 {
+            self.file_names.get(file as usize)
+        } else if file == 0 {
             self.comp_name.as_ref()
         } else {
             let file = file as usize - 1;
@@ -1131,29 +1518,84 @@ impl<R: Reader> LineNumberProgramHeader<R> {
     pub fn opcodes(&self) -> OpcodesIter<R> {
         OpcodesIter {
             input: self.program_buf.clone(),
+            offset: self.program_offset,
         }
     }
 
+    /// Iterate over the instructions in this header's line number program,
+    /// decoding special opcodes without running the state machine.
+    ///
+    /// ```
+    /// # fn foo() {
+    /// use gimli::{LineInstruction, LineNumberProgramHeader, EndianBuf, NativeEndian};
+    ///
+    /// fn get_line_number_program_header<'a>() -> LineNumberProgramHeader<EndianBuf<'a, NativeEndian>> {
+    ///     // Get a line number program header from some offset in a
+    ///     // `.debug_line` section...
+    /// #   unimplemented!()
+    /// }
+    ///
+    /// let header = get_line_number_program_header();
+    /// let mut instructions = header.instructions();
+    /// while let Some(instruction) = instructions.next().unwrap() {
+    ///     if let LineInstruction::Special { line_advance, operation_advance, .. } = instruction {
+    ///         println!("Special: line += {}, operation += {}", line_advance, operation_advance);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn instructions(&self) -> LineInstructions<'_, R> {
+        LineInstructions {
+            header: self,
+            opcodes: self.opcodes(),
+        }
+    }
+
+    /// Return the `(line_advance, operation_advance)` for the given special
+    /// opcode, as described in section 6.2.5.1.
+    fn special_opcode_advances(&self, opcode: u8) -> (i64, u64) {
+        let adjusted_opcode = opcode - self.opcode_base;
+        let line_advance = adjusted_opcode % self.line_range;
+        let operation_advance = adjusted_opcode / self.line_range;
+        (
+            self.line_base as i64 + line_advance as i64,
+            operation_advance as u64,
+        )
+    }
+
     fn parse(
         input: &mut R,
+        offset: DebugLineOffset<R::Offset>,
         address_size: u8,
         comp_dir: Option<R>,
         comp_name: Option<R>,
+        debug_str: &DebugStr<R>,
+        debug_line_str: &DebugLineStr<R>,
     ) -> parser::Result<LineNumberProgramHeader<R>> {
         let (unit_length, format) = parser::parse_initial_length(input)?;
         let unit_length = R::Offset::from_u64(unit_length)?;
         let rest = &mut input.split(unit_length)?;
 
         let version = rest.read_u16()?;
-        if version < 2 || version > 4 {
+        if version < 2 || version > 5 {
             return Err(parser::Error::UnknownVersion(version as u64));
         }
 
+        if version >= 5 {
+            // The address size must match the compilation unit's, so we ignore
+            // this one. The line number program never contains segment
+            // selectors, so we can ignore the segment selector size.
+            let _address_size = rest.read_u8()?;
+            let _segment_selector_size = rest.read_u8()?;
+        }
+
         let header_length = rest.read_word(format).and_then(R::Offset::from_u64)?;
 
         let mut program_buf = rest.clone();
         program_buf.skip(header_length)?;
         rest.truncate(header_length)?;
+        let program_offset = offset.0 + R::Offset::from_u8(format.initial_length_size())
+            + unit_length - program_buf.len();
 
         let minimum_instruction_length = rest.read_u8()?;
         if minimum_instruction_length == 0 {
@@ -1183,21 +1625,37 @@ impl<R: Reader> LineNumberProgramHeader<R> {
         let standard_opcode_lengths = rest.split(standard_opcode_count)?;
 
         let mut include_directories = Vec::new();
-        loop {
-            let directory = rest.read_null_terminated_slice()?;
-            if directory.is_empty() {
-                break;
+        let mut file_names = Vec::new();
+        if version >= 5 {
+            let strings = (debug_str, debug_line_str);
+            let formats = parse_entry_formats(rest)?;
+            let count = rest.read_uleb128()?;
+            for _ in 0..count {
+                let entry = FileEntry::parse_v5(rest, format, &formats, strings)?;
+                include_directories.push(entry.path_name);
             }
-            include_directories.push(directory);
-        }
 
-        let mut file_names = Vec::new();
-        loop {
-            let path_name = rest.read_null_terminated_slice()?;
-            if path_name.is_empty() {
-                break;
+            let formats = parse_entry_formats(rest)?;
+            let count = rest.read_uleb128()?;
+            for _ in 0..count {
+                file_names.push(FileEntry::parse_v5(rest, format, &formats, strings)?);
+            }
+        } else {
+            loop {
+                let directory = rest.read_null_terminated_slice()?;
+                if directory.is_empty() {
+                    break;
+                }
+                include_directories.push(directory);
+            }
+
+            loop {
+                let path_name = rest.read_null_terminated_slice()?;
+                if path_name.is_empty() {
+                    break;
+                }
+                file_names.push(FileEntry::parse(rest, path_name)?);
             }
-            file_names.push(FileEntry::parse(rest, path_name)?);
         }
 
         let comp_name = comp_name.map(|name| {
@@ -1210,6 +1668,7 @@ impl<R: Reader> LineNumberProgramHeader<R> {
         });
 
         let header = LineNumberProgramHeader {
+            offset: offset,
             unit_length: unit_length,
             version: version,
             header_length: header_length,
@@ -1224,6 +1683,7 @@ impl<R: Reader> LineNumberProgramHeader<R> {
             file_names: file_names,
             format: format,
             program_buf: program_buf,
+            program_offset: program_offset,
             address_size: address_size,
             comp_dir: comp_dir,
             comp_name: comp_name,
@@ -1351,6 +1811,55 @@ impl<R: Reader> CompleteLineNumberProgram<R> {
     ) -> ResumedStateMachine<'program, R> {
         ResumedStateMachine::resume(self, sequence)
     }
+
+    /// Find the row in the line number matrix that applies to the given
+    /// `address`, using sequences that were previously returned by
+    /// `IncompleteLineNumberProgram::sequences`.
+    ///
+    /// Only the opcodes for the sequence containing `address` are executed, so
+    /// repeated lookups do not need to rerun the entire line number program.
+    ///
+    /// Returns `Ok(None)` if no sequence contains the address.
+    ///
+    /// ```
+    /// # fn foo() {
+    /// use gimli::{IncompleteLineNumberProgram, EndianBuf, NativeEndian};
+    ///
+    /// fn get_line_number_program<'a>() -> IncompleteLineNumberProgram<EndianBuf<'a, NativeEndian>> {
+    ///     // Get a line number program from some offset in a
+    ///     // `.debug_line` section...
+    /// #   unimplemented!()
+    /// }
+    ///
+    /// let program = get_line_number_program();
+    /// let (program, sequences) = program.sequences().unwrap();
+    /// for address in &[0x1000, 0x2000] {
+    ///     if let Some(row) = program.find_row(&sequences, *address).unwrap() {
+    ///         println!("{:x}: line {:?}", address, row.line());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn find_row(
+        &self,
+        sequences: &[LineNumberSequence<R>],
+        address: u64,
+    ) -> parser::Result<Option<LineNumberRow>> {
+        let sequence = match sequences.iter().find(|sequence| sequence.contains(address)) {
+            Some(sequence) => sequence,
+            None => return Ok(None),
+        };
+
+        let mut rows = self.resume_from(sequence);
+        let mut result = None;
+        while let Some((_, row)) = rows.next_row()? {
+            if row.address() > address {
+                break;
+            }
+            result = Some(*row);
+        }
+        Ok(result)
+    }
 }
 
 /// An entry in the `LineNumberProgramHeader`'s `file_names` set.
@@ -1378,6 +1887,42 @@ impl<R: Reader> FileEntry<R> {
         Ok(entry)
     }
 
+    /// Parse a directory or file name entry of a version 5 header, which is
+    /// encoded using the given content types and forms.
+    fn parse_v5(
+        input: &mut R,
+        format: parser::Format,
+        formats: &[(constants::DwLnct, constants::DwForm)],
+        strings: (&DebugStr<R>, &DebugLineStr<R>),
+    ) -> parser::Result<FileEntry<R>> {
+        let mut path_name = None;
+        let mut directory_index = 0;
+        let mut last_modification = 0;
+        let mut length = 0;
+        for &(content_type, form) in formats {
+            let value = parse_entry_value(input, format, form, strings)?;
+            match (content_type, value) {
+                (constants::DW_LNCT_path, EntryValue::String(value)) => path_name = Some(value),
+                (constants::DW_LNCT_directory_index, EntryValue::Udata(value)) => {
+                    directory_index = value;
+                }
+                (constants::DW_LNCT_timestamp, EntryValue::Udata(value)) => {
+                    last_modification = value;
+                }
+                (constants::DW_LNCT_size, EntryValue::Udata(value)) => length = value,
+                // Other content types, such as `DW_LNCT_MD5`, are ignored.
+                _ => {}
+            }
+        }
+
+        Ok(FileEntry {
+            path_name: path_name.ok_or(parser::Error::MissingFileEntryFormatPath)?,
+            directory_index: directory_index,
+            last_modification: last_modification,
+            length: length,
+        })
+    }
+
     /// > A slice containing the full or relative path name of
     /// > a source file. If the entry contains a file name or a relative path
     /// > name, the file is located relative to either the compilation directory
@@ -1398,13 +1943,17 @@ impl<R: Reader> FileEntry<R> {
     /// > in the first directory in the include_directories section, and so
     /// > on. The directory index is ignored for file names that represent full
     /// > path names.
-    pub fn directory_index(&self) -> u64 {
-        self.directory_index
+    ///
+    /// See `DirectoryIndex` for how this index is interpreted in other
+    /// versions.
+    pub fn directory_index(&self) -> DirectoryIndex {
+        DirectoryIndex(self.directory_index)
     }
 
     /// Get this file's directory.
     ///
-    /// A directory index of 0 corresponds to the compilation unit directory.
+    /// Before version 5, a directory index of 0 corresponds to the
+    /// compilation unit directory.
     pub fn directory(&self, header: &LineNumberProgramHeader<R>) -> Option<R> {
         header.directory(self.directory_index)
     }
@@ -1422,6 +1971,62 @@ impl<R: Reader> FileEntry<R> {
     }
 }
 
+/// Parse the content types and forms of the directory or file name entries
+/// of a version 5 header.
+fn parse_entry_formats<R: Reader>(
+    input: &mut R,
+) -> parser::Result<Vec<(constants::DwLnct, constants::DwForm)>> {
+    let count = input.read_u8()?;
+    let mut formats = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let content_type = parser::u64_to_u16(input.read_uleb128()?)?;
+        let form = input.read_uleb128()?;
+        formats.push((constants::DwLnct(content_type), constants::DwForm(form)));
+    }
+    Ok(formats)
+}
+
+/// A field of a directory or file name entry of a version 5 header.
+enum EntryValue<R: Reader> {
+    String(R),
+    Udata(u64),
+    Block,
+}
+
+fn parse_entry_value<R: Reader>(
+    input: &mut R,
+    format: parser::Format,
+    form: constants::DwForm,
+    strings: (&DebugStr<R>, &DebugLineStr<R>),
+) -> parser::Result<EntryValue<R>> {
+    Ok(match form {
+        constants::DW_FORM_string => EntryValue::String(input.read_null_terminated_slice()?),
+        constants::DW_FORM_strp => {
+            let offset = DebugStrOffset(input.read_offset(format)?);
+            EntryValue::String(strings.0.get_str(offset)?)
+        }
+        constants::DW_FORM_line_strp => {
+            let offset = DebugLineStrOffset(input.read_offset(format)?);
+            EntryValue::String(strings.1.get_str(offset)?)
+        }
+        constants::DW_FORM_udata => EntryValue::Udata(input.read_uleb128()?),
+        constants::DW_FORM_data1 => EntryValue::Udata(u64::from(input.read_u8()?)),
+        constants::DW_FORM_data2 => EntryValue::Udata(u64::from(input.read_u16()?)),
+        constants::DW_FORM_data4 => EntryValue::Udata(u64::from(input.read_u32()?)),
+        constants::DW_FORM_data8 => EntryValue::Udata(input.read_u64()?),
+        constants::DW_FORM_data16 => {
+            input.skip(R::Offset::from_u8(16))?;
+            EntryValue::Block
+        }
+        constants::DW_FORM_block => {
+            let len = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            input.skip(len)?;
+            EntryValue::Block
+        }
+        _ => return Err(parser::Error::UnknownForm),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1429,7 +2034,25 @@ mod tests {
     use constants;
     use endianity::{EndianBuf, LittleEndian};
     use parser::{Error, Format};
-    use std::u8;
+    use std::{u64, u8};
+
+    fn parse_header<'input>(
+        input: &mut EndianBuf<'input, LittleEndian>,
+        offset: DebugLineOffset,
+        address_size: u8,
+        comp_dir: Option<EndianBuf<'input, LittleEndian>>,
+        comp_name: Option<EndianBuf<'input, LittleEndian>>,
+    ) -> parser::Result<LineNumberProgramHeader<EndianBuf<'input, LittleEndian>>> {
+        LineNumberProgramHeader::parse(
+            input,
+            offset,
+            address_size,
+            comp_dir,
+            comp_name,
+            &DebugStr::new(&[], LittleEndian),
+            &DebugLineStr::new(&[], LittleEndian),
+        )
+    }
 
     #[test]
     #[cfg_attr(rustfmt, rustfmt_skip)]
@@ -1488,12 +2111,18 @@ mod tests {
         let comp_dir = EndianBuf::new(b"/comp_dir", LittleEndian);
         let comp_name = EndianBuf::new(b"/comp_name", LittleEndian);
 
-        let header = LineNumberProgramHeader::parse(rest, 4, Some(comp_dir), Some(comp_name))
+        let offset = DebugLineOffset(0);
+        let header = parse_header(rest, offset, 4, Some(comp_dir), Some(comp_name))
             .expect("should parse header ok");
 
         assert_eq!(*rest, EndianBuf::new(&buf[buf.len() - 16..], LittleEndian));
+        assert_eq!(
+            header.opcodes().offset(),
+            buf.len() - 16 - header.raw_program_buf().len()
+        );
 
         assert_eq!(header.version, 4);
+        assert_eq!(header.format(), Format::Dwarf32);
         assert_eq!(header.minimum_instruction_length(), 1);
         assert_eq!(header.maximum_operations_per_instruction(), 1);
         assert_eq!(header.default_is_stmt(), true);
@@ -1529,6 +2158,75 @@ mod tests {
         assert_eq!(&*header.file_names(), &expected_file_names);
     }
 
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_parse_debug_line_64_ok() {
+        let buf = [
+            // 64-bit length = 66.
+            0xff, 0xff, 0xff, 0xff,
+            0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Version.
+            0x04, 0x00,
+            // Header length = 40.
+            0x28, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Minimum instruction length.
+            0x01,
+            // Maximum operations per byte.
+            0x01,
+            // Default is_stmt.
+            0x01,
+            // Line base.
+            0x00,
+            // Line range.
+            0x01,
+            // Opcode base.
+            0x03,
+            // Standard opcode lengths for opcodes 1 .. opcode base - 1.
+            0x01, 0x02,
+            // Include directories = '/', 'i', 'n', 'c', '\0', '/', 'i', 'n', 'c', '2', '\0', '\0'
+            0x2f, 0x69, 0x6e, 0x63, 0x00, 0x2f, 0x69, 0x6e, 0x63, 0x32, 0x00, 0x00,
+            // File names
+                // foo.rs
+                0x66, 0x6f, 0x6f, 0x2e, 0x72, 0x73, 0x00,
+                0x00,
+                0x00,
+                0x00,
+                // bar.h
+                0x62, 0x61, 0x72, 0x2e, 0x68, 0x00,
+                0x01,
+                0x00,
+                0x00,
+            // End file names.
+            0x00,
+
+            // Dummy line program data.
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+
+            // Dummy next line program.
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+
+        let header = parse_header(rest, DebugLineOffset(0), 8, None, None)
+            .expect("should parse header ok");
+
+        assert_eq!(*rest, EndianBuf::new(&buf[buf.len() - 16..], LittleEndian));
+        assert_eq!(header.format(), Format::Dwarf64);
+        assert_eq!(header.unit_length(), 0x42);
+        assert_eq!(header.header_length(), 0x28);
+        assert_eq!(header.opcode_base(), 3);
+        assert_eq!(header.include_directories().len(), 2);
+        assert_eq!(header.file_names().len(), 2);
+        assert_eq!(header.raw_program_buf().len(), 16);
+    }
+
     #[test]
     #[cfg_attr(rustfmt, rustfmt_skip)]
     fn test_parse_debug_line_header_length_too_short() {
@@ -1584,8 +2282,8 @@ mod tests {
 
         let input = &mut EndianBuf::new(&buf, LittleEndian);
 
-        match LineNumberProgramHeader::parse(input, 4, None, None) {
-            Err(Error::UnexpectedEof) => return,
+        match parse_header(input, DebugLineOffset(0), 4, None, None) {
+            Err(Error::UnexpectedEof(_)) => return,
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         }
     }
@@ -1645,8 +2343,8 @@ mod tests {
 
         let input = &mut EndianBuf::new(&buf, LittleEndian);
 
-        match LineNumberProgramHeader::parse(input, 4, None, None) {
-            Err(Error::UnexpectedEof) => return,
+        match parse_header(input, DebugLineOffset(0), 4, None, None) {
+            Err(Error::UnexpectedEof(_)) => return,
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         }
     }
@@ -1658,12 +2356,14 @@ mod tests {
         buf: EndianBuf<LittleEndian>,
     ) -> LineNumberProgramHeader<EndianBuf<LittleEndian>> {
         LineNumberProgramHeader {
+            offset: DebugLineOffset(0),
             opcode_base: OPCODE_BASE,
             address_size: 8,
             minimum_instruction_length: 1,
             maximum_operations_per_instruction: 1,
             default_is_stmt: true,
             program_buf: buf,
+            program_offset: 0,
             version: 4,
             header_length: 1,
             file_names: vec![
@@ -1786,6 +2486,47 @@ mod tests {
         assert_eq!(*rest, *input.range_from(1..));
     }
 
+    #[test]
+    fn test_parse_small_opcode_base() {
+        // DWARF 2 only defines 9 standard opcodes.
+        let input = [10, 1, 2, 3];
+        let input = EndianBuf::new(&input, LittleEndian);
+        let mut header = make_test_header(input);
+        header.opcode_base = 10;
+        header.standard_opcode_lengths =
+            EndianBuf::new(&STANDARD_OPCODE_LENGTHS[..9], LittleEndian);
+
+        let mut rest = input;
+        let opcode = Opcode::parse(&header, &mut rest).expect("Should parse the opcode OK");
+
+        assert_eq!(opcode, Opcode::Special(10));
+        assert_eq!(*rest, *input.range_from(1..));
+    }
+
+    #[test]
+    fn test_parse_redefined_standard_opcode() {
+        // DW_LNS_copy redefined to take two operands.
+        let input = [constants::DW_LNS_copy.0, 1, 2, 3];
+        let input = EndianBuf::new(&input, LittleEndian);
+        let mut standard_opcode_lengths = Vec::new();
+        let mut header = make_test_header(input);
+        standard_opcode_lengths.extend(header.standard_opcode_lengths.buf());
+        standard_opcode_lengths[0] = 2;
+        header.standard_opcode_lengths = EndianBuf::new(&standard_opcode_lengths, LittleEndian);
+
+        let mut rest = input;
+        let opcode = Opcode::parse(&header, &mut rest).expect("Should parse the opcode OK");
+
+        assert_eq!(
+            opcode,
+            Opcode::UnknownStandardN(
+                constants::DW_LNS_copy,
+                EndianBuf::new(&[1, 2], LittleEndian)
+            )
+        );
+        assert_eq!(*rest, *input.range_from(3..));
+    }
+
     #[test]
     fn test_parse_unknown_standard_opcode_one_arg() {
         let input = [OPCODE_BASE, 1, 2, 3];
@@ -1826,7 +2567,7 @@ mod tests {
             opcode,
             Opcode::UnknownStandardN(constants::DwLns(OPCODE_BASE), args)
         );
-        assert_eq!(*rest, []);
+        assert!(rest.is_empty());
     }
 
     #[test]
@@ -2080,6 +2821,22 @@ mod tests {
         assert_exec_opcode(header, initial_registers, opcode, expected_registers, false);
     }
 
+    #[test]
+    fn test_exec_advance_pc_overflow() {
+        // Producers such as wasm linkers mark the sequences of discarded
+        // functions with a tombstone address, so advancing from it must
+        // not panic.
+        let header = make_test_header(EndianBuf::new(&[], LittleEndian));
+        let mut initial_registers = new_registers();
+        initial_registers.address = u64::MAX;
+        let opcode = Opcode::AdvancePc(42);
+
+        let mut expected_registers = initial_registers.clone();
+        expected_registers.address = 41;
+
+        assert_exec_opcode(header, initial_registers, opcode, expected_registers, false);
+    }
+
     #[test]
     fn test_exec_advance_line() {
         let header = make_test_header(EndianBuf::new(&[], LittleEndian));
@@ -2154,6 +2911,143 @@ mod tests {
         assert_eq!(row.file(&header), Some(&header.file_names()[1]));
     }
 
+    #[test]
+    fn test_file_index_version_base() {
+        let mut header = make_test_header(EndianBuf::new(&[], LittleEndian));
+        header.include_directories = vec![EndianBuf::new(b"/inc", LittleEndian)];
+        header.comp_dir = Some(EndianBuf::new(b"/comp_dir", LittleEndian));
+
+        assert_eq!(FileIndex(0).file(&header), None);
+        assert_eq!(FileIndex(1).file(&header), Some(&header.file_names()[0]));
+        assert_eq!(FileIndex(2).file(&header), Some(&header.file_names()[1]));
+        assert_eq!(FileIndex(3).file(&header), None);
+        assert_eq!(DirectoryIndex(0).directory(&header), header.comp_dir);
+        assert_eq!(
+            DirectoryIndex(1).directory(&header),
+            Some(EndianBuf::new(b"/inc", LittleEndian))
+        );
+    }
+
+    #[test]
+    fn test_parse_v5_header() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // 32-bit length = 89.
+            0x59, 0x00, 0x00, 0x00,
+            // Version.
+            0x05, 0x00,
+            // Address size.
+            0x04,
+            // Segment selector size.
+            0x00,
+            // Header length = 80.
+            0x50, 0x00, 0x00, 0x00,
+            // Minimum instruction length.
+            0x01,
+            // Maximum operations per instruction.
+            0x01,
+            // Default is_stmt.
+            0x01,
+            // Line base.
+            0xfb,
+            // Line range.
+            0x0e,
+            // Opcode base.
+            0x0d,
+            // Standard opcode lengths for opcodes 1 .. 12.
+            0x00, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+            // Directory entry format: DW_LNCT_path, DW_FORM_line_strp.
+            0x01, 0x01, 0x1f,
+            // Directories.
+            0x02,
+            0x00, 0x00, 0x00, 0x00,
+            0x0a, 0x00, 0x00, 0x00,
+            // File name entry format: DW_LNCT_path, DW_FORM_string;
+            // DW_LNCT_directory_index, DW_FORM_data1; DW_LNCT_MD5, DW_FORM_data16.
+            0x03, 0x01, 0x08, 0x02, 0x0b, 0x05, 0x1e,
+            // File names.
+            0x02,
+            b'a', b'.', b'c', 0x00,
+            0x00,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+            b'b', b'.', b'h', 0x00,
+            0x01,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+            // Line number program: DW_LNS_copy.
+            0x01,
+        ];
+        let debug_line_str = b"/comp_dir\0/inc\0";
+
+        let debug_line = DebugLine::new(&buf, LittleEndian);
+        let program = debug_line
+            .program_with_strings(
+                DebugLineOffset(0),
+                4,
+                None,
+                None,
+                &DebugStr::new(&[], LittleEndian),
+                &DebugLineStr::new(debug_line_str, LittleEndian),
+            )
+            .expect("should parse header ok");
+        let header = program.header();
+        assert_eq!(header.version(), 5);
+        assert_eq!(header.line_base(), -5);
+        assert_eq!(header.opcode_base(), 13);
+        assert_eq!(header.include_directories().len(), 2);
+        assert_eq!(header.file_names().len(), 2);
+        assert_eq!(header.raw_program_buf(), EndianBuf::new(&buf[92..], LittleEndian));
+
+        // Version 5 file and directory indices are 0-based.
+        let file = FileIndex(0).file(header).unwrap();
+        assert_eq!(file.path_name(), EndianBuf::new(b"a.c", LittleEndian));
+        assert_eq!(
+            file.directory(header),
+            Some(EndianBuf::new(b"/comp_dir", LittleEndian))
+        );
+        let file = FileIndex(1).file(header).unwrap();
+        assert_eq!(file.path_name(), EndianBuf::new(b"b.h", LittleEndian));
+        assert_eq!(file.directory_index(), DirectoryIndex(1));
+        assert_eq!(file.directory(header), Some(EndianBuf::new(b"/inc", LittleEndian)));
+        assert_eq!(FileIndex(2).file(header), None);
+        assert_eq!(DirectoryIndex(2).directory(header), None);
+
+        // The directories can't be resolved without the `.debug_line_str` section.
+        match debug_line.program(DebugLineOffset(0), 4, None, None) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_parse_v5_header_missing_path() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // 32-bit length = 28.
+            0x1c, 0x00, 0x00, 0x00,
+            // Version, address size, segment selector size.
+            0x05, 0x00, 0x04, 0x00,
+            // Header length = 20.
+            0x14, 0x00, 0x00, 0x00,
+            // Minimum instruction length, maximum operations per instruction,
+            // default is_stmt, line base, line range, opcode base.
+            0x01, 0x01, 0x01, 0xfb, 0x0e, 0x0a,
+            // Standard opcode lengths for opcodes 1 .. 9.
+            0x00, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01,
+            // Directory entry format: DW_LNCT_size, DW_FORM_udata.
+            0x01, 0x04, 0x0f,
+            // Directories.
+            0x01, 0x00,
+        ];
+
+        let input = &mut EndianBuf::new(&buf, LittleEndian);
+        assert_eq!(
+            parse_header(input, DebugLineOffset(0), 4, None, None),
+            Err(Error::MissingFileEntryFormatPath)
+        );
+    }
+
     #[test]
     fn test_exec_set_column() {
         let header = make_test_header(EndianBuf::new(&[], LittleEndian));
@@ -2321,6 +3215,164 @@ mod tests {
         assert_eq!(Some(&file), sm.header().file_names.last());
     }
 
+    #[test]
+    fn test_find_row() {
+        let mut buf = vec![0, 9, constants::DW_LNE_set_address.0];
+        buf.extend_from_slice(&[0x00, 0x10, 0, 0, 0, 0, 0, 0]);
+        buf.extend_from_slice(&[
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            4,
+            constants::DW_LNS_advance_line.0,
+            1,
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            4,
+            0,
+            1,
+            constants::DW_LNE_end_sequence.0,
+        ]);
+        let program = make_test_program(EndianBuf::new(&buf, LittleEndian));
+        let (program, sequences) = program.sequences().unwrap();
+        assert_eq!(sequences.len(), 1);
+        assert!(sequences[0].contains(0x1000));
+        assert!(!sequences[0].contains(0x1008));
+
+        let line = |address| {
+            program
+                .find_row(&sequences, address)
+                .map(|row| row.and_then(|row| row.line()))
+        };
+        assert_eq!(line(0xfff), Ok(None));
+        assert_eq!(line(0x1000), Ok(Some(1)));
+        assert_eq!(line(0x1003), Ok(Some(1)));
+        assert_eq!(line(0x1004), Ok(Some(2)));
+        assert_eq!(line(0x1007), Ok(Some(2)));
+        assert_eq!(line(0x1008), Ok(None));
+    }
+
+    #[test]
+    fn test_line_instructions() {
+        let buf = [
+            OPCODE_BASE + 2 * 12 + 5,
+            constants::DW_LNS_const_add_pc.0,
+            constants::DW_LNS_advance_line.0,
+            0x7f,
+            0,
+            1,
+            constants::DW_LNE_end_sequence.0,
+        ];
+        let header = make_test_header(EndianBuf::new(&buf, LittleEndian));
+        let mut instructions = header.instructions();
+        assert_eq!(
+            instructions.next(),
+            Ok(Some(LineInstruction::Special {
+                opcode: OPCODE_BASE + 2 * 12 + 5,
+                line_advance: 2,
+                operation_advance: 2,
+            }))
+        );
+        assert_eq!(
+            instructions.next(),
+            Ok(Some(LineInstruction::ConstAddPc {
+                operation_advance: 20,
+            }))
+        );
+        assert_eq!(
+            instructions.next(),
+            Ok(Some(LineInstruction::Opcode(Opcode::AdvanceLine(-1))))
+        );
+        assert_eq!(
+            instructions.next(),
+            Ok(Some(LineInstruction::Opcode(Opcode::EndSequence)))
+        );
+        assert_eq!(instructions.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_next_row_limits() {
+        let buf = [
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            1,
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_copy.0,
+        ];
+        let program = make_test_program(EndianBuf::new(&buf, LittleEndian));
+        let mut sm = program.rows();
+        let mut limits = parser::ReaderLimits::default();
+        limits.max_line_instructions = Some(3);
+        sm.set_limits(&limits);
+
+        assert!(sm.next_row().unwrap().is_some());
+        assert!(sm.next_row().unwrap().is_some());
+        assert_eq!(sm.next_row().map(|_| ()), Err(Error::LimitExceeded));
+        assert_eq!(sm.next_row().map(|r| r.is_none()), Ok(true));
+    }
+
+    #[test]
+    fn test_rows_address_adjustment() {
+        let mut buf = vec![0, 9, constants::DW_LNE_set_address.0];
+        buf.extend_from_slice(&0x100u64.to_le_bytes());
+        buf.extend_from_slice(&[
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            0x10,
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            4,
+            0,
+            1,
+            constants::DW_LNE_end_sequence.0,
+        ]);
+        // A sequence that has no adjusted addresses.
+        buf.extend_from_slice(&[0, 9, constants::DW_LNE_set_address.0]);
+        buf.extend_from_slice(&0x2000u64.to_le_bytes());
+        buf.extend_from_slice(&[
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            4,
+            0,
+            1,
+            constants::DW_LNE_end_sequence.0,
+        ]);
+
+        let program = make_test_program(EndianBuf::new(&buf, LittleEndian));
+        let mut sm = program.rows();
+        sm.set_address_adjustment(Some(AddressAdjustment::new(|address| match address {
+            0x110 => None,
+            address if address < 0x1000 => Some(address + 0x4000),
+            _ => None,
+        })));
+        let rows = sm.collect::<Vec<_>>().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].address(), 0x4100);
+        assert!(!rows[0].end_sequence());
+        // The end of the sequence is relative to the previous row that has an
+        // adjusted address.
+        assert_eq!(rows[1].address(), 0x4114);
+        assert!(rows[1].end_sequence());
+    }
+
+    #[test]
+    fn test_rows_fallible_iterator() {
+        let buf = [
+            constants::DW_LNS_copy.0,
+            constants::DW_LNS_advance_pc.0,
+            4,
+            constants::DW_LNS_advance_line.0,
+            1,
+            constants::DW_LNS_copy.0,
+        ];
+        let program = make_test_program(EndianBuf::new(&buf, LittleEndian));
+        let rows = program.rows().collect::<Vec<_>>().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].address(), 0);
+        assert_eq!(rows[0].line(), Some(1));
+        assert_eq!(rows[1].address(), 4);
+        assert_eq!(rows[1].line(), Some(2));
+    }
+
     #[test]
     fn test_exec_set_discriminator() {
         let header = make_test_header(EndianBuf::new(&[], LittleEndian));
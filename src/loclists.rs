@@ -3,9 +3,9 @@ use endianity::{EndianBuf, Endianity};
 use fallible_iterator::FallibleIterator;
 use op::Expression;
 use parser::{self, Format, Error, Result};
-use reader::{Reader, ReaderOffset};
-use rnglists::{AddressIndex, Range};
-use Section;
+use reader::{Reader, ReaderOffset, ReaderOffsetId};
+use rnglists::{is_tombstone_entry, AddressAdjustment, AddressIndex, Range, TombstoneMode};
+use {Section, SectionId};
 
 /// The `DebugLoc` struct represents the DWARF strings
 /// found in the `.debug_loc` section.
@@ -38,8 +38,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugLoc<R> {
-    fn section_name() -> &'static str {
-        ".debug_loc"
+    fn id() -> SectionId {
+        SectionId::DebugLoc
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_loc_section
     }
 }
 
@@ -80,8 +84,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugLocLists<R> {
-    fn section_name() -> &'static str {
-        ".debug_loclists"
+    fn id() -> SectionId {
+        SectionId::DebugLocLists
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_loclists_section
     }
 }
 
@@ -117,10 +125,9 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<LocListsHeader> {
     }
 
     let address_size = input.read_u8()?;
-    let segment_selector_size = input.read_u8()?;
-    if segment_selector_size != 0 {
-        return Err(Error::UnsupportedSegmentSize);
-    }
+    // The list entries never contain segment selectors, so we can ignore
+    // the segment selector size.
+    let _segment_selector_size = input.read_u8()?;
     let offset_entry_count = input.read_u32()?;
     Ok(LocListsHeader {
         format: format,
@@ -132,13 +139,24 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<LocListsHeader> {
 /// An offset into either the `.debug_loc` section or the `.debug_loclists` section,
 /// depending on the version of the unit the offset was contained in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LocationListsOffset<T = usize>(pub T);
 
+/// An offset to a set of offsets in the `.debug_loclists` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugLocListsBase<T = usize>(pub T);
+
+/// An index into a set of offsets in the `.debug_loclists` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugLocListsIndex<T = usize>(pub T);
+
 /// The DWARF data found in `.debug_loc` and `.debug_loclists` sections.
 #[derive(Debug, Clone, Copy)]
 pub struct LocationLists<R: Reader> {
-    debug_loc: DebugLoc<R>,
-    debug_loclists: DebugLocLists<R>,
+    pub(crate) debug_loc: DebugLoc<R>,
+    pub(crate) debug_loclists: DebugLocLists<R>,
     header: LocListsHeader,
 }
 
@@ -163,6 +181,56 @@ impl<R: Reader> LocationLists<R> {
         })
     }
 
+    /// Returns the `SectionId` and section offset corresponding to the given
+    /// `id`, if it is associated with one of these sections.
+    pub fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<(SectionId, R::Offset)> {
+        self.debug_loc
+            .lookup_offset_id(id)
+            .or_else(|| self.debug_loclists.lookup_offset_id(id))
+    }
+
+    /// Return the location list offset at the given index of a unit's set of
+    /// offsets in the `.debug_loclists` section.
+    ///
+    /// `format` must be the format of the unit, and `base` must be the unit's
+    /// `DW_AT_loclists_base` attribute. The offsets are relative to `base`.
+    ///
+    /// Returns `Error::OffsetOutOfBounds` if `index` is not less than the
+    /// offset entry count of the table at `base`.
+    pub fn get_offset(
+        &self,
+        format: Format,
+        base: DebugLocListsBase<R::Offset>,
+        index: DebugLocListsIndex<R::Offset>,
+    ) -> Result<LocationListsOffset<R::Offset>> {
+        let input = &mut self.debug_loclists.debug_loclists_section.clone();
+        // The offsets follow the header of their table, which ends with the
+        // number of offsets.
+        let count_size = R::Offset::from_u8(4);
+        if base.0 < count_size {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        input.skip(base.0 - count_size)?;
+        let offset_entry_count = input.read_u32()?;
+        if index.0.into_u64() >= u64::from(offset_entry_count) {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let offset = index
+            .0
+            .into_u64()
+            .checked_mul(u64::from(format.word_size()))
+            .ok_or(Error::OffsetOutOfBounds)?;
+        input.skip(R::Offset::from_u64(offset)?)?;
+        let offset = input.read_offset(format)?;
+        Ok(LocationListsOffset(base.0.wrapping_add(offset)))
+    }
+
+    /// Return the size of the header of the first set of offsets in the
+    /// `.debug_loclists` section.
+    pub(crate) fn header_size(&self) -> u8 {
+        self.header.size()
+    }
+
     /// Iterate over the `LocationListEntry`s starting at the given offset.
     ///
     /// The `unit_version` and `address_size` must match the compilation unit that the
@@ -200,18 +268,20 @@ impl<R: Reader> LocationLists<R> {
         unit_version: u16,
         address_size: u8,
     ) -> Result<RawLocListIter<R>> {
-        if unit_version < 5 {
+        let mut iter = if unit_version < 5 {
             let mut input = self.debug_loc.debug_loc_section.clone();
             input.skip(offset.0)?;
-            Ok(RawLocListIter::new(input, unit_version, address_size))
+            RawLocListIter::new(input, unit_version, address_size)
         } else {
             if offset.0 < R::Offset::from_u8(self.header.size()) {
                 return Err(Error::OffsetOutOfBounds);
             }
             let mut input = self.debug_loclists.debug_loclists_section.clone();
             input.skip(offset.0)?;
-            Ok(RawLocListIter::new(input, unit_version, self.header.address_size))
-        }
+            RawLocListIter::new(input, unit_version, self.header.address_size)
+        };
+        iter.offset = offset;
+        Ok(iter)
     }
 }
 
@@ -219,15 +289,16 @@ impl<R: Reader> LocationLists<R> {
 ///
 /// This iterator does not perform any processing of the location entries,
 /// such as handling base addresses.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RawLocListIter<R: Reader> {
     input: R,
+    offset: LocationListsOffset<R::Offset>,
     version: u16,
     address_size: u8,
 }
 
 /// A raw entry in .debug_loclists.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RawLocListEntry<R: Reader> {
     /// DW_LLE_base_address
     BaseAddress {
@@ -380,22 +451,37 @@ impl<R: Reader> RawLocListEntry<R> {
 
 impl<R: Reader> RawLocListIter<R> {
     /// Construct a `RawLocListIter`.
+    ///
+    /// The offsets returned by `offset()` are relative to the start of `input`.
     pub fn new(input: R, version: u16, address_size: u8) -> RawLocListIter<R> {
         RawLocListIter {
             input,
+            offset: LocationListsOffset(R::Offset::from_u8(0)),
             version,
             address_size,
         }
     }
 
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    ///
+    /// Entries are returned in the order that they occur in the list. If
+    /// parsing an entry fails, this is the offset of that entry.
+    #[inline]
+    pub fn offset(&self) -> LocationListsOffset<R::Offset> {
+        self.offset
+    }
+
     /// Advance the iterator to the next location.
     pub fn next(&mut self) -> Result<Option<RawLocListEntry<R>>> {
         if self.input.is_empty() {
             return Ok(None);
         }
 
+        let len = self.input.len();
         match RawLocListEntry::parse(&mut self.input, self.version, self.address_size) {
             Ok(entry) => {
+                self.offset.0 += len - self.input.len();
                 if entry.is_none() {
                     self.input.empty();
                 }
@@ -427,6 +513,8 @@ impl<R: Reader> FallibleIterator for RawLocListIter<R> {
 pub struct LocListIter<R: Reader> {
     raw: RawLocListIter<R>,
     base_address: u64,
+    tombstones: TombstoneMode,
+    adjustment: Option<AddressAdjustment>,
 }
 
 impl<R: Reader> LocListIter<R> {
@@ -435,17 +523,101 @@ impl<R: Reader> LocListIter<R> {
         LocListIter {
             raw: raw,
             base_address: base_address,
+            tombstones: TombstoneMode::default(),
+            adjustment: None,
         }
     }
 
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    ///
+    /// This includes entries, such as base address selections, that are not
+    /// returned by the iterator.
+    #[inline]
+    pub fn offset(&self) -> LocationListsOffset<R::Offset> {
+        self.raw.offset()
+    }
+
+    /// Set how entries for code that was discarded by the linker are
+    /// handled.
+    ///
+    /// The default is `TombstoneMode::Keep`.
+    pub fn set_tombstone_mode(&mut self, tombstones: TombstoneMode) {
+        self.tombstones = tombstones;
+    }
+
+    /// Set the adjustment to apply to the ranges of the returned locations.
+    ///
+    /// Locations whose range has no adjusted address are omitted. Entries
+    /// for discarded code and default location entries are not adjusted.
+    /// The default is no adjustment.
+    pub fn set_address_adjustment(&mut self, adjustment: Option<AddressAdjustment>) {
+        self.adjustment = adjustment;
+    }
+
     /// Advance the iterator to the next location.
     pub fn next(&mut self) -> Result<Option<LocationListEntry<R>>> {
+        self.next_entry().map(|entry| entry.map(|(entry, _)| entry))
+    }
+
+    /// Find the location description that is valid at the given address.
+    ///
+    /// Returns the first entry whose range contains `address`, after
+    /// applying base addresses. A default location entry is only returned if
+    /// no other entry contains the address, and its range covers all
+    /// addresses. Empty ranges never contain an address.
+    ///
+    /// This consumes the remaining entries of the iterator.
+    pub fn find_location(&mut self, address: u64) -> Result<Option<LocationListEntry<R>>> {
+        let mut default = None;
+        while let Some((entry, is_default)) = self.next_entry()? {
+            if is_default {
+                if default.is_none() {
+                    default = Some(entry);
+                }
+            } else if entry.range.begin <= address && address < entry.range.end {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(default)
+    }
+
+    /// Return true if an entry is for code that was discarded by the linker.
+    fn is_tombstone(&self, base_address: Option<u64>, begin: u64) -> bool {
+        is_tombstone_entry(
+            self.tombstones,
+            self.raw.version,
+            self.raw.address_size,
+            base_address,
+            begin,
+        )
+    }
+
+    /// Advance the iterator to the next location, and return whether it is
+    /// a default location entry.
+    fn next_entry(&mut self) -> Result<Option<(LocationListEntry<R>, bool)>> {
         loop {
             let raw_loc = match self.raw.next()? {
                 Some(loc) => loc,
                 None => return Ok(None),
             };
 
+            let discarded = match raw_loc {
+                RawLocListEntry::OffsetPair { begin, .. } => {
+                    self.is_tombstone(Some(self.base_address), begin)
+                }
+                RawLocListEntry::StartEnd { begin, .. } |
+                RawLocListEntry::StartLength { begin, .. } => self.is_tombstone(None, begin),
+                _ => false,
+            };
+            if discarded {
+                continue;
+            }
+
+            let is_default = match raw_loc {
+                RawLocListEntry::DefaultLocation { .. } => true,
+                _ => false,
+            };
             let (range, data) = match raw_loc {
                 RawLocListEntry::BaseAddress { addr } => {
                     self.base_address = addr;
@@ -463,7 +635,8 @@ impl<R: Reader> LocListIter<R> {
                     (Range { begin: begin, end: end }, data)
                 },
                 RawLocListEntry::StartLength { begin, length, data } => {
-                    (Range { begin: begin, end: begin + length }, data)
+                    let end = begin.checked_add(length).ok_or(Error::AddressOverflow)?;
+                    (Range { begin: begin, end: end }, data)
                 },
                 _ => {
                     // We don't support AddressIndex-based entries yet
@@ -476,10 +649,19 @@ impl<R: Reader> LocListIter<R> {
                 return Err(Error::InvalidLocationAddressRange);
             }
 
-            return Ok(Some(LocationListEntry {
+            let range = match self.adjustment {
+                Some(ref adjustment) if !is_default => match adjustment.adjust_range(range) {
+                    Some(range) => range,
+                    None => continue,
+                },
+                _ => range,
+            };
+
+            let entry = LocationListEntry {
                 range: range,
                 data: data,
-            }));
+            };
+            return Ok(Some((entry, is_default)));
         }
     }
 }
@@ -495,6 +677,7 @@ impl<R: Reader> FallibleIterator for LocListIter<R> {
 
 /// A location list entry from the `.debug_loc` or `.debug_loclists` sections.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LocationListEntry<R: Reader> {
     /// The address range that this location is valid for.
     pub range: Range,
@@ -675,6 +858,80 @@ mod tests {
         assert_eq!(locations.next(), Ok(None));
     }
 
+    #[test]
+    fn test_raw_loclists() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A BaseAddressx followed by an OffsetPair.
+            .L8(1).uleb(1)
+            .L8(4).uleb(0x100).uleb(0x200).uleb(4).L32(2)
+            // A StartxEndx
+            .L8(2).uleb(2).uleb(3).uleb(4).L32(3)
+            // A StartxLength
+            .L8(3).uleb(4).uleb(0x100).uleb(4).L32(4)
+            // A BaseAddress
+            .L8(6).L32(0x02000000)
+            // A range end.
+            .L8(0)
+            // Some extra data.
+            .L32(0xffffffff);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_loc = DebugLoc::new(&[], LittleEndian);
+        let debug_loclists = DebugLocLists::new(&buf, LittleEndian);
+        let loclists = LocationLists::new(debug_loc, debug_loclists).unwrap();
+        let offset = LocationListsOffset((&first - &start) as usize);
+        let mut locations = loclists.raw_locations(offset, 5, 0).unwrap();
+        assert_eq!(
+            locations.next(),
+            Ok(Some(RawLocListEntry::BaseAddressx {
+                addr: AddressIndex(1),
+            }))
+        );
+        assert_eq!(
+            locations.next(),
+            Ok(Some(RawLocListEntry::OffsetPair {
+                begin: 0x100,
+                end: 0x200,
+                data: Expression(EndianBuf::new(&[2, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(
+            locations.next(),
+            Ok(Some(RawLocListEntry::StartxEndx {
+                begin: AddressIndex(2),
+                end: AddressIndex(3),
+                data: Expression(EndianBuf::new(&[3, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(
+            locations.next(),
+            Ok(Some(RawLocListEntry::StartxLength {
+                begin: AddressIndex(4),
+                length: 0x100,
+                data: Expression(EndianBuf::new(&[4, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(
+            locations.next(),
+            Ok(Some(RawLocListEntry::BaseAddress {
+                addr: 0x02000000,
+            }))
+        );
+        assert_eq!(locations.next(), Ok(None));
+    }
+
     #[test]
     fn test_loclists_64() {
         let start = Label::new();
@@ -1068,6 +1325,195 @@ mod tests {
         assert_eq!(locations.next(), Ok(None));
     }
 
+    #[test]
+    fn test_loclists_overflow() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(8)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A StartLength whose end overflows.
+            .L8(8).L64(0xffff_ffff_ffff_ff00).uleb(0x100).uleb(4).L32(1)
+            // A location list end.
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_loc = DebugLoc::new(&[], LittleEndian);
+        let debug_loclists = DebugLocLists::new(&buf, LittleEndian);
+        let loclists = LocationLists::new(debug_loc, debug_loclists).unwrap();
+        let offset = LocationListsOffset((&first - &start) as usize);
+        let mut locations = loclists.locations(offset, 5, 8, 0).unwrap();
+        assert_eq!(locations.next(), Err(Error::AddressOverflow));
+    }
+
+    #[test]
+    fn test_loclists_offsets() {
+        let start = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(1)
+            // Offsets
+            .L32(4)
+            // A StartLength
+            .L8(8).L32(0x2000).uleb(0x10).uleb(4).L32(2)
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_loc = DebugLoc::new(&[], LittleEndian);
+        let debug_loclists = DebugLocLists::new(&buf, LittleEndian);
+        let loclists = LocationLists::new(debug_loc, debug_loclists).unwrap();
+        let base = DebugLocListsBase(12);
+
+        let offset = loclists.get_offset(Format::Dwarf32, base, DebugLocListsIndex(0));
+        assert_eq!(offset, Ok(LocationListsOffset(16)));
+        let mut locations = loclists.locations(offset.unwrap(), 5, 0, 0).unwrap();
+        assert_eq!(
+            locations.next(),
+            Ok(Some(LocationListEntry {
+                range: Range {
+                    begin: 0x2000,
+                    end: 0x2010,
+                },
+                data: Expression(EndianBuf::new(&[2, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(locations.next(), Ok(None));
+
+        assert_eq!(
+            loclists.get_offset(Format::Dwarf32, base, DebugLocListsIndex(2)),
+            Err(Error::OffsetOutOfBounds)
+        );
+        assert_eq!(
+            loclists.get_offset(Format::Dwarf32, base, DebugLocListsIndex(100)),
+            Err(Error::OffsetOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_find_location() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A DefaultLocation
+            .L8(5).uleb(4).L32(1)
+            // An OffsetPair
+            .L8(4).uleb(0x100).uleb(0x200).uleb(4).L32(2)
+            // A base address selection followed by an OffsetPair.
+            .L8(6).L32(0x02000000)
+            .L8(4).uleb(0x100).uleb(0x200).uleb(4).L32(3)
+            // An empty StartEnd
+            .L8(7).L32(0x3000).L32(0x3000).uleb(4).L32(4)
+            // A range end.
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_loc = DebugLoc::new(&[], LittleEndian);
+        let debug_loclists = DebugLocLists::new(&buf, LittleEndian);
+        let loclists = LocationLists::new(debug_loc, debug_loclists).unwrap();
+        let offset = LocationListsOffset((&first - &start) as usize);
+        let find = |address| {
+            let mut locations = loclists.locations(offset, 5, 0, 0x01000000).unwrap();
+            locations.find_location(address).unwrap()
+        };
+
+        let entry = find(0x01000100).unwrap();
+        assert_eq!(entry.range, Range { begin: 0x01000100, end: 0x01000200 });
+        assert_eq!(entry.data, Expression(EndianBuf::new(&[2, 0, 0, 0], LittleEndian)));
+
+        let entry = find(0x020001ff).unwrap();
+        assert_eq!(entry.range, Range { begin: 0x02000100, end: 0x02000200 });
+        assert_eq!(entry.data, Expression(EndianBuf::new(&[3, 0, 0, 0], LittleEndian)));
+
+        // The default location is used for addresses that no other entry
+        // contains, including the end of a range and empty ranges.
+        for &address in &[0, 0x01000200, 0x3000, u64::max_value()] {
+            let entry = find(address).unwrap();
+            assert_eq!(entry.data, Expression(EndianBuf::new(&[1, 0, 0, 0], LittleEndian)));
+        }
+
+        // Without a default location, there is no match.
+        let mut locations = loclists
+            .locations(LocationListsOffset((&first - &start) as usize + 6), 5, 0, 0x01000000)
+            .unwrap();
+        assert_eq!(locations.find_location(0x3000), Ok(None));
+    }
+
+    #[test]
+    fn test_location_list_tombstones() {
+        let section = Section::with_endian(Endian::Little)
+            // A location for discarded code, with tombstone support.
+            .L32(0xfffffffe).L32(0xfe).L16(4).L32(1)
+            // A normal location.
+            .L32(0x10200).L32(0x10300).L16(4).L32(2)
+            // A location for discarded code, without tombstone support.
+            .L32(1).L32(0x101).L16(4).L32(3)
+            // A location list end.
+            .L32(0).L32(0);
+
+        let buf = section.get_contents().unwrap();
+        let debug_loc = DebugLoc::new(&buf, LittleEndian);
+        let debug_loclists = DebugLocLists::new(&[], LittleEndian);
+        let loclists = LocationLists::new(debug_loc, debug_loclists).unwrap();
+        let offset = LocationListsOffset(0);
+        let version = 4;
+
+        let mut locations = loclists.locations(offset, version, 4, 0).unwrap();
+        assert_eq!(locations.next(), Err(Error::InvalidLocationAddressRange));
+
+        let mut locations = loclists.locations(offset, version, 4, 0).unwrap();
+        locations.set_tombstone_mode(TombstoneMode::SkipZero);
+        assert_eq!(locations.next(), Err(Error::InvalidLocationAddressRange));
+
+        let mut locations = loclists.locations(offset, version, 4, 0).unwrap();
+        locations.set_tombstone_mode(TombstoneMode::SkipMax);
+        assert_eq!(
+            locations.next(),
+            Ok(Some(LocationListEntry {
+                range: Range {
+                    begin: 0x10200,
+                    end: 0x10300,
+                },
+                data: Expression(EndianBuf::new(&[2, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(
+            locations.next(),
+            Ok(Some(LocationListEntry {
+                range: Range {
+                    begin: 1,
+                    end: 0x101,
+                },
+                data: Expression(EndianBuf::new(&[3, 0, 0, 0], LittleEndian)),
+            }))
+        );
+        assert_eq!(locations.next(), Ok(None));
+    }
+
     #[test]
     fn test_locations_invalid() {
         let section = Section::with_endian(Endian::Little)
@@ -1096,7 +1542,7 @@ mod tests {
 
         // An invalid offset.
         match loclists.locations(LocationListsOffset(buf.len() + 1), version, 4, 0x01000000) {
-            Err(Error::UnexpectedEof) => {}
+            Err(Error::UnexpectedEof(_)) => {}
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         }
     }
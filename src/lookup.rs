@@ -60,8 +60,14 @@ where
         LookupEntryIter {
             current_set: None,
             remaining_input: self.input_buffer.clone(),
+            section_len: self.input_buffer.len(),
+            offset: R::Offset::from_u8(0),
         }
     }
+
+    pub fn reader(&self) -> &R {
+        &self.input_buffer
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -72,6 +78,8 @@ where
 {
     current_set: Option<(R, Parser::Header)>, // Only none at the very beginning and end.
     remaining_input: R,
+    section_len: R::Offset,
+    offset: R::Offset,
 }
 
 impl<R, Parser> LookupEntryIter<R, Parser>
@@ -79,6 +87,16 @@ where
     R: Reader,
     Parser: LookupParser<R>,
 {
+    /// Return the section offset of the next entry or header that the
+    /// iterator will parse.
+    ///
+    /// Entries are returned in the order that they occur in the section. If
+    /// parsing fails, this is the offset of the entry or header that failed.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.offset
+    }
+
     /// Advance the iterator and return the next entry.
     ///
     /// Returns the newly parsed entry as `Ok(Some(Parser::Entry))`. Returns
@@ -92,8 +110,12 @@ where
         loop {
             if let Some((ref mut input, ref header)) = self.current_set {
                 if !input.is_empty() {
+                    let len = input.len();
                     match Parser::parse_entry(input, header) {
-                        Ok(Some(entry)) => return Ok(Some(entry)),
+                        Ok(Some(entry)) => {
+                            self.offset += len - input.len();
+                            return Ok(Some(entry));
+                        }
                         Ok(None) => {}
                         Err(e) => {
                             input.empty();
@@ -103,12 +125,15 @@ where
                     }
                 }
             }
+            self.offset = self.section_len - self.remaining_input.len();
             if self.remaining_input.is_empty() {
                 self.current_set = None;
                 return Ok(None);
             }
             match Parser::parse_header(&mut self.remaining_input) {
                 Ok(set) => {
+                    // The entries of the set end where the remaining input begins.
+                    self.offset = self.section_len - self.remaining_input.len() - set.0.len();
                     self.current_set = Some(set);
                 }
                 Err(e) => {
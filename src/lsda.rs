@@ -0,0 +1,507 @@
+//! Parsing of the language-specific data area (LSDA) that is used by GCC
+//! compatible personality routines, such as `__gcc_personality_v0` and
+//! `__gxx_personality_v0`.
+//!
+//! The LSDA for a function is found in the `.gcc_except_table` section, at the
+//! address given by `FrameDescriptionEntry::lsda`.
+
+use cfi::BaseAddresses;
+use constants::{self, DwEhPe};
+use endianity::{EndianBuf, Endianity};
+use parser::{parse_encoded_pointer, parse_pointer_encoding, Error, Pointer, Result};
+use reader::{Reader, ReaderOffset};
+
+/// The `.gcc_except_table` section, which contains the LSDAs referenced by
+/// FDEs in the `.eh_frame` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GccExceptTable<R: Reader>(R);
+
+impl<'input, Endian> GccExceptTable<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `GccExceptTable` instance from the data in the
+    /// `.gcc_except_table` section.
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(section, endian))
+    }
+}
+
+impl<R: Reader> From<R> for GccExceptTable<R> {
+    fn from(section: R) -> Self {
+        GccExceptTable(section)
+    }
+}
+
+impl<R: Reader> GccExceptTable<R> {
+    /// Parse the header of the LSDA at the given offset within this section.
+    ///
+    /// `function_start` is the initial address of the FDE that refers to this
+    /// LSDA. Call sites are relative to this address, and so are landing pads
+    /// unless the LSDA specifies a different base.
+    ///
+    /// `bases.cfi` is the address of the `.gcc_except_table` section, and is
+    /// used for `DW_EH_PE_pcrel` pointers.
+    ///
+    /// ```
+    /// use gimli::{BaseAddresses, GccExceptTable, LittleEndian};
+    ///
+    /// # fn foo() -> gimli::Result<()> {
+    /// # let read_gcc_except_table_section = || unimplemented!();
+    /// # let (lsda_offset, function_start) = (0, 0);
+    /// let table = GccExceptTable::new(read_gcc_except_table_section(), LittleEndian);
+    /// let lsda = table.lsda(lsda_offset, &BaseAddresses::default(), 8, function_start)?;
+    /// let mut call_sites = lsda.call_sites();
+    /// while let Some(call_site) = call_sites.next()? {
+    ///     if let Some(landing_pad) = call_site.landing_pad() {
+    ///         println!("{:#x}: landing pad at {:#x}", call_site.start(), landing_pad);
+    ///     }
+    /// }
+    /// # unreachable!()
+    /// # }
+    /// ```
+    pub fn lsda(
+        &self,
+        offset: R::Offset,
+        bases: &BaseAddresses,
+        address_size: u8,
+        function_start: u64,
+    ) -> Result<Lsda<R>> {
+        let bases = BaseAddresses {
+            func: Some(function_start),
+            ..bases.clone()
+        };
+        let mut input = self.0.clone();
+        input.skip(offset)?;
+
+        let landing_pad_encoding = parse_pointer_encoding(&mut input)?;
+        let landing_pad_base = if landing_pad_encoding.is_absent() {
+            function_start
+        } else {
+            direct(parse_encoded_pointer(
+                landing_pad_encoding,
+                &bases,
+                address_size,
+                &self.0,
+                &mut input,
+            )?)?
+        };
+
+        let type_table_encoding = parse_pointer_encoding(&mut input)?;
+        let type_table_end = if type_table_encoding.is_absent() {
+            None
+        } else {
+            let len = R::Offset::from_u64(input.read_uleb128()?)?;
+            let end = input
+                .offset_from(&self.0)?
+                .checked_add(len)
+                .ok_or(Error::OffsetOverflow)?;
+            Some(end)
+        };
+
+        let call_site_encoding = parse_pointer_encoding(&mut input)?;
+        let call_site_len = R::Offset::from_u64(input.read_uleb128()?)?;
+        let call_sites = input.split(call_site_len)?;
+
+        Ok(Lsda {
+            section: self.0.clone(),
+            bases,
+            address_size,
+            function_start,
+            landing_pad_base,
+            type_table_encoding,
+            type_table_end,
+            call_site_encoding,
+            call_sites,
+            actions: input,
+        })
+    }
+}
+
+/// Convert a pointer to an address, or return an error if it is indirect.
+fn direct(pointer: Pointer) -> Result<u64> {
+    match pointer {
+        Pointer::Direct(address) => Ok(address),
+        Pointer::Indirect(_) => Err(Error::UnsupportedPointerEncoding),
+    }
+}
+
+/// The parsed header of a language-specific data area.
+#[derive(Debug, Clone)]
+pub struct Lsda<R: Reader> {
+    section: R,
+    bases: BaseAddresses,
+    address_size: u8,
+    function_start: u64,
+    landing_pad_base: u64,
+    type_table_encoding: DwEhPe,
+    type_table_end: Option<R::Offset>,
+    call_site_encoding: DwEhPe,
+    call_sites: R,
+    actions: R,
+}
+
+impl<R: Reader> Lsda<R> {
+    /// The address that landing pads are relative to.
+    ///
+    /// This is the start of the function, unless the LSDA specifies otherwise.
+    pub fn landing_pad_base(&self) -> u64 {
+        self.landing_pad_base
+    }
+
+    /// The pointer encoding of the entries in the type table, or
+    /// `DW_EH_PE_omit` if there is no type table.
+    pub fn type_table_encoding(&self) -> DwEhPe {
+        self.type_table_encoding
+    }
+
+    /// The pointer encoding of the fields in the call-site table.
+    pub fn call_site_encoding(&self) -> DwEhPe {
+        self.call_site_encoding
+    }
+
+    /// Iterate over the entries in the call-site table.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn call_sites(&self) -> CallSiteIter<'_, R> {
+        CallSiteIter {
+            lsda: self,
+            input: self.call_sites.clone(),
+        }
+    }
+
+    /// Find the call-site table entry that contains the given address.
+    ///
+    /// Returns `Ok(None)` if no entry contains the address, in which case a
+    /// personality routine would terminate the program.
+    pub fn call_site_for_address(&self, address: u64) -> Result<Option<CallSite>> {
+        let mut call_sites = self.call_sites();
+        while let Some(call_site) = call_sites.next()? {
+            if call_site.contains(address) {
+                return Ok(Some(call_site));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Iterate over the chain of actions for the given call site.
+    ///
+    /// The iterator is empty if the call site has no actions.
+    pub fn actions(&self, call_site: &CallSite) -> Result<LsdaActionIter<R>> {
+        let mut input = self.actions.clone();
+        match call_site.action {
+            Some(offset) => input.skip(R::Offset::from_u64(offset)?)?,
+            None => input.empty(),
+        }
+        Ok(LsdaActionIter {
+            actions: self.actions.clone(),
+            input,
+        })
+    }
+
+    /// Return the type info pointer for the given index into the type table.
+    ///
+    /// Indices are given by `LsdaAction::Catch`. A pointer value of zero
+    /// indicates a catch-all handler.
+    pub fn type_info(&self, index: u64) -> Result<Pointer> {
+        let end = match self.type_table_end {
+            Some(end) => end,
+            None => return Err(Error::NoEntryAtGivenOffset),
+        };
+        let size = match self.type_table_encoding.format() {
+            constants::DW_EH_PE_absptr => self.address_size as u64,
+            constants::DW_EH_PE_sdata2 | constants::DW_EH_PE_udata2 => 2,
+            constants::DW_EH_PE_sdata4 | constants::DW_EH_PE_udata4 => 4,
+            constants::DW_EH_PE_sdata8 | constants::DW_EH_PE_udata8 => 8,
+            _ => return Err(Error::UnsupportedPointerEncoding),
+        };
+        let offset = index.checked_mul(size).ok_or(Error::OffsetOverflow)?;
+        let offset = end.checked_sub(R::Offset::from_u64(offset)?)
+            .ok_or(Error::OffsetOutOfBounds)?;
+        let mut input = self.section.clone();
+        input.skip(offset)?;
+        parse_encoded_pointer(
+            self.type_table_encoding,
+            &self.bases,
+            self.address_size,
+            &self.section,
+            &mut input,
+        )
+    }
+}
+
+/// An entry in the call-site table of an LSDA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallSite {
+    start: u64,
+    length: u64,
+    landing_pad: Option<u64>,
+    action: Option<u64>,
+}
+
+impl CallSite {
+    /// The first address of the instructions covered by this call site.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The number of bytes of instructions covered by this call site.
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Return `true` if the given address is within this call site, `false`
+    /// otherwise.
+    pub fn contains(&self, address: u64) -> bool {
+        self.start <= address && address - self.start < self.length
+    }
+
+    /// The address of the landing pad for this call site, or `None` if
+    /// there is no landing pad and unwinding should continue.
+    pub fn landing_pad(&self) -> Option<u64> {
+        self.landing_pad
+    }
+
+    /// The offset of the first action for this call site within the action
+    /// table, or `None` if the landing pad only performs cleanup.
+    pub fn action(&self) -> Option<u64> {
+        self.action
+    }
+}
+
+/// An iterator over the call-site table of an LSDA.
+#[derive(Debug, Clone)]
+pub struct CallSiteIter<'a, R: Reader + 'a> {
+    lsda: &'a Lsda<R>,
+    input: R,
+}
+
+impl<'a, R: Reader> CallSiteIter<'a, R> {
+    /// Advance the iterator to the next call site.
+    pub fn next(&mut self) -> Result<Option<CallSite>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse() {
+            Ok(call_site) => Ok(Some(call_site)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<CallSite> {
+        let lsda = self.lsda;
+        let read = |input: &mut R| {
+            parse_encoded_pointer(
+                lsda.call_site_encoding,
+                &lsda.bases,
+                lsda.address_size,
+                &lsda.section,
+                input,
+            ).and_then(direct)
+        };
+        let start = read(&mut self.input)?;
+        let length = read(&mut self.input)?;
+        let landing_pad = read(&mut self.input)?;
+        let action = self.input.read_uleb128()?;
+        Ok(CallSite {
+            start: lsda.function_start.wrapping_add(start),
+            length,
+            landing_pad: if landing_pad == 0 {
+                None
+            } else {
+                Some(lsda.landing_pad_base.wrapping_add(landing_pad))
+            },
+            action: if action == 0 { None } else { Some(action - 1) },
+        })
+    }
+}
+
+impl<'a, R: Reader> ::fallible_iterator::FallibleIterator for CallSiteIter<'a, R> {
+    type Item = CallSite;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        CallSiteIter::next(self)
+    }
+}
+
+/// An action performed by a landing pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LsdaAction {
+    /// The landing pad performs cleanup.
+    Cleanup,
+
+    /// The landing pad catches exceptions matching the type given by this
+    /// index into the type table. See `Lsda::type_info`.
+    Catch(u64),
+
+    /// The landing pad handles exceptions that do not match the exception
+    /// specification at this byte offset after the end of the type table.
+    ExceptionSpec(u64),
+}
+
+/// An iterator over a chain of actions in the action table of an LSDA.
+#[derive(Debug, Clone)]
+pub struct LsdaActionIter<R: Reader> {
+    actions: R,
+    input: R,
+}
+
+impl<R: Reader> LsdaActionIter<R> {
+    /// Advance the iterator to the next action.
+    pub fn next(&mut self) -> Result<Option<LsdaAction>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse() {
+            Ok(action) => Ok(Some(action)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<LsdaAction> {
+        let filter = self.input.read_sleb128()?;
+        let next_base = self.input.offset_from(&self.actions)?;
+        let next = self.input.read_sleb128()?;
+        if next == 0 {
+            self.input.empty();
+        } else {
+            // The displacement is relative to the start of the field.
+            let next = (next_base.into_u64() as i64).wrapping_add(next);
+            if next < 0 {
+                return Err(Error::OffsetOutOfBounds);
+            }
+            self.input = self.actions.clone();
+            self.input.skip(R::Offset::from_u64(next as u64)?)?;
+        }
+        Ok(match filter {
+            0 => LsdaAction::Cleanup,
+            f if f > 0 => LsdaAction::Catch(f as u64),
+            f => LsdaAction::ExceptionSpec((-(f + 1)) as u64),
+        })
+    }
+}
+
+impl<R: Reader> ::fallible_iterator::FallibleIterator for LsdaActionIter<R> {
+    type Item = LsdaAction;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        LsdaActionIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+
+    #[test]
+    fn test_lsda() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // Padding, so the LSDA is not at the start of the section.
+            0x00, 0x00,
+            // Landing pad base is omitted.
+            0xff,
+            // Type table encoding and offset to its end.
+            0x03, 0x19,
+            // Call site encoding and table length.
+            0x01, 0x08,
+            // Call site: start, length, landing pad, action.
+            0x00, 0x10, 0x00, 0x00,
+            0x10, 0x08, 0x20, 0x01,
+            // Action: catch type 1, next action.
+            0x01, 0x01,
+            // Action: cleanup, no next action.
+            0x00, 0x00,
+            // Action: exception spec, no next action.
+            0x7f, 0x00,
+            // Padding.
+            0x00,
+            // Type table, indexed backwards from its end.
+            0x00, 0x00, 0x00, 0x00,
+            0x78, 0x56, 0x34, 0x12,
+        ];
+        let table = GccExceptTable::new(&buf, LittleEndian);
+        let lsda = table
+            .lsda(2, &BaseAddresses::default(), 4, 0x1000)
+            .unwrap();
+        assert_eq!(lsda.landing_pad_base(), 0x1000);
+        assert_eq!(lsda.type_table_encoding(), constants::DW_EH_PE_udata4);
+        assert_eq!(lsda.call_site_encoding(), constants::DW_EH_PE_uleb128);
+
+        let mut call_sites = lsda.call_sites();
+        let first = call_sites.next().unwrap().unwrap();
+        assert_eq!(
+            first,
+            CallSite {
+                start: 0x1000,
+                length: 0x10,
+                landing_pad: None,
+                action: None,
+            }
+        );
+        let second = call_sites.next().unwrap().unwrap();
+        assert_eq!(second.start(), 0x1010);
+        assert_eq!(second.len(), 8);
+        assert_eq!(second.landing_pad(), Some(0x1020));
+        assert_eq!(second.action(), Some(0));
+        assert_eq!(call_sites.next(), Ok(None));
+
+        assert_eq!(lsda.call_site_for_address(0x100f), Ok(Some(first)));
+        assert_eq!(lsda.call_site_for_address(0x1017), Ok(Some(second)));
+        assert_eq!(lsda.call_site_for_address(0x1018), Ok(None));
+
+        let mut actions = lsda.actions(&first).unwrap();
+        assert_eq!(actions.next(), Ok(None));
+
+        let mut actions = lsda.actions(&second).unwrap();
+        assert_eq!(actions.next(), Ok(Some(LsdaAction::Catch(1))));
+        assert_eq!(actions.next(), Ok(Some(LsdaAction::Cleanup)));
+        assert_eq!(actions.next(), Ok(None));
+
+        let spec = CallSite {
+            action: Some(4),
+            ..second
+        };
+        let mut actions = lsda.actions(&spec).unwrap();
+        assert_eq!(actions.next(), Ok(Some(LsdaAction::ExceptionSpec(0))));
+        assert_eq!(actions.next(), Ok(None));
+
+        assert_eq!(lsda.type_info(1), Ok(Pointer::Direct(0x12345678)));
+        assert_eq!(lsda.type_info(2), Ok(Pointer::Direct(0)));
+    }
+
+    #[test]
+    fn test_lsda_landing_pad_base() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // Landing pad base is an absolute udata2 pointer.
+            0x02, 0x00, 0x20,
+            // No type table.
+            0xff,
+            // Call site encoding and table length.
+            0x01, 0x04,
+            // Call site: start, length, landing pad, action.
+            0x04, 0x04, 0x02, 0x00,
+        ];
+        let table = GccExceptTable::new(&buf, LittleEndian);
+        let lsda = table
+            .lsda(0, &BaseAddresses::default(), 4, 0x1000)
+            .unwrap();
+        assert_eq!(lsda.landing_pad_base(), 0x2000);
+        let call_site = lsda.call_site_for_address(0x1004).unwrap().unwrap();
+        assert_eq!(call_site.landing_pad(), Some(0x2002));
+        assert_eq!(lsda.type_info(1), Err(Error::NoEntryAtGivenOffset));
+    }
+}
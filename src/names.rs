@@ -0,0 +1,1235 @@
+//! Functions for parsing the DWARF 5 `.debug_names` section.
+
+use fallible_iterator::FallibleIterator;
+use vec::Vec;
+
+use constants;
+use endianity::{EndianBuf, Endianity};
+use parser::{parse_initial_length, Error, Format, Result};
+use reader::{Reader, ReaderOffset};
+use str::{DebugStr, DebugStrOffset};
+use unit::{DebugInfoOffset, DebugTypeSignature, UnitOffset, UnitSectionOffset};
+use {Section, SectionId};
+
+/// An offset into the `.debug_names` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DebugNamesOffset<T = usize>(pub T);
+
+/// An offset of an entry within the entry pool of a name index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NameEntryOffset<T = usize>(pub T);
+
+/// The `DebugNames` struct represents the DWARF 5 name indexes
+/// found in the `.debug_names` section.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugNames<R: Reader> {
+    debug_names_section: R,
+}
+
+impl<'input, Endian> DebugNames<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugNames` instance from the data in the `.debug_names`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_names` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugNames, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_names_section_somehow = || &buf;
+    /// let debug_names = DebugNames::new(read_debug_names_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_names_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(debug_names_section, endian))
+    }
+}
+
+impl<R: Reader> DebugNames<R> {
+    /// Iterate the name indexes in the `.debug_names` section.
+    ///
+    /// A linked object usually contains either a single name index for all of
+    /// its compilation units, or one name index per compilation unit.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn name_indexes(&self) -> NameIndexIter<R> {
+        NameIndexIter {
+            input: self.debug_names_section.clone(),
+            offset: DebugNamesOffset(R::Offset::from_u8(0)),
+        }
+    }
+}
+
+impl<R: Reader> Section<R> for DebugNames<R> {
+    fn id() -> SectionId {
+        SectionId::DebugNames
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_names_section
+    }
+}
+
+impl<R: Reader> From<R> for DebugNames<R> {
+    fn from(debug_names_section: R) -> Self {
+        DebugNames {
+            debug_names_section,
+        }
+    }
+}
+
+/// An iterator over the name indexes of a `.debug_names` section.
+///
+/// See the [documentation on
+/// `DebugNames::name_indexes`](./struct.DebugNames.html#method.name_indexes)
+/// for more detail.
+#[derive(Clone, Debug)]
+pub struct NameIndexIter<R: Reader> {
+    input: R,
+    offset: DebugNamesOffset<R::Offset>,
+}
+
+impl<R: Reader> NameIndexIter<R> {
+    /// Return the section offset of the next name index that the iterator
+    /// will parse.
+    ///
+    /// If parsing a name index fails, this is the offset of that name index.
+    #[inline]
+    pub fn offset(&self) -> DebugNamesOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Advance the iterator to the next name index.
+    pub fn next(&mut self) -> Result<Option<NameIndex<R>>> {
+        if self.input.is_empty() {
+            Ok(None)
+        } else {
+            let len = self.input.len();
+            match NameIndex::parse(&mut self.input, self.offset) {
+                Ok(index) => {
+                    self.offset.0 += len - self.input.len();
+                    Ok(Some(index))
+                }
+                Err(e) => {
+                    self.input.empty();
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+impl<R: Reader> FallibleIterator for NameIndexIter<R> {
+    type Item = NameIndex<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        NameIndexIter::next(self)
+    }
+}
+
+/// The specification of an attribute in a name index abbreviation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameAttributeSpecification {
+    name: constants::DwIdx,
+    form: constants::DwForm,
+}
+
+impl NameAttributeSpecification {
+    /// Get the attribute's index attribute (`DW_IDX_*`) value.
+    #[inline]
+    pub fn name(&self) -> constants::DwIdx {
+        self.name
+    }
+
+    /// Get the attribute's form.
+    #[inline]
+    pub fn form(&self) -> constants::DwForm {
+        self.form
+    }
+}
+
+/// An abbreviation in a name index, which describes the tag and attributes
+/// of the index entries that use it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameAbbreviation {
+    code: u64,
+    tag: constants::DwTag,
+    attributes: Vec<NameAttributeSpecification>,
+}
+
+impl NameAbbreviation {
+    /// Get this abbreviation's code.
+    #[inline]
+    pub fn code(&self) -> u64 {
+        self.code
+    }
+
+    /// Get the tag of the entries that use this abbreviation.
+    #[inline]
+    pub fn tag(&self) -> constants::DwTag {
+        self.tag
+    }
+
+    /// Get the attribute specifications for the entries that use this
+    /// abbreviation.
+    #[inline]
+    pub fn attributes(&self) -> &[NameAttributeSpecification] {
+        &self.attributes[..]
+    }
+
+    fn parse<R: Reader>(input: &mut R) -> Result<Option<NameAbbreviation>> {
+        let code = input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let tag = constants::DwTag(input.read_uleb128()?);
+        let mut attributes = Vec::new();
+        loop {
+            let name = input.read_uleb128()?;
+            let form = input.read_uleb128()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            if name > u64::from(u16::max_value()) {
+                return Err(Error::InvalidNameAttributeIndex(name));
+            }
+            attributes.push(NameAttributeSpecification {
+                name: constants::DwIdx(name as u16),
+                form: constants::DwForm(form),
+            });
+        }
+        Ok(Some(NameAbbreviation {
+            code,
+            tag,
+            attributes,
+        }))
+    }
+}
+
+/// The value of an attribute of a name index entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameAttributeValue {
+    /// An unsigned constant or reference.
+    Unsigned(u64),
+
+    /// A flag.
+    Flag(bool),
+}
+
+impl NameAttributeValue {
+    /// Return the value as an unsigned integer, if it is one.
+    pub fn udata_value(&self) -> Option<u64> {
+        match *self {
+            NameAttributeValue::Unsigned(value) => Some(value),
+            NameAttributeValue::Flag(_) => None,
+        }
+    }
+
+    fn parse<R: Reader>(input: &mut R, form: constants::DwForm) -> Result<NameAttributeValue> {
+        Ok(match form {
+            constants::DW_FORM_flag_present => NameAttributeValue::Flag(true),
+            constants::DW_FORM_flag => NameAttributeValue::Flag(input.read_u8()? != 0),
+            constants::DW_FORM_data1 | constants::DW_FORM_ref1 => {
+                NameAttributeValue::Unsigned(u64::from(input.read_u8()?))
+            }
+            constants::DW_FORM_data2 | constants::DW_FORM_ref2 => {
+                NameAttributeValue::Unsigned(u64::from(input.read_u16()?))
+            }
+            constants::DW_FORM_data4 | constants::DW_FORM_ref4 => {
+                NameAttributeValue::Unsigned(u64::from(input.read_u32()?))
+            }
+            constants::DW_FORM_data8 | constants::DW_FORM_ref8 | constants::DW_FORM_ref_sig8 => {
+                NameAttributeValue::Unsigned(input.read_u64()?)
+            }
+            constants::DW_FORM_udata | constants::DW_FORM_ref_udata => {
+                NameAttributeValue::Unsigned(input.read_uleb128()?)
+            }
+            _ => return Err(Error::UnknownForm),
+        })
+    }
+}
+
+/// An attribute of a name index entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameAttribute {
+    name: constants::DwIdx,
+    value: NameAttributeValue,
+}
+
+impl NameAttribute {
+    /// Get this attribute's index attribute (`DW_IDX_*`) value.
+    #[inline]
+    pub fn name(&self) -> constants::DwIdx {
+        self.name
+    }
+
+    /// Get this attribute's value.
+    #[inline]
+    pub fn value(&self) -> NameAttributeValue {
+        self.value
+    }
+}
+
+/// An entry in the entry pool of a name index, which describes a debugging
+/// information entry that has a given name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameEntry<T = usize> {
+    offset: NameEntryOffset<T>,
+    tag: constants::DwTag,
+    attributes: Vec<NameAttribute>,
+}
+
+impl<T: ReaderOffset> NameEntry<T> {
+    /// Get the offset of this entry within the entry pool.
+    #[inline]
+    pub fn offset(&self) -> NameEntryOffset<T> {
+        self.offset
+    }
+
+    /// Get the tag of the debugging information entry.
+    #[inline]
+    pub fn tag(&self) -> constants::DwTag {
+        self.tag
+    }
+
+    /// Get all of the attributes of this entry.
+    #[inline]
+    pub fn attributes(&self) -> &[NameAttribute] {
+        &self.attributes[..]
+    }
+
+    /// Find the value of the first attribute with the given index attribute
+    /// (`DW_IDX_*`) value.
+    pub fn attr_value(&self, name: constants::DwIdx) -> Option<NameAttributeValue> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value)
+    }
+
+    /// Get the index of the compilation unit in the name index's list of
+    /// compilation units, from the `DW_IDX_compile_unit` attribute.
+    ///
+    /// See also `NameIndex::entry_compile_unit`, which handles name indexes
+    /// that omit this attribute because they only have one compilation unit.
+    pub fn compile_unit(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_compile_unit)
+            .and_then(|value| value.udata_value())
+    }
+
+    /// Get the index of the type unit in the name index's lists of local and
+    /// foreign type units, from the `DW_IDX_type_unit` attribute.
+    pub fn type_unit(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_type_unit)
+            .and_then(|value| value.udata_value())
+    }
+
+    /// Get the offset of the debugging information entry within its unit,
+    /// from the `DW_IDX_die_offset` attribute.
+    pub fn die_offset(&self) -> Option<UnitOffset<T>> {
+        self.attr_value(constants::DW_IDX_die_offset)
+            .and_then(|value| value.udata_value())
+            .and_then(|offset| T::from_u64(offset).ok())
+            .map(UnitOffset)
+    }
+
+    /// Get the offset of the index entry for the parent of the debugging
+    /// information entry, from the `DW_IDX_parent` attribute.
+    ///
+    /// Returns `None` if there is no such attribute, or if the attribute
+    /// indicates that the parent is not indexed.
+    pub fn parent(&self) -> Option<NameEntryOffset<T>> {
+        self.attr_value(constants::DW_IDX_parent)
+            .and_then(|value| value.udata_value())
+            .and_then(|offset| T::from_u64(offset).ok())
+            .map(NameEntryOffset)
+    }
+
+    /// Get the hash of the type's signature, from the `DW_IDX_type_hash`
+    /// attribute.
+    pub fn type_hash(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_type_hash)
+            .and_then(|value| value.udata_value())
+    }
+}
+
+/// A name in the name table of a name index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexedName<T = usize> {
+    index: u32,
+    hash: Option<u32>,
+    string_offset: DebugStrOffset<T>,
+    entry_offset: NameEntryOffset<T>,
+}
+
+impl<T: ReaderOffset> IndexedName<T> {
+    /// Get the index of this name in the name table.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Get the hash of this name, if the name index has a hash table.
+    #[inline]
+    pub fn hash(&self) -> Option<u32> {
+        self.hash
+    }
+
+    /// Get the offset of this name's string in the `.debug_str` section.
+    #[inline]
+    pub fn string_offset(&self) -> DebugStrOffset<T> {
+        self.string_offset
+    }
+
+    /// Get the offset of the first of this name's entries in the entry pool.
+    #[inline]
+    pub fn entry_offset(&self) -> NameEntryOffset<T> {
+        self.entry_offset
+    }
+}
+
+/// A name index in the `.debug_names` section.
+///
+/// A name index maps names to the debugging information entries that have
+/// those names, for the units in the index's unit lists.
+///
+/// ```
+/// use gimli::{DebugNames, DebugStr, LittleEndian};
+///
+/// # let debug_names_buf = [];
+/// # let debug_str_buf = [];
+/// # let read_debug_names_section_somehow = || &debug_names_buf;
+/// # let read_debug_str_section_somehow = || &debug_str_buf;
+/// let debug_names = DebugNames::new(read_debug_names_section_somehow(), LittleEndian);
+/// let debug_str = DebugStr::new(read_debug_str_section_somehow(), LittleEndian);
+///
+/// let mut indexes = debug_names.name_indexes();
+/// while let Some(index) = indexes.next().unwrap() {
+///     if let Some(name) = index.find(&debug_str, "main").unwrap() {
+///         let mut entries = index.entries(&name).unwrap();
+///         while let Some(entry) = entries.next().unwrap() {
+///             let unit = index.entry_compile_unit(&entry).unwrap();
+///             println!("main: {:?} {:?}", unit, entry.die_offset());
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameIndex<R: Reader> {
+    offset: DebugNamesOffset<R::Offset>,
+    format: Format,
+    version: u16,
+    comp_unit_count: u32,
+    local_type_unit_count: u32,
+    foreign_type_unit_count: u32,
+    bucket_count: u32,
+    name_count: u32,
+    augmentation_string: R,
+    comp_unit_list: R,
+    local_type_unit_list: R,
+    foreign_type_unit_list: R,
+    buckets: R,
+    hashes: R,
+    string_offsets: R,
+    entry_offsets: R,
+    abbreviations: Vec<NameAbbreviation>,
+    entry_pool: R,
+}
+
+/// Split off an array of `count` elements of `size` bytes each.
+fn split_array<R: Reader>(input: &mut R, count: u32, size: u8) -> Result<R> {
+    let len = u64::from(count) * u64::from(size);
+    input.split(R::Offset::from_u64(len)?)
+}
+
+/// Skip to the element at `index` in an array of elements of `size` bytes
+/// each.
+fn array_element<R: Reader>(array: &R, index: u32, size: u8) -> Result<R> {
+    let mut input = array.clone();
+    let offset = u64::from(index) * u64::from(size);
+    input.skip(R::Offset::from_u64(offset)?)?;
+    Ok(input)
+}
+
+/// Compare two strings using the same case folding as the name index hash.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// Compute the hash of a name, as used by the hash table of a name index.
+pub(crate) fn name_hash(name: &str) -> u32 {
+    let mut hash: u32 = 5381;
+    let mut buf = [0; 4];
+    for c in name.chars().flat_map(char::to_lowercase) {
+        for byte in c.encode_utf8(&mut buf).bytes() {
+            hash = hash.wrapping_mul(33).wrapping_add(u32::from(byte));
+        }
+    }
+    hash
+}
+
+impl<R: Reader> NameIndex<R> {
+    fn parse(input: &mut R, offset: DebugNamesOffset<R::Offset>) -> Result<NameIndex<R>> {
+        let (length, format) = parse_initial_length(input)?;
+        let length = R::Offset::from_u64(length)?;
+        let mut rest = input.split(length)?;
+
+        let version = rest.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+        let _padding = rest.read_u16()?;
+        let comp_unit_count = rest.read_u32()?;
+        let local_type_unit_count = rest.read_u32()?;
+        let foreign_type_unit_count = rest.read_u32()?;
+        let bucket_count = rest.read_u32()?;
+        let name_count = rest.read_u32()?;
+        let abbrev_table_size = rest.read_u32()?;
+        let augmentation_string_size = rest.read_u32()?;
+
+        // The augmentation string is padded to a multiple of four bytes.
+        let augmentation_string = rest.split(R::Offset::from_u32(augmentation_string_size))?;
+        let padding = augmentation_string_size.wrapping_neg() % 4;
+        rest.skip(R::Offset::from_u32(padding))?;
+
+        let word_size = format.word_size();
+        let comp_unit_list = split_array(&mut rest, comp_unit_count, word_size)?;
+        let local_type_unit_list = split_array(&mut rest, local_type_unit_count, word_size)?;
+        let foreign_type_unit_list = split_array(&mut rest, foreign_type_unit_count, 8)?;
+        let buckets = split_array(&mut rest, bucket_count, 4)?;
+        let hash_count = if bucket_count == 0 { 0 } else { name_count };
+        let hashes = split_array(&mut rest, hash_count, 4)?;
+        let string_offsets = split_array(&mut rest, name_count, word_size)?;
+        let entry_offsets = split_array(&mut rest, name_count, word_size)?;
+
+        let mut abbrev_table = rest.split(R::Offset::from_u32(abbrev_table_size))?;
+        let mut abbreviations: Vec<NameAbbreviation> = Vec::new();
+        while let Some(abbrev) = NameAbbreviation::parse(&mut abbrev_table)? {
+            if abbreviations.iter().any(|a| a.code == abbrev.code) {
+                return Err(Error::DuplicateAbbreviationCode);
+            }
+            abbreviations.push(abbrev);
+        }
+
+        Ok(NameIndex {
+            offset,
+            format,
+            version,
+            comp_unit_count,
+            local_type_unit_count,
+            foreign_type_unit_count,
+            bucket_count,
+            name_count,
+            augmentation_string,
+            comp_unit_list,
+            local_type_unit_list,
+            foreign_type_unit_list,
+            buckets,
+            hashes,
+            string_offsets,
+            entry_offsets,
+            abbreviations,
+            entry_pool: rest,
+        })
+    }
+
+    /// Compute the hash of a name, as used by the hash table of a name index.
+    ///
+    /// This is the DJB hash of the name after case folding. Characters
+    /// outside of the ASCII range are folded using their lowercase mapping.
+    pub fn hash(name: &str) -> u32 {
+        name_hash(name)
+    }
+
+    /// Get the offset of this name index in the `.debug_names` section.
+    #[inline]
+    pub fn offset(&self) -> DebugNamesOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Get whether this name index uses the 32-bit or 64-bit DWARF format.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Get the version of this name index.
+    #[inline]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Get the number of compilation units in this name index.
+    #[inline]
+    pub fn comp_unit_count(&self) -> u32 {
+        self.comp_unit_count
+    }
+
+    /// Get the number of type units in the `.debug_info` section that are in
+    /// this name index.
+    #[inline]
+    pub fn local_type_unit_count(&self) -> u32 {
+        self.local_type_unit_count
+    }
+
+    /// Get the number of type units in split DWARF objects that are in this
+    /// name index.
+    #[inline]
+    pub fn foreign_type_unit_count(&self) -> u32 {
+        self.foreign_type_unit_count
+    }
+
+    /// Get the number of buckets in the hash table, or zero if this name
+    /// index does not have a hash table.
+    #[inline]
+    pub fn bucket_count(&self) -> u32 {
+        self.bucket_count
+    }
+
+    /// Get the number of names in this name index.
+    #[inline]
+    pub fn name_count(&self) -> u32 {
+        self.name_count
+    }
+
+    /// Get the vendor specific augmentation string, without its padding.
+    #[inline]
+    pub fn augmentation_string(&self) -> &R {
+        &self.augmentation_string
+    }
+
+    /// Get the abbreviations used by the entries of this name index.
+    #[inline]
+    pub fn abbreviations(&self) -> &[NameAbbreviation] {
+        &self.abbreviations[..]
+    }
+
+    /// Get the offset in the `.debug_info` section of the compilation unit at
+    /// the given index in the compilation unit list.
+    pub fn comp_unit(&self, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        let mut input = array_element(&self.comp_unit_list, index, self.format.word_size())?;
+        input.read_offset(self.format).map(DebugInfoOffset)
+    }
+
+    /// Get the offset in the `.debug_info` section of the type unit at the
+    /// given index in the local type unit list.
+    pub fn local_type_unit(&self, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        let size = self.format.word_size();
+        let mut input = array_element(&self.local_type_unit_list, index, size)?;
+        input.read_offset(self.format).map(DebugInfoOffset)
+    }
+
+    /// Get the type signature of the type unit at the given index in the
+    /// foreign type unit list.
+    pub fn foreign_type_unit(&self, index: u32) -> Result<DebugTypeSignature> {
+        let mut input = array_element(&self.foreign_type_unit_list, index, 8)?;
+        input.read_u64().map(DebugTypeSignature)
+    }
+
+    /// Get the name at the given index in the name table.
+    pub fn name(&self, index: u32) -> Result<IndexedName<R::Offset>> {
+        let size = self.format.word_size();
+        let string_offset = array_element(&self.string_offsets, index, size)?
+            .read_offset(self.format)
+            .map(DebugStrOffset)?;
+        let entry_offset = array_element(&self.entry_offsets, index, size)?
+            .read_offset(self.format)
+            .map(NameEntryOffset)?;
+        let hash = if self.bucket_count == 0 {
+            None
+        } else {
+            Some(array_element(&self.hashes, index, 4)?.read_u32()?)
+        };
+        Ok(IndexedName {
+            index,
+            hash,
+            string_offset,
+            entry_offset,
+        })
+    }
+
+    /// Iterate over all of the names in the name table.
+    ///
+    /// This can be used along with `entries` to visit every entry in the name
+    /// index, such as when building an external index.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn names(&self) -> IndexedNameIter<'_, R> {
+        IndexedNameIter {
+            index: self,
+            next: 0,
+        }
+    }
+
+    /// Iterate over the entries for the given name.
+    pub fn entries(&self, name: &IndexedName<R::Offset>) -> Result<NameEntryIter<'_, R>> {
+        let mut input = self.entry_pool.clone();
+        input.skip(name.entry_offset.0)?;
+        Ok(NameEntryIter {
+            index: self,
+            input,
+            offset: name.entry_offset,
+        })
+    }
+
+    /// Parse the entry at the given offset in the entry pool.
+    ///
+    /// This can be used to find the entry for the parent given by
+    /// `NameEntry::parent`.
+    pub fn entry(&self, offset: NameEntryOffset<R::Offset>) -> Result<NameEntry<R::Offset>> {
+        let mut input = self.entry_pool.clone();
+        input.skip(offset.0)?;
+        match self.parse_entry(&mut input)? {
+            Some(entry) => Ok(entry),
+            None => Err(Error::NoEntryAtGivenOffset),
+        }
+    }
+
+    /// Get the offset in the `.debug_info` section of the compilation unit
+    /// that contains the given entry.
+    ///
+    /// This uses the entry's `DW_IDX_compile_unit` attribute if it has one.
+    /// Otherwise, if the entry is not in a type unit and this name index only
+    /// has one compilation unit, then that is used. Returns `None` if the
+    /// compilation unit is unknown.
+    pub fn entry_compile_unit(
+        &self,
+        entry: &NameEntry<R::Offset>,
+    ) -> Result<Option<DebugInfoOffset<R::Offset>>> {
+        match entry.compile_unit() {
+            Some(index) => {
+                if index >= u64::from(self.comp_unit_count) {
+                    return Err(Error::OffsetOutOfBounds);
+                }
+                self.comp_unit(index as u32).map(Some)
+            }
+            None if entry.type_unit().is_none() && self.comp_unit_count == 1 => {
+                self.comp_unit(0).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the section offset of the debugging information entry that is
+    /// described by the given entry.
+    ///
+    /// If the entry has a `DW_IDX_type_unit` attribute that refers to a local
+    /// type unit, then the offset is relative to that unit. Otherwise it is
+    /// relative to the compilation unit given by `entry_compile_unit`.
+    /// Returns `None` if the unit or the `DW_IDX_die_offset` attribute is
+    /// unknown, or if the entry is in a foreign type unit.
+    pub fn entry_die_offset(
+        &self,
+        entry: &NameEntry<R::Offset>,
+    ) -> Result<Option<UnitSectionOffset<R::Offset>>> {
+        let die_offset = match entry.die_offset() {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let unit = match entry.type_unit() {
+            Some(index) if index < u64::from(self.local_type_unit_count) => {
+                self.local_type_unit(index as u32)?
+            }
+            Some(_) => return Ok(None),
+            None => match self.entry_compile_unit(entry)? {
+                Some(unit) => unit,
+                None => return Ok(None),
+            },
+        };
+        Ok(UnitSectionOffset::from(unit).add_unit_offset(die_offset))
+    }
+
+    /// Find the name that exactly matches the given name.
+    ///
+    /// Mangled names, such as those given by `DW_AT_linkage_name`, are indexed
+    /// separately from unmangled names, and can be found in the same way.
+    pub fn find(
+        &self,
+        debug_str: &DebugStr<R>,
+        name: &str,
+    ) -> Result<Option<IndexedName<R::Offset>>> {
+        let mut found = None;
+        self.lookup(name, |candidate| {
+            let string = debug_str.get_str(candidate.string_offset)?;
+            if &*string.to_slice()? == name.as_bytes() {
+                found = Some(candidate);
+                return Ok(false);
+            }
+            Ok(true)
+        })?;
+        Ok(found)
+    }
+
+    /// Find all of the names that match the given name, ignoring case.
+    ///
+    /// The name table may contain multiple names that differ only in case,
+    /// such as for case insensitive source languages.
+    pub fn find_ignore_case(
+        &self,
+        debug_str: &DebugStr<R>,
+        name: &str,
+    ) -> Result<Vec<IndexedName<R::Offset>>> {
+        let mut found = Vec::new();
+        self.lookup(name, |candidate| {
+            let string = debug_str.get_str(candidate.string_offset)?;
+            if eq_ignore_case(&string.to_string_lossy()?, name) {
+                found.push(candidate);
+            }
+            Ok(true)
+        })?;
+        Ok(found)
+    }
+
+    /// Call `f` for each name that may match the given name, until it returns
+    /// `false`.
+    ///
+    /// Uses the hash table if there is one, or else every name is a
+    /// candidate.
+    fn lookup<F>(&self, name: &str, mut f: F) -> Result<()>
+    where
+        F: FnMut(IndexedName<R::Offset>) -> Result<bool>,
+    {
+        if self.bucket_count == 0 {
+            for index in 0..self.name_count {
+                if !f(self.name(index)?)? {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
+        let hash = Self::hash(name);
+        let bucket = hash % self.bucket_count;
+        // The bucket contains the 1-based index of its first name, or 0 if
+        // it is empty. The names in a bucket are contiguous.
+        let first = array_element(&self.buckets, bucket, 4)?.read_u32()?;
+        if first == 0 {
+            return Ok(());
+        }
+        for index in first - 1..self.name_count {
+            let name_hash = array_element(&self.hashes, index, 4)?.read_u32()?;
+            if name_hash % self.bucket_count != bucket {
+                break;
+            }
+            if name_hash == hash && !f(self.name(index)?)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the entry at the start of `input`, or return `None` if it is the
+    /// end of a series of entries.
+    fn parse_entry(&self, input: &mut R) -> Result<Option<NameEntry<R::Offset>>> {
+        let offset = NameEntryOffset(input.offset_from(&self.entry_pool)?);
+        let code = input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let abbrev = match self.abbreviations.iter().find(|a| a.code == code) {
+            Some(abbrev) => abbrev,
+            None => return Err(Error::UnknownAbbreviation),
+        };
+        let mut attributes = Vec::with_capacity(abbrev.attributes.len());
+        for spec in &abbrev.attributes {
+            attributes.push(NameAttribute {
+                name: spec.name,
+                value: NameAttributeValue::parse(input, spec.form)?,
+            });
+        }
+        Ok(Some(NameEntry {
+            offset,
+            tag: abbrev.tag,
+            attributes,
+        }))
+    }
+}
+
+/// An iterator over the names in the name table of a name index.
+///
+/// See the [documentation on
+/// `NameIndex::names`](./struct.NameIndex.html#method.names) for more detail.
+#[derive(Clone, Debug)]
+pub struct IndexedNameIter<'index, R: Reader + 'index> {
+    index: &'index NameIndex<R>,
+    next: u32,
+}
+
+impl<'index, R: Reader> IndexedNameIter<'index, R> {
+    /// Return the index in the name table of the next name that the iterator
+    /// will return.
+    ///
+    /// A name has no offset of its own, since its string offset and entry
+    /// offset are stored in separate arrays.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.next
+    }
+
+    /// Advance the iterator to the next name.
+    pub fn next(&mut self) -> Result<Option<IndexedName<R::Offset>>> {
+        if self.next >= self.index.name_count {
+            return Ok(None);
+        }
+        match self.index.name(self.next) {
+            Ok(name) => {
+                self.next += 1;
+                Ok(Some(name))
+            }
+            Err(e) => {
+                self.next = self.index.name_count;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'index, R: Reader> FallibleIterator for IndexedNameIter<'index, R> {
+    type Item = IndexedName<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        IndexedNameIter::next(self)
+    }
+}
+
+/// An iterator over the entries for a name in a name index.
+///
+/// See the [documentation on
+/// `NameIndex::entries`](./struct.NameIndex.html#method.entries) for more
+/// detail.
+#[derive(Clone, Debug)]
+pub struct NameEntryIter<'index, R: Reader + 'index> {
+    index: &'index NameIndex<R>,
+    input: R,
+    offset: NameEntryOffset<R::Offset>,
+}
+
+impl<'index, R: Reader> NameEntryIter<'index, R> {
+    /// Return the offset within the entry pool of the next entry that the
+    /// iterator will parse.
+    ///
+    /// If parsing an entry fails, this is the offset of that entry.
+    #[inline]
+    pub fn offset(&self) -> NameEntryOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Advance the iterator to the next entry.
+    pub fn next(&mut self) -> Result<Option<NameEntry<R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let len = self.input.len();
+        match self.index.parse_entry(&mut self.input) {
+            Ok(Some(entry)) => {
+                self.offset.0 += len - self.input.len();
+                Ok(Some(entry))
+            }
+            Ok(None) => {
+                self.offset.0 += len - self.input.len();
+                self.input.empty();
+                Ok(None)
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'index, R: Reader> FallibleIterator for NameEntryIter<'index, R> {
+    type Item = NameEntry<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        NameEntryIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use super::*;
+    use constants::*;
+    use endianity::LittleEndian;
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use test_util::GimliSectionMethods;
+
+    // Names and their offsets in the `.debug_str` section.
+    const DEBUG_STR: &[u8] = b"main\0Foo\0foo\0_ZN3Foo3barEv\0";
+    const MAIN: u64 = 0;
+    const FOO_UPPER: u64 = 5;
+    const FOO_LOWER: u64 = 9;
+    const MANGLED: u64 = 13;
+
+    // Build a name index. The names must be sorted by bucket.
+    fn name_index(names: &[(&str, u64)], bucket_count: u32) -> Vec<u8> {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let abbrev_start = Label::new();
+        let abbrev_end = Label::new();
+        let abbrev_size = Label::new();
+        let mut section = Section::with_endian(Endian::Little)
+            .L32(&length)
+            .mark(&start)
+            // Version, padding.
+            .L16(5)
+            .L16(0)
+            // Compilation units, local and foreign type units.
+            .L32(2)
+            .L32(0)
+            .L32(1)
+            .L32(bucket_count)
+            .L32(names.len() as u32)
+            // Abbreviation table size.
+            .L32(&abbrev_size)
+            // Augmentation string size, and string.
+            .L32(5)
+            .append_bytes(b"LLVM0\0\0\0")
+            // Compilation unit list.
+            .L32(0x10)
+            .L32(0x80)
+            // Foreign type unit list.
+            .L64(0x1234_5678_9abc_def0);
+
+        // Hash table.
+        let mut next_bucket = 0;
+        for (i, &(name, _)) in names.iter().enumerate() {
+            if bucket_count == 0 {
+                break;
+            }
+            let bucket = NameIndex::<EndianBuf<LittleEndian>>::hash(name) % bucket_count;
+            while next_bucket < bucket {
+                section = section.L32(0);
+                next_bucket += 1;
+            }
+            if next_bucket == bucket {
+                section = section.L32(i as u32 + 1);
+                next_bucket += 1;
+            }
+        }
+        while next_bucket < bucket_count {
+            section = section.L32(0);
+            next_bucket += 1;
+        }
+        if bucket_count != 0 {
+            for &(name, _) in names {
+                section = section.L32(NameIndex::<EndianBuf<LittleEndian>>::hash(name));
+            }
+        }
+
+        // String offsets, and entry offsets. The first name has the entries at
+        // offset 0, and the other names have the entry at offset 13.
+        for &(_, offset) in names {
+            section = section.L32(offset as u32);
+        }
+        for i in 0..names.len() {
+            section = section.L32(if i == 0 { 0 } else { 13 });
+        }
+
+        section = section
+            .mark(&abbrev_start)
+            // Abbreviation 1: subprogram, compile unit, DIE offset, parent.
+            .uleb(1)
+            .uleb(DW_TAG_subprogram.0)
+            .uleb(DW_IDX_compile_unit.0 as u64)
+            .uleb(DW_FORM_data1.0)
+            .uleb(DW_IDX_die_offset.0 as u64)
+            .uleb(DW_FORM_ref4.0)
+            .uleb(DW_IDX_parent.0 as u64)
+            .uleb(DW_FORM_flag_present.0)
+            .uleb(0)
+            .uleb(0)
+            .uleb(0)
+            .mark(&abbrev_end)
+            // Entry pool, with the entries at offset 0.
+            .uleb(1)
+            .D8(1)
+            .L32(0x2a)
+            .uleb(1)
+            .D8(0)
+            .L32(0x30)
+            .uleb(0)
+            // Entry at offset 13.
+            .uleb(1)
+            .D8(0)
+            .L32(0x40)
+            .uleb(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        abbrev_size.set_const((&abbrev_end - &abbrev_start) as u64);
+        section.get_contents().unwrap()
+    }
+
+    #[test]
+    fn test_hash() {
+        type Index<'a> = NameIndex<EndianBuf<'a, LittleEndian>>;
+        assert_eq!(Index::hash(""), 5381);
+        // DJB hash of "main".
+        assert_eq!(Index::hash("main"), 0x7c9a_7f6a);
+        assert_eq!(Index::hash("MAIN"), Index::hash("main"));
+    }
+
+    #[test]
+    fn test_parse_name_index() {
+        let names = [("main", MAIN), ("Foo", FOO_UPPER)];
+        let buf = name_index(&names, 0);
+        let debug_names = DebugNames::new(&buf, LittleEndian);
+        let mut indexes = debug_names.name_indexes();
+        assert_eq!(indexes.offset(), DebugNamesOffset(0));
+        let index = indexes.next().unwrap().unwrap();
+        assert_eq!(indexes.offset(), DebugNamesOffset(buf.len()));
+        assert!(indexes.next().unwrap().is_none());
+
+        assert_eq!(index.offset(), DebugNamesOffset(0));
+        assert_eq!(index.format(), Format::Dwarf32);
+        assert_eq!(index.version(), 5);
+        assert_eq!(index.comp_unit_count(), 2);
+        assert_eq!(index.local_type_unit_count(), 0);
+        assert_eq!(index.foreign_type_unit_count(), 1);
+        assert_eq!(index.bucket_count(), 0);
+        assert_eq!(index.name_count(), 2);
+        assert_eq!(index.augmentation_string().buf(), b"LLVM0");
+        assert_eq!(index.comp_unit(1), Ok(DebugInfoOffset(0x80)));
+        assert_eq!(
+            index.foreign_type_unit(0),
+            Ok(DebugTypeSignature(0x1234_5678_9abc_def0))
+        );
+        match index.comp_unit(2) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+
+        let abbrevs = index.abbreviations();
+        assert_eq!(abbrevs.len(), 1);
+        assert_eq!(abbrevs[0].code(), 1);
+        assert_eq!(abbrevs[0].tag(), DW_TAG_subprogram);
+        assert_eq!(abbrevs[0].attributes().len(), 3);
+        assert_eq!(abbrevs[0].attributes()[1].name(), DW_IDX_die_offset);
+        assert_eq!(abbrevs[0].attributes()[1].form(), DW_FORM_ref4);
+
+        let mut iter = index.names();
+        assert_eq!(iter.index(), 0);
+        assert_eq!(iter.next().map(|name| name.map(|name| name.index())), Ok(Some(0)));
+        assert_eq!(iter.index(), 1);
+        let name = iter.next().unwrap().unwrap();
+        assert!(iter.next().unwrap().is_none());
+        assert_eq!(iter.index(), 2);
+        assert_eq!(name.index(), 1);
+        assert_eq!(name.hash(), None);
+        assert_eq!(name.string_offset(), DebugStrOffset(FOO_UPPER as usize));
+        assert_eq!(name.entry_offset(), NameEntryOffset(13));
+
+        let first = index.name(0).unwrap();
+        let entries: Vec<_> = index.entries(&first).unwrap().collect().unwrap();
+        assert_eq!(entries.len(), 2);
+        let mut iter = index.entries(&first).unwrap();
+        assert_eq!(iter.offset(), NameEntryOffset(0));
+        iter.next().unwrap();
+        assert_eq!(iter.offset(), entries[1].offset());
+        assert_eq!(entries[0].offset(), NameEntryOffset(0));
+        assert_eq!(entries[0].tag(), DW_TAG_subprogram);
+        assert_eq!(entries[0].compile_unit(), Some(1));
+        assert_eq!(entries[0].die_offset(), Some(UnitOffset(0x2a)));
+        assert_eq!(entries[0].parent(), None);
+        assert_eq!(
+            entries[0].attr_value(DW_IDX_parent),
+            Some(NameAttributeValue::Flag(true))
+        );
+        assert_eq!(entries[0].type_unit(), None);
+        assert_eq!(entries[0].type_hash(), None);
+        assert_eq!(
+            index.entry_compile_unit(&entries[0]),
+            Ok(Some(DebugInfoOffset(0x80)))
+        );
+        assert_eq!(
+            index.entry_die_offset(&entries[0]),
+            Ok(Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0xaa))))
+        );
+        assert_eq!(entries[1].offset(), NameEntryOffset(6));
+        assert_eq!(index.entry(NameEntryOffset(6)), Ok(entries[1].clone()));
+        assert_eq!(
+            index.entry(NameEntryOffset(12)),
+            Err(Error::NoEntryAtGivenOffset)
+        );
+
+        let entries: Vec<_> = index.entries(&name).unwrap().collect().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].compile_unit(), Some(0));
+        assert_eq!(entries[0].die_offset(), Some(UnitOffset(0x40)));
+        assert_eq!(
+            index.entry_compile_unit(&entries[0]),
+            Ok(Some(DebugInfoOffset(0x10)))
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let debug_str = DebugStr::new(DEBUG_STR, LittleEndian);
+        let mut names = [
+            ("main", MAIN),
+            ("Foo", FOO_UPPER),
+            ("foo", FOO_LOWER),
+            ("_ZN3Foo3barEv", MANGLED),
+        ];
+        for &bucket_count in &[0, 1, 3] {
+            if bucket_count != 0 {
+                names.sort_by_key(|&(name, _)| {
+                    NameIndex::<EndianBuf<LittleEndian>>::hash(name) % bucket_count
+                });
+            }
+            let buf = name_index(&names, bucket_count);
+            let debug_names = DebugNames::new(&buf, LittleEndian);
+            let index = debug_names.name_indexes().next().unwrap().unwrap();
+
+            let find = |name: &str| {
+                index
+                    .find(&debug_str, name)
+                    .unwrap()
+                    .map(|name| name.string_offset().0 as u64)
+            };
+            assert_eq!(find("main"), Some(MAIN));
+            assert_eq!(find("Foo"), Some(FOO_UPPER));
+            assert_eq!(find("foo"), Some(FOO_LOWER));
+            assert_eq!(find("_ZN3Foo3barEv"), Some(MANGLED));
+            assert_eq!(find("MAIN"), None);
+            assert_eq!(find("bar"), None);
+
+            let mut found: Vec<_> = index
+                .find_ignore_case(&debug_str, "FOO")
+                .unwrap()
+                .iter()
+                .map(|name| name.string_offset().0 as u64)
+                .collect();
+            found.sort();
+            assert_eq!(found, [FOO_UPPER, FOO_LOWER]);
+            assert!(index.find_ignore_case(&debug_str, "bar").unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_name_index_bad_version() {
+        let buf = Section::with_endian(Endian::Little)
+            .L32(2)
+            .L16(4)
+            .get_contents()
+            .unwrap();
+        let debug_names = DebugNames::new(&buf, LittleEndian);
+        let mut indexes = debug_names.name_indexes();
+        assert_eq!(indexes.next(), Err(Error::UnknownVersion(4)));
+        assert_eq!(indexes.next(), Ok(None));
+    }
+}
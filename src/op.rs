@@ -1,9 +1,14 @@
 //! Functions for parsing and evaluating DWARF expressions.
 
+use arch::Register;
+use arrayvec::ArrayVec;
+use cfi::{ArrayLike, StoreOnHeap, StoreOnStack};
 use constants;
-use parser::{Error, Format};
+use parser::{parse_register, Error, Format, ReaderLimits};
 use reader::{Reader, ReaderOffset};
+use rnglists::AddressIndex;
 use unit::{DebugInfoOffset, UnitOffset};
+use value::{Value, ValueType};
 use std::mem;
 use vec::Vec;
 
@@ -126,13 +131,13 @@ where
         /// The DIE of the base type or 0 to indicate the generic type
         base_type: UnitOffset<Offset>,
         /// The register number.
-        register: u64,
+        register: Register,
     },
     /// Find the value of the given register, add the offset, and then
     /// push the resulting sum on the stack.
     RegisterOffset {
         /// The register number.
-        register: u64,
+        register: Register,
         /// The offset to add.
         offset: i64,
     },
@@ -203,6 +208,36 @@ where
         /// The offfset to add.
         offset: u64,
     },
+    /// Represents `DW_OP_addrx`. Push the address at the given index in
+    /// the `.debug_addr` section.
+    AddressIndex {
+        /// The index into the unit's `.debug_addr` entries.
+        index: AddressIndex,
+    },
+    /// Represents `DW_OP_constx`. Push the constant at the given index in
+    /// the `.debug_addr` section.
+    ConstantIndex {
+        /// The index into the unit's `.debug_addr` entries.
+        index: AddressIndex,
+    },
+    /// Represents `DW_OP_WASM_location 0x0`. The piece is the given
+    /// WebAssembly local.
+    WasmLocal {
+        /// The index of the local.
+        index: u64,
+    },
+    /// Represents `DW_OP_WASM_location 0x1` or `DW_OP_WASM_location 0x3`.
+    /// The piece is the given WebAssembly global.
+    WasmGlobal {
+        /// The index of the global.
+        index: u64,
+    },
+    /// Represents `DW_OP_WASM_location 0x2`. The piece is the given item on
+    /// the WebAssembly operand stack.
+    WasmStack {
+        /// The index of the item, where 0 is the bottom of the stack.
+        index: u64,
+    },
     /// Represents `DW_OP_const_type`.
     TypedLiteral {
         /// The DIE of the base type
@@ -233,8 +268,14 @@ enum OperationEvaluationResult<R: Reader> {
         address: u64,
         size: u8,
         space: Option<u64>,
+        value_type: ValueType,
+    },
+    AwaitingRegister {
+        register: Register,
+        offset: u64,
+        value_type: ValueType,
     },
-    AwaitingRegister { register: u64, offset: u64 },
+    AwaitingBaseType { base_type: UnitOffset<R::Offset> },
     AwaitingFrameBase { offset: u64 },
     AwaitingTls { index: u64 },
     AwaitingCfa,
@@ -242,10 +283,11 @@ enum OperationEvaluationResult<R: Reader> {
     AwaitingEntryValue { expression: R },
     AwaitingParameterRef { parameter: UnitOffset<R::Offset> },
     AwaitingTextBase { offset: u64 },
+    AwaitingVendorOperation,
 }
 
 /// A single location of a piece of the result of a DWARF expression.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Location<R, Offset = usize>
 where
     R: Reader<Offset = Offset>,
@@ -257,18 +299,24 @@ where
     /// The piece is found in a register.
     Register {
         /// The register number.
-        register: u64,
+        register: Register,
     },
     /// The piece is found in memory.
     Address {
         /// The address.
         address: u64,
     },
-    /// The piece is a scalar value.
+    /// The piece is a scalar value of the generic type.
     Scalar {
         /// The value.
         value: u64,
     },
+    /// The piece is a value with a base type, as produced by the DWARF 5
+    /// typed stack operations.
+    Value {
+        /// The value.
+        value: Value,
+    },
     /// The piece is represented by some constant bytes.
     Bytes {
         /// The value.
@@ -281,6 +329,21 @@ where
         /// The byte offset into the value that the implicit pointer points to.
         byte_offset: i64,
     },
+    /// The piece is found in a WebAssembly local.
+    WasmLocal {
+        /// The index of the local.
+        index: u64,
+    },
+    /// The piece is found in a WebAssembly global.
+    WasmGlobal {
+        /// The index of the global.
+        index: u64,
+    },
+    /// The piece is found on the WebAssembly operand stack.
+    WasmStack {
+        /// The index of the item, where 0 is the bottom of the stack.
+        index: u64,
+    },
 }
 
 impl<R, Offset> Location<R, Offset>
@@ -299,7 +362,7 @@ where
 
 /// The description of a single piece of the result of a DWARF
 /// expression.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Piece<R, Offset = usize>
 where
     R: Reader<Offset = Offset>,
@@ -317,7 +380,7 @@ where
 
 // A helper function to handle branch offsets.
 fn compute_pc<R: Reader>(pc: &R, bytecode: &R, offset: i16) -> Result<R, Error> {
-    let pc_offset = pc.offset_from(bytecode);
+    let pc_offset = pc.offset_from(bytecode)?;
     let new_pc_offset = pc_offset.wrapping_add(R::Offset::from_i16(offset));
     if new_pc_offset > bytecode.len() {
         Err(Error::BadBranchTarget(new_pc_offset.into_u64()))
@@ -332,11 +395,11 @@ fn generic_type<O: ReaderOffset>() -> UnitOffset<O> {
     UnitOffset(O::from_u64(0).unwrap())
 }
 
-fn generic_register<R: Reader>(reg: u64) -> Operation<R, R::Offset> {
+fn generic_register<R: Reader>(register: Register) -> Operation<R, R::Offset> {
     Operation::Register {
         base_type: generic_type(),
-        register: reg,
-   }
+        register: register,
+    }
 }
 
 impl<R, Offset> Operation<R, Offset>
@@ -508,275 +571,275 @@ where
             constants::DW_OP_lit29 => Ok(Operation::Literal { value: 29 }),
             constants::DW_OP_lit30 => Ok(Operation::Literal { value: 30 }),
             constants::DW_OP_lit31 => Ok(Operation::Literal { value: 31 }),
-            constants::DW_OP_reg0 => Ok(generic_register(0)),
-            constants::DW_OP_reg1 => Ok(generic_register(1)),
-            constants::DW_OP_reg2 => Ok(generic_register(2)),
-            constants::DW_OP_reg3 => Ok(generic_register(3)),
-            constants::DW_OP_reg4 => Ok(generic_register(4)),
-            constants::DW_OP_reg5 => Ok(generic_register(5)),
-            constants::DW_OP_reg6 => Ok(generic_register(6)),
-            constants::DW_OP_reg7 => Ok(generic_register(7)),
-            constants::DW_OP_reg8 => Ok(generic_register(8)),
-            constants::DW_OP_reg9 => Ok(generic_register(9)),
-            constants::DW_OP_reg10 => Ok(generic_register(10)),
-            constants::DW_OP_reg11 => Ok(generic_register(11)),
-            constants::DW_OP_reg12 => Ok(generic_register(12)),
-            constants::DW_OP_reg13 => Ok(generic_register(13)),
-            constants::DW_OP_reg14 => Ok(generic_register(14)),
-            constants::DW_OP_reg15 => Ok(generic_register(15)),
-            constants::DW_OP_reg16 => Ok(generic_register(16)),
-            constants::DW_OP_reg17 => Ok(generic_register(17)),
-            constants::DW_OP_reg18 => Ok(generic_register(18)),
-            constants::DW_OP_reg19 => Ok(generic_register(19)),
-            constants::DW_OP_reg20 => Ok(generic_register(20)),
-            constants::DW_OP_reg21 => Ok(generic_register(21)),
-            constants::DW_OP_reg22 => Ok(generic_register(22)),
-            constants::DW_OP_reg23 => Ok(generic_register(23)),
-            constants::DW_OP_reg24 => Ok(generic_register(24)),
-            constants::DW_OP_reg25 => Ok(generic_register(25)),
-            constants::DW_OP_reg26 => Ok(generic_register(26)),
-            constants::DW_OP_reg27 => Ok(generic_register(27)),
-            constants::DW_OP_reg28 => Ok(generic_register(28)),
-            constants::DW_OP_reg29 => Ok(generic_register(29)),
-            constants::DW_OP_reg30 => Ok(generic_register(30)),
-            constants::DW_OP_reg31 => Ok(generic_register(31)),
+            constants::DW_OP_reg0 => Ok(generic_register(Register(0))),
+            constants::DW_OP_reg1 => Ok(generic_register(Register(1))),
+            constants::DW_OP_reg2 => Ok(generic_register(Register(2))),
+            constants::DW_OP_reg3 => Ok(generic_register(Register(3))),
+            constants::DW_OP_reg4 => Ok(generic_register(Register(4))),
+            constants::DW_OP_reg5 => Ok(generic_register(Register(5))),
+            constants::DW_OP_reg6 => Ok(generic_register(Register(6))),
+            constants::DW_OP_reg7 => Ok(generic_register(Register(7))),
+            constants::DW_OP_reg8 => Ok(generic_register(Register(8))),
+            constants::DW_OP_reg9 => Ok(generic_register(Register(9))),
+            constants::DW_OP_reg10 => Ok(generic_register(Register(10))),
+            constants::DW_OP_reg11 => Ok(generic_register(Register(11))),
+            constants::DW_OP_reg12 => Ok(generic_register(Register(12))),
+            constants::DW_OP_reg13 => Ok(generic_register(Register(13))),
+            constants::DW_OP_reg14 => Ok(generic_register(Register(14))),
+            constants::DW_OP_reg15 => Ok(generic_register(Register(15))),
+            constants::DW_OP_reg16 => Ok(generic_register(Register(16))),
+            constants::DW_OP_reg17 => Ok(generic_register(Register(17))),
+            constants::DW_OP_reg18 => Ok(generic_register(Register(18))),
+            constants::DW_OP_reg19 => Ok(generic_register(Register(19))),
+            constants::DW_OP_reg20 => Ok(generic_register(Register(20))),
+            constants::DW_OP_reg21 => Ok(generic_register(Register(21))),
+            constants::DW_OP_reg22 => Ok(generic_register(Register(22))),
+            constants::DW_OP_reg23 => Ok(generic_register(Register(23))),
+            constants::DW_OP_reg24 => Ok(generic_register(Register(24))),
+            constants::DW_OP_reg25 => Ok(generic_register(Register(25))),
+            constants::DW_OP_reg26 => Ok(generic_register(Register(26))),
+            constants::DW_OP_reg27 => Ok(generic_register(Register(27))),
+            constants::DW_OP_reg28 => Ok(generic_register(Register(28))),
+            constants::DW_OP_reg29 => Ok(generic_register(Register(29))),
+            constants::DW_OP_reg30 => Ok(generic_register(Register(30))),
+            constants::DW_OP_reg31 => Ok(generic_register(Register(31))),
             constants::DW_OP_breg0 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 0,
+                    register: Register(0),
                     offset: value,
                 })
             }
             constants::DW_OP_breg1 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 1,
+                    register: Register(1),
                     offset: value,
                 })
             }
             constants::DW_OP_breg2 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 2,
+                    register: Register(2),
                     offset: value,
                 })
             }
             constants::DW_OP_breg3 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 3,
+                    register: Register(3),
                     offset: value,
                 })
             }
             constants::DW_OP_breg4 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 4,
+                    register: Register(4),
                     offset: value,
                 })
             }
             constants::DW_OP_breg5 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 5,
+                    register: Register(5),
                     offset: value,
                 })
             }
             constants::DW_OP_breg6 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 6,
+                    register: Register(6),
                     offset: value,
                 })
             }
             constants::DW_OP_breg7 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 7,
+                    register: Register(7),
                     offset: value,
                 })
             }
             constants::DW_OP_breg8 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 8,
+                    register: Register(8),
                     offset: value,
                 })
             }
             constants::DW_OP_breg9 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 9,
+                    register: Register(9),
                     offset: value,
                 })
             }
             constants::DW_OP_breg10 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 10,
+                    register: Register(10),
                     offset: value,
                 })
             }
             constants::DW_OP_breg11 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 11,
+                    register: Register(11),
                     offset: value,
                 })
             }
             constants::DW_OP_breg12 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 12,
+                    register: Register(12),
                     offset: value,
                 })
             }
             constants::DW_OP_breg13 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 13,
+                    register: Register(13),
                     offset: value,
                 })
             }
             constants::DW_OP_breg14 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 14,
+                    register: Register(14),
                     offset: value,
                 })
             }
             constants::DW_OP_breg15 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 15,
+                    register: Register(15),
                     offset: value,
                 })
             }
             constants::DW_OP_breg16 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 16,
+                    register: Register(16),
                     offset: value,
                 })
             }
             constants::DW_OP_breg17 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 17,
+                    register: Register(17),
                     offset: value,
                 })
             }
             constants::DW_OP_breg18 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 18,
+                    register: Register(18),
                     offset: value,
                 })
             }
             constants::DW_OP_breg19 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 19,
+                    register: Register(19),
                     offset: value,
                 })
             }
             constants::DW_OP_breg20 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 20,
+                    register: Register(20),
                     offset: value,
                 })
             }
             constants::DW_OP_breg21 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 21,
+                    register: Register(21),
                     offset: value,
                 })
             }
             constants::DW_OP_breg22 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 22,
+                    register: Register(22),
                     offset: value,
                 })
             }
             constants::DW_OP_breg23 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 23,
+                    register: Register(23),
                     offset: value,
                 })
             }
             constants::DW_OP_breg24 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 24,
+                    register: Register(24),
                     offset: value,
                 })
             }
             constants::DW_OP_breg25 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 25,
+                    register: Register(25),
                     offset: value,
                 })
             }
             constants::DW_OP_breg26 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 26,
+                    register: Register(26),
                     offset: value,
                 })
             }
             constants::DW_OP_breg27 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 27,
+                    register: Register(27),
                     offset: value,
                 })
             }
             constants::DW_OP_breg28 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 28,
+                    register: Register(28),
                     offset: value,
                 })
             }
             constants::DW_OP_breg29 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 29,
+                    register: Register(29),
                     offset: value,
                 })
             }
             constants::DW_OP_breg30 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 30,
+                    register: Register(30),
                     offset: value,
                 })
             }
             constants::DW_OP_breg31 => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: 31,
+                    register: Register(31),
                     offset: value,
                 })
             }
             constants::DW_OP_regx => {
-                let value = bytes.read_uleb128()?;
-                Ok(generic_register(value))
+                let register = parse_register(bytes)?;
+                Ok(generic_register(register))
             }
             constants::DW_OP_fbreg => {
                 let value = bytes.read_sleb128()?;
                 Ok(Operation::FrameOffset { offset: value })
             }
             constants::DW_OP_bregx => {
-                let regno = bytes.read_uleb128()?;
+                let register = parse_register(bytes)?;
                 let offset = bytes.read_sleb128()?;
                 Ok(Operation::RegisterOffset {
-                    register: regno,
+                    register: register,
                     offset: offset,
                 })
             }
@@ -862,6 +925,39 @@ where
                     offset: UnitOffset(value),
                 })
             }
+            constants::DW_OP_addrx | constants::DW_OP_GNU_addr_index => {
+                let index = bytes.read_uleb128()?;
+                Ok(Operation::AddressIndex {
+                    index: AddressIndex(index),
+                })
+            }
+            constants::DW_OP_constx | constants::DW_OP_GNU_const_index => {
+                let index = bytes.read_uleb128()?;
+                Ok(Operation::ConstantIndex {
+                    index: AddressIndex(index),
+                })
+            }
+            constants::DW_OP_WASM_location => match bytes.read_u8()? {
+                0x0 => {
+                    let index = bytes.read_uleb128()?;
+                    Ok(Operation::WasmLocal { index: index })
+                }
+                0x1 => {
+                    let index = bytes.read_uleb128()?;
+                    Ok(Operation::WasmGlobal { index: index })
+                }
+                0x2 => {
+                    let index = bytes.read_uleb128()?;
+                    Ok(Operation::WasmStack { index: index })
+                }
+                0x3 => {
+                    let index = bytes.read_u32()?;
+                    Ok(Operation::WasmGlobal {
+                        index: index as u64,
+                    })
+                }
+                _ => Err(Error::InvalidExpression(name)),
+            },
             constants::DW_OP_const_type | constants::DW_OP_GNU_const_type => {
                 let base_type = bytes.read_uleb128().and_then(R::Offset::from_u64)?;
                 let len = bytes.read_u8()?;
@@ -872,7 +968,7 @@ where
                 })
             }
             constants::DW_OP_regval_type | constants::DW_OP_GNU_regval_type => {
-                let register = bytes.read_uleb128()?;
+                let register = parse_register(bytes)?;
                 let base_type = bytes.read_uleb128().and_then(R::Offset::from_u64)?;
                 Ok(Operation::Register {
                     base_type: UnitOffset(base_type),
@@ -937,7 +1033,7 @@ pub enum EvaluationResult<R: Reader> {
     /// The `Evaluation` needs a value from a register to proceed further.  Once
     /// the caller determines what value to provide it should resume the
     /// `Evaluation` by calling `Evaluation::resume_with_register`.
-    RequiresRegister(u64),
+    RequiresRegister(Register),
     /// The `Evaluation` needs the frame base address to proceed further.  Once
     /// the caller determines what value to provide it should resume the
     /// `Evaluation` by calling `Evaluation::resume_with_frame_base`.  The frame
@@ -958,9 +1054,13 @@ pub enum EvaluationResult<R: Reader> {
     /// `Evaluation::resume_with_at_location`.
     RequiresAtLocation(DieReference<R::Offset>),
     /// The `Evaluation` needs the value produced by evaluating a DWARF
-    /// expression at the entry point of the current subprogram.  Once the
-    /// caller determines what value to provide it should resume the
-    /// `Evaluation` by calling `Evaluation::resume_with_entry_value`.
+    /// expression at the entry point of the current subprogram.  The
+    /// expression uses the same address size and format as the one being
+    /// evaluated; typically it is a single register operation, whose value
+    /// must be recovered from the caller's frame (for example by unwinding,
+    /// or by matching a `DW_TAG_call_site_parameter`).  Once the caller
+    /// determines what value to provide it should resume the `Evaluation`
+    /// by calling `Evaluation::resume_with_entry_value`.
     RequiresEntryValue(Expression<R>),
     /// The `Evaluation` needs the value of the parameter at the given location
     /// in the current function's caller.  Once the caller determines what value
@@ -972,10 +1072,29 @@ pub enum EvaluationResult<R: Reader> {
     /// should resume the `Evaluation` by calling
     /// `Evaluation::resume_with_text_base`.
     RequiresTextBase,
+    /// The `Evaluation` needs the type of the base type DIE at the given
+    /// offset in the current unit, as used by the typed stack operations.
+    /// Once the caller determines the type, typically by calling
+    /// `ValueType::from_entry`, it should resume the `Evaluation` by calling
+    /// `Evaluation::resume_with_base_type`.
+    RequiresBaseType(UnitOffset<R::Offset>),
+    /// The `Evaluation` encountered an opcode in the vendor extension range
+    /// (`DW_OP_lo_user` to `DW_OP_hi_user`) that it does not understand.
+    /// The caller should decode the operation's operands from the start of
+    /// `operands`, perform the operation, and then resume the `Evaluation`
+    /// by calling `Evaluation::resume_with_vendor_operation`.
+    RequiresVendorOperation {
+        /// The unrecognized opcode.
+        opcode: constants::DwOp,
+        /// The remainder of the expression, starting with the operands of
+        /// the unrecognized operation.
+        operands: R,
+    },
 }
 
 /// The bytecode for a DWARF expression or location description.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Expression<R: Reader>(pub R);
 
 impl<R: Reader> Expression<R> {
@@ -1001,6 +1120,135 @@ impl<R: Reader> Expression<R> {
     pub fn evaluation(self, address_size: u8, format: Format) -> Evaluation<R> {
         Evaluation::new(self.0, address_size, format)
     }
+
+    /// Decode this expression and return everything it refers to outside
+    /// of itself.
+    ///
+    /// This does not evaluate the expression, so references are reported
+    /// even if they are on a branch that would never be taken.  The
+    /// expressions nested within `DW_OP_entry_value` are scanned too, after
+    /// the expression containing them.
+    /// References to the generic base type (a unit offset of 0) are not
+    /// reported.
+    pub fn references(
+        &self,
+        address_size: u8,
+        format: Format,
+    ) -> Result<Vec<ExpressionReference<R::Offset>>, Error> {
+        let mut references = Vec::new();
+        let mut stack = Vec::new();
+        stack.push(self.0.clone());
+        while let Some(bytecode) = stack.pop() {
+            let mut pc = bytecode.clone();
+            while !pc.is_empty() {
+                let operation = Operation::parse(&mut pc, &bytecode, address_size, format)?;
+                let reference = match operation {
+                    Operation::Call { offset } => ExpressionReference::Die(offset),
+                    Operation::ImplicitPointer { value, .. } => {
+                        ExpressionReference::Die(DieReference::DebugInfoRef(value))
+                    }
+                    Operation::ParameterRef { offset } => {
+                        ExpressionReference::Die(DieReference::UnitRef(offset))
+                    }
+                    Operation::Register { base_type, .. } |
+                    Operation::Deref { base_type, .. } |
+                    Operation::TypedLiteral { base_type, .. } |
+                    Operation::Convert { base_type } |
+                    Operation::Reinterpret { base_type } => {
+                        if base_type == generic_type() {
+                            continue;
+                        }
+                        ExpressionReference::Die(DieReference::UnitRef(base_type))
+                    }
+                    Operation::AddressIndex { index } | Operation::ConstantIndex { index } => {
+                        ExpressionReference::AddressIndex(index)
+                    }
+                    Operation::EntryValue { expression } => {
+                        stack.push(expression);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                references.push(reference);
+            }
+        }
+        Ok(references)
+    }
+}
+
+/// Something outside of a DWARF expression that the expression refers to,
+/// as returned by `Expression::references`.
+///
+/// DWARF expressions never refer to `.debug_str_offsets` indices, so there
+/// is no variant for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpressionReference<T = usize> {
+    /// A reference to a DIE, such as the target of `DW_OP_call_ref` or
+    /// `DW_OP_implicit_pointer`, or the base type of a typed operation.
+    Die(DieReference<T>),
+    /// An index into the `.debug_addr` section, from `DW_OP_addrx` or
+    /// `DW_OP_constx`.
+    AddressIndex(AddressIndex),
+}
+
+/// Specifies how an `Evaluation` stores its stack of values, the stack of
+/// expressions being evaluated for `DW_OP_call*` operations and
+/// `DW_AT_frame_base`, the pieces of its result, and the base types that it
+/// has been given.
+///
+/// If the stack of values, the stack of expressions, or the result is full,
+/// then evaluation fails with `Error::EvaluationStorageFull`. If the base
+/// types are full, then an entry is evicted, and that base type will be
+/// requested again if it is needed.
+pub trait EvaluationStorage<R: Reader> {
+    /// The storage for the stack of values.
+    type Stack: ArrayLike<Item = Value>;
+
+    /// The storage for the stack of expressions.
+    type ExpressionStack: ArrayLike<Item = (R, R)>;
+
+    /// The storage for the pieces of the result.
+    type Result: ArrayLike<Item = Piece<R, R::Offset>>;
+
+    /// The storage for the base types given by
+    /// `Evaluation::resume_with_base_type`.
+    type BaseTypes: ArrayLike<Item = (UnitOffset<R::Offset>, ValueType)>;
+}
+
+const MAX_EVALUATION_STACK_DEPTH: usize = 64;
+const MAX_EXPRESSION_STACK_DEPTH: usize = 8;
+const MAX_RESULT_PIECES: usize = 16;
+const MAX_BASE_TYPES: usize = 8;
+
+/// Fixed capacity storage, which never allocates.
+///
+/// The stack may hold up to 64 values, up to 8 expressions may be called at
+/// once, the result may have up to 16 pieces, and up to 8 base types are
+/// remembered.
+impl<R: Reader> EvaluationStorage<R> for StoreOnStack {
+    type Stack = ArrayVec<[Value; MAX_EVALUATION_STACK_DEPTH]>;
+    type ExpressionStack = ArrayVec<[(R, R); MAX_EXPRESSION_STACK_DEPTH]>;
+    type Result = ArrayVec<[Piece<R, R::Offset>; MAX_RESULT_PIECES]>;
+    type BaseTypes = ArrayVec<[(UnitOffset<R::Offset>, ValueType); MAX_BASE_TYPES]>;
+}
+
+/// Growable storage, which has no limits.  This is the default storage.
+impl<R: Reader> EvaluationStorage<R> for StoreOnHeap {
+    type Stack = Vec<Value>;
+    type ExpressionStack = Vec<(R, R)>;
+    type Result = Vec<Piece<R, R::Offset>>;
+    type BaseTypes = Vec<(UnitOffset<R::Offset>, ValueType)>;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrameBaseEvaluation {
+    // The offset of the DW_OP_fbreg.
+    offset: u64,
+    // The depth of the stack before evaluating the frame base expression.
+    stack_len: usize,
+    // The depth of the expression stack that the frame base expression
+    // returns to.
+    expression_stack_len: usize,
 }
 
 /// A DWARF expression evaluator.
@@ -1021,6 +1269,11 @@ impl<R: Reader> Expression<R> {
 /// be driven synchronously (as shown below) or by some asynchronous mechanism
 /// such as futures.
 ///
+/// By default, the stack and the result are stored in growable vectors.  An
+/// `Evaluation` created by `new_in` with `StoreOnStack` storage never
+/// allocates, and fails with `Error::EvaluationStorageFull` instead.  Its
+/// result is available from `as_result()`.
+///
 /// # Examples
 /// ```rust,no_run
 /// use gimli::{EndianBuf, Evaluation, EvaluationResult, Format, LittleEndian};
@@ -1050,12 +1303,13 @@ impl<R: Reader> Expression<R> {
 /// println!("{:?}", result);
 /// ```
 #[derive(Debug)]
-pub struct Evaluation<R: Reader> {
+pub struct Evaluation<R: Reader, S: EvaluationStorage<R> = StoreOnHeap> {
     bytecode: R,
     address_size: u8,
     format: Format,
     object_address: Option<u64>,
     max_iterations: Option<u32>,
+    max_expression_length: Option<usize>,
     iteration: u32,
     state: EvaluationState<R>,
 
@@ -1065,16 +1319,26 @@ pub struct Evaluation<R: Reader> {
     addr_mask: u64,
 
     // The stack.
-    stack: Vec<u64>,
+    stack: S::Stack,
 
     // The next operation to decode and evaluate.
     pc: R,
 
     // If we see a DW_OP_call* operation, the previous PC and bytecode
     // is stored here while evaluating the subroutine.
-    expression_stack: Vec<(R, R)>,
+    expression_stack: S::ExpressionStack,
+
+    // The frame base, if it is known, and the `DW_AT_frame_base` expression
+    // used to compute it otherwise.  That expression is evaluated like a
+    // DW_OP_call* subroutine, and its state is stored here meanwhile.
+    frame_base: Option<u64>,
+    frame_base_expression: Option<R>,
+    frame_base_evaluation: Option<FrameBaseEvaluation>,
+
+    // The types of the base type DIEs that have been requested.
+    base_types: S::BaseTypes,
 
-    result: Vec<Piece<R, R::Offset>>,
+    result: S::Result,
 }
 
 impl<R: Reader> Evaluation<R> {
@@ -1083,6 +1347,29 @@ impl<R: Reader> Evaluation<R> {
     /// The new evaluator is created without an initial value, without
     /// an object address, and without a maximum number of iterations.
     pub fn new(bytecode: R, address_size: u8, format: Format) -> Evaluation<R> {
+        Self::new_in(bytecode, address_size, format)
+    }
+
+    /// Get the result of this `Evaluation`.
+    ///
+    /// # Panics
+    /// Panics if this `Evaluation` has not been driven to completion.
+    pub fn result(self) -> Vec<Piece<R, R::Offset>> {
+        match self.state {
+            EvaluationState::Complete => self.result,
+            _ => {
+                panic!("Called `Evaluation::result` on an `Evaluation` that has not been completed")
+            }
+        }
+    }
+}
+
+impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
+    /// Create a new DWARF expression evaluator that uses the given storage.
+    ///
+    /// The new evaluator is created without an initial value, without
+    /// an object address, and without a maximum number of iterations.
+    pub fn new_in(bytecode: R, address_size: u8, format: Format) -> Evaluation<R, S> {
         let pc = bytecode.clone();
         Evaluation {
             bytecode: bytecode,
@@ -1090,17 +1377,22 @@ impl<R: Reader> Evaluation<R> {
             format: format,
             object_address: None,
             max_iterations: None,
+            max_expression_length: None,
             iteration: 0,
             state: EvaluationState::Start(None),
-            addr_mask: if address_size == 8 {
+            addr_mask: if address_size >= 8 {
                 !0u64
             } else {
                 (1 << (8 * address_size as u64)) - 1
             },
-            stack: Vec::new(),
-            expression_stack: Vec::new(),
+            stack: Default::default(),
+            expression_stack: Default::default(),
+            frame_base: None,
+            frame_base_expression: None,
+            frame_base_evaluation: None,
+            base_types: Default::default(),
             pc: pc,
-            result: Vec::new(),
+            result: Default::default(),
         }
     }
 
@@ -1133,6 +1425,28 @@ impl<R: Reader> Evaluation<R> {
         self.object_address = Some(value);
     }
 
+    /// Set the frame base address, as used by `DW_OP_fbreg`.  If the frame
+    /// base is set, then evaluation will not stop with
+    /// `EvaluationResult::RequiresFrameBase`.
+    pub fn set_frame_base(&mut self, value: u64) {
+        self.frame_base = Some(value);
+    }
+
+    /// Set the expression from the `DW_AT_frame_base` attribute of the
+    /// current function, to be used by `DW_OP_fbreg` when the frame base has
+    /// not been set with `set_frame_base`.
+    ///
+    /// The frame base expression is evaluated the first time it is needed,
+    /// and any values it requires are requested in the same way as for the
+    /// expression being evaluated.  If it produces a register location, such
+    /// as `DW_OP_reg6`, then the value of that register is requested and used
+    /// as the frame base.  If it produces an address, then the address is
+    /// used as the frame base.  Any other result, or a frame base expression
+    /// that itself uses `DW_OP_fbreg`, fails with `Error::InvalidFrameBase`.
+    pub fn set_frame_base_expression(&mut self, expression: Expression<R>) {
+        self.frame_base_expression = Some(expression.0);
+    }
+
     /// Set the maximum number of iterations to be allowed by the
     /// expression evaluator.
     ///
@@ -1146,29 +1460,82 @@ impl<R: Reader> Evaluation<R> {
         self.max_iterations = Some(value);
     }
 
-    fn pop(&mut self) -> Result<u64, Error> {
-        match self.stack.pop() {
-            Some(value) => Ok(value & self.addr_mask),
-            None => Err(Error::NotEnoughStackItems),
+    /// Apply the `max_expression_length` limit of the given `ReaderLimits`
+    /// to this evaluation.
+    ///
+    /// The limit applies separately to the expression being evaluated and to
+    /// each expression provided by `resume_with_at_location`. Longer
+    /// expressions fail with `Error::LimitExceeded`.
+    pub fn set_limits(&mut self, limits: &ReaderLimits) {
+        self.max_expression_length = limits.max_expression_length;
+    }
+
+    fn check_expression_length(&self, bytecode: &R) -> Result<(), Error> {
+        if let Some(max_length) = self.max_expression_length {
+            if bytecode.len().into_u64() > max_length as u64 {
+                return Err(Error::LimitExceeded);
+            }
         }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u64, Error> {
+        self.pop_value()?.to_u64(self.addr_mask)
     }
 
-    fn pop_signed(&mut self) -> Result<i64, Error> {
+    fn pop_value(&mut self) -> Result<Value, Error> {
         match self.stack.pop() {
-            Some(value) => {
-                let mut value = value & self.addr_mask;
-                if self.address_size < 8 && (value & (1u64 << (8 * self.address_size - 1))) != 0 {
-                    // Sign extend.
-                    value |= !self.addr_mask;
-                }
-                Ok(value as i64)
-            }
+            Some(value) => Ok(value),
             None => Err(Error::NotEnoughStackItems),
         }
     }
 
-    fn push(&mut self, value: u64) {
-        self.stack.push(value);
+    fn push(&mut self, value: u64) -> Result<(), Error> {
+        self.push_value(Value::Generic(value))
+    }
+
+    fn push_value(&mut self, value: Value) -> Result<(), Error> {
+        self.stack
+            .try_push(value)
+            .map_err(|_| Error::EvaluationStorageFull)
+    }
+
+    fn push_piece(&mut self, piece: Piece<R, R::Offset>) -> Result<(), Error> {
+        self.result
+            .try_push(piece)
+            .map_err(|_| Error::EvaluationStorageFull)
+    }
+
+    fn binary_operation<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(Value, Value, u64) -> Result<Value, Error>,
+    {
+        let v1 = self.pop_value()?;
+        let v2 = self.pop_value()?;
+        let value = f(v2, v1, self.addr_mask)?;
+        self.push_value(value)
+    }
+
+    fn unary_operation<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(Value, u64) -> Result<Value, Error>,
+    {
+        let v = self.pop_value()?;
+        let value = f(v, self.addr_mask)?;
+        self.push_value(value)
+    }
+
+    /// Return the type for the given base type DIE, or `None` if it must be
+    /// requested with `EvaluationResult::RequiresBaseType`.
+    fn value_type(&self, base_type: UnitOffset<R::Offset>) -> Option<ValueType> {
+        if base_type == generic_type() {
+            return Some(ValueType::Generic);
+        }
+        self.base_types
+            .as_slice()
+            .iter()
+            .find(|&&(offset, _)| offset == base_type)
+            .map(|&(_, value_type)| value_type)
     }
 
     fn evaluate_one_operation(
@@ -1179,146 +1546,76 @@ impl<R: Reader> Evaluation<R> {
         let mut piece_end = false;
         let mut current_location = Location::Empty;
 
+        macro_rules! value_type {
+            ($base_type:expr) => {
+                match self.value_type($base_type) {
+                    Some(value_type) => value_type,
+                    None => {
+                        return Ok(OperationEvaluationResult::AwaitingBaseType {
+                            base_type: $base_type,
+                        })
+                    }
+                }
+            };
+        }
+
         match *operation {
             Operation::Deref { base_type, size, space } => {
-                if base_type != UnitOffset(R::Offset::from_u64(0).unwrap()) {
-                    return Err(Error::UnsupportedTypedStack);
-                }
+                let value_type = value_type!(base_type);
                 let addr = self.pop()?;
                 let addr_space = if space { Some(self.pop()?) } else { None };
                 return Ok(OperationEvaluationResult::AwaitingMemory {
                     address: addr,
                     size: size,
                     space: addr_space,
+                    value_type: value_type,
                 });
             }
 
             Operation::Drop => {
-                self.pop()?;
+                self.pop_value()?;
             }
             Operation::Pick { index } => {
-                let len = self.stack.len();
+                let len = self.stack.as_slice().len();
                 let index = index as usize;
                 if index >= len {
                     return Err(Error::NotEnoughStackItems.into());
                 }
-                let value = self.stack[len - index - 1];
-                self.push(value);
+                let value = self.stack.as_slice()[len - index - 1];
+                self.push_value(value)?;
             }
             Operation::Swap => {
-                let top = self.pop()?;
-                let next = self.pop()?;
-                self.push(top);
-                self.push(next);
+                let top = self.pop_value()?;
+                let next = self.pop_value()?;
+                self.push_value(top)?;
+                self.push_value(next)?;
             }
             Operation::Rot => {
-                let one = self.pop()?;
-                let two = self.pop()?;
-                let three = self.pop()?;
-                self.push(one);
-                self.push(three);
-                self.push(two);
-            }
-
-            Operation::Abs => {
-                let value = self.pop_signed()?;
-                self.push(value.abs() as u64);
-            }
-            Operation::And => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2 & v1);
-            }
-            Operation::Div => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                if v1 == 0 {
-                    return Err(Error::DivisionByZero.into());
-                }
-                self.push(v2.wrapping_div(v1) as u64);
-            }
-            Operation::Minus => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2.wrapping_sub(v1));
-            }
-            Operation::Mod => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                if v1 == 0 {
-                    return Err(Error::DivisionByZero.into());
-                }
-                self.push(v2.wrapping_rem(v1));
-            }
-            Operation::Mul => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2.wrapping_mul(v1));
-            }
-            Operation::Neg => {
-                let v = self.pop()?;
-                self.push(v.wrapping_neg());
-            }
-            Operation::Not => {
-                let value = self.pop()?;
-                self.push(!value);
-            }
-            Operation::Or => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2 | v1);
-            }
-            Operation::Plus => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2.wrapping_add(v1));
-            }
+                let one = self.pop_value()?;
+                let two = self.pop_value()?;
+                let three = self.pop_value()?;
+                self.push_value(one)?;
+                self.push_value(three)?;
+                self.push_value(two)?;
+            }
+
+            Operation::Abs => self.unary_operation(Value::abs)?,
+            Operation::And => self.binary_operation(Value::and)?,
+            Operation::Div => self.binary_operation(Value::div)?,
+            Operation::Minus => self.binary_operation(Value::sub)?,
+            Operation::Mod => self.binary_operation(Value::rem)?,
+            Operation::Mul => self.binary_operation(Value::mul)?,
+            Operation::Neg => self.unary_operation(Value::neg)?,
+            Operation::Not => self.unary_operation(Value::not)?,
+            Operation::Or => self.binary_operation(Value::or)?,
+            Operation::Plus => self.binary_operation(Value::add)?,
             Operation::PlusConstant { value } => {
-                let v = self.pop()?;
-                self.push(v.wrapping_add(value));
-            }
-            Operation::Shl => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                // Because wrapping_shl takes a u32, not a u64, we do
-                // the check by hand.
-                if v1 >= 64 {
-                    self.push(0);
-                } else {
-                    self.push(v2 << v1)
-                }
-            }
-            Operation::Shr => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                // Because wrapping_shr takes a u32, not a u64, we do
-                // the check by hand.
-                if v1 >= 64 {
-                    self.push(0);
-                } else {
-                    self.push(v2 >> v1)
-                }
-            }
-            Operation::Shra => {
-                let v1 = self.pop()?;
-                let v2 = self.pop_signed()?;
-                // Because wrapping_shr takes a u32, not a u64, we do
-                // the check by hand.
-                if v1 >= 64 {
-                    if v2 < 0 {
-                        self.push(!0u64);
-                    } else {
-                        self.push(0);
-                    }
-                } else {
-                    self.push((v2 >> v1) as u64);
-                }
-            }
-            Operation::Xor => {
-                let v1 = self.pop()?;
-                let v2 = self.pop()?;
-                self.push(v2 ^ v1);
+                self.unary_operation(|v, addr_mask| v.add_constant(value, addr_mask))?
             }
+            Operation::Shl => self.binary_operation(Value::shl)?,
+            Operation::Shr => self.binary_operation(Value::shr)?,
+            Operation::Shra => self.binary_operation(Value::shra)?,
+            Operation::Xor => self.binary_operation(Value::xor)?,
 
             Operation::Bra { ref target } => {
                 let v = self.pop()?;
@@ -1327,62 +1624,41 @@ impl<R: Reader> Evaluation<R> {
                 }
             }
 
-            Operation::Eq => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 == v1 { 1 } else { 0 });
-            }
-            Operation::Ge => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 >= v1 { 1 } else { 0 });
-            }
-            Operation::Gt => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 > v1 { 1 } else { 0 });
-            }
-            Operation::Le => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 <= v1 { 1 } else { 0 });
-            }
-            Operation::Lt => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 < v1 { 1 } else { 0 });
-            }
-            Operation::Ne => {
-                let v1 = self.pop_signed()?;
-                let v2 = self.pop_signed()?;
-                self.push(if v2 != v1 { 1 } else { 0 });
-            }
+            Operation::Eq => self.binary_operation(Value::eq)?,
+            Operation::Ge => self.binary_operation(Value::ge)?,
+            Operation::Gt => self.binary_operation(Value::gt)?,
+            Operation::Le => self.binary_operation(Value::le)?,
+            Operation::Lt => self.binary_operation(Value::lt)?,
+            Operation::Ne => self.binary_operation(Value::ne)?,
 
             Operation::Skip { ref target } => {
                 self.pc = target.clone();
             }
 
             Operation::Literal { value } => {
-                self.push(value);
+                self.push(value)?;
             }
 
             Operation::RegisterOffset { register, offset } => {
                 return Ok(OperationEvaluationResult::AwaitingRegister {
                     register: register,
                     offset: offset as u64,
+                    value_type: ValueType::Generic,
                 });
             }
 
-            Operation::FrameOffset { offset } => {
+            Operation::FrameOffset { offset } => if let Some(frame_base) = self.frame_base {
+                self.push(frame_base.wrapping_add(offset as u64))?;
+            } else if !self.start_frame_base(offset as u64)? {
                 return Ok(OperationEvaluationResult::AwaitingFrameBase {
                     offset: offset as u64,
                 });
-            }
+            },
 
             Operation::Nop => {}
 
             Operation::PushObjectAddress => if let Some(value) = self.object_address {
-                self.push(value);
+                self.push(value)?;
             } else {
                 return Err(Error::InvalidPushObjectAddress.into());
             },
@@ -1403,8 +1679,12 @@ impl<R: Reader> Evaluation<R> {
             }
 
             Operation::Register { base_type, register } => {
-                if base_type != UnitOffset(R::Offset::from_u64(0).unwrap()) {
-                    return Err(Error::UnsupportedTypedStack);
+                if base_type != generic_type() {
+                    return Ok(OperationEvaluationResult::AwaitingRegister {
+                        register: register,
+                        offset: 0,
+                        value_type: value_type!(base_type),
+                    });
                 }
                 terminated = true;
                 current_location = Location::Register { register: register };
@@ -1419,7 +1699,12 @@ impl<R: Reader> Evaluation<R> {
 
             Operation::StackValue => {
                 terminated = true;
-                current_location = Location::Scalar { value: self.pop()? };
+                current_location = match self.pop_value()? {
+                    Value::Generic(value) => Location::Scalar {
+                        value: value & self.addr_mask,
+                    },
+                    value => Location::Value { value: value },
+                };
             }
 
             Operation::ImplicitPointer { value, byte_offset } => {
@@ -1452,9 +1737,42 @@ impl<R: Reader> Evaluation<R> {
                 piece_end = true;
             }
 
-            Operation::TypedLiteral { .. } | Operation::Convert { .. } |
-            Operation::Reinterpret { .. } => {
-                return Err(Error::UnsupportedTypedStack);
+            Operation::TypedLiteral {
+                base_type,
+                ref value,
+            } => {
+                let value_type = value_type!(base_type);
+                let value = Value::parse(value_type, value.clone())?;
+                self.push_value(value)?;
+            }
+
+            Operation::Convert { base_type } => {
+                let value_type = value_type!(base_type);
+                self.unary_operation(|v, addr_mask| v.convert(value_type, addr_mask))?;
+            }
+
+            Operation::Reinterpret { base_type } => {
+                let value_type = value_type!(base_type);
+                self.unary_operation(|v, addr_mask| v.reinterpret(value_type, addr_mask))?;
+            }
+
+            Operation::AddressIndex { .. } | Operation::ConstantIndex { .. } => {
+                return Err(Error::UnsupportedAddressIndex);
+            }
+
+            Operation::WasmLocal { index } => {
+                terminated = true;
+                current_location = Location::WasmLocal { index: index };
+            }
+
+            Operation::WasmGlobal { index } => {
+                terminated = true;
+                current_location = Location::WasmGlobal { index: index };
+            }
+
+            Operation::WasmStack { index } => {
+                terminated = true;
+                current_location = Location::WasmStack { index: index };
             }
         }
 
@@ -1469,12 +1787,12 @@ impl<R: Reader> Evaluation<R> {
     ///
     /// # Panics
     /// Panics if this `Evaluation` has not been driven to completion.
-    pub fn result(self) -> Vec<Piece<R, R::Offset>> {
+    pub fn as_result(&self) -> &[Piece<R, R::Offset>] {
         match self.state {
-            EvaluationState::Complete => self.result,
-            _ => {
-                panic!("Called `Evaluation::result` on an `Evaluation` that has not been completed")
-            }
+            EvaluationState::Complete => self.result.as_slice(),
+            _ => panic!(
+                "Called `Evaluation::as_result` on an `Evaluation` that has not been completed"
+            ),
         }
     }
 
@@ -1486,8 +1804,12 @@ impl<R: Reader> Evaluation<R> {
     pub fn evaluate(&mut self) -> Result<EvaluationResult<R>, Error> {
         match self.state {
             EvaluationState::Start(initial_value) => {
+                if let Err(e) = self.check_expression_length(&self.bytecode) {
+                    self.state = EvaluationState::Error(e);
+                    return Err(e);
+                }
                 if let Some(value) = initial_value {
-                    self.push(value);
+                    self.push(value)?;
                 }
                 self.state = EvaluationState::Ready;
             }
@@ -1516,8 +1838,10 @@ impl<R: Reader> Evaluation<R> {
     pub fn resume_with_memory(&mut self, value: u64) -> Result<EvaluationResult<R>, Error> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
-            EvaluationState::Waiting(OperationEvaluationResult::AwaitingMemory { .. }) => {
-                self.push(value);
+            EvaluationState::Waiting(
+                OperationEvaluationResult::AwaitingMemory { value_type, .. },
+            ) => {
+                self.push_value(Value::from_u64(value_type, value))?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_memory` without a preceding `EvaluationResult::RequiresMemory`"
@@ -1538,9 +1862,9 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(
-                OperationEvaluationResult::AwaitingRegister { offset, .. },
+                OperationEvaluationResult::AwaitingRegister { offset, value_type, .. },
             ) => {
-                self.push(register.wrapping_add(offset));
+                self.push_value(Value::from_u64(value_type, register.wrapping_add(offset)))?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_register` without a preceding `EvaluationResult::RequiresRegister`"
@@ -1564,7 +1888,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingFrameBase { offset }) => {
-                self.push(frame_base.wrapping_add(offset));
+                self.push(frame_base.wrapping_add(offset))?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_frame_base` without a preceding `EvaluationResult::RequiresFrameBase`"
@@ -1585,7 +1909,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingTls { .. }) => {
-                self.push(value);
+                self.push(value)?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_tls` without a preceding `EvaluationResult::RequiresTls`"
@@ -1606,7 +1930,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingCfa) => {
-                self.push(cfa);
+                self.push(cfa)?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_call_frame_cfa` without a preceding `EvaluationResult::RequiresCallFrameCfa`"
@@ -1623,15 +1947,13 @@ impl<R: Reader> Evaluation<R> {
     ///
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresAtLocation`.
-    pub fn resume_with_at_location(&mut self, mut bytes: R) -> Result<EvaluationResult<R>, Error> {
+    pub fn resume_with_at_location(&mut self, bytes: R) -> Result<EvaluationResult<R>, Error> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingAtLocation { .. }) => {
+                self.check_expression_length(&bytes)?;
                 if !bytes.is_empty() {
-                    let mut pc = bytes.clone();
-                    mem::swap(&mut pc, &mut self.pc);
-                    mem::swap(&mut bytes, &mut self.bytecode);
-                    self.expression_stack.push((pc, bytes));
+                    self.call(bytes)?;
                 }
             }
             _ => panic!(
@@ -1656,7 +1978,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingEntryValue { .. }) => {
-                self.push(entry_value);
+                self.push(entry_value)?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_entry_value` without a preceding `EvaluationResult::RequiresEntryValue`"
@@ -1680,7 +2002,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingParameterRef { .. }) => {
-                self.push(parameter_value);
+                self.push(parameter_value)?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_parameter_ref` without a preceding `EvaluationResult::RequiresParameterRef`"
@@ -1701,7 +2023,7 @@ impl<R: Reader> Evaluation<R> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(OperationEvaluationResult::AwaitingTextBase { offset }) => {
-                self.push(text_base.wrapping_add(offset));
+                self.push(text_base.wrapping_add(offset))?;
             }
             _ => panic!(
                 "Called `Evaluation::resume_with_text_base` without a preceding `EvaluationResult::RequiresTextBase`"
@@ -1711,6 +2033,120 @@ impl<R: Reader> Evaluation<R> {
         self.evaluate_internal()
     }
 
+    /// Resume the `Evaluation` with the provided `value_type` for the base
+    /// type DIE.  This will continue evaluating opcodes until the evaluation
+    /// is completed, reaches an error, or needs more information again.
+    ///
+    /// # Panics
+    /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresBaseType`.
+    pub fn resume_with_base_type(
+        &mut self,
+        value_type: ValueType,
+    ) -> Result<EvaluationResult<R>, Error> {
+        match self.state {
+            EvaluationState::Error(err) => return Err(err),
+            EvaluationState::Waiting(OperationEvaluationResult::AwaitingBaseType { base_type }) => {
+                if let Err(entry) = self.base_types.try_push((base_type, value_type)) {
+                    // The storage is full, so evict an entry to make room.
+                    self.base_types.swap_remove(0);
+                    let _ = self.base_types.try_push(entry);
+                }
+            }
+            _ => panic!(
+                "Called `Evaluation::resume_with_base_type` without a preceding `EvaluationResult::RequiresBaseType`"
+            ),
+        };
+
+        self.evaluate_internal()
+    }
+
+    /// Resume the `Evaluation` after the caller has performed a vendor
+    /// operation.  `operands_len` is the number of bytes of operands that the
+    /// operation consumed, and `value`, if given, is pushed on the stack.
+    /// This will continue evaluating opcodes until the evaluation is
+    /// completed, reaches an error, or needs more information again.
+    ///
+    /// # Panics
+    /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresVendorOperation`.
+    pub fn resume_with_vendor_operation(
+        &mut self,
+        operands_len: R::Offset,
+        value: Option<u64>,
+    ) -> Result<EvaluationResult<R>, Error> {
+        match self.state {
+            EvaluationState::Error(err) => return Err(err),
+            EvaluationState::Waiting(OperationEvaluationResult::AwaitingVendorOperation) => {
+                self.pc.skip(operands_len)?;
+                if let Some(value) = value {
+                    self.push(value)?;
+                }
+            }
+            _ => panic!(
+                "Called `Evaluation::resume_with_vendor_operation` without a preceding `EvaluationResult::RequiresVendorOperation`"
+            ),
+        };
+
+        self.evaluate_internal()
+    }
+
+    fn call(&mut self, mut bytes: R) -> Result<(), Error> {
+        let mut pc = bytes.clone();
+        mem::swap(&mut pc, &mut self.pc);
+        mem::swap(&mut bytes, &mut self.bytecode);
+        self.expression_stack
+            .try_push((pc, bytes))
+            .map_err(|_| Error::EvaluationStorageFull)
+    }
+
+    fn return_to(&mut self, depth: usize) {
+        while self.expression_stack.as_slice().len() > depth {
+            let (pc, bytes) = self.expression_stack.pop().unwrap();
+            self.pc = pc;
+            self.bytecode = bytes;
+        }
+    }
+
+    fn start_frame_base(&mut self, offset: u64) -> Result<bool, Error> {
+        if self.frame_base_evaluation.is_some() {
+            // The frame base can't depend on itself.
+            return Err(Error::InvalidFrameBase);
+        }
+        let expression = match self.frame_base_expression {
+            Some(ref expression) => expression.clone(),
+            None => return Ok(false),
+        };
+        self.check_expression_length(&expression)?;
+        if expression.is_empty() {
+            return Err(Error::InvalidFrameBase);
+        }
+        self.frame_base_evaluation = Some(FrameBaseEvaluation {
+            offset: offset,
+            stack_len: self.stack.as_slice().len(),
+            expression_stack_len: self.expression_stack.as_slice().len(),
+        });
+        self.call(expression)?;
+        Ok(true)
+    }
+
+    fn end_frame_base(&mut self) -> Result<(), Error> {
+        let frame_base_evaluation = match self.frame_base_evaluation {
+            Some(frame_base_evaluation)
+                if frame_base_evaluation.expression_stack_len ==
+                    self.expression_stack.as_slice().len() =>
+            {
+                frame_base_evaluation
+            }
+            _ => return Ok(()),
+        };
+        self.frame_base_evaluation = None;
+        if self.stack.as_slice().len() != frame_base_evaluation.stack_len + 1 {
+            return Err(Error::InvalidFrameBase);
+        }
+        let frame_base = self.pop()?;
+        self.frame_base = Some(frame_base);
+        self.push(frame_base.wrapping_add(frame_base_evaluation.offset))
+    }
+
     fn evaluate_internal(&mut self) -> Result<EvaluationResult<R>, Error> {
         'eval: loop {
             while self.pc.is_empty() {
@@ -1718,6 +2154,7 @@ impl<R: Reader> Evaluation<R> {
                     Some((newpc, newbytes)) => {
                         self.pc = newpc;
                         self.bytecode = newbytes;
+                        self.end_frame_base()?;
                     }
                     None => break 'eval,
                 }
@@ -1730,10 +2167,51 @@ impl<R: Reader> Evaluation<R> {
                 }
             }
 
-            let operation =
-                Operation::parse(&mut self.pc, &self.bytecode, self.address_size, self.format)?;
+            let pc = self.pc.clone();
+            let result =
+                Operation::parse(&mut self.pc, &self.bytecode, self.address_size, self.format);
+            let operation = match result {
+                Ok(operation) => operation,
+                Err(Error::InvalidExpression(opcode)) if opcode.0 >= constants::DW_OP_lo_user.0 => {
+                    let waiting = OperationEvaluationResult::AwaitingVendorOperation;
+                    self.state = EvaluationState::Waiting(waiting);
+                    return Ok(EvaluationResult::RequiresVendorOperation {
+                        opcode: opcode,
+                        operands: self.pc.clone(),
+                    });
+                }
+                Err(e) => return Err(e),
+            };
 
             let op_result = self.evaluate_one_operation(&operation)?;
+            if let Some(frame_base_evaluation) = self.frame_base_evaluation {
+                if let OperationEvaluationResult::Complete {
+                    terminated,
+                    piece_end,
+                    ref current_location,
+                } = op_result
+                {
+                    // A frame base expression may be a register location
+                    // description, in which case the frame base is the value
+                    // of the register.  Other location descriptions are
+                    // invalid.
+                    match *current_location {
+                        Location::Register { register } if self.pc.is_empty() => {
+                            self.frame_base_evaluation = None;
+                            self.return_to(frame_base_evaluation.expression_stack_len);
+                            let op_result = OperationEvaluationResult::AwaitingRegister {
+                                register: register,
+                                offset: frame_base_evaluation.offset,
+                                value_type: ValueType::Generic,
+                            };
+                            self.state = EvaluationState::Waiting(op_result);
+                            return Ok(EvaluationResult::RequiresRegister(register));
+                        }
+                        _ if terminated || piece_end => return Err(Error::InvalidFrameBase),
+                        _ => {}
+                    }
+                }
+            }
             match op_result {
                 OperationEvaluationResult::Complete {
                     terminated,
@@ -1752,7 +2230,7 @@ impl<R: Reader> Evaluation<R> {
                             // terminating the expression.  This means the
                             // result is the address on the stack.
                             assert!(current_location.is_empty());
-                            if !self.stack.is_empty() {
+                            if !self.stack.as_slice().is_empty() {
                                 current_location = Location::Address {
                                     address: self.pop()?,
                                 };
@@ -1767,28 +2245,28 @@ impl<R: Reader> Evaluation<R> {
                         }
                         match pieceop {
                             _ if eof => {
-                                if !self.result.is_empty() {
+                                if !self.result.as_slice().is_empty() {
                                     // We saw a piece earlier and then some
                                     // unterminated piece.  It's not clear this is
                                     // well-defined.
                                     return Err(Error::InvalidPiece.into());
                                 }
-                                self.result.push(Piece {
+                                self.push_piece(Piece {
                                     size_in_bits: None,
                                     bit_offset: None,
                                     location: current_location,
-                                });
+                                })?;
                             }
 
                             Operation::Piece {
                                 size_in_bits,
                                 bit_offset,
                             } => {
-                                self.result.push(Piece {
+                                self.push_piece(Piece {
                                     size_in_bits: Some(size_in_bits),
                                     bit_offset: bit_offset,
                                     location: current_location,
-                                });
+                                })?;
                             }
 
                             _ => {
@@ -1803,6 +2281,7 @@ impl<R: Reader> Evaluation<R> {
                     address,
                     size,
                     space,
+                    ..
                 } => {
                     self.state = EvaluationState::Waiting(op_result);
                     return Ok(EvaluationResult::RequiresMemory {
@@ -1815,6 +2294,12 @@ impl<R: Reader> Evaluation<R> {
                     self.state = EvaluationState::Waiting(op_result);
                     return Ok(EvaluationResult::RequiresRegister(register));
                 }
+                OperationEvaluationResult::AwaitingBaseType { base_type } => {
+                    // The operation is evaluated again once the type is known.
+                    self.pc = pc;
+                    self.state = EvaluationState::Waiting(op_result);
+                    return Ok(EvaluationResult::RequiresBaseType(base_type));
+                }
                 OperationEvaluationResult::AwaitingFrameBase { .. } => {
                     self.state = EvaluationState::Waiting(op_result);
                     return Ok(EvaluationResult::RequiresFrameBase);
@@ -1843,18 +2328,26 @@ impl<R: Reader> Evaluation<R> {
                     self.state = EvaluationState::Waiting(op_result);
                     return Ok(EvaluationResult::RequiresTextBase);
                 }
+                OperationEvaluationResult::AwaitingVendorOperation => unreachable!(),
             };
         }
 
         // If no pieces have been seen, use the stack top as the
-        // result.
-        if self.result.is_empty() {
-            let addr = self.pop()?;
-            self.result.push(Piece {
+        // result.  An expression with no operations at all is an empty
+        // location description, meaning the object is not present.
+        if self.result.as_slice().is_empty() {
+            let location = if self.bytecode.is_empty() && self.stack.as_slice().is_empty() {
+                Location::Empty
+            } else {
+                Location::Address {
+                    address: self.pop()?,
+                }
+            };
+            self.push_piece(Piece {
                 size_in_bits: None,
                 bit_offset: None,
-                location: Location::Address { address: addr },
-            });
+                location: location,
+            })?;
         }
 
         self.state = EvaluationState::Complete;
@@ -1876,6 +2369,7 @@ mod tests {
     use std::usize;
     use test_util::GimliSectionMethods;
     use unit::{DebugInfoOffset, UnitOffset};
+    use reader::ReaderOffsetId;
 
     #[test]
     fn test_compute_pc() {
@@ -1930,6 +2424,18 @@ mod tests {
         }
     }
 
+    fn check_op_parse_eof(input: &[u8], address_size: u8, format: Format) {
+        let buf = EndianBuf::new(input, LittleEndian);
+        let mut pc = buf;
+        match Operation::parse(&mut pc, &buf, address_size, format) {
+            Err(Error::UnexpectedEof(id)) => {
+                assert!(buf.lookup_offset_id(id).is_some());
+            }
+
+            _ => panic!("Unexpected result"),
+        }
+    }
+
     fn check_op_parse<F>(
         input: F,
         expect: &Operation<EndianBuf<LittleEndian>>,
@@ -1942,7 +2448,7 @@ mod tests {
             .get_contents()
             .unwrap();
         for i in 1..input.len() {
-            check_op_parse_failure(&input[..i], Error::UnexpectedEof, address_size, format);
+            check_op_parse_eof(&input[..i], address_size, format);
         }
         check_op_parse_simple(&input, expect, address_size, format);
     }
@@ -2028,38 +2534,38 @@ mod tests {
             (constants::DW_OP_lit29, Operation::Literal { value: 29 }),
             (constants::DW_OP_lit30, Operation::Literal { value: 30 }),
             (constants::DW_OP_lit31, Operation::Literal { value: 31 }),
-            (constants::DW_OP_reg0, generic_register(0)),
-            (constants::DW_OP_reg1, generic_register(1)),
-            (constants::DW_OP_reg2, generic_register(2)),
-            (constants::DW_OP_reg3, generic_register(3)),
-            (constants::DW_OP_reg4, generic_register(4)),
-            (constants::DW_OP_reg5, generic_register(5)),
-            (constants::DW_OP_reg6, generic_register(6)),
-            (constants::DW_OP_reg7, generic_register(7)),
-            (constants::DW_OP_reg8, generic_register(8)),
-            (constants::DW_OP_reg9, generic_register(9)),
-            (constants::DW_OP_reg10, generic_register(10)),
-            (constants::DW_OP_reg11, generic_register(11)),
-            (constants::DW_OP_reg12, generic_register(12)),
-            (constants::DW_OP_reg13, generic_register(13)),
-            (constants::DW_OP_reg14, generic_register(14)),
-            (constants::DW_OP_reg15, generic_register(15)),
-            (constants::DW_OP_reg16, generic_register(16)),
-            (constants::DW_OP_reg17, generic_register(17)),
-            (constants::DW_OP_reg18, generic_register(18)),
-            (constants::DW_OP_reg19, generic_register(19)),
-            (constants::DW_OP_reg20, generic_register(20)),
-            (constants::DW_OP_reg21, generic_register(21)),
-            (constants::DW_OP_reg22, generic_register(22)),
-            (constants::DW_OP_reg23, generic_register(23)),
-            (constants::DW_OP_reg24, generic_register(24)),
-            (constants::DW_OP_reg25, generic_register(25)),
-            (constants::DW_OP_reg26, generic_register(26)),
-            (constants::DW_OP_reg27, generic_register(27)),
-            (constants::DW_OP_reg28, generic_register(28)),
-            (constants::DW_OP_reg29, generic_register(29)),
-            (constants::DW_OP_reg30, generic_register(30)),
-            (constants::DW_OP_reg31, generic_register(31)),
+            (constants::DW_OP_reg0, generic_register(Register(0))),
+            (constants::DW_OP_reg1, generic_register(Register(1))),
+            (constants::DW_OP_reg2, generic_register(Register(2))),
+            (constants::DW_OP_reg3, generic_register(Register(3))),
+            (constants::DW_OP_reg4, generic_register(Register(4))),
+            (constants::DW_OP_reg5, generic_register(Register(5))),
+            (constants::DW_OP_reg6, generic_register(Register(6))),
+            (constants::DW_OP_reg7, generic_register(Register(7))),
+            (constants::DW_OP_reg8, generic_register(Register(8))),
+            (constants::DW_OP_reg9, generic_register(Register(9))),
+            (constants::DW_OP_reg10, generic_register(Register(10))),
+            (constants::DW_OP_reg11, generic_register(Register(11))),
+            (constants::DW_OP_reg12, generic_register(Register(12))),
+            (constants::DW_OP_reg13, generic_register(Register(13))),
+            (constants::DW_OP_reg14, generic_register(Register(14))),
+            (constants::DW_OP_reg15, generic_register(Register(15))),
+            (constants::DW_OP_reg16, generic_register(Register(16))),
+            (constants::DW_OP_reg17, generic_register(Register(17))),
+            (constants::DW_OP_reg18, generic_register(Register(18))),
+            (constants::DW_OP_reg19, generic_register(Register(19))),
+            (constants::DW_OP_reg20, generic_register(Register(20))),
+            (constants::DW_OP_reg21, generic_register(Register(21))),
+            (constants::DW_OP_reg22, generic_register(Register(22))),
+            (constants::DW_OP_reg23, generic_register(Register(23))),
+            (constants::DW_OP_reg24, generic_register(Register(24))),
+            (constants::DW_OP_reg25, generic_register(Register(25))),
+            (constants::DW_OP_reg26, generic_register(Register(26))),
+            (constants::DW_OP_reg27, generic_register(Register(27))),
+            (constants::DW_OP_reg28, generic_register(Register(28))),
+            (constants::DW_OP_reg29, generic_register(Register(29))),
+            (constants::DW_OP_reg30, generic_register(Register(30))),
+            (constants::DW_OP_reg31, generic_register(Register(31))),
             (constants::DW_OP_nop, Operation::Nop),
             (
                 constants::DW_OP_push_object_address,
@@ -2072,7 +2578,7 @@ mod tests {
         ];
 
         let input = [];
-        check_op_parse_failure(&input[..], Error::UnexpectedEof, address_size, format);
+        check_op_parse_eof(&input[..], address_size, format);
 
         for item in inputs.iter() {
             let (opcode, ref result) = *item;
@@ -2341,7 +2847,7 @@ mod tests {
                 inputs.push((
                     constants::DW_OP_breg0.0 + i,
                     Operation::RegisterOffset {
-                        register: i as u64,
+                        register: Register(u16::from(i)),
                         offset: *value,
                     },
                 ));
@@ -2371,12 +2877,18 @@ mod tests {
                     constants::DW_OP_plus_uconst,
                     Operation::PlusConstant { value: *value },
                 ),
-                (
-                    constants::DW_OP_regx,
-                    Operation::Register { base_type: generic_type(), register: *value },
-                ),
             ];
 
+            if *value <= 0xffff {
+                inputs.push((
+                    constants::DW_OP_regx,
+                    Operation::Register {
+                        base_type: generic_type(),
+                        register: Register(*value as u16),
+                    },
+                ));
+            }
+
             // FIXME
             if *value < !0u64 / 8 {
                 inputs.push((
@@ -2421,10 +2933,20 @@ mod tests {
 
         for v1 in uvalues.iter() {
             for v2 in svalues.iter() {
+                if *v1 > 0xffff {
+                    let input = Section::with_endian(Endian::Little)
+                        .D8(constants::DW_OP_bregx.0)
+                        .uleb(*v1)
+                        .sleb(*v2)
+                        .get_contents()
+                        .unwrap();
+                    check_op_parse_failure(&input, Error::CannotFitInU16, address_size, format);
+                    continue;
+                }
                 check_op_parse(
                     |s| s.D8(constants::DW_OP_bregx.0).uleb(*v1).sleb(*v2),
                     &Operation::RegisterOffset {
-                        register: *v1,
+                        register: Register(*v1 as u16),
                         offset: *v2,
                     },
                     address_size,
@@ -2537,7 +3059,7 @@ mod tests {
             },
             &Operation::Register {
                 base_type: UnitOffset(100),
-                register: 1,
+                register: Register(1),
             },
             address_size,
             format,
@@ -2550,7 +3072,7 @@ mod tests {
             },
             &Operation::Register {
                 base_type: UnitOffset(100),
-                register: 1,
+                register: Register(1),
             },
             address_size,
             format,
@@ -2711,15 +3233,72 @@ mod tests {
         )
     }
 
-    enum AssemblerEntry {
-        Op(constants::DwOp),
-        Mark(u8),
-        Branch(u8),
-        U8(u8),
-        U16(u16),
-        U32(u32),
-        U64(u64),
-        Uleb(u64),
+    #[test]
+    fn test_op_parse_wasm_location() {
+        let inputs = [
+            (0x0, Operation::WasmLocal { index: 1000 }),
+            (0x1, Operation::WasmGlobal { index: 1000 }),
+            (0x2, Operation::WasmStack { index: 1000 }),
+        ];
+
+        for &(kind, ref op) in &inputs {
+            check_op_parse(
+                |s| s.D8(constants::DW_OP_WASM_location.0).D8(kind).uleb(1000),
+                op,
+                4,
+                Format::Dwarf32,
+            );
+        }
+
+        check_op_parse(
+            |s| s.D8(constants::DW_OP_WASM_location.0).D8(0x3).D32(0x12345678),
+            &Operation::WasmGlobal { index: 0x12345678 },
+            4,
+            Format::Dwarf32,
+        );
+
+        check_op_parse_failure(
+            &[constants::DW_OP_WASM_location.0, 0x4, 0x0],
+            Error::InvalidExpression(constants::DW_OP_WASM_location),
+            4,
+            Format::Dwarf32,
+        );
+    }
+
+    #[test]
+    fn test_op_parse_address_index() {
+        let inputs = [
+            (constants::DW_OP_addrx, true),
+            (constants::DW_OP_GNU_addr_index, true),
+            (constants::DW_OP_constx, false),
+            (constants::DW_OP_GNU_const_index, false),
+        ];
+
+        for &(opcode, is_address) in &inputs {
+            let index = AddressIndex(0x1234);
+            let op = if is_address {
+                Operation::AddressIndex { index: index }
+            } else {
+                Operation::ConstantIndex { index: index }
+            };
+            check_op_parse(
+                |s| s.D8(opcode.0).uleb(0x1234),
+                &op,
+                4,
+                Format::Dwarf32,
+            );
+        }
+    }
+
+    enum AssemblerEntry {
+        Op(constants::DwOp),
+        Mark(u8),
+        Branch(u8),
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        Uleb(u64),
         Sleb(u64),
     }
 
@@ -2829,6 +3408,11 @@ mod tests {
                     assert_eq!(vec[i], pieces[i]);
                 }
             }
+            // The offset id depends on the address of the assembled program,
+            // so only check that it is within the program.
+            (Err(Error::UnexpectedEof(id)), Err(Error::UnexpectedEof(_))) => {
+                assert!(bytes.lookup_offset_id(id).is_some());
+            }
             (Err(f1), Err(f2)) => {
                 assert_eq!(f1, f2);
             }
@@ -3221,8 +3805,8 @@ mod tests {
         }
 
         program.push(Op(DW_OP_bregx));
-        program.push(Uleb(0x123456));
-        program.push(Sleb(0x123456));
+        program.push(Uleb(0x1234));
+        program.push(Sleb(0x1234));
         program.push(Op(DW_OP_plus));
 
         program.push(Op(DW_OP_stack_value));
@@ -3239,7 +3823,7 @@ mod tests {
                                  while result != EvaluationResult::Complete {
                                      result = eval.resume_with_register(match result {
                                          EvaluationResult::RequiresRegister(regno) => {
-                                             regno.wrapping_neg()
+                                             u64::from(regno.0).wrapping_neg()
                                          },
                                          _ => panic!(),
                                      })?;
@@ -3360,7 +3944,7 @@ mod tests {
             let ok_result = [
                 Piece { size_in_bits: None,
                         bit_offset: None,
-                        location: Location::Register{register: i as u64},
+                        location: Location::Register{register: Register(u16::from(i))},
                 },
             ];
 
@@ -3371,13 +3955,13 @@ mod tests {
         }
 
         let program = [
-            Op(DW_OP_regx), Uleb(0x11223344)
+            Op(DW_OP_regx), Uleb(0x1234)
         ];
 
         let result = [
             Piece { size_in_bits: None,
                     bit_offset: None,
-                    location: Location::Register{register: 0x11223344},
+                    location: Location::Register{register: Register(0x1234)},
             },
         ];
 
@@ -3445,6 +4029,38 @@ mod tests {
                                  eval.resume_with_entry_value(entry_value)
                              });
 
+        // Test the GNU form, with a nested register expression as commonly
+        // produced by GCC for parameters in callee-clobbered registers.
+        let program = [
+            Op(DW_OP_GNU_entry_value), Uleb(1), Op(DW_OP_reg5),
+            Op(DW_OP_lit1),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value)
+        ];
+
+        let result = [
+            Piece { size_in_bits: None,
+                    bit_offset: None,
+                    location: Location::Scalar{value: 0x101},
+            },
+        ];
+
+        check_eval_with_args(&program, Ok(&result), 8, Format::Dwarf64,
+                             None, None, None, |eval, result| {
+                                 let entry_value = match result {
+                                     EvaluationResult::RequiresEntryValue(expression) => {
+                                         let mut inner = expression.evaluation(8, Format::Dwarf64);
+                                         assert_eq!(inner.evaluate()?, EvaluationResult::Complete);
+                                         match inner.result()[0].location {
+                                             Location::Register { register: Register(5) } => 0x100,
+                                             _ => panic!(),
+                                         }
+                                     },
+                                     _ => panic!(),
+                                 };
+                                 eval.resume_with_entry_value(entry_value)
+                             });
+
         // Test missing `object_address` field.
         let program = [
             Op(DW_OP_push_object_address),
@@ -3484,6 +4100,235 @@ mod tests {
                              None, Some(0x12345678), None, |_, result| Ok(result));
     }
 
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_eval_frame_base() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        let program = assemble(&[
+            Op(DW_OP_fbreg), Sleb((-8i8) as u64),
+            Op(DW_OP_fbreg), Sleb(8),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value),
+        ]);
+        let program = EndianBuf::new(&program, LittleEndian);
+        let scalar = |value| vec![
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Scalar { value } },
+        ];
+
+        // A known frame base is used directly.
+        let mut eval = Evaluation::new(program, 8, Format::Dwarf64);
+        eval.set_frame_base(0x1000);
+        assert_eq!(eval.evaluate(), Ok(EvaluationResult::Complete));
+        assert_eq!(eval.result(), scalar(0x2000));
+
+        // A frame base expression producing an address is evaluated once.
+        let frame_base = assemble(&[
+            Op(DW_OP_breg6), Sleb(0x10),
+        ]);
+        let mut eval = Evaluation::new(program, 8, Format::Dwarf64);
+        eval.set_frame_base_expression(Expression(EndianBuf::new(&frame_base, LittleEndian)));
+        assert_eq!(eval.evaluate(), Ok(EvaluationResult::RequiresRegister(Register(6))));
+        assert_eq!(eval.resume_with_register(0x1000), Ok(EvaluationResult::Complete));
+        assert_eq!(eval.result(), scalar(0x2020));
+
+        // A frame base expression producing a register uses its value.
+        let frame_base = assemble(&[
+            Op(DW_OP_reg6),
+        ]);
+        let mut eval = Evaluation::new(program, 8, Format::Dwarf64);
+        eval.set_frame_base_expression(Expression(EndianBuf::new(&frame_base, LittleEndian)));
+        assert_eq!(eval.evaluate(), Ok(EvaluationResult::RequiresRegister(Register(6))));
+        assert_eq!(
+            eval.resume_with_register(0x1000),
+            Ok(EvaluationResult::RequiresRegister(Register(6)))
+        );
+        assert_eq!(eval.resume_with_register(0x1000), Ok(EvaluationResult::Complete));
+        assert_eq!(eval.result(), scalar(0x2000));
+
+        // Requests from the frame base expression are forwarded.
+        let frame_base = assemble(&[
+            Op(DW_OP_call_frame_cfa),
+        ]);
+        let mut eval = Evaluation::new(program, 8, Format::Dwarf64);
+        eval.set_frame_base_expression(Expression(EndianBuf::new(&frame_base, LittleEndian)));
+        assert_eq!(eval.evaluate(), Ok(EvaluationResult::RequiresCallFrameCfa));
+        assert_eq!(eval.resume_with_call_frame_cfa(0x3000), Ok(EvaluationResult::Complete));
+        assert_eq!(eval.result(), scalar(0x6000));
+
+        // The frame base expression can't use the frame base, or produce a
+        // value.
+        for frame_base in &[
+            assemble(&[Op(DW_OP_fbreg), Sleb(0)]),
+            assemble(&[Op(DW_OP_lit1), Op(DW_OP_stack_value)]),
+        ] {
+            let mut eval = Evaluation::new(program, 8, Format::Dwarf64);
+            eval.set_frame_base_expression(Expression(EndianBuf::new(frame_base, LittleEndian)));
+            assert_eq!(eval.evaluate(), Err(Error::InvalidFrameBase));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_eval_typed_stack() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        // Add a float register to a float constant, and convert the sum to
+        // a signed integer.
+        let program = [
+            Op(DW_OP_const_type), Uleb(0x10), U8(4), U32(0x3fc0_0000),
+            Op(DW_OP_regval_type), Uleb(17), Uleb(0x10),
+            Op(DW_OP_plus),
+            Op(DW_OP_convert), Uleb(0x20),
+            Op(DW_OP_stack_value),
+        ];
+
+        let result = [
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Value { value: Value::I32(4) } },
+        ];
+
+        check_eval_with_args(&program, Ok(&result), 4, Format::Dwarf32,
+                             None, None, None, |eval, result| {
+                                 assert_eq!(result, EvaluationResult::RequiresBaseType(UnitOffset(0x10)));
+                                 // The type is only requested once.
+                                 assert_eq!(eval.resume_with_base_type(ValueType::F32)?,
+                                            EvaluationResult::RequiresRegister(Register(17)));
+                                 let register = u64::from(2.5f32.to_bits());
+                                 assert_eq!(eval.resume_with_register(register)?,
+                                            EvaluationResult::RequiresBaseType(UnitOffset(0x20)));
+                                 eval.resume_with_base_type(ValueType::I32)
+                             });
+
+        // Each base type is only requested once, even when several are used.
+        let program = [
+            Op(DW_OP_const_type), Uleb(0x10), U8(4), U32(1),
+            Op(DW_OP_convert), Uleb(0x20),
+            Op(DW_OP_convert), Uleb(0x10),
+            Op(DW_OP_stack_value),
+        ];
+
+        let result = [
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Value { value: Value::U32(1) } },
+        ];
+
+        check_eval_with_args(&program, Ok(&result), 4, Format::Dwarf32,
+                             None, None, None, |eval, result| {
+                                 assert_eq!(result, EvaluationResult::RequiresBaseType(UnitOffset(0x10)));
+                                 assert_eq!(eval.resume_with_base_type(ValueType::U32)?,
+                                            EvaluationResult::RequiresBaseType(UnitOffset(0x20)));
+                                 eval.resume_with_base_type(ValueType::U64)
+                             });
+
+        // Dereference a typed value, and reinterpret it as the generic type.
+        let program = [
+            Op(DW_OP_lit8),
+            Op(DW_OP_deref_type), U8(2), Uleb(0x10),
+            Op(DW_OP_const_type), Uleb(0x10), U8(2), U16(0x8000),
+            Op(DW_OP_or),
+            Op(DW_OP_convert), Uleb(0),
+            Op(DW_OP_stack_value),
+        ];
+
+        let result = [
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Scalar { value: 0xffff_8001 } },
+        ];
+
+        check_eval_with_args(&program, Ok(&result), 4, Format::Dwarf32,
+                             None, None, None, |eval, result| {
+                                 assert_eq!(result, EvaluationResult::RequiresBaseType(UnitOffset(0x10)));
+                                 assert_eq!(eval.resume_with_base_type(ValueType::I16)?,
+                                            EvaluationResult::RequiresMemory {
+                                                address: 8, size: 2, space: None,
+                                            });
+                                 eval.resume_with_memory(1)
+                             });
+
+        // Operands must have the same type.
+        let program = [
+            Op(DW_OP_const_type), Uleb(0x10), U8(2), U16(1),
+            Op(DW_OP_lit1),
+            Op(DW_OP_plus),
+        ];
+
+        check_eval_with_args(&program, Err(Error::TypeMismatch), 4, Format::Dwarf32,
+                             None, None, None, |eval, _| {
+                                 eval.resume_with_base_type(ValueType::U16)
+                             });
+
+        // Floating point values can't be used as addresses.
+        let program = [
+            Op(DW_OP_const_type), Uleb(0x10), U8(4), U32(0),
+            Op(DW_OP_deref),
+        ];
+
+        check_eval_with_args(&program, Err(Error::IntegralTypeRequired), 4, Format::Dwarf32,
+                             None, None, None, |eval, _| {
+                                 eval.resume_with_base_type(ValueType::F32)
+                             });
+    }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_eval_store_on_stack() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        let evaluate = |program: &[AssemblerEntry]| {
+            let program = assemble(program);
+            let program = EndianBuf::new(&program, LittleEndian);
+            let mut eval = Evaluation::<_, StoreOnStack>::new_in(program, 4, Format::Dwarf32);
+            eval.evaluate().map(|result| {
+                assert_eq!(result, EvaluationResult::Complete);
+                eval.as_result().len()
+            })
+        };
+
+        let program = assemble(&[
+            Op(DW_OP_lit1),
+            Op(DW_OP_lit2),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value),
+        ]);
+        let program = EndianBuf::new(&program, LittleEndian);
+        let mut eval = Evaluation::<_, StoreOnStack>::new_in(program, 4, Format::Dwarf32);
+        assert_eq!(eval.evaluate(), Ok(EvaluationResult::Complete));
+        assert_eq!(eval.as_result(), &[
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Scalar { value: 3 } },
+        ]);
+
+        // The stack is full.
+        let mut program = Vec::new();
+        for _ in 0..MAX_EVALUATION_STACK_DEPTH {
+            program.push(Op(DW_OP_lit0));
+        }
+        assert_eq!(evaluate(&program), Ok(1));
+        program.push(Op(DW_OP_lit0));
+        assert_eq!(evaluate(&program), Err(Error::EvaluationStorageFull));
+
+        // The result is full.
+        let mut program = Vec::new();
+        for _ in 0..MAX_RESULT_PIECES + 1 {
+            program.push(Op(DW_OP_reg0));
+            program.push(Op(DW_OP_piece));
+            program.push(Uleb(4));
+        }
+        assert_eq!(evaluate(&program[3..]), Ok(MAX_RESULT_PIECES));
+        assert_eq!(evaluate(&program), Err(Error::EvaluationStorageFull));
+    }
+
     #[test]
     #[cfg_attr(rustfmt, rustfmt_skip)]
     fn test_eval_empty_stack() {
@@ -3601,9 +4446,9 @@ mod tests {
 
         let result = [
             Piece { size_in_bits: Some(32), bit_offset: None,
-                    location: Location::Register { register: 3 } },
+                    location: Location::Register { register: Register(3) } },
             Piece { size_in_bits: Some(16), bit_offset: None,
-                    location: Location::Register { register: 4 } },
+                    location: Location::Register { register: Register(4) } },
         ];
 
         check_eval(&program, Ok(&result), 4, Format::Dwarf32);
@@ -3620,7 +4465,7 @@ mod tests {
 
         let result = [
             Piece { size_in_bits: Some(32), bit_offset: None,
-                    location: Location::Register { register: 0 } },
+                    location: Location::Register { register: Register(0) } },
             Piece { size_in_bits: Some(32), bit_offset: None,
                     location: Location::Empty },
             Piece { size_in_bits: Some(32), bit_offset: None,
@@ -3696,6 +4541,167 @@ mod tests {
         ];
 
         check_eval(&program, Ok(&result), 4, Format::Dwarf32);
+
+        let program = [
+            Op(DW_OP_reg3),
+            Op(DW_OP_piece), Uleb(4),
+            Op(DW_OP_implicit_pointer), U32(0x12345678), Sleb(-8i64 as u64),
+            Op(DW_OP_piece), Uleb(4),
+            Op(DW_OP_lit2),
+            Op(DW_OP_stack_value),
+            Op(DW_OP_bit_piece), Uleb(3), Uleb(1),
+        ];
+
+        let result = [
+            Piece { size_in_bits: Some(32), bit_offset: None,
+                    location: Location::Register { register: Register(3) } },
+            Piece { size_in_bits: Some(32), bit_offset: None,
+                    location: Location::ImplicitPointer {
+                        value: DebugInfoOffset(0x12345678),
+                        byte_offset: -8,
+                    },
+            },
+            Piece { size_in_bits: Some(3), bit_offset: Some(1),
+                    location: Location::Scalar { value: 2 } },
+        ];
+
+        check_eval(&program, Ok(&result), 4, Format::Dwarf32);
+
+        let program = [
+            Op(DW_OP_WASM_location), U8(0x0), Uleb(2),
+            Op(DW_OP_piece), Uleb(4),
+            Op(DW_OP_WASM_location), U8(0x3), U32(7),
+            Op(DW_OP_piece), Uleb(4),
+            Op(DW_OP_WASM_location), U8(0x2), Uleb(0),
+            Op(DW_OP_piece), Uleb(4),
+        ];
+
+        let result = [
+            Piece { size_in_bits: Some(32), bit_offset: None,
+                    location: Location::WasmLocal { index: 2 } },
+            Piece { size_in_bits: Some(32), bit_offset: None,
+                    location: Location::WasmGlobal { index: 7 } },
+            Piece { size_in_bits: Some(32), bit_offset: None,
+                    location: Location::WasmStack { index: 0 } },
+        ];
+
+        check_eval(&program, Ok(&result), 4, Format::Dwarf32);
+
+        // An empty expression means the object has been optimized away.
+        let result = [
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Empty },
+        ];
+
+        check_eval(&[], Ok(&result), 4, Format::Dwarf32);
+    }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_eval_vendor_operation() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        let program = [
+            Op(DwOp(0xe5)), U16(0x1234),
+            Op(DW_OP_lit1),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value),
+        ];
+
+        let result = [
+            Piece { size_in_bits: None, bit_offset: None,
+                    location: Location::Scalar { value: 0x1235 } },
+        ];
+
+        check_eval_with_args(&program, Ok(&result), 4, Format::Dwarf32,
+                             None, None, None, |eval, result| {
+                                 let value = match result {
+                                     EvaluationResult::RequiresVendorOperation {
+                                         opcode: DwOp(0xe5),
+                                         mut operands,
+                                     } => operands.read_u16()?,
+                                     _ => panic!(),
+                                 };
+                                 eval.resume_with_vendor_operation(2, Some(value as u64))
+                             });
+
+        // Claiming more operand bytes than remain is an error.
+        let program = [
+            Op(DwOp(0xe5)), U8(0),
+        ];
+
+        check_eval_with_args(&program, Err(Error::UnexpectedEof(ReaderOffsetId(0))), 4, Format::Dwarf32,
+                             None, None, None, |eval, _| {
+                                 eval.resume_with_vendor_operation(2, None)
+                             });
+
+        // Unknown opcodes outside the vendor range are still invalid.
+        let program = [
+            Op(DwOp(0x01)),
+        ];
+
+        check_eval(&program, Err(Error::InvalidExpression(DwOp(0x01))), 4, Format::Dwarf32);
+    }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_eval_limits() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        let mut limits = ReaderLimits::default();
+        limits.max_expression_length = Some(2);
+
+        let program = assemble(&[
+            Op(DW_OP_lit1),
+            Op(DW_OP_lit2),
+            Op(DW_OP_plus),
+        ]);
+        let mut eval = Evaluation::new(EndianBuf::new(&program, LittleEndian), 4, Format::Dwarf32);
+        eval.set_limits(&limits);
+        assert_eq!(eval.evaluate(), Err(Error::LimitExceeded));
+
+        let program = assemble(&[
+            Op(DW_OP_call2), U16(0x10),
+        ]);
+        let subroutine = assemble(&[
+            Op(DW_OP_lit1),
+            Op(DW_OP_lit2),
+            Op(DW_OP_plus),
+        ]);
+        limits.max_expression_length = Some(3);
+        let mut eval = Evaluation::new(EndianBuf::new(&program, LittleEndian), 4, Format::Dwarf32);
+        eval.set_limits(&limits);
+        assert_eq!(
+            eval.evaluate(),
+            Ok(EvaluationResult::RequiresAtLocation(DieReference::UnitRef(UnitOffset(0x10))))
+        );
+        assert_eq!(
+            eval.resume_with_at_location(EndianBuf::new(&subroutine, LittleEndian)),
+            Ok(EvaluationResult::Complete)
+        );
+
+        let subroutine = assemble(&[
+            Op(DW_OP_lit1),
+            Op(DW_OP_lit2),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value),
+        ]);
+        let mut eval = Evaluation::new(EndianBuf::new(&program, LittleEndian), 4, Format::Dwarf32);
+        eval.set_limits(&limits);
+        assert_eq!(
+            eval.evaluate(),
+            Ok(EvaluationResult::RequiresAtLocation(DieReference::UnitRef(UnitOffset(0x10))))
+        );
+        assert_eq!(
+            eval.resume_with_at_location(EndianBuf::new(&subroutine, LittleEndian)),
+            Err(Error::LimitExceeded)
+        );
     }
 
     #[test]
@@ -3715,4 +4721,52 @@ mod tests {
                              4, Format::Dwarf32, None, None, Some(150),
                              |_, _| panic!());
     }
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_expression_references() {
+        use constants::*;
+        use self::AssemblerEntry::*;
+
+        let entry = assemble(&[
+            Op(DW_OP_GNU_parameter_ref), U32(0x44),
+        ]);
+        let mut program = vec![
+            Op(DW_OP_lit1),
+            Op(DW_OP_call2), U16(0x11),
+            Op(DW_OP_call_ref), U32(0x22),
+            Op(DW_OP_addrx), Uleb(3),
+            Op(DW_OP_convert), Uleb(0),
+            Op(DW_OP_convert), Uleb(0x33),
+            Op(DW_OP_entry_value), Uleb(entry.len() as u64),
+        ];
+        program.extend(entry.into_iter().map(U8));
+        program.extend(vec![
+            Op(DW_OP_GNU_const_index), Uleb(4),
+            Op(DW_OP_implicit_pointer), U32(0x55), Sleb(0),
+        ]);
+
+        let bytes = assemble(&program);
+        let expression = Expression(EndianBuf::new(&bytes, LittleEndian));
+        let references = expression.references(4, Format::Dwarf32).unwrap();
+        assert_eq!(
+            references,
+            vec![
+                ExpressionReference::Die(DieReference::UnitRef(UnitOffset(0x11))),
+                ExpressionReference::Die(DieReference::DebugInfoRef(DebugInfoOffset(0x22))),
+                ExpressionReference::AddressIndex(AddressIndex(3)),
+                ExpressionReference::Die(DieReference::UnitRef(UnitOffset(0x33))),
+                ExpressionReference::AddressIndex(AddressIndex(4)),
+                ExpressionReference::Die(DieReference::DebugInfoRef(DebugInfoOffset(0x55))),
+                ExpressionReference::Die(DieReference::UnitRef(UnitOffset(0x44))),
+            ]
+        );
+
+        let bytes = assemble(&[Op(DW_OP_call_ref), U16(0x22)]);
+        let expression = Expression(EndianBuf::new(&bytes, LittleEndian));
+        assert_eq!(
+            expression.references(4, Format::Dwarf32),
+            Err(Error::UnexpectedEof(EndianBuf::new(&bytes[1..], LittleEndian).offset_id()))
+        );
+    }
 }
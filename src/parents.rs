@@ -0,0 +1,282 @@
+//! Navigating from entries to their parents, and building qualified names.
+
+use constants;
+use dwarf::{Dwarf, Unit};
+use parser::{Error, Result};
+use reader::{Reader, ReaderOffset};
+use string::String;
+use unit::UnitOffset;
+use vec::Vec;
+
+/// The maximum number of `DW_AT_specification` and `DW_AT_abstract_origin`
+/// links, and enclosing scopes, that are followed when building a qualified
+/// name. This prevents infinite loops for cyclic references.
+const MAX_QUALIFIED_NAME_DEPTH: usize = 64;
+
+/// An index from the offset of each entry in a unit to the offset of its
+/// parent entry.
+///
+/// Entries don't record their parents, so this is built by walking all of the
+/// entries in the unit with `Unit::parents`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitParents<T = usize> {
+    // Sorted by entry offset.
+    entries: Vec<(UnitOffset<T>, Option<UnitOffset<T>>)>,
+}
+
+impl<T: ReaderOffset> UnitParents<T> {
+    /// Return the offset of the parent of the entry at the given offset.
+    ///
+    /// Returns `None` for the root entry of the unit, and for offsets that
+    /// are not the offset of an entry.
+    pub fn parent(&self, offset: UnitOffset<T>) -> Option<UnitOffset<T>> {
+        match self.entries.binary_search_by_key(&offset, |entry| entry.0) {
+            Ok(index) => self.entries[index].1,
+            Err(_) => None,
+        }
+    }
+
+    /// Return the number of entries in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if the index contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<R: Reader> Unit<R> {
+    /// Walk all of the entries in this unit, and return an index of their
+    /// parents.
+    pub fn parents(&self) -> Result<UnitParents<R::Offset>> {
+        let mut entries = Vec::new();
+        let mut stack = Vec::new();
+        let mut cursor = self.entries();
+        while cursor.next_entry()?.is_some() {
+            let offset = match cursor.current() {
+                Some(entry) => entry.offset(),
+                None => continue,
+            };
+            let depth = if cursor.depth() > 0 {
+                cursor.depth() as usize
+            } else {
+                0
+            };
+            stack.truncate(depth);
+            entries.push((offset, stack.last().cloned()));
+            stack.push(offset);
+        }
+        Ok(UnitParents { entries })
+    }
+
+    /// Return the name of the entry at the given offset, qualified by the
+    /// names of the namespaces and types that enclose it, such as
+    /// `std::vector<int>::push_back`.
+    ///
+    /// If the entry has a `DW_AT_specification` or `DW_AT_abstract_origin`
+    /// attribute, then the enclosing scopes of the referenced entry are used
+    /// instead, and its name is used if the entry doesn't have a name. Only
+    /// references to entries within this unit are followed.
+    ///
+    /// Anonymous namespaces are named `(anonymous namespace)`, and other
+    /// anonymous scopes are named `(anonymous)`.
+    ///
+    /// Returns `None` if neither the entry nor any entry that it refers to
+    /// has a name. Returns `Error::LimitExceeded` if the references or
+    /// enclosing scopes are nested too deeply, which may indicate a cycle.
+    pub fn qualified_name(
+        &self,
+        dwarf: &Dwarf<R>,
+        parents: &UnitParents<R::Offset>,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<Option<String>> {
+        self.qualified_name_with_depth(dwarf, parents, offset, 0, false)
+    }
+
+    fn qualified_name_with_depth(
+        &self,
+        dwarf: &Dwarf<R>,
+        parents: &UnitParents<R::Offset>,
+        mut offset: UnitOffset<R::Offset>,
+        mut depth: usize,
+        anonymous: bool,
+    ) -> Result<Option<String>> {
+        // Follow the references to find the name, and the entry that is
+        // nested within the enclosing scopes.
+        let mut name = None;
+        let mut tag = None;
+        loop {
+            depth += 1;
+            if depth > MAX_QUALIFIED_NAME_DEPTH {
+                return Err(Error::LimitExceeded);
+            }
+
+            let mut cursor = self.entries_at_offset(offset)?;
+            cursor.next_entry()?;
+            let entry = cursor.current().ok_or(Error::NoEntryAtGivenOffset)?;
+            if tag.is_none() {
+                tag = Some(entry.tag());
+            }
+            if name.is_none() {
+                if let Some(value) = entry.attr_value(constants::DW_AT_name)? {
+                    name = dwarf.attr_string_lossy(&value)?;
+                }
+            }
+            let reference = match entry.attr_value(constants::DW_AT_specification)? {
+                Some(value) => Some(value),
+                None => entry.attr_value(constants::DW_AT_abstract_origin)?,
+            };
+            match reference.and_then(|value| self.attr_unit_offset(&value)) {
+                Some(reference) => offset = reference,
+                None => break,
+            }
+        }
+        let name = match name {
+            Some(name) => name,
+            None if !anonymous => return Ok(None),
+            None if tag == Some(constants::DW_TAG_namespace) => {
+                String::from("(anonymous namespace)")
+            }
+            None => String::from("(anonymous)"),
+        };
+
+        // Find the nearest enclosing scope.
+        let mut scope = parents.parent(offset);
+        while let Some(scope_offset) = scope {
+            let mut cursor = self.entries_at_offset(scope_offset)?;
+            cursor.next_entry()?;
+            let tag = cursor.current().ok_or(Error::NoEntryAtGivenOffset)?.tag();
+            if is_scope(tag) {
+                break;
+            }
+            scope = parents.parent(scope_offset);
+        }
+
+        let mut qualified_name = String::new();
+        if let Some(scope) = scope {
+            if let Some(scope_name) =
+                self.qualified_name_with_depth(dwarf, parents, scope, depth, true)?
+            {
+                qualified_name.push_str(&scope_name);
+                qualified_name.push_str("::");
+            }
+        }
+        qualified_name.push_str(&name);
+        Ok(Some(qualified_name))
+    }
+}
+
+/// Return true if entries with the given tag contribute to qualified names.
+fn is_scope(tag: constants::DwTag) -> bool {
+    match tag {
+        constants::DW_TAG_namespace
+        | constants::DW_TAG_class_type
+        | constants::DW_TAG_structure_type
+        | constants::DW_TAG_union_type
+        | constants::DW_TAG_enumeration_type
+        | constants::DW_TAG_interface_type => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use SectionId;
+
+    #[test]
+    fn test_qualified_name() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x39, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x13, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x2e, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x05, 0x2e, 0x00,
+            // DW_AT_specification, DW_FORM_ref4.
+            0x47, 0x13,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x06, 0x39, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x07, 0x34, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x1f, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Root entry.
+            0x01,
+            // 0x0c: Namespace "ns".
+            0x02, b'n', b's', 0x00,
+            // 0x10: Structure "S".
+            0x03, b'S', 0x00,
+            // 0x13: Declaration of subprogram "f".
+            0x04, b'f', 0x00,
+            0x00,
+            0x00,
+            // 0x18: Definition of "f".
+            0x05, 0x13, 0x00, 0x00, 0x00,
+            // 0x1d: Anonymous namespace.
+            0x06,
+            // 0x1e: Variable "v".
+            0x07, b'v', 0x00,
+            0x00,
+            0x00,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        let parents = unit.parents().unwrap();
+        assert_eq!(parents.len(), 7);
+        assert_eq!(parents.parent(UnitOffset(0x0b)), None);
+        assert_eq!(parents.parent(UnitOffset(0x13)), Some(UnitOffset(0x10)));
+        assert_eq!(parents.parent(UnitOffset(0x18)), Some(UnitOffset(0x0b)));
+        assert_eq!(parents.parent(UnitOffset(0x1e)), Some(UnitOffset(0x1d)));
+        assert_eq!(parents.parent(UnitOffset(0x14)), None);
+
+        let name = |offset| unit.qualified_name(&dwarf, &parents, UnitOffset(offset));
+        assert_eq!(name(0x0b), Ok(None));
+        assert_eq!(name(0x10), Ok(Some(String::from("ns::S"))));
+        assert_eq!(name(0x13), Ok(Some(String::from("ns::S::f"))));
+        assert_eq!(name(0x18), Ok(Some(String::from("ns::S::f"))));
+        assert_eq!(name(0x1d), Ok(None));
+        assert_eq!(name(0x1e), Ok(Some(String::from("(anonymous namespace)::v"))));
+    }
+}
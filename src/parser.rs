@@ -1,12 +1,14 @@
 //! Functions for parsing DWARF debugging information.
 
-use std::fmt::{self, Debug};
+use std::fmt;
 use std::result;
 #[cfg(feature = "std")]
 use std::{io, error};
+use arch::Register;
 use cfi::BaseAddresses;
 use constants;
-use reader::{Reader, ReaderOffset};
+use reader::{Reader, ReaderOffset, ReaderOffsetId};
+use SectionId;
 
 /// An error that occurred when parsing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,6 +41,9 @@ pub enum Error {
     BadLength,
     /// Found an unknown `DW_FORM_*` type.
     UnknownForm,
+    /// Found a `DW_FORM_indirect` attribute whose actual form is
+    /// `DW_FORM_implicit_const`, or that is nested too deeply.
+    InvalidIndirectForm,
     /// Expected a zero, found something else.
     ExpectedZero,
     /// Found an abbreviation code that has already been used.
@@ -52,7 +57,11 @@ pub enum Error {
     /// Found a record with an unknown abbreviation code.
     UnknownAbbreviation,
     /// Hit the end of input before it was expected.
-    UnexpectedEof,
+    ///
+    /// The `ReaderOffsetId` identifies the reader offset at which this
+    /// occurred, and can be mapped back to a section and offset with
+    /// `Dwarf::lookup_offset_id` or `Section::lookup_offset_id`.
+    UnexpectedEof(ReaderOffsetId),
     /// Read a null entry before it was expected.
     UnexpectedNull,
     /// Found an unknown standard opcode.
@@ -81,6 +90,16 @@ pub enum Error {
     BadBranchTarget(u64),
     /// DW_OP_push_object_address used but no address passed in.
     InvalidPushObjectAddress,
+    /// The operands of a DWARF expression operation have different types.
+    TypeMismatch,
+    /// A DWARF expression operation requires an integral type, but found a
+    /// floating point type.
+    IntegralTypeRequired,
+    /// The storage of an expression evaluation is full.
+    EvaluationStorageFull,
+    /// The `DW_AT_frame_base` expression used by a DW_OP_fbreg did not
+    /// produce a register or an address.
+    InvalidFrameBase,
     /// Not enough items on the stack when evaluating an expression.
     NotEnoughStackItems,
     /// Too many iterations to compute the expression.
@@ -98,6 +117,8 @@ pub enum Error {
     DivisionByZero,
     /// An unknown DW_CFA_* instruction.
     UnknownCallFrameInstruction(constants::DwCfa),
+    /// An unknown DW_DSC_* descriptor in a discriminant list.
+    UnknownDiscriminantDescriptor(constants::DwDsc),
     /// The end of an address range was before the beginning.
     InvalidAddressRange,
     /// The end offset of a loc list entry was before the beginning.
@@ -124,6 +145,8 @@ pub enum Error {
     UnsupportedPointerEncoding,
     /// We tried to convert some number into a `u8`, but it was too large.
     CannotFitInU8,
+    /// We tried to convert some number into a `u16`, but it was too large.
+    CannotFitInU16,
     /// The CFI program defined more register rules than we have storage for.
     TooManyRegisterRules,
     /// Attempted to push onto the CFI stack, but it was already at full
@@ -138,14 +161,84 @@ pub enum Error {
     UnsupportedAddressIndex,
     /// Nonzero segment selector sizes aren't supported yet.
     UnsupportedSegmentSize,
-    /// Typed stack values aren't supported yet
-    UnsupportedTypedStack,
+    /// A compilation unit or type unit is missing its top level DIE.
+    MissingUnitDie,
+    /// A limit given in `ReaderLimits` was exceeded.
+    LimitExceeded,
+    /// Found a cycle in the `DW_AT_specification` or `DW_AT_abstract_origin`
+    /// references between entries.
+    CyclicReference,
+    /// An address calculation overflowed.
+    AddressOverflow,
+    /// An offset calculation overflowed.
+    OffsetOverflow,
+    /// The compression type of a compressed section is not supported.
+    UnsupportedCompressionType(u32),
+    /// The data of a compressed section is invalid, or its decompressed size
+    /// does not match the size given in its header.
+    InvalidCompressedData,
+    /// A name index abbreviation uses a `DW_IDX_*` value that is too large.
+    InvalidNameAttributeIndex(u64),
+    /// An ARM exception table entry specifies that the function cannot be
+    /// unwound.
+    ArmCannotUnwind,
+    /// An ARM exception table entry uses an unknown personality routine index.
+    UnknownArmPersonality(u8),
+    /// An ARM exception table entry contains a reserved, spare, or unsupported
+    /// unwind instruction.
+    UnsupportedArmUnwindInstruction(u8),
+    /// The value of a register that is needed to unwind a frame is not
+    /// available.
+    UnavailableRegister(Register),
+    /// The memory at an address that is needed to unwind a frame is not
+    /// available.
+    UnavailableMemory(u64),
+    /// A DWARF expression in call frame information requires the CFA, but
+    /// the CFA is not available because it is being computed.
+    UnavailableCfa,
+    /// The unwinder does not support the register rule.
+    UnsupportedRegisterRule,
+    /// A DWARF expression in call frame information requires a value that is
+    /// not available when unwinding, or does not produce an address.
+    UnsupportedCfiExpression,
+    /// A directory or file name entry in a line number program header does
+    /// not have a `DW_LNCT_path` field.
+    MissingFileEntryFormatPath,
 }
 
 impl fmt::Display for Error {
-    #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
-        Debug::fmt(self, f)
+        let description = self.description();
+        match *self {
+            Error::UnknownVersion(version) => write!(f, "{}: {}", description, version),
+            Error::UnknownStandardOpcode(opcode) => write!(f, "{}: {}", description, opcode),
+            Error::UnknownExtendedOpcode(opcode) => write!(f, "{}: {}", description, opcode),
+            Error::UnsupportedAddressSize(size) | Error::UnsupportedFieldSize(size) => {
+                write!(f, "{}: {}", description, size)
+            }
+            Error::BadBranchTarget(target) => write!(f, "{}: 0x{:x}", description, target),
+            Error::InvalidExpression(opcode) => write!(f, "{}: {}", description, opcode),
+            Error::InvalidExpressionTerminator(offset) => {
+                write!(f, "{} at expression offset 0x{:x}", description, offset)
+            }
+            Error::UnknownCallFrameInstruction(instruction) => {
+                write!(f, "{}: {}", description, instruction)
+            }
+            Error::UnknownDiscriminantDescriptor(descriptor) => {
+                write!(f, "{}: {}", description, descriptor)
+            }
+            Error::UnsupportedCompressionType(ch_type) => {
+                write!(f, "{}: {}", description, ch_type)
+            }
+            Error::InvalidNameAttributeIndex(index) => write!(f, "{}: {}", description, index),
+            Error::UnknownArmPersonality(index) => write!(f, "{}: {}", description, index),
+            Error::UnsupportedArmUnwindInstruction(opcode) => {
+                write!(f, "{}: 0x{:02x}", description, opcode)
+            }
+            Error::UnavailableRegister(register) => write!(f, "{}: {}", description, register.0),
+            Error::UnavailableMemory(address) => write!(f, "{}: 0x{:x}", description, address),
+            _ => f.write_str(description),
+        }
     }
 }
 
@@ -182,6 +275,7 @@ impl Error {
             }
             Error::BadLength => "The specified length is impossible",
             Error::UnknownForm => "Found an unknown `DW_FORM_*` type",
+            Error::InvalidIndirectForm => "Found an invalid `DW_FORM_indirect` attribute",
             Error::ExpectedZero => "Expected a zero, found something else",
             Error::DuplicateAbbreviationCode => {
                 "Found an abbreviation code that has already been used"
@@ -190,7 +284,7 @@ impl Error {
             Error::UnknownReservedLength => "Found an unknown reserved length value",
             Error::UnknownVersion(_) => "Found an unknown DWARF version",
             Error::UnknownAbbreviation => "Found a record with an unknown abbreviation code",
-            Error::UnexpectedEof => "Hit the end of input before it was expected",
+            Error::UnexpectedEof(_) => "Hit the end of input before it was expected",
             Error::UnexpectedNull => "Read a null entry before it was expected.",
             Error::UnknownStandardOpcode(_) => "Found an unknown standard opcode",
             Error::UnknownExtendedOpcode(_) => "Found an unknown extended opcode",
@@ -211,6 +305,14 @@ impl Error {
             Error::InvalidPushObjectAddress => {
                 "DW_OP_push_object_address used but no object address given"
             }
+            Error::TypeMismatch => "The operands of a DWARF expression operation have different types",
+            Error::IntegralTypeRequired => {
+                "A DWARF expression operation requires an integral type"
+            }
+            Error::EvaluationStorageFull => "The storage of an expression evaluation is full",
+            Error::InvalidFrameBase => {
+                "The frame base expression did not produce a register or an address"
+            }
             Error::NotEnoughStackItems => "Not enough items on stack when evaluating expression",
             Error::TooManyIterations => "Too many iterations to evaluate DWARF expression",
             Error::InvalidExpression(_) => "Invalid opcode in DWARF expression",
@@ -219,7 +321,10 @@ impl Error {
             }
             Error::InvalidExpressionTerminator(_) => "Expected DW_OP_piece or DW_OP_bit_piece",
             Error::DivisionByZero => "Division or modulus by zero when evaluating expression",
-            Error::UnknownCallFrameInstruction(_) => "An unknown DW_CFA_* instructiion",
+            Error::UnknownCallFrameInstruction(_) => "An unknown DW_CFA_* instruction",
+            Error::UnknownDiscriminantDescriptor(_) => {
+                "An unknown DW_DSC_* descriptor in a discriminant list"
+            }
             Error::InvalidAddressRange => {
                 "The end of an address range must not be before the beginning."
             }
@@ -249,6 +354,9 @@ impl Error {
             Error::CannotFitInU8 => {
                 "We tried to convert some number into a `u8`, but it was too large."
             }
+            Error::CannotFitInU16 => {
+                "We tried to convert some number into a `u16`, but it was too large."
+            }
             Error::TooManyRegisterRules => {
                 "The CFI program defined more register rules than we have storage for."
             }
@@ -268,11 +376,59 @@ impl Error {
             Error::UnsupportedSegmentSize => {
                 "Nonzero segment size not supported yet"
             }
-            Error::UnsupportedTypedStack => {
-                "Typed stack values not supported yet"
+            Error::MissingUnitDie => {
+                "A compilation unit or type unit is missing its top level DIE."
+            }
+            Error::LimitExceeded => "A configured parsing limit was exceeded",
+            Error::CyclicReference => {
+                "Found a cycle in the specification or abstract origin references of entries"
+            }
+            Error::AddressOverflow => "An address calculation overflowed",
+            Error::OffsetOverflow => "An offset calculation overflowed",
+            Error::UnsupportedCompressionType(_) => {
+                "The section compression type is not supported"
+            }
+            Error::InvalidCompressedData => "The compressed section data is invalid",
+            Error::InvalidNameAttributeIndex(_) => "Found an invalid `DW_IDX_*` value",
+            Error::ArmCannotUnwind => "The ARM exception table forbids unwinding this function",
+            Error::UnknownArmPersonality(_) => {
+                "Found an unknown ARM exception table personality routine index"
+            }
+            Error::UnsupportedArmUnwindInstruction(_) => {
+                "Found an unsupported ARM exception table unwind instruction"
+            }
+            Error::UnavailableRegister(_) => "The value of a register needed to unwind is unavailable",
+            Error::UnavailableMemory(_) => "The memory needed to unwind is unavailable",
+            Error::UnavailableCfa => "The CFA needed to evaluate an expression is unavailable",
+            Error::UnsupportedRegisterRule => "The register rule is not supported by the unwinder",
+            Error::UnsupportedCfiExpression => {
+                "The call frame information expression is not supported by the unwinder"
+            }
+            Error::MissingFileEntryFormatPath => {
+                "A line number program file entry is missing a `DW_LNCT_path` field"
             }
         }
     }
+
+    /// Attach the name of the section and the offset within that section at
+    /// which this error occurred.
+    ///
+    /// ```
+    /// use gimli::{Error, SectionId};
+    ///
+    /// let error = Error::UnknownAbbreviation.with_context(SectionId::DebugInfo, 0x1234);
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Found a record with an unknown abbreviation code at .debug_info+0x1234"
+    /// );
+    /// ```
+    pub fn with_context(self, section: SectionId, offset: u64) -> ContextError {
+        ContextError {
+            error: self,
+            section: section,
+            offset: offset,
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -282,6 +438,40 @@ impl error::Error for Error {
     }
 }
 
+/// An `Error` along with the section and byte offset at which it occurred.
+///
+/// Created with `Error::with_context`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextError {
+    /// The underlying error.
+    pub error: Error,
+
+    /// The section in which the error occurred.
+    pub section: SectionId,
+
+    /// The offset within the section at which the error occurred.
+    pub offset: u64,
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        write!(f, "{} at {}+0x{:x}", self.error, self.section.name(), self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ContextError {
+    fn description(&self) -> &str {
+        self.error.description()
+    }
+}
+
+impl From<ContextError> for Error {
+    fn from(error: ContextError) -> Self {
+        error.error
+    }
+}
+
 #[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(_: io::Error) -> Self {
@@ -292,18 +482,54 @@ impl From<io::Error> for Error {
 /// The result of a parse.
 pub type Result<T> = result::Result<T, Error>;
 
-/// Convert a `u64` to a `u8` and return it.
+/// Limits on the resources used when parsing, for use with untrusted input.
+///
+/// A limit of `None` means that there is no limit, which is the default.
+/// When a limit is exceeded, parsing fails with `Error::LimitExceeded`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderLimits {
+    /// The maximum number of abbreviations in a single set of abbreviations.
+    ///
+    /// Used by `DebugAbbrev::abbreviations_with_limits`.
+    pub max_abbreviations: Option<usize>,
+
+    /// The maximum nesting depth of DIEs, relative to the entry that
+    /// traversal started at.
+    ///
+    /// Used by `EntriesCursor::set_limits` and `EntriesTree::set_limits`.
+    pub max_die_depth: Option<usize>,
+
+    /// The maximum length in bytes of a DWARF expression, including the
+    /// expressions evaluated for `DW_OP_call*`.
+    ///
+    /// Used by `Evaluation::set_limits`.
+    pub max_expression_length: Option<usize>,
+
+    /// The maximum number of line number program instructions executed by a
+    /// single state machine.
+    ///
+    /// Used by `StateMachine::set_limits`.
+    pub max_line_instructions: Option<u64>,
+}
+
+/// Convert a `u64` to a `u16` and return it.
 #[doc(hidden)]
 #[inline]
-pub fn u64_to_u8(x: u64) -> Result<u8> {
-    let y = x as u8;
+pub fn u64_to_u16(x: u64) -> Result<u16> {
+    let y = x as u16;
     if y as u64 == x {
         Ok(y)
     } else {
-        Err(Error::CannotFitInU8)
+        Err(Error::CannotFitInU16)
     }
 }
 
+/// Parse a ULEB128 encoded DWARF register number.
+#[inline]
+pub(crate) fn parse_register<R: Reader>(input: &mut R) -> Result<Register> {
+    input.read_uleb128().and_then(u64_to_u16).map(Register)
+}
+
 /// Parse a `DW_EH_PE_*` pointer encoding.
 #[doc(hidden)]
 #[inline]
@@ -412,7 +638,7 @@ pub fn parse_encoded_pointer<'bases, R: Reader>(
             Ok(Pointer::new(encoding, addr.into()))
         }
         constants::DW_EH_PE_pcrel => if let Some(cfi) = bases.cfi {
-            let offset_from_section = input.offset_from(section);
+            let offset_from_section = input.offset_from(section)?;
             let offset = parse_data(encoding, address_size, input)?;
             let p = cfi.wrapping_add(offset_from_section.into_u64())
                 .wrapping_add(offset);
@@ -433,8 +659,7 @@ pub fn parse_encoded_pointer<'bases, R: Reader>(
             Err(Error::DataRelativePointerButDataBaseIsUndefined)
         },
         constants::DW_EH_PE_funcrel => {
-            let func = bases.func.borrow();
-            if let Some(func) = *func {
+            if let Some(func) = bases.func {
                 let offset = parse_data(encoding, address_size, input)?;
                 Ok(Pointer::new(encoding, func.wrapping_add(offset)))
             } else {
@@ -448,10 +673,17 @@ pub fn parse_encoded_pointer<'bases, R: Reader>(
 
 /// An offset into the `.debug_macinfo` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugMacinfoOffset<T = usize>(pub T);
 
+/// An offset into the `.debug_macro` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugMacroOffset<T = usize>(pub T);
+
 /// Whether the format of a compilation unit is 32- or 64-bit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Format {
     /// 64-bit DWARF
     Dwarf64,
@@ -497,6 +729,50 @@ pub fn parse_initial_length<R: Reader>(input: &mut R) -> Result<(u64, Format)> {
     }
 }
 
+/// Either an entry in a section, or zero padding between entries.
+///
+/// Some linkers and tools such as `objcopy` leave zero padding between the
+/// units in `.debug_info` or the entries in `.debug_frame`. Section iterators
+/// skip this padding, but their `next_or_padding` methods return it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryOrPadding<T, Offset = usize> {
+    /// An entry.
+    Entry(T),
+    /// Zero padding between entries.
+    Padding {
+        /// The offset of the padding from the start of the section.
+        offset: Offset,
+        /// The length of the padding.
+        length: Offset,
+    },
+}
+
+/// Return the length of the zero padding at the start of `input`.
+///
+/// Padding consists of zero 32-bit initial lengths. Fewer than 4 zero bytes at
+/// the end of the input are also padding.
+pub(crate) fn padding_length<R: Reader>(input: &R) -> Result<R::Offset> {
+    let mut rest = input.clone();
+    loop {
+        if rest.len() >= R::Offset::from_u8(4) {
+            if rest.clone().read_u32()? != 0 {
+                break;
+            }
+            rest.skip(R::Offset::from_u8(4))?;
+        } else {
+            let mut tail = rest.clone();
+            while !tail.is_empty() {
+                if tail.read_u8()? != 0 {
+                    return Ok(input.len() - rest.len());
+                }
+            }
+            rest.empty();
+            break;
+        }
+    }
+    Ok(input.len() - rest.len())
+}
+
 #[cfg(test)]
 mod tests {
     extern crate test_assembler;
@@ -506,9 +782,31 @@ mod tests {
     use constants;
     use endianity::{EndianBuf, LittleEndian};
     use self::test_assembler::{Endian, Section};
-    use std::cell::RefCell;
     use test_util::GimliSectionMethods;
 
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            format!("{}", Error::UnexpectedEof(ReaderOffsetId(0))),
+            "Hit the end of input before it was expected"
+        );
+        assert_eq!(
+            format!("{}", Error::UnknownVersion(7)),
+            "Found an unknown DWARF version: 7"
+        );
+        assert_eq!(
+            format!("{}", Error::InvalidExpression(constants::DW_OP_nop)),
+            "Invalid opcode in DWARF expression: DW_OP_nop"
+        );
+
+        let error = Error::UnsupportedAddressSize(3).with_context(SectionId::DebugInfo, 0x2a);
+        assert_eq!(
+            format!("{}", error),
+            "The specified address size is not supported: 3 at .debug_info+0x2a"
+        );
+        assert_eq!(Error::from(error), Error::UnsupportedAddressSize(3));
+    }
+
     #[test]
     fn test_parse_initial_length_32_ok() {
         let section = Section::with_endian(Endian::Little).L32(0x78563412);
@@ -561,7 +859,7 @@ mod tests {
         let buf = [0xff, 0xff, 0xff]; // Need at least 4 bytes.
 
         match parse_initial_length(&mut EndianBuf::new(&buf, LittleEndian)) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -576,7 +874,7 @@ mod tests {
         let buf = section.get_contents().unwrap();
 
         match parse_initial_length(&mut EndianBuf::new(&buf, LittleEndian)) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -812,7 +1110,7 @@ mod tests {
         let encoding = constants::DW_EH_PE_funcrel;
 
         let mut bases = BaseAddresses::default();
-        bases.func = RefCell::new(Some(0x10));
+        bases.func = Some(0x10);
 
         let address_size = 4;
         let expected_rest = [1, 2, 3, 4];
@@ -1103,4 +1401,15 @@ mod tests {
         );
         assert_eq!(rest, EndianBuf::new(&expected_rest, LittleEndian));
     }
+
+    #[test]
+    fn test_padding_length() {
+        let padding = |buf: &[u8]| padding_length(&EndianBuf::new(buf, LittleEndian));
+        assert_eq!(padding(&[]), Ok(0));
+        assert_eq!(padding(&[1, 0, 0, 0]), Ok(0));
+        assert_eq!(padding(&[0, 1, 0, 0]), Ok(0));
+        assert_eq!(padding(&[0, 0, 0, 0, 1, 0, 0, 0]), Ok(4));
+        assert_eq!(padding(&[0, 0, 0, 0, 0, 0]), Ok(6));
+        assert_eq!(padding(&[0, 0, 0, 0, 0, 1]), Ok(4));
+    }
 }
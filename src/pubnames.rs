@@ -3,8 +3,8 @@ use fallible_iterator::FallibleIterator;
 use lookup::{DebugLookup, LookupEntryIter, PubStuffEntry, PubStuffParser};
 use parser::{Error, Result};
 use reader::Reader;
-use unit::{DebugInfoOffset, UnitOffset};
-use Section;
+use unit::{DebugInfoOffset, UnitOffset, UnitSectionOffset};
+use {Section, SectionId};
 
 /// A single parsed pubname.
 #[derive(Debug, Clone)]
@@ -31,6 +31,14 @@ impl<R: Reader> PubNamesEntry<R> {
     pub fn die_offset(&self) -> UnitOffset<R::Offset> {
         self.die_offset
     }
+
+    /// Returns the offset into the .debug_info section for the debugging
+    /// information entry which has this name.
+    ///
+    /// Returns `None` if the offset would overflow.
+    pub fn die_section_offset(&self) -> Option<UnitSectionOffset<R::Offset>> {
+        UnitSectionOffset::from(self.unit_header_offset).add_unit_offset(self.die_offset)
+    }
 }
 
 impl<R: Reader> PubStuffEntry<R> for PubNamesEntry<R> {
@@ -98,8 +106,12 @@ impl<R: Reader> DebugPubNames<R> {
 }
 
 impl<R: Reader> Section<R> for DebugPubNames<R> {
-    fn section_name() -> &'static str {
-        ".debug_pubnames"
+    fn id() -> SectionId {
+        SectionId::DebugPubNames
+    }
+
+    fn reader(&self) -> &R {
+        self.0.reader()
     }
 }
 
@@ -117,6 +129,15 @@ impl<R: Reader> From<R> for DebugPubNames<R> {
 pub struct PubNamesEntryIter<R: Reader>(LookupEntryIter<R, PubStuffParser<R, PubNamesEntry<R>>>);
 
 impl<R: Reader> PubNamesEntryIter<R> {
+    /// Return the section offset of the next pubname, or of the set header
+    /// before it, that the iterator will parse.
+    ///
+    /// If parsing fails, this is the offset of the pubname or header that failed.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.0.offset()
+    }
+
     /// Advance the iterator and return the next pubname.
     ///
     /// Returns the newly parsed pubname as `Ok(Some(pubname))`. Returns
@@ -137,3 +158,41 @@ impl<R: Reader> FallibleIterator for PubNamesEntryIter<R> {
         self.0.next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+
+    #[test]
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_pubnames_dwarf64() {
+        let buf = [
+            // 64-bit length = 38.
+            0xff, 0xff, 0xff, 0xff,
+            0x26, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Version.
+            0x02, 0x00,
+            // Offset of the unit in .debug_info.
+            0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Length of the unit.
+            0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Offset of the entry within the unit, and its name.
+            0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            b'f', b'o', b'o', 0x00,
+            // Terminator.
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let debug_pubnames = DebugPubNames::new(&buf, LittleEndian);
+        let mut items = debug_pubnames.items();
+        assert_eq!(items.offset(), 0);
+        let entry = items.next().unwrap().unwrap();
+        assert_eq!(items.offset(), 42);
+        assert_eq!(entry.name().buf(), b"foo");
+        assert_eq!(entry.unit_header_offset(), DebugInfoOffset(0x10));
+        assert_eq!(entry.die_offset(), UnitOffset(0x20));
+        assert!(items.next().unwrap().is_none());
+        assert_eq!(items.offset(), buf.len());
+    }
+}
@@ -3,8 +3,8 @@ use fallible_iterator::FallibleIterator;
 use lookup::{DebugLookup, LookupEntryIter, PubStuffEntry, PubStuffParser};
 use parser::{Error, Result};
 use reader::Reader;
-use unit::{DebugInfoOffset, UnitOffset};
-use Section;
+use unit::{DebugInfoOffset, UnitOffset, UnitSectionOffset};
+use {Section, SectionId};
 
 /// A single parsed pubtype.
 #[derive(Debug, Clone)]
@@ -31,6 +31,14 @@ impl<R: Reader> PubTypesEntry<R> {
     pub fn die_offset(&self) -> UnitOffset<R::Offset> {
         self.die_offset
     }
+
+    /// Returns the offset into the .debug_info section for the debugging
+    /// information entry which describes the type with this name.
+    ///
+    /// Returns `None` if the offset would overflow.
+    pub fn die_section_offset(&self) -> Option<UnitSectionOffset<R::Offset>> {
+        UnitSectionOffset::from(self.unit_header_offset).add_unit_offset(self.die_offset)
+    }
 }
 
 impl<R: Reader> PubStuffEntry<R> for PubTypesEntry<R> {
@@ -98,8 +106,12 @@ impl<R: Reader> DebugPubTypes<R> {
 }
 
 impl<R: Reader> Section<R> for DebugPubTypes<R> {
-    fn section_name() -> &'static str {
-        ".debug_pubtypes"
+    fn id() -> SectionId {
+        SectionId::DebugPubTypes
+    }
+
+    fn reader(&self) -> &R {
+        self.0.reader()
     }
 }
 
@@ -117,6 +129,15 @@ impl<R: Reader> From<R> for DebugPubTypes<R> {
 pub struct PubTypesEntryIter<R: Reader>(LookupEntryIter<R, PubStuffParser<R, PubTypesEntry<R>>>);
 
 impl<R: Reader> PubTypesEntryIter<R> {
+    /// Return the section offset of the next pubtype, or of the set header
+    /// before it, that the iterator will parse.
+    ///
+    /// If parsing fails, this is the offset of the pubtype or header that failed.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.0.offset()
+    }
+
     /// Advance the iterator and return the next pubtype.
     ///
     /// Returns the newly parsed pubtype as `Ok(Some(pubtype))`. Returns
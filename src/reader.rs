@@ -6,6 +6,46 @@ use endianity::Endianity;
 use leb128;
 use parser::{Error, Format, Result};
 
+/// Find the index of the first occurence of `byte` in `haystack`.
+///
+/// This is used by the `Reader::find` implementations of readers that are
+/// backed by slices, since finding the null terminator of strings is a
+/// measurable fraction of the time spent parsing.
+#[cfg(feature = "memchr")]
+#[inline]
+pub(crate) fn find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    ::memchr::memchr(byte, haystack)
+}
+
+/// Find the index of the first occurence of `byte` in `haystack`.
+///
+/// This is used by the `Reader::find` implementations of readers that are
+/// backed by slices, since finding the null terminator of strings is a
+/// measurable fraction of the time spent parsing. Without `memchr`, this
+/// checks 8 bytes at a time until it finds the word containing the byte.
+#[cfg(not(feature = "memchr"))]
+pub(crate) fn find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    const LO: u64 = 0x0101_0101_0101_0101;
+    const HI: u64 = 0x8080_8080_8080_8080;
+    let repeated = LO * u64::from(byte);
+
+    let mut offset = 0;
+    for chunk in haystack.chunks_exact(8) {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        // Zero the bytes that match, and check for any zero bytes.
+        let word = u64::from_ne_bytes(word) ^ repeated;
+        if word.wrapping_sub(LO) & !word & HI != 0 {
+            break;
+        }
+        offset += 8;
+    }
+    haystack[offset..]
+        .iter()
+        .position(|ch| *ch == byte)
+        .map(|index| offset + index)
+}
+
 /// A trait for offsets with a DWARF section.
 ///
 /// This allows consumers to choose a size that is appropriate for their address space.
@@ -35,6 +75,9 @@ pub trait ReaderOffset
     /// Wrapping (modular) addition. Computes `self + other`.
     fn wrapping_add(self, other: Self) -> Self;
 
+    /// Checked addition. Computes `self + other`.
+    fn checked_add(self, other: Self) -> Option<Self>;
+
     /// Checked subtraction. Computes `self - other`.
     fn checked_sub(self, other: Self) -> Option<Self>;
 }
@@ -75,6 +118,11 @@ impl ReaderOffset for u64 {
         self.wrapping_add(other)
     }
 
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.checked_add(other)
+    }
+
     #[inline]
     fn checked_sub(self, other: Self) -> Option<Self> {
         self.checked_sub(other)
@@ -122,6 +170,11 @@ impl ReaderOffset for u32 {
         self.wrapping_add(other)
     }
 
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.checked_add(other)
+    }
+
     #[inline]
     fn checked_sub(self, other: Self) -> Option<Self> {
         self.checked_sub(other)
@@ -169,12 +222,27 @@ impl ReaderOffset for usize {
         self.wrapping_add(other)
     }
 
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.checked_add(other)
+    }
+
     #[inline]
     fn checked_sub(self, other: Self) -> Option<Self> {
         self.checked_sub(other)
     }
 }
 
+/// An identifier for an offset within a section reader.
+///
+/// This is used for error reporting, so that an error can be mapped back to
+/// the section and offset at which it occurred. The meaning of this value is
+/// specific to each `Reader` implementation. The values should be unique
+/// amongst all of the readers for the sections being parsed; if they are not,
+/// then errors may point to the wrong section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReaderOffsetId(pub u64);
+
 /// A trait for reading the data from a DWARF section.
 ///
 /// All read operations advance the section offset of the reader
@@ -204,9 +272,16 @@ pub trait Reader: Debug + Clone {
     /// Return the offset of this reader's data relative to the start of
     /// the given base reader's data.
     ///
-    /// May panic if this reader's data is not contained within the given
-    /// base reader's data.
-    fn offset_from(&self, base: &Self) -> Self::Offset;
+    /// Returns an error if this reader's data is not contained within the
+    /// given base reader's data.
+    fn offset_from(&self, base: &Self) -> Result<Self::Offset>;
+
+    /// Return an identifier for the current reader offset.
+    fn offset_id(&self) -> ReaderOffsetId;
+
+    /// Return the offset corresponding to the given `id` if
+    /// it is associated with this reader.
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<Self::Offset>;
 
     /// Find the index of the first occurence of the given byte.
     /// The offset of the reader is not changed.
@@ -252,6 +327,28 @@ pub trait Reader: Debug + Clone {
     where
         A: Sized + Default + AsMut<[u8]>;
 
+    /// Read bytes into the given buffer, filling it completely.
+    ///
+    /// This is useful for fixed size data such as `DW_FORM_data16` values
+    /// and build IDs, and avoids the allocation that `to_slice` may need.
+    ///
+    /// The default implementation uses `split` and `to_slice`, so readers
+    /// should override it where possible.
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        let len = Self::Offset::from_u64(buf.len() as u64)?;
+        let data = self.split(len)?;
+        buf.copy_from_slice(&data.to_slice()?);
+        Ok(())
+    }
+
+    /// Return a reader for the next `len` bytes, and advance past them.
+    ///
+    /// This is the same as `split`.
+    #[inline]
+    fn read_bytes(&mut self, len: Self::Offset) -> Result<Self> {
+        self.split(len)
+    }
+
     /// Read a u8.
     fn read_u8(&mut self) -> Result<u8>;
 
@@ -264,6 +361,19 @@ pub trait Reader: Debug + Clone {
     /// Read an i16.
     fn read_i16(&mut self) -> Result<i16>;
 
+    /// Read a u24, and return it as a u32.
+    ///
+    /// This is used by the `DW_FORM_strx3` and `DW_FORM_addrx3` forms.
+    fn read_u24(&mut self) -> Result<u32> {
+        let mut buf = [0; 3];
+        self.read_slice(&mut buf)?;
+        if self.endian().is_big_endian() {
+            Ok(u32::from(buf[0]) << 16 | u32::from(buf[1]) << 8 | u32::from(buf[2]))
+        } else {
+            Ok(u32::from(buf[2]) << 16 | u32::from(buf[1]) << 8 | u32::from(buf[0]))
+        }
+    }
+
     /// Read a u32.
     fn read_u32(&mut self) -> Result<u32>;
 
@@ -276,6 +386,24 @@ pub trait Reader: Debug + Clone {
     /// Read an i64.
     fn read_i64(&mut self) -> Result<i64>;
 
+    /// Read an f32.
+    ///
+    /// The value is read as a u32 and reinterpreted as an IEEE 754 single
+    /// precision float.
+    #[inline]
+    fn read_f32(&mut self) -> Result<f32> {
+        self.read_u32().map(f32::from_bits)
+    }
+
+    /// Read an f64.
+    ///
+    /// The value is read as a u64 and reinterpreted as an IEEE 754 double
+    /// precision float.
+    #[inline]
+    fn read_f64(&mut self) -> Result<f64> {
+        self.read_u64().map(f64::from_bits)
+    }
+
     /// Read a null-terminated slice, and return it (excluding the null).
     fn read_null_terminated_slice(&mut self) -> Result<Self> {
         let idx = self.find(0)?;
@@ -294,6 +422,22 @@ pub trait Reader: Debug + Clone {
         leb128::read::signed(self)
     }
 
+    /// Read past a LEB128 encoded integer without decoding it.
+    fn skip_leb128(&mut self) -> Result<()> {
+        leb128::read::skip(self)
+    }
+
+    /// Read an unsigned LEB128 encoded integer that may be up to 128 bits
+    /// wide.
+    fn read_uleb128_u128(&mut self) -> Result<u128> {
+        leb128::read::unsigned128(self)
+    }
+
+    /// Read a signed LEB128 encoded integer that may be up to 128 bits wide.
+    fn read_sleb128_i128(&mut self) -> Result<i128> {
+        leb128::read::signed128(self)
+    }
+
     /// Read an address-sized integer, and return it as a `u64`.
     fn read_address(&mut self, address_size: u8) -> Result<u64> {
         match address_size {
@@ -2,13 +2,22 @@ use constants;
 use endianity::{EndianBuf, Endianity};
 use fallible_iterator::FallibleIterator;
 use parser::{self, Error, Format, Result};
-use reader::{Reader, ReaderOffset};
-use Section;
+use reader::{Reader, ReaderOffset, ReaderOffsetId};
+use std::fmt;
+use sync::Arc;
+use vec::{self, Vec};
+use {Section, SectionId};
 
-/// An offset into the `.debug_addr` section.
+/// An index into a set of entries in the `.debug_addr` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AddressIndex(pub u64);
 
+/// An offset to a set of entries in the `.debug_addr` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugAddrBase<T = usize>(pub T);
+
 /// The `DebugRanges` struct represents the DWARF strings
 /// found in the `.debug_ranges` section.
 #[derive(Debug, Clone, Copy)]
@@ -40,8 +49,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugRanges<R> {
-    fn section_name() -> &'static str {
-        ".debug_ranges"
+    fn id() -> SectionId {
+        SectionId::DebugRanges
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_ranges_section
     }
 }
 
@@ -85,8 +98,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugRngLists<R> {
-    fn section_name() -> &'static str {
-        ".debug_rnglists"
+    fn id() -> SectionId {
+        SectionId::DebugRngLists
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_rnglists_section
     }
 }
 
@@ -124,10 +141,9 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<RngListsHeader> {
     }
 
     let address_size = input.read_u8()?;
-    let segment_selector_size = input.read_u8()?;
-    if segment_selector_size != 0 {
-        return Err(Error::UnsupportedSegmentSize);
-    }
+    // The list entries never contain segment selectors, so we can ignore
+    // the segment selector size.
+    let _segment_selector_size = input.read_u8()?;
     let offset_entry_count = input.read_u32()?;
     Ok(RngListsHeader {
         format: format,
@@ -139,13 +155,28 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<RngListsHeader> {
 /// An offset into either the `.debug_ranges` section or the `.debug_rnglists` section,
 /// depending on the version of the unit the offset was contained in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct RangeListsOffset<T = usize>(pub T);
 
+/// An offset to a set of offsets in the `.debug_rnglists` section.
+///
+/// This is also used for the `DW_AT_GNU_ranges_base` attribute of the
+/// pre-standard GNU split DWARF extension, which is an offset into the
+/// `.debug_ranges` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugRngListsBase<T = usize>(pub T);
+
+/// An index into a set of offsets in the `.debug_rnglists` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugRngListsIndex<T = usize>(pub T);
+
 /// The DWARF data found in `.debug_ranges` and `.debug_rnglists` sections.
 #[derive(Debug, Clone, Copy)]
 pub struct RangeLists<R: Reader> {
-    debug_ranges: DebugRanges<R>,
-    debug_rnglists: DebugRngLists<R>,
+    pub(crate) debug_ranges: DebugRanges<R>,
+    pub(crate) debug_rnglists: DebugRngLists<R>,
     header: RngListsHeader,
 }
 
@@ -170,6 +201,56 @@ impl<R: Reader> RangeLists<R> {
         })
     }
 
+    /// Returns the `SectionId` and section offset corresponding to the given
+    /// `id`, if it is associated with one of these sections.
+    pub fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<(SectionId, R::Offset)> {
+        self.debug_ranges
+            .lookup_offset_id(id)
+            .or_else(|| self.debug_rnglists.lookup_offset_id(id))
+    }
+
+    /// Return the range list offset at the given index of a unit's set of
+    /// offsets in the `.debug_rnglists` section.
+    ///
+    /// `format` must be the format of the unit, and `base` must be the unit's
+    /// `DW_AT_rnglists_base` attribute. The offsets are relative to `base`.
+    ///
+    /// Returns `Error::OffsetOutOfBounds` if `index` is not less than the
+    /// offset entry count of the table at `base`.
+    pub fn get_offset(
+        &self,
+        format: Format,
+        base: DebugRngListsBase<R::Offset>,
+        index: DebugRngListsIndex<R::Offset>,
+    ) -> Result<RangeListsOffset<R::Offset>> {
+        let input = &mut self.debug_rnglists.debug_rnglists_section.clone();
+        // The offsets follow the header of their table, which ends with the
+        // number of offsets.
+        let count_size = R::Offset::from_u8(4);
+        if base.0 < count_size {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        input.skip(base.0 - count_size)?;
+        let offset_entry_count = input.read_u32()?;
+        if index.0.into_u64() >= u64::from(offset_entry_count) {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let offset = index
+            .0
+            .into_u64()
+            .checked_mul(u64::from(format.word_size()))
+            .ok_or(Error::OffsetOutOfBounds)?;
+        input.skip(R::Offset::from_u64(offset)?)?;
+        let offset = input.read_offset(format)?;
+        Ok(RangeListsOffset(base.0.wrapping_add(offset)))
+    }
+
+    /// Return the size of the header of the first set of offsets in the
+    /// `.debug_rnglists` section.
+    pub(crate) fn header_size(&self) -> u8 {
+        self.header.size()
+    }
+
     /// Iterate over the `Range` list entries starting at the given offset.
     ///
     /// The `unit_version` and `address_size` must match the compilation unit that the
@@ -206,18 +287,20 @@ impl<R: Reader> RangeLists<R> {
         unit_version: u16,
         address_size: u8,
     ) -> Result<RawRngListIter<R>> {
-        if unit_version < 5 {
+        let mut iter = if unit_version < 5 {
             let mut input = self.debug_ranges.debug_ranges_section.clone();
             input.skip(offset.0)?;
-            Ok(RawRngListIter::new(input, unit_version, address_size))
+            RawRngListIter::new(input, unit_version, address_size)
         } else {
             if offset.0 < R::Offset::from_u8(self.header.size()) {
                 return Err(Error::OffsetOutOfBounds);
             }
             let mut input = self.debug_rnglists.debug_rnglists_section.clone();
             input.skip(offset.0)?;
-            Ok(RawRngListIter::new(input, unit_version, self.header.address_size))
-        }
+            RawRngListIter::new(input, unit_version, self.header.address_size)
+        };
+        iter.offset = offset;
+        Ok(iter)
     }
 }
 
@@ -225,15 +308,16 @@ impl<R: Reader> RangeLists<R> {
 ///
 /// This iterator does not perform any processing of the range entries,
 /// such as handling base addresses.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RawRngListIter<R: Reader> {
     input: R,
+    offset: RangeListsOffset<R::Offset>,
     version: u16,
     address_size: u8,
 }
 
 /// A raw entry in .debug_rnglists
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RawRngListEntry {
     /// DW_RLE_base_address
     BaseAddress {
@@ -356,19 +440,32 @@ impl<R: Reader> RawRngListIter<R> {
     fn new(input: R, version: u16, address_size: u8) -> RawRngListIter<R> {
         RawRngListIter {
             input,
+            offset: RangeListsOffset(R::Offset::from_u8(0)),
             version,
             address_size,
         }
     }
 
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    ///
+    /// Entries are returned in the order that they occur in the list. If
+    /// parsing an entry fails, this is the offset of that entry.
+    #[inline]
+    pub fn offset(&self) -> RangeListsOffset<R::Offset> {
+        self.offset
+    }
+
     /// Advance the iterator to the next range.
     pub fn next(&mut self) -> Result<Option<RawRngListEntry>> {
         if self.input.is_empty() {
             return Ok(None);
         }
 
+        let len = self.input.len();
         match RawRngListEntry::parse(&mut self.input, self.version, self.address_size) {
             Ok(range) => {
+                self.offset.0 += len - self.input.len();
                 if range.is_none() {
                     self.input.empty();
                 }
@@ -400,6 +497,8 @@ impl<R: Reader> FallibleIterator for RawRngListIter<R> {
 pub struct RngListIter<R: Reader> {
     raw: RawRngListIter<R>,
     base_address: u64,
+    tombstones: TombstoneMode,
+    adjustment: Option<AddressAdjustment>,
 }
 
 impl<R: Reader> RngListIter<R> {
@@ -408,9 +507,56 @@ impl<R: Reader> RngListIter<R> {
         RngListIter {
             raw: raw,
             base_address: base_address,
+            tombstones: TombstoneMode::default(),
+            adjustment: None,
         }
     }
 
+    /// Return the section offset of the next entry that the iterator will
+    /// parse.
+    ///
+    /// This includes entries, such as base address selections, that are not
+    /// returned by the iterator.
+    #[inline]
+    pub fn offset(&self) -> RangeListsOffset<R::Offset> {
+        self.raw.offset()
+    }
+
+    /// Set how entries for code that was discarded by the linker are
+    /// handled.
+    ///
+    /// The default is `TombstoneMode::Keep`.
+    pub fn set_tombstone_mode(&mut self, tombstones: TombstoneMode) {
+        self.tombstones = tombstones;
+    }
+
+    /// Set the adjustment to apply to the returned ranges.
+    ///
+    /// Ranges that have no adjusted address are omitted. Entries for
+    /// discarded code are not adjusted. The default is no adjustment.
+    pub fn set_address_adjustment(&mut self, adjustment: Option<AddressAdjustment>) {
+        self.adjustment = adjustment;
+    }
+
+    /// Read all of the remaining ranges, and return an iterator over them
+    /// after they have been sorted and merged.
+    ///
+    /// See `MergedRangeIter` for details.
+    pub fn merged(self) -> Result<MergedRangeIter> {
+        MergedRangeIter::from_ranges(self)
+    }
+
+    /// Return true if an entry is for code that was discarded by the linker.
+    fn is_tombstone(&self, base_address: Option<u64>, begin: u64) -> bool {
+        is_tombstone_entry(
+            self.tombstones,
+            self.raw.version,
+            self.raw.address_size,
+            base_address,
+            begin,
+        )
+    }
+
     /// Advance the iterator to the next range.
     pub fn next(&mut self) -> Result<Option<Range>> {
         loop {
@@ -419,6 +565,18 @@ impl<R: Reader> RngListIter<R> {
                 None => return Ok(None),
             };
 
+            let discarded = match raw_range {
+                RawRngListEntry::OffsetPair { begin, .. } => {
+                    self.is_tombstone(Some(self.base_address), begin)
+                }
+                RawRngListEntry::StartEnd { begin, .. } |
+                RawRngListEntry::StartLength { begin, .. } => self.is_tombstone(None, begin),
+                _ => false,
+            };
+            if discarded {
+                continue;
+            }
+
             let range = match raw_range {
                 RawRngListEntry::BaseAddress { addr } => {
                     self.base_address = addr;
@@ -433,7 +591,8 @@ impl<R: Reader> RngListIter<R> {
                     Range { begin: begin, end: end }
                 },
                 RawRngListEntry::StartLength { begin, length } => {
-                    Range { begin: begin, end: begin + length }
+                    let end = begin.checked_add(length).ok_or(Error::AddressOverflow)?;
+                    Range { begin: begin, end: end }
                 },
                 _ => {
                     // We don't support AddressIndex-based entries yet
@@ -446,6 +605,13 @@ impl<R: Reader> RngListIter<R> {
                 return Err(Error::InvalidAddressRange);
             }
 
+            if let Some(ref adjustment) = self.adjustment {
+                match adjustment.adjust_range(range) {
+                    Some(range) => return Ok(Some(range)),
+                    None => continue,
+                }
+            }
+
             return Ok(Some(range));
         }
     }
@@ -460,8 +626,228 @@ impl<R: Reader> FallibleIterator for RngListIter<R> {
     }
 }
 
+/// An iterator over a minimal set of address ranges that cover the same
+/// addresses as a given set of ranges.
+///
+/// The ranges are sorted by address, and ranges that overlap or are
+/// contiguous are merged into a single range. Empty ranges are removed.
+///
+/// ```
+/// use gimli::{MergedRangeIter, Range};
+///
+/// let ranges = vec![
+///     Range { begin: 0x30, end: 0x40 },
+///     Range { begin: 0x10, end: 0x20 },
+///     Range { begin: 0x18, end: 0x28 },
+///     Range { begin: 0x28, end: 0x2c },
+/// ];
+/// let merged: Vec<_> = MergedRangeIter::new(ranges).collect();
+/// assert_eq!(
+///     merged,
+///     [Range { begin: 0x10, end: 0x2c }, Range { begin: 0x30, end: 0x40 }]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MergedRangeIter {
+    ranges: vec::IntoIter<Range>,
+}
+
+impl MergedRangeIter {
+    /// Sort and merge the given ranges.
+    pub fn new(mut ranges: Vec<Range>) -> MergedRangeIter {
+        ranges.retain(|range| range.begin < range.end);
+        ranges.sort_by_key(|range| range.begin);
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = merged.last_mut() {
+                if range.begin <= last.end {
+                    if range.end > last.end {
+                        last.end = range.end;
+                    }
+                    continue;
+                }
+            }
+            merged.push(range);
+        }
+        MergedRangeIter {
+            ranges: merged.into_iter(),
+        }
+    }
+
+    /// Read all of the ranges from the given iterator, and sort and merge
+    /// them.
+    pub fn from_ranges<I>(mut iter: I) -> Result<MergedRangeIter>
+    where
+        I: FallibleIterator<Item = Range, Error = Error>,
+    {
+        let mut ranges = Vec::new();
+        while let Some(range) = iter.next()? {
+            ranges.push(range);
+        }
+        Ok(MergedRangeIter::new(ranges))
+    }
+}
+
+impl Iterator for MergedRangeIter {
+    type Item = Range;
+
+    fn next(&mut self) -> Option<Range> {
+        self.ranges.next()
+    }
+}
+
+/// Returns a mask covering all bits of an address of the given size.
+#[inline]
+fn address_mask(address_size: u8) -> u64 {
+    if address_size >= 8 {
+        !0
+    } else {
+        !(!0 << (address_size * 8))
+    }
+}
+
+/// How range and location list iterators handle entries for code that was
+/// discarded by the linker.
+///
+/// Linkers that garbage collect sections without support for tombstone
+/// values leave the relocated addresses of discarded code as 0 (or 1 in
+/// `.debug_ranges` and `.debug_loc`, where 0 would end the list). Linkers
+/// that do support them write -1 (or -2 in `.debug_ranges` and `.debug_loc`,
+/// where -1 selects a base address). These entries describe no code in the
+/// final binary. Since 0 can also be a valid address, only the value for the
+/// selected mode is recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TombstoneMode {
+    /// Return the entries unchanged.
+    Keep,
+
+    /// Omit the entries that begin at 0, or at 1 in `.debug_ranges` and
+    /// `.debug_loc`.
+    SkipZero,
+
+    /// Omit the entries that begin at -1, or at -2 in `.debug_ranges` and
+    /// `.debug_loc`, and the entries that are relative to a base address of
+    /// this value.
+    SkipMax,
+}
+
+impl Default for TombstoneMode {
+    fn default() -> Self {
+        TombstoneMode::Keep
+    }
+}
+
+impl TombstoneMode {
+    /// Return the beginning address that marks an entry for discarded code
+    /// in this mode, or `None` for `TombstoneMode::Keep`.
+    ///
+    /// `version` is the version of the unit that a list entry belongs to.
+    /// Units before version 5 use `.debug_ranges` and `.debug_loc`.
+    fn address(self, version: u16, address_size: u8) -> Option<u64> {
+        let mask = address_mask(address_size);
+        match self {
+            TombstoneMode::Keep => None,
+            TombstoneMode::SkipZero if version < 5 => Some(1),
+            TombstoneMode::SkipZero => Some(0),
+            TombstoneMode::SkipMax if version < 5 => Some(mask - 1),
+            TombstoneMode::SkipMax => Some(mask),
+        }
+    }
+}
+
+/// A function that adjusts the addresses of code and data.
+///
+/// This can be used to add the slide of a loaded image, or to map the
+/// addresses in an unlinked object file to the addresses in the linked image,
+/// such as when using the debug map of a Mach-O executable. The function
+/// returns `None` if an address has no adjusted address, such as for code
+/// that was not included in the linked image.
+///
+/// ```
+/// use gimli::{AddressAdjustment, Range};
+///
+/// let adjustment = AddressAdjustment::new(|address| {
+///     if address < 0x1000 {
+///         Some(address + 0x4000)
+///     } else {
+///         None
+///     }
+/// });
+/// assert_eq!(adjustment.adjust(0x10), Some(0x4010));
+/// assert_eq!(
+///     adjustment.adjust_range(Range { begin: 0x10, end: 0x20 }),
+///     Some(Range { begin: 0x4010, end: 0x4020 })
+/// );
+/// assert_eq!(adjustment.adjust_range(Range { begin: 0x20, end: 0x10 }), None);
+/// ```
+#[derive(Clone)]
+pub struct AddressAdjustment(Arc<dyn Fn(u64) -> Option<u64> + Send + Sync>);
+
+impl AddressAdjustment {
+    /// Construct a new `AddressAdjustment` that uses the given function.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(u64) -> Option<u64> + Send + Sync + 'static,
+    {
+        AddressAdjustment(Arc::new(f))
+    }
+
+    /// Return the adjusted address, or `None` if it has none.
+    #[inline]
+    pub fn adjust(&self, address: u64) -> Option<u64> {
+        (self.0)(address)
+    }
+
+    /// Return the adjusted range, or `None` if its beginning address has no
+    /// adjusted address, or if its end is before its beginning.
+    ///
+    /// The end of the range is kept at the same offset from its beginning,
+    /// since it is usually the address after the end of a function, which may
+    /// not have an adjusted address itself.
+    pub fn adjust_range(&self, range: Range) -> Option<Range> {
+        let length = range.end.checked_sub(range.begin)?;
+        let begin = self.adjust(range.begin)?;
+        let end = begin.checked_add(length)?;
+        Some(Range { begin, end })
+    }
+}
+
+impl fmt::Debug for AddressAdjustment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AddressAdjustment").finish()
+    }
+}
+
+/// Returns true if a list entry is for code that was discarded by the linker.
+///
+/// `base_address` is the base address that the entry is relative to, if any.
+/// A base address of 0 is common for units that use absolute addresses in
+/// `.debug_ranges`, so only `TombstoneMode::SkipMax` discards all of the
+/// entries relative to a tombstone base address.
+pub(crate) fn is_tombstone_entry(
+    mode: TombstoneMode,
+    version: u16,
+    address_size: u8,
+    base_address: Option<u64>,
+    begin: u64,
+) -> bool {
+    let tombstone = match mode.address(version, address_size) {
+        Some(tombstone) => tombstone,
+        None => return false,
+    };
+    match base_address {
+        Some(base_address) => {
+            let relocated = base_address.wrapping_add(begin) & address_mask(address_size);
+            (mode == TombstoneMode::SkipMax && base_address == tombstone) ||
+                relocated == tombstone
+        }
+        None => begin == tombstone,
+    }
+}
+
 /// An address range from the `.debug_ranges` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Range {
     /// The beginning address of the range.
     pub begin: u64,
@@ -485,7 +871,18 @@ impl Range {
     /// range entries are relative to.  This will only occur for raw ranges.
     #[inline]
     pub fn is_base_address(&self, address_size: u8) -> bool {
-        self.begin == !0 >> (64 - address_size * 8)
+        self.begin == address_mask(address_size)
+    }
+
+    /// Check if the beginning address of this range is the value left by a
+    /// linker for discarded code in the given mode.
+    ///
+    /// This uses the values for `.debug_info` and `.debug_rnglists`, such as
+    /// for a range given by `DW_AT_low_pc` and `DW_AT_high_pc`. Range list
+    /// iterators handle the values for `.debug_ranges` themselves.
+    #[inline]
+    pub fn is_tombstone(&self, mode: TombstoneMode, address_size: u8) -> bool {
+        mode.address(5, address_size) == Some(self.begin)
     }
 
     /// Add a base address to this range.
@@ -495,7 +892,7 @@ impl Range {
     pub fn add_base_address(&mut self, base_address: u64, address_size: u8) {
         debug_assert!(!self.is_end());
         debug_assert!(!self.is_base_address(address_size));
-        let mask = !0 >> (64 - address_size * 8);
+        let mask = address_mask(address_size);
         self.begin = base_address.wrapping_add(self.begin) & mask;
         self.end = base_address.wrapping_add(self.end) & mask;
     }
@@ -647,6 +1044,161 @@ mod tests {
         assert_eq!(ranges.next(), Ok(None));
     }
 
+    #[test]
+    fn test_raw_rnglists() {
+        let start = Label::new();
+        let first = Label::new();
+        let end = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A BaseAddressx followed by an OffsetPair.
+            .L8(1).uleb(1)
+            .L8(4).uleb(0x100).uleb(0x200)
+            // A StartxEndx
+            .L8(2).uleb(2).uleb(3)
+            // A StartxLength
+            .L8(3).uleb(4).uleb(0x100)
+            // A BaseAddress
+            .L8(5).L32(0x02000000)
+            // A range end.
+            .L8(0)
+            .mark(&end)
+            // Some extra data.
+            .L32(0xffffffff);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let offset = RangeListsOffset((&first - &start) as usize);
+        let mut ranges = rnglists.raw_ranges(offset, 5, 0).unwrap();
+        assert_eq!(ranges.offset(), offset);
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(RawRngListEntry::BaseAddressx {
+                addr: AddressIndex(1),
+            }))
+        );
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(RawRngListEntry::OffsetPair {
+                begin: 0x100,
+                end: 0x200,
+            }))
+        );
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(RawRngListEntry::StartxEndx {
+                begin: AddressIndex(2),
+                end: AddressIndex(3),
+            }))
+        );
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(RawRngListEntry::StartxLength {
+                begin: AddressIndex(4),
+                length: 0x100,
+            }))
+        );
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(RawRngListEntry::BaseAddress {
+                addr: 0x02000000,
+            }))
+        );
+        assert_eq!(ranges.next(), Ok(None));
+        assert_eq!(ranges.offset(), RangeListsOffset((&end - &start) as usize));
+    }
+
+    #[test]
+    fn test_rnglists_segment_selector() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(2)
+            .L32(0)
+            .mark(&first)
+            // OffsetPair
+            .L8(4).uleb(0x10200).uleb(0x10300)
+            // A range end.
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let offset = RangeListsOffset((&first - &start) as usize);
+        let mut ranges = rnglists.ranges(offset, 5, 4, 0x01000000).unwrap();
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(Range {
+                begin: 0x01010200,
+                end: 0x01010300,
+            }))
+        );
+        assert_eq!(ranges.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_rnglists_overflow() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(8)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A StartLength whose end overflows.
+            .L8(7).L64(0xffff_ffff_ffff_ff00).uleb(0x100)
+            // A range end.
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let offset = RangeListsOffset((&first - &start) as usize);
+        let mut ranges = rnglists.ranges(offset, 5, 8, 0).unwrap();
+        assert_eq!(ranges.next(), Err(Error::AddressOverflow));
+    }
+
+    #[test]
+    fn test_range_address_mask() {
+        let range = Range { begin: 0, end: 0 };
+        assert!(range.is_base_address(0));
+        assert!(!range.is_base_address(4));
+
+        let mut range = Range { begin: 0x10, end: 0x20 };
+        range.add_base_address(0xffff_fff0, 4);
+        assert_eq!(range, Range { begin: 0, end: 0x10 });
+
+        let mut range = Range { begin: 0x10, end: 0x20 };
+        range.add_base_address(0x1000, 16);
+        assert_eq!(range, Range { begin: 0x1010, end: 0x1020 });
+    }
+
     #[test]
     fn test_rnglists_64() {
         let start = Label::new();
@@ -804,6 +1356,43 @@ mod tests {
         assert!(range.is_base_address(8));
     }
 
+    #[test]
+    fn test_ranges_merged() {
+        let section = Section::with_endian(Endian::Little)
+            // Unsorted ranges, with an overlap.
+            .L32(0x10400).L32(0x10500)
+            .L32(0x10000).L32(0x10200)
+            .L32(0x10100).L32(0x10180)
+            // A contiguous range.
+            .L32(0x10200).L32(0x10300)
+            // An empty range.
+            .L32(0x10380).L32(0x10380)
+            // A range overlapping the end of an earlier range.
+            .L32(0x10480).L32(0x10600)
+            // A range end.
+            .L32(0).L32(0);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&buf, LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&[], LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let ranges = rnglists.ranges(RangeListsOffset(0), 4, 4, 0).unwrap();
+        let merged: Vec<_> = ranges.merged().unwrap().collect();
+        assert_eq!(
+            merged,
+            [
+                Range {
+                    begin: 0x10000,
+                    end: 0x10300,
+                },
+                Range {
+                    begin: 0x10400,
+                    end: 0x10600,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_ranges_32() {
         let start = Label::new();
@@ -998,6 +1587,187 @@ mod tests {
         assert_eq!(ranges.next(), Ok(None));
     }
 
+    #[test]
+    fn test_rnglists_offsets() {
+        let start = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(2)
+            // Offsets
+            .L32(8)
+            .L32(18)
+            // A StartEnd
+            .L8(6).L32(0x1000).L32(0x1100)
+            .L8(0)
+            // A StartLength
+            .L8(7).L32(0x2000).uleb(0x10)
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let base = DebugRngListsBase(12);
+
+        let offset = rnglists.get_offset(Format::Dwarf32, base, DebugRngListsIndex(0));
+        assert_eq!(offset, Ok(RangeListsOffset(20)));
+        let offset = rnglists.get_offset(Format::Dwarf32, base, DebugRngListsIndex(1));
+        assert_eq!(offset, Ok(RangeListsOffset(30)));
+        let mut ranges = rnglists.ranges(offset.unwrap(), 5, 0, 0).unwrap();
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x2000, end: 0x2010 })));
+        assert_eq!(ranges.next(), Ok(None));
+
+        assert_eq!(
+            rnglists.get_offset(Format::Dwarf32, base, DebugRngListsIndex(2)),
+            Err(Error::OffsetOutOfBounds)
+        );
+        assert_eq!(
+            rnglists.get_offset(Format::Dwarf32, base, DebugRngListsIndex(100)),
+            Err(Error::OffsetOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_rnglists_offsets_64() {
+        let start = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(0xffffffff)
+            .L64(&size)
+            .L16(5)
+            .L8(8)
+            .L8(0)
+            .L32(1)
+            // Offsets
+            .L64(8)
+            // A StartLength
+            .L8(7).L64(0x2000).uleb(0x10)
+            .L8(0);
+        size.set_const((&section.here() - &start - 12) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let base = DebugRngListsBase(20);
+
+        let offset = rnglists.get_offset(Format::Dwarf64, base, DebugRngListsIndex(0));
+        assert_eq!(offset, Ok(RangeListsOffset(28)));
+        let mut ranges = rnglists.ranges(offset.unwrap(), 5, 0, 0).unwrap();
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x2000, end: 0x2010 })));
+        assert_eq!(ranges.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_ranges_tombstones() {
+        let section = Section::with_endian(Endian::Little)
+            // A normal range.
+            .L32(0x10200).L32(0x10300)
+            // A range for discarded code, without tombstone support.
+            .L32(1).L32(0x101)
+            // A range for discarded code, with tombstone support.
+            .L32(0xfffffffe).L32(0xfe)
+            // A range relative to a discarded base address.
+            .L32(0xffffffff).L32(0xfffffffe)
+            .L32(0x10).L32(0x20)
+            // A base address selection followed by a normal range.
+            .L32(0xffffffff).L32(0)
+            .L32(0x10400).L32(0x10500)
+            // A range end.
+            .L32(0).L32(0);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&buf, LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&[], LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let offset = RangeListsOffset(0);
+        let version = 4;
+
+        let mut ranges = rnglists.ranges(offset, version, 4, 0).unwrap();
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10200, end: 0x10300 })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 1, end: 0x101 })));
+        assert_eq!(ranges.next(), Err(Error::InvalidAddressRange));
+
+        // Only the tombstone value for the mode is recognized.
+        let mut ranges = rnglists.ranges(offset, version, 4, 0).unwrap();
+        ranges.set_tombstone_mode(TombstoneMode::SkipZero);
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10200, end: 0x10300 })));
+        assert_eq!(ranges.next(), Err(Error::InvalidAddressRange));
+
+        let mut ranges = rnglists.ranges(offset, version, 4, 0).unwrap();
+        ranges.set_tombstone_mode(TombstoneMode::SkipMax);
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10200, end: 0x10300 })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 1, end: 0x101 })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10400, end: 0x10500 })));
+        assert_eq!(ranges.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_rnglists_tombstones() {
+        let start = Label::new();
+        let first = Label::new();
+        let size = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            // Header
+            .mark(&start)
+            .L32(&size)
+            .L16(5)
+            .L8(4)
+            .L8(0)
+            .L32(0)
+            .mark(&first)
+            // A StartLength for discarded code, without tombstone support.
+            .L8(7).L32(0).uleb(0x100)
+            // A StartEnd for discarded code, with tombstone support.
+            .L8(6).L32(0xffffffff).L32(0xffffffff)
+            // An OffsetPair relative to a discarded base address.
+            .L8(5).L32(0xffffffff)
+            .L8(4).uleb(0x10).uleb(0x20)
+            // A normal range.
+            .L8(5).L32(0)
+            .L8(4).uleb(0x10200).uleb(0x10300)
+            // A range end.
+            .L8(0);
+        size.set_const((&section.here() - &start - 4) as u64);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists).unwrap();
+        let offset = RangeListsOffset((&first - &start) as usize);
+
+        let mut ranges = rnglists.ranges(offset, 5, 0, 0).unwrap();
+        ranges.set_tombstone_mode(TombstoneMode::SkipZero);
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0xffffffff, end: 0xffffffff })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0xf, end: 0x1f })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10200, end: 0x10300 })));
+        assert_eq!(ranges.next(), Ok(None));
+
+        let mut ranges = rnglists.ranges(offset, 5, 0, 0).unwrap();
+        ranges.set_tombstone_mode(TombstoneMode::SkipMax);
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0, end: 0x100 })));
+        assert_eq!(ranges.next(), Ok(Some(Range { begin: 0x10200, end: 0x10300 })));
+        assert_eq!(ranges.next(), Ok(None));
+
+        let range = Range { begin: 0, end: 0x100 };
+        assert!(!range.is_tombstone(TombstoneMode::Keep, 4));
+        assert!(range.is_tombstone(TombstoneMode::SkipZero, 4));
+        assert!(!range.is_tombstone(TombstoneMode::SkipMax, 4));
+        let range = Range { begin: 0xffffffff, end: 0xffffffff };
+        assert!(!range.is_tombstone(TombstoneMode::SkipZero, 4));
+        assert!(range.is_tombstone(TombstoneMode::SkipMax, 4));
+        assert!(!range.is_tombstone(TombstoneMode::SkipMax, 8));
+    }
+
     #[test]
     fn test_ranges_invalid() {
         let section = Section::with_endian(Endian::Little)
@@ -1026,7 +1796,7 @@ mod tests {
 
         // An invalid offset.
         match rnglists.ranges(RangeListsOffset(buf.len() + 1), version, 4, 0x01000000) {
-            Err(Error::UnexpectedEof) => {}
+            Err(Error::UnexpectedEof(_)) => {}
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         }
     }
@@ -0,0 +1,421 @@
+//! A collection of related DWARF files that refer to each other.
+
+use constants;
+use dwarf::{Dwarf, Unit};
+use parser::Result;
+use reader::Reader;
+use unit::{AttributeValue, CompilationUnitHeader, DebugInfoOffset};
+use vec::Vec;
+
+/// Identifies one of the files in a `DwarfSession`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DwarfFile {
+    /// The main file.
+    Main,
+
+    /// The supplementary file, such as the file named by `.gnu_debugaltlink`
+    /// for files produced by `dwz`.
+    Supplementary,
+
+    /// A split DWARF object (a `.dwo` or `.dwp` file), identified by the
+    /// order in which it was added to the session.
+    Split(usize),
+}
+
+/// The DWARF sections of a main file, together with its supplementary file
+/// and split DWARF objects.
+///
+/// This resolves attribute values that refer to other files, such as
+/// `DW_FORM_GNU_ref_alt` and `DW_FORM_GNU_strp_alt` references into the
+/// supplementary file, and skeleton units that refer to split units by
+/// their `DW_AT_GNU_dwo_id`.
+#[derive(Debug)]
+pub struct DwarfSession<R: Reader> {
+    /// The sections of the main file.
+    pub main: Dwarf<R>,
+
+    /// The sections of the supplementary file, if any.
+    pub sup: Option<Dwarf<R>>,
+
+    split: Vec<Dwarf<R>>,
+
+    // The DWO id, file index, and offset of each unit in the split files.
+    split_units: Vec<(u64, usize, DebugInfoOffset<R::Offset>)>,
+}
+
+impl<R: Reader> DwarfSession<R> {
+    /// Create a new session for the given main file.
+    pub fn new(main: Dwarf<R>) -> Self {
+        DwarfSession {
+            main,
+            sup: None,
+            split: Vec::new(),
+            split_units: Vec::new(),
+        }
+    }
+
+    /// Add a split DWARF object to the session, and return its identifier.
+    ///
+    /// The DWO ids of the units in the object are read so that they can be
+    /// found by `split_unit`. The object uses the sections of the main file
+    /// that are given by `Dwarf::set_skeleton_sections`.
+    pub fn add_split(&mut self, mut dwarf: Dwarf<R>) -> Result<DwarfFile> {
+        dwarf.set_skeleton_sections(&self.main);
+        let index = self.split.len();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            if let Some(dwo_id) = dwo_id(&dwarf, &header)? {
+                self.split_units.push((dwo_id, index, header.offset()));
+            }
+        }
+        self.split.push(dwarf);
+        Ok(DwarfFile::Split(index))
+    }
+
+    /// Return the sections of the given file, if it is present in the session.
+    pub fn file(&self, file: DwarfFile) -> Option<&Dwarf<R>> {
+        match file {
+            DwarfFile::Main => Some(&self.main),
+            DwarfFile::Supplementary => self.sup.as_ref(),
+            DwarfFile::Split(index) => self.split.get(index),
+        }
+    }
+
+    /// Try to return an attribute value from the given file as a string.
+    ///
+    /// In addition to the forms supported by `Dwarf::attr_string`, this
+    /// supports `DW_FORM_strp_sup` and `DW_FORM_GNU_strp_alt` references into
+    /// the `.debug_str` section of the supplementary file.
+    pub fn attr_string(&self, file: DwarfFile, attr: &AttributeValue<R>) -> Option<R> {
+        match *attr {
+            AttributeValue::DebugStrRefSup(offset) => self.sup
+                .as_ref()
+                .and_then(|sup| sup.debug_str.get_str(offset).ok()),
+            _ => self.file(file).and_then(|dwarf| dwarf.attr_string(attr)),
+        }
+    }
+
+    /// Try to return the file and `.debug_info` offset of the entry that is
+    /// referred to by an attribute value.
+    ///
+    /// `file` and `unit` identify the unit that contains the attribute.
+    /// `DW_FORM_ref_sup*` and `DW_FORM_GNU_ref_alt` references are resolved
+    /// to the supplementary file, if it is present in the session.
+    pub fn attr_reference(
+        &self,
+        file: DwarfFile,
+        unit: &CompilationUnitHeader<R, R::Offset>,
+        attr: &AttributeValue<R>,
+    ) -> Option<(DwarfFile, DebugInfoOffset<R::Offset>)> {
+        match *attr {
+            AttributeValue::UnitRef(offset) => Some((file, offset.to_debug_info_offset(unit))),
+            AttributeValue::DebugInfoRef(offset) => Some((file, offset)),
+            AttributeValue::DebugInfoRefSup(offset) => {
+                self.sup.as_ref().map(|_| (DwarfFile::Supplementary, offset))
+            }
+            _ => None,
+        }
+    }
+
+    /// Find the split unit corresponding to a skeleton unit in the main file.
+    ///
+    /// Returns `None` if the skeleton unit has no DWO id, or if
+    /// no split DWARF object in the session contains a matching unit.
+    ///
+    /// The split unit inherits attributes of the skeleton unit using
+    /// `Unit::copy_skeleton_attributes`, so that the indices and offsets in
+    /// the split unit are resolved correctly.
+    pub fn split_unit(&self, skeleton: &Unit<R>) -> Result<Option<(DwarfFile, Unit<R>)>> {
+        let dwo_id = match skeleton.dwo_id {
+            Some(dwo_id) => dwo_id,
+            None => return Ok(None),
+        };
+        let (index, offset) = match self.split_units.iter().find(|unit| unit.0 == dwo_id) {
+            Some(&(_, index, offset)) => (index, offset),
+            None => return Ok(None),
+        };
+        let dwarf = &self.split[index];
+        let header = dwarf.debug_info.header_from_offset(offset)?;
+        let mut unit = dwarf.unit(header)?;
+        unit.copy_skeleton_attributes(skeleton);
+        Ok(Some((DwarfFile::Split(index), unit)))
+    }
+}
+
+/// Read the DWO id from a unit's header, or from the `DW_AT_GNU_dwo_id`
+/// attribute of its root entry.
+fn dwo_id<R: Reader>(
+    dwarf: &Dwarf<R>,
+    header: &CompilationUnitHeader<R, R::Offset>,
+) -> Result<Option<u64>> {
+    if let Some(dwo_id) = header.dwo_id() {
+        return Ok(Some(dwo_id));
+    }
+    let abbreviations = dwarf.abbreviations(header)?;
+    let mut cursor = header.entries(&abbreviations);
+    cursor.set_limits(&dwarf.limits);
+    cursor.next_dfs()?;
+    let root = match cursor.current() {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    Ok(root.attr(constants::DW_AT_GNU_dwo_id)?
+        .and_then(|attr| attr.udata_value()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use rnglists::{AddressAdjustment, AddressIndex, DebugAddrBase};
+    use str::DebugStrOffset;
+    use SectionId;
+
+    fn dwarf<'input>(
+        debug_abbrev: &'input [u8],
+        debug_info: &'input [u8],
+        debug_str: &'input [u8],
+    ) -> Dwarf<EndianBuf<'input, LittleEndian>> {
+        Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => debug_abbrev,
+                SectionId::DebugInfo => debug_info,
+                SectionId::DebugStr => debug_str,
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_name, DW_FORM_GNU_strp_alt.
+            0x03, 0xa1, 0x3e,
+            // DW_AT_type, DW_FORM_GNU_ref_alt.
+            0x49, 0xa0, 0x3e,
+            // DW_AT_GNU_dwo_id, DW_FORM_data8.
+            0xb1, 0x42, 0x07,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x18, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x02, 0x00, 0x00, 0x00,
+            0x0b, 0x00, 0x00, 0x00,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_GNU_dwo_id, DW_FORM_data8.
+            0xb1, 0x42, 0x07,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x10, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry with a different DWO id.
+            0x01,
+            0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+
+            // Unit length, version, abbrev offset, address size.
+            0x10, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry with a matching DWO id.
+            0x01,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ];
+
+        let sup_debug_str = b"a\0bc\0";
+
+        let mut session = DwarfSession::new(dwarf(&debug_abbrev, &debug_info, &[]));
+        let header = session.main.units().next().unwrap().unwrap();
+        let unit = session.main.unit(header).unwrap();
+        let (name, reference) = {
+            let mut entries = unit.entries();
+            let (_, root) = entries.next_dfs().unwrap().unwrap();
+            (
+                root.attr_value(constants::DW_AT_name).unwrap().unwrap(),
+                root.attr_value(constants::DW_AT_type).unwrap().unwrap(),
+            )
+        };
+
+        assert_eq!(session.attr_string(DwarfFile::Main, &name), None);
+        assert_eq!(
+            session.attr_reference(DwarfFile::Main, &unit.header, &reference),
+            None
+        );
+        assert!(session.split_unit(&unit).unwrap().is_none());
+
+        session.sup = Some(dwarf(&[], &[], sup_debug_str));
+        assert_eq!(
+            session.attr_string(DwarfFile::Main, &name),
+            Some(EndianBuf::new(b"bc", LittleEndian))
+        );
+        assert_eq!(
+            session.attr_reference(DwarfFile::Main, &unit.header, &reference),
+            Some((DwarfFile::Supplementary, DebugInfoOffset(0x0b)))
+        );
+        assert_eq!(
+            session.attr_string(
+                DwarfFile::Supplementary,
+                &AttributeValue::DebugStrRef(DebugStrOffset(0))
+            ),
+            Some(EndianBuf::new(b"a", LittleEndian))
+        );
+
+        let file = session
+            .add_split(dwarf(&split_debug_abbrev, &split_debug_info, &[]))
+            .unwrap();
+        assert_eq!(file, DwarfFile::Split(0));
+        assert!(session.file(file).is_some());
+        let (split_file, split_unit) = session.split_unit(&unit).unwrap().unwrap();
+        assert_eq!(split_file, file);
+        assert_eq!(split_unit.header.offset(), DebugInfoOffset(0x14));
+    }
+
+    #[test]
+    fn test_split_unit_gnu_indices() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_GNU_dwo_id, DW_FORM_data8.
+            0xb1, 0x42, 0x07,
+            // DW_AT_GNU_addr_base, DW_FORM_sec_offset.
+            0xb3, 0x42, 0x17,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x18, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x00, 0x10, 0x00, 0x00,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x04, 0x00, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_addr = [
+            // Entries for another unit.
+            0xff, 0xff, 0xff, 0xff,
+            // Entries for the skeleton unit.
+            0x00, 0x10, 0x00, 0x00,
+            0x00, 0x20, 0x00, 0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            // DW_AT_name, DW_FORM_GNU_str_index.
+            0x03, 0x82, 0x3e,
+            // DW_AT_entry_pc, DW_FORM_GNU_addr_index.
+            0x52, 0x81, 0x3e,
+            // DW_AT_GNU_dwo_id, DW_FORM_data8.
+            0xb1, 0x42, 0x07,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let split_debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x12, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            0x01,
+            0x01,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ];
+
+        // The pre-standard GNU `.debug_str_offsets.dwo` section has no header.
+        let split_debug_str_offsets = [0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+        let split_debug_str = b"a\0bc\0";
+
+        let main = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugAddr => &debug_addr[..],
+                SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+        let split = Dwarf::load_dwo(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &split_debug_abbrev[..],
+                SectionId::DebugInfo => &split_debug_info[..],
+                SectionId::DebugStr => &split_debug_str[..],
+                SectionId::DebugStrOffsets => &split_debug_str_offsets[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        let mut session = DwarfSession::new(main);
+        let file = session.add_split(split).unwrap();
+        let header = session.main.units().next().unwrap().unwrap();
+        let skeleton = session.main.unit(header).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(0x0807060504030201));
+        assert_eq!(skeleton.addr_base, DebugAddrBase(4));
+
+        let (split_file, split_unit) = session.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split_file, file);
+        assert_eq!(split_unit.name, Some(EndianBuf::new(b"bc", LittleEndian)));
+        assert_eq!(split_unit.low_pc, 0x1000);
+        assert_eq!(split_unit.addr_base, DebugAddrBase(4));
+
+        let entry_pc = {
+            let mut entries = split_unit.entries();
+            let (_, root) = entries.next_dfs().unwrap().unwrap();
+            root.attr_value(constants::DW_AT_entry_pc).unwrap().unwrap()
+        };
+        assert_eq!(entry_pc, AttributeValue::DebugAddrIndex(AddressIndex(1)));
+        let split = session.file(split_file).unwrap();
+        assert_eq!(split.attr_address(&split_unit, &entry_pc), Some(0x2000));
+
+        // The split unit uses the address adjustment of the skeleton unit.
+        let adjustment = AddressAdjustment::new(|address| Some(address + 0x100));
+        session.main.address_adjustment = Some(adjustment);
+        let skeleton = session.main.unit(header).unwrap();
+        let (_, split_unit) = session.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split_unit.low_pc, 0x1000);
+        let split = session.file(split_file).unwrap();
+        assert_eq!(split.attr_address(&split_unit, &entry_pc), Some(0x2100));
+    }
+}
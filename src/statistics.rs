@@ -0,0 +1,238 @@
+//! Aggregate statistics about DWARF debugging information.
+
+use btree_map::BTreeMap;
+use constants;
+use dwarf::Dwarf;
+use parser::Result;
+use reader::{Reader, ReaderOffset};
+use unit::AttributeValue;
+use vec::Vec;
+use {Section, SectionId};
+
+/// Aggregate statistics about the DWARF debugging information in a `Dwarf`,
+/// as returned by `Dwarf::statistics`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    /// The size in bytes of each non-empty section.
+    pub section_sizes: BTreeMap<SectionId, u64>,
+
+    /// The number of units in the `.debug_info` section.
+    pub units: usize,
+
+    /// The number of entries in all units, excluding null entries.
+    pub entries: usize,
+
+    /// The number of entries for each tag.
+    pub tags: BTreeMap<constants::DwTag, usize>,
+
+    /// The number of `DW_TAG_variable` and `DW_TAG_formal_parameter` entries.
+    pub variables: usize,
+
+    /// The number of `DW_TAG_variable` and `DW_TAG_formal_parameter` entries
+    /// that have a `DW_AT_location` or `DW_AT_const_value` attribute.
+    pub variables_with_location: usize,
+
+    /// The number of `DW_TAG_inlined_subroutine` entries.
+    pub inlined_subroutines: usize,
+
+    /// The number of distinct functions referenced by the
+    /// `DW_AT_abstract_origin` attributes of `DW_TAG_inlined_subroutine`
+    /// entries.
+    pub inlined_functions: usize,
+
+    /// The number of distinct abbreviation tables used by the units.
+    ///
+    /// This is less than `units` if some units share an abbreviation table.
+    pub abbreviation_tables: usize,
+
+    /// The number of abbreviations declared in all of the abbreviation
+    /// tables used by the units.
+    pub abbreviations: usize,
+}
+
+impl Statistics {
+    /// Return the number of `DW_TAG_variable` and `DW_TAG_formal_parameter`
+    /// entries that have no location.
+    pub fn variables_without_location(&self) -> usize {
+        self.variables - self.variables_with_location
+    }
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Walk all of the units in the `.debug_info` section and return
+    /// aggregate statistics about them.
+    ///
+    /// ```
+    /// use gimli::{Dwarf, EndianBuf, LittleEndian};
+    ///
+    /// let dwarf = Dwarf::load(|_| -> gimli::Result<_> {
+    ///     Ok(EndianBuf::new(&[], LittleEndian))
+    /// }).unwrap();
+    /// let statistics = dwarf.statistics().unwrap();
+    /// assert_eq!(statistics.units, 0);
+    /// ```
+    pub fn statistics(&self) -> Result<Statistics> {
+        let mut statistics = Statistics::default();
+        {
+            let mut add_section = |id, len: R::Offset| {
+                let len = len.into_u64();
+                if len != 0 {
+                    statistics.section_sizes.insert(id, len);
+                }
+            };
+            add_section(SectionId::DebugAbbrev, self.debug_abbrev.reader().len());
+            add_section(SectionId::DebugInfo, self.debug_info.reader().len());
+            add_section(SectionId::DebugLine, self.debug_line.reader().len());
+            add_section(SectionId::DebugStr, self.debug_str.reader().len());
+            add_section(SectionId::DebugTypes, self.debug_types.reader().len());
+            add_section(SectionId::DebugLoc, self.locations.debug_loc.reader().len());
+            add_section(
+                SectionId::DebugLocLists,
+                self.locations.debug_loclists.reader().len(),
+            );
+            add_section(SectionId::DebugRanges, self.ranges.debug_ranges.reader().len());
+            add_section(
+                SectionId::DebugRngLists,
+                self.ranges.debug_rnglists.reader().len(),
+            );
+        }
+
+        let mut abbreviation_offsets = Vec::new();
+        let mut inlined_functions = Vec::new();
+        let mut units = self.units();
+        while let Some(header) = units.next()? {
+            statistics.units += 1;
+
+            let abbreviations = self.abbreviations(&header)?;
+            let abbreviation_offset = header.debug_abbrev_offset().0;
+            if !abbreviation_offsets.contains(&abbreviation_offset) {
+                abbreviation_offsets.push(abbreviation_offset);
+                statistics.abbreviations += abbreviations.len();
+            }
+
+            let mut entries = header.entries(&abbreviations);
+            entries.set_limits(&self.limits);
+            while let Some((_, entry)) = entries.next_dfs()? {
+                statistics.entries += 1;
+                *statistics.tags.entry(entry.tag()).or_insert(0) += 1;
+                match entry.tag() {
+                    constants::DW_TAG_variable | constants::DW_TAG_formal_parameter => {
+                        statistics.variables += 1;
+                        if entry.attr(constants::DW_AT_location)?.is_some()
+                            || entry.attr(constants::DW_AT_const_value)?.is_some()
+                        {
+                            statistics.variables_with_location += 1;
+                        }
+                    }
+                    constants::DW_TAG_inlined_subroutine => {
+                        statistics.inlined_subroutines += 1;
+                        match entry.attr_value_raw(constants::DW_AT_abstract_origin)? {
+                            Some(AttributeValue::UnitRef(offset)) => {
+                                inlined_functions.push(offset.to_debug_info_offset(&header).0);
+                            }
+                            Some(AttributeValue::DebugInfoRef(offset)) => {
+                                inlined_functions.push(offset.0);
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        statistics.abbreviation_tables = abbreviation_offsets.len();
+        inlined_functions.sort();
+        inlined_functions.dedup();
+        statistics.inlined_functions = inlined_functions.len();
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+
+    #[test]
+    fn test_statistics() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x34, 0x00,
+            // DW_AT_location, DW_FORM_exprloc.
+            0x02, 0x18,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x05, 0x00,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x1d, 0x00,
+            // DW_AT_abstract_origin, DW_FORM_ref4.
+            0x31, 0x13,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x17, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            // Variable with location.
+            0x02, 0x01, 0x50,
+            // Parameter without location.
+            0x03,
+            // Two inlined subroutines with the same abstract origin.
+            0x04, 0x0b, 0x00, 0x00, 0x00,
+            0x04, 0x0b, 0x00, 0x00, 0x00,
+            // End of children.
+            0x00,
+
+            // Unit length, version, abbrev offset, address size.
+            0x09, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            // End of children.
+            0x00,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        let statistics = dwarf.statistics().unwrap();
+        let mut section_sizes = BTreeMap::new();
+        section_sizes.insert(SectionId::DebugAbbrev, debug_abbrev.len() as u64);
+        section_sizes.insert(SectionId::DebugInfo, debug_info.len() as u64);
+        assert_eq!(statistics.section_sizes, section_sizes);
+        assert_eq!(statistics.units, 2);
+        assert_eq!(statistics.entries, 6);
+        assert_eq!(statistics.tags.get(&constants::DW_TAG_compile_unit), Some(&2));
+        assert_eq!(statistics.tags.get(&constants::DW_TAG_variable), Some(&1));
+        assert_eq!(statistics.tags.get(&constants::DW_TAG_formal_parameter), Some(&1));
+        assert_eq!(statistics.tags.get(&constants::DW_TAG_inlined_subroutine), Some(&2));
+        assert_eq!(statistics.tags.len(), 4);
+        assert_eq!(statistics.variables, 2);
+        assert_eq!(statistics.variables_with_location, 1);
+        assert_eq!(statistics.variables_without_location(), 1);
+        assert_eq!(statistics.inlined_subroutines, 2);
+        assert_eq!(statistics.inlined_functions, 1);
+        assert_eq!(statistics.abbreviation_tables, 1);
+        assert_eq!(statistics.abbreviations, 4);
+    }
+}
@@ -1,12 +1,50 @@
+use btree_map::BTreeMap;
+use rc::Rc;
+use std::cell::RefCell;
+
 use endianity::{EndianBuf, Endianity};
-use parser::Result;
-use reader::Reader;
-use Section;
+use fallible_iterator::FallibleIterator;
+use parser::{self, Error, Format, Result};
+use reader::{Reader, ReaderOffset};
+use {Section, SectionId};
 
 /// An offset into the `.debug_str` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugStrOffset<T = usize>(pub T);
 
+/// An offset into the `.debug_line_str` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugLineStrOffset<T = usize>(pub T);
+
+/// An offset to a set of entries in the `.debug_str_offsets` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugStrOffsetsBase<T = usize>(pub T);
+
+impl<T: ReaderOffset> DebugStrOffsetsBase<T> {
+    /// Return the default `DW_AT_str_offsets_base` for a unit in a split DWARF
+    /// object, which doesn't have this attribute.
+    ///
+    /// For DWARF version 5, the unit's entries follow the header at the start
+    /// of the `.debug_str_offsets.dwo` section. The pre-standard GNU split
+    /// DWARF extension to DWARF version 4 doesn't have a header, so the entries
+    /// start at offset 0.
+    pub fn default_for_dwo(version: u16, format: Format) -> Self {
+        if version >= 5 {
+            DebugStrOffsetsBase(T::from_u8(DebugStrOffsetsHeader::<T>::size_of_header(format)))
+        } else {
+            DebugStrOffsetsBase(T::from_u8(0))
+        }
+    }
+}
+
+/// An index into a set of entries in the `.debug_str_offsets` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DebugStrOffsetsIndex<T = usize>(pub T);
+
 /// The `DebugStr` struct represents the DWARF strings
 /// found in the `.debug_str` section.
 #[derive(Debug, Clone, Copy)]
@@ -55,11 +93,42 @@ impl<R: Reader> DebugStr<R> {
         input.skip(offset.0)?;
         input.read_null_terminated_slice()
     }
+
+    /// Iterate over all of the strings in the `.debug_str` section, in the
+    /// order that they occur.
+    ///
+    /// Each item is the offset of a string and the string itself, not
+    /// including the null terminator. Strings that are only referenced by an
+    /// offset into the middle of another string, such as when a linker merges
+    /// strings with a common suffix, are not returned separately.
+    ///
+    /// ```
+    /// use gimli::{DebugStr, DebugStrOffset, LittleEndian};
+    ///
+    /// let buf = [b'a', 0x00, b'b', b'c', 0x00];
+    /// let debug_str = DebugStr::new(&buf, LittleEndian);
+    /// let mut strings = debug_str.strings();
+    /// let (offset, string) = strings.next().unwrap().unwrap();
+    /// assert_eq!((offset, string.buf()), (DebugStrOffset(0), &b"a"[..]));
+    /// let (offset, string) = strings.next().unwrap().unwrap();
+    /// assert_eq!((offset, string.buf()), (DebugStrOffset(2), &b"bc"[..]));
+    /// assert!(strings.next().unwrap().is_none());
+    /// ```
+    pub fn strings(&self) -> DebugStrIter<R> {
+        DebugStrIter {
+            input: self.debug_str_section.clone(),
+            offset: DebugStrOffset(R::Offset::from_u8(0)),
+        }
+    }
 }
 
 impl<R: Reader> Section<R> for DebugStr<R> {
-    fn section_name() -> &'static str {
-        ".debug_str"
+    fn id() -> SectionId {
+        SectionId::DebugStr
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_str_section
     }
 }
 
@@ -68,3 +137,549 @@ impl<R: Reader> From<R> for DebugStr<R> {
         DebugStr { debug_str_section }
     }
 }
+
+/// An iterator over the strings in a `.debug_str` section.
+///
+/// See [`DebugStr::strings`](./struct.DebugStr.html#method.strings).
+#[derive(Debug, Clone)]
+pub struct DebugStrIter<R: Reader> {
+    input: R,
+    offset: DebugStrOffset<R::Offset>,
+}
+
+impl<R: Reader> DebugStrIter<R> {
+    /// Return the offset of the next string that the iterator will return.
+    #[inline]
+    pub fn offset(&self) -> DebugStrOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Advance the iterator to the next string.
+    pub fn next(&mut self) -> Result<Option<(DebugStrOffset<R::Offset>, R)>> {
+        let offset = self.offset;
+        Ok(next_string(&mut self.input, &mut self.offset.0)?.map(|s| (offset, s)))
+    }
+}
+
+impl<R: Reader> FallibleIterator for DebugStrIter<R> {
+    type Item = (DebugStrOffset<R::Offset>, R);
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        DebugStrIter::next(self)
+    }
+}
+
+/// Read the next null terminated string, and advance `offset` past it.
+fn next_string<R: Reader>(input: &mut R, offset: &mut R::Offset) -> Result<Option<R>> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    match input.read_null_terminated_slice() {
+        Ok(string) => {
+            *offset += string.len() + R::Offset::from_u8(1);
+            Ok(Some(string))
+        }
+        Err(e) => {
+            input.empty();
+            Err(e)
+        }
+    }
+}
+
+/// The `DebugLineStr` struct represents the DWARF strings
+/// found in the `.debug_line_str` section.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugLineStr<R: Reader> {
+    debug_line_str_section: R,
+}
+
+impl<'input, Endian> DebugLineStr<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugLineStr` instance from the data in the
+    /// `.debug_line_str` section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_line_str` section
+    /// and present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugLineStr, LittleEndian};
+    ///
+    /// # let buf = [0x00, 0x01, 0x02, 0x03];
+    /// # let read_debug_line_str_section_somehow = || &buf;
+    /// let debug_line_str =
+    ///     DebugLineStr::new(read_debug_line_str_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_line_str_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(debug_line_str_section, endian))
+    }
+}
+
+impl<R: Reader> DebugLineStr<R> {
+    /// Lookup a string from the `.debug_line_str` section by
+    /// `DebugLineStrOffset`.
+    pub fn get_str(&self, offset: DebugLineStrOffset<R::Offset>) -> Result<R> {
+        let input = &mut self.debug_line_str_section.clone();
+        input.skip(offset.0)?;
+        input.read_null_terminated_slice()
+    }
+
+    /// Iterate over all of the strings in the `.debug_line_str` section, in
+    /// the order that they occur.
+    ///
+    /// See [`DebugStr::strings`](./struct.DebugStr.html#method.strings) for
+    /// details.
+    pub fn strings(&self) -> DebugLineStrIter<R> {
+        DebugLineStrIter {
+            input: self.debug_line_str_section.clone(),
+            offset: DebugLineStrOffset(R::Offset::from_u8(0)),
+        }
+    }
+}
+
+impl<R: Reader> Section<R> for DebugLineStr<R> {
+    fn id() -> SectionId {
+        SectionId::DebugLineStr
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_line_str_section
+    }
+}
+
+impl<R: Reader> From<R> for DebugLineStr<R> {
+    fn from(debug_line_str_section: R) -> Self {
+        DebugLineStr { debug_line_str_section }
+    }
+}
+
+/// An iterator over the strings in a `.debug_line_str` section.
+///
+/// See [`DebugLineStr::strings`](./struct.DebugLineStr.html#method.strings).
+#[derive(Debug, Clone)]
+pub struct DebugLineStrIter<R: Reader> {
+    input: R,
+    offset: DebugLineStrOffset<R::Offset>,
+}
+
+impl<R: Reader> DebugLineStrIter<R> {
+    /// Return the offset of the next string that the iterator will return.
+    #[inline]
+    pub fn offset(&self) -> DebugLineStrOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Advance the iterator to the next string.
+    pub fn next(&mut self) -> Result<Option<(DebugLineStrOffset<R::Offset>, R)>> {
+        let offset = self.offset;
+        Ok(next_string(&mut self.input, &mut self.offset.0)?.map(|s| (offset, s)))
+    }
+}
+
+impl<R: Reader> FallibleIterator for DebugLineStrIter<R> {
+    type Item = (DebugLineStrOffset<R::Offset>, R);
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        DebugLineStrIter::next(self)
+    }
+}
+
+/// A `DebugStr` that caches the strings that have been looked up, so that
+/// repeated lookups of the same offset don't need to search for the null
+/// terminator, or validate the string's UTF-8 again.
+///
+/// This is useful when the same strings are referenced by many entries, such
+/// as type names in C++ programs. Lookups that fail are not cached.
+///
+/// ```
+/// use gimli::{CachedDebugStr, DebugStr, DebugStrOffset, LittleEndian};
+///
+/// let buf = [0x01, b'i', b'n', b't', 0x00];
+/// let debug_str = CachedDebugStr::new(DebugStr::new(&buf, LittleEndian));
+/// assert_eq!(&*debug_str.get_string(DebugStrOffset(1)).unwrap(), "int");
+/// assert_eq!(debug_str.get_str(DebugStrOffset(1)).unwrap().buf(), b"int");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedDebugStr<R: Reader> {
+    debug_str: DebugStr<R>,
+    slices: RefCell<BTreeMap<R::Offset, R>>,
+    strings: RefCell<BTreeMap<R::Offset, Rc<str>>>,
+}
+
+impl<R: Reader> CachedDebugStr<R> {
+    /// Construct a new `CachedDebugStr` with an empty cache.
+    pub fn new(debug_str: DebugStr<R>) -> Self {
+        CachedDebugStr {
+            debug_str,
+            slices: RefCell::new(BTreeMap::new()),
+            strings: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Return the underlying `DebugStr`.
+    pub fn debug_str(&self) -> &DebugStr<R> {
+        &self.debug_str
+    }
+
+    /// Lookup a string from the `.debug_str` section by DebugStrOffset.
+    ///
+    /// This is equivalent to `DebugStr::get_str`.
+    pub fn get_str(&self, offset: DebugStrOffset<R::Offset>) -> Result<R> {
+        if let Some(slice) = self.slices.borrow().get(&offset.0) {
+            return Ok(slice.clone());
+        }
+        let slice = self.debug_str.get_str(offset)?;
+        self.slices.borrow_mut().insert(offset.0, slice.clone());
+        Ok(slice)
+    }
+
+    /// Lookup a string from the `.debug_str` section by DebugStrOffset, and
+    /// convert it to a `str`.
+    ///
+    /// Returns `Error::BadUtf8` if the string is not valid UTF-8.
+    pub fn get_string(&self, offset: DebugStrOffset<R::Offset>) -> Result<Rc<str>> {
+        if let Some(string) = self.strings.borrow().get(&offset.0) {
+            return Ok(string.clone());
+        }
+        let slice = self.get_str(offset)?;
+        let string: Rc<str> = Rc::from(&*slice.to_string()?);
+        self.strings.borrow_mut().insert(offset.0, string.clone());
+        Ok(string)
+    }
+
+    /// Remove all strings from the cache.
+    pub fn clear(&self) {
+        self.slices.borrow_mut().clear();
+        self.strings.borrow_mut().clear();
+    }
+}
+
+impl<R: Reader> From<DebugStr<R>> for CachedDebugStr<R> {
+    fn from(debug_str: DebugStr<R>) -> Self {
+        Self::new(debug_str)
+    }
+}
+
+/// The `DebugStrOffsets` struct represents the string offsets table found in
+/// the `.debug_str_offsets` section.
+///
+/// Each unit that uses the `DW_FORM_strx` forms has a set of entries in this
+/// section, and its `DW_AT_str_offsets_base` attribute gives the offset of the
+/// first entry. In DWARF version 5, each set of entries is preceded by a
+/// header. The pre-standard GNU split DWARF extension to DWARF version 4 uses
+/// the `.debug_str_offsets.dwo` section without any headers.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugStrOffsets<R: Reader> {
+    debug_str_offsets_section: R,
+}
+
+impl<'input, Endian> DebugStrOffsets<EndianBuf<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugStrOffsets` instance from the data in the
+    /// `.debug_str_offsets` section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_str_offsets`
+    /// section and present it as a `&[u8]` slice. That means using some ELF
+    /// loader on Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugStrOffsets, LittleEndian};
+    ///
+    /// # let buf = [0x00, 0x01, 0x02, 0x03];
+    /// # let read_debug_str_offsets_section_somehow = || &buf;
+    /// let debug_str_offsets =
+    ///     DebugStrOffsets::new(read_debug_str_offsets_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_str_offsets_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianBuf::new(debug_str_offsets_section, endian))
+    }
+}
+
+impl<R: Reader> DebugStrOffsets<R> {
+    /// Return the offset into the `.debug_str` section of the string at the
+    /// given index of a unit's entries.
+    ///
+    /// `format` must be the format of the unit, and `base` must be the unit's
+    /// `DW_AT_str_offsets_base` attribute, or
+    /// `DebugStrOffsetsBase::default_for_dwo` for split units.
+    ///
+    /// ```
+    /// use gimli::{DebugStrOffset, DebugStrOffsets, DebugStrOffsetsBase, DebugStrOffsetsIndex,
+    ///             Format, LittleEndian};
+    ///
+    /// // A pre-standard GNU split DWARF table, which has no header.
+    /// let buf = [0x10, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00];
+    /// let debug_str_offsets = DebugStrOffsets::new(&buf, LittleEndian);
+    /// let base = DebugStrOffsetsBase::default_for_dwo(4, Format::Dwarf32);
+    /// let offset = debug_str_offsets.get_str_offset(Format::Dwarf32, base, DebugStrOffsetsIndex(1));
+    /// assert_eq!(offset, Ok(DebugStrOffset(0x20)));
+    /// ```
+    pub fn get_str_offset(
+        &self,
+        format: Format,
+        base: DebugStrOffsetsBase<R::Offset>,
+        index: DebugStrOffsetsIndex<R::Offset>,
+    ) -> Result<DebugStrOffset<R::Offset>> {
+        let input = &mut self.debug_str_offsets_section.clone();
+        input.skip(base.0)?;
+        let offset = index
+            .0
+            .into_u64()
+            .checked_mul(u64::from(format.word_size()))
+            .ok_or(Error::OffsetOutOfBounds)?;
+        input.skip(R::Offset::from_u64(offset)?)?;
+        input.read_offset(format).map(DebugStrOffset)
+    }
+
+    /// Iterate the headers of the sets of entries in the section.
+    ///
+    /// This requires every set of entries to have a DWARF version 5 header,
+    /// so it can't be used for the pre-standard GNU split DWARF extension.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn headers(&self) -> DebugStrOffsetsHeadersIter<R> {
+        DebugStrOffsetsHeadersIter {
+            input: self.debug_str_offsets_section.clone(),
+            offset: R::Offset::from_u8(0),
+        }
+    }
+}
+
+impl<R: Reader> Section<R> for DebugStrOffsets<R> {
+    fn id() -> SectionId {
+        SectionId::DebugStrOffsets
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_str_offsets_section
+    }
+}
+
+impl<R: Reader> From<R> for DebugStrOffsets<R> {
+    fn from(debug_str_offsets_section: R) -> Self {
+        DebugStrOffsets { debug_str_offsets_section }
+    }
+}
+
+/// The header of a set of entries in the `.debug_str_offsets` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugStrOffsetsHeader<T = usize> {
+    offset: T,
+    length: T,
+    format: Format,
+    version: u16,
+}
+
+impl<T: ReaderOffset> DebugStrOffsetsHeader<T> {
+    /// Return the serialized size of the header for the given format.
+    pub fn size_of_header(format: Format) -> u8 {
+        // initial_length + version + padding
+        format.initial_length_size() + 2 + 2
+    }
+
+    /// Return the offset of this header within the section.
+    pub fn offset(&self) -> T {
+        self.offset
+    }
+
+    /// Return the length of the entries, including the version and padding
+    /// fields, but not including the initial length field.
+    pub fn length(&self) -> T {
+        self.length
+    }
+
+    /// Return the format of the entries.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Return the version of the header.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the offset of the first entry, which is the value that units
+    /// use for their `DW_AT_str_offsets_base` attribute.
+    pub fn base(&self) -> DebugStrOffsetsBase<T> {
+        DebugStrOffsetsBase(self.offset + T::from_u8(Self::size_of_header(self.format)))
+    }
+
+    /// Return the number of entries.
+    pub fn count(&self) -> u64 {
+        (self.length.into_u64() - 4) / u64::from(self.format.word_size())
+    }
+}
+
+/// An iterator over the headers in the `.debug_str_offsets` section.
+#[derive(Debug, Clone)]
+pub struct DebugStrOffsetsHeadersIter<R: Reader> {
+    input: R,
+    offset: R::Offset,
+}
+
+impl<R: Reader> DebugStrOffsetsHeadersIter<R> {
+    /// Advance the iterator to the next header.
+    pub fn next(&mut self) -> Result<Option<DebugStrOffsetsHeader<R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse_header() {
+            Ok(header) => {
+                self.offset += R::Offset::from_u8(header.format.initial_length_size()) +
+                    header.length;
+                Ok(Some(header))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse_header(&mut self) -> Result<DebugStrOffsetsHeader<R::Offset>> {
+        let (length, format) = parser::parse_initial_length(&mut self.input)?;
+        let length = R::Offset::from_u64(length)?;
+        let mut rest = self.input.split(length)?;
+
+        let version = rest.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+        let _padding = rest.read_u16()?;
+
+        Ok(DebugStrOffsetsHeader {
+            offset: self.offset,
+            length,
+            format,
+            version,
+        })
+    }
+}
+
+impl<R: Reader> FallibleIterator for DebugStrOffsetsHeadersIter<R> {
+    type Item = DebugStrOffsetsHeader<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        DebugStrOffsetsHeadersIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+    use parser::Error;
+
+    #[test]
+    fn test_debug_line_str_strings() {
+        let buf = [b'a', 0x00, 0x00, b'b', b'c', 0x00, b'd'];
+        let debug_line_str = DebugLineStr::new(&buf, LittleEndian);
+        let mut strings = debug_line_str.strings();
+        let mut next = || strings.next().map(|s| s.map(|(offset, s)| (offset, s.buf())));
+        assert_eq!(next(), Ok(Some((DebugLineStrOffset(0), &b"a"[..]))));
+        assert_eq!(next(), Ok(Some((DebugLineStrOffset(2), &b""[..]))));
+        assert_eq!(next(), Ok(Some((DebugLineStrOffset(3), &b"bc"[..]))));
+        assert!(next().is_err());
+        assert_eq!(next(), Ok(None));
+
+        assert_eq!(
+            debug_line_str.get_str(DebugLineStrOffset(4)),
+            Ok(EndianBuf::new(b"c", LittleEndian))
+        );
+    }
+
+    #[test]
+    fn test_debug_str_offsets_headers() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // A DWARF 32 set of entries.
+            0x0c, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0x20, 0x00, 0x00, 0x00,
+            // A DWARF 64 set of entries.
+            0xff, 0xff, 0xff, 0xff,
+            0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x05, 0x00,
+            0x00, 0x00,
+            0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let debug_str_offsets = DebugStrOffsets::new(&buf, LittleEndian);
+
+        let mut headers = debug_str_offsets.headers();
+        let first = headers.next().unwrap().unwrap();
+        assert_eq!(first.offset(), 0);
+        assert_eq!(first.format(), Format::Dwarf32);
+        assert_eq!(first.version(), 5);
+        assert_eq!(first.count(), 2);
+        assert_eq!(first.base(), DebugStrOffsetsBase(8));
+        assert_eq!(
+            debug_str_offsets.get_str_offset(Format::Dwarf32, first.base(), DebugStrOffsetsIndex(1)),
+            Ok(DebugStrOffset(0x20))
+        );
+
+        let second = headers.next().unwrap().unwrap();
+        assert_eq!(second.offset(), 0x10);
+        assert_eq!(second.format(), Format::Dwarf64);
+        assert_eq!(second.count(), 1);
+        assert_eq!(second.base(), DebugStrOffsetsBase(0x20));
+        assert_eq!(
+            debug_str_offsets.get_str_offset(Format::Dwarf64, second.base(), DebugStrOffsetsIndex(0)),
+            Ok(DebugStrOffset(0x30))
+        );
+
+        assert_eq!(headers.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_debug_str_offsets_default_for_dwo() {
+        assert_eq!(
+            DebugStrOffsetsBase::<usize>::default_for_dwo(4, Format::Dwarf32),
+            DebugStrOffsetsBase(0)
+        );
+        assert_eq!(
+            DebugStrOffsetsBase::<usize>::default_for_dwo(5, Format::Dwarf32),
+            DebugStrOffsetsBase(8)
+        );
+        assert_eq!(
+            DebugStrOffsetsBase::<usize>::default_for_dwo(5, Format::Dwarf64),
+            DebugStrOffsetsBase(16)
+        );
+    }
+
+    #[test]
+    fn test_cached_debug_str() {
+        let buf = [b'a', b'b', 0x00, 0xff, 0x00, b'c'];
+        let debug_str = CachedDebugStr::new(DebugStr::new(&buf, LittleEndian));
+
+        let first = debug_str.get_string(DebugStrOffset(0)).unwrap();
+        let second = debug_str.get_string(DebugStrOffset(0)).unwrap();
+        assert_eq!(&*first, "ab");
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(&*debug_str.get_string(DebugStrOffset(1)).unwrap(), "b");
+
+        assert_eq!(
+            debug_str.get_str(DebugStrOffset(3)),
+            Ok(EndianBuf::new(&buf[3..4], LittleEndian))
+        );
+        assert_eq!(debug_str.get_string(DebugStrOffset(3)), Err(Error::BadUtf8));
+        match debug_str.get_str(DebugStrOffset(5)) {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+
+        debug_str.clear();
+        let third = debug_str.get_string(DebugStrOffset(0)).unwrap();
+        assert!(!Rc::ptr_eq(&first, &third));
+    }
+}
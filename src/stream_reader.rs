@@ -0,0 +1,484 @@
+//! A `Reader` implementation for seekable streams.
+
+use borrow::Cow;
+use rc::Rc;
+use std::cell::RefCell;
+use std::cmp;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::mem;
+use string::String;
+use vec::Vec;
+
+use endianity::Endianity;
+use parser::{Error, Result};
+use reader::{find_byte, Reader, ReaderOffsetId};
+
+/// The default number of bytes that a `StreamReader` buffers.
+const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// A stream shared by all of the readers that are cloned from a
+/// `StreamReader`, along with the most recently read chunk of it.
+struct StreamBuffer<S> {
+    stream: S,
+    // The stream offset of `buf[0]`.
+    buf_offset: u64,
+    buf: Vec<u8>,
+    capacity: usize,
+}
+
+impl<S: Read + Seek> StreamBuffer<S> {
+    /// Fill `out` with the bytes at `offset` in the stream.
+    fn read_at(&mut self, offset: u64, out: &mut [u8]) -> Result<()> {
+        let len = out.len() as u64;
+        if offset < self.buf_offset || offset + len > self.buf_offset + self.buf.len() as u64 {
+            if out.len() > self.capacity {
+                // Too large to buffer, so read it directly.
+                self.stream.seek(SeekFrom::Start(offset))?;
+                return read_all(&mut self.stream, offset, out);
+            }
+            self.fill(offset)?;
+            let buf_end = self.buf_offset + self.buf.len() as u64;
+            if offset + len > buf_end {
+                return Err(Error::UnexpectedEof(ReaderOffsetId(buf_end)));
+            }
+        }
+        let start = (offset - self.buf_offset) as usize;
+        out.copy_from_slice(&self.buf[start..start + out.len()]);
+        Ok(())
+    }
+
+    /// Replace the buffer with up to `capacity` bytes starting at `offset`.
+    fn fill(&mut self, offset: u64) -> Result<()> {
+        self.buf.clear();
+        self.buf_offset = offset;
+        self.stream.seek(SeekFrom::Start(offset))?;
+        let capacity = self.capacity as u64;
+        (&mut self.stream).take(capacity).read_to_end(&mut self.buf)?;
+        Ok(())
+    }
+}
+
+fn read_all<S: Read>(stream: &mut S, mut offset: u64, mut out: &mut [u8]) -> Result<()> {
+    while !out.is_empty() {
+        match stream.read(out)? {
+            0 => return Err(Error::UnexpectedEof(ReaderOffsetId(offset))),
+            n => {
+                let tmp = out;
+                out = &mut tmp[n..];
+                offset += n as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A `Reader` for a section that is read on demand from a seekable stream,
+/// such as a `std::fs::File`.
+///
+/// This avoids needing to read or map the whole section into memory, at the
+/// cost of performing I/O during parsing. Recently read data is kept in a
+/// buffer, so sequential reads only access the stream once per buffer's worth
+/// of data.
+///
+/// Clones of a reader share the stream and its buffer, so a `StreamReader`
+/// can't be sent to other threads. I/O errors are reported as `Error::Io`.
+///
+/// Offsets are `u64`, so sections larger than the address space can be
+/// parsed. The `ReaderOffsetId`s of a reader are positions within its stream,
+/// so they are only unique amongst readers for the same stream.
+///
+/// ```
+/// use gimli::{DebugStr, DebugStrOffset, LittleEndian, Reader, StreamReader};
+/// use std::io::Cursor;
+///
+/// // Usually this would be a `File` containing the object file.
+/// let file = Cursor::new(b"\x7fELF...\0main\0".to_vec());
+///
+/// // The `.debug_str` section starts at offset 7 and is 6 bytes long.
+/// let reader = StreamReader::new(file, 7, 6, LittleEndian);
+/// let debug_str = DebugStr::from(reader);
+/// let name = debug_str.get_str(DebugStrOffset(1)).unwrap();
+/// assert_eq!(name.to_string().unwrap(), "main");
+/// ```
+pub struct StreamReader<S, Endian>
+where
+    S: Read + Seek,
+    Endian: Endianity,
+{
+    stream: Rc<RefCell<StreamBuffer<S>>>,
+    start: u64,
+    end: u64,
+    endian: Endian,
+}
+
+impl<S, Endian> StreamReader<S, Endian>
+where
+    S: Read + Seek,
+    Endian: Endianity,
+{
+    /// Construct a new `StreamReader` for the `len` bytes of `stream` that
+    /// start at `offset`.
+    pub fn new(stream: S, offset: u64, len: u64, endian: Endian) -> Self {
+        Self::with_capacity(stream, offset, len, endian, DEFAULT_CAPACITY)
+    }
+
+    /// Construct a new `StreamReader` that buffers up to `capacity` bytes of
+    /// the stream at a time.
+    pub fn with_capacity(
+        stream: S,
+        offset: u64,
+        len: u64,
+        endian: Endian,
+        capacity: usize,
+    ) -> Self {
+        let buffer = StreamBuffer {
+            stream,
+            buf_offset: 0,
+            buf: Vec::new(),
+            capacity: cmp::max(capacity, 16),
+        };
+        StreamReader {
+            stream: Rc::new(RefCell::new(buffer)),
+            start: offset,
+            end: offset.saturating_add(len),
+            endian,
+        }
+    }
+
+    /// Read all of the remaining bytes, without advancing.
+    fn to_vec(&self) -> Result<Vec<u8>> {
+        let len = self.len();
+        if len > usize::max_value() as u64 {
+            return Err(Error::UnsupportedOffset);
+        }
+        let mut bytes = vec![0; len as usize];
+        self.stream.borrow_mut().read_at(self.start, &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl<S, Endian> Clone for StreamReader<S, Endian>
+where
+    S: Read + Seek,
+    Endian: Endianity,
+{
+    fn clone(&self) -> Self {
+        StreamReader {
+            stream: self.stream.clone(),
+            start: self.start,
+            end: self.end,
+            endian: self.endian,
+        }
+    }
+}
+
+impl<S, Endian> fmt::Debug for StreamReader<S, Endian>
+where
+    S: Read + Seek,
+    Endian: Endianity,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("endian", &self.endian)
+            .finish()
+    }
+}
+
+impl<S, Endian> Reader for StreamReader<S, Endian>
+where
+    S: Read + Seek,
+    Endian: Endianity,
+{
+    type Endian = Endian;
+    type Offset = u64;
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    #[inline]
+    fn empty(&mut self) {
+        self.start = self.end;
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<()> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.end = self.start + len;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn offset_from(&self, base: &Self) -> Result<u64> {
+        if base.start <= self.start && self.end <= base.end {
+            Ok(self.start - base.start)
+        } else {
+            Err(Error::OffsetOutOfBounds)
+        }
+    }
+
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        ReaderOffsetId(self.start)
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<u64> {
+        if id.0 >= self.start && id.0 <= self.end {
+            Some(id.0 - self.start)
+        } else {
+            None
+        }
+    }
+
+    fn find(&self, byte: u8) -> Result<u64> {
+        let mut chunk = [0; 256];
+        let mut offset = self.start;
+        while offset < self.end {
+            let len = cmp::min(chunk.len() as u64, self.end - offset) as usize;
+            self.stream
+                .borrow_mut()
+                .read_at(offset, &mut chunk[..len])?;
+            if let Some(index) = find_byte(&chunk[..len], byte) {
+                return Ok(offset - self.start + index as u64);
+            }
+            offset += len as u64;
+        }
+        Err(Error::UnexpectedEof(self.offset_id()))
+    }
+
+    fn skip(&mut self, len: u64) -> Result<()> {
+        if self.len() < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.start += len;
+            Ok(())
+        }
+    }
+
+    fn split(&mut self, len: u64) -> Result<Self> {
+        if self.len() < len {
+            return Err(Error::UnexpectedEof(self.offset_id()));
+        }
+        let mut head = self.clone();
+        head.end = self.start + len;
+        self.start += len;
+        Ok(head)
+    }
+
+    fn to_slice(&self) -> Result<Cow<'_, [u8]>> {
+        self.to_vec().map(Cow::Owned)
+    }
+
+    fn to_string(&self) -> Result<Cow<'_, str>> {
+        match String::from_utf8(self.to_vec()?) {
+            Ok(s) => Ok(Cow::Owned(s)),
+            _ => Err(Error::BadUtf8),
+        }
+    }
+
+    fn to_string_lossy(&self) -> Result<Cow<'_, str>> {
+        let bytes = self.to_vec()?;
+        Ok(Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    fn read_u8_array<A>(&mut self) -> Result<A>
+    where
+        A: Sized + Default + AsMut<[u8]>,
+    {
+        let mut val: A = Default::default();
+        debug_assert_eq!(val.as_mut().len(), mem::size_of::<A>());
+        self.read_slice(val.as_mut())?;
+        Ok(val)
+    }
+
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        if self.len() < buf.len() as u64 {
+            return Err(Error::UnexpectedEof(self.offset_id()));
+        }
+        self.stream.borrow_mut().read_at(self.start, buf)?;
+        self.start += buf.len() as u64;
+        Ok(())
+    }
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut buf = [0; 1];
+        self.read_slice(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    #[inline]
+    fn read_i8(&mut self) -> Result<i8> {
+        self.read_u8().map(|v| v as i8)
+    }
+
+    #[inline]
+    fn read_u16(&mut self) -> Result<u16> {
+        let mut buf = [0; 2];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_u16(&buf))
+    }
+
+    #[inline]
+    fn read_i16(&mut self) -> Result<i16> {
+        let mut buf = [0; 2];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_i16(&buf))
+    }
+
+    #[inline]
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut buf = [0; 4];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_u32(&buf))
+    }
+
+    #[inline]
+    fn read_i32(&mut self) -> Result<i32> {
+        let mut buf = [0; 4];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_i32(&buf))
+    }
+
+    #[inline]
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut buf = [0; 8];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_u64(&buf))
+    }
+
+    #[inline]
+    fn read_i64(&mut self) -> Result<i64> {
+        let mut buf = [0; 8];
+        self.read_slice(&mut buf)?;
+        Ok(self.endian.read_i64(&buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{BigEndian, LittleEndian};
+    use fallible_iterator::FallibleIterator;
+    use std::io::Cursor;
+    use unit::DebugInfo;
+
+    fn reader(bytes: &[u8], capacity: usize) -> StreamReader<Cursor<Vec<u8>>, LittleEndian> {
+        let len = bytes.len() as u64;
+        StreamReader::with_capacity(Cursor::new(bytes.to_vec()), 0, len, LittleEndian, capacity)
+    }
+
+    #[test]
+    fn test_stream_reader_read() {
+        // Use a small buffer so that reads cross buffer boundaries.
+        let bytes: Vec<u8> = (0..64).collect();
+        let mut r = reader(&bytes, 16);
+        assert_eq!(r.read_u8(), Ok(0));
+        for i in 0..7 {
+            let b = 1 + 8 * i as u64;
+            let expected = (0..8).fold(0, |v, j| v | ((b + j) << (8 * j)));
+            assert_eq!(r.read_u64(), Ok(expected));
+        }
+        assert_eq!(r.len(), 7);
+        assert_eq!(r.read_u64(), Err(Error::UnexpectedEof(r.offset_id())));
+        assert_eq!(r.read_u32(), Ok(0x3c3b_3a39));
+        assert_eq!(r.read_u8_array::<[u8; 3]>(), Ok([0x3d, 0x3e, 0x3f]));
+        assert!(r.is_empty());
+
+        // Reads larger than the buffer bypass it.
+        let mut r = reader(&bytes, 16);
+        r.skip(4).unwrap();
+        let head = r.split(40).unwrap();
+        assert_eq!(&*head.to_slice().unwrap(), &bytes[4..44]);
+
+        let mut r = StreamReader::new(Cursor::new(vec![1, 2, 3]), 1, 2, BigEndian);
+        assert_eq!(r.read_u16(), Ok(0x0203));
+    }
+
+    #[test]
+    fn test_stream_reader_read_slice() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let mut r = reader(&bytes, 8);
+        r.skip(5).unwrap();
+        let mut buf = [0; 12];
+        assert_eq!(r.read_slice(&mut buf), Ok(()));
+        assert_eq!(&buf, &bytes[5..17]);
+        assert_eq!(r.read_slice(&mut buf), Err(Error::UnexpectedEof(r.offset_id())));
+        assert_eq!(r.read_bytes(2).map(|r| r.to_slice().unwrap().into_owned()), Ok(vec![17, 18]));
+        assert_eq!(r.read_bytes(2).map(|_| ()), Err(Error::UnexpectedEof(r.offset_id())));
+        assert_eq!(r.len(), 1);
+    }
+
+    #[test]
+    fn test_stream_reader_split() {
+        let bytes = b"abc\0defghijklmnopqrstuvwxyz\0";
+        let base = reader(bytes, 16);
+        let mut r = base.clone();
+        assert_eq!(r.find(b'z'), Ok(26));
+        let s = r.read_null_terminated_slice().unwrap();
+        assert_eq!(s.to_string().unwrap(), "abc");
+        assert_eq!(s.offset_from(&base), Ok(0));
+        assert_eq!(r.offset_from(&base), Ok(4));
+        assert_eq!(base.offset_from(&r), Err(Error::OffsetOutOfBounds));
+
+        let mut head = r.split(5).unwrap();
+        assert_eq!(head.to_string_lossy().unwrap(), "defgh");
+        head.truncate(2).unwrap();
+        assert_eq!(head.to_string().unwrap(), "de");
+        assert_eq!(head.truncate(3), Err(Error::UnexpectedEof(head.offset_id())));
+        assert_eq!(r.find(0), Ok(18));
+        assert_eq!(r.find(b'A'), Err(Error::UnexpectedEof(r.offset_id())));
+
+        r.empty();
+        assert!(r.is_empty());
+        assert_eq!(r.skip(1), Err(Error::UnexpectedEof(r.offset_id())));
+    }
+
+    #[test]
+    fn test_stream_reader_short_stream() {
+        // The stream ends before the section does.
+        let mut r = StreamReader::new(Cursor::new(vec![1, 2, 3]), 0, 8, LittleEndian);
+        assert_eq!(r.read_u16(), Ok(0x0201));
+        // The error is reported at the end of the stream.
+        assert_eq!(r.read_u16(), Err(Error::UnexpectedEof(ReaderOffsetId(3))));
+        assert_eq!(r.lookup_offset_id(ReaderOffsetId(3)), Some(1));
+        assert_eq!(r.lookup_offset_id(ReaderOffsetId(9)), None);
+    }
+
+    #[test]
+    fn test_stream_reader_debug_info() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // Unit length.
+            0x07, 0x00, 0x00, 0x00,
+            // Version.
+            0x04, 0x00,
+            // Abbreviation offset.
+            0x00, 0x00, 0x00, 0x00,
+            // Address size.
+            0x08,
+        ];
+        let debug_info = DebugInfo::from(reader(&buf, 16));
+        let units = debug_info.units().collect::<Vec<_>>().unwrap();
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].unit_length(), 7);
+        assert_eq!(units[0].address_size(), 8);
+    }
+}
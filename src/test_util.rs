@@ -1,24 +1,105 @@
-#![allow(missing_docs)]
+//! Utilities for building DWARF sections in tests.
+//!
+//! This is enabled by the `test_util` feature, and extends the `Section`
+//! type from the [`test-assembler`](https://crates.io/crates/test-assembler)
+//! crate with methods for writing values whose encoding depends on the
+//! endianity, DWARF format, or address size. This allows the same test to
+//! be run for both big and little endian data, and for both 32-bit and 64-bit
+//! DWARF.
+//!
+//! It also provides `CfiBuilder`, which writes `.debug_frame` and `.eh_frame`
+//! sections from descriptions of their CIEs and FDEs.
+//!
+//! ```
+//! use gimli::Format;
+//! use gimli::test_util::GimliSectionMethods;
+//! use gimli::test_util::test_assembler::{Endian, Label, LabelMaker, Section};
+//!
+//! for &endian in &[Endian::Little, Endian::Big] {
+//!     for &format in &[Format::Dwarf32, Format::Dwarf64] {
+//!         let length = Label::new();
+//!         let start = Label::new();
+//!         let end = Label::new();
+//!         let section = Section::with_endian(endian)
+//!             .initial_length(format, &length)
+//!             .mark(&start)
+//!             .e16(endian, 4)
+//!             .word(format, 0)
+//!             .D8(8)
+//!             .mark(&end);
+//!         length.set_const((&end - &start) as u64);
+//!         let buf = section.get_contents().unwrap();
+//!         assert_eq!(buf.len(), format.initial_length_size() as usize + 3 +
+//!                               format.word_size() as usize);
+//!     }
+//! }
+//! ```
 
-extern crate test_assembler;
+pub extern crate test_assembler;
 
+use std::fmt;
+use std::mem;
 use vec::Vec;
 
+use arch::Register;
+use cfi::{BaseAddresses, CallFrameInstruction};
+use constants;
 use leb128;
-use self::test_assembler::{Endian, Section, ToLabelOrNum};
+use parser::Format;
+use reader::Reader;
+use self::test_assembler::{Endian, Label, LabelMaker, Section, ToLabelOrNum};
 
+/// Methods for writing DWARF values to a `Section`.
 pub trait GimliSectionMethods {
+    /// Write a 16-bit value with the given endianity.
+    fn e16<'a, T>(self, endian: Endian, val: T) -> Self
+    where
+        T: ToLabelOrNum<'a, u16>;
+
+    /// Write a 32-bit value with the given endianity.
     fn e32<'a, T>(self, endian: Endian, val: T) -> Self
     where
         T: ToLabelOrNum<'a, u32>;
+
+    /// Write a 64-bit value with the given endianity.
     fn e64<'a, T>(self, endian: Endian, val: T) -> Self
     where
         T: ToLabelOrNum<'a, u64>;
+
+    /// Write a signed LEB128 value.
     fn sleb(self, val: i64) -> Self;
+
+    /// Write an unsigned LEB128 value.
     fn uleb(self, val: u64) -> Self;
+
+    /// Write an initial length field for the given format, using the
+    /// section's default endianity.
+    ///
+    /// For 64-bit DWARF, this includes the `0xffffffff` escape.
+    fn initial_length(self, format: Format, length: &Label) -> Self;
+
+    /// Write an offset or other word-sized value for the given format, using
+    /// the section's default endianity.
+    fn word(self, format: Format, val: u64) -> Self;
+
+    /// Write an address of the given size, using the section's default
+    /// endianity.
+    ///
+    /// Panics if the size is not 1, 2, 4 or 8.
+    fn address(self, address_size: u8, val: u64) -> Self;
 }
 
 impl GimliSectionMethods for Section {
+    fn e16<'a, T>(self, endian: Endian, val: T) -> Self
+    where
+        T: ToLabelOrNum<'a, u16>,
+    {
+        match endian {
+            Endian::Little => self.L16(val),
+            Endian::Big => self.B16(val),
+        }
+    }
+
     fn e32<'a, T>(self, endian: Endian, val: T) -> Self
     where
         T: ToLabelOrNum<'a, u32>,
@@ -50,4 +131,729 @@ impl GimliSectionMethods for Section {
         let written = leb128::write::unsigned(&mut buf, val).unwrap();
         self.append_bytes(&buf[0..written])
     }
+
+    fn initial_length(self, format: Format, length: &Label) -> Self {
+        match format {
+            Format::Dwarf32 => self.D32(length),
+            Format::Dwarf64 => self.D32(0xffff_ffff).D64(length),
+        }
+    }
+
+    fn word(self, format: Format, val: u64) -> Self {
+        match format {
+            Format::Dwarf32 => self.D32(val as u32),
+            Format::Dwarf64 => self.D64(val),
+        }
+    }
+
+    fn address(self, address_size: u8, val: u64) -> Self {
+        match address_size {
+            1 => self.D8(val as u8),
+            2 => self.D16(val as u16),
+            4 => self.D32(val as u32),
+            8 => self.D64(val),
+            otherwise => panic!("Unsupported address size: {}", otherwise),
+        }
+    }
+}
+
+/// The kind of unwind section that a `CfiBuilder` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfiSectionKind {
+    /// A `.debug_frame` section.
+    DebugFrame,
+    /// An `.eh_frame` section.
+    EhFrame,
+}
+
+/// A description of a CIE for `CfiBuilder::cie`.
+#[derive(Debug, Clone)]
+pub struct CieDescription<R: Reader> {
+    /// The DWARF format of the CIE's initial length and CIE id.
+    pub format: Format,
+
+    /// The CIE version. The address and segment sizes are only written to
+    /// `.debug_frame` for version 4.
+    pub version: u8,
+
+    /// The size of a target address.
+    pub address_size: u8,
+
+    /// The size of a segment selector. Only written to `.debug_frame` for
+    /// version 4.
+    pub segment_size: u8,
+
+    /// The constant that is factored out of all advance location instructions.
+    pub code_alignment_factor: u64,
+
+    /// The constant that is factored out of certain offset instructions.
+    pub data_alignment_factor: i64,
+
+    /// The column in the rule table that represents the return address.
+    pub return_address_register: Register,
+
+    /// The pointer encoding and address of the personality routine.
+    pub personality: Option<(constants::DwEhPe, u64)>,
+
+    /// The pointer encoding of the LSDA address in FDEs.
+    pub lsda_encoding: Option<constants::DwEhPe>,
+
+    /// The pointer encoding of the addresses in FDEs. If `None`, then FDE
+    /// addresses are written using the address size.
+    pub fde_address_encoding: Option<constants::DwEhPe>,
+
+    /// True for signal trampolines.
+    pub signal_trampoline: bool,
+
+    /// The initial instructions.
+    pub initial_instructions: Vec<CallFrameInstruction<R>>,
+}
+
+impl<R: Reader> CieDescription<R> {
+    /// Create a new version 1 CIE with no augmentations or initial
+    /// instructions, and with code and data alignment factors of 1.
+    pub fn new(format: Format, address_size: u8) -> Self {
+        CieDescription {
+            format,
+            version: 1,
+            address_size,
+            segment_size: 0,
+            code_alignment_factor: 1,
+            data_alignment_factor: 1,
+            return_address_register: Register(0),
+            personality: None,
+            lsda_encoding: None,
+            fde_address_encoding: None,
+            signal_trampoline: false,
+            initial_instructions: Vec::new(),
+        }
+    }
+
+    fn has_augmentation(&self) -> bool {
+        self.personality.is_some() || self.lsda_encoding.is_some()
+            || self.fde_address_encoding.is_some() || self.signal_trampoline
+    }
+}
+
+/// A description of an FDE for `CfiBuilder::fde`.
+#[derive(Debug, Clone)]
+pub struct FdeDescription<R: Reader> {
+    /// The DWARF format of the FDE's initial length and CIE pointer.
+    ///
+    /// This may differ from the format of the CIE. The CIE pointer in
+    /// `.eh_frame` is always 4 bytes.
+    pub format: Format,
+
+    /// The segment selector of the code range. Only written if the CIE has
+    /// a non-zero segment size.
+    pub initial_segment: u64,
+
+    /// The initial address of the code range.
+    pub initial_address: u64,
+
+    /// The length of the code range.
+    pub address_range: u64,
+
+    /// The address of the LSDA. Only written if the CIE has an LSDA encoding,
+    /// in which case `None` is written as zero.
+    pub lsda: Option<u64>,
+
+    /// The instructions.
+    pub instructions: Vec<CallFrameInstruction<R>>,
+}
+
+impl<R: Reader> FdeDescription<R> {
+    /// Create a new FDE for the given code range, with no instructions.
+    pub fn new(format: Format, initial_address: u64, address_range: u64) -> Self {
+        FdeDescription {
+            format,
+            initial_segment: 0,
+            initial_address,
+            address_range,
+            lsda: None,
+            instructions: Vec::new(),
+        }
+    }
+}
+
+/// A builder for `.debug_frame` and `.eh_frame` sections.
+///
+/// CIEs and FDEs are written from descriptions of their fields, with all
+/// lengths, CIE pointers, augmentation strings and pointer encodings computed
+/// by the builder. Each entry is padded with `DW_CFA_nop` to a multiple of
+/// its address size.
+///
+/// Relative pointer encodings are computed using the given `BaseAddresses`,
+/// which should also be used when parsing the section. `DW_EH_PE_funcrel` and
+/// `DW_EH_PE_aligned` are not supported, and the indirection flag is ignored.
+///
+/// ```
+/// use gimli::{BaseAddresses, CallFrameInstruction, EhFrame, EndianBuf, Format,
+///             LittleEndian, UnwindSection};
+/// use gimli::test_util::{CfiBuilder, CfiSectionKind, CieDescription, FdeDescription};
+/// use gimli::test_util::test_assembler::Endian;
+///
+/// let bases = BaseAddresses::default().set_cfi(0x1000);
+/// let mut builder = CfiBuilder::new(CfiSectionKind::EhFrame, Endian::Little, bases.clone());
+///
+/// let mut cie = CieDescription::<EndianBuf<LittleEndian>>::new(Format::Dwarf64, 8);
+/// cie.fde_address_encoding = Some(gimli::DW_EH_PE_pcrel);
+/// cie.initial_instructions.push(CallFrameInstruction::DefCfa {
+///     register: gimli::Register(7),
+///     offset: 8,
+/// });
+/// let cie_offset = builder.cie(&cie);
+///
+/// let fde = FdeDescription::new(Format::Dwarf32, 0x2000, 0x100);
+/// builder.fde(cie_offset, &cie, &fde);
+/// builder.terminator();
+///
+/// let buf = builder.finish();
+/// let eh_frame = EhFrame::new(&buf, LittleEndian);
+/// let mut entries = eh_frame.entries(&bases);
+/// assert!(entries.next().unwrap().is_some());
+/// ```
+pub struct CfiBuilder {
+    kind: CfiSectionKind,
+    bases: BaseAddresses,
+    section: Section,
+}
+
+impl fmt::Debug for CfiBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CfiBuilder")
+            .field("kind", &self.kind)
+            .field("bases", &self.bases)
+            .field("size", &self.section.size())
+            .finish()
+    }
+}
+
+impl CfiBuilder {
+    /// Create a new builder for a section of the given kind and endianity.
+    pub fn new(kind: CfiSectionKind, endian: Endian, bases: BaseAddresses) -> Self {
+        CfiBuilder {
+            kind,
+            bases,
+            section: Section::with_endian(endian),
+        }
+    }
+
+    /// Write a CIE, and return its offset.
+    pub fn cie<R: Reader>(&mut self, cie: &CieDescription<R>) -> u64 {
+        let kind = self.kind;
+        let bases = self.bases.clone();
+        self.entry(cie.format, cie.address_size, |section| {
+            let section = match kind {
+                CfiSectionKind::DebugFrame => section.word(cie.format, 0xffff_ffff_ffff_ffff),
+                CfiSectionKind::EhFrame => section.D32(0),
+            };
+            let mut section = section.D8(cie.version);
+
+            if cie.has_augmentation() {
+                section = section.append_bytes(b"z");
+                if cie.personality.is_some() {
+                    section = section.append_bytes(b"P");
+                }
+                if cie.lsda_encoding.is_some() {
+                    section = section.append_bytes(b"L");
+                }
+                if cie.fde_address_encoding.is_some() {
+                    section = section.append_bytes(b"R");
+                }
+                if cie.signal_trampoline {
+                    section = section.append_bytes(b"S");
+                }
+            }
+            let mut section = section.D8(0);
+
+            if kind == CfiSectionKind::DebugFrame && cie.version == 4 {
+                section = section.D8(cie.address_size).D8(cie.segment_size);
+            }
+
+            section = section
+                .uleb(cie.code_alignment_factor)
+                .sleb(cie.data_alignment_factor);
+            section = if cie.version == 1 {
+                section.D8(cie.return_address_register.0 as u8)
+            } else {
+                section.uleb(cie.return_address_register.0.into())
+            };
+
+            if cie.has_augmentation() {
+                let mut data = Section::with_endian(section.endian);
+                if let Some((encoding, address)) = cie.personality {
+                    // The pointer is relative to its position in the section,
+                    // so make the data section start at the right offset.
+                    let offset = section.size() + 1;
+                    let bases = BaseAddresses {
+                        cfi: bases.cfi.map(|cfi| cfi.wrapping_add(offset)),
+                        ..bases.clone()
+                    };
+                    data = data.D8(encoding.0);
+                    data = eh_pointer(data, &bases, encoding, cie.address_size, address);
+                }
+                if let Some(encoding) = cie.lsda_encoding {
+                    data = data.D8(encoding.0);
+                }
+                if let Some(encoding) = cie.fde_address_encoding {
+                    data = data.D8(encoding.0);
+                }
+                let data = data.get_contents().unwrap();
+                section = section.uleb(data.len() as u64).append_bytes(&data);
+            }
+
+            cfi_instructions(section, cie, &cie.initial_instructions)
+        })
+    }
+
+    /// Write an FDE that uses the CIE at the given offset, and return the
+    /// offset of the FDE.
+    ///
+    /// `cie` must describe the CIE that was written at `cie_offset`.
+    pub fn fde<R: Reader>(
+        &mut self,
+        cie_offset: u64,
+        cie: &CieDescription<R>,
+        fde: &FdeDescription<R>,
+    ) -> u64 {
+        let kind = self.kind;
+        let bases = self.bases.clone();
+        self.entry(fde.format, cie.address_size, |section| {
+            let mut section = match kind {
+                CfiSectionKind::DebugFrame => section.word(fde.format, cie_offset),
+                CfiSectionKind::EhFrame => {
+                    let cie_pointer = section.size() - cie_offset;
+                    section.D32(cie_pointer as u32)
+                }
+            };
+
+            if kind == CfiSectionKind::DebugFrame && cie.version == 4 && cie.segment_size != 0 {
+                section = section.address(cie.segment_size, fde.initial_segment);
+            }
+
+            section = match cie.fde_address_encoding {
+                Some(encoding) => {
+                    let section = eh_pointer(
+                        section,
+                        &bases,
+                        encoding,
+                        cie.address_size,
+                        fde.initial_address,
+                    );
+                    eh_pointer_data(section, encoding, cie.address_size, fde.address_range)
+                }
+                None => section
+                    .address(cie.address_size, fde.initial_address)
+                    .address(cie.address_size, fde.address_range),
+            };
+
+            if cie.has_augmentation() {
+                match cie.lsda_encoding {
+                    Some(encoding) => {
+                        let mut data = Section::with_endian(section.endian);
+                        let offset = section.size() + 1;
+                        let bases = BaseAddresses {
+                            cfi: bases.cfi.map(|cfi| cfi.wrapping_add(offset)),
+                            ..bases.clone()
+                        };
+                        // A zero value means there is no LSDA.
+                        data = match fde.lsda {
+                            Some(lsda) => eh_pointer(data, &bases, encoding, cie.address_size, lsda),
+                            None => eh_pointer_data(data, encoding, cie.address_size, 0),
+                        };
+                        let data = data.get_contents().unwrap();
+                        section = section.uleb(data.len() as u64).append_bytes(&data);
+                    }
+                    None => section = section.uleb(0),
+                }
+            }
+
+            cfi_instructions(section, cie, &fde.instructions)
+        })
+    }
+
+    /// Write a zero length terminator entry.
+    pub fn terminator(&mut self) {
+        let section = mem::replace(&mut self.section, Section::new());
+        self.section = section.D32(0);
+    }
+
+    /// Return the contents of the section.
+    pub fn finish(self) -> Vec<u8> {
+        self.section.get_contents().unwrap()
+    }
+
+    fn entry<F>(&mut self, format: Format, address_size: u8, f: F) -> u64
+    where
+        F: FnOnce(Section) -> Section,
+    {
+        let section = mem::replace(&mut self.section, Section::new());
+        let offset = section.size();
+        let length = Label::new();
+        let section = section.initial_length(format, &length);
+        let start = section.size();
+        let mut section = f(section);
+        if address_size != 0 {
+            while (section.size() - offset) % u64::from(address_size) != 0 {
+                section = section.D8(constants::DW_CFA_nop.0);
+            }
+        }
+        length.set_const(section.size() - start);
+        self.section = section;
+        offset
+    }
+}
+
+/// Write a pointer with the given encoding, applying the relative base that
+/// it requires.
+fn eh_pointer(
+    section: Section,
+    bases: &BaseAddresses,
+    encoding: constants::DwEhPe,
+    address_size: u8,
+    address: u64,
+) -> Section {
+    let base = match encoding.application() {
+        constants::DW_EH_PE_absptr => 0,
+        constants::DW_EH_PE_pcrel => bases
+            .cfi
+            .expect("pcrel pointer requires a cfi base")
+            .wrapping_add(section.size()),
+        constants::DW_EH_PE_textrel => bases.text.expect("textrel pointer requires a text base"),
+        constants::DW_EH_PE_datarel => bases.data.expect("datarel pointer requires a data base"),
+        _ => panic!("Unsupported pointer encoding: {}", encoding),
+    };
+    eh_pointer_data(section, encoding, address_size, address.wrapping_sub(base))
+}
+
+/// Write a value using the format of the given pointer encoding.
+fn eh_pointer_data(
+    section: Section,
+    encoding: constants::DwEhPe,
+    address_size: u8,
+    val: u64,
+) -> Section {
+    match encoding.format() {
+        constants::DW_EH_PE_absptr => section.address(address_size, val),
+        constants::DW_EH_PE_uleb128 => section.uleb(val),
+        constants::DW_EH_PE_udata2 | constants::DW_EH_PE_sdata2 => section.D16(val as u16),
+        constants::DW_EH_PE_udata4 | constants::DW_EH_PE_sdata4 => section.D32(val as u32),
+        constants::DW_EH_PE_udata8 | constants::DW_EH_PE_sdata8 => section.D64(val),
+        constants::DW_EH_PE_sleb128 => section.sleb(val as i64),
+        _ => panic!("Unsupported pointer encoding: {}", encoding),
+    }
+}
+
+/// Write call frame instructions, using the most compact encoding for each.
+fn cfi_instructions<R: Reader>(
+    mut section: Section,
+    cie: &CieDescription<R>,
+    instructions: &[CallFrameInstruction<R>],
+) -> Section {
+    for instruction in instructions {
+        section = cfi_instruction(section, cie, instruction);
+    }
+    section
+}
+
+fn cfi_instruction<R: Reader>(
+    section: Section,
+    cie: &CieDescription<R>,
+    instruction: &CallFrameInstruction<R>,
+) -> Section {
+    fn op(section: Section, op: constants::DwCfa) -> Section {
+        section.D8(op.0)
+    }
+
+    fn block<R: Reader>(section: Section, expression: &R) -> Section {
+        let bytes = expression.to_slice().unwrap();
+        section.uleb(bytes.len() as u64).append_bytes(&bytes)
+    }
+
+    match *instruction {
+        CallFrameInstruction::SetLoc { address } => {
+            let section = op(section, constants::DW_CFA_set_loc);
+            match cie.fde_address_encoding {
+                Some(encoding) => eh_pointer_data(section, encoding, cie.address_size, address),
+                None => section.address(cie.address_size, address),
+            }
+        }
+        CallFrameInstruction::AdvanceLoc { delta } => {
+            if delta < 0x40 {
+                section.D8(constants::DW_CFA_advance_loc.0 | delta as u8)
+            } else if delta < 0x100 {
+                op(section, constants::DW_CFA_advance_loc1).D8(delta as u8)
+            } else if delta < 0x10000 {
+                op(section, constants::DW_CFA_advance_loc2).D16(delta as u16)
+            } else {
+                op(section, constants::DW_CFA_advance_loc4).D32(delta)
+            }
+        }
+        CallFrameInstruction::DefCfa { register, offset } => op(section, constants::DW_CFA_def_cfa)
+            .uleb(register.0.into())
+            .uleb(offset),
+        CallFrameInstruction::DefCfaSf {
+            register,
+            factored_offset,
+        } => op(section, constants::DW_CFA_def_cfa_sf)
+            .uleb(register.0.into())
+            .sleb(factored_offset),
+        CallFrameInstruction::DefCfaRegister { register } => {
+            op(section, constants::DW_CFA_def_cfa_register).uleb(register.0.into())
+        }
+        CallFrameInstruction::DefCfaOffset { offset } => {
+            op(section, constants::DW_CFA_def_cfa_offset).uleb(offset)
+        }
+        CallFrameInstruction::DefCfaOffsetSf { factored_offset } => {
+            op(section, constants::DW_CFA_def_cfa_offset_sf).sleb(factored_offset)
+        }
+        CallFrameInstruction::DefCfaExpression { ref expression } => {
+            block(op(section, constants::DW_CFA_def_cfa_expression), &expression.0)
+        }
+        CallFrameInstruction::Undefined { register } => {
+            op(section, constants::DW_CFA_undefined).uleb(register.0.into())
+        }
+        CallFrameInstruction::SameValue { register } => {
+            op(section, constants::DW_CFA_same_value).uleb(register.0.into())
+        }
+        CallFrameInstruction::Offset {
+            register,
+            factored_offset,
+        } => {
+            if register.0 < 0x40 {
+                section
+                    .D8(constants::DW_CFA_offset.0 | register.0 as u8)
+                    .uleb(factored_offset)
+            } else {
+                op(section, constants::DW_CFA_offset_extended)
+                    .uleb(register.0.into())
+                    .uleb(factored_offset)
+            }
+        }
+        CallFrameInstruction::OffsetExtendedSf {
+            register,
+            factored_offset,
+        } => op(section, constants::DW_CFA_offset_extended_sf)
+            .uleb(register.0.into())
+            .sleb(factored_offset),
+        CallFrameInstruction::ValOffset {
+            register,
+            factored_offset,
+        } => op(section, constants::DW_CFA_val_offset)
+            .uleb(register.0.into())
+            .uleb(factored_offset),
+        CallFrameInstruction::ValOffsetSf {
+            register,
+            factored_offset,
+        } => op(section, constants::DW_CFA_val_offset_sf)
+            .uleb(register.0.into())
+            .sleb(factored_offset),
+        CallFrameInstruction::Register {
+            dest_register,
+            src_register,
+        } => op(section, constants::DW_CFA_register)
+            .uleb(dest_register.0.into())
+            .uleb(src_register.0.into()),
+        CallFrameInstruction::Expression {
+            register,
+            ref expression,
+        } => {
+            let section = op(section, constants::DW_CFA_expression).uleb(register.0.into());
+            block(section, &expression.0)
+        }
+        CallFrameInstruction::ValExpression {
+            register,
+            ref expression,
+        } => {
+            let section = op(section, constants::DW_CFA_val_expression).uleb(register.0.into());
+            block(section, &expression.0)
+        }
+        CallFrameInstruction::Restore { register } => {
+            if register.0 < 0x40 {
+                section.D8(constants::DW_CFA_restore.0 | register.0 as u8)
+            } else {
+                op(section, constants::DW_CFA_restore_extended).uleb(register.0.into())
+            }
+        }
+        CallFrameInstruction::RememberState => op(section, constants::DW_CFA_remember_state),
+        CallFrameInstruction::RestoreState => op(section, constants::DW_CFA_restore_state),
+        CallFrameInstruction::Nop => op(section, constants::DW_CFA_nop),
+        CallFrameInstruction::ArgsSize { size } => {
+            op(section, constants::DW_CFA_GNU_args_size).uleb(size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arch::X86_64;
+    use cfi::{CieOrFde, DebugFrame, EhFrame, UnwindSection};
+    use endianity::{BigEndian, EndianBuf, LittleEndian};
+    use op::Expression;
+    use parser::Pointer;
+
+    #[test]
+    fn test_cross_width() {
+        let length = Label::new();
+        let section = Section::with_endian(Endian::Big)
+            .initial_length(Format::Dwarf64, &length)
+            .word(Format::Dwarf32, 0x0102_0304)
+            .address(2, 0x0506)
+            .e16(Endian::Little, 0x0708);
+        length.set_const(0x090a);
+        assert_eq!(
+            section.get_contents().unwrap(),
+            vec![
+                0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0x09, 0x0a, 0x01, 0x02, 0x03, 0x04,
+                0x05, 0x06, 0x08, 0x07,
+            ]
+        );
+    }
+
+    fn assert_instructions<R: Reader + PartialEq>(
+        mut iter: ::cfi::CallFrameInstructionIter<R>,
+        expected: &[CallFrameInstruction<R>],
+    ) {
+        for instruction in expected {
+            assert_eq!(iter.next(), Ok(Some(instruction.clone())));
+        }
+        // The remainder is padding.
+        while let Some(instruction) = iter.next().unwrap() {
+            assert_eq!(instruction, CallFrameInstruction::Nop);
+        }
+    }
+
+    #[test]
+    fn test_cfi_builder_eh_frame() {
+        let bases = BaseAddresses::default()
+            .set_cfi(0x1000)
+            .set_text(0x2000)
+            .set_data(0x3000);
+        let mut builder = CfiBuilder::new(CfiSectionKind::EhFrame, Endian::Little, bases.clone());
+
+        let expression = [constants::DW_OP_breg7.0, 8];
+        let mut cie = CieDescription::new(Format::Dwarf64, 8);
+        cie.data_alignment_factor = -8;
+        cie.return_address_register = X86_64::RA;
+        cie.personality = Some((
+            constants::DwEhPe(constants::DW_EH_PE_datarel.0 | constants::DW_EH_PE_sdata4.0),
+            0x3456,
+        ));
+        cie.lsda_encoding = Some(constants::DwEhPe(
+            constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sleb128.0,
+        ));
+        cie.fde_address_encoding = Some(constants::DwEhPe(
+            constants::DW_EH_PE_textrel.0 | constants::DW_EH_PE_udata2.0,
+        ));
+        cie.signal_trampoline = true;
+        cie.initial_instructions = vec![
+            CallFrameInstruction::DefCfa {
+                register: Register(7),
+                offset: 8,
+            },
+            CallFrameInstruction::Offset {
+                register: Register(16),
+                factored_offset: 1,
+            },
+        ];
+        let cie_offset = builder.cie(&cie);
+
+        let mut fde = FdeDescription::new(Format::Dwarf32, 0x2100, 0x80);
+        fde.lsda = Some(0x800);
+        fde.instructions = vec![
+            CallFrameInstruction::AdvanceLoc { delta: 1 },
+            CallFrameInstruction::DefCfaOffset { offset: 16 },
+            CallFrameInstruction::AdvanceLoc { delta: 0x1234 },
+            CallFrameInstruction::Offset {
+                register: Register(70),
+                factored_offset: 2,
+            },
+            CallFrameInstruction::DefCfaExpression {
+                expression: Expression(EndianBuf::new(&expression, LittleEndian)),
+            },
+            CallFrameInstruction::Restore { register: Register(70) },
+        ];
+        let fde_offset = builder.fde(cie_offset, &cie, &fde);
+        builder.terminator();
+
+        let buf = builder.finish();
+        let eh_frame = EhFrame::new(&buf, LittleEndian);
+        let mut entries = eh_frame.entries(&bases);
+
+        let parsed_cie = match entries.next() {
+            Ok(Some(CieOrFde::Cie(cie))) => cie,
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        };
+        assert_eq!(parsed_cie.offset(), cie_offset as usize);
+        assert_eq!(parsed_cie.format(), Format::Dwarf64);
+        assert_eq!((parsed_cie.entry_len() + 12) % 8, 0);
+        assert_eq!(parsed_cie.data_alignment_factor(), -8);
+        assert_eq!(parsed_cie.return_address_register(), X86_64::RA);
+        assert_eq!(parsed_cie.personality(), Some(Pointer::Direct(0x3456)));
+        assert_eq!(parsed_cie.lsda_encoding(), cie.lsda_encoding);
+        assert!(parsed_cie.augmentation().unwrap().is_signal_trampoline());
+        assert_instructions(parsed_cie.instructions(), &cie.initial_instructions);
+
+        let parsed_fde = match entries.next() {
+            Ok(Some(CieOrFde::Fde(partial))) => partial
+                .parse(|offset| eh_frame.cie_from_offset(&bases, offset))
+                .unwrap(),
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        };
+        assert_eq!(parsed_fde.offset(), fde_offset as usize);
+        assert_eq!(parsed_fde.format(), Format::Dwarf32);
+        assert_eq!((parsed_fde.entry_len() + 4) % 8, 0);
+        assert_eq!(parsed_fde.initial_address(), 0x2100);
+        assert_eq!(parsed_fde.len(), 0x80);
+        assert_eq!(parsed_fde.lsda(), Some(Pointer::Direct(0x800)));
+        assert_instructions(parsed_fde.instructions(), &fde.instructions);
+
+        assert_eq!(entries.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_cfi_builder_debug_frame() {
+        let bases = BaseAddresses::default();
+        let mut builder = CfiBuilder::new(CfiSectionKind::DebugFrame, Endian::Big, bases.clone());
+
+        let mut cie = CieDescription::<EndianBuf<BigEndian>>::new(Format::Dwarf64, 4);
+        cie.version = 4;
+        cie.segment_size = 2;
+        cie.code_alignment_factor = 4;
+        let cie_offset = builder.cie(&cie);
+
+        let mut fde = FdeDescription::new(Format::Dwarf64, 0x1234_5678, 0x100);
+        fde.initial_segment = 0x9abc;
+        fde.instructions = vec![CallFrameInstruction::ArgsSize { size: 0x80 }];
+        builder.fde(cie_offset, &cie, &fde);
+
+        let buf = builder.finish();
+        let debug_frame = DebugFrame::new(&buf, BigEndian);
+        let mut entries = debug_frame.entries(&bases);
+
+        let parsed_cie = match entries.next() {
+            Ok(Some(CieOrFde::Cie(cie))) => cie,
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        };
+        assert_eq!(parsed_cie.version(), 4);
+        assert_eq!(parsed_cie.address_size(), 4);
+        assert_eq!(parsed_cie.segment_size(), 2);
+        assert_eq!(parsed_cie.code_alignment_factor(), 4);
+
+        let parsed_fde = match entries.next() {
+            Ok(Some(CieOrFde::Fde(partial))) => partial
+                .parse(|offset| debug_frame.cie_from_offset(&bases, offset))
+                .unwrap(),
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        };
+        assert_eq!(parsed_fde.format(), Format::Dwarf64);
+        assert_eq!(parsed_fde.initial_segment(), Some(0x9abc));
+        assert_eq!(parsed_fde.initial_address(), 0x1234_5678);
+        assert_eq!(parsed_fde.len(), 0x100);
+        assert_instructions(parsed_fde.instructions(), &fde.instructions);
+
+        assert_eq!(entries.next(), Ok(None));
+    }
 }
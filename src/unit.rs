@@ -2,22 +2,28 @@
 
 use constants;
 use abbrev::{Abbreviation, Abbreviations, AttributeSpecification, DebugAbbrev, DebugAbbrevOffset};
-use endianity::{EndianBuf, Endianity};
+use endianity::{EndianBuf, Endianity, LittleEndian};
 use fallible_iterator::FallibleIterator;
 use line::DebugLineOffset;
-use loclists::LocationListsOffset;
+use loclists::{DebugLocListsBase, DebugLocListsIndex, LocationListsOffset};
 use op::Expression;
-use parser::{parse_initial_length, DebugMacinfoOffset, Error, Format, Result};
-use rnglists::RangeListsOffset;
+use parser::{padding_length, parse_initial_length, ContextError, DebugMacinfoOffset,
+             DebugMacroOffset, EntryOrPadding, Error, Format, ReaderLimits, Result};
+use rnglists::{self, AddressIndex, DebugAddrBase, DebugRngListsBase, DebugRngListsIndex,
+               RangeListsOffset};
 use reader::{Reader, ReaderOffset};
 use std::cell::Cell;
+use std::fmt;
 use std::ops::{Range, RangeFrom, RangeTo};
 use std::{u16, u8};
-use str::{DebugStr, DebugStrOffset};
-use Section;
+use str::{DebugLineStrOffset, DebugStr, DebugStrOffset, DebugStrOffsetsBase,
+          DebugStrOffsetsIndex};
+use vec::Vec;
+use {Section, SectionId};
 
 /// An offset into the `.debug_types` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugTypesOffset<T = usize>(pub T);
 
 impl<T: ReaderOffset> DebugTypesOffset<T> {
@@ -41,10 +47,12 @@ impl<T: ReaderOffset> DebugTypesOffset<T> {
 
 /// A type signature as used in the `.debug_types` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DebugTypeSignature(pub u64);
 
 /// An offset into the `.debug_info` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DebugInfoOffset<T = usize>(pub T);
 
 impl<T: ReaderOffset> DebugInfoOffset<T> {
@@ -71,11 +79,15 @@ impl<T: ReaderOffset> DebugInfoOffset<T> {
 
 /// An offset into the current compilation or type unit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct UnitOffset<T = usize>(pub T);
 
 impl<T: ReaderOffset> UnitOffset<T> {
     /// Convert an offset to be relative to the start of the .debug_info section,
     /// instead of relative to the start of the given compilation unit.
+    ///
+    /// The addition wraps on overflow, so that an invalid offset read from
+    /// the section data results in an offset that won't be found.
     pub fn to_debug_info_offset<R>(
         &self,
         unit: &CompilationUnitHeader<R, R::Offset>,
@@ -83,11 +95,14 @@ impl<T: ReaderOffset> UnitOffset<T> {
     where
         R: Reader<Offset = T>,
     {
-        DebugInfoOffset(unit.offset.0 + self.0)
+        DebugInfoOffset(unit.offset.0.wrapping_add(self.0))
     }
 
     /// Convert an offset to be relative to the start of the .debug_types section,
     /// instead of relative to the start of the given type unit.
+    ///
+    /// The addition wraps on overflow, so that an invalid offset read from
+    /// the section data results in an offset that won't be found.
     pub fn to_debug_types_offset<R>(
         &self,
         unit: &TypeUnitHeader<R, R::Offset>,
@@ -95,7 +110,66 @@ impl<T: ReaderOffset> UnitOffset<T> {
     where
         R: Reader<Offset = T>,
     {
-        DebugTypesOffset(unit.offset.0 + self.0)
+        DebugTypesOffset(unit.offset.0.wrapping_add(self.0))
+    }
+}
+
+/// An offset into either the `.debug_info` section or the `.debug_types`
+/// section.
+///
+/// This is used where a debugging information entry or unit may be in either
+/// section, such as for references from index sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum UnitSectionOffset<T = usize> {
+    /// An offset into the `.debug_info` section.
+    DebugInfoOffset(DebugInfoOffset<T>),
+    /// An offset into the `.debug_types` section.
+    DebugTypesOffset(DebugTypesOffset<T>),
+}
+
+impl<T> From<DebugInfoOffset<T>> for UnitSectionOffset<T> {
+    fn from(offset: DebugInfoOffset<T>) -> Self {
+        UnitSectionOffset::DebugInfoOffset(offset)
+    }
+}
+
+impl<T> From<DebugTypesOffset<T>> for UnitSectionOffset<T> {
+    fn from(offset: DebugTypesOffset<T>) -> Self {
+        UnitSectionOffset::DebugTypesOffset(offset)
+    }
+}
+
+impl<T: ReaderOffset> UnitSectionOffset<T> {
+    /// Return the offset if it is an offset into the `.debug_info` section.
+    pub fn as_debug_info_offset(&self) -> Option<DebugInfoOffset<T>> {
+        match *self {
+            UnitSectionOffset::DebugInfoOffset(offset) => Some(offset),
+            UnitSectionOffset::DebugTypesOffset(_) => None,
+        }
+    }
+
+    /// Return the offset if it is an offset into the `.debug_types` section.
+    pub fn as_debug_types_offset(&self) -> Option<DebugTypesOffset<T>> {
+        match *self {
+            UnitSectionOffset::DebugInfoOffset(_) => None,
+            UnitSectionOffset::DebugTypesOffset(offset) => Some(offset),
+        }
+    }
+
+    /// Add a unit offset to this offset of a unit header, giving the section
+    /// offset of an entry within the unit.
+    ///
+    /// Returns `None` if the resulting offset would overflow.
+    pub fn add_unit_offset(&self, offset: UnitOffset<T>) -> Option<UnitSectionOffset<T>> {
+        match *self {
+            UnitSectionOffset::DebugInfoOffset(base) => {
+                base.0.checked_add(offset.0).map(|o| DebugInfoOffset(o).into())
+            }
+            UnitSectionOffset::DebugTypesOffset(base) => {
+                base.0.checked_add(offset.0).map(|o| DebugTypesOffset(o).into())
+            }
+        }
     }
 }
 
@@ -166,11 +240,70 @@ impl<R: Reader> DebugInfo<R> {
         input.skip(offset.0)?;
         CompilationUnitHeader::parse(input, offset)
     }
+
+    /// Collect the offsets of all of the compilation unit headers in this
+    /// `.debug_info` section.
+    ///
+    /// The headers can later be parsed again using `header_from_offset`. This
+    /// allows the units to be processed independently, such as by a pool of
+    /// worker threads that are each given a subset of the offsets.
+    ///
+    /// ```
+    /// use gimli::{DebugInfo, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_info_section_somehow = || &buf;
+    /// let debug_info = DebugInfo::new(read_debug_info_section_somehow(), LittleEndian);
+    ///
+    /// let offsets = debug_info.unit_offsets().unwrap();
+    /// // Each offset could be sent to a different thread.
+    /// for offset in offsets {
+    ///     let unit = debug_info.header_from_offset(offset).unwrap();
+    ///     println!("unit's length is {}", unit.unit_length());
+    /// }
+    /// ```
+    pub fn unit_offsets(&self) -> Result<Vec<DebugInfoOffset<R::Offset>>> {
+        let mut offsets = Vec::new();
+        let mut iter = self.units();
+        while let Some(header) = iter.next()? {
+            offsets.push(header.offset());
+        }
+        Ok(offsets)
+    }
+
+    /// Parse all of the compilation unit headers in this `.debug_info` section,
+    /// so that the unit containing a given `DebugInfoOffset` can be found
+    /// efficiently.
+    ///
+    /// ```
+    /// use gimli::{DebugInfo, DebugInfoOffset, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_info_section_somehow = || &buf;
+    /// let debug_info = DebugInfo::new(read_debug_info_section_somehow(), LittleEndian);
+    ///
+    /// let headers = debug_info.unit_headers().unwrap();
+    /// if let Some((unit, offset)) = headers.find(DebugInfoOffset(0x1234)) {
+    ///     println!("Found entry at {:?} in unit at {:?}", offset, unit.offset());
+    /// }
+    /// ```
+    pub fn unit_headers(&self) -> Result<CompilationUnitHeaders<R>> {
+        let mut headers = Vec::new();
+        let mut iter = self.units();
+        while let Some(header) = iter.next()? {
+            headers.push(header);
+        }
+        Ok(CompilationUnitHeaders { headers })
+    }
 }
 
 impl<R: Reader> Section<R> for DebugInfo<R> {
-    fn section_name() -> &'static str {
-        ".debug_info"
+    fn id() -> SectionId {
+        SectionId::DebugInfo
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_info_section
     }
 }
 
@@ -180,6 +313,48 @@ impl<R: Reader> From<R> for DebugInfo<R> {
     }
 }
 
+/// All of the compilation unit headers in a `.debug_info` section, sorted by
+/// their offset.
+///
+/// See the [documentation on
+/// `DebugInfo::unit_headers`](./struct.DebugInfo.html#method.unit_headers) for
+/// more detail.
+#[derive(Clone, Debug)]
+pub struct CompilationUnitHeaders<R: Reader> {
+    headers: Vec<CompilationUnitHeader<R, R::Offset>>,
+}
+
+impl<R: Reader> CompilationUnitHeaders<R> {
+    /// Return all of the compilation unit headers, in the order that they
+    /// occur in the `.debug_info` section.
+    #[inline]
+    pub fn headers(&self) -> &[CompilationUnitHeader<R, R::Offset>] {
+        &self.headers
+    }
+
+    /// Find the compilation unit header for the unit that contains the given
+    /// offset, and convert the offset to be relative to that unit.
+    ///
+    /// Returns `None` if the offset is not within the entries of any unit.
+    pub fn find(
+        &self,
+        offset: DebugInfoOffset<R::Offset>,
+    ) -> Option<(&CompilationUnitHeader<R, R::Offset>, UnitOffset<R::Offset>)> {
+        // Find the last unit that starts at or before the offset.
+        let index = match self.headers
+            .binary_search_by(|header| header.offset.0.cmp(&offset.0))
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let header = &self.headers[index];
+        offset
+            .to_unit_offset(header)
+            .map(|unit_offset| (header, unit_offset))
+    }
+}
+
 /// An iterator over the compilation- and partial-units of a section.
 ///
 /// See the [documentation on
@@ -191,21 +366,101 @@ pub struct CompilationUnitHeadersIter<R: Reader> {
 }
 
 impl<R: Reader> CompilationUnitHeadersIter<R> {
+    /// Return the section offset of the next unit that the iterator will
+    /// parse.
+    ///
+    /// Units are returned in the order that they occur in the section. If
+    /// parsing a unit header fails, this is the offset of that unit.
+    #[inline]
+    pub fn offset(&self) -> DebugInfoOffset<R::Offset> {
+        self.offset
+    }
+
     /// Advance the iterator to the next unit header.
+    ///
+    /// Zero padding between units is skipped.
     pub fn next(&mut self) -> Result<Option<CompilationUnitHeader<R, R::Offset>>> {
-        if self.input.is_empty() {
-            Ok(None)
-        } else {
-            let len = self.input.len();
-            match CompilationUnitHeader::parse(&mut self.input, self.offset) {
-                Ok(header) => {
-                    self.offset.0 += len - self.input.len();
-                    Ok(Some(header))
-                }
+        loop {
+            match self.next_or_padding()? {
+                Some(EntryOrPadding::Entry(header)) => return Ok(Some(header)),
+                Some(EntryOrPadding::Padding { .. }) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Advance the iterator to the next unit header, recovering from errors in
+    /// the headers of individual units.
+    ///
+    /// If a unit's header is invalid but its length is valid, then
+    /// `Ok(Some(Err(e)))` is returned, where `e` contains the section offset of
+    /// the unit, and iteration continues with the next unit. Zero padding
+    /// between units is skipped.
+    ///
+    /// If a unit's length is invalid then the following units cannot be
+    /// located, so `Err` is returned and iteration ends.
+    pub fn next_recoverable(
+        &mut self,
+    ) -> Result<Option<::std::result::Result<CompilationUnitHeader<R, R::Offset>, ContextError>>> {
+        loop {
+            if self.input.is_empty() {
+                return Ok(None);
+            }
+
+            let padding = padding_length(&self.input)?;
+            if padding > R::Offset::from_u8(0) {
+                self.input.skip(padding)?;
+                self.offset.0 = self.offset.0.checked_add(padding).ok_or(Error::OffsetOverflow)?;
+                continue;
+            }
+
+            let size = match unit_size(&self.input) {
+                Ok(size) => size,
                 Err(e) => {
                     self.input.empty();
-                    Err(e)
+                    return Err(e);
                 }
+            };
+            let offset = self.offset;
+            let mut unit = self.input.split(size)?;
+            self.offset.0 = self.offset.0.checked_add(size).ok_or(Error::OffsetOverflow)?;
+
+            return Ok(Some(CompilationUnitHeader::parse(&mut unit, offset).map_err(|e| {
+                e.with_context(SectionId::DebugInfo, offset.0.into_u64())
+            })));
+        }
+    }
+
+    /// Advance the iterator to the next unit header, or to the zero padding
+    /// before it.
+    pub fn next_or_padding(
+        &mut self,
+    ) -> Result<Option<EntryOrPadding<CompilationUnitHeader<R, R::Offset>, R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        let padding = padding_length(&self.input)?;
+        if padding > R::Offset::from_u8(0) {
+            let offset = self.offset.0;
+            self.input.skip(padding)?;
+            self.offset.0 = self.offset.0.checked_add(padding).ok_or(Error::OffsetOverflow)?;
+            return Ok(Some(EntryOrPadding::Padding {
+                offset,
+                length: padding,
+            }));
+        }
+
+        let len = self.input.len();
+        match CompilationUnitHeader::parse(&mut self.input, self.offset) {
+            Ok(header) => {
+                let size = len - self.input.len();
+                self.offset.0 = self.offset.0.checked_add(size).ok_or(Error::OffsetOverflow)?;
+                Ok(Some(EntryOrPadding::Entry(header)))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
             }
         }
     }
@@ -222,6 +477,7 @@ impl<R: Reader> FallibleIterator for CompilationUnitHeadersIter<R> {
 
 /// The header of a compilation unit's debugging information.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CompilationUnitHeader<R, Offset = usize>
 where
     R: Reader<Offset = Offset>,
@@ -285,6 +541,43 @@ where
         self.header.header_size()
     }
 
+    /// The type of this unit.
+    ///
+    /// Units before DWARF version 5 don't encode the unit type in their
+    /// header, so this is `DW_UT_compile` for them.
+    pub fn unit_type(&self) -> constants::DwUt {
+        self.header.unit_type
+    }
+
+    /// The identifier of the split unit that corresponds to this unit.
+    ///
+    /// This is only present in the header of DWARF version 5 skeleton units
+    /// and split compilation units. Earlier versions use the
+    /// `DW_AT_GNU_dwo_id` attribute instead.
+    pub fn dwo_id(&self) -> Option<u64> {
+        self.header.dwo_id
+    }
+
+    /// The size of the debugging information entries that follow the header
+    /// of this compilation unit.
+    pub fn content_size(&self) -> R::Offset {
+        self.header.content_size()
+    }
+
+    /// Return a reader for the raw bytes of the debugging information entries
+    /// that follow the header of this compilation unit.
+    pub fn raw_entries_reader(&self) -> R {
+        self.header.raw_entries_reader()
+    }
+
+    /// Convert a reference attribute value to an offset within this unit.
+    pub(crate) fn attr_unit_offset(
+        &self,
+        value: &AttributeValue<R>,
+    ) -> Option<UnitOffset<R::Offset>> {
+        self.header.attr_unit_offset(value)
+    }
+
     /// Navigate this compilation unit's `DebuggingInformationEntry`s.
     pub fn entries<'me, 'abbrev>(
         &'me self,
@@ -303,6 +596,17 @@ where
         self.header.entries_at_offset(abbreviations, offset)
     }
 
+    /// Navigate this compilation unit's `DebuggingInformationEntry`s
+    /// starting at the given offset, after checking that the offset is the
+    /// offset of an entry.
+    pub fn entries_at_offset_checked<'me, 'abbrev>(
+        &'me self,
+        abbreviations: &'abbrev Abbreviations,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<EntriesCursor<'abbrev, 'me, R>> {
+        self.header.entries_at_offset_checked(abbreviations, offset)
+    }
+
     /// Navigate this compilation unit's `DebuggingInformationEntry`s as a tree
     /// starting at the given offset.
     pub fn entries_tree<'me, 'abbrev>(
@@ -400,7 +704,8 @@ where
         input: &mut R,
         offset: DebugInfoOffset<R::Offset>,
     ) -> Result<CompilationUnitHeader<R, R::Offset>> {
-        let header = parse_unit_header(input)?;
+        let mut header = parse_unit_header(input)?;
+        header.debug_info_offset = Some(offset);
         Ok(CompilationUnitHeader {
             header: header,
             offset: offset,
@@ -433,6 +738,7 @@ pub fn parse_debug_info_offset<R: Reader>(
 /// The common fields for the headers of compilation units and
 /// type units.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct UnitHeader<R, Offset = usize>
 where
     R: Reader<Offset = Offset>,
@@ -443,7 +749,11 @@ where
     debug_abbrev_offset: DebugAbbrevOffset<Offset>,
     address_size: u8,
     format: Format,
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     entries_buf: R,
+    debug_info_offset: Option<DebugInfoOffset<Offset>>,
+    unit_type: constants::DwUt,
+    dwo_id: Option<u64>,
 }
 
 /// Static methods.
@@ -468,6 +778,9 @@ where
             address_size: address_size,
             format: format,
             entries_buf: entries_buf,
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_compile,
+            dwo_id: None,
         }
     }
 
@@ -527,6 +840,48 @@ where
         self.length_including_self() - self.entries_buf.len()
     }
 
+    /// The size of the debugging information entries that follow the header
+    /// of this unit.
+    pub fn content_size(&self) -> R::Offset {
+        self.entries_buf.len()
+    }
+
+    /// Return a reader for the raw bytes of the debugging information entries
+    /// that follow the header of this unit.
+    ///
+    /// This can be used to copy the entries verbatim, or to parse specific
+    /// regions of them.
+    pub fn raw_entries_reader(&self) -> R {
+        self.entries_buf.clone()
+    }
+
+    /// Convert a reference attribute value to an offset within this unit.
+    ///
+    /// This accepts both unit relative references, and `DW_FORM_ref_addr`
+    /// references to entries within this unit. Returns `None` for other
+    /// attribute values, and for references to other units.
+    pub(crate) fn attr_unit_offset(
+        &self,
+        value: &AttributeValue<R>,
+    ) -> Option<UnitOffset<R::Offset>> {
+        match *value {
+            AttributeValue::UnitRef(offset) => Some(offset),
+            AttributeValue::DebugInfoRef(offset) => {
+                let unit_offset = match self.debug_info_offset {
+                    Some(unit_offset) => unit_offset,
+                    None => return None,
+                };
+                let offset = UnitOffset(offset.0.checked_sub(unit_offset.0)?);
+                if self.is_valid_offset(offset) {
+                    Some(offset)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
     fn is_valid_offset(&self, offset: UnitOffset<R::Offset>) -> bool {
         let size_of_header = self.header_size();
         if offset.0 < size_of_header {
@@ -588,11 +943,18 @@ where
             abbreviations: abbreviations,
             cached_current: None,
             delta_depth: 0,
+            depth: 0,
+            max_depth: None,
         }
     }
 
     /// Navigate this compilation unit's `DebuggingInformationEntry`s
     /// starting at the given offset.
+    ///
+    /// The offset is trusted to be the offset of an entry. Only its bounds
+    /// are checked, so an offset in the middle of an entry will result in
+    /// garbage or a parse error. Use `entries_at_offset_checked` for offsets
+    /// from untrusted sources.
     pub fn entries_at_offset<'me, 'abbrev>(
         &'me self,
         abbreviations: &'abbrev Abbreviations,
@@ -605,9 +967,44 @@ where
             abbreviations: abbreviations,
             cached_current: None,
             delta_depth: 0,
+            depth: 0,
+            max_depth: None,
         })
     }
 
+    /// Navigate this compilation unit's `DebuggingInformationEntry`s
+    /// starting at the given offset, after checking that the offset is the
+    /// offset of an entry.
+    ///
+    /// The entries of the unit are scanned from the start of the unit until
+    /// the offset is reached, so this is slower than `entries_at_offset`.
+    /// Returns `Error::NoEntryAtGivenOffset` if the offset is not the offset
+    /// of a non-null entry.
+    pub fn entries_at_offset_checked<'me, 'abbrev>(
+        &'me self,
+        abbreviations: &'abbrev Abbreviations,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<EntriesCursor<'abbrev, 'me, R>> {
+        // Check the bounds first, so that we don't scan for an offset that
+        // can never be found.
+        self.range_from(offset..)?;
+
+        let mut cursor = self.entries(abbreviations);
+        while cursor.next_entry()?.is_some() {
+            let entry_offset = match cursor.current() {
+                Some(entry) => entry.offset(),
+                None => continue,
+            };
+            if entry_offset == offset {
+                return self.entries_at_offset(abbreviations, offset);
+            }
+            if entry_offset > offset {
+                break;
+            }
+        }
+        Err(Error::NoEntryAtGivenOffset)
+    }
+
     /// Navigate this unit's `DebuggingInformationEntry`s as a tree
     /// starting at the given offset.
     pub fn entries_tree<'me, 'abbrev>(
@@ -626,41 +1023,72 @@ where
     pub fn abbreviations(&self, debug_abbrev: &DebugAbbrev<R>) -> Result<Abbreviations> {
         debug_abbrev.abbreviations(self.debug_abbrev_offset())
     }
+
+    /// Advance `input` past the values of the given attributes in this unit,
+    /// without parsing the values.
+    ///
+    /// `specs` are usually the attributes of an entry's abbreviation, and
+    /// `input` is positioned after the entry's abbreviation code.
+    pub fn skip_attributes(&self, input: &mut R, specs: &[AttributeSpecification]) -> Result<()> {
+        for spec in specs {
+            skip_attribute(input, self, *spec)?;
+        }
+        Ok(())
+    }
 }
 
 /// Parse a compilation unit header.
 fn parse_unit_header<R: Reader>(input: &mut R) -> Result<UnitHeader<R, R::Offset>> {
     let (unit_length, format) = parse_initial_length(input)?;
     let unit_length = R::Offset::from_u64(unit_length)?;
+    // Offsets within the unit are computed relative to the start of its
+    // initial length field, so its total length must not overflow.
+    if R::Offset::from_u8(format.word_size()).checked_add(unit_length).is_none() {
+        return Err(Error::OffsetOverflow);
+    }
     let mut rest = input.split(unit_length)?;
 
     let version = rest.read_u16()?;
     let offset;
     let address_size;
+    let mut unit_type = constants::DW_UT_compile;
+    let mut dwo_id = None;
     // DWARF 1 was very different, and is obsolete, so isn't supported by this
     // reader.
     if 2 <= version && version <= 4 {
         offset = parse_debug_abbrev_offset(&mut rest, format)?;
         address_size = rest.read_u8()?;
     } else if version == 5 {
-        let unit_type = parse_compilation_unit_type(&mut rest)?;
-        if unit_type != constants::DW_UT_compile {
-            return Err(Error::UnsupportedUnitType.into());
-        }
+        unit_type = parse_compilation_unit_type(&mut rest)?;
         address_size = rest.read_u8()?;
         offset = parse_debug_abbrev_offset(&mut rest, format)?;
+        match unit_type {
+            constants::DW_UT_compile | constants::DW_UT_partial => {}
+            constants::DW_UT_skeleton | constants::DW_UT_split_compile => {
+                dwo_id = Some(rest.read_u64()?);
+            }
+            _ => return Err(Error::UnsupportedUnitType.into()),
+        }
     } else {
         return Err(Error::UnknownVersion(version as u64));
     }
 
-    Ok(UnitHeader::new(
-        unit_length,
-        version,
-        offset,
-        address_size,
-        format,
-        rest,
-    ))
+    let mut header = UnitHeader::new(unit_length, version, offset, address_size, format, rest);
+    header.unit_type = unit_type;
+    header.dwo_id = dwo_id;
+    Ok(header)
+}
+
+/// Return the size of the unit at the start of `input`, including its initial
+/// length field.
+fn unit_size<R: Reader>(input: &R) -> Result<R::Offset> {
+    let mut rest = input.clone();
+    let (unit_length, _) = parse_initial_length(&mut rest)?;
+    let unit_length = R::Offset::from_u64(unit_length)?;
+    if unit_length > rest.len() {
+        return Err(Error::UnexpectedEof(rest.offset_id()));
+    }
+    Ok(input.len() - rest.len() + unit_length)
 }
 
 /// A Debugging Information Entry (DIE).
@@ -856,10 +1284,12 @@ where
     /// and return it. Returns `Ok(None)` if no attribute is found.
     pub fn attr(&self, name: constants::DwAt) -> Result<Option<Attribute<R>>> {
         let mut attrs = self.attrs();
-        while let Some(attr) = attrs.next()? {
-            if attr.name() == name {
-                return Ok(Some(attr));
+        // Only parse the value of the attribute that is being looked for.
+        while let Some(spec) = attrs.attributes.first().cloned() {
+            if spec.name() == name {
+                return attrs.next();
             }
+            attrs.skip_attribute()?;
         }
         Ok(None)
     }
@@ -878,6 +1308,161 @@ where
         self.attr(name).map(|attr| attr.map(|attr| attr.value()))
     }
 
+    /// Find the first attribute which has the given name in this entry, or
+    /// in the entries that it refers to with `DW_AT_specification` or
+    /// `DW_AT_abstract_origin` attributes, and return it.
+    ///
+    /// This is useful for finding attributes such as `DW_AT_name` that are
+    /// often only present on the declaration of a definition, or on the
+    /// abstract instance of an inlined or concrete instance. The references
+    /// are followed until an entry is found that has the attribute, or that
+    /// has no further references. `abbreviations` must be the abbreviations
+    /// of the unit containing this entry.
+    ///
+    /// References to entries in other units are not followed. The
+    /// `DW_AT_sibling`, `DW_AT_declaration`, `DW_AT_specification` and
+    /// `DW_AT_abstract_origin` attributes are never looked up in the
+    /// referenced entries, since they describe a specific entry.
+    ///
+    /// Returns `Error::CyclicReference` if an entry is visited twice.
+    pub fn attr_followed(
+        &self,
+        abbreviations: &Abbreviations,
+        name: constants::DwAt,
+    ) -> Result<Option<Attribute<R>>> {
+        if let Some(attr) = self.attr(name)? {
+            return Ok(Some(attr));
+        }
+        match name {
+            constants::DW_AT_sibling
+            | constants::DW_AT_declaration
+            | constants::DW_AT_specification
+            | constants::DW_AT_abstract_origin => return Ok(None),
+            _ => {}
+        }
+
+        let mut visited = vec![self.offset];
+        let mut next = self.origin()?;
+        while let Some(offset) = next {
+            if visited.contains(&offset) {
+                return Err(Error::CyclicReference);
+            }
+            visited.push(offset);
+
+            let mut cursor = self.unit.entries_at_offset(abbreviations, offset)?;
+            cursor.next_entry()?;
+            let entry = cursor.current().ok_or(Error::NoEntryAtGivenOffset)?;
+            if let Some(attr) = entry.attr(name)? {
+                return Ok(Some(attr));
+            }
+            next = entry.origin()?;
+        }
+        Ok(None)
+    }
+
+    /// Find the first attribute which has the given name in this entry, or
+    /// in the entries that it refers to, and return its normalized value.
+    ///
+    /// See `attr_followed` for details.
+    pub fn attr_value_followed(
+        &self,
+        abbreviations: &Abbreviations,
+        name: constants::DwAt,
+    ) -> Result<Option<AttributeValue<R>>> {
+        self.attr_followed(abbreviations, name)
+            .map(|attr| attr.map(|attr| attr.value()))
+    }
+
+    /// Return the source language of this entry, and its version.
+    ///
+    /// This uses the `DW_AT_language_name` and `DW_AT_language_version`
+    /// attributes if present. Otherwise, the `DW_AT_language` attribute is
+    /// converted using `DwLang::language_name`. A version of 0 means that the
+    /// language version is unspecified.
+    pub fn language_name(&self) -> Result<Option<(constants::DwLname, u32)>> {
+        let mut language = None;
+        let mut name = None;
+        let mut version = None;
+        let mut attrs = self.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.value() {
+                AttributeValue::Language(value) => language = Some(value),
+                AttributeValue::LanguageName(value) => name = Some(value),
+                _ if attr.name() == constants::DW_AT_language_version => {
+                    version = attr.udata_value();
+                }
+                _ => {}
+            }
+        }
+        if let Some(name) = name {
+            let version = version.map_or(0, |version| version as u32);
+            return Ok(Some((name, version)));
+        }
+        Ok(language.and_then(|language| language.language_name()))
+    }
+
+    /// Return the address range given by the `DW_AT_low_pc` and
+    /// `DW_AT_high_pc` attributes of this entry.
+    ///
+    /// In DWARF 2 and 3, `DW_AT_high_pc` is always an address. Since DWARF 4,
+    /// it may instead be a constant, in which case it is the size of the
+    /// range, and is added to `DW_AT_low_pc` to obtain the end address. This
+    /// handles both encodings.
+    ///
+    /// Returns `None` if either attribute is missing or has an unexpected
+    /// form, or if the end address overflows. This does not consider any
+    /// `DW_AT_ranges` attribute; use `Dwarf::die_ranges` to obtain all of the
+    /// ranges of an entry.
+    pub fn pc_range(&self) -> Result<Option<rnglists::Range>> {
+        self.pc_range_impl(false)
+    }
+
+    /// Return the address range given by the `DW_AT_low_pc` and
+    /// `DW_AT_high_pc` attributes of this entry, optionally treating a
+    /// constant `DW_AT_high_pc` as an address.
+    pub(crate) fn pc_range_impl(
+        &self,
+        high_pc_is_address: bool,
+    ) -> Result<Option<rnglists::Range>> {
+        let mut low_pc = None;
+        let mut high_pc = None;
+        let mut attrs = self.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_low_pc => low_pc = Some(attr),
+                constants::DW_AT_high_pc => high_pc = Some(attr),
+                _ => {}
+            }
+        }
+        let begin = match low_pc.map(|attr| attr.value()) {
+            Some(AttributeValue::Addr(address)) => address,
+            _ => return Ok(None),
+        };
+        let end = match high_pc {
+            Some(attr) => match attr.value() {
+                AttributeValue::Addr(address) => Some(address),
+                _ if high_pc_is_address => attr.udata_value(),
+                _ => attr.udata_value().and_then(|size| begin.checked_add(size)),
+            },
+            None => None,
+        };
+        Ok(end.map(|end| rnglists::Range { begin, end }))
+    }
+
+    /// Return the offset of the entry that is referred to by the
+    /// `DW_AT_specification` or `DW_AT_abstract_origin` attribute of this
+    /// entry, if it is within the same unit.
+    fn origin(&self) -> Result<Option<UnitOffset<Offset>>> {
+        let value = match self.attr_value(constants::DW_AT_specification)? {
+            Some(value) => value,
+            None => match self.attr_value(constants::DW_AT_abstract_origin)? {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+        Ok(self.unit.attr_unit_offset(&value))
+    }
+
     /// Return the input buffer after the last attribute.
     #[allow(inline_always)]
     #[inline(always)]
@@ -888,20 +1473,27 @@ where
             Ok(input)
         } else {
             let mut attrs = self.attrs();
-            while let Some(_) = attrs.next()? {}
+            while let Some(_) = attrs.skip_attribute()? {}
             Ok(attrs.input)
         }
     }
 
     /// Use the `DW_AT_sibling` attribute to find the input buffer for the
     /// next sibling. Returns `None` if the attribute is missing or invalid.
+    ///
+    /// The attribute may use any of the unit relative reference forms, or
+    /// `DW_FORM_ref_addr` if it refers to an entry within the same unit.
     fn sibling(&self) -> Option<R> {
-        let attr = self.attr_value(constants::DW_AT_sibling);
-        if let Ok(Some(AttributeValue::UnitRef(offset))) = attr {
-            if offset.0 > self.offset.0 {
-                if let Ok(input) = self.unit.range_from(offset..) {
-                    return Some(input);
-                }
+        let offset = match self.attr_value(constants::DW_AT_sibling) {
+            Ok(Some(value)) => match self.unit.attr_unit_offset(&value) {
+                Some(offset) => offset,
+                None => return None,
+            },
+            _ => return None,
+        };
+        if offset.0 > self.offset.0 {
+            if let Ok(input) = self.unit.range_from(offset..) {
+                return Some(input);
             }
         }
         None
@@ -915,12 +1507,12 @@ where
         unit: &'unit UnitHeader<R, R::Offset>,
         abbreviations: &'abbrev Abbreviations,
     ) -> Result<Option<Self>> {
-        let offset = unit.header_size() + input.offset_from(&unit.entries_buf);
+        let offset = unit.header_size() + input.offset_from(&unit.entries_buf)?;
         let code = input.read_uleb128()?;
         if code == 0 {
             return Ok(None);
         };
-        let abbrev = abbreviations.get(code).ok_or(Error::UnknownAbbreviation)?;
+        let abbrev = abbreviations.lookup(code)?;
         Ok(Some(DebuggingInformationEntry {
             offset: UnitOffset(offset),
             attrs_slice: input.clone(),
@@ -937,10 +1529,18 @@ where
 // for their data.  This gives better code generation in `parse_attribute`.
 #[repr(u64)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde",
+           serde(bound = "R: ::serde::Serialize, R::Endian: ::serde::Serialize, \
+                          R::Offset: ::serde::Serialize"))]
 pub enum AttributeValue<R: Reader> {
     /// "Refers to some location in the address space of the described program."
     Addr(u64),
 
+    /// An index into the `.debug_addr` section, whose value is relative to
+    /// the unit's `DW_AT_addr_base` attribute.
+    DebugAddrIndex(AddressIndex),
+
     /// A slice of an arbitrary number of bytes.
     Block(R),
 
@@ -972,12 +1572,33 @@ pub enum AttributeValue<R: Reader> {
     /// anything else."
     Data8(([u8; 8], R::Endian)),
 
+    /// A sixteen byte constant data value. How to interpret the bytes depends on context.
+    ///
+    /// This is used by `DW_FORM_data16`, which was added in DWARF 5.
+    Data16(([u8; 16], R::Endian)),
+
     /// A signed integer constant.
     Sdata(i64),
 
     /// An unsigned integer constant.
     Udata(u64),
 
+    /// A signed integer constant that is too large to be represented by
+    /// `Sdata`.
+    ///
+    /// The value is stored as little endian bytes so that it doesn't increase
+    /// the alignment of `AttributeValue`. Use `Attribute::sdata128_value` to
+    /// get the value as an `i128`.
+    Sdata128([u8; 16]),
+
+    /// An unsigned integer constant that is too large to be represented by
+    /// `Udata`.
+    ///
+    /// The value is stored as little endian bytes so that it doesn't increase
+    /// the alignment of `AttributeValue`. Use `Attribute::udata128_value` to
+    /// get the value as a `u128`.
+    Udata128([u8; 16]),
+
     /// "The information bytes contain a DWARF expression (see Section 2.5) or
     /// location description (see Section 2.6)."
     Exprloc(Expression<R>),
@@ -1006,12 +1627,35 @@ pub enum AttributeValue<R: Reader> {
     /// An offset into either the `.debug_loc` section or the `.debug_loclists` section.
     LocationListsRef(LocationListsOffset<R::Offset>),
 
+    /// An index into the set of offsets in the `.debug_loclists` section,
+    /// whose value is relative to the unit's `DW_AT_loclists_base` attribute.
+    DebugLocListsIndex(DebugLocListsIndex<R::Offset>),
+
     /// An offset into the `.debug_macinfo` section.
     DebugMacinfoRef(DebugMacinfoOffset<R::Offset>),
 
+    /// An offset into the `.debug_macro` section.
+    DebugMacroRef(DebugMacroOffset<R::Offset>),
+
     /// An offset into the `.debug_ranges` section.
     RangeListsRef(RangeListsOffset<R::Offset>),
 
+    /// An index into the set of offsets in the `.debug_rnglists` section,
+    /// whose value is relative to the unit's `DW_AT_rnglists_base` attribute.
+    DebugRngListsIndex(DebugRngListsIndex<R::Offset>),
+
+    /// The value of a `DW_AT_str_offsets_base` attribute.
+    DebugStrOffsetsBase(DebugStrOffsetsBase<R::Offset>),
+
+    /// The value of a `DW_AT_addr_base` attribute.
+    DebugAddrBase(DebugAddrBase<R::Offset>),
+
+    /// The value of a `DW_AT_loclists_base` attribute.
+    DebugLocListsBase(DebugLocListsBase<R::Offset>),
+
+    /// The value of a `DW_AT_rnglists_base` attribute.
+    DebugRngListsBase(DebugRngListsBase<R::Offset>),
+
     /// A type signature.
     DebugTypesRef(DebugTypeSignature),
 
@@ -1021,6 +1665,13 @@ pub enum AttributeValue<R: Reader> {
     /// An offset into the `.debug_str` section of the supplementary object file.
     DebugStrRefSup(DebugStrOffset<R::Offset>),
 
+    /// An index into the `.debug_str_offsets` section, whose value is
+    /// relative to the unit's `DW_AT_str_offsets_base` attribute.
+    DebugStrOffsetsIndex(DebugStrOffsetsIndex<R::Offset>),
+
+    /// An offset into the `.debug_line_str` section.
+    DebugLineStrRef(DebugLineStrOffset<R::Offset>),
+
     /// A slice of bytes representing a string. Does not include a final null byte.
     /// Not guaranteed to be UTF-8 or anything like that.
     String(R),
@@ -1046,6 +1697,9 @@ pub enum AttributeValue<R: Reader> {
     /// The value of a `DW_AT_language` attribute.
     Language(constants::DwLang),
 
+    /// The value of a `DW_AT_language_name` attribute.
+    LanguageName(constants::DwLname),
+
     /// The value of a `DW_AT_address_class` attribute.
     AddressClass(constants::DwAddr),
 
@@ -1066,12 +1720,242 @@ pub enum AttributeValue<R: Reader> {
     FileIndex(u64),
 }
 
-/// An attribute in a `DebuggingInformationEntry`, consisting of a name and
-/// associated value.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub struct Attribute<R: Reader> {
-    name: constants::DwAt,
-    value: AttributeValue<R>,
+impl<R: Reader> AttributeValue<R> {
+    /// Return the class of this attribute value.
+    ///
+    /// Values that are offsets into other sections, such as `lineptr`,
+    /// `loclistptr` and `rangelistptr` values, are all in the
+    /// `SectionOffset` class.
+    pub fn class(&self) -> AttributeClass {
+        match *self {
+            AttributeValue::Addr(_) | AttributeValue::DebugAddrIndex(_) => {
+                AttributeClass::Address
+            }
+            AttributeValue::Block(_) => AttributeClass::Block,
+            AttributeValue::Data1(_)
+            | AttributeValue::Data2(_)
+            | AttributeValue::Data4(_)
+            | AttributeValue::Data8(_)
+            | AttributeValue::Data16(_)
+            | AttributeValue::Sdata(_)
+            | AttributeValue::Udata(_)
+            | AttributeValue::Sdata128(_)
+            | AttributeValue::Udata128(_)
+            | AttributeValue::Encoding(_)
+            | AttributeValue::DecimalSign(_)
+            | AttributeValue::Endianity(_)
+            | AttributeValue::Accessibility(_)
+            | AttributeValue::Visibility(_)
+            | AttributeValue::Virtuality(_)
+            | AttributeValue::Language(_)
+            | AttributeValue::LanguageName(_)
+            | AttributeValue::AddressClass(_)
+            | AttributeValue::IdentifierCase(_)
+            | AttributeValue::CallingConvention(_)
+            | AttributeValue::Inline(_)
+            | AttributeValue::Ordering(_)
+            | AttributeValue::FileIndex(_) => AttributeClass::Constant,
+            AttributeValue::Exprloc(_) => AttributeClass::Exprloc,
+            AttributeValue::Flag(_) => AttributeClass::Flag,
+            AttributeValue::UnitRef(_)
+            | AttributeValue::DebugInfoRef(_)
+            | AttributeValue::DebugInfoRefSup(_)
+            | AttributeValue::DebugTypesRef(_) => AttributeClass::Reference,
+            AttributeValue::SecOffset(_)
+            | AttributeValue::DebugLineRef(_)
+            | AttributeValue::LocationListsRef(_)
+            | AttributeValue::DebugLocListsIndex(_)
+            | AttributeValue::DebugMacinfoRef(_)
+            | AttributeValue::DebugMacroRef(_)
+            | AttributeValue::RangeListsRef(_)
+            | AttributeValue::DebugRngListsIndex(_)
+            | AttributeValue::DebugStrOffsetsBase(_)
+            | AttributeValue::DebugAddrBase(_)
+            | AttributeValue::DebugLocListsBase(_)
+            | AttributeValue::DebugRngListsBase(_) => AttributeClass::SectionOffset,
+            AttributeValue::DebugStrRef(_)
+            | AttributeValue::DebugStrRefSup(_)
+            | AttributeValue::DebugStrOffsetsIndex(_)
+            | AttributeValue::DebugLineStrRef(_)
+            | AttributeValue::String(_) => AttributeClass::String,
+        }
+    }
+}
+
+/// The class of an attribute value, as described in Section 7.5.5 of the
+/// DWARF 5 standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeClass {
+    /// A target address.
+    Address,
+    /// An arbitrary number of uninterpreted bytes.
+    Block,
+    /// An integer constant.
+    Constant,
+    /// A DWARF expression or location description.
+    Exprloc,
+    /// A boolean flag.
+    Flag,
+    /// A reference to another debugging information entry.
+    Reference,
+    /// An offset into another section, such as a `lineptr`, `loclistptr`,
+    /// `macptr` or `rangelistptr` value.
+    SectionOffset,
+    /// A string.
+    String,
+}
+
+/// Formats the value without any knowledge of the attribute that it belongs
+/// to. Constants are only printed using their names if the value has already
+/// been interpreted, such as by `Attribute::value`. References and section
+/// offsets are printed in hexadecimal, along with the section that they refer
+/// to.
+impl<R: Reader> fmt::Display for AttributeValue<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        fn write_bytes<R: Reader>(f: &mut fmt::Formatter, bytes: &R) -> fmt::Result {
+            let bytes = bytes.to_slice().map_err(|_| fmt::Error)?;
+            write!(f, "len 0x{:04x}:", bytes.len())?;
+            for byte in bytes.iter() {
+                write!(f, " {:02x}", byte)?;
+            }
+            Ok(())
+        }
+
+        match *self {
+            AttributeValue::Addr(address) => write!(f, "0x{:08x}", address),
+            AttributeValue::DebugAddrIndex(AddressIndex(index)) => {
+                write!(f, "<.debug_addr index 0x{:x}>", index)
+            }
+            AttributeValue::Block(ref data) => write_bytes(f, data),
+            AttributeValue::Data1(data) => write!(f, "0x{:02x}", data[0]),
+            AttributeValue::Data2((data, endian)) => {
+                write!(f, "0x{:04x}", endian.read_u16(&data))
+            }
+            AttributeValue::Data4((data, endian)) => {
+                write!(f, "0x{:08x}", endian.read_u32(&data))
+            }
+            AttributeValue::Data8((data, endian)) => {
+                write!(f, "0x{:016x}", endian.read_u64(&data))
+            }
+            AttributeValue::Data16((data, endian)) => {
+                write!(f, "0x{:032x}", endian.read_u128(&data))
+            }
+            AttributeValue::Sdata(data) => write!(f, "{}", data),
+            AttributeValue::Udata(data) => write!(f, "{}", data),
+            AttributeValue::Sdata128(ref data) => {
+                write!(f, "{}", LittleEndian.read_i128(data))
+            }
+            AttributeValue::Udata128(ref data) => {
+                write!(f, "{}", LittleEndian.read_u128(data))
+            }
+            AttributeValue::Exprloc(Expression(ref data)) => write_bytes(f, data),
+            AttributeValue::Flag(flag) => write!(f, "{}", flag),
+            AttributeValue::SecOffset(offset) => write!(f, "0x{:08x}", offset.into_u64()),
+            AttributeValue::UnitRef(UnitOffset(offset)) => {
+                write!(f, "<0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugInfoRef(DebugInfoOffset(offset)) => {
+                write!(f, "<.debug_info+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset)) => {
+                write!(f, "<.debug_info(sup)+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugLineRef(DebugLineOffset(offset)) => {
+                write!(f, "<.debug_line+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::LocationListsRef(LocationListsOffset(offset)) => {
+                write!(f, "<loclist 0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugLocListsIndex(DebugLocListsIndex(index)) => {
+                write!(f, "<loclist index 0x{:x}>", index.into_u64())
+            }
+            AttributeValue::DebugMacinfoRef(DebugMacinfoOffset(offset)) => {
+                write!(f, "<.debug_macinfo+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugMacroRef(DebugMacroOffset(offset)) => {
+                write!(f, "<.debug_macro+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::RangeListsRef(RangeListsOffset(offset)) => {
+                write!(f, "<rnglist 0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugRngListsIndex(DebugRngListsIndex(index)) => {
+                write!(f, "<rnglist index 0x{:x}>", index.into_u64())
+            }
+            AttributeValue::DebugStrOffsetsBase(DebugStrOffsetsBase(offset)) => {
+                write!(f, "<.debug_str_offsets+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugAddrBase(DebugAddrBase(offset)) => {
+                write!(f, "<.debug_addr+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugLocListsBase(DebugLocListsBase(offset)) => {
+                write!(f, "<.debug_loclists+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugRngListsBase(DebugRngListsBase(offset)) => {
+                write!(f, "<.debug_rnglists+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugTypesRef(DebugTypeSignature(signature)) => {
+                write!(f, "<signature 0x{:016x}>", signature)
+            }
+            AttributeValue::DebugStrRef(DebugStrOffset(offset)) => {
+                write!(f, "<.debug_str+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugStrRefSup(DebugStrOffset(offset)) => {
+                write!(f, "<.debug_str(sup)+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index)) => {
+                write!(f, "<.debug_str_offsets index 0x{:x}>", index.into_u64())
+            }
+            AttributeValue::DebugLineStrRef(DebugLineStrOffset(offset)) => {
+                write!(f, "<.debug_line_str+0x{:08x}>", offset.into_u64())
+            }
+            AttributeValue::String(ref string) => {
+                let string = string.to_string_lossy().map_err(|_| fmt::Error)?;
+                write!(f, "\"{}\"", string)
+            }
+            AttributeValue::Encoding(value) => write!(f, "{}", value),
+            AttributeValue::DecimalSign(value) => write!(f, "{}", value),
+            AttributeValue::Endianity(value) => write!(f, "{}", value),
+            AttributeValue::Accessibility(value) => write!(f, "{}", value),
+            AttributeValue::Visibility(value) => write!(f, "{}", value),
+            AttributeValue::Virtuality(value) => write!(f, "{}", value),
+            AttributeValue::Language(value) => write!(f, "{}", value),
+            AttributeValue::LanguageName(value) => write!(f, "{}", value),
+            AttributeValue::AddressClass(value) => write!(f, "{}", value),
+            AttributeValue::IdentifierCase(value) => write!(f, "{}", value),
+            AttributeValue::CallingConvention(value) => write!(f, "{}", value),
+            AttributeValue::Inline(value) => write!(f, "{}", value),
+            AttributeValue::Ordering(value) => write!(f, "{}", value),
+            AttributeValue::FileIndex(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Information about how an attribute was encoded, as returned by
+/// `AttrsIter::next_with_metadata`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeMetadata<T = usize> {
+    /// The form of the attribute. If the attribute was declared with
+    /// `DW_FORM_indirect`, then this is the form that was read from the
+    /// entry.
+    pub form: constants::DwForm,
+
+    /// The class of the attribute's raw value.
+    pub class: AttributeClass,
+
+    /// The offset of the attribute's value from the start of the unit.
+    /// This is after any form that was read for `DW_FORM_indirect`.
+    pub offset: UnitOffset<T>,
+
+    /// The length in bytes of the attribute's value.
+    pub length: T,
+}
+
+/// An attribute in a `DebuggingInformationEntry`, consisting of a name and
+/// associated value.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Attribute<R: Reader> {
+    name: constants::DwAt,
+    value: AttributeValue<R>,
 }
 
 impl<R: Reader> Attribute<R> {
@@ -1154,12 +2038,24 @@ impl<R: Reader> Attribute<R> {
                     return AttributeValue::DebugMacinfoRef(DebugMacinfoOffset(offset));
                 });
         }
+        macro_rules! macroptr {
+            () => (
+                if let Some(offset) = self.offset_value() {
+                    return AttributeValue::DebugMacroRef(DebugMacroOffset(offset));
+                });
+        }
         macro_rules! rangelistptr {
             () => (
                 if let Some(offset) = self.offset_value() {
                     return AttributeValue::RangeListsRef(RangeListsOffset(offset));
                 });
         }
+        macro_rules! base {
+            ($variant:ident) => (
+                if let Some(offset) = self.offset_value() {
+                    return AttributeValue::$variant($variant(offset));
+                });
+        }
         macro_rules! reference {
             () => ();
         }
@@ -1200,6 +2096,12 @@ impl<R: Reader> Attribute<R> {
             constants::DW_AT_language => {
                 constant!(u16_value, Language, DwLang);
             }
+            constants::DW_AT_language_name => {
+                constant!(u16_value, LanguageName, DwLname);
+            }
+            constants::DW_AT_language_version => {
+                constant!(udata_value, Udata);
+            }
             constants::DW_AT_discr => {
                 reference!();
             }
@@ -1475,6 +2377,27 @@ impl<R: Reader> Attribute<R> {
             constants::DW_AT_linkage_name => {
                 string!();
             }
+            constants::DW_AT_str_offsets_base => {
+                base!(DebugStrOffsetsBase);
+            }
+            constants::DW_AT_addr_base => {
+                base!(DebugAddrBase);
+            }
+            constants::DW_AT_rnglists_base => {
+                base!(DebugRngListsBase);
+            }
+            constants::DW_AT_macros => {
+                macroptr!();
+            }
+            constants::DW_AT_loclists_base => {
+                base!(DebugLocListsBase);
+            }
+            constants::DW_AT_GNU_addr_base => {
+                base!(DebugAddrBase);
+            }
+            constants::DW_AT_GNU_ranges_base => {
+                base!(DebugRngListsBase);
+            }
             _ => {}
         }
         self.value.clone()
@@ -1538,6 +2461,44 @@ impl<R: Reader> Attribute<R> {
         })
     }
 
+    /// Try to convert this attribute's value to an unsigned 128 bit integer.
+    ///
+    /// Unlike `udata_value`, this supports `DW_FORM_data16` values, and
+    /// `DW_FORM_udata` values that are too large for a `u64`.
+    pub fn udata128_value(&self) -> Option<u128> {
+        Some(match self.value {
+            AttributeValue::Data16((ref data, endian)) => endian.read_u128(data),
+            AttributeValue::Udata128(ref data) => LittleEndian.read_u128(data),
+            AttributeValue::Sdata128(ref data) => {
+                let data = LittleEndian.read_i128(data);
+                if data < 0 {
+                    return None;
+                }
+                data as u128
+            }
+            _ => return self.udata_value().map(|data| data as u128),
+        })
+    }
+
+    /// Try to convert this attribute's value to a signed 128 bit integer.
+    ///
+    /// Unlike `sdata_value`, this supports `DW_FORM_data16` values, and
+    /// `DW_FORM_sdata` values that are too large for an `i64`.
+    pub fn sdata128_value(&self) -> Option<i128> {
+        Some(match self.value {
+            AttributeValue::Data16((ref data, endian)) => endian.read_i128(data),
+            AttributeValue::Sdata128(ref data) => LittleEndian.read_i128(data),
+            AttributeValue::Udata128(ref data) => {
+                let data = LittleEndian.read_u128(data);
+                if data > i128::max_value() as u128 {
+                    return None;
+                }
+                data as i128
+            }
+            _ => return self.sdata_value().map(|data| data as i128),
+        })
+    }
+
     /// Try to convert this attribute's value to an offset.
     ///
     /// Offsets will be `Data` in DWARF version 2/3, and `SecOffset` otherwise.
@@ -1567,6 +2528,22 @@ impl<R: Reader> Attribute<R> {
         })
     }
 
+    /// Try to convert this attribute's value to a discriminant list, as used
+    /// by `DW_AT_discr_list` attributes.
+    ///
+    /// `signed` specifies whether the type of the discriminant of the variant
+    /// part is signed, which determines how the values in the list are
+    /// encoded.
+    pub fn discr_list_value(&self, signed: bool) -> Option<DiscriminantListIter<R>> {
+        match self.value {
+            AttributeValue::Block(ref data) => Some(DiscriminantListIter {
+                input: data.clone(),
+                signed,
+            }),
+            _ => None,
+        }
+    }
+
     /// Try to return this attribute's value as a string slice.
     ///
     /// If this attribute's value is either an inline `DW_FORM_string` string,
@@ -1600,6 +2577,108 @@ impl<R: Reader> Attribute<R> {
     }
 }
 
+/// Formats the attribute as its name followed by its value.
+///
+/// The value is interpreted according to the attribute name, as for
+/// `Attribute::value`, so that constants are printed using their names.
+/// For example, a `DW_AT_language` attribute with a value of `0x1c` is
+/// printed as `DW_AT_language: DW_LANG_Rust`.
+impl<R: Reader> fmt::Display for Attribute<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        write!(f, "{}: {}", self.name, self.value())
+    }
+}
+
+/// A value in a discriminant list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscriminantValue {
+    /// A value of a signed discriminant.
+    Signed(i64),
+    /// A value of an unsigned discriminant.
+    Unsigned(u64),
+}
+
+/// An entry in the discriminant list of a `DW_TAG_variant` entry.
+///
+/// See Section 5.7.10 of the DWARF 5 standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscriminantListEntry {
+    /// "The variant is selected if the discriminant is equal to the label."
+    Label(DiscriminantValue),
+    /// "The variant is selected if the discriminant is between the low and
+    /// high bounds, inclusive."
+    Range {
+        /// The low bound of the range.
+        low: DiscriminantValue,
+        /// The high bound of the range.
+        high: DiscriminantValue,
+    },
+}
+
+/// An iterator over the entries in the block value of a `DW_AT_discr_list`
+/// attribute.
+///
+/// This is returned by `Attribute::discr_list_value`.
+#[derive(Debug, Clone)]
+pub struct DiscriminantListIter<R: Reader> {
+    input: R,
+    signed: bool,
+}
+
+impl<R: Reader> DiscriminantListIter<R> {
+    /// Construct a new `DiscriminantListIter` for the given block data.
+    ///
+    /// `signed` specifies whether the type of the discriminant is signed.
+    pub fn new(input: R, signed: bool) -> Self {
+        DiscriminantListIter { input, signed }
+    }
+
+    /// Advance the iterator to the next entry in the list.
+    pub fn next(&mut self) -> Result<Option<DiscriminantListEntry>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parse_entry() {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse_entry(&mut self) -> Result<DiscriminantListEntry> {
+        let descriptor = constants::DwDsc(self.input.read_u8()?);
+        match descriptor {
+            constants::DW_DSC_label => Ok(DiscriminantListEntry::Label(self.parse_value()?)),
+            constants::DW_DSC_range => {
+                let low = self.parse_value()?;
+                let high = self.parse_value()?;
+                Ok(DiscriminantListEntry::Range { low, high })
+            }
+            _ => Err(Error::UnknownDiscriminantDescriptor(descriptor)),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<DiscriminantValue> {
+        if self.signed {
+            self.input.read_sleb128().map(DiscriminantValue::Signed)
+        } else {
+            self.input.read_uleb128().map(DiscriminantValue::Unsigned)
+        }
+    }
+}
+
+impl<R: Reader> FallibleIterator for DiscriminantListIter<R> {
+    type Item = DiscriminantListEntry;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        DiscriminantListIter::next(self)
+    }
+}
+
 fn length_u8_value<R: Reader>(input: &mut R) -> Result<R> {
     let len = input.read_u8().map(R::Offset::from_u8)?;
     input.split(len)
@@ -1623,175 +2702,328 @@ fn length_uleb128_value<R: Reader>(input: &mut R) -> Result<R> {
 fn parse_attribute<'unit, 'abbrev, R: Reader>(
     input: &mut R,
     unit: &'unit UnitHeader<R, R::Offset>,
-    mut specs: &'abbrev [AttributeSpecification],
+    specs: &'abbrev [AttributeSpecification],
 ) -> Result<(Attribute<R>, &'abbrev [AttributeSpecification])> {
     let spec = specs[0];
-    specs = &specs[1..];
-    let mut form = spec.form();
-    loop {
-        let value = match form {
-            constants::DW_FORM_indirect => {
-                let dynamic_form = input.read_uleb128()?;
-                form = constants::DwForm(dynamic_form);
-                continue;
-            }
-            constants::DW_FORM_addr => {
-                let addr = input.read_address(unit.address_size())?;
-                AttributeValue::Addr(addr)
-            }
-            constants::DW_FORM_block1 => {
-                let block = length_u8_value(input)?;
-                AttributeValue::Block(block)
-            }
-            constants::DW_FORM_block2 => {
-                let block = length_u16_value(input)?;
-                AttributeValue::Block(block)
-            }
-            constants::DW_FORM_block4 => {
-                let block = length_u32_value(input)?;
-                AttributeValue::Block(block)
-            }
-            constants::DW_FORM_block => {
-                let block = length_uleb128_value(input)?;
-                AttributeValue::Block(block)
+    let form = parse_attribute_form(input, spec.form())?;
+    let value = parse_attribute_value(input, unit, spec, form)?;
+    let attr = Attribute {
+        name: spec.name(),
+        value: value,
+    };
+    Ok((attr, &specs[1..]))
+}
+
+/// The maximum number of `DW_FORM_indirect` forms that may precede an
+/// attribute value.
+const MAX_INDIRECT_FORMS: usize = 8;
+
+/// Read the actual form of an attribute if its declared form is
+/// `DW_FORM_indirect`.
+fn parse_attribute_form<R: Reader>(input: &mut R, form: constants::DwForm) -> Result<constants::DwForm> {
+    if form != constants::DW_FORM_indirect {
+        return Ok(form);
+    }
+    for _ in 0..MAX_INDIRECT_FORMS {
+        let form = constants::DwForm(input.read_uleb128()?);
+        match form {
+            constants::DW_FORM_indirect => {}
+            // The value of an implicit constant is stored in the abbreviation,
+            // so it can't be selected by the entry.
+            constants::DW_FORM_implicit_const => return Err(Error::InvalidIndirectForm),
+            _ => return Ok(form),
+        }
+    }
+    Err(Error::InvalidIndirectForm)
+}
+
+/// Advance `input` past the value of an attribute, without parsing the value.
+fn skip_attribute<R: Reader>(
+    input: &mut R,
+    unit: &UnitHeader<R, R::Offset>,
+    spec: AttributeSpecification,
+) -> Result<()> {
+    let form = parse_attribute_form(input, spec.form())?;
+    match form {
+        constants::DW_FORM_block1 => {
+            let len = input.read_u8().map(R::Offset::from_u8)?;
+            input.skip(len)
+        }
+        constants::DW_FORM_block2 => {
+            let len = input.read_u16().map(R::Offset::from_u16)?;
+            input.skip(len)
+        }
+        constants::DW_FORM_block4 => {
+            let len = input.read_u32().map(R::Offset::from_u32)?;
+            input.skip(len)
+        }
+        constants::DW_FORM_block | constants::DW_FORM_exprloc => {
+            let len = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            input.skip(len)
+        }
+        constants::DW_FORM_udata |
+        constants::DW_FORM_sdata |
+        constants::DW_FORM_ref_udata |
+        constants::DW_FORM_strx |
+        constants::DW_FORM_GNU_str_index |
+        constants::DW_FORM_addrx |
+        constants::DW_FORM_GNU_addr_index |
+        constants::DW_FORM_loclistx |
+        constants::DW_FORM_rnglistx => input.skip_leb128(),
+        constants::DW_FORM_string => {
+            let len = input.find(0)?;
+            input.skip(len + R::Offset::from_u8(1))
+        }
+        _ => {
+            let size = if form == spec.form() {
+                spec.size(unit)
+            } else {
+                AttributeSpecification::new(spec.name(), form, None).size(unit)
+            };
+            match size {
+                Some(size) => input.skip(R::Offset::from_u64(size as u64)?),
+                None => Err(Error::UnknownForm),
             }
-            constants::DW_FORM_data1 => {
+        }
+    }
+}
+
+fn parse_attribute_value<'unit, R: Reader>(
+    input: &mut R,
+    unit: &'unit UnitHeader<R, R::Offset>,
+    spec: AttributeSpecification,
+    form: constants::DwForm,
+) -> Result<AttributeValue<R>> {
+    Ok(match form {
+        constants::DW_FORM_addr => {
+            let addr = input.read_address(unit.address_size())?;
+            AttributeValue::Addr(addr)
+        }
+        constants::DW_FORM_block1 => {
+            let block = length_u8_value(input)?;
+            AttributeValue::Block(block)
+        }
+        constants::DW_FORM_block2 => {
+            let block = length_u16_value(input)?;
+            AttributeValue::Block(block)
+        }
+        constants::DW_FORM_block4 => {
+            let block = length_u32_value(input)?;
+            AttributeValue::Block(block)
+        }
+        constants::DW_FORM_block => {
+            let block = length_uleb128_value(input)?;
+            AttributeValue::Block(block)
+        }
+        constants::DW_FORM_data1 => {
+            let data = input.read_u8_array()?;
+            AttributeValue::Data1(data)
+        }
+        constants::DW_FORM_data2 => {
+            let data = input.read_u8_array()?;
+            AttributeValue::Data2((data, input.endian()))
+        }
+        constants::DW_FORM_data4 => {
+            // DWARF version 2/3 may use DW_FORM_data4/8 for section offsets.
+            // Generally we can defer interpretation of these until
+            // `AttributeValue::value()`, but this is ambiguous for
+            // `DW_AT_data_member_location`.
+            if (unit.version() == 2 || unit.version() == 3) &&
+                spec.name() == constants::DW_AT_data_member_location
+            {
+                let offset = input.read_u32().map(R::Offset::from_u32)?;
+                AttributeValue::SecOffset(offset)
+            } else {
                 let data = input.read_u8_array()?;
-                AttributeValue::Data1(data)
+                AttributeValue::Data4((data, input.endian()))
             }
-            constants::DW_FORM_data2 => {
+        }
+        constants::DW_FORM_data8 => {
+            // DWARF version 2/3 may use DW_FORM_data4/8 for section offsets.
+            // Generally we can defer interpretation of these until
+            // `AttributeValue::value()`, but this is ambiguous for
+            // `DW_AT_data_member_location`.
+            if (unit.version() == 2 || unit.version() == 3) &&
+                spec.name() == constants::DW_AT_data_member_location
+            {
+                let offset = input.read_u64().and_then(R::Offset::from_u64)?;
+                AttributeValue::SecOffset(offset)
+            } else {
                 let data = input.read_u8_array()?;
-                AttributeValue::Data2((data, input.endian()))
-            }
-            constants::DW_FORM_data4 => {
-                // DWARF version 2/3 may use DW_FORM_data4/8 for section offsets.
-                // Generally we can defer interpretation of these until
-                // `AttributeValue::value()`, but this is ambiguous for
-                // `DW_AT_data_member_location`.
-                if (unit.version() == 2 || unit.version() == 3) &&
-                    spec.name() == constants::DW_AT_data_member_location
-                {
-                    let offset = input.read_u32().map(R::Offset::from_u32)?;
-                    AttributeValue::SecOffset(offset)
-                } else {
-                    let data = input.read_u8_array()?;
-                    AttributeValue::Data4((data, input.endian()))
-                }
+                AttributeValue::Data8((data, input.endian()))
             }
-            constants::DW_FORM_data8 => {
-                // DWARF version 2/3 may use DW_FORM_data4/8 for section offsets.
-                // Generally we can defer interpretation of these until
-                // `AttributeValue::value()`, but this is ambiguous for
-                // `DW_AT_data_member_location`.
-                if (unit.version() == 2 || unit.version() == 3) &&
-                    spec.name() == constants::DW_AT_data_member_location
-                {
-                    let offset = input.read_u64().and_then(R::Offset::from_u64)?;
-                    AttributeValue::SecOffset(offset)
-                } else {
-                    let data = input.read_u8_array()?;
-                    AttributeValue::Data8((data, input.endian()))
+        }
+        constants::DW_FORM_data16 => {
+            let data = input.read_u8_array()?;
+            AttributeValue::Data16((data, input.endian()))
+        }
+        constants::DW_FORM_udata => {
+            // Values that don't fit in 64 bits are rare, so only use the
+            // slower 128 bit decoding when they are encountered.
+            let mut rest = input.clone();
+            match rest.read_uleb128() {
+                Ok(data) => {
+                    *input = rest;
+                    AttributeValue::Udata(data)
                 }
+                Err(Error::BadUnsignedLeb128) => {
+                    let data = input.read_uleb128_u128()?;
+                    AttributeValue::Udata128(data.to_le_bytes())
+                }
+                Err(e) => return Err(e),
             }
-            constants::DW_FORM_udata => {
-                let data = input.read_uleb128()?;
-                AttributeValue::Udata(data)
-            }
-            constants::DW_FORM_sdata => {
-                let data = input.read_sleb128()?;
-                AttributeValue::Sdata(data)
-            }
-            constants::DW_FORM_exprloc => {
-                let block = length_uleb128_value(input)?;
-                AttributeValue::Exprloc(Expression(block))
-            }
-            constants::DW_FORM_flag => {
-                let present = input.read_u8()?;
-                AttributeValue::Flag(present != 0)
-            }
-            constants::DW_FORM_flag_present => {
-                // FlagPresent is this weird compile time always true thing that
-                // isn't actually present in the serialized DIEs, only in the abbreviation.
-                AttributeValue::Flag(true)
-            }
-            constants::DW_FORM_sec_offset => {
-                let offset = input.read_offset(unit.format())?;
-                AttributeValue::SecOffset(offset)
-            }
-            constants::DW_FORM_ref1 => {
-                let reference = input.read_u8().map(R::Offset::from_u8)?;
-                AttributeValue::UnitRef(UnitOffset(reference))
-            }
-            constants::DW_FORM_ref2 => {
-                let reference = input.read_u16().map(R::Offset::from_u16)?;
-                AttributeValue::UnitRef(UnitOffset(reference))
-            }
-            constants::DW_FORM_ref4 => {
-                let reference = input.read_u32().map(R::Offset::from_u32)?;
-                AttributeValue::UnitRef(UnitOffset(reference))
-            }
-            constants::DW_FORM_ref8 => {
-                let reference = input.read_u64().and_then(R::Offset::from_u64)?;
-                AttributeValue::UnitRef(UnitOffset(reference))
-            }
-            constants::DW_FORM_ref_udata => {
-                let reference = input.read_uleb128().and_then(R::Offset::from_u64)?;
-                AttributeValue::UnitRef(UnitOffset(reference))
-            }
-            constants::DW_FORM_ref_addr => {
-                // This is an offset, but DWARF version 2 specifies that DW_FORM_ref_addr
-                // has the same size as an address on the target system.  This was changed
-                // in DWARF version 3.
-                let offset = if unit.version() == 2 {
-                    input
-                        .read_address(unit.address_size())
-                        .and_then(R::Offset::from_u64)?
-                } else {
-                    input.read_offset(unit.format())?
-                };
-                AttributeValue::DebugInfoRef(DebugInfoOffset(offset))
-            }
-            constants::DW_FORM_ref_sig8 => {
-                let signature = input.read_u64()?;
-                AttributeValue::DebugTypesRef(DebugTypeSignature(signature))
-            }
-            constants::DW_FORM_ref_sup4 => {
-                let offset = input.read_u32().map(R::Offset::from_u32)?;
-                AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
-            }
-            constants::DW_FORM_ref_sup8 => {
-                let offset = input.read_u64().and_then(R::Offset::from_u64)?;
-                AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
-            }
-            constants::DW_FORM_GNU_ref_alt => {
-                let offset = input.read_offset(unit.format())?;
-                AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
-            }
-            constants::DW_FORM_string => {
-                let string = input.read_null_terminated_slice()?;
-                AttributeValue::String(string)
-            }
-            constants::DW_FORM_strp => {
-                let offset = input.read_offset(unit.format())?;
-                AttributeValue::DebugStrRef(DebugStrOffset(offset))
-            }
-            constants::DW_FORM_strp_sup | constants::DW_FORM_GNU_strp_alt => {
-                let offset = input.read_offset(unit.format())?;
-                AttributeValue::DebugStrRefSup(DebugStrOffset(offset))
-            }
-            constants::DW_FORM_implicit_const => {
-                AttributeValue::Sdata(spec.implicit_const_value())
-            }
-            _ => {
-                return Err(Error::UnknownForm);
+        }
+        constants::DW_FORM_sdata => {
+            let mut rest = input.clone();
+            match rest.read_sleb128() {
+                Ok(data) => {
+                    *input = rest;
+                    AttributeValue::Sdata(data)
+                }
+                Err(Error::BadSignedLeb128) => {
+                    let data = input.read_sleb128_i128()?;
+                    AttributeValue::Sdata128(data.to_le_bytes())
+                }
+                Err(e) => return Err(e),
             }
-        };
-        let attr = Attribute {
-            name: spec.name(),
-            value: value,
-        };
-        return Ok((attr, specs));
-    }
+        }
+        constants::DW_FORM_exprloc => {
+            let block = length_uleb128_value(input)?;
+            AttributeValue::Exprloc(Expression(block))
+        }
+        constants::DW_FORM_flag => {
+            let present = input.read_u8()?;
+            AttributeValue::Flag(present != 0)
+        }
+        constants::DW_FORM_flag_present => {
+            // FlagPresent is this weird compile time always true thing that
+            // isn't actually present in the serialized DIEs, only in the abbreviation.
+            AttributeValue::Flag(true)
+        }
+        constants::DW_FORM_sec_offset => {
+            let offset = input.read_offset(unit.format())?;
+            AttributeValue::SecOffset(offset)
+        }
+        constants::DW_FORM_ref1 => {
+            let reference = input.read_u8().map(R::Offset::from_u8)?;
+            AttributeValue::UnitRef(UnitOffset(reference))
+        }
+        constants::DW_FORM_ref2 => {
+            let reference = input.read_u16().map(R::Offset::from_u16)?;
+            AttributeValue::UnitRef(UnitOffset(reference))
+        }
+        constants::DW_FORM_ref4 => {
+            let reference = input.read_u32().map(R::Offset::from_u32)?;
+            AttributeValue::UnitRef(UnitOffset(reference))
+        }
+        constants::DW_FORM_ref8 => {
+            let reference = input.read_u64().and_then(R::Offset::from_u64)?;
+            AttributeValue::UnitRef(UnitOffset(reference))
+        }
+        constants::DW_FORM_ref_udata => {
+            let reference = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            AttributeValue::UnitRef(UnitOffset(reference))
+        }
+        constants::DW_FORM_ref_addr => {
+            // This is an offset, but DWARF version 2 specifies that DW_FORM_ref_addr
+            // has the same size as an address on the target system.  This was changed
+            // in DWARF version 3.
+            let offset = if unit.version() == 2 {
+                input
+                    .read_address(unit.address_size())
+                    .and_then(R::Offset::from_u64)?
+            } else {
+                input.read_offset(unit.format())?
+            };
+            AttributeValue::DebugInfoRef(DebugInfoOffset(offset))
+        }
+        constants::DW_FORM_ref_sig8 => {
+            let signature = input.read_u64()?;
+            AttributeValue::DebugTypesRef(DebugTypeSignature(signature))
+        }
+        constants::DW_FORM_ref_sup4 => {
+            let offset = input.read_u32().map(R::Offset::from_u32)?;
+            AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
+        }
+        constants::DW_FORM_ref_sup8 => {
+            let offset = input.read_u64().and_then(R::Offset::from_u64)?;
+            AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
+        }
+        constants::DW_FORM_GNU_ref_alt => {
+            let offset = input.read_offset(unit.format())?;
+            AttributeValue::DebugInfoRefSup(DebugInfoOffset(offset))
+        }
+        constants::DW_FORM_string => {
+            let string = input.read_null_terminated_slice()?;
+            AttributeValue::String(string)
+        }
+        constants::DW_FORM_strp => {
+            let offset = input.read_offset(unit.format())?;
+            AttributeValue::DebugStrRef(DebugStrOffset(offset))
+        }
+        constants::DW_FORM_strp_sup | constants::DW_FORM_GNU_strp_alt => {
+            let offset = input.read_offset(unit.format())?;
+            AttributeValue::DebugStrRefSup(DebugStrOffset(offset))
+        }
+        constants::DW_FORM_line_strp => {
+            let offset = input.read_offset(unit.format())?;
+            AttributeValue::DebugLineStrRef(DebugLineStrOffset(offset))
+        }
+        constants::DW_FORM_strx | constants::DW_FORM_GNU_str_index => {
+            let index = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index))
+        }
+        constants::DW_FORM_strx1 => {
+            let index = input.read_u8().map(R::Offset::from_u8)?;
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index))
+        }
+        constants::DW_FORM_strx2 => {
+            let index = input.read_u16().map(R::Offset::from_u16)?;
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index))
+        }
+        constants::DW_FORM_strx3 => {
+            let index = input.read_u24().map(R::Offset::from_u32)?;
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index))
+        }
+        constants::DW_FORM_strx4 => {
+            let index = input.read_u32().map(R::Offset::from_u32)?;
+            AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(index))
+        }
+        constants::DW_FORM_addrx | constants::DW_FORM_GNU_addr_index => {
+            let index = input.read_uleb128()?;
+            AttributeValue::DebugAddrIndex(AddressIndex(index))
+        }
+        constants::DW_FORM_addrx1 => {
+            let index = input.read_u8()?;
+            AttributeValue::DebugAddrIndex(AddressIndex(u64::from(index)))
+        }
+        constants::DW_FORM_addrx2 => {
+            let index = input.read_u16()?;
+            AttributeValue::DebugAddrIndex(AddressIndex(u64::from(index)))
+        }
+        constants::DW_FORM_addrx3 => {
+            let index = input.read_u24()?;
+            AttributeValue::DebugAddrIndex(AddressIndex(u64::from(index)))
+        }
+        constants::DW_FORM_addrx4 => {
+            let index = input.read_u32()?;
+            AttributeValue::DebugAddrIndex(AddressIndex(u64::from(index)))
+        }
+        constants::DW_FORM_loclistx => {
+            let index = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            AttributeValue::DebugLocListsIndex(DebugLocListsIndex(index))
+        }
+        constants::DW_FORM_rnglistx => {
+            let index = input.read_uleb128().and_then(R::Offset::from_u64)?;
+            AttributeValue::DebugRngListsIndex(DebugRngListsIndex(index))
+        }
+        constants::DW_FORM_implicit_const => {
+            AttributeValue::Sdata(spec.implicit_const_value())
+        }
+        _ => {
+            return Err(Error::UnknownForm);
+        }
+    })
 }
 
 /// An iterator over a particular entry's attributes.
@@ -1815,6 +3047,19 @@ where
 }
 
 impl<'abbrev, 'entry, 'unit, R: Reader> AttrsIter<'abbrev, 'entry, 'unit, R> {
+    /// Return the offset within the unit of the next attribute that the
+    /// iterator will parse.
+    ///
+    /// Attributes are returned in the order that they occur in the entry.
+    /// Once all attributes have been parsed, this is the offset of the
+    /// entry's first child or next sibling.
+    #[inline]
+    pub fn offset(&self) -> UnitOffset<R::Offset> {
+        // The entry's attributes are always a suffix of the unit's entries.
+        let unit = self.entry.unit;
+        UnitOffset(unit.header_size() + (unit.entries_buf.len() - self.input.len()))
+    }
+
     /// Advance the iterator and return the next attribute.
     ///
     /// Returns `None` when iteration is finished. If an error
@@ -1829,11 +3074,11 @@ impl<'abbrev, 'entry, 'unit, R: Reader> AttrsIter<'abbrev, 'entry, 'unit, R> {
             // this entry has children; or (2) where this entry's siblings
             // begin.
             if let Some(end) = self.entry.attrs_len.get() {
-                debug_assert_eq!(end, self.input.offset_from(&self.entry.attrs_slice));
+                debug_assert_eq!(Ok(end), self.input.offset_from(&self.entry.attrs_slice));
             } else {
                 self.entry
                     .attrs_len
-                    .set(Some(self.input.offset_from(&self.entry.attrs_slice)));
+                    .set(Some(self.input.offset_from(&self.entry.attrs_slice)?));
             }
 
             return Ok(None);
@@ -1850,6 +3095,76 @@ impl<'abbrev, 'entry, 'unit, R: Reader> AttrsIter<'abbrev, 'entry, 'unit, R> {
             }
         }
     }
+
+    /// Advance the iterator past the next attribute without parsing its
+    /// value, and return the attribute's name.
+    ///
+    /// This is faster than `next` when the value isn't needed, since values
+    /// such as LEB128 numbers are skipped without being decoded.
+    ///
+    /// Returns `None` when iteration is finished. If an error occurs while
+    /// skipping the next attribute, then this error is returned.
+    pub fn skip_attribute(&mut self) -> Result<Option<constants::DwAt>> {
+        if self.attributes.is_empty() {
+            return self.next().map(|_| None);
+        }
+
+        let spec = self.attributes[0];
+        match skip_attribute(&mut self.input, self.entry.unit, spec) {
+            Ok(()) => {
+                self.attributes = &self.attributes[1..];
+                Ok(Some(spec.name()))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    /// Advance the iterator and return the next attribute, together with
+    /// information about how it was encoded.
+    ///
+    /// Returns `None` when iteration is finished. If an error
+    /// occurs while parsing the next attribute, then this error
+    /// is returned, and all subsequent calls return `None`.
+    pub fn next_with_metadata(
+        &mut self,
+    ) -> Result<Option<(Attribute<R>, AttributeMetadata<R::Offset>)>> {
+        if self.attributes.is_empty() {
+            return self.next().map(|_| None);
+        }
+
+        let spec = self.attributes[0];
+        let unit = self.entry.unit;
+        let result = parse_attribute_form(&mut self.input, spec.form()).and_then(|form| {
+            let offset = unit.header_size() + self.input.offset_from(&unit.entries_buf)?;
+            let offset = UnitOffset(offset);
+            let len = self.input.len();
+            let value = parse_attribute_value(&mut self.input, unit, spec, form)?;
+            let metadata = AttributeMetadata {
+                form,
+                class: value.class(),
+                offset,
+                length: len - self.input.len(),
+            };
+            let attr = Attribute {
+                name: spec.name(),
+                value: value,
+            };
+            Ok((attr, metadata))
+        });
+        match result {
+            Ok(result) => {
+                self.attributes = &self.attributes[1..];
+                Ok(Some(result))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
 }
 
 impl<'abbrev, 'entry, 'unit, R: Reader> FallibleIterator for AttrsIter<'abbrev, 'entry, 'unit, R> {
@@ -1874,6 +3189,16 @@ impl<'abbrev, 'entry, 'unit, R: Reader> FallibleIterator for AttrsIter<'abbrev,
 /// the entry following the current entry will be a sibling or child. `current()`
 /// will return `None` if the current entry is a null entry, which signifies the
 /// end of the current tree depth.
+///
+/// Can be [used with
+/// `FallibleIterator`](./index.html#using-with-fallibleiterator), in which
+/// case it yields the same items as `next_dfs()`, with the entries cloned.
+///
+/// Cloning an `EntriesCursor` is cheap: it copies the reader and the current
+/// entry, and does not allocate. A clone can be used to save the current
+/// position and later resume traversal from it. If the position needs to be
+/// stored independently of the cursor's borrows, use `checkpoint()` and
+/// `restore()` instead.
 #[derive(Clone, Debug)]
 pub struct EntriesCursor<'abbrev, 'unit, R>
 where
@@ -1884,6 +3209,8 @@ where
     abbreviations: &'abbrev Abbreviations,
     cached_current: Option<DebuggingInformationEntry<'abbrev, 'unit, R, R::Offset>>,
     delta_depth: isize,
+    depth: isize,
+    max_depth: Option<usize>,
 }
 
 impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
@@ -1896,6 +3223,76 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
         self.cached_current.as_ref()
     }
 
+    /// Return the depth of the current entry, relative to the entry that the
+    /// cursor started at.
+    ///
+    /// The first entry has a depth of 0, and its children have a depth of 1.
+    /// A null entry has the same depth as the siblings that it terminates.
+    #[inline]
+    pub fn depth(&self) -> isize {
+        self.depth
+    }
+
+    /// Save the current position of the cursor.
+    ///
+    /// The returned checkpoint can be passed to `restore()` to return to
+    /// this position, either on this cursor or on any other cursor for the
+    /// same unit.
+    pub fn checkpoint(&self) -> EntriesCursorCheckpoint<R::Offset> {
+        let offset = match self.cached_current {
+            Some(ref current) => current.offset(),
+            // The cursor's input is always a suffix of the unit's entries.
+            None => UnitOffset(
+                self.unit.header_size() + (self.unit.entries_buf.len() - self.input.len()),
+            ),
+        };
+        EntriesCursorCheckpoint {
+            offset,
+            current: self.cached_current.is_some(),
+            delta_depth: self.delta_depth,
+            depth: self.depth,
+        }
+    }
+
+    /// Return the cursor to a position previously saved with `checkpoint()`.
+    ///
+    /// The checkpoint must have been created by a cursor for the same unit.
+    /// If there was a current entry at the saved position, then it is parsed
+    /// again, which only requires reading its abbreviation code.
+    pub fn restore(&mut self, checkpoint: &EntriesCursorCheckpoint<R::Offset>) -> Result<()> {
+        let end = self.unit.header_size() + self.unit.entries_buf.len();
+        let mut input = if checkpoint.offset.0 == end && !checkpoint.current {
+            let mut input = self.unit.entries_buf.clone();
+            input.empty();
+            input
+        } else {
+            self.unit.range_from(checkpoint.offset..)?
+        };
+        let current = if checkpoint.current {
+            match DebuggingInformationEntry::parse(&mut input, self.unit, self.abbreviations)? {
+                Some(entry) => Some(entry),
+                None => return Err(Error::NoEntryAtGivenOffset),
+            }
+        } else {
+            None
+        };
+        self.input = input;
+        self.cached_current = current;
+        self.delta_depth = checkpoint.delta_depth;
+        self.depth = checkpoint.depth;
+        Ok(())
+    }
+
+    /// Apply the `max_die_depth` limit of the given `ReaderLimits` to this
+    /// cursor.
+    ///
+    /// Depth is measured relative to the entry that the cursor started at.
+    /// Moving the cursor deeper than the limit fails with
+    /// `Error::LimitExceeded`.
+    pub fn set_limits(&mut self, limits: &ReaderLimits) {
+        self.max_depth = limits.max_die_depth;
+    }
+
     /// Move the cursor to the next DIE in the tree.
     ///
     /// Returns `Some` if there is a next entry, even if this entry is null.
@@ -1911,7 +3308,15 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
             return Ok(None);
         }
 
+        self.depth += self.delta_depth;
+
         match DebuggingInformationEntry::parse(&mut self.input, self.unit, self.abbreviations) {
+            Ok(Some(_)) if self.max_depth.map_or(false, |max| self.depth > max as isize) => {
+                self.input.empty();
+                self.delta_depth = 0;
+                self.cached_current = None;
+                Err(Error::LimitExceeded)
+            }
             Ok(Some(entry)) => {
                 self.delta_depth = entry.has_children() as isize;
                 self.cached_current = Some(entry);
@@ -2074,6 +3479,56 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
         }
     }
 
+    /// Skip over the children of the current entry.
+    ///
+    /// If the current entry has children, then the cursor is moved to the null
+    /// entry that terminates its list of children, and `current()` will return
+    /// `None`. The next call to `next_dfs()` will then move to the entry following
+    /// the current entry's subtree, with a delta depth that is relative to the
+    /// depth of the current entry. If the current entry does not have children,
+    /// then the cursor is not moved.
+    ///
+    /// The `DW_AT_sibling` attribute is used to skip the children if it is
+    /// present and valid. Otherwise the children are parsed in order to find the
+    /// end of the subtree.
+    pub fn skip_children(&mut self) -> Result<()> {
+        let input = match self.cached_current {
+            Some(ref current) if current.has_children() => match current.sibling() {
+                Some(sibling_input) => sibling_input,
+                None => {
+                    let mut input = current.after_attrs()?;
+                    let mut depth = 1;
+                    while depth > 0 && !input.is_empty() {
+                        match DebuggingInformationEntry::parse(
+                            &mut input,
+                            self.unit,
+                            self.abbreviations,
+                        )? {
+                            Some(entry) => if entry.has_children() {
+                                match entry.sibling() {
+                                    Some(sibling_input) => input = sibling_input,
+                                    None => {
+                                        input = entry.after_attrs()?;
+                                        depth += 1;
+                                    }
+                                }
+                            } else {
+                                input = entry.after_attrs()?;
+                            },
+                            None => depth -= 1,
+                        }
+                    }
+                    input
+                }
+            },
+            _ => return Ok(()),
+        };
+        self.input = input;
+        self.cached_current = None;
+        self.delta_depth = 0;
+        Ok(())
+    }
+
     /// Move the cursor to the next sibling DIE of the current one.
     ///
     /// Returns `Ok(Some(entry))` when the cursor has been moved to
@@ -2083,6 +3538,11 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
     /// Once `Ok(None)` is returned, this method will continue to return
     /// `Ok(None)` until either `next_entry` or `next_dfs` is called.
     ///
+    /// If the current entry has a valid `DW_AT_sibling` attribute, then it is
+    /// used to skip over the current entry's children without parsing them.
+    /// The attribute may use any of the reference forms, including
+    /// `DW_FORM_ref_addr` as long as it refers to an entry in the same unit.
+    ///
     /// Here is an example that iterates over all of the direct children of the
     /// root entry:
     ///
@@ -2199,6 +3659,7 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
                     // to it (which keeps us at the same depth).
                     self.input = sibling_input;
                     self.cached_current = None;
+                    self.delta_depth = 0;
                 } else {
                     // This entry has children, so the next entry is
                     // down one level.
@@ -2225,6 +3686,158 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
     }
 }
 
+impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
+    /// Iterate over the direct children of the current entry.
+    ///
+    /// This avoids the need to track the delta depth returned by `next_dfs`.
+    /// Each call to `next` on the returned iterator skips the remainder of the
+    /// previous child's subtree, and returns the next child. The cursor may
+    /// be moved deeper into a child's subtree between calls, for example by
+    /// iterating over the child's own children.
+    ///
+    /// Once `next` returns `None`, the cursor has finished the subtree of the
+    /// current entry, so the next call to `next_dfs` moves to the entry
+    /// following the subtree.
+    ///
+    /// ```
+    /// # use gimli::{DebugAbbrev, DebugInfo, LittleEndian};
+    /// # let info_buf = [
+    /// #     // Unit length, version, abbrev offset, address size.
+    /// #     0x0d, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04,
+    /// #     // Root, with two children, the first of which has a child.
+    /// #     0x01, 0x01, 0x02, 0x00, 0x02, 0x00,
+    /// # ];
+    /// # let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+    /// # let abbrev_buf = [
+    /// #     // Code, DW_TAG_compile_unit, DW_CHILDREN_yes, no attributes.
+    /// #     0x01, 0x11, 0x01, 0x00, 0x00,
+    /// #     // Code, DW_TAG_subprogram, DW_CHILDREN_no, no attributes.
+    /// #     0x02, 0x2e, 0x00, 0x00, 0x00,
+    /// #     0x00,
+    /// # ];
+    /// # let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+    /// # let unit = debug_info.units().next().unwrap().unwrap();
+    /// # let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+    /// let mut cursor = unit.entries(&abbrevs);
+    ///
+    /// // Move the cursor to the root.
+    /// assert!(cursor.next_dfs().unwrap().is_some());
+    ///
+    /// let mut count = 0;
+    /// let mut children = cursor.children();
+    /// while let Some(child) = children.next().unwrap() {
+    ///     println!("{:?} is a child of the root", child.tag());
+    ///     count += 1;
+    /// }
+    /// assert_eq!(count, 2);
+    /// ```
+    pub fn children<'me>(&'me mut self) -> EntriesCursorChildren<'abbrev, 'unit, 'me, R> {
+        let depth = self.depth + 1;
+        let done = !self.current().map_or(false, |entry| entry.has_children());
+        EntriesCursorChildren {
+            cursor: self,
+            depth,
+            started: false,
+            done,
+        }
+    }
+}
+
+/// An iterator over the direct children of an entry in an `EntriesCursor`.
+///
+/// See [the documentation for
+/// `EntriesCursor::children()`](./struct.EntriesCursor.html#method.children)
+/// for details.
+#[derive(Debug)]
+pub struct EntriesCursorChildren<'abbrev, 'unit, 'cursor, R>
+where
+    'abbrev: 'cursor,
+    'unit: 'cursor,
+    R: Reader + 'unit,
+{
+    cursor: &'cursor mut EntriesCursor<'abbrev, 'unit, R>,
+    depth: isize,
+    started: bool,
+    done: bool,
+}
+
+impl<'abbrev, 'unit, 'cursor, R: Reader> EntriesCursorChildren<'abbrev, 'unit, 'cursor, R> {
+    /// Move the cursor to the next child, and return it.
+    ///
+    /// Returns `None` if there are no more children.
+    pub fn next(
+        &mut self,
+    ) -> Result<Option<&DebuggingInformationEntry<'abbrev, 'unit, R, R::Offset>>> {
+        if self.done {
+            return Ok(None);
+        }
+        loop {
+            if self.started && self.cursor.depth() == self.depth &&
+                self.cursor.current().is_some()
+            {
+                // Skip the rest of the previous child's subtree.
+                self.cursor.skip_children()?;
+            }
+            self.started = true;
+
+            if self.cursor.next_entry()?.is_none() {
+                self.done = true;
+                return Ok(None);
+            }
+            if self.cursor.depth() <= self.depth {
+                if self.cursor.depth() < self.depth || self.cursor.current().is_none() {
+                    // The null entry that terminates the children.
+                    self.done = true;
+                    return Ok(None);
+                }
+                return Ok(self.cursor.current());
+            }
+        }
+    }
+
+    /// Return the cursor, which is positioned at the current child.
+    ///
+    /// The cursor may be moved into the current child's subtree, but must not
+    /// be moved past the end of it.
+    pub fn cursor(&mut self) -> &mut EntriesCursor<'abbrev, 'unit, R> {
+        self.cursor
+    }
+}
+
+impl<'abbrev, 'unit, R: Reader> FallibleIterator for EntriesCursor<'abbrev, 'unit, R> {
+    type Item = (isize, DebuggingInformationEntry<'abbrev, 'unit, R, R::Offset>);
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        self.next_dfs()
+            .map(|next| next.map(|(delta_depth, entry)| (delta_depth, entry.clone())))
+    }
+}
+
+/// A saved position of an `EntriesCursor`.
+///
+/// A checkpoint only stores offsets, so it does not borrow the cursor, the
+/// unit, or the abbreviations. It is created by
+/// [`EntriesCursor::checkpoint`](./struct.EntriesCursor.html#method.checkpoint)
+/// and used by
+/// [`EntriesCursor::restore`](./struct.EntriesCursor.html#method.restore).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntriesCursorCheckpoint<T = usize> {
+    offset: UnitOffset<T>,
+    current: bool,
+    delta_depth: isize,
+    depth: isize,
+}
+
+impl<T: Copy> EntriesCursorCheckpoint<T> {
+    /// The offset of the entry that the cursor was pointing to, or the
+    /// offset of the next entry if the cursor was not pointing to an entry.
+    #[inline]
+    pub fn offset(&self) -> UnitOffset<T> {
+        self.offset
+    }
+}
+
 /// The state information for a tree view of the Debugging Information Entries.
 ///
 /// The `EntriesTree` can be used to recursively iterate through the DIE
@@ -2232,6 +3845,13 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
 /// shared state for all nodes in the tree, avoiding any duplicate parsing of
 /// entries during the traversal.
 ///
+/// Nodes borrow the tree mutably, so they cannot be cloned. To save a position
+/// in the tree, record the offset of a node's entry, and later create a new
+/// tree rooted at that offset with `UnitHeader::entries_tree`.
+///
+/// Attributes that are needed for every node can be read in the same pass
+/// that locates each node by using `set_prefetch`.
+///
 /// ## Example Usage
 /// ```rust,no_run
 /// extern crate gimli;
@@ -2278,6 +3898,9 @@ where
     input: R,
     entry: Option<DebuggingInformationEntry<'abbrev, 'unit, R, R::Offset>>,
     depth: isize,
+    max_depth: Option<usize>,
+    prefetch: Vec<constants::DwAt>,
+    prefetched: Vec<Option<AttributeValue<R>>>,
 }
 
 impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
@@ -2294,7 +3917,70 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
             input,
             entry: None,
             depth: 0,
+            max_depth: None,
+            prefetch: Vec::new(),
+            prefetched: Vec::new(),
+        }
+    }
+
+    /// Apply the `max_die_depth` limit of the given `ReaderLimits` to this
+    /// tree.
+    ///
+    /// Depth is measured relative to the root of the tree. Moving deeper
+    /// than the limit fails with `Error::LimitExceeded`.
+    pub fn set_limits(&mut self, limits: &ReaderLimits) {
+        self.max_depth = limits.max_die_depth;
+    }
+
+    /// Set the attributes to read for each node of the tree.
+    ///
+    /// The values of these attributes are read in a single pass over the
+    /// attributes of each node's entry when the tree moves to that node.
+    /// `EntriesTreeNode::attr_value` then returns them without parsing the
+    /// attributes again. The default is to not read any attributes.
+    pub fn set_prefetch(&mut self, names: &[constants::DwAt]) {
+        self.prefetch = names.to_vec();
+        self.prefetched.clear();
+    }
+
+    fn prefetch(&mut self) -> Result<()> {
+        self.prefetched.clear();
+        if self.prefetch.is_empty() {
+            return Ok(());
+        }
+        let entry = match self.entry {
+            Some(ref entry) => entry,
+            None => return Ok(()),
+        };
+        self.prefetched.resize(self.prefetch.len(), None);
+        let mut attrs = entry.attrs();
+        loop {
+            let attr = match attrs.next() {
+                Ok(Some(attr)) => attr,
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    self.input.empty();
+                    self.entry = None;
+                    return Err(e);
+                }
+            };
+            if let Some(index) = self.prefetch.iter().position(|name| *name == attr.name()) {
+                if self.prefetched[index].is_none() {
+                    self.prefetched[index] = Some(attr.value());
+                }
+            }
+        }
+    }
+
+    fn check_depth(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if self.entry.is_some() && self.depth > max_depth as isize {
+                self.input.empty();
+                self.entry = None;
+                return Err(Error::LimitExceeded);
+            }
         }
+        Ok(())
     }
 
     /// Returns the root node of the tree.
@@ -2306,6 +3992,7 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
             return Err(Error::UnexpectedNull);
         }
         self.depth = 0;
+        self.prefetch()?;
         Ok(EntriesTreeNode::new(self, 1))
     }
 
@@ -2341,6 +4028,8 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
             ) {
                 Ok(entry) => {
                     self.entry = entry;
+                    self.check_depth()?;
+                    self.prefetch()?;
                     Ok(self.entry.is_some())
                 }
                 Err(e) => {
@@ -2385,7 +4074,9 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
             match DebuggingInformationEntry::parse(&mut self.input, self.unit, self.abbreviations) {
                 Ok(entry) => {
                     self.entry = entry;
+                    self.check_depth()?;
                     if self.depth == depth {
+                        self.prefetch()?;
                         return Ok(self.entry.is_some());
                     }
                 }
@@ -2432,6 +4123,22 @@ impl<'abbrev, 'unit, 'tree, R: Reader> EntriesTreeNode<'abbrev, 'unit, 'tree, R>
         self.tree.entry.as_ref().unwrap()
     }
 
+    /// Find the first attribute in the current entry which has the given
+    /// name, and return its normalized value. Returns `Ok(None)` if no
+    /// attribute is found.
+    ///
+    /// If the attribute is one of those given to `EntriesTree::set_prefetch`,
+    /// then its value has already been read and the attributes are not
+    /// parsed again.
+    pub fn attr_value(&self, name: constants::DwAt) -> Result<Option<AttributeValue<R>>> {
+        if let Some(index) = self.tree.prefetch.iter().position(|n| *n == name) {
+            if let Some(value) = self.tree.prefetched.get(index) {
+                return Ok(value.clone());
+            }
+        }
+        self.entry().attr_value(name)
+    }
+
     /// Create an iterator for the children of the current entry.
     ///
     /// The current entry can no longer be accessed after creating the
@@ -2528,8 +4235,12 @@ where
 }
 
 impl<R: Reader> Section<R> for DebugTypes<R> {
-    fn section_name() -> &'static str {
-        ".debug_types"
+    fn id() -> SectionId {
+        SectionId::DebugTypes
+    }
+
+    fn reader(&self) -> &R {
+        &self.debug_types_section
     }
 }
 
@@ -2566,6 +4277,29 @@ impl<R: Reader> DebugTypes<R> {
             offset: DebugTypesOffset(R::Offset::from_u8(0)),
         }
     }
+
+    /// Get the TypeUnitHeader located at offset from this .debug_types section.
+    pub fn header_from_offset(
+        &self,
+        offset: DebugTypesOffset<R::Offset>,
+    ) -> Result<TypeUnitHeader<R, R::Offset>> {
+        let input = &mut self.debug_types_section.clone();
+        input.skip(offset.0)?;
+        parse_type_unit_header(input, offset)
+    }
+
+    /// Collect the offsets of all of the type unit headers in this
+    /// `.debug_types` section.
+    ///
+    /// The headers can later be parsed again using `header_from_offset`.
+    pub fn unit_offsets(&self) -> Result<Vec<DebugTypesOffset<R::Offset>>> {
+        let mut offsets = Vec::new();
+        let mut iter = self.units();
+        while let Some(header) = iter.next()? {
+            offsets.push(header.offset());
+        }
+        Ok(offsets)
+    }
 }
 
 /// An iterator over the type-units of this `.debug_types` section.
@@ -2580,21 +4314,101 @@ pub struct TypeUnitHeadersIter<R: Reader> {
 }
 
 impl<R: Reader> TypeUnitHeadersIter<R> {
+    /// Return the section offset of the next unit that the iterator will
+    /// parse.
+    ///
+    /// Units are returned in the order that they occur in the section. If
+    /// parsing a unit header fails, this is the offset of that unit.
+    #[inline]
+    pub fn offset(&self) -> DebugTypesOffset<R::Offset> {
+        self.offset
+    }
+
     /// Advance the iterator to the next type unit header.
+    ///
+    /// Zero padding between units is skipped.
     pub fn next(&mut self) -> Result<Option<TypeUnitHeader<R, R::Offset>>> {
-        if self.input.is_empty() {
-            Ok(None)
-        } else {
-            let len = self.input.len();
-            match parse_type_unit_header(&mut self.input, self.offset) {
-                Ok(header) => {
-                    self.offset.0 += len - self.input.len();
-                    Ok(Some(header))
-                }
+        loop {
+            match self.next_or_padding()? {
+                Some(EntryOrPadding::Entry(header)) => return Ok(Some(header)),
+                Some(EntryOrPadding::Padding { .. }) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Advance the iterator to the next type unit header, recovering from errors in
+    /// the headers of individual units.
+    ///
+    /// If a unit's header is invalid but its length is valid, then
+    /// `Ok(Some(Err(e)))` is returned, where `e` contains the section offset of
+    /// the unit, and iteration continues with the next unit. Zero padding
+    /// between units is skipped.
+    ///
+    /// If a unit's length is invalid then the following units cannot be
+    /// located, so `Err` is returned and iteration ends.
+    pub fn next_recoverable(
+        &mut self,
+    ) -> Result<Option<::std::result::Result<TypeUnitHeader<R, R::Offset>, ContextError>>> {
+        loop {
+            if self.input.is_empty() {
+                return Ok(None);
+            }
+
+            let padding = padding_length(&self.input)?;
+            if padding > R::Offset::from_u8(0) {
+                self.input.skip(padding)?;
+                self.offset.0 = self.offset.0.checked_add(padding).ok_or(Error::OffsetOverflow)?;
+                continue;
+            }
+
+            let size = match unit_size(&self.input) {
+                Ok(size) => size,
                 Err(e) => {
                     self.input.empty();
-                    Err(e)
+                    return Err(e);
                 }
+            };
+            let offset = self.offset;
+            let mut unit = self.input.split(size)?;
+            self.offset.0 = self.offset.0.checked_add(size).ok_or(Error::OffsetOverflow)?;
+
+            return Ok(Some(parse_type_unit_header(&mut unit, offset).map_err(|e| {
+                e.with_context(SectionId::DebugTypes, offset.0.into_u64())
+            })));
+        }
+    }
+
+    /// Advance the iterator to the next type unit header, or to the zero padding
+    /// before it.
+    pub fn next_or_padding(
+        &mut self,
+    ) -> Result<Option<EntryOrPadding<TypeUnitHeader<R, R::Offset>, R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+
+        let padding = padding_length(&self.input)?;
+        if padding > R::Offset::from_u8(0) {
+            let offset = self.offset.0;
+            self.input.skip(padding)?;
+            self.offset.0 = self.offset.0.checked_add(padding).ok_or(Error::OffsetOverflow)?;
+            return Ok(Some(EntryOrPadding::Padding {
+                offset,
+                length: padding,
+            }));
+        }
+
+        let len = self.input.len();
+        match parse_type_unit_header(&mut self.input, self.offset) {
+            Ok(header) => {
+                let size = len - self.input.len();
+                self.offset.0 = self.offset.0.checked_add(size).ok_or(Error::OffsetOverflow)?;
+                Ok(Some(EntryOrPadding::Entry(header)))
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
             }
         }
     }
@@ -2611,6 +4425,7 @@ impl<R: Reader> FallibleIterator for TypeUnitHeadersIter<R> {
 
 /// The header of a type unit's debugging information.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct TypeUnitHeader<R, Offset = usize>
 where
     R: Reader<Offset = Offset>,
@@ -2693,6 +4508,26 @@ where
         self.header.header_size()
     }
 
+    /// The type of this unit, which is always `DW_UT_type`.
+    ///
+    /// Type units in the `.debug_types` section were replaced by `DW_UT_type`
+    /// units in DWARF version 5, but are otherwise equivalent.
+    pub fn unit_type(&self) -> constants::DwUt {
+        constants::DW_UT_type
+    }
+
+    /// The size of the debugging information entries that follow the header
+    /// of this type-unit.
+    pub fn content_size(&self) -> R::Offset {
+        self.header.content_size()
+    }
+
+    /// Return a reader for the raw bytes of the debugging information entries
+    /// that follow the header of this type-unit.
+    pub fn raw_entries_reader(&self) -> R {
+        self.header.raw_entries_reader()
+    }
+
     /// Get the unique type signature for this type unit.
     pub fn type_signature(&self) -> DebugTypeSignature {
         self.type_signature
@@ -2721,6 +4556,17 @@ where
         self.header.entries_at_offset(abbreviations, offset)
     }
 
+    /// Navigate this type unit's `DebuggingInformationEntry`s
+    /// starting at the given offset, after checking that the offset is the
+    /// offset of an entry.
+    pub fn entries_at_offset_checked<'me, 'abbrev>(
+        &'me self,
+        abbreviations: &'abbrev Abbreviations,
+        offset: UnitOffset<R::Offset>,
+    ) -> Result<EntriesCursor<'abbrev, 'me, R>> {
+        self.header.entries_at_offset_checked(abbreviations, offset)
+    }
+
     /// Navigate this type unit's `DebuggingInformationEntry`s as a tree
     /// starting at the given offset.
     pub fn entries_tree<'me, 'abbrev>(
@@ -2841,7 +4687,7 @@ mod tests {
     use abbrev::tests::AbbrevSectionMethods;
     use constants;
     use constants::*;
-    use endianity::{EndianBuf, Endianity, LittleEndian};
+    use endianity::{BigEndian, EndianBuf, Endianity, LittleEndian};
     use leb128;
     use loclists::LocationListsOffset;
     use parser::{Error, Format, Result};
@@ -2889,6 +4735,7 @@ mod tests {
             E: Endianity,
         {
             unit.offset = DebugInfoOffset(self.size() as usize);
+            unit.header.debug_info_offset = Some(unit.offset);
             self.unit(&mut unit.header, &[])
         }
 
@@ -2930,15 +4777,22 @@ mod tests {
                     .append_bytes(extra_header)
                     .append_bytes(unit.entries_buf.into())
                     .mark(&end),
-                5 => section
-                    .mark(&start)
-                    .L16(unit.version)
-                    .D8(constants::DW_UT_compile.0)
-                    .D8(unit.address_size)
-                    .offset(unit.debug_abbrev_offset.0, unit.format)
-                    .append_bytes(extra_header)
-                    .append_bytes(unit.entries_buf.into())
-                    .mark(&end),
+                5 => {
+                    let section = section
+                        .mark(&start)
+                        .L16(unit.version)
+                        .D8(unit.unit_type.0)
+                        .D8(unit.address_size)
+                        .offset(unit.debug_abbrev_offset.0, unit.format);
+                    let section = match unit.dwo_id {
+                        Some(dwo_id) => section.L64(dwo_id),
+                        None => section,
+                    };
+                    section
+                        .append_bytes(extra_header)
+                        .append_bytes(unit.entries_buf.into())
+                        .mark(&end)
+                }
                 _ => unreachable!(),
             };
 
@@ -2994,7 +4848,7 @@ mod tests {
         let buf = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_debug_abbrev_offset(buf, Format::Dwarf32) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3018,7 +4872,7 @@ mod tests {
         let buf = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_debug_abbrev_offset(buf, Format::Dwarf64) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3041,7 +4895,7 @@ mod tests {
         let buf = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_debug_info_offset(buf, Format::Dwarf32) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3065,7 +4919,7 @@ mod tests {
         let buf = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_debug_info_offset(buf, Format::Dwarf64) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3082,6 +4936,9 @@ mod tests {
                 address_size: 8,
                 format: Format::Dwarf64,
                 entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -3093,6 +4950,9 @@ mod tests {
                 address_size: 4,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -3109,6 +4969,118 @@ mod tests {
         assert_eq!(units.next(), Ok(None));
     }
 
+    #[test]
+    fn test_units_padding() {
+        let expected_rest = &[1, 2, 3, 4];
+        let mut unit1 = CompilationUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+                address_size: 4,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugInfoOffset(0),
+        };
+        let mut unit2 = unit1;
+        let section = Section::with_endian(Endian::Little)
+            .comp_unit(&mut unit1)
+            .append_repeated(0, 8)
+            .comp_unit(&mut unit2)
+            .append_repeated(0, 2);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let mut units = debug_info.units();
+        assert_eq!(units.next(), Ok(Some(unit1)));
+        assert_eq!(units.next(), Ok(Some(unit2)));
+        assert_eq!(units.next(), Ok(None));
+
+        let mut units = debug_info.units();
+        assert_eq!(units.offset(), DebugInfoOffset(0));
+        assert_eq!(units.next_or_padding(), Ok(Some(EntryOrPadding::Entry(unit1))));
+        assert_eq!(units.offset(), DebugInfoOffset(15));
+        assert_eq!(
+            units.next_or_padding(),
+            Ok(Some(EntryOrPadding::Padding {
+                offset: 15,
+                length: 8,
+            }))
+        );
+        assert_eq!(units.offset(), DebugInfoOffset(23));
+        assert_eq!(units.next_or_padding(), Ok(Some(EntryOrPadding::Entry(unit2))));
+        assert_eq!(
+            units.next_or_padding(),
+            Ok(Some(EntryOrPadding::Padding {
+                offset: 38,
+                length: 2,
+            }))
+        );
+        assert_eq!(units.offset(), DebugInfoOffset(40));
+        assert_eq!(units.next_or_padding(), Ok(None));
+    }
+
+    #[test]
+    fn test_units_recoverable() {
+        let expected_rest = &[1, 2, 3, 4];
+        let mut unit1 = CompilationUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+                address_size: 4,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugInfoOffset(0),
+        };
+        let mut unit2 = unit1;
+        let bad_unit = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            .comp_unit(&mut unit1)
+            .mark(&bad_unit)
+            // A unit with an unknown version.
+            .L32(7)
+            .L16(9)
+            .L32(0)
+            .D8(4)
+            .comp_unit(&mut unit2)
+            // A unit with a length that is too large.
+            .L32(0x100)
+            .L16(4);
+        section.start().set_const(0);
+        let bad_unit = bad_unit.value().unwrap();
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let mut units = debug_info.units();
+        assert_eq!(units.next_recoverable(), Ok(Some(Ok(unit1))));
+        assert_eq!(
+            units.next_recoverable(),
+            Ok(Some(Err(
+                Error::UnknownVersion(9).with_context(SectionId::DebugInfo, bad_unit)
+            )))
+        );
+        assert_eq!(units.next_recoverable(), Ok(Some(Ok(unit2))));
+        match units.next_recoverable() {
+            Err(Error::UnexpectedEof(_)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+        assert_eq!(units.next_recoverable(), Ok(None));
+
+        let mut units = debug_info.units();
+        assert_eq!(units.next(), Ok(Some(unit1)));
+        assert_eq!(units.next(), Err(Error::UnknownVersion(9)));
+        assert_eq!(units.next(), Ok(None));
+    }
+
     #[test]
     fn test_unit_version_unknown_version() {
         let buf = [0x02, 0x00, 0x00, 0x00, 0xab, 0xcd];
@@ -3134,7 +5106,7 @@ mod tests {
         let rest = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_unit_header(rest) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3149,6 +5121,9 @@ mod tests {
             address_size: 4,
             format: Format::Dwarf32,
             entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_compile,
+            dwo_id: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit, &[])
@@ -3171,6 +5146,9 @@ mod tests {
             address_size: 8,
             format: Format::Dwarf64,
             entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_compile,
+            dwo_id: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit, &[])
@@ -3192,6 +5170,9 @@ mod tests {
             address_size: 4,
             format: Format::Dwarf32,
             entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_compile,
+            dwo_id: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit, &[])
@@ -3214,6 +5195,9 @@ mod tests {
             address_size: 8,
             format: Format::Dwarf64,
             entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_compile,
+            dwo_id: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit, &[])
@@ -3225,6 +5209,79 @@ mod tests {
         assert_eq!(*rest, EndianBuf::new(expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_v5_split_unit_headers() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        for &unit_type in &[constants::DW_UT_skeleton, constants::DW_UT_split_compile] {
+            let mut expected_unit = UnitHeader {
+                unit_length: 0,
+                version: 5,
+                debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+                address_size: 4,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: unit_type,
+                dwo_id: Some(0x0102030405060708),
+            };
+            let section = Section::with_endian(Endian::Little)
+                .unit(&mut expected_unit, &[])
+                .append_bytes(expected_rest);
+            let buf = section.get_contents().unwrap();
+            let rest = &mut EndianBuf::new(&buf, LittleEndian);
+
+            assert_eq!(parse_unit_header(rest), Ok(expected_unit));
+            assert_eq!(*rest, EndianBuf::new(expected_rest, LittleEndian));
+        }
+    }
+
+    #[test]
+    fn test_parse_v5_partial_unit_header() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut expected_unit = UnitHeader {
+            unit_length: 0,
+            version: 5,
+            debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+            address_size: 4,
+            format: Format::Dwarf32,
+            entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_partial,
+            dwo_id: None,
+        };
+        let section = Section::with_endian(Endian::Little)
+            .unit(&mut expected_unit, &[])
+            .append_bytes(expected_rest);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        assert_eq!(unit.unit_type(), constants::DW_UT_partial);
+    }
+
+    #[test]
+    fn test_parse_v5_type_unit_header_unsupported() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut unit = UnitHeader {
+            unit_length: 0,
+            version: 5,
+            debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+            address_size: 4,
+            format: Format::Dwarf32,
+            entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+            debug_info_offset: None,
+            unit_type: constants::DW_UT_type,
+            dwo_id: None,
+        };
+        let section = Section::with_endian(Endian::Little)
+            .unit(&mut unit, &[])
+            .append_bytes(expected_rest);
+        let buf = section.get_contents().unwrap();
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+
+        assert_eq!(parse_unit_header(rest), Err(Error::UnsupportedUnitType));
+    }
+
     #[test]
     fn test_parse_type_offset_32_ok() {
         let buf = [0x12, 0x34, 0x56, 0x78, 0x00];
@@ -3261,7 +5318,7 @@ mod tests {
         let rest = &mut EndianBuf::new(&buf, LittleEndian);
 
         match parse_type_offset(rest, Format::Dwarf32) {
-            Err(Error::UnexpectedEof) => assert!(true),
+            Err(Error::UnexpectedEof(_)) => assert!(true),
             otherwise => panic!("Unexpected result: {:?}", otherwise),
         };
     }
@@ -3277,6 +5334,9 @@ mod tests {
                 address_size: 8,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugTypesOffset(0),
             type_signature: DebugTypeSignature(0xdeadbeefdeadbeef),
@@ -3295,6 +5355,47 @@ mod tests {
         assert_eq!(*rest, EndianBuf::new(expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_unit_header_accessors() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        assert_eq!(unit.unit_type(), constants::DW_UT_compile);
+        assert_eq!(unit.version(), 4);
+        assert_eq!(unit.address_size(), 4);
+        assert_eq!(unit.debug_abbrev_offset(), DebugAbbrevOffset(0));
+        assert_eq!(unit.length_including_self(), info_buf.len());
+        assert_eq!(unit.header_size(), 11);
+        assert_eq!(unit.content_size(), info_buf.len() - 11);
+        assert_eq!(unit.raw_entries_reader(), EndianBuf::new(&info_buf[11..], LittleEndian));
+
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut unit = TypeUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0x08070605),
+                address_size: 8,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugTypesOffset(0),
+            type_signature: DebugTypeSignature(0xdeadbeefdeadbeef),
+            type_offset: UnitOffset(0x78563412),
+        };
+        let section = Section::with_endian(Endian::Little).type_unit(&mut unit);
+        let buf = section.get_contents().unwrap();
+        let input = &mut EndianBuf::new(&buf, LittleEndian);
+        let unit = parse_type_unit_header(input, DebugTypesOffset(0)).unwrap();
+        assert_eq!(unit.unit_type(), constants::DW_UT_type);
+        assert_eq!(unit.header_size(), 23);
+        assert_eq!(unit.content_size(), 9);
+        assert_eq!(unit.raw_entries_reader(), EndianBuf::new(expected_rest, LittleEndian));
+    }
+
     #[test]
     #[cfg(target_pointer_width = "64")]
     fn test_parse_type_unit_header_64_ok() {
@@ -3307,6 +5408,9 @@ mod tests {
                 address_size: 8,
                 format: Format::Dwarf64,
                 entries_buf: EndianBuf::new(expected_rest, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugTypesOffset(0),
             type_signature: DebugTypeSignature(0xdeadbeefdeadbeef),
@@ -3343,6 +5447,9 @@ mod tests {
         let buf = section_contents(|s| s.uleb(block_data.len() as u64).append_bytes(block_data));
         let block = EndianBuf::new(&buf, endian);
 
+        let buf = section_contents(|s| s.L8(0x02));
+        let data1 = EndianBuf::new(&buf, endian);
+
         let buf = section_contents(|s| s.L32(0x01020304));
         let data4 = EndianBuf::new(&buf, endian);
 
@@ -3391,6 +5498,78 @@ mod tests {
                 AttributeValue::Data8(([8, 7, 6, 5, 4, 3, 2, 1], endian)),
                 AttributeValue::Udata(0x0102030405060708),
             ),
+            (
+                5,
+                constants::DW_AT_str_offsets_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugStrOffsetsBase(DebugStrOffsetsBase(0x01020304)),
+            ),
+            (
+                5,
+                constants::DW_AT_addr_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugAddrBase(DebugAddrBase(0x01020304)),
+            ),
+            (
+                5,
+                constants::DW_AT_loclists_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugLocListsBase(DebugLocListsBase(0x01020304)),
+            ),
+            (
+                5,
+                constants::DW_AT_rnglists_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugRngListsBase(DebugRngListsBase(0x01020304)),
+            ),
+            (
+                4,
+                constants::DW_AT_GNU_addr_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugAddrBase(DebugAddrBase(0x01020304)),
+            ),
+            (
+                4,
+                constants::DW_AT_GNU_ranges_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugRngListsBase(DebugRngListsBase(0x01020304)),
+            ),
+            (
+                5,
+                constants::DW_AT_macros,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x01020304),
+                AttributeValue::DebugMacroRef(DebugMacroOffset(0x01020304)),
+            ),
+            (
+                2,
+                constants::DW_AT_address_class,
+                constants::DW_FORM_data1,
+                data1,
+                AttributeValue::Data1([0x02]),
+                AttributeValue::AddressClass(constants::DW_ADDR_x86_far16),
+            ),
+            (
+                5,
+                constants::DW_AT_name,
+                constants::DW_FORM_line_strp,
+                data4,
+                AttributeValue::DebugLineStrRef(DebugLineStrOffset(0x01020304)),
+                AttributeValue::DebugLineStrRef(DebugLineStrOffset(0x01020304)),
+            ),
         ];
 
         for test in tests.iter() {
@@ -3406,6 +5585,350 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_language_name() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev_buf = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_language_name, DW_FORM_data2.
+            0x90, 0x01, 0x05,
+            // DW_AT_language_version, DW_FORM_data4.
+            0x91, 0x01, 0x06,
+            // DW_AT_language, DW_FORM_data2.
+            0x13, 0x05,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x11, 0x00,
+            // DW_AT_language, DW_FORM_data2.
+            0x13, 0x05,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x11, 0x00,
+            // DW_AT_language_name, DW_FORM_data2.
+            0x90, 0x01, 0x05,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x2e, 0x00,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info_buf = [
+            // Unit length, version, abbrev offset, address size.
+            0x18, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Language name, version, and language.
+            0x01,
+            0x03, 0x00,
+            0x98, 0x11, 0x03, 0x00,
+            0x0c, 0x00,
+            // 0x14: Language only.
+            0x02,
+            0x1c, 0x00,
+            // 0x17: Language name only.
+            0x03,
+            0x19, 0x00,
+            // 0x1a: No language.
+            0x04,
+            0x00,
+        ];
+
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let mut cursor = unit.entries_at_offset(&abbrevs, UnitOffset(0x0b)).unwrap();
+        cursor.next_entry().unwrap();
+        let entry = cursor.current().unwrap();
+        assert_eq!(
+            entry.attr_value(DW_AT_language_name),
+            Ok(Some(AttributeValue::LanguageName(DW_LNAME_C)))
+        );
+        assert_eq!(
+            entry.attr_value(DW_AT_language_version),
+            Ok(Some(AttributeValue::Udata(201112)))
+        );
+
+        let language_name = |offset| {
+            let mut cursor = unit.entries_at_offset(&abbrevs, UnitOffset(offset)).unwrap();
+            cursor.next_entry().unwrap();
+            cursor.current().unwrap().language_name()
+        };
+        assert_eq!(language_name(0x0b), Ok(Some((DW_LNAME_C, 201112))));
+        assert_eq!(language_name(0x14), Ok(Some((DW_LNAME_Rust, 0))));
+        assert_eq!(language_name(0x17), Ok(Some((DW_LNAME_Rust, 0))));
+        assert_eq!(language_name(0x1a), Ok(None));
+    }
+
+    #[test]
+    fn test_pc_range() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev_buf = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_addr.
+            0x12, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x05, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_udata.
+            0x12, 0x0f,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info_buf = [
+            // Unit length, version, abbrev offset, address size.
+            0x3a, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Root entry.
+            0x01,
+            // 0x0c: High pc is an address.
+            0x02,
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // 0x1d: High pc is an offset.
+            0x03,
+            0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            // 0x2a: No high pc.
+            0x04,
+            0x00, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // 0x33: High pc overflows.
+            0x05,
+            0xf8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0x10,
+            0x00,
+        ];
+
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let pc_range = |offset| {
+            let mut cursor = unit.entries_at_offset(&abbrevs, UnitOffset(offset)).unwrap();
+            cursor.next_entry().unwrap();
+            cursor.current().unwrap().pc_range()
+        };
+        assert_eq!(pc_range(0x0b), Ok(None));
+        assert_eq!(
+            pc_range(0x0c),
+            Ok(Some(rnglists::Range {
+                begin: 0x1000,
+                end: 0x1100,
+            }))
+        );
+        assert_eq!(
+            pc_range(0x1d),
+            Ok(Some(rnglists::Range {
+                begin: 0x2000,
+                end: 0x2010,
+            }))
+        );
+        assert_eq!(pc_range(0x2a), Ok(None));
+        assert_eq!(pc_range(0x33), Ok(None));
+    }
+
+    #[test]
+    fn test_attr_followed() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev_buf = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            // DW_AT_decl_line, DW_FORM_data1.
+            0x3b, 0x0b,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x2e, 0x00,
+            // DW_AT_specification, DW_FORM_ref4.
+            0x47, 0x13,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x04, 0x1d, 0x00,
+            // DW_AT_abstract_origin, DW_FORM_ref4.
+            0x31, 0x13,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info_buf = [
+            // Unit length, version, abbrev offset, address size.
+            0x21, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // 0x0b: Root entry.
+            0x01,
+            // 0x0c: Declaration.
+            0x02, b'f', 0x00, 0x07,
+            // 0x10: Definition.
+            0x03, 0x0c, 0x00, 0x00, 0x00,
+            // 0x15: Inlined instance.
+            0x04, 0x10, 0x00, 0x00, 0x00,
+            // 0x1a: Cyclic definitions.
+            0x03, 0x1f, 0x00, 0x00, 0x00,
+            0x03, 0x1a, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let mut cursor = unit.entries_at_offset(&abbrevs, UnitOffset(0x15)).unwrap();
+        cursor.next_entry().unwrap();
+        let entry = cursor.current().unwrap();
+        assert_eq!(entry.attr_value(DW_AT_name), Ok(None));
+        assert_eq!(
+            entry.attr_value_followed(&abbrevs, DW_AT_name),
+            Ok(Some(AttributeValue::String(EndianBuf::new(b"f", LittleEndian))))
+        );
+        assert_eq!(
+            entry.attr_value_followed(&abbrevs, DW_AT_decl_line),
+            Ok(Some(AttributeValue::Udata(7)))
+        );
+        assert_eq!(entry.attr_value_followed(&abbrevs, DW_AT_low_pc), Ok(None));
+        assert_eq!(entry.attr_value_followed(&abbrevs, DW_AT_specification), Ok(None));
+
+        let mut cursor = unit.entries_at_offset(&abbrevs, UnitOffset(0x1a)).unwrap();
+        cursor.next_entry().unwrap();
+        let entry = cursor.current().unwrap();
+        assert_eq!(
+            entry.attr_value_followed(&abbrevs, DW_AT_name),
+            Err(Error::CyclicReference)
+        );
+    }
+
+    #[test]
+    fn test_attribute_display() {
+        let endian = LittleEndian;
+        let tests: &[(constants::DwAt, AttributeValue<EndianBuf<LittleEndian>>, &str)] = &[
+            (DW_AT_language, AttributeValue::Data1([0x1c]), "DW_AT_language: DW_LANG_Rust"),
+            (DW_AT_encoding, AttributeValue::Udata(5), "DW_AT_encoding: DW_ATE_signed"),
+            (
+                DW_AT_accessibility,
+                AttributeValue::Data1([1]),
+                "DW_AT_accessibility: DW_ACCESS_public",
+            ),
+            (
+                DW_AT_language,
+                AttributeValue::Udata(0x7777),
+                "DW_AT_language: Unknown DwLang: 30583",
+            ),
+            (DW_AT_byte_size, AttributeValue::Data2(([4, 0], endian)), "DW_AT_byte_size: 4"),
+            (DW_AT_low_pc, AttributeValue::Addr(0x1234), "DW_AT_low_pc: 0x00001234"),
+            (
+                DW_AT_name,
+                AttributeValue::String(EndianBuf::new(b"foo", endian)),
+                "DW_AT_name: \"foo\"",
+            ),
+            (
+                DW_AT_name,
+                AttributeValue::DebugStrRef(DebugStrOffset(0x10)),
+                "DW_AT_name: <.debug_str+0x00000010>",
+            ),
+            (DW_AT_type, AttributeValue::UnitRef(UnitOffset(0x2a)), "DW_AT_type: <0x0000002a>"),
+            (
+                DW_AT_location,
+                AttributeValue::Exprloc(Expression(EndianBuf::new(&[0x50, 0x93, 0x08], endian))),
+                "DW_AT_location: len 0x0003: 50 93 08",
+            ),
+        ];
+        for &(name, value, expect) in tests {
+            let attribute = Attribute { name, value };
+            assert_eq!(format!("{}", attribute), expect);
+        }
+    }
+
+    #[test]
+    fn test_discr_list_value() {
+        let endian = LittleEndian;
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // DW_DSC_label, 1.
+            0x00, 0x01,
+            // DW_DSC_range, 2, 0x7f.
+            0x01, 0x02, 0x7f,
+        ];
+        let attribute = Attribute {
+            name: DW_AT_discr_list,
+            value: AttributeValue::Block(EndianBuf::new(&buf, endian)),
+        };
+
+        let unsigned = attribute.discr_list_value(false).unwrap();
+        assert_eq!(
+            unsigned.collect::<Vec<_>>(),
+            Ok(vec![
+                DiscriminantListEntry::Label(DiscriminantValue::Unsigned(1)),
+                DiscriminantListEntry::Range {
+                    low: DiscriminantValue::Unsigned(2),
+                    high: DiscriminantValue::Unsigned(0x7f),
+                },
+            ])
+        );
+
+        let signed = attribute.discr_list_value(true).unwrap();
+        assert_eq!(
+            signed.collect::<Vec<_>>(),
+            Ok(vec![
+                DiscriminantListEntry::Label(DiscriminantValue::Signed(1)),
+                DiscriminantListEntry::Range {
+                    low: DiscriminantValue::Signed(2),
+                    high: DiscriminantValue::Signed(-1),
+                },
+            ])
+        );
+
+        let buf = [0x02, 0x00];
+        let mut iter = DiscriminantListIter::new(EndianBuf::new(&buf, endian), false);
+        assert_eq!(
+            iter.next(),
+            Err(Error::UnknownDiscriminantDescriptor(constants::DwDsc(2)))
+        );
+        assert_eq!(iter.next(), Ok(None));
+
+        let attribute = Attribute::<EndianBuf<LittleEndian>> {
+            name: DW_AT_discr_list,
+            value: AttributeValue::Udata(0),
+        };
+        assert!(attribute.discr_list_value(false).is_none());
+    }
+
     #[test]
     fn test_attribute_udata_sdata_value() {
         let endian = LittleEndian;
@@ -3507,6 +6030,10 @@ mod tests {
                 assert!(false);
             }
         };
+
+        let rest = &mut EndianBuf::new(buf, Endian::default());
+        unit.skip_attributes(rest, &spec[..]).expect("Should skip attribute");
+        assert_eq!(*rest, EndianBuf::new(&buf[len..], Endian::default()));
     }
 
     #[test]
@@ -3637,9 +6164,67 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_attribute_exprloc() {
-        // LEB length of data (2, one byte), two bytes of data, one byte left over input.
-        let buf = [0x02, 0x99, 0x99, 0x11];
+    fn test_parse_attribute_data16() {
+        let buf = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+            0x99,
+        ];
+        let data = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+        ];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_data16;
+        let value = AttributeValue::Data16((data, LittleEndian));
+        test_parse_attribute(&buf, 16, &unit, form, value.clone());
+
+        let attr = Attribute {
+            name: constants::DW_AT_const_value,
+            value,
+        };
+        assert_eq!(attr.udata_value(), None);
+        assert_eq!(attr.udata128_value(), Some(0x100f0e0d0c0b0a090807060504030201));
+        assert_eq!(attr.sdata128_value(), Some(0x100f0e0d0c0b0a090807060504030201));
+    }
+
+    #[test]
+    fn test_parse_attribute_128() {
+        // 2^64 and -2^64, followed by one byte left over input.
+        let buf = [
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02, 0x99,
+        ];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_udata;
+        let value = AttributeValue::Udata128((1u128 << 64).to_le_bytes());
+        test_parse_attribute(&buf, 10, &unit, form, value);
+
+        let buf = [
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7e, 0x99,
+        ];
+        let form = constants::DW_FORM_sdata;
+        let value = AttributeValue::Sdata128((-1i128 << 64).to_le_bytes());
+        test_parse_attribute(&buf, 10, &unit, form, value);
+
+        let attr = Attribute {
+            name: constants::DW_AT_const_value,
+            value: AttributeValue::<EndianBuf<LittleEndian>>::Sdata128(
+                (-1i128 << 64).to_le_bytes(),
+            ),
+        };
+        assert_eq!(attr.sdata_value(), None);
+        assert_eq!(attr.sdata128_value(), Some(-1 << 64));
+        assert_eq!(attr.udata128_value(), None);
+        assert_eq!(format!("{}", attr.value()), format!("{}", -1i128 << 64));
+
+        // The 128 bit values must not increase the alignment.
+        assert_eq!(std::mem::align_of::<AttributeValue<EndianBuf<LittleEndian>>>(), 8);
+    }
+
+    #[test]
+    fn test_parse_attribute_exprloc() {
+        // LEB length of data (2, one byte), two bytes of data, one byte left over input.
+        let buf = [0x02, 0x99, 0x99, 0x11];
         let unit = test_parse_attribute_unit_default();
         let form = constants::DW_FORM_exprloc;
         let value = AttributeValue::Exprloc(Expression(EndianBuf::new(&buf[1..3], LittleEndian)));
@@ -3898,6 +6483,78 @@ mod tests {
         test_parse_attribute(&buf, 8, &unit, form, value);
     }
 
+    #[test]
+    fn test_parse_attribute_strx() {
+        let buf = [0x81, 0x01, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_strx;
+        let value = AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(0x81));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_loclistx() {
+        let buf = [0x81, 0x01, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_loclistx;
+        let value = AttributeValue::DebugLocListsIndex(DebugLocListsIndex(0x81));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_rnglistx() {
+        let buf = [0x81, 0x01, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_rnglistx;
+        let value = AttributeValue::DebugRngListsIndex(DebugRngListsIndex(0x81));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_gnu_str_index() {
+        let buf = [0x81, 0x01, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_GNU_str_index;
+        let value = AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(0x81));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_gnu_addr_index() {
+        let buf = [0x81, 0x01, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_GNU_addr_index;
+        let value = AttributeValue::DebugAddrIndex(AddressIndex(0x81));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_strx3() {
+        let buf = [0x01, 0x02, 0x03, 0x04, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_strx3;
+        let value = AttributeValue::DebugStrOffsetsIndex(DebugStrOffsetsIndex(0x030201));
+        test_parse_attribute(&buf, 3, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_addrx2() {
+        let buf = [0x01, 0x02, 0x03, 0x04, 0x99, 0x99];
+        let unit = test_parse_attribute_unit_default();
+        let form = constants::DW_FORM_addrx2;
+        let value = AttributeValue::DebugAddrIndex(AddressIndex(0x0201));
+        test_parse_attribute(&buf, 2, &unit, form, value);
+    }
+
+    #[test]
+    fn test_parse_attribute_addrx3_big_endian() {
+        let buf = [0x01, 0x02, 0x03, 0x04, 0x99, 0x99];
+        let unit = test_parse_attribute_unit(4, Format::Dwarf32, BigEndian);
+        let form = constants::DW_FORM_addrx3;
+        let value = AttributeValue::DebugAddrIndex(AddressIndex(0x010203));
+        test_parse_attribute(&buf, 3, &unit, form, value);
+    }
+
     #[test]
     fn test_parse_attribute_indirect() {
         let mut buf = [0; 100];
@@ -3915,6 +6572,34 @@ mod tests {
         test_parse_attribute(&buf, bytes_written, &unit, form, value);
     }
 
+    #[test]
+    fn test_parse_attribute_indirect_nested() {
+        let unit = test_parse_attribute_unit_default();
+        let spec = vec![
+            AttributeSpecification::new(constants::DW_AT_low_pc, constants::DW_FORM_indirect, None),
+        ];
+
+        let buf = [0x16, 0x16, 0x0b, 0x2a, 0x99];
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+        let (attr, _) = parse_attribute(rest, &unit, &spec[..]).unwrap();
+        assert_eq!(attr.raw_value(), AttributeValue::Data1([0x2a]));
+        assert_eq!(*rest, EndianBuf::new(&buf[4..], LittleEndian));
+
+        let buf = [0x16; 100];
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+        assert_eq!(
+            parse_attribute(rest, &unit, &spec[..]).map(|_| ()),
+            Err(Error::InvalidIndirectForm)
+        );
+
+        let buf = [0x21, 0x2a];
+        let rest = &mut EndianBuf::new(&buf, LittleEndian);
+        assert_eq!(
+            parse_attribute(rest, &unit, &spec[..]).map(|_| ()),
+            Err(Error::InvalidIndirectForm)
+        );
+    }
+
     #[test]
     fn test_attrs_iter() {
         let unit = UnitHeader::new(
@@ -4033,6 +6718,142 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_attrs_iter_with_metadata() {
+        let abbrev = Abbreviation::new(
+            42,
+            constants::DW_TAG_subprogram,
+            constants::DW_CHILDREN_no,
+            vec![
+                AttributeSpecification::new(constants::DW_AT_name, constants::DW_FORM_string, None),
+                AttributeSpecification::new(constants::DW_AT_low_pc, constants::DW_FORM_indirect, None),
+                AttributeSpecification::new(constants::DW_AT_type, constants::DW_FORM_ref4, None),
+            ],
+        );
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = [
+            // "foo"
+            0x66, 0x6f, 0x6f, 0x00,
+            // DW_FORM_addr, 0x2a
+            0x01, 0x2a, 0x00, 0x00, 0x00,
+            // 0x539
+            0x39, 0x05, 0x00, 0x00,
+        ];
+
+        let unit = UnitHeader::new(
+            20,
+            4,
+            DebugAbbrevOffset(0),
+            4,
+            Format::Dwarf32,
+            EndianBuf::new(&buf, LittleEndian),
+        );
+
+        let entry = DebuggingInformationEntry {
+            offset: UnitOffset(0),
+            attrs_slice: EndianBuf::new(&buf, LittleEndian),
+            attrs_len: Cell::new(None),
+            abbrev: &abbrev,
+            unit: &unit,
+        };
+
+        let mut attrs = entry.attrs();
+        assert_eq!(attrs.offset(), UnitOffset(11));
+        let (attr, metadata) = attrs.next_with_metadata().unwrap().unwrap();
+        // The next attribute begins with its `DW_FORM_indirect` form.
+        assert_eq!(attrs.offset(), UnitOffset(15));
+        assert_eq!(attr.name(), constants::DW_AT_name);
+        assert_eq!(
+            metadata,
+            AttributeMetadata {
+                form: constants::DW_FORM_string,
+                class: AttributeClass::String,
+                offset: UnitOffset(11),
+                length: 4,
+            }
+        );
+
+        let (attr, metadata) = attrs.next_with_metadata().unwrap().unwrap();
+        assert_eq!(attr.raw_value(), AttributeValue::Addr(0x2a));
+        assert_eq!(
+            metadata,
+            AttributeMetadata {
+                form: constants::DW_FORM_addr,
+                class: AttributeClass::Address,
+                offset: UnitOffset(16),
+                length: 4,
+            }
+        );
+
+        let (attr, metadata) = attrs.next_with_metadata().unwrap().unwrap();
+        assert_eq!(attr.raw_value(), AttributeValue::UnitRef(UnitOffset(0x539)));
+        assert_eq!(
+            metadata,
+            AttributeMetadata {
+                form: constants::DW_FORM_ref4,
+                class: AttributeClass::Reference,
+                offset: UnitOffset(20),
+                length: 4,
+            }
+        );
+
+        assert!(attrs.next_with_metadata().unwrap().is_none());
+        assert_eq!(attrs.offset(), UnitOffset(11 + buf.len()));
+        assert_eq!(entry.attrs_len.get(), Some(buf.len()));
+    }
+
+    #[test]
+    fn test_attrs_iter_skip_attribute() {
+        let unit = test_parse_attribute_unit_default();
+
+        let abbrev = Abbreviation::new(
+            42,
+            constants::DW_TAG_subprogram,
+            constants::DW_CHILDREN_yes,
+            vec![
+                AttributeSpecification::new(constants::DW_AT_name, constants::DW_FORM_string, None),
+                AttributeSpecification::new(constants::DW_AT_byte_size, constants::DW_FORM_udata, None),
+                AttributeSpecification::new(constants::DW_AT_location, constants::DW_FORM_exprloc, None),
+                AttributeSpecification::new(constants::DW_AT_low_pc, constants::DW_FORM_addr, None),
+                AttributeSpecification::new(constants::DW_AT_high_pc, constants::DwForm(0x7f), None),
+            ],
+        );
+
+        // "foo", 300, an expression of 2 bytes, 0x1337.
+        let buf = [
+            0x66, 0x6f, 0x6f, 0x00, 0xac, 0x02, 0x02, 0x01, 0x02, 0x37, 0x13, 0x00, 0x00, 0xaa,
+        ];
+
+        let entry = DebuggingInformationEntry {
+            offset: UnitOffset(0),
+            attrs_slice: EndianBuf::new(&buf, LittleEndian),
+            attrs_len: Cell::new(None),
+            abbrev: &abbrev,
+            unit: &unit,
+        };
+
+        let mut attrs = entry.attrs();
+        assert_eq!(attrs.skip_attribute(), Ok(Some(constants::DW_AT_name)));
+        assert_eq!(attrs.skip_attribute(), Ok(Some(constants::DW_AT_byte_size)));
+        assert_eq!(attrs.skip_attribute(), Ok(Some(constants::DW_AT_location)));
+        assert_eq!(
+            attrs.next(),
+            Ok(Some(Attribute {
+                name: constants::DW_AT_low_pc,
+                value: AttributeValue::Addr(0x1337),
+            }))
+        );
+        assert_eq!(attrs.skip_attribute(), Err(Error::UnknownForm));
+        assert!(attrs.skip_attribute().is_err());
+
+        // Looking up an attribute skips the attributes before it.
+        assert_eq!(
+            entry.attr_value(constants::DW_AT_low_pc),
+            Ok(Some(AttributeValue::Addr(0x1337)))
+        );
+    }
+
     #[test]
     fn test_attrs_iter_incomplete() {
         let unit = UnitHeader::new(
@@ -4240,6 +7061,9 @@ mod tests {
                 address_size: 4,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -4264,6 +7088,9 @@ mod tests {
                 address_size: 4,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -4375,6 +7202,64 @@ mod tests {
         assert!(cursor.current().is_none());
     }
 
+    #[test]
+    fn test_cursor_fallible_iterator() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let depths = unit.entries(&abbrevs)
+            .map(|(delta_depth, _)| delta_depth)
+            .collect::<Vec<_>>()
+            .unwrap();
+        assert_eq!(depths, [0, 1, 1, -1, 1, 0, -1, 1, 1, -2]);
+    }
+
+    #[test]
+    fn test_cursor_checkpoint() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let mut cursor = unit.entries(&abbrevs);
+        let start = cursor.checkpoint();
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        assert_next_dfs(&mut cursor, "003", 1);
+        let checkpoint = cursor.checkpoint();
+        assert_eq!(checkpoint.offset(), cursor.current().unwrap().offset());
+        assert_next_dfs(&mut cursor, "004", -1);
+        assert_next_dfs(&mut cursor, "005", 1);
+
+        // Restore into the same cursor.
+        cursor.restore(&checkpoint).unwrap();
+        assert_entry_name(cursor.current().unwrap(), "003");
+        assert_eq!(cursor.depth(), 2);
+        assert_next_dfs(&mut cursor, "004", -1);
+
+        // Restore into a different cursor.
+        let mut other = unit.entries(&abbrevs);
+        other.restore(&checkpoint).unwrap();
+        assert_next_dfs(&mut other, "004", -1);
+
+        // Restore positions without a current entry.
+        cursor.restore(&start).unwrap();
+        assert!(cursor.current().is_none());
+        assert_next_dfs(&mut cursor, "001", 0);
+
+        while cursor.next_dfs().unwrap().is_some() {}
+        let end = cursor.checkpoint();
+        cursor.restore(&checkpoint).unwrap();
+        cursor.restore(&end).unwrap();
+        assert!(cursor.next_entry().unwrap().is_none());
+    }
+
     #[test]
     #[cfg_attr(rustfmt, rustfmt_skip)]
     fn test_cursor_next_sibling_no_sibling_ptr() {
@@ -4411,6 +7296,92 @@ mod tests {
         assert!(cursor.current().is_none());
     }
 
+    #[test]
+    fn test_cursor_children() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+
+        let abbrevs = unit
+            .abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        let mut cursor = unit.entries(&abbrevs);
+        assert_next_dfs(&mut cursor, "001", 0);
+
+        let mut children = cursor.children();
+        {
+            let entry = children.next().unwrap().expect("Should have a child");
+            assert_entry_name(entry, "002");
+        }
+        {
+            let entry = children.next().unwrap().expect("Should have a child");
+            assert_entry_name(entry, "004");
+        }
+        {
+            // Iterating the grandchildren leaves the cursor positioned so
+            // that the next child can still be found.
+            let mut grandchildren = children.cursor().children();
+            {
+                let entry = grandchildren.next().unwrap().expect("Should have a child");
+                assert_entry_name(entry, "005");
+            }
+            {
+                let entry = grandchildren.next().unwrap().expect("Should have a child");
+                assert_entry_name(entry, "006");
+            }
+            assert!(grandchildren.next().unwrap().is_none());
+            assert!(grandchildren.next().unwrap().is_none());
+        }
+        {
+            let entry = children.next().unwrap().expect("Should have a child");
+            assert_entry_name(entry, "007");
+        }
+        {
+            let entry = children.next().unwrap().expect("Should have a child");
+            assert_entry_name(entry, "010");
+        }
+        assert!(children.next().unwrap().is_none());
+        assert!(children.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cursor_children_empty() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+
+        let abbrevs = unit
+            .abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        let mut cursor = unit.entries(&abbrevs);
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        assert_next_dfs(&mut cursor, "003", 1);
+        // "003" has the children flag set, but its list of children is empty.
+        assert!(cursor.children().next().unwrap().is_none());
+        assert!(cursor.current().is_none());
+        assert_next_entry_null(&mut cursor);
+        assert_next_entry(&mut cursor, "004");
+    }
+
     #[test]
     fn test_cursor_next_sibling_continuation() {
         let info_buf = &entries_cursor_tests_debug_info_buf();
@@ -4482,6 +7453,172 @@ mod tests {
         assert!(cursor.current().is_none());
     }
 
+    #[test]
+    fn test_cursor_skip_children() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+
+        let abbrevs = unit.abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        let mut cursor = unit.entries(&abbrevs);
+
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+
+        cursor.skip_children().expect("Should skip children");
+        assert!(cursor.current().is_none());
+        assert_next_dfs(&mut cursor, "004", 0);
+        assert_next_dfs(&mut cursor, "005", 1);
+
+        // Skipping an empty list of children.
+        cursor.skip_children().expect("Should skip children");
+        assert_next_dfs(&mut cursor, "006", 0);
+
+        // Skipping the children of the last entry in a list.
+        cursor.skip_children().expect("Should skip children");
+        assert_next_dfs(&mut cursor, "007", -1);
+
+        // Skipping nested children.
+        cursor.skip_children().expect("Should skip children");
+        assert_next_dfs(&mut cursor, "010", 0);
+
+        // Skipping when there is no current entry does nothing.
+        cursor.skip_children().expect("Should skip children");
+        cursor.skip_children().expect("Should skip children");
+        assert!(cursor.next_dfs().expect("Should parse next dfs").is_none());
+    }
+
+    #[test]
+    fn test_cursor_limits() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+
+        let abbrevs = unit.abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        let mut limits = ReaderLimits::default();
+        limits.max_die_depth = Some(3);
+        let mut cursor = unit.entries(&abbrevs);
+        cursor.set_limits(&limits);
+        let mut count = 0;
+        while cursor.next_dfs().expect("Should parse next dfs").is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 10);
+
+        limits.max_die_depth = Some(1);
+        let mut cursor = unit.entries(&abbrevs);
+        cursor.set_limits(&limits);
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        assert_eq!(cursor.next_dfs().map(|_| ()), Err(Error::LimitExceeded));
+        assert!(cursor.next_dfs().expect("Should be empty").is_none());
+
+        // Skipping children avoids exceeding the limit.
+        let mut cursor = unit.entries(&abbrevs);
+        cursor.set_limits(&limits);
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        cursor.skip_children().expect("Should skip children");
+        assert_next_dfs(&mut cursor, "004", 0);
+    }
+
+    #[test]
+    fn test_cursor_next_sibling_ref_addr() {
+        let abbrev_buf = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_subprogram, DW_CHILDREN_yes)
+            .abbrev_attr(DW_AT_name, DW_FORM_string)
+            .abbrev_attr(DW_AT_sibling, DW_FORM_ref_addr)
+            .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_yes)
+            .abbrev_attr(DW_AT_name, DW_FORM_string)
+            .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+
+        let padding = &[0; 10];
+        let format = Format::Dwarf32;
+        let header_size =
+            CompilationUnitHeader::<EndianBuf<LittleEndian>, _>::size_of_header(format);
+
+        let start = Label::new();
+        let sibling_ref = Label::new();
+        let sibling = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            .mark(&start)
+            .die(2, |s| s.attr_string("001"))
+                .die(1, |s| s.attr_string("002").L32(&sibling_ref))
+                    // Invalid code to ensure the sibling attribute was used.
+                    .die(10, |s| s.attr_string("003"))
+                        .die_null()
+                    .die_null()
+                .mark(&sibling)
+                .die(2, |s| s.attr_string("004"))
+                    .die_null()
+                .die_null();
+        let offset = (padding.len() + header_size) as u64 + (&sibling - &start) as u64;
+        sibling_ref.set_const(offset);
+        let entries_buf = section.get_contents().unwrap();
+
+        let mut unit = CompilationUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0),
+                address_size: 4,
+                format: format,
+                entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugInfoOffset(0),
+        };
+        let section = Section::with_endian(Endian::Little)
+            .append_bytes(padding)
+            .comp_unit(&mut unit);
+        let info_buf = section.get_contents().unwrap();
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+        let unit = debug_info
+            .header_from_offset(DebugInfoOffset(padding.len()))
+            .expect("Should parse unit header");
+        let abbrevs = unit.abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        let mut cursor = unit.entries(&abbrevs);
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        assert_next_sibling(&mut cursor, "004");
+
+        let mut cursor = unit.entries(&abbrevs);
+        assert_next_dfs(&mut cursor, "001", 0);
+        assert_next_dfs(&mut cursor, "002", 1);
+        cursor.skip_children().expect("Should skip children");
+        assert_next_dfs(&mut cursor, "004", 0);
+    }
+
     fn entries_cursor_sibling_abbrev_buf() -> Vec<u8> {
         let section = Section::with_endian(Endian::Little)
             .abbrev(1, DW_TAG_subprogram, DW_CHILDREN_yes)
@@ -4585,6 +7722,9 @@ mod tests {
                 address_size: 4,
                 format: format,
                 entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -4622,6 +7762,9 @@ mod tests {
                 address_size: 4,
                 format: format,
                 entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             type_signature: DebugTypeSignature(0),
             type_offset: UnitOffset(0),
@@ -4678,6 +7821,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_entries_at_offset_checked() {
+        let info_buf = &entries_cursor_tests_debug_info_buf();
+        let debug_info = DebugInfo::new(info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrevs_buf = &entries_cursor_tests_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(abbrevs_buf, LittleEndian);
+
+        let abbrevs = unit
+            .abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+
+        // Each entry is an abbreviation code followed by a 3 character name.
+        let header_size = unit.header_size();
+        let mut cursor = unit.entries_at_offset_checked(&abbrevs, UnitOffset(header_size + 5))
+            .unwrap();
+        assert_next_entry(&mut cursor, "002");
+
+        // In the middle of the name attribute of "002".
+        let cursor = unit.entries_at_offset_checked(&abbrevs, UnitOffset(header_size + 6));
+        assert_eq!(cursor.err(), Some(Error::NoEntryAtGivenOffset));
+
+        // The null entry that terminates the children of "003".
+        let cursor = unit.entries_at_offset_checked(&abbrevs, UnitOffset(header_size + 15));
+        assert_eq!(cursor.err(), Some(Error::NoEntryAtGivenOffset));
+
+        let cursor = unit.entries_at_offset_checked(&abbrevs, UnitOffset(0));
+        assert_eq!(cursor.err(), Some(Error::OffsetOutOfBounds));
+    }
+
     #[cfg_attr(rustfmt, rustfmt_skip)]
     fn entries_tree_tests_debug_abbrevs_buf() -> Vec<u8> {
         Section::with_endian(Endian::Little)
@@ -4769,6 +7948,9 @@ mod tests {
                 address_size: 4,
                 format: format,
                 entries_buf: EndianBuf::new(&entries_buf, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -4788,6 +7970,18 @@ mod tests {
         let mut tree = unit.entries_tree(&abbrevs, None)
             .expect("Should have entries tree");
 
+        // Test the depth limit.
+        {
+            let mut limits = ReaderLimits::default();
+            limits.max_die_depth = Some(1);
+            let mut tree = unit.entries_tree(&abbrevs, None)
+                .expect("Should have entries tree");
+            tree.set_limits(&limits);
+            let mut iter = assert_entry(tree.root().map(Some), "root");
+            let mut iter = assert_entry(iter.next(), "1");
+            assert!(iter.next().is_err());
+        }
+
         // Test we can restart iteration of the tree.
         {
             let mut iter = assert_entry(tree.root().map(Some), "root");
@@ -4845,6 +8039,28 @@ mod tests {
         assert_entry(iter.next(), "2a");
         assert_entry(iter.next(), "2b");
         assert_null(iter.next());
+
+        // Test prefetching attributes.
+        let name = |name: &'static str| {
+            Ok(Some(AttributeValue::String(EndianBuf::new(name.as_bytes(), LittleEndian))))
+        };
+        let mut tree = unit.entries_tree(&abbrevs, Some(entry2))
+            .expect("Should have entries tree");
+        tree.set_prefetch(&[DW_AT_decl_line, DW_AT_name]);
+        let root = tree.root().expect("Should parse root");
+        assert_eq!(root.attr_value(DW_AT_name), name("2"));
+        assert_eq!(root.attr_value(DW_AT_decl_line), Ok(None));
+        assert_eq!(root.attr_value(DW_AT_byte_size), Ok(None));
+        let mut iter = root.children();
+        {
+            let node = iter.next().expect("Should parse entry").expect("Should have entry");
+            assert_eq!(node.attr_value(DW_AT_name), name("2a"));
+            let mut iter = node.children();
+            let node = iter.next().expect("Should parse entry").expect("Should have entry");
+            assert_eq!(node.attr_value(DW_AT_name), name("2a1"));
+        }
+        let node = iter.next().expect("Should parse entry").expect("Should have entry");
+        assert_eq!(node.attr_value(DW_AT_name), name("2b"));
     }
 
     #[test]
@@ -4859,6 +8075,9 @@ mod tests {
                 address_size: 4,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(entries, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             offset: DebugInfoOffset(0),
         };
@@ -4893,6 +8112,121 @@ mod tests {
             UnitOffset(length - 1).to_debug_info_offset(&unit),
             DebugInfoOffset(offset + length - 1)
         );
+        assert_eq!(
+            UnitOffset(usize::max_value())
+                .to_debug_info_offset(&unit)
+                .to_unit_offset(&unit),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unit_headers_find() {
+        let entries = &[0; 20];
+        let mut unit1 = CompilationUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0),
+                address_size: 4,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(entries, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugInfoOffset(0),
+        };
+        let mut unit2 = unit1;
+        let mut unit3 = unit1;
+        let section = Section::with_endian(Endian::Little)
+            .comp_unit(&mut unit1)
+            .comp_unit(&mut unit2)
+            .comp_unit(&mut unit3);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let headers = debug_info.unit_headers().unwrap();
+        assert_eq!(headers.headers(), &[unit1, unit2, unit3][..]);
+
+        let header_length =
+            CompilationUnitHeader::<EndianBuf<LittleEndian>, _>::size_of_header(unit1.format());
+        let length = unit1.length_including_self();
+        for unit in &[unit1, unit2, unit3] {
+            let offset = unit.offset().0;
+            assert_eq!(headers.find(DebugInfoOffset(offset)), None);
+            assert_eq!(
+                headers.find(DebugInfoOffset(offset + header_length)),
+                Some((unit, UnitOffset(header_length)))
+            );
+            assert_eq!(
+                headers.find(DebugInfoOffset(offset + length - 1)),
+                Some((unit, UnitOffset(length - 1)))
+            );
+            assert_eq!(
+                UnitOffset(length - 1).to_debug_info_offset(unit),
+                DebugInfoOffset(offset + length - 1)
+            );
+        }
+        assert_eq!(headers.find(DebugInfoOffset(3 * length)), None);
+    }
+
+    #[test]
+    fn test_unit_offsets() {
+        let entries = &[0; 20];
+        let mut unit1 = CompilationUnitHeader {
+            header: UnitHeader {
+                unit_length: 0,
+                version: 4,
+                debug_abbrev_offset: DebugAbbrevOffset(0),
+                address_size: 4,
+                format: Format::Dwarf32,
+                entries_buf: EndianBuf::new(entries, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
+            },
+            offset: DebugInfoOffset(0),
+        };
+        let mut unit2 = unit1;
+        let section = Section::with_endian(Endian::Little)
+            .comp_unit(&mut unit1)
+            .comp_unit(&mut unit2);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let offsets = debug_info.unit_offsets().unwrap();
+        assert_eq!(offsets, vec![unit1.offset(), unit2.offset()]);
+        assert_eq!(debug_info.header_from_offset(offsets[1]), Ok(unit2));
+
+        let mut unit1 = TypeUnitHeader {
+            header: unit1.header,
+            type_signature: DebugTypeSignature(0),
+            type_offset: UnitOffset(0),
+            offset: DebugTypesOffset(0),
+        };
+        unit1.header.debug_info_offset = None;
+        let mut unit2 = unit1;
+        let section = Section::with_endian(Endian::Little)
+            .type_unit(&mut unit1)
+            .type_unit(&mut unit2);
+        let buf = section.get_contents().unwrap();
+
+        let debug_types = DebugTypes::new(&buf, LittleEndian);
+        let offsets = debug_types.unit_offsets().unwrap();
+        assert_eq!(offsets, vec![unit1.offset(), unit2.offset()]);
+        assert_eq!(debug_types.header_from_offset(offsets[1]), Ok(unit2));
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DebugInfo<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<DebugTypes<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<CompilationUnitHeader<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<TypeUnitHeader<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<CompilationUnitHeaders<EndianBuf<LittleEndian>>>();
+        assert_send_sync::<Abbreviations>();
     }
 
     #[test]
@@ -4907,6 +8241,9 @@ mod tests {
                 address_size: 4,
                 format: Format::Dwarf32,
                 entries_buf: EndianBuf::new(entries, LittleEndian),
+                debug_info_offset: None,
+                unit_type: constants::DW_UT_compile,
+                dwo_id: None,
             },
             type_signature: DebugTypeSignature(0),
             type_offset: UnitOffset(0),
@@ -4946,5 +8283,11 @@ mod tests {
             UnitOffset(length - 1).to_debug_types_offset(&unit),
             DebugTypesOffset(offset + length - 1)
         );
+        assert_eq!(
+            UnitOffset(usize::max_value())
+                .to_debug_types_offset(&unit)
+                .to_unit_offset(&unit),
+            None
+        );
     }
 }
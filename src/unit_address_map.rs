@@ -0,0 +1,307 @@
+//! A map for finding the unit that contains an address.
+
+use aranges::DebugAranges;
+use constants;
+use dwarf::Dwarf;
+use parser::Result;
+use reader::{Reader, ReaderOffset};
+use rnglists::Range;
+use std::cmp::Ordering;
+use unit::DebugInfoOffset;
+use vec::Vec;
+
+/// A sorted map from address ranges to the units in the `.debug_info`
+/// section that contain them.
+///
+/// This is built by `Dwarf::unit_address_map`. When the `serde` feature is
+/// enabled, the map can be serialized and deserialized, so that it only
+/// needs to be built once for each object file. Only the ranges are
+/// serialized, and the map is rebuilt from them when it is deserialized.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde",
+           serde(from = "Vec<(Range, DebugInfoOffset<T>)>",
+                 into = "Vec<(Range, DebugInfoOffset<T>)>",
+                 bound(serialize = "T: ReaderOffset + ::serde::Serialize",
+                       deserialize = "T: ReaderOffset + ::serde::Deserialize<'de>")))]
+pub struct UnitAddressMap<T = usize> {
+    // Sorted by the beginning address of the range.
+    ranges: Vec<(Range, DebugInfoOffset<T>)>,
+    // The largest end address of the ranges up to and including each index.
+    max_end: Vec<u64>,
+}
+
+impl<T: ReaderOffset> UnitAddressMap<T> {
+    /// Construct a new map from a list of address ranges and the offsets
+    /// of the units that contain them. Empty ranges are ignored.
+    pub fn new(mut ranges: Vec<(Range, DebugInfoOffset<T>)>) -> Self {
+        ranges.retain(|&(range, _)| range.begin < range.end);
+        ranges.sort_by(|a, b| {
+            (a.0.begin, a.0.end, (a.1).0).cmp(&(b.0.begin, b.0.end, (b.1).0))
+        });
+        ranges.dedup();
+        let mut max_end = Vec::with_capacity(ranges.len());
+        let mut end = 0;
+        for &(range, _) in &ranges {
+            if range.end > end {
+                end = range.end;
+            }
+            max_end.push(end);
+        }
+        UnitAddressMap { ranges, max_end }
+    }
+
+    /// Return the offset of the unit that contains the given address.
+    ///
+    /// If more than one unit contains the address, then the unit with the
+    /// range that begins closest to the address is returned.
+    pub fn unit_for_address(&self, address: u64) -> Option<DebugInfoOffset<T>> {
+        // The number of ranges that begin at or before the address.
+        let count = match self.ranges.binary_search_by(|&(range, _)| {
+            if range.begin <= address {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }) {
+            Ok(index) | Err(index) => index,
+        };
+        for index in (0..count).rev() {
+            if self.max_end[index] <= address {
+                break;
+            }
+            let (range, offset) = self.ranges[index];
+            if address < range.end {
+                return Some(offset);
+            }
+        }
+        None
+    }
+
+    /// Return the address ranges in the map, and the offsets of the units
+    /// that contain them, sorted by address.
+    pub fn ranges(&self) -> &[(Range, DebugInfoOffset<T>)] {
+        &self.ranges
+    }
+
+    /// Return the number of address ranges in the map.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Return true if the map contains no address ranges.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl<T: ReaderOffset> From<Vec<(Range, DebugInfoOffset<T>)>> for UnitAddressMap<T> {
+    fn from(ranges: Vec<(Range, DebugInfoOffset<T>)>) -> Self {
+        UnitAddressMap::new(ranges)
+    }
+}
+
+impl<T> From<UnitAddressMap<T>> for Vec<(Range, DebugInfoOffset<T>)> {
+    fn from(map: UnitAddressMap<T>) -> Self {
+        map.ranges
+    }
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Build a map for finding the unit that contains an address.
+    ///
+    /// The address ranges of each unit are read from `debug_aranges`, if it
+    /// is given and contains entries for the unit. Otherwise they are read
+    /// from the `DW_AT_low_pc`, `DW_AT_high_pc` and `DW_AT_ranges` attributes
+    /// of the unit's root entry, or if it has none of these, from the
+    /// `DW_TAG_subprogram` entries in the unit.
+    pub fn unit_address_map(
+        &self,
+        debug_aranges: Option<&DebugAranges<R>>,
+    ) -> Result<UnitAddressMap<R::Offset>> {
+        let mut ranges = Vec::new();
+        let mut aranges_units = Vec::new();
+        if let Some(debug_aranges) = debug_aranges {
+            let mut aranges = debug_aranges.items();
+            while let Some(arange) = aranges.next()? {
+                let begin = arange.address();
+                let end = begin.saturating_add(arange.length());
+                ranges.push((Range { begin, end }, arange.debug_info_offset()));
+                aranges_units.push(arange.debug_info_offset().0);
+            }
+        }
+        aranges_units.sort();
+        aranges_units.dedup();
+
+        let mut units = self.units();
+        while let Some(header) = units.next()? {
+            let offset = header.offset();
+            if aranges_units.binary_search(&offset.0).is_ok() {
+                continue;
+            }
+
+            let unit = self.unit(header)?;
+            let mut unit_ranges = Vec::new();
+            let mut entries = unit.entries();
+            if let Some((_, root)) = entries.next_dfs()? {
+                unit_ranges = self.die_ranges(&unit, root)?;
+            }
+            if unit_ranges.is_empty() {
+                while let Some((_, entry)) = entries.next_dfs()? {
+                    if entry.tag() == constants::DW_TAG_subprogram {
+                        unit_ranges.extend(self.die_ranges(&unit, entry)?);
+                    }
+                }
+            }
+            ranges.extend(unit_ranges.into_iter().map(|range| (range, offset)));
+        }
+        Ok(UnitAddressMap::new(ranges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use SectionId;
+
+    #[test]
+    fn test_unit_address_map_new() {
+        let map = UnitAddressMap::<usize>::new(vec![
+            (Range { begin: 10, end: 20 }, DebugInfoOffset(1)),
+            (Range { begin: 0, end: 100 }, DebugInfoOffset(0)),
+            (Range { begin: 30, end: 30 }, DebugInfoOffset(2)),
+            (Range { begin: 10, end: 20 }, DebugInfoOffset(1)),
+        ]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.ranges()[0], (Range { begin: 0, end: 100 }, DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(0), Some(DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(10), Some(DebugInfoOffset(1)));
+        assert_eq!(map.unit_for_address(19), Some(DebugInfoOffset(1)));
+        assert_eq!(map.unit_for_address(20), Some(DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(30), Some(DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(100), None);
+
+        let map = UnitAddressMap::<usize>::new(Vec::new());
+        assert!(map.is_empty());
+        assert_eq!(map.unit_for_address(0), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_unit_address_map_serde() {
+        extern crate serde_json;
+
+        let map = UnitAddressMap::<usize>::new(vec![
+            (Range { begin: 10, end: 20 }, DebugInfoOffset(1)),
+            (Range { begin: 0, end: 100 }, DebugInfoOffset(0)),
+        ]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            json,
+            r#"[[{"begin":0,"end":100},0],[{"begin":10,"end":20},1]]"#
+        );
+        assert_eq!(serde_json::from_str::<UnitAddressMap>(&json).unwrap(), map);
+
+        // Deserialized ranges are sorted, as if given to `new`.
+        let json = r#"[[{"begin":10,"end":20},1],[{"begin":0,"end":100},0]]"#;
+        let map: UnitAddressMap = serde_json::from_str(json).unwrap();
+        assert_eq!(map.unit_for_address(15), Some(DebugInfoOffset(1)));
+        assert_eq!(map.unit_for_address(50), Some(DebugInfoOffset(0)));
+    }
+
+    #[test]
+    fn test_unit_address_map() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x11, 0x01,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x2e, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x15, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // Root entry, [0x1000, 0x1100).
+            0x01,
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+            0x00,
+
+            // Unit length, version, abbrev offset, address size.
+            0x16, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            // Root entry without ranges.
+            0x02,
+            // Subprogram, [0x2000, 0x2010).
+            0x03,
+            0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_aranges = [
+            // Unit length, version, debug info offset, address size,
+            // segment size, padding.
+            0x2c, 0x00, 0x00, 0x00,
+            0x02, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            0x00,
+            0x00, 0x00, 0x00, 0x00,
+            // Address and length.
+            0x00, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Terminator.
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        let map = dwarf.unit_address_map(None).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.unit_for_address(0), None);
+        assert_eq!(map.unit_for_address(0x1000), Some(DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(0x10ff), Some(DebugInfoOffset(0)));
+        assert_eq!(map.unit_for_address(0x1100), None);
+        assert_eq!(map.unit_for_address(0x2008), Some(DebugInfoOffset(0x19)));
+        assert_eq!(map.unit_for_address(0x3000), None);
+
+        let debug_aranges = DebugAranges::new(&debug_aranges, LittleEndian);
+        let map = dwarf.unit_address_map(Some(&debug_aranges)).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.unit_for_address(0x1000), None);
+        assert_eq!(map.unit_for_address(0x2008), Some(DebugInfoOffset(0x19)));
+        assert_eq!(map.unit_for_address(0x3000), Some(DebugInfoOffset(0)));
+    }
+}
@@ -0,0 +1,421 @@
+//! Recovering the canonical frame address and the values of registers in the
+//! previous frame from a row of an unwind table.
+
+use std::fmt;
+
+use arch::Register;
+use cfi::{CfaRule, RegisterRule, StoreOnStack, UnwindContextStorage, UnwindTableRow};
+use op::{Evaluation, EvaluationResult, Expression, Location};
+use parser::{Error, Format, Result};
+use reader::Reader;
+use vec::Vec;
+
+/// Evaluates the rules in a row of an unwind table, using callbacks to read
+/// the registers and memory of the current frame.
+///
+/// `read_register` returns the value of a register in the current frame, and
+/// `read_memory` returns the value of the given size in bytes at an address.
+/// Either callback may return `None` if the value is not available, in which
+/// case evaluation fails with `Error::UnavailableRegister` or
+/// `Error::UnavailableMemory`.
+///
+/// DWARF expressions in `CfaRule::Expression`, `RegisterRule::Expression` and
+/// `RegisterRule::ValExpression` rules are evaluated using the same
+/// callbacks.
+///
+/// ```
+/// use gimli::{CfaRule, EndianBuf, LittleEndian, RegisterRule, UnwindRowEvaluator,
+///             UnwindTableRow, X86_64};
+///
+/// let cfa = CfaRule::RegisterAndOffset {
+///     register: X86_64::RSP,
+///     offset: 16,
+/// };
+/// let mut row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0x1000, 0x1010, cfa);
+/// row.set_register(X86_64::RA, RegisterRule::Offset(-8)).unwrap();
+///
+/// let mut evaluator = UnwindRowEvaluator::new(
+///     8,
+///     |register| if register == X86_64::RSP { Some(0x7000) } else { None },
+///     |address, _size| if address == 0x7008 { Some(0x1234) } else { None },
+/// );
+/// let cfa = evaluator.cfa(&row).unwrap();
+/// assert_eq!(cfa, 0x7010);
+/// assert_eq!(evaluator.register(&row, cfa, X86_64::RA), Ok(Some(0x1234)));
+/// ```
+pub struct UnwindRowEvaluator<F, M> {
+    address_size: u8,
+    read_register: F,
+    read_memory: M,
+}
+
+impl<F, M> fmt::Debug for UnwindRowEvaluator<F, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnwindRowEvaluator")
+            .field("address_size", &self.address_size)
+            .finish()
+    }
+}
+
+impl<F, M> UnwindRowEvaluator<F, M>
+where
+    F: FnMut(Register) -> Option<u64>,
+    M: FnMut(u64, u8) -> Option<u64>,
+{
+    /// Construct a new evaluator for a target with the given address size.
+    pub fn new(address_size: u8, read_register: F, read_memory: M) -> Self {
+        UnwindRowEvaluator {
+            address_size,
+            read_register,
+            read_memory,
+        }
+    }
+
+    /// Compute the canonical frame address (CFA) using the CFA rule of the
+    /// row.
+    pub fn cfa<R, S>(&mut self, row: &UnwindTableRow<R, S>) -> Result<u64>
+    where
+        R: Reader,
+        S: UnwindContextStorage<R>,
+    {
+        self.cfa_rule(row.cfa())
+    }
+
+    /// Compute the canonical frame address (CFA) using the given CFA rule.
+    ///
+    /// This is useful for consumers that track rules themselves instead of
+    /// using an `UnwindTableRow`.
+    pub fn cfa_rule<R: Reader>(&mut self, rule: &CfaRule<R>) -> Result<u64> {
+        match *rule {
+            CfaRule::RegisterAndOffset { register, offset } => {
+                let value = self.read_register(register)?;
+                Ok(self.mask(value.wrapping_add(offset as u64)))
+            }
+            CfaRule::Expression(ref expression) => self.evaluate(expression, None),
+        }
+    }
+
+    /// Recover the value of the given register in the previous frame, given
+    /// the CFA computed by `cfa`.
+    ///
+    /// Returns `None` if the register has the rule `RegisterRule::Undefined`.
+    /// Returns `Error::UnsupportedRegisterRule` for
+    /// `RegisterRule::Architectural`.
+    pub fn register<R, S>(
+        &mut self,
+        row: &UnwindTableRow<R, S>,
+        cfa: u64,
+        register: Register,
+    ) -> Result<Option<u64>>
+    where
+        R: Reader,
+        S: UnwindContextStorage<R>,
+    {
+        self.register_rule(&row.register(register), cfa, register)
+    }
+
+    /// Recover the value of the given register in the previous frame using
+    /// the given rule for it, and the CFA computed by `cfa` or `cfa_rule`.
+    ///
+    /// This is useful for consumers that track rules themselves instead of
+    /// using an `UnwindTableRow`. The register is only used by
+    /// `RegisterRule::SameValue`.
+    pub fn register_rule<R: Reader>(
+        &mut self,
+        rule: &RegisterRule<R>,
+        cfa: u64,
+        register: Register,
+    ) -> Result<Option<u64>> {
+        let address_size = self.address_size;
+        let value = match *rule {
+            RegisterRule::Undefined => return Ok(None),
+            RegisterRule::SameValue => self.read_register(register)?,
+            RegisterRule::Offset(offset) => {
+                let address = self.mask(cfa.wrapping_add(offset as u64));
+                self.read_memory(address, address_size)?
+            }
+            RegisterRule::ValOffset(offset) => self.mask(cfa.wrapping_add(offset as u64)),
+            RegisterRule::Register(other) => self.read_register(other)?,
+            RegisterRule::Expression(ref expression) => {
+                let address = self.evaluate(expression, Some(cfa))?;
+                self.read_memory(address, address_size)?
+            }
+            RegisterRule::ValExpression(ref expression) => self.evaluate(expression, Some(cfa))?,
+            RegisterRule::Architectural => return Err(Error::UnsupportedRegisterRule),
+            RegisterRule::Constant(value) => value,
+        };
+        Ok(Some(value))
+    }
+
+    /// Recover the values of all registers that have a defined rule in the
+    /// row, given the CFA computed by `cfa`.
+    ///
+    /// The registers are returned in the same order as
+    /// `UnwindTableRow::registers`. Registers that are not returned have no
+    /// recoverable value.
+    pub fn registers<R, S>(
+        &mut self,
+        row: &UnwindTableRow<R, S>,
+        cfa: u64,
+    ) -> Result<Vec<(Register, u64)>>
+    where
+        R: Reader,
+        S: UnwindContextStorage<R>,
+    {
+        let mut values = Vec::new();
+        for &(register, _) in row.registers() {
+            if let Some(value) = self.register(row, cfa, register)? {
+                values.push((register, value));
+            }
+        }
+        Ok(values)
+    }
+
+    fn read_register(&mut self, register: Register) -> Result<u64> {
+        (self.read_register)(register).ok_or(Error::UnavailableRegister(register))
+    }
+
+    fn read_memory(&mut self, address: u64, size: u8) -> Result<u64> {
+        (self.read_memory)(address, size).ok_or(Error::UnavailableMemory(address))
+    }
+
+    fn mask(&self, value: u64) -> u64 {
+        if self.address_size >= 8 {
+            value
+        } else {
+            value & ((1 << (8 * u32::from(self.address_size))) - 1)
+        }
+    }
+
+    /// Evaluate a DWARF expression from a CFI rule, using the callbacks for
+    /// any registers and memory that it reads.
+    ///
+    /// For `RegisterRule::Expression` and `RegisterRule::ValExpression`, the
+    /// `cfa` must be given, and it is pushed onto the stack before evaluation.
+    /// For `CfaRule::Expression`, the `cfa` must be `None`.
+    ///
+    /// The expression must produce an address or a value. Operations that
+    /// aren't valid in CFI fail with `Error::UnsupportedCfiExpression`, and
+    /// `DW_OP_call_frame_cfa` fails with `Error::UnavailableCfa` if `cfa` is
+    /// `None`.
+    pub fn evaluate<R: Reader>(
+        &mut self,
+        expression: &Expression<R>,
+        cfa: Option<u64>,
+    ) -> Result<u64> {
+        // The format only affects operations that aren't valid in CFI.  CFI
+        // expressions are small, so fixed capacity storage avoids allocating.
+        let mut evaluation = Evaluation::<R, StoreOnStack>::new_in(
+            expression.0.clone(),
+            self.address_size,
+            Format::Dwarf32,
+        );
+        if let Some(cfa) = cfa {
+            evaluation.set_initial_value(cfa);
+        }
+        let mut result = evaluation.evaluate()?;
+        loop {
+            result = match (result, cfa) {
+                (EvaluationResult::Complete, _) => break,
+                (
+                    EvaluationResult::RequiresMemory {
+                        address,
+                        size,
+                        space: None,
+                    },
+                    _,
+                ) => {
+                    let value = self.read_memory(address, size)?;
+                    evaluation.resume_with_memory(value)?
+                }
+                (EvaluationResult::RequiresRegister(register), _) => {
+                    let value = self.read_register(register)?;
+                    evaluation.resume_with_register(value)?
+                }
+                (EvaluationResult::RequiresCallFrameCfa, Some(cfa)) => {
+                    evaluation.resume_with_call_frame_cfa(cfa)?
+                }
+                (EvaluationResult::RequiresCallFrameCfa, None) => return Err(Error::UnavailableCfa),
+                _ => return Err(Error::UnsupportedCfiExpression),
+            };
+        }
+        let pieces = evaluation.as_result();
+        if pieces.len() != 1 || pieces[0].size_in_bits.is_some() {
+            return Err(Error::UnsupportedCfiExpression);
+        }
+        match pieces[0].location {
+            Location::Address { address } => Ok(self.mask(address)),
+            Location::Scalar { value } => Ok(self.mask(value)),
+            _ => Err(Error::UnsupportedCfiExpression),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arch::X86_64;
+    use constants;
+    use endianity::{EndianBuf, LittleEndian};
+
+    fn read_register(register: Register) -> Option<u64> {
+        match register {
+            X86_64::RSP => Some(0x7000),
+            X86_64::RBX => Some(0xb),
+            X86_64::R13 => Some(0xd),
+            _ => None,
+        }
+    }
+
+    fn read_memory(address: u64, size: u8) -> Option<u64> {
+        assert_eq!(size, 8);
+        match address {
+            0x7008 => Some(0x1234),
+            0x7000 => Some(0x7100),
+            0x6ff8 => Some(0xf),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_unwind_row_evaluator() {
+        let expression = [constants::DW_OP_lit8.0, constants::DW_OP_minus.0];
+        let val_expression = [constants::DW_OP_breg13.0, 2];
+        let cfa = CfaRule::RegisterAndOffset {
+            register: X86_64::RSP,
+            offset: 16,
+        };
+        let mut row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0, 1, cfa);
+        let rules = [
+            (X86_64::RA, RegisterRule::Offset(-8)),
+            (X86_64::RBP, RegisterRule::Offset(-16)),
+            (X86_64::RSP, RegisterRule::ValOffset(0)),
+            (X86_64::RBX, RegisterRule::SameValue),
+            (X86_64::R12, RegisterRule::Register(X86_64::R13)),
+            (
+                X86_64::R14,
+                RegisterRule::ValExpression(Expression(EndianBuf::new(
+                    &val_expression,
+                    LittleEndian,
+                ))),
+            ),
+            (
+                X86_64::R15,
+                RegisterRule::Expression(Expression(EndianBuf::new(&expression, LittleEndian))),
+            ),
+            (X86_64::RAX, RegisterRule::Constant(7)),
+        ];
+        for rule in rules.iter() {
+            row.set_register(rule.0, rule.1.clone()).unwrap();
+        }
+
+        let mut evaluator = UnwindRowEvaluator::new(8, read_register, read_memory);
+        let cfa = evaluator.cfa(&row).unwrap();
+        assert_eq!(cfa, 0x7010);
+
+        let mut values = evaluator.registers(&row, cfa).unwrap();
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                (X86_64::RAX, 7),
+                (X86_64::RBX, 0xb),
+                (X86_64::RBP, 0x7100),
+                (X86_64::RSP, 0x7010),
+                (X86_64::R12, 0xd),
+                (X86_64::R14, 0xf),
+                (X86_64::R15, 0x1234),
+                (X86_64::RA, 0x1234),
+            ]
+        );
+        assert_eq!(evaluator.register(&row, cfa, X86_64::RCX), Ok(None));
+
+        row.set_register(X86_64::RCX, RegisterRule::SameValue).unwrap();
+        assert_eq!(
+            evaluator.register(&row, cfa, X86_64::RCX),
+            Err(Error::UnavailableRegister(X86_64::RCX))
+        );
+        row.set_register(X86_64::RCX, RegisterRule::Offset(8)).unwrap();
+        assert_eq!(
+            evaluator.register(&row, cfa, X86_64::RCX),
+            Err(Error::UnavailableMemory(0x7018))
+        );
+        row.set_register(X86_64::RCX, RegisterRule::Architectural).unwrap();
+        assert_eq!(
+            evaluator.register(&row, cfa, X86_64::RCX),
+            Err(Error::UnsupportedRegisterRule)
+        );
+    }
+
+    #[test]
+    fn test_unwind_row_evaluator_cfa_expression() {
+        // The CFA is not pushed for a CFA expression.
+        let expression = [constants::DW_OP_breg7.0, 8, constants::DW_OP_deref.0];
+        let cfa = CfaRule::Expression(Expression(EndianBuf::new(&expression, LittleEndian)));
+        let row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0, 1, cfa);
+        let mut evaluator = UnwindRowEvaluator::new(8, read_register, read_memory);
+        assert_eq!(evaluator.cfa(&row), Ok(0x1234));
+
+        let expression = [constants::DW_OP_call_frame_cfa.0];
+        let cfa = CfaRule::Expression(Expression(EndianBuf::new(&expression, LittleEndian)));
+        let row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0, 1, cfa);
+        assert_eq!(evaluator.cfa(&row), Err(Error::UnavailableCfa));
+    }
+
+    #[test]
+    fn test_unwind_rule_evaluation() {
+        let mut evaluator = UnwindRowEvaluator::new(8, read_register, read_memory);
+
+        let expression = [constants::DW_OP_breg7.0, 8];
+        let expression = Expression(EndianBuf::new(&expression, LittleEndian));
+        let cfa = evaluator
+            .cfa_rule(&CfaRule::Expression(expression.clone()))
+            .unwrap();
+        assert_eq!(cfa, 0x7008);
+        assert_eq!(evaluator.evaluate(&expression, None), Ok(0x7008));
+
+        // The CFA is pushed for register rules.
+        let expression = [constants::DW_OP_lit8.0, constants::DW_OP_minus.0];
+        let expression = Expression(EndianBuf::new(&expression, LittleEndian));
+        assert_eq!(evaluator.evaluate(&expression, Some(cfa)), Ok(0x7000));
+        assert_eq!(
+            evaluator.register_rule(
+                &RegisterRule::ValExpression(expression.clone()),
+                cfa,
+                X86_64::RA,
+            ),
+            Ok(Some(0x7000))
+        );
+        assert_eq!(
+            evaluator.register_rule(&RegisterRule::Expression(expression), cfa, X86_64::RA),
+            Ok(Some(0x7100))
+        );
+        assert_eq!(
+            evaluator.register_rule::<EndianBuf<LittleEndian>>(
+                &RegisterRule::SameValue,
+                cfa,
+                X86_64::RBX,
+            ),
+            Ok(Some(0xb))
+        );
+        assert_eq!(
+            evaluator.register_rule::<EndianBuf<LittleEndian>>(
+                &RegisterRule::Undefined,
+                cfa,
+                X86_64::RBX,
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_unwind_row_evaluator_address_size() {
+        let cfa = CfaRule::RegisterAndOffset {
+            register: Register(4),
+            offset: -8,
+        };
+        let row = UnwindTableRow::<EndianBuf<LittleEndian>>::new(0, 1, cfa);
+        let mut evaluator = UnwindRowEvaluator::new(4, |_| Some(4), |_, _| None);
+        assert_eq!(evaluator.cfa(&row), Ok(0xffff_fffc));
+    }
+}
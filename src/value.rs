@@ -0,0 +1,764 @@
+//! Definitions for values used in DWARF expressions.
+
+use constants;
+use parser::{Error, Result};
+use reader::{Reader, ReaderOffset};
+use unit::{AttributeValue, DebuggingInformationEntry};
+
+/// The type of an entry on the DWARF expression stack.
+///
+/// Operations that don't specify a type use the generic type, which is an
+/// integral type that has the size of an address on the target machine and
+/// unspecified signedness.  DWARF 5 typed stack operations, such as
+/// `DW_OP_const_type` and `DW_OP_convert`, instead refer to a base type DIE,
+/// which must be converted to a `ValueType` using `ValueType::from_entry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// The generic type.
+    Generic,
+    /// Signed 8-bit integer type.
+    I8,
+    /// Unsigned 8-bit integer type.
+    U8,
+    /// Signed 16-bit integer type.
+    I16,
+    /// Unsigned 16-bit integer type.
+    U16,
+    /// Signed 32-bit integer type.
+    I32,
+    /// Unsigned 32-bit integer type.
+    U32,
+    /// Signed 64-bit integer type.
+    I64,
+    /// Unsigned 64-bit integer type.
+    U64,
+    /// 32-bit floating point type.
+    F32,
+    /// 64-bit floating point type.
+    F64,
+}
+
+/// A value on the DWARF expression stack.
+///
+/// Values of the generic type are stored as a `u64`, but only the low bits
+/// that fit in an address are significant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    /// A value of the generic type.
+    Generic(u64),
+    /// A signed 8-bit integer value.
+    I8(i8),
+    /// An unsigned 8-bit integer value.
+    U8(u8),
+    /// A signed 16-bit integer value.
+    I16(i16),
+    /// An unsigned 16-bit integer value.
+    U16(u16),
+    /// A signed 32-bit integer value.
+    I32(i32),
+    /// An unsigned 32-bit integer value.
+    U32(u32),
+    /// A signed 64-bit integer value.
+    I64(i64),
+    /// An unsigned 64-bit integer value.
+    U64(u64),
+    /// A 32-bit floating point value.
+    F32(f32),
+    /// A 64-bit floating point value.
+    F64(f64),
+}
+
+impl ValueType {
+    /// The size in bits of a value of this type.
+    fn bit_size(self, addr_mask: u64) -> u32 {
+        match self {
+            ValueType::Generic => 64 - addr_mask.leading_zeros(),
+            ValueType::I8 | ValueType::U8 => 8,
+            ValueType::I16 | ValueType::U16 => 16,
+            ValueType::I32 | ValueType::U32 | ValueType::F32 => 32,
+            ValueType::I64 | ValueType::U64 | ValueType::F64 => 64,
+        }
+    }
+
+    fn is_float(self) -> bool {
+        match self {
+            ValueType::F32 | ValueType::F64 => true,
+            _ => false,
+        }
+    }
+
+    /// Construct a `ValueType` from the `DW_AT_encoding` and `DW_AT_byte_size`
+    /// attributes of a base type DIE.
+    ///
+    /// Returns `None` if the encoding and size aren't supported.
+    pub fn from_encoding(encoding: constants::DwAte, byte_size: u64) -> Option<ValueType> {
+        let value_type = match (encoding, byte_size) {
+            (constants::DW_ATE_signed, 1) | (constants::DW_ATE_signed_char, 1) => ValueType::I8,
+            (constants::DW_ATE_signed, 2) => ValueType::I16,
+            (constants::DW_ATE_signed, 4) => ValueType::I32,
+            (constants::DW_ATE_signed, 8) => ValueType::I64,
+            (constants::DW_ATE_unsigned, 1)
+            | (constants::DW_ATE_unsigned_char, 1)
+            | (constants::DW_ATE_boolean, 1) => ValueType::U8,
+            (constants::DW_ATE_unsigned, 2) | (constants::DW_ATE_UTF, 2) => ValueType::U16,
+            (constants::DW_ATE_unsigned, 4) | (constants::DW_ATE_UTF, 4) => ValueType::U32,
+            (constants::DW_ATE_unsigned, 8) => ValueType::U64,
+            (constants::DW_ATE_float, 4) => ValueType::F32,
+            (constants::DW_ATE_float, 8) => ValueType::F64,
+            _ => return None,
+        };
+        Some(value_type)
+    }
+
+    /// Construct a `ValueType` from a base type DIE, as referenced by the
+    /// typed stack operations.
+    ///
+    /// Returns `None` if the DIE is not a `DW_TAG_base_type`, or if its
+    /// encoding, size or endianity aren't supported.
+    pub fn from_entry<R: Reader>(
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+    ) -> Result<Option<ValueType>> {
+        if entry.tag() != constants::DW_TAG_base_type {
+            return Ok(None);
+        }
+        let mut encoding = None;
+        let mut byte_size = None;
+        let mut endianity = constants::DW_END_default;
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_byte_size => byte_size = attr.udata_value(),
+                constants::DW_AT_encoding => if let AttributeValue::Encoding(x) = attr.value() {
+                    encoding = Some(x);
+                },
+                constants::DW_AT_endianity => if let AttributeValue::Endianity(x) = attr.value() {
+                    endianity = x;
+                },
+                _ => {}
+            }
+        }
+
+        if endianity != constants::DW_END_default {
+            return Ok(None);
+        }
+        match (encoding, byte_size) {
+            (Some(encoding), Some(byte_size)) => Ok(ValueType::from_encoding(encoding, byte_size)),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn sign_extend(value: u64, addr_mask: u64) -> i64 {
+    let value = value & addr_mask;
+    let sign = (addr_mask >> 1) + 1;
+    if value & sign != 0 {
+        (value | !addr_mask) as i64
+    } else {
+        value as i64
+    }
+}
+
+macro_rules! binop {
+    ($lhs:expr, $rhs:expr, $generic:expr, | $a:ident, $b:ident | $int:expr, $float:expr) => {
+        match ($lhs, $rhs) {
+            (Value::F32($a), Value::F32($b)) => Value::F32($float),
+            (Value::F64($a), Value::F64($b)) => Value::F64($float),
+            (lhs, rhs) => binop!(lhs, rhs, $generic, |$a, $b| $int),
+        }
+    };
+    ($lhs:expr, $rhs:expr, $generic:expr, | $a:ident, $b:ident | $int:expr) => {
+        match ($lhs, $rhs) {
+            (Value::Generic($a), Value::Generic($b)) => Value::Generic($generic),
+            (Value::I8($a), Value::I8($b)) => Value::I8($int),
+            (Value::U8($a), Value::U8($b)) => Value::U8($int),
+            (Value::I16($a), Value::I16($b)) => Value::I16($int),
+            (Value::U16($a), Value::U16($b)) => Value::U16($int),
+            (Value::I32($a), Value::I32($b)) => Value::I32($int),
+            (Value::U32($a), Value::U32($b)) => Value::U32($int),
+            (Value::I64($a), Value::I64($b)) => Value::I64($int),
+            (Value::U64($a), Value::U64($b)) => Value::U64($int),
+            (Value::F32(_), Value::F32(_)) | (Value::F64(_), Value::F64(_)) => {
+                return Err(Error::IntegralTypeRequired)
+            }
+            _ => return Err(Error::TypeMismatch),
+        }
+    };
+}
+
+macro_rules! unop {
+    ($value:expr, $generic:expr, | $a:ident | $int:expr, $uint:expr, $float:expr) => {
+        match $value {
+            Value::F32($a) => Value::F32($float),
+            Value::F64($a) => Value::F64($float),
+            value => unop!(value, $generic, |$a| $int, $uint),
+        }
+    };
+    ($value:expr, $generic:expr, | $a:ident | $int:expr, $uint:expr) => {
+        match $value {
+            Value::Generic($a) => Value::Generic($generic),
+            Value::I8($a) => Value::I8($int),
+            Value::U8($a) => Value::U8($uint),
+            Value::I16($a) => Value::I16($int),
+            Value::U16($a) => Value::U16($uint),
+            Value::I32($a) => Value::I32($int),
+            Value::U32($a) => Value::U32($uint),
+            Value::I64($a) => Value::I64($int),
+            Value::U64($a) => Value::U64($uint),
+            Value::F32(_) | Value::F64(_) => return Err(Error::IntegralTypeRequired),
+        }
+    };
+}
+
+macro_rules! compare {
+    ($lhs:expr, $rhs:expr, $addr_mask:expr, $op:tt) => {
+        match ($lhs, $rhs) {
+            (Value::Generic(a), Value::Generic(b)) => {
+                sign_extend(a, $addr_mask) $op sign_extend(b, $addr_mask)
+            }
+            (Value::I8(a), Value::I8(b)) => a $op b,
+            (Value::U8(a), Value::U8(b)) => a $op b,
+            (Value::I16(a), Value::I16(b)) => a $op b,
+            (Value::U16(a), Value::U16(b)) => a $op b,
+            (Value::I32(a), Value::I32(b)) => a $op b,
+            (Value::U32(a), Value::U32(b)) => a $op b,
+            (Value::I64(a), Value::I64(b)) => a $op b,
+            (Value::U64(a), Value::U64(b)) => a $op b,
+            (Value::F32(a), Value::F32(b)) => a $op b,
+            (Value::F64(a), Value::F64(b)) => a $op b,
+            _ => return Err(Error::TypeMismatch),
+        }
+    };
+}
+
+macro_rules! convert {
+    ($value:expr, $value_type:expr, $addr_mask:expr) => {
+        match $value_type {
+            ValueType::Generic => Value::Generic(($value as u64) & $addr_mask),
+            ValueType::I8 => Value::I8($value as i8),
+            ValueType::U8 => Value::U8($value as u8),
+            ValueType::I16 => Value::I16($value as i16),
+            ValueType::U16 => Value::U16($value as u16),
+            ValueType::I32 => Value::I32($value as i32),
+            ValueType::U32 => Value::U32($value as u32),
+            ValueType::I64 => Value::I64($value as i64),
+            ValueType::U64 => Value::U64($value as u64),
+            ValueType::F32 => Value::F32($value as f32),
+            ValueType::F64 => Value::F64($value as f64),
+        }
+    };
+}
+
+macro_rules! shift {
+    ($value:expr, $amount:expr, $addr_mask:expr, $generic:expr, | $a:ident, $bits:ident | $signed:expr, $unsigned:expr) => {{
+        let $bits = u64::from($value.value_type().bit_size($addr_mask));
+        match $value {
+            Value::Generic($a) => Value::Generic($generic & $addr_mask),
+            Value::I8($a) => Value::I8($signed as i8),
+            Value::U8($a) => Value::U8($unsigned as u8),
+            Value::I16($a) => Value::I16($signed as i16),
+            Value::U16($a) => Value::U16($unsigned as u16),
+            Value::I32($a) => Value::I32($signed as i32),
+            Value::U32($a) => Value::U32($unsigned as u32),
+            Value::I64($a) => Value::I64($signed as i64),
+            Value::U64($a) => Value::U64($unsigned as u64),
+            Value::F32(_) | Value::F64(_) => return Err(Error::IntegralTypeRequired),
+        }
+    }};
+}
+
+impl Value {
+    /// Return the type of this value.
+    pub fn value_type(&self) -> ValueType {
+        match *self {
+            Value::Generic(_) => ValueType::Generic,
+            Value::I8(_) => ValueType::I8,
+            Value::U8(_) => ValueType::U8,
+            Value::I16(_) => ValueType::I16,
+            Value::U16(_) => ValueType::U16,
+            Value::I32(_) => ValueType::I32,
+            Value::U32(_) => ValueType::U32,
+            Value::I64(_) => ValueType::I64,
+            Value::U64(_) => ValueType::U64,
+            Value::F32(_) => ValueType::F32,
+            Value::F64(_) => ValueType::F64,
+        }
+    }
+
+    /// Read a value of the given type from `bytes`, such as the constant of
+    /// a `DW_OP_const_type` operation.
+    ///
+    /// The size of `bytes` must match the size of the type.  For the generic
+    /// type, it may be any size up to 8 bytes.
+    pub fn parse<R: Reader>(value_type: ValueType, mut bytes: R) -> Result<Value> {
+        let len = bytes.len().into_u64();
+        let expected = match value_type {
+            ValueType::Generic if len <= 8 => len,
+            _ => u64::from(value_type.bit_size(!0) / 8),
+        };
+        if len != expected {
+            return Err(Error::TypeMismatch);
+        }
+        let value = match value_type {
+            ValueType::Generic => Value::Generic(bytes.read_address(len as u8)?),
+            ValueType::I8 => Value::I8(bytes.read_i8()?),
+            ValueType::U8 => Value::U8(bytes.read_u8()?),
+            ValueType::I16 => Value::I16(bytes.read_i16()?),
+            ValueType::U16 => Value::U16(bytes.read_u16()?),
+            ValueType::I32 => Value::I32(bytes.read_i32()?),
+            ValueType::U32 => Value::U32(bytes.read_u32()?),
+            ValueType::I64 => Value::I64(bytes.read_i64()?),
+            ValueType::U64 => Value::U64(bytes.read_u64()?),
+            ValueType::F32 => Value::F32(bytes.read_f32()?),
+            ValueType::F64 => Value::F64(bytes.read_f64()?),
+        };
+        Ok(value)
+    }
+
+    /// Construct a value of the given type from its bit representation, such
+    /// as the contents of a register or memory.
+    ///
+    /// Bits beyond the size of the type are ignored.
+    pub fn from_u64(value_type: ValueType, value: u64) -> Value {
+        match value_type {
+            ValueType::Generic => Value::Generic(value),
+            ValueType::I8 => Value::I8(value as i8),
+            ValueType::U8 => Value::U8(value as u8),
+            ValueType::I16 => Value::I16(value as i16),
+            ValueType::U16 => Value::U16(value as u16),
+            ValueType::I32 => Value::I32(value as i32),
+            ValueType::U32 => Value::U32(value as u32),
+            ValueType::I64 => Value::I64(value as i64),
+            ValueType::U64 => Value::U64(value),
+            ValueType::F32 => Value::F32(f32::from_bits(value as u32)),
+            ValueType::F64 => Value::F64(f64::from_bits(value)),
+        }
+    }
+
+    /// Convert an integral value to a `u64`.
+    ///
+    /// Signed values are sign extended, and generic values are masked to the
+    /// size of an address.  Floating point values are an error.
+    pub fn to_u64(self, addr_mask: u64) -> Result<u64> {
+        let value = match self {
+            Value::Generic(value) => value & addr_mask,
+            Value::I8(value) => value as u64,
+            Value::U8(value) => u64::from(value),
+            Value::I16(value) => value as u64,
+            Value::U16(value) => u64::from(value),
+            Value::I32(value) => value as u64,
+            Value::U32(value) => u64::from(value),
+            Value::I64(value) => value as u64,
+            Value::U64(value) => value,
+            Value::F32(_) | Value::F64(_) => return Err(Error::IntegralTypeRequired),
+        };
+        Ok(value)
+    }
+
+    /// Return the bit representation of this value, zero extended.
+    fn to_bits(self, addr_mask: u64) -> u64 {
+        match self {
+            Value::Generic(value) => value & addr_mask,
+            Value::I8(value) => u64::from(value as u8),
+            Value::U8(value) => u64::from(value),
+            Value::I16(value) => u64::from(value as u16),
+            Value::U16(value) => u64::from(value),
+            Value::I32(value) => u64::from(value as u32),
+            Value::U32(value) => u64::from(value),
+            Value::I64(value) => value as u64,
+            Value::U64(value) => value,
+            Value::F32(value) => u64::from(value.to_bits()),
+            Value::F64(value) => value.to_bits(),
+        }
+    }
+
+    /// Convert this value to the given type, as done by `DW_OP_convert`.
+    ///
+    /// Integral values are truncated or extended according to their
+    /// signedness, and conversions to and from floating point types convert
+    /// the numeric value.  Generic values are treated as unsigned.
+    pub fn convert(self, value_type: ValueType, addr_mask: u64) -> Result<Value> {
+        let value = match self {
+            Value::Generic(value) => {
+                let value = value & addr_mask;
+                convert!(value, value_type, addr_mask)
+            }
+            Value::I8(value) => convert!(value, value_type, addr_mask),
+            Value::U8(value) => convert!(value, value_type, addr_mask),
+            Value::I16(value) => convert!(value, value_type, addr_mask),
+            Value::U16(value) => convert!(value, value_type, addr_mask),
+            Value::I32(value) => convert!(value, value_type, addr_mask),
+            Value::U32(value) => convert!(value, value_type, addr_mask),
+            Value::I64(value) => convert!(value, value_type, addr_mask),
+            Value::U64(value) => convert!(value, value_type, addr_mask),
+            Value::F32(value) => convert!(value, value_type, addr_mask),
+            Value::F64(value) => convert!(value, value_type, addr_mask),
+        };
+        Ok(value)
+    }
+
+    /// Reinterpret the bits of this value as the given type, as done by
+    /// `DW_OP_reinterpret`.
+    ///
+    /// The types must have the same size.
+    pub fn reinterpret(self, value_type: ValueType, addr_mask: u64) -> Result<Value> {
+        if self.value_type().bit_size(addr_mask) != value_type.bit_size(addr_mask) {
+            return Err(Error::TypeMismatch);
+        }
+        Ok(Value::from_u64(value_type, self.to_bits(addr_mask)))
+    }
+
+    /// Add a constant to this value, as done by `DW_OP_plus_uconst`.
+    pub(crate) fn add_constant(self, constant: u64, addr_mask: u64) -> Result<Value> {
+        let value_type = self.value_type();
+        if value_type.is_float() {
+            return Err(Error::IntegralTypeRequired);
+        }
+        self.add(Value::from_u64(value_type, constant), addr_mask)
+    }
+
+    pub(crate) fn abs(self, addr_mask: u64) -> Result<Value> {
+        Ok(unop!(
+            self,
+            sign_extend(a, addr_mask).wrapping_abs() as u64 & addr_mask,
+            |a| a.wrapping_abs(),
+            a,
+            a.abs()
+        ))
+    }
+
+    pub(crate) fn neg(self, addr_mask: u64) -> Result<Value> {
+        Ok(unop!(
+            self,
+            a.wrapping_neg() & addr_mask,
+            |a| a.wrapping_neg(),
+            a.wrapping_neg(),
+            -a
+        ))
+    }
+
+    pub(crate) fn not(self, addr_mask: u64) -> Result<Value> {
+        Ok(unop!(
+            self,
+            !a & addr_mask,
+            |a| !a,
+            !a
+        ))
+    }
+
+    pub(crate) fn add(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            a.wrapping_add(b) & addr_mask,
+            |a, b| a.wrapping_add(b),
+            a + b
+        ))
+    }
+
+    pub(crate) fn sub(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            a.wrapping_sub(b) & addr_mask,
+            |a, b| a.wrapping_sub(b),
+            a - b
+        ))
+    }
+
+    pub(crate) fn mul(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            a.wrapping_mul(b) & addr_mask,
+            |a, b| a.wrapping_mul(b),
+            a * b
+        ))
+    }
+
+    /// Divide this value by `rhs`.  Generic values are treated as signed.
+    pub(crate) fn div(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        if !rhs.value_type().is_float() && rhs.to_bits(addr_mask) == 0 {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(binop!(
+            self,
+            rhs,
+            sign_extend(a, addr_mask).wrapping_div(sign_extend(b, addr_mask)) as u64 & addr_mask,
+            |a, b| a.wrapping_div(b),
+            a / b
+        ))
+    }
+
+    /// Compute the remainder of dividing this value by `rhs`.  Generic
+    /// values are treated as unsigned.
+    pub(crate) fn rem(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        if rhs.value_type().is_float() {
+            return Err(Error::IntegralTypeRequired);
+        }
+        if rhs.to_bits(addr_mask) == 0 {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(binop!(
+            self,
+            rhs,
+            (a & addr_mask).wrapping_rem(b & addr_mask),
+            |a, b| a.wrapping_rem(b)
+        ))
+    }
+
+    pub(crate) fn and(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            a & b & addr_mask,
+            |a, b| a & b
+        ))
+    }
+
+    pub(crate) fn or(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            (a | b) & addr_mask,
+            |a, b| a | b
+        ))
+    }
+
+    pub(crate) fn xor(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        Ok(binop!(
+            self,
+            rhs,
+            (a ^ b) & addr_mask,
+            |a, b| a ^ b
+        ))
+    }
+
+    /// Shift this value left by `rhs`, which may be of any integral type.
+    pub(crate) fn shl(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let amount = rhs.to_u64(addr_mask)?;
+        Ok(shift!(
+            self,
+            amount,
+            addr_mask,
+            if amount >= 64 { 0 } else { a << amount },
+            |a, bits| if amount >= bits { 0 } else { a << amount },
+            if amount >= bits { 0 } else { a << amount }
+        ))
+    }
+
+    /// Logically shift this value right by `rhs`, which may be of any
+    /// integral type.
+    pub(crate) fn shr(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let amount = rhs.to_u64(addr_mask)?;
+        Ok(shift!(
+            self,
+            amount,
+            addr_mask,
+            if amount >= 64 { 0 } else { (a & addr_mask) >> amount },
+            |a, bits| if amount >= bits {
+                0
+            } else {
+                ((a as u64) & (!0u64 >> (64 - bits))) >> amount
+            },
+            if amount >= bits { 0 } else { a >> amount }
+        ))
+    }
+
+    /// Arithmetically shift this value right by `rhs`, which may be of any
+    /// integral type.
+    pub(crate) fn shra(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let amount = rhs.to_u64(addr_mask)?;
+        Ok(shift!(
+            self,
+            amount,
+            addr_mask,
+            (sign_extend(a, addr_mask) >> amount.min(63)) as u64,
+            |a, bits| a >> amount.min(bits - 1),
+            shra_unsigned(u64::from(a), bits, amount)
+        ))
+    }
+
+    pub(crate) fn eq(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, ==);
+        Ok(Value::Generic(result as u64))
+    }
+
+    pub(crate) fn ne(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, !=);
+        Ok(Value::Generic(result as u64))
+    }
+
+    pub(crate) fn ge(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, >=);
+        Ok(Value::Generic(result as u64))
+    }
+
+    pub(crate) fn gt(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, >);
+        Ok(Value::Generic(result as u64))
+    }
+
+    pub(crate) fn le(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, <=);
+        Ok(Value::Generic(result as u64))
+    }
+
+    pub(crate) fn lt(self, rhs: Value, addr_mask: u64) -> Result<Value> {
+        let result = compare!(self, rhs, addr_mask, <);
+        Ok(Value::Generic(result as u64))
+    }
+}
+
+/// Arithmetically shift an unsigned value of the given bit size right.
+fn shra_unsigned(value: u64, bits: u64, amount: u64) -> u64 {
+    // Sign extend from the top bit of the value, then shift.
+    let extend = 64 - bits;
+    (((value << extend) as i64) >> extend >> amount.min(bits - 1)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constants;
+    use endianity::{EndianBuf, LittleEndian};
+    use parser::Error;
+
+    const MASK32: u64 = 0xffff_ffff;
+
+    #[test]
+    fn test_value_type_from_encoding() {
+        assert_eq!(
+            ValueType::from_encoding(constants::DW_ATE_signed, 4),
+            Some(ValueType::I32)
+        );
+        assert_eq!(
+            ValueType::from_encoding(constants::DW_ATE_unsigned_char, 1),
+            Some(ValueType::U8)
+        );
+        assert_eq!(
+            ValueType::from_encoding(constants::DW_ATE_float, 8),
+            Some(ValueType::F64)
+        );
+        assert_eq!(ValueType::from_encoding(constants::DW_ATE_float, 16), None);
+        assert_eq!(ValueType::from_encoding(constants::DW_ATE_complex_float, 8), None);
+    }
+
+    #[test]
+    fn test_value_parse() {
+        let bytes = EndianBuf::new(&[0x00, 0x00, 0xc0, 0x3f], LittleEndian);
+        assert_eq!(Value::parse(ValueType::F32, bytes), Ok(Value::F32(1.5)));
+        assert_eq!(Value::parse(ValueType::I32, bytes), Ok(Value::I32(0x3fc0_0000)));
+        assert_eq!(Value::parse(ValueType::Generic, bytes), Ok(Value::Generic(0x3fc0_0000)));
+        assert_eq!(Value::parse(ValueType::I16, bytes), Err(Error::TypeMismatch));
+    }
+
+    #[test]
+    fn test_value_convert() {
+        assert_eq!(Value::I8(-1).convert(ValueType::I32, MASK32), Ok(Value::I32(-1)));
+        assert_eq!(Value::I8(-1).convert(ValueType::U32, MASK32), Ok(Value::U32(0xffff_ffff)));
+        assert_eq!(Value::U8(0xff).convert(ValueType::I32, MASK32), Ok(Value::I32(0xff)));
+        assert_eq!(Value::I32(-3).convert(ValueType::F64, MASK32), Ok(Value::F64(-3.0)));
+        assert_eq!(Value::F32(2.75).convert(ValueType::I16, MASK32), Ok(Value::I16(2)));
+        assert_eq!(
+            Value::I64(-1).convert(ValueType::Generic, MASK32),
+            Ok(Value::Generic(0xffff_ffff))
+        );
+        assert_eq!(
+            Value::Generic(0xffff_ffff_ffff_ffff).convert(ValueType::U64, MASK32),
+            Ok(Value::U64(0xffff_ffff))
+        );
+    }
+
+    #[test]
+    fn test_value_reinterpret() {
+        assert_eq!(
+            Value::U32(0x3fc0_0000).reinterpret(ValueType::F32, MASK32),
+            Ok(Value::F32(1.5))
+        );
+        assert_eq!(
+            Value::F64(-0.0).reinterpret(ValueType::U64, MASK32),
+            Ok(Value::U64(0x8000_0000_0000_0000))
+        );
+        assert_eq!(
+            Value::I32(-1).reinterpret(ValueType::Generic, MASK32),
+            Ok(Value::Generic(0xffff_ffff))
+        );
+        assert_eq!(
+            Value::I32(-1).reinterpret(ValueType::Generic, !0),
+            Err(Error::TypeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_value_arithmetic() {
+        assert_eq!(Value::I8(127).add(Value::I8(1), MASK32), Ok(Value::I8(-128)));
+        assert_eq!(Value::U16(0).sub(Value::U16(1), MASK32), Ok(Value::U16(0xffff)));
+        assert_eq!(Value::F64(1.5).mul(Value::F64(2.0), MASK32), Ok(Value::F64(3.0)));
+        assert_eq!(Value::F32(1.0).div(Value::F32(4.0), MASK32), Ok(Value::F32(0.25)));
+        assert_eq!(Value::I32(-7).div(Value::I32(2), MASK32), Ok(Value::I32(-3)));
+        assert_eq!(Value::U32(7).rem(Value::U32(0), MASK32), Err(Error::DivisionByZero));
+        assert_eq!(Value::F32(1.0).rem(Value::F32(1.0), MASK32), Err(Error::IntegralTypeRequired));
+        assert_eq!(Value::F32(1.0).and(Value::F32(1.0), MASK32), Err(Error::IntegralTypeRequired));
+        assert_eq!(Value::I32(1).add(Value::U32(1), MASK32), Err(Error::TypeMismatch));
+        assert_eq!(Value::I32(1).add(Value::Generic(1), MASK32), Err(Error::TypeMismatch));
+        assert_eq!(Value::F64(-2.0).abs(MASK32), Ok(Value::F64(2.0)));
+        assert_eq!(Value::U8(1).neg(MASK32), Ok(Value::U8(0xff)));
+        assert_eq!(Value::F32(1.0).not(MASK32), Err(Error::IntegralTypeRequired));
+        assert_eq!(Value::I16(1).add_constant(2, MASK32), Ok(Value::I16(3)));
+        assert_eq!(
+            Value::F32(1.0).add_constant(2, MASK32),
+            Err(Error::IntegralTypeRequired)
+        );
+
+        // Generic values are masked to the address size, and treated as
+        // signed for division and comparisons.
+        assert_eq!(
+            Value::Generic(0xffff_ffff).add(Value::Generic(2), MASK32),
+            Ok(Value::Generic(1))
+        );
+        assert_eq!(
+            Value::Generic(0xffff_fffe).div(Value::Generic(2), MASK32),
+            Ok(Value::Generic(0xffff_ffff))
+        );
+        assert_eq!(
+            Value::Generic(0xffff_ffff).lt(Value::Generic(0), MASK32),
+            Ok(Value::Generic(1))
+        );
+    }
+
+    #[test]
+    fn test_value_shift() {
+        assert_eq!(Value::I8(-128).shr(Value::U8(7), MASK32), Ok(Value::I8(1)));
+        assert_eq!(Value::I8(-128).shra(Value::U8(7), MASK32), Ok(Value::I8(-1)));
+        assert_eq!(Value::U8(0x80).shra(Value::Generic(7), MASK32), Ok(Value::U8(0xff)));
+        assert_eq!(Value::U8(0x80).shra(Value::Generic(100), MASK32), Ok(Value::U8(0xff)));
+        assert_eq!(Value::U8(0x40).shra(Value::Generic(100), MASK32), Ok(Value::U8(0)));
+        assert_eq!(Value::U16(1).shl(Value::I8(15), MASK32), Ok(Value::U16(0x8000)));
+        assert_eq!(Value::U16(1).shl(Value::I8(16), MASK32), Ok(Value::U16(0)));
+        assert_eq!(Value::I32(1).shl(Value::I8(-1), MASK32), Ok(Value::I32(0)));
+        assert_eq!(
+            Value::Generic(0x8000_0000).shra(Value::Generic(31), MASK32),
+            Ok(Value::Generic(0xffff_ffff))
+        );
+        assert_eq!(
+            Value::F64(1.0).shl(Value::U8(1), MASK32),
+            Err(Error::IntegralTypeRequired)
+        );
+        assert_eq!(
+            Value::U8(1).shl(Value::F64(1.0), MASK32),
+            Err(Error::IntegralTypeRequired)
+        );
+    }
+
+    #[test]
+    fn test_value_compare() {
+        assert_eq!(Value::I8(-1).lt(Value::I8(0), MASK32), Ok(Value::Generic(1)));
+        assert_eq!(Value::U8(0xff).lt(Value::U8(0), MASK32), Ok(Value::Generic(0)));
+        assert_eq!(Value::F32(1.5).ge(Value::F32(1.5), MASK32), Ok(Value::Generic(1)));
+        assert_eq!(
+            Value::F64(::std::f64::NAN).eq(Value::F64(::std::f64::NAN), MASK32),
+            Ok(Value::Generic(0))
+        );
+        assert_eq!(Value::F32(1.0).ne(Value::F64(1.0), MASK32), Err(Error::TypeMismatch));
+    }
+}
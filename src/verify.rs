@@ -0,0 +1,561 @@
+//! Checks for structural problems in DWARF debugging information.
+
+use constants;
+use dwarf::{Dwarf, Unit};
+use parser::{Error, Result};
+use reader::Reader;
+use rnglists::Range;
+use unit::{AttributeValue, DebugInfoOffset, UnitOffset};
+use vec::Vec;
+
+/// A structural problem found by `Dwarf::verify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyIssue<T = usize> {
+    /// The offset of the unit containing the problem.
+    pub unit: DebugInfoOffset<T>,
+
+    /// The offset of the entry containing the problem, if the problem is
+    /// associated with a single entry.
+    pub entry: Option<UnitOffset<T>>,
+
+    /// The kind of problem.
+    pub kind: VerifyIssueKind<T>,
+}
+
+/// The kind of a structural problem found by `Dwarf::verify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyIssueKind<T = usize> {
+    /// An error occurred while parsing the unit. The remainder of the unit
+    /// was not verified.
+    Error(Error),
+
+    /// An entry uses an abbreviation code that is not declared in the unit's
+    /// abbreviations. The remainder of the unit was not verified.
+    UnknownAbbreviation,
+
+    /// A reference attribute does not refer to the start of an entry in the
+    /// `.debug_info` section.
+    DanglingReference {
+        /// The name of the attribute.
+        name: constants::DwAt,
+        /// The offset that the attribute refers to.
+        target: DebugInfoOffset<T>,
+    },
+
+    /// An attribute uses a form that does not belong to any of the classes
+    /// that are permitted for the attribute.
+    InvalidForm {
+        /// The name of the attribute.
+        name: constants::DwAt,
+        /// The form of the attribute.
+        form: constants::DwForm,
+    },
+
+    /// An address range of the unit overlaps an address range of another unit.
+    OverlappingRanges {
+        /// The overlapping range of this unit.
+        range: Range,
+        /// The offset of the other unit.
+        other: DebugInfoOffset<T>,
+    },
+
+    /// A row in the unit's line number program has an address that is not
+    /// within the address ranges of the unit.
+    LineAddressOutOfRange {
+        /// The address of the row.
+        address: u64,
+    },
+}
+
+// Attribute classes, as listed in Section 7.5.5 of the DWARF 5 standard.
+// Pointer classes (lineptr, loclist, rnglist, etc) are combined into
+// `SECTION_OFFSET`.
+const ADDRESS: u8 = 1 << 0;
+const BLOCK: u8 = 1 << 1;
+const CONSTANT: u8 = 1 << 2;
+const EXPRLOC: u8 = 1 << 3;
+const FLAG: u8 = 1 << 4;
+const REFERENCE: u8 = 1 << 5;
+const SECTION_OFFSET: u8 = 1 << 6;
+const STRING: u8 = 1 << 7;
+
+/// The classes that a form can belong to, or `None` if this can't be known
+/// without reading the attribute value.
+fn form_classes(form: constants::DwForm, version: u16) -> Option<u8> {
+    let classes = match form {
+        constants::DW_FORM_addr
+        | constants::DW_FORM_addrx
+        | constants::DW_FORM_addrx1
+        | constants::DW_FORM_addrx2
+        | constants::DW_FORM_addrx3
+        | constants::DW_FORM_addrx4
+        | constants::DW_FORM_GNU_addr_index => ADDRESS,
+        constants::DW_FORM_block
+        | constants::DW_FORM_block1
+        | constants::DW_FORM_block2
+        | constants::DW_FORM_block4 => BLOCK,
+        // DWARF version 2/3 may use DW_FORM_data4/8 for section offsets.
+        constants::DW_FORM_data4 | constants::DW_FORM_data8 if version < 4 => {
+            CONSTANT | SECTION_OFFSET
+        }
+        constants::DW_FORM_data1
+        | constants::DW_FORM_data2
+        | constants::DW_FORM_data4
+        | constants::DW_FORM_data8
+        | constants::DW_FORM_data16
+        | constants::DW_FORM_sdata
+        | constants::DW_FORM_udata
+        | constants::DW_FORM_implicit_const => CONSTANT,
+        constants::DW_FORM_exprloc => EXPRLOC,
+        constants::DW_FORM_flag | constants::DW_FORM_flag_present => FLAG,
+        constants::DW_FORM_ref_addr
+        | constants::DW_FORM_ref1
+        | constants::DW_FORM_ref2
+        | constants::DW_FORM_ref4
+        | constants::DW_FORM_ref8
+        | constants::DW_FORM_ref_udata
+        | constants::DW_FORM_ref_sig8
+        | constants::DW_FORM_ref_sup4
+        | constants::DW_FORM_ref_sup8
+        | constants::DW_FORM_GNU_ref_alt => REFERENCE,
+        constants::DW_FORM_sec_offset
+        | constants::DW_FORM_loclistx
+        | constants::DW_FORM_rnglistx => SECTION_OFFSET,
+        constants::DW_FORM_string
+        | constants::DW_FORM_strp
+        | constants::DW_FORM_strx
+        | constants::DW_FORM_strx1
+        | constants::DW_FORM_strx2
+        | constants::DW_FORM_strx3
+        | constants::DW_FORM_strx4
+        | constants::DW_FORM_line_strp
+        | constants::DW_FORM_strp_sup
+        | constants::DW_FORM_GNU_str_index
+        | constants::DW_FORM_GNU_strp_alt => STRING,
+        _ => return None,
+    };
+    Some(classes)
+}
+
+/// The classes that are permitted for an attribute, or `None` if the
+/// attribute is not checked.
+fn attribute_classes(name: constants::DwAt, version: u16) -> Option<u8> {
+    // DWARF version 2/3 used blocks for location descriptions.
+    let location = if version < 4 {
+        BLOCK | SECTION_OFFSET
+    } else {
+        EXPRLOC | SECTION_OFFSET
+    };
+    let classes = match name {
+        constants::DW_AT_sibling
+        | constants::DW_AT_type
+        | constants::DW_AT_abstract_origin
+        | constants::DW_AT_specification
+        | constants::DW_AT_containing_type
+        | constants::DW_AT_import
+        | constants::DW_AT_common_reference
+        | constants::DW_AT_friend
+        | constants::DW_AT_object_pointer
+        | constants::DW_AT_extension
+        | constants::DW_AT_signature => REFERENCE,
+        constants::DW_AT_name
+        | constants::DW_AT_comp_dir
+        | constants::DW_AT_producer
+        | constants::DW_AT_linkage_name
+        | constants::DW_AT_MIPS_linkage_name => STRING,
+        constants::DW_AT_low_pc => ADDRESS,
+        constants::DW_AT_high_pc => ADDRESS | CONSTANT,
+        constants::DW_AT_stmt_list | constants::DW_AT_ranges => SECTION_OFFSET,
+        constants::DW_AT_location
+        | constants::DW_AT_frame_base
+        | constants::DW_AT_return_addr
+        | constants::DW_AT_static_link
+        | constants::DW_AT_use_location
+        | constants::DW_AT_vtable_elem_location => location,
+        constants::DW_AT_data_member_location => location | CONSTANT,
+        constants::DW_AT_byte_size
+        | constants::DW_AT_bit_size
+        | constants::DW_AT_bit_offset
+        | constants::DW_AT_count
+        | constants::DW_AT_lower_bound
+        | constants::DW_AT_upper_bound => BLOCK | CONSTANT | EXPRLOC | REFERENCE,
+        constants::DW_AT_const_value => BLOCK | CONSTANT | STRING,
+        constants::DW_AT_decl_file
+        | constants::DW_AT_decl_line
+        | constants::DW_AT_decl_column
+        | constants::DW_AT_call_file
+        | constants::DW_AT_call_line
+        | constants::DW_AT_call_column
+        | constants::DW_AT_language
+        | constants::DW_AT_language_name
+        | constants::DW_AT_language_version
+        | constants::DW_AT_encoding
+        | constants::DW_AT_accessibility
+        | constants::DW_AT_visibility
+        | constants::DW_AT_virtuality
+        | constants::DW_AT_inline
+        | constants::DW_AT_ordering
+        | constants::DW_AT_calling_convention
+        | constants::DW_AT_identifier_case
+        | constants::DW_AT_decimal_sign
+        | constants::DW_AT_endianity
+        | constants::DW_AT_alignment => CONSTANT,
+        constants::DW_AT_external
+        | constants::DW_AT_declaration
+        | constants::DW_AT_artificial
+        | constants::DW_AT_prototyped
+        | constants::DW_AT_is_optional
+        | constants::DW_AT_variable_parameter
+        | constants::DW_AT_main_subprogram
+        | constants::DW_AT_enum_class
+        | constants::DW_AT_explicit
+        | constants::DW_AT_pure
+        | constants::DW_AT_elemental
+        | constants::DW_AT_recursive
+        | constants::DW_AT_noreturn
+        | constants::DW_AT_export_symbols => FLAG,
+        _ => return None,
+    };
+    Some(classes)
+}
+
+/// A reference attribute: the unit and entry containing it, its name, and
+/// its target.
+type Reference<T> = (DebugInfoOffset<T>, UnitOffset<T>, constants::DwAt, DebugInfoOffset<T>);
+
+impl<R: Reader> Dwarf<R> {
+    /// Walk all of the units in the `.debug_info` section, and report any
+    /// structural problems that are found.
+    ///
+    /// The following problems are detected:
+    ///
+    /// * references to offsets that are not the start of an entry,
+    /// * attribute forms that are inconsistent with the attribute's class,
+    /// * address ranges of units that overlap other units,
+    /// * line number program addresses that are outside the unit's ranges,
+    /// * abbreviation codes that have no declaration.
+    ///
+    /// Errors that prevent verification of a single unit are reported as
+    /// issues for that unit. Errors that prevent locating the remaining units
+    /// are returned as `Err`.
+    pub fn verify(&self) -> Result<Vec<VerifyIssue<R::Offset>>> {
+        let mut issues = Vec::new();
+        let mut entries = Vec::new();
+        let mut references = Vec::new();
+        let mut unit_ranges = Vec::new();
+
+        let mut units = self.units();
+        while let Some(header) = units.next()? {
+            let unit_offset = header.offset();
+            let mut issue = |entry, kind| {
+                issues.push(VerifyIssue {
+                    unit: unit_offset,
+                    entry,
+                    kind,
+                })
+            };
+            let unit = match self.unit(header) {
+                Ok(unit) => unit,
+                Err(e) => {
+                    issue(None, error_kind(e));
+                    continue;
+                }
+            };
+
+            let ranges = match self.verify_entries(&unit, &mut entries, &mut references, &mut issue)
+            {
+                Ok(ranges) => ranges,
+                Err(e) => {
+                    issue(None, error_kind(e));
+                    continue;
+                }
+            };
+
+            if let Some(ref program) = unit.line_program {
+                if !ranges.is_empty() {
+                    let mut rows = program.clone().rows();
+                    loop {
+                        match rows.next_row() {
+                            Ok(Some((_, row))) => {
+                                let address = row.address();
+                                if !row.end_sequence()
+                                    && !ranges
+                                        .iter()
+                                        .any(|r| r.begin <= address && address < r.end)
+                                {
+                                    issue(None, VerifyIssueKind::LineAddressOutOfRange { address });
+                                }
+                            }
+                            Ok(None) => break,
+                            Err(e) => {
+                                issue(None, VerifyIssueKind::Error(e));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            unit_ranges.extend(ranges.into_iter().map(|range| (range, unit_offset)));
+        }
+
+        // Entry offsets are already sorted because units and entries are
+        // visited in order.
+        for (unit, entry, name, target) in references {
+            if entries.binary_search_by(|e| e.0.cmp(&target.0)).is_err() {
+                issues.push(VerifyIssue {
+                    unit,
+                    entry: Some(entry),
+                    kind: VerifyIssueKind::DanglingReference { name, target },
+                });
+            }
+        }
+
+        unit_ranges.sort_by_key(|&(range, _)| (range.begin, range.end));
+        let mut previous: Option<(Range, DebugInfoOffset<R::Offset>)> = None;
+        for (range, unit) in unit_ranges {
+            if let Some((previous_range, previous_unit)) = previous {
+                if range.begin < previous_range.end && previous_unit != unit {
+                    issues.push(VerifyIssue {
+                        unit,
+                        entry: None,
+                        kind: VerifyIssueKind::OverlappingRanges {
+                            range,
+                            other: previous_unit,
+                        },
+                    });
+                }
+                if previous_range.end >= range.end {
+                    continue;
+                }
+            }
+            previous = Some((range, unit));
+        }
+
+        Ok(issues)
+    }
+
+    /// Check the entries of a unit, recording their offsets and references.
+    ///
+    /// Returns the non-empty address ranges of the unit's root entry.
+    fn verify_entries<F>(
+        &self,
+        unit: &Unit<R>,
+        entries: &mut Vec<DebugInfoOffset<R::Offset>>,
+        references: &mut Vec<Reference<R::Offset>>,
+        issue: &mut F,
+    ) -> Result<Vec<Range>>
+    where
+        F: FnMut(Option<UnitOffset<R::Offset>>, VerifyIssueKind<R::Offset>),
+    {
+        let version = unit.header.version();
+        let mut ranges = Vec::new();
+        let mut cursor = unit.entries();
+        let mut is_root = true;
+        while let Some((_, entry)) = cursor.next_dfs()? {
+            let offset = entry.offset();
+            entries.push(offset.to_debug_info_offset(&unit.header));
+
+            if let Some(abbrev) = unit.abbreviations.get(entry.code()) {
+                for spec in abbrev.attributes() {
+                    let allowed = match attribute_classes(spec.name(), version) {
+                        Some(allowed) => allowed,
+                        None => continue,
+                    };
+                    if let Some(classes) = form_classes(spec.form(), version) {
+                        if classes & allowed == 0 {
+                            issue(
+                                Some(offset),
+                                VerifyIssueKind::InvalidForm {
+                                    name: spec.name(),
+                                    form: spec.form(),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+
+            let mut attrs = entry.attrs();
+            while let Some(attr) = attrs.next()? {
+                let target = match attr.raw_value() {
+                    AttributeValue::UnitRef(target) => target.to_debug_info_offset(&unit.header),
+                    AttributeValue::DebugInfoRef(target) => target,
+                    _ => continue,
+                };
+                references.push((unit.header.offset(), offset, attr.name(), target));
+            }
+
+            if is_root {
+                is_root = false;
+                ranges = self.die_ranges(unit, entry)?;
+            }
+        }
+        Ok(ranges)
+    }
+}
+
+fn error_kind<T>(e: Error) -> VerifyIssueKind<T> {
+    match e {
+        Error::UnknownAbbreviation => VerifyIssueKind::UnknownAbbreviation,
+        e => VerifyIssueKind::Error(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use SectionId;
+
+    #[test]
+    fn test_verify() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            // DW_AT_stmt_list, DW_FORM_sec_offset.
+            0x10, 0x17,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x02, 0x2e, 0x00,
+            // DW_AT_name, DW_FORM_data1.
+            0x03, 0x0b,
+            // DW_AT_type, DW_FORM_ref4.
+            0x49, 0x13,
+            0x00, 0x00,
+            // Code, tag, children.
+            0x03, 0x11, 0x00,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_high_pc, DW_FORM_data4.
+            0x12, 0x06,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x1d, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry.
+            0x01,
+            b'a', 0x00,
+            0x00, 0x10, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            // Child entry, with a reference into the middle of the root entry.
+            0x02,
+            0x05,
+            0x0c, 0x00, 0x00, 0x00,
+            // End of children.
+            0x00,
+
+            // Unit length, version, abbrev offset, address size.
+            0x10, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry, overlapping the first unit.
+            0x03,
+            0x80, 0x10, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+
+            // Unit length, version, abbrev offset, address size.
+            0x08, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x04,
+            // Root entry with an undeclared abbreviation code.
+            0x09,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_line = [
+            // Unit length, version, header length.
+            0x31, 0x00, 0x00, 0x00,
+            0x02, 0x00,
+            0x18, 0x00, 0x00, 0x00,
+            // Minimum instruction length, default is_stmt, line base,
+            // line range, opcode base.
+            0x01, 0x01, 0xfb, 0x0e, 0x0d,
+            // Standard opcode lengths.
+            0x00, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+            // Include directories.
+            0x00,
+            // File names.
+            b'a', 0x00, 0x00, 0x00, 0x00,
+            0x00,
+            // DW_LNE_set_address, DW_LNS_copy.
+            0x00, 0x05, 0x02, 0x00, 0x10, 0x00, 0x00,
+            0x01,
+            // DW_LNE_set_address, DW_LNS_copy.
+            0x00, 0x05, 0x02, 0x00, 0x50, 0x00, 0x00,
+            0x01,
+            // DW_LNE_end_sequence.
+            0x00, 0x01, 0x01,
+        ];
+
+        let dwarf = Dwarf::load(|id| -> Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugLine => &debug_line[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        assert_eq!(
+            dwarf.verify(),
+            Ok(vec![
+                VerifyIssue {
+                    unit: DebugInfoOffset(0),
+                    entry: Some(UnitOffset(0x1a)),
+                    kind: VerifyIssueKind::InvalidForm {
+                        name: constants::DW_AT_name,
+                        form: constants::DW_FORM_data1,
+                    },
+                },
+                VerifyIssue {
+                    unit: DebugInfoOffset(0),
+                    entry: None,
+                    kind: VerifyIssueKind::LineAddressOutOfRange { address: 0x5000 },
+                },
+                VerifyIssue {
+                    unit: DebugInfoOffset(0x35),
+                    entry: None,
+                    kind: VerifyIssueKind::UnknownAbbreviation,
+                },
+                VerifyIssue {
+                    unit: DebugInfoOffset(0),
+                    entry: Some(UnitOffset(0x1a)),
+                    kind: VerifyIssueKind::DanglingReference {
+                        name: constants::DW_AT_type,
+                        target: DebugInfoOffset(0x0c),
+                    },
+                },
+                VerifyIssue {
+                    unit: DebugInfoOffset(0x21),
+                    entry: None,
+                    kind: VerifyIssueKind::OverlappingRanges {
+                        range: Range {
+                            begin: 0x1080,
+                            end: 0x1180,
+                        },
+                        other: DebugInfoOffset(0),
+                    },
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use constants;
+use vec::Vec;
+use write::{Result, Writer};
+
+/// A table of abbreviations that will be stored in a `.debug_abbrev` section.
+///
+/// Identical abbreviations are only stored once.
+#[derive(Debug, Default)]
+pub(crate) struct AbbreviationTable {
+    abbrevs: Vec<Abbreviation>,
+}
+
+impl AbbreviationTable {
+    /// Add an abbreviation to the table, and return its code.
+    ///
+    /// If an identical abbreviation was already added, then the existing code
+    /// is returned.
+    pub fn add(&mut self, abbrev: Abbreviation) -> u64 {
+        let index = match self.abbrevs.iter().position(|a| *a == abbrev) {
+            Some(index) => index,
+            None => {
+                self.abbrevs.push(abbrev);
+                self.abbrevs.len() - 1
+            }
+        };
+        // Code 0 is reserved for null entries.
+        index as u64 + 1
+    }
+
+    /// Write the abbreviation table, including the terminating null entry.
+    pub fn write<W: Writer>(&self, w: &mut W) -> Result<()> {
+        for (index, abbrev) in self.abbrevs.iter().enumerate() {
+            w.write_uleb128(index as u64 + 1)?;
+            abbrev.write(w)?;
+        }
+        // Null abbreviation code.
+        w.write_u8(0)
+    }
+}
+
+/// An abbreviation describes the shape of a `DebuggingInformationEntry`'s
+/// type: its tag, whether it has children, and its attribute names and forms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Abbreviation {
+    tag: constants::DwTag,
+    has_children: bool,
+    attributes: Vec<AttributeSpecification>,
+}
+
+impl Abbreviation {
+    /// Construct a new `Abbreviation`.
+    pub fn new(
+        tag: constants::DwTag,
+        has_children: bool,
+        attributes: Vec<AttributeSpecification>,
+    ) -> Abbreviation {
+        Abbreviation {
+            tag,
+            has_children,
+            attributes,
+        }
+    }
+
+    fn write<W: Writer>(&self, w: &mut W) -> Result<()> {
+        w.write_uleb128(self.tag.0.into())?;
+        w.write_u8(if self.has_children {
+            constants::DW_CHILDREN_yes.0
+        } else {
+            constants::DW_CHILDREN_no.0
+        })?;
+        for attr in &self.attributes {
+            w.write_uleb128(attr.name.0.into())?;
+            w.write_uleb128(attr.form.0.into())?;
+        }
+        // Null name and form.
+        w.write_u8(0)?;
+        w.write_u8(0)
+    }
+}
+
+/// The description of an attribute in an abbreviated type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AttributeSpecification {
+    name: constants::DwAt,
+    form: constants::DwForm,
+}
+
+impl AttributeSpecification {
+    /// Construct a new `AttributeSpecification`.
+    pub fn new(name: constants::DwAt, form: constants::DwForm) -> AttributeSpecification {
+        AttributeSpecification { name, form }
+    }
+
+    /// Get the attribute's form.
+    pub fn form(&self) -> constants::DwForm {
+        self.form
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abbrev::{self, DebugAbbrev, DebugAbbrevOffset};
+    use endianity::LittleEndian;
+    use write::EndianVec;
+
+    #[test]
+    fn test_abbreviation_table() {
+        let mut abbrevs = AbbreviationTable::default();
+        let abbrev1 = Abbreviation::new(
+            constants::DW_TAG_subprogram,
+            false,
+            vec![
+                AttributeSpecification::new(constants::DW_AT_name, constants::DW_FORM_string),
+            ],
+        );
+        let abbrev2 = Abbreviation::new(
+            constants::DW_TAG_compile_unit,
+            true,
+            vec![
+                AttributeSpecification::new(constants::DW_AT_producer, constants::DW_FORM_strp),
+                AttributeSpecification::new(constants::DW_AT_language, constants::DW_FORM_data2),
+            ],
+        );
+        let code1 = abbrevs.add(abbrev1.clone());
+        let code2 = abbrevs.add(abbrev2.clone());
+        assert_eq!(abbrevs.add(abbrev1), code1);
+        assert_eq!(abbrevs.add(abbrev2), code2);
+        assert_eq!(abbrevs.abbrevs.len(), 2);
+
+        let mut w = EndianVec::new(LittleEndian);
+        abbrevs.write(&mut w).unwrap();
+        let buf = w.into_vec();
+
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let read_abbrevs = debug_abbrev
+            .abbreviations(DebugAbbrevOffset(0))
+            .unwrap();
+        let read1 = read_abbrevs.get(code1).unwrap();
+        assert_eq!(read1.tag(), constants::DW_TAG_subprogram);
+        assert_eq!(read1.has_children(), false);
+        assert_eq!(
+            read1.attributes(),
+            &[
+                abbrev::AttributeSpecification::new(
+                    constants::DW_AT_name,
+                    constants::DW_FORM_string,
+                    None,
+                ),
+            ]
+        );
+        let read2 = read_abbrevs.get(code2).unwrap();
+        assert_eq!(read2.tag(), constants::DW_TAG_compile_unit);
+        assert_eq!(read2.has_children(), true);
+        assert_eq!(read2.attributes().len(), 2);
+    }
+}
@@ -0,0 +1,859 @@
+use arch::Register;
+use constants;
+use vec::Vec;
+use write::{Address, Error, Expression, Result, Writer};
+
+const CIE_ID_EH_FRAME: u32 = 0;
+const CIE_ID_DEBUG_FRAME: u32 = 0xffff_ffff;
+
+/// An identifier for a CIE in a `FrameTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CieId(usize);
+
+/// A table of frame description entries, which can be written as either a
+/// `.debug_frame` or an `.eh_frame` section.
+#[derive(Debug, Default)]
+pub struct FrameTable {
+    cies: Vec<CommonInformationEntry>,
+    fdes: Vec<(CieId, FrameDescriptionEntry)>,
+}
+
+impl FrameTable {
+    /// Create an empty frame table.
+    pub fn new() -> Self {
+        FrameTable::default()
+    }
+
+    /// Add a CIE and return its id.
+    ///
+    /// If the CIE is identical to a CIE that was previously added, then the id
+    /// of the existing CIE is returned.
+    pub fn add_cie(&mut self, cie: CommonInformationEntry) -> CieId {
+        if let Some(index) = self.cies.iter().position(|c| *c == cie) {
+            return CieId(index);
+        }
+        self.cies.push(cie);
+        CieId(self.cies.len() - 1)
+    }
+
+    /// Add an FDE that uses the given CIE.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cie` was not returned by `add_cie` for this table.
+    pub fn add_fde(&mut self, cie: CieId, fde: FrameDescriptionEntry) {
+        assert!(cie.0 < self.cies.len());
+        self.fdes.push((cie, fde));
+    }
+
+    /// Write the frame table entries to the given `.debug_frame` section.
+    ///
+    /// CIEs are written with version 4, and all addresses are absolute.
+    pub fn write_debug_frame<W: Writer>(&self, w: &mut W) -> Result<()> {
+        let mut cie_offsets = Vec::with_capacity(self.cies.len());
+        for cie in &self.cies {
+            cie_offsets.push(cie.write(w, None, 0)?);
+        }
+
+        for &(cie_id, ref fde) in &self.fdes {
+            let cie = &self.cies[cie_id.0];
+            fde.write(w, None, 0, cie_offsets[cie_id.0], cie)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the frame table entries to the given `.eh_frame` section, which
+    /// will be loaded at `section_address`.
+    ///
+    /// CIEs are written with version 1. Unless a CIE specifies its FDE address
+    /// encoding, then the encoding is selected automatically:
+    /// `DW_EH_PE_pcrel | DW_EH_PE_sdata4` is used if all of its FDE addresses
+    /// are constants within 1 GiB of the section address, and `DW_EH_PE_absptr`
+    /// otherwise.
+    ///
+    /// The section is terminated with a zero length entry.
+    ///
+    /// The returned offsets may be used to write an `.eh_frame_hdr` section.
+    pub fn write_eh_frame<W: Writer>(
+        &self,
+        w: &mut W,
+        section_address: u64,
+    ) -> Result<EhFrameOffsets> {
+        let base = w.len();
+        let mut cie_offsets = Vec::with_capacity(self.cies.len());
+        let mut encodings = Vec::with_capacity(self.cies.len());
+        for (index, cie) in self.cies.iter().enumerate() {
+            let encoding = match cie.fde_address_encoding {
+                Some(encoding) => encoding,
+                None => {
+                    let fits = self.fdes.iter().filter(|&&(id, _)| id.0 == index).all(
+                        |&(_, ref fde)| match fde.address {
+                            Address::Constant(address) => {
+                                let delta = address.wrapping_sub(section_address) as i64;
+                                delta >= -(1 << 30) && delta < (1 << 30)
+                            }
+                            Address::Symbol { .. } => false,
+                        },
+                    );
+                    if fits {
+                        constants::DwEhPe(
+                            constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sdata4.0,
+                        )
+                    } else {
+                        constants::DW_EH_PE_absptr
+                    }
+                }
+            };
+            cie_offsets.push(cie.write(w, Some(encoding), section_address)?);
+            encodings.push(encoding);
+        }
+
+        let mut fdes = Vec::with_capacity(self.fdes.len());
+        for &(cie_id, ref fde) in &self.fdes {
+            let cie = &self.cies[cie_id.0];
+            let offset = fde.write(
+                w,
+                Some(encodings[cie_id.0]),
+                section_address,
+                cie_offsets[cie_id.0],
+                cie,
+            )?;
+            fdes.push((fde.address, (offset - base) as u64));
+        }
+
+        w.write_u32(0)?;
+
+        Ok(EhFrameOffsets {
+            section_address,
+            fdes,
+        })
+    }
+}
+
+/// The locations of the FDEs that were written to an `.eh_frame` section.
+#[derive(Debug, Clone, Default)]
+pub struct EhFrameOffsets {
+    section_address: u64,
+    fdes: Vec<(Address, u64)>,
+}
+
+impl EhFrameOffsets {
+    /// Return the address of the `.eh_frame` section.
+    pub fn section_address(&self) -> u64 {
+        self.section_address
+    }
+
+    /// Return the `(initial address, section offset)` pairs of the FDEs.
+    pub fn fdes(&self) -> &[(Address, u64)] {
+        &self.fdes
+    }
+
+    /// Write an `.eh_frame_hdr` section, which will be loaded at
+    /// `section_address`, containing a binary search table for the FDEs.
+    ///
+    /// All pointers are written as 4 byte values relative to the
+    /// `.eh_frame_hdr` section. The table must be sorted by address, so all
+    /// FDE addresses must be constants.
+    pub fn write_eh_frame_hdr<W: Writer>(&self, w: &mut W, section_address: u64) -> Result<()> {
+        let base = w.len() as u64;
+        let relative = |address: u64, offset: u64| {
+            let delta = address.wrapping_sub(section_address.wrapping_add(offset)) as i64;
+            if delta < i64::from(i32::min_value()) || delta > i64::from(i32::max_value()) {
+                Err(Error::ValueTooLarge)
+            } else {
+                Ok(delta as i32 as u32)
+            }
+        };
+
+        w.write_u8(1)?;
+        w.write_u8(constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sdata4.0)?;
+        w.write_u8(constants::DW_EH_PE_udata4.0)?;
+        w.write_u8(constants::DW_EH_PE_datarel.0 | constants::DW_EH_PE_sdata4.0)?;
+        let offset = w.len() as u64 - base;
+        w.write_u32(relative(self.section_address, offset)?)?;
+        w.write_udata(self.fdes.len() as u64, 4)?;
+
+        let mut table = Vec::with_capacity(self.fdes.len());
+        for &(address, fde_offset) in &self.fdes {
+            match address {
+                Address::Constant(address) => table.push((address, fde_offset)),
+                Address::Symbol { .. } => return Err(Error::UnsupportedSymbolAddress),
+            }
+        }
+        table.sort_by_key(|&(address, _)| address);
+        for (address, fde_offset) in table {
+            w.write_u32(relative(address, 0)?)?;
+            w.write_u32(relative(self.section_address.wrapping_add(fde_offset), 0)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// A common information entry. This contains information that is shared
+/// between FDEs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonInformationEntry {
+    /// The size of a target address.
+    pub address_size: u8,
+
+    /// The constant that is factored out of all advance location instructions.
+    pub code_alignment_factor: u64,
+
+    /// The constant that is factored out of all register offsets.
+    pub data_alignment_factor: i64,
+
+    /// The column in the rule table that represents the return address of
+    /// the function.
+    pub return_address_register: Register,
+
+    /// The pointer encoding and address of the personality routine.
+    ///
+    /// Only used for `.eh_frame`.
+    pub personality: Option<(constants::DwEhPe, Address)>,
+
+    /// The pointer encoding of the LSDA address in FDEs.
+    ///
+    /// Only used for `.eh_frame`.
+    pub lsda_encoding: Option<constants::DwEhPe>,
+
+    /// The pointer encoding of the addresses in FDEs. If `None`, then the
+    /// encoding is selected automatically.
+    ///
+    /// Only used for `.eh_frame`.
+    pub fde_address_encoding: Option<constants::DwEhPe>,
+
+    /// True for signal trampolines.
+    ///
+    /// Only used for `.eh_frame`.
+    pub signal_trampoline: bool,
+
+    instructions: Vec<CallFrameInstruction>,
+}
+
+impl CommonInformationEntry {
+    /// Create a new CIE with no augmentations or initial instructions.
+    pub fn new(
+        address_size: u8,
+        code_alignment_factor: u64,
+        data_alignment_factor: i64,
+        return_address_register: Register,
+    ) -> Self {
+        CommonInformationEntry {
+            address_size,
+            code_alignment_factor,
+            data_alignment_factor,
+            return_address_register,
+            personality: None,
+            lsda_encoding: None,
+            fde_address_encoding: None,
+            signal_trampoline: false,
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Add an initial instruction.
+    pub fn add_instruction(&mut self, instruction: CallFrameInstruction) {
+        self.instructions.push(instruction);
+    }
+
+    /// The initial instructions.
+    pub fn instructions(&self) -> &[CallFrameInstruction] {
+        &self.instructions
+    }
+
+    /// Write the CIE, and return its offset.
+    ///
+    /// `fde_address_encoding` is `Some` for `.eh_frame`.
+    fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        fde_address_encoding: Option<constants::DwEhPe>,
+        section_address: u64,
+    ) -> Result<usize> {
+        let offset = w.len();
+        w.write_u32(0)?;
+        let length_base = w.len();
+
+        match fde_address_encoding {
+            Some(fde_address_encoding) => {
+                w.write_u32(CIE_ID_EH_FRAME)?;
+                w.write_u8(1)?;
+                // Always use the 'z' augmentation, since we always write the
+                // FDE address encoding.
+                let mut augmentation_data_length = 1;
+                w.write(b"z")?;
+                if let Some((encoding, _)) = self.personality {
+                    w.write(b"P")?;
+                    augmentation_data_length += 1 + pointer_size(encoding, self.address_size)?;
+                }
+                if self.lsda_encoding.is_some() {
+                    w.write(b"L")?;
+                    augmentation_data_length += 1;
+                }
+                w.write(b"R")?;
+                if self.signal_trampoline {
+                    w.write(b"S")?;
+                }
+                w.write_u8(0)?;
+
+                w.write_uleb128(self.code_alignment_factor)?;
+                w.write_sleb128(self.data_alignment_factor)?;
+                if self.return_address_register.0 > 0xff {
+                    return Err(Error::ValueTooLarge);
+                }
+                w.write_u8(self.return_address_register.0 as u8)?;
+
+                w.write_uleb128(augmentation_data_length)?;
+                if let Some((encoding, address)) = self.personality {
+                    w.write_u8(encoding.0)?;
+                    w.write_eh_pointer(address, encoding, self.address_size, section_address)?;
+                }
+                if let Some(encoding) = self.lsda_encoding {
+                    w.write_u8(encoding.0)?;
+                }
+                w.write_u8(fde_address_encoding.0)?;
+            }
+            None => {
+                w.write_u32(CIE_ID_DEBUG_FRAME)?;
+                w.write_u8(4)?;
+                // No augmentation.
+                w.write_u8(0)?;
+                w.write_u8(self.address_size)?;
+                // Segment size.
+                w.write_u8(0)?;
+                w.write_uleb128(self.code_alignment_factor)?;
+                w.write_sleb128(self.data_alignment_factor)?;
+                w.write_uleb128(self.return_address_register.0.into())?;
+            }
+        }
+
+        for instruction in &self.instructions {
+            instruction.write(w, self)?;
+        }
+
+        write_nop_padding(w, offset, self.address_size)?;
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(offset, length as u32)?;
+        Ok(offset)
+    }
+}
+
+/// A frame description entry. There is one FDE per function or code range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameDescriptionEntry {
+    /// The initial address of the code range.
+    pub address: Address,
+
+    /// The length of the code range.
+    pub length: u64,
+
+    /// The address of the LSDA.
+    ///
+    /// Only used for `.eh_frame`, and only if the CIE has an LSDA encoding.
+    pub lsda: Option<Address>,
+
+    instructions: Vec<(u64, CallFrameInstruction)>,
+}
+
+impl FrameDescriptionEntry {
+    /// Create a new FDE for the given code range, with no instructions.
+    pub fn new(address: Address, length: u64) -> Self {
+        FrameDescriptionEntry {
+            address,
+            length,
+            lsda: None,
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Add an instruction that applies from the given code offset, relative to
+    /// the start of the code range.
+    ///
+    /// Instructions must be added in order of increasing offset. The advance
+    /// location instructions are generated automatically when the FDE is
+    /// written.
+    pub fn add_instruction(&mut self, offset: u64, instruction: CallFrameInstruction) {
+        self.instructions.push((offset, instruction));
+    }
+
+    /// The instructions and their code offsets.
+    pub fn instructions(&self) -> &[(u64, CallFrameInstruction)] {
+        &self.instructions
+    }
+
+    /// Write the FDE, and return its offset.
+    ///
+    /// `fde_address_encoding` is `Some` for `.eh_frame`.
+    fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        fde_address_encoding: Option<constants::DwEhPe>,
+        section_address: u64,
+        cie_offset: usize,
+        cie: &CommonInformationEntry,
+    ) -> Result<usize> {
+        let offset = w.len();
+        w.write_u32(0)?;
+        let length_base = w.len();
+
+        match fde_address_encoding {
+            Some(encoding) => {
+                let cie_pointer = w.len() - cie_offset;
+                w.write_u32(cie_pointer as u32)?;
+                w.write_eh_pointer(self.address, encoding, cie.address_size, section_address)?;
+                w.write_eh_pointer_data(self.length, encoding.format(), cie.address_size)?;
+
+                if let Some(lsda_encoding) = cie.lsda_encoding {
+                    w.write_uleb128(pointer_size(lsda_encoding, cie.address_size)?)?;
+                    match self.lsda {
+                        Some(lsda) => {
+                            w.write_eh_pointer(lsda, lsda_encoding, cie.address_size, section_address)?
+                        }
+                        // A zero value means there is no LSDA.
+                        None => {
+                            w.write_eh_pointer_data(0, lsda_encoding.format(), cie.address_size)?
+                        }
+                    }
+                } else {
+                    w.write_uleb128(0)?;
+                }
+            }
+            None => {
+                w.write_u32(cie_offset as u32)?;
+                w.write_address(self.address, cie.address_size)?;
+                w.write_udata(self.length, cie.address_size)?;
+            }
+        }
+
+        let mut prev_offset = 0;
+        for &(code_offset, ref instruction) in &self.instructions {
+            let factor = cie.code_alignment_factor;
+            if code_offset < prev_offset || code_offset.checked_rem(factor) != Some(0) {
+                return Err(Error::InvalidFrameCodeOffset(code_offset));
+            }
+            let delta = (code_offset - prev_offset) / factor;
+            if delta != 0 {
+                write_advance_loc(w, delta)?;
+            }
+            prev_offset = code_offset;
+            instruction.write(w, cie)?;
+        }
+
+        write_nop_padding(w, offset, cie.address_size)?;
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(offset, length as u32)?;
+        Ok(offset)
+    }
+}
+
+/// A call frame instruction.
+///
+/// Offsets are in bytes, and are factored by the CIE's data alignment factor
+/// when written. The most compact instruction encoding is selected
+/// automatically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallFrameInstruction {
+    /// Define the CFA rule to use the provided register and offset.
+    Cfa(Register, i64),
+    /// Update the CFA rule to use the provided register.
+    CfaRegister(Register),
+    /// Update the CFA rule to use the provided offset.
+    CfaOffset(i64),
+    /// Define the CFA rule to use the provided expression.
+    CfaExpression(Expression),
+
+    /// Restore the initial rule for the register.
+    Restore(Register),
+    /// The previous value of the register is not recoverable.
+    Undefined(Register),
+    /// The register has not been modified.
+    SameValue(Register),
+    /// The previous value of the register is saved at address CFA + offset.
+    Offset(Register, i64),
+    /// The previous value of the register is CFA + offset.
+    ValOffset(Register, i64),
+    /// The previous value of the first register is stored in the second
+    /// register.
+    Register(Register, Register),
+    /// The previous value of the register is saved at the address given by
+    /// the expression.
+    Expression(Register, Expression),
+    /// The previous value of the register is given by the expression.
+    ValExpression(Register, Expression),
+
+    /// Push all register rules onto a stack.
+    RememberState,
+    /// Pop all register rules off the stack.
+    RestoreState,
+    /// The size of the arguments that have been pushed onto the stack.
+    ArgsSize(u64),
+}
+
+impl CallFrameInstruction {
+    fn write<W: Writer>(&self, w: &mut W, cie: &CommonInformationEntry) -> Result<()> {
+        match *self {
+            CallFrameInstruction::Cfa(register, offset) => {
+                if offset < 0 {
+                    let offset = factored_data_offset(offset, cie)?;
+                    w.write_u8(constants::DW_CFA_def_cfa_sf.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_sleb128(offset)?;
+                } else {
+                    // Unfactored offset.
+                    w.write_u8(constants::DW_CFA_def_cfa.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_uleb128(offset as u64)?;
+                }
+            }
+            CallFrameInstruction::CfaRegister(register) => {
+                w.write_u8(constants::DW_CFA_def_cfa_register.0)?;
+                w.write_uleb128(register.0.into())?;
+            }
+            CallFrameInstruction::CfaOffset(offset) => {
+                if offset < 0 {
+                    let offset = factored_data_offset(offset, cie)?;
+                    w.write_u8(constants::DW_CFA_def_cfa_offset_sf.0)?;
+                    w.write_sleb128(offset)?;
+                } else {
+                    // Unfactored offset.
+                    w.write_u8(constants::DW_CFA_def_cfa_offset.0)?;
+                    w.write_uleb128(offset as u64)?;
+                }
+            }
+            CallFrameInstruction::CfaExpression(ref expression) => {
+                w.write_u8(constants::DW_CFA_def_cfa_expression.0)?;
+                w.write_uleb128(expression.size(cie.address_size) as u64)?;
+                expression.write(w, cie.address_size)?;
+            }
+            CallFrameInstruction::Restore(register) => {
+                if register.0 < 0x40 {
+                    w.write_u8(constants::DW_CFA_restore.0 | register.0 as u8)?;
+                } else {
+                    w.write_u8(constants::DW_CFA_restore_extended.0)?;
+                    w.write_uleb128(register.0.into())?;
+                }
+            }
+            CallFrameInstruction::Undefined(register) => {
+                w.write_u8(constants::DW_CFA_undefined.0)?;
+                w.write_uleb128(register.0.into())?;
+            }
+            CallFrameInstruction::SameValue(register) => {
+                w.write_u8(constants::DW_CFA_same_value.0)?;
+                w.write_uleb128(register.0.into())?;
+            }
+            CallFrameInstruction::Offset(register, offset) => {
+                let offset = factored_data_offset(offset, cie)?;
+                if offset < 0 {
+                    w.write_u8(constants::DW_CFA_offset_extended_sf.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_sleb128(offset)?;
+                } else if register.0 < 0x40 {
+                    w.write_u8(constants::DW_CFA_offset.0 | register.0 as u8)?;
+                    w.write_uleb128(offset as u64)?;
+                } else {
+                    w.write_u8(constants::DW_CFA_offset_extended.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_uleb128(offset as u64)?;
+                }
+            }
+            CallFrameInstruction::ValOffset(register, offset) => {
+                let offset = factored_data_offset(offset, cie)?;
+                if offset < 0 {
+                    w.write_u8(constants::DW_CFA_val_offset_sf.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_sleb128(offset)?;
+                } else {
+                    w.write_u8(constants::DW_CFA_val_offset.0)?;
+                    w.write_uleb128(register.0.into())?;
+                    w.write_uleb128(offset as u64)?;
+                }
+            }
+            CallFrameInstruction::Register(register1, register2) => {
+                w.write_u8(constants::DW_CFA_register.0)?;
+                w.write_uleb128(register1.0.into())?;
+                w.write_uleb128(register2.0.into())?;
+            }
+            CallFrameInstruction::Expression(register, ref expression) => {
+                w.write_u8(constants::DW_CFA_expression.0)?;
+                w.write_uleb128(register.0.into())?;
+                w.write_uleb128(expression.size(cie.address_size) as u64)?;
+                expression.write(w, cie.address_size)?;
+            }
+            CallFrameInstruction::ValExpression(register, ref expression) => {
+                w.write_u8(constants::DW_CFA_val_expression.0)?;
+                w.write_uleb128(register.0.into())?;
+                w.write_uleb128(expression.size(cie.address_size) as u64)?;
+                expression.write(w, cie.address_size)?;
+            }
+            CallFrameInstruction::RememberState => {
+                w.write_u8(constants::DW_CFA_remember_state.0)?;
+            }
+            CallFrameInstruction::RestoreState => {
+                w.write_u8(constants::DW_CFA_restore_state.0)?;
+            }
+            CallFrameInstruction::ArgsSize(size) => {
+                w.write_u8(constants::DW_CFA_GNU_args_size.0)?;
+                w.write_uleb128(size)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn factored_data_offset(offset: i64, cie: &CommonInformationEntry) -> Result<i64> {
+    let factor = cie.data_alignment_factor;
+    if offset.checked_rem(factor) != Some(0) {
+        return Err(Error::InvalidFrameDataOffset(offset));
+    }
+    Ok(offset / factor)
+}
+
+fn write_advance_loc<W: Writer>(w: &mut W, delta: u64) -> Result<()> {
+    if delta < 0x40 {
+        w.write_u8(constants::DW_CFA_advance_loc.0 | delta as u8)
+    } else if delta <= 0xff {
+        w.write_u8(constants::DW_CFA_advance_loc1.0)?;
+        w.write_u8(delta as u8)
+    } else if delta <= 0xffff {
+        w.write_u8(constants::DW_CFA_advance_loc2.0)?;
+        w.write_u16(delta as u16)
+    } else if delta <= 0xffff_ffff {
+        w.write_u8(constants::DW_CFA_advance_loc4.0)?;
+        w.write_u32(delta as u32)
+    } else {
+        Err(Error::ValueTooLarge)
+    }
+}
+
+/// Pad the entry starting at `offset` with `DW_CFA_nop` so that its total
+/// length is a multiple of the address size.
+fn write_nop_padding<W: Writer>(w: &mut W, offset: usize, address_size: u8) -> Result<()> {
+    let align = address_size as usize;
+    if align == 0 {
+        return Ok(());
+    }
+    while (w.len() - offset) % align != 0 {
+        w.write_u8(constants::DW_CFA_nop.0)?;
+    }
+    Ok(())
+}
+
+/// The size of a pointer with the given encoding. Variable length formats
+/// are not supported.
+fn pointer_size(encoding: constants::DwEhPe, address_size: u8) -> Result<u64> {
+    match encoding.format() {
+        constants::DW_EH_PE_absptr => Ok(address_size.into()),
+        constants::DW_EH_PE_udata2 | constants::DW_EH_PE_sdata2 => Ok(2),
+        constants::DW_EH_PE_udata4 | constants::DW_EH_PE_sdata4 => Ok(4),
+        constants::DW_EH_PE_udata8 | constants::DW_EH_PE_sdata8 => Ok(8),
+        _ => Err(Error::UnsupportedPointerEncoding(encoding)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfi::{BaseAddresses, CfaRule, CfiEntry, CieCache, DebugFrame, EhFrame, EhFrameHdr,
+              RegisterRule, UninitializedUnwindContext, UnwindSection};
+    use endianity::{EndianBuf, LittleEndian};
+    use parser::Pointer;
+    use write::EndianVec;
+
+    fn make_table() -> FrameTable {
+        let mut table = FrameTable::new();
+
+        let mut cie = CommonInformationEntry::new(8, 1, -8, Register(16));
+        cie.add_instruction(CallFrameInstruction::Cfa(Register(7), 8));
+        cie.add_instruction(CallFrameInstruction::Offset(Register(16), -8));
+        let cie_id = table.add_cie(cie.clone());
+        assert_eq!(table.add_cie(cie), cie_id);
+
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x1000), 0x20);
+        fde.add_instruction(1, CallFrameInstruction::CfaOffset(16));
+        fde.add_instruction(1, CallFrameInstruction::Offset(Register(6), -16));
+        fde.add_instruction(4, CallFrameInstruction::CfaRegister(Register(6)));
+        fde.add_instruction(0x1f, CallFrameInstruction::Cfa(Register(7), 8));
+        table.add_fde(cie_id, fde);
+
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x2000), 0x10);
+        fde.add_instruction(0x100, CallFrameInstruction::CfaOffset(32));
+        table.add_fde(cie_id, fde);
+
+        table
+    }
+
+    fn check_rows<'a, Section>(section: &Section, bases: &BaseAddresses)
+    where
+        Section: UnwindSection<EndianBuf<'a, LittleEndian>>,
+    {
+        let ctx = UninitializedUnwindContext::new();
+        let (row, ctx) = section
+            .unwind_info_for_address(bases, ctx, 0x1000)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(7),
+                offset: 8,
+            }
+        );
+        assert_eq!(row.register(Register(16)), RegisterRule::Offset(-8));
+
+        let (row, ctx) = section
+            .unwind_info_for_address(bases, ctx, 0x1001)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(7),
+                offset: 16,
+            }
+        );
+        assert_eq!(row.register(Register(6)), RegisterRule::Offset(-16));
+
+        let (row, ctx) = section
+            .unwind_info_for_address(bases, ctx, 0x1010)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(6),
+                offset: 16,
+            }
+        );
+
+        let (row, _) = section
+            .unwind_info_for_address(bases, ctx, 0x2008)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(7),
+                offset: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_debug_frame() {
+        let table = make_table();
+        let mut w = EndianVec::new(LittleEndian);
+        table.write_debug_frame(&mut w).unwrap();
+        let buf = w.into_vec();
+
+        let debug_frame = DebugFrame::new(&buf, LittleEndian);
+        let bases = BaseAddresses::default();
+        check_rows(&debug_frame, &bases);
+    }
+
+    #[test]
+    fn test_write_eh_frame() {
+        let mut table = make_table();
+        let mut cie = CommonInformationEntry::new(8, 1, -8, Register(16));
+        cie.personality = Some((constants::DW_EH_PE_udata8, Address::Constant(0x5000)));
+        cie.lsda_encoding = Some(constants::DW_EH_PE_udata4);
+        let cie_id = table.add_cie(cie);
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x3000), 0x10);
+        fde.lsda = Some(Address::Constant(0x6000));
+        table.add_fde(cie_id, fde);
+
+        let eh_frame_address = 0x4000;
+        let mut w = EndianVec::new(LittleEndian);
+        let offsets = table.write_eh_frame(&mut w, eh_frame_address).unwrap();
+        assert_eq!(offsets.fdes().len(), 3);
+        let buf = w.into_vec();
+
+        let eh_frame = EhFrame::new(&buf, LittleEndian);
+        let bases = BaseAddresses::default().set_cfi(eh_frame_address);
+        check_rows(&eh_frame, &bases);
+
+        let mut cache = CieCache::new();
+        let mut entries = eh_frame.entries_with_cache(&bases, &mut cache);
+        let mut fdes = Vec::new();
+        while let Some(entry) = entries.next().unwrap() {
+            if let CfiEntry::Fde(fde) = entry {
+                fdes.push(fde);
+            }
+        }
+        assert_eq!(fdes.len(), 3);
+        assert_eq!(fdes[2].initial_address(), 0x3000);
+        assert_eq!(fdes[2].len(), 0x10);
+        assert_eq!(fdes[2].personality(), Some(Pointer::Direct(0x5000)));
+        assert_eq!(fdes[2].lsda(), Some(Pointer::Direct(0x6000)));
+
+        let eh_frame_hdr_address = 0x3800;
+        let mut w = EndianVec::new(LittleEndian);
+        offsets
+            .write_eh_frame_hdr(&mut w, eh_frame_hdr_address)
+            .unwrap();
+        let buf = w.into_vec();
+        let bases = BaseAddresses::default()
+            .set_cfi(eh_frame_hdr_address)
+            .set_data(eh_frame_hdr_address);
+        let hdr = EhFrameHdr::new(&buf, LittleEndian).parse(&bases, 8).unwrap();
+        assert_eq!(hdr.eh_frame_ptr(), Pointer::Direct(eh_frame_address));
+        let table = hdr.table().unwrap();
+        for &(address, fde_offset) in offsets.fdes() {
+            let address = match address {
+                Address::Constant(address) => address,
+                Address::Symbol { .. } => unreachable!(),
+            };
+            assert_eq!(
+                table.lookup(address + 1, &bases),
+                Ok(Pointer::Direct(eh_frame_address + fde_offset))
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_invalid_offsets() {
+        let mut table = FrameTable::new();
+        let cie_id = table.add_cie(CommonInformationEntry::new(8, 4, -8, Register(16)));
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x1000), 0x20);
+        fde.add_instruction(2, CallFrameInstruction::CfaOffset(16));
+        table.add_fde(cie_id, fde);
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write_debug_frame(&mut w),
+            Err(Error::InvalidFrameCodeOffset(2))
+        );
+
+        let mut table = FrameTable::new();
+        let cie_id = table.add_cie(CommonInformationEntry::new(8, 0, -8, Register(16)));
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x1000), 0x20);
+        fde.add_instruction(0, CallFrameInstruction::CfaOffset(16));
+        table.add_fde(cie_id, fde);
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write_debug_frame(&mut w),
+            Err(Error::InvalidFrameCodeOffset(0))
+        );
+
+        let mut table = FrameTable::new();
+        let mut cie = CommonInformationEntry::new(8, 1, -8, Register(16));
+        cie.add_instruction(CallFrameInstruction::Offset(Register(16), -4));
+        table.add_cie(cie);
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write_debug_frame(&mut w),
+            Err(Error::InvalidFrameDataOffset(-4))
+        );
+
+        let mut table = FrameTable::new();
+        let mut cie = CommonInformationEntry::new(8, 1, -1, Register(16));
+        cie.add_instruction(CallFrameInstruction::Offset(Register(16), i64::min_value()));
+        table.add_cie(cie);
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write_debug_frame(&mut w),
+            Err(Error::InvalidFrameDataOffset(i64::min_value()))
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use endianity::Endianity;
+use write::{Address, Error, Result, Writer};
+
+/// A `Writer` that discards the data, and only counts its length.
+///
+/// This can be used to compute the size of a section, or the offsets of
+/// items within it, without allocating a buffer for the data. Symbol
+/// addresses are supported, since no relocations need to be recorded.
+#[derive(Debug, Clone, Copy)]
+pub struct CountingWriter<Endian>
+where
+    Endian: Endianity,
+{
+    len: usize,
+    endian: Endian,
+}
+
+impl<Endian> CountingWriter<Endian>
+where
+    Endian: Endianity,
+{
+    /// Construct a `CountingWriter` with a length of zero and the given
+    /// endianity.
+    pub fn new(endian: Endian) -> CountingWriter<Endian> {
+        CountingWriter { len: 0, endian }
+    }
+}
+
+impl<Endian> Writer for CountingWriter<Endian>
+where
+    Endian: Endianity,
+{
+    type Endian = Endian;
+
+    #[inline]
+    fn endian(&self) -> Self::Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        if offset > self.len || bytes.len() > self.len - offset {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        Ok(())
+    }
+
+    fn write_address(&mut self, address: Address, size: u8) -> Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata(val, size),
+            Address::Symbol { .. } => self.write_udata(0, size),
+        }
+    }
+
+    fn write_address_at(&mut self, offset: usize, address: Address, size: u8) -> Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata_at(offset, val, size),
+            Address::Symbol { .. } => self.write_udata_at(offset, 0, size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+    use write::{EndianVec, LineEncoding, LineProgram, LineRow};
+
+    #[test]
+    fn test_counting_writer() {
+        let mut w = CountingWriter::new(LittleEndian);
+        w.write_u8(1).unwrap();
+        w.write_u32(2).unwrap();
+        w.write_uleb128(624_485).unwrap();
+        assert_eq!(w.len(), 8);
+        w.write_u32_at(4, 0).unwrap();
+        assert_eq!(w.write_u32_at(5, 0), Err(Error::OffsetOutOfBounds));
+        assert_eq!(w.write_udata(0x100, 1), Err(Error::ValueTooLarge));
+
+        let symbol = Address::Symbol {
+            symbol: 0,
+            addend: 0,
+        };
+        w.write_address(symbol, 8).unwrap();
+        assert_eq!(w.len(), 16);
+    }
+
+    #[test]
+    fn test_counting_writer_line_program() {
+        let mut program = LineProgram::new(
+            4,
+            8,
+            LineEncoding::default(),
+            b"/tmp".to_vec(),
+            b"foo.c".to_vec(),
+        ).unwrap();
+        let file = program.default_file();
+        program.add_row(LineRow::new(Address::Constant(0x1000), file, 1, 0));
+        program.add_row(LineRow::new(Address::Constant(0x1040), file, 30, 2));
+        program.end_sequence(Address::Constant(0x1100));
+
+        let mut counter = CountingWriter::new(LittleEndian);
+        program.write(&mut counter).unwrap();
+        let mut w = EndianVec::new(LittleEndian);
+        program.write(&mut w).unwrap();
+        assert_eq!(counter.len(), w.len());
+    }
+}
@@ -0,0 +1,423 @@
+use reader::Reader;
+use write::{Address, ConvertResult, DebugInfoOffsets, LineStringTable, Result, Sections,
+            StringTable, UnitTable, Writer};
+
+/// Writable DWARF information for more than one unit.
+#[derive(Debug, Default)]
+pub struct Dwarf {
+    /// A table of units. These are primarily stored in the `.debug_info`
+    /// section, but they also contain information that is stored in other
+    /// sections.
+    pub units: UnitTable,
+
+    /// A table of strings that will be stored in the `.debug_line_str` section.
+    pub line_strings: LineStringTable,
+
+    /// A table of strings that will be stored in the `.debug_str` section.
+    pub strings: StringTable,
+}
+
+impl Dwarf {
+    /// Create a new `Dwarf` instance.
+    pub fn new() -> Self {
+        Dwarf::default()
+    }
+
+    /// Create a `Dwarf` by converting a read `Dwarf`.
+    ///
+    /// `convert_address` is used to map each address in the read DWARF to
+    /// an address in the written DWARF. It may return `None` for addresses
+    /// that cannot be mapped, which causes the conversion to fail.
+    ///
+    /// See `UnitTable::from` for details of what is converted.
+    pub fn from<R: Reader>(
+        dwarf: &::Dwarf<R>,
+        convert_address: &dyn Fn(u64) -> Option<Address>,
+    ) -> ConvertResult<Dwarf> {
+        let mut strings = StringTable::new();
+        let units = UnitTable::from(dwarf, &mut strings, convert_address)?;
+        Ok(Dwarf {
+            units,
+            line_strings: LineStringTable::new(),
+            strings,
+        })
+    }
+
+    /// Write the DWARF information to the given sections.
+    ///
+    /// Returns the `.debug_info` offsets of the units and their entries.
+    pub fn write<W: Writer>(&self, sections: &mut Sections<W>) -> Result<DebugInfoOffsets> {
+        let line_strings = self.line_strings.write(&mut sections.debug_line_str)?;
+        let strings = self.strings.write(&mut sections.debug_str)?;
+        self.units.write(sections, &line_strings, &strings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use super::*;
+    use constants;
+    use endianity::{EndianBuf, LittleEndian};
+    use test_util::GimliSectionMethods;
+    use vec::Vec;
+    use write::{AttributeValue, ConvertError, EndianVec, Expression, LineEncoding, LineProgram,
+                LineRow, Range, Unit};
+    use SectionId;
+
+    #[test]
+    fn test_convert_dwarf() {
+        let mut line_program = LineProgram::new(
+            4,
+            8,
+            LineEncoding::default(),
+            b"/tmp".to_vec(),
+            b"foo.c".to_vec(),
+        ).unwrap();
+        let dir = line_program.add_directory(b"/inc".to_vec());
+        let file = line_program.add_file(b"bar.h".to_vec(), dir);
+        line_program.add_row(LineRow::new(Address::Constant(0x1000), line_program.default_file(), 1, 0));
+        line_program.add_row(LineRow::new(Address::Constant(0x1010), file, 2, 0));
+        line_program.end_sequence(Address::Constant(0x1020));
+        let mut debug_line = EndianVec::new(LittleEndian);
+        line_program.write(&mut debug_line).unwrap();
+        let debug_line = debug_line.into_vec();
+
+        let debug_str = b"foo.c\0";
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // DW_TAG_compile_unit, children.
+            0x01, 0x11, 0x01,
+            // DW_AT_name, DW_FORM_strp.
+            0x03, 0x0e,
+            // DW_AT_comp_dir, DW_FORM_string.
+            0x1b, 0x08,
+            // DW_AT_low_pc, DW_FORM_addr.
+            0x11, 0x01,
+            // DW_AT_stmt_list, DW_FORM_sec_offset.
+            0x10, 0x17,
+            0x00, 0x00,
+            // DW_TAG_subprogram, children.
+            0x02, 0x2e, 0x01,
+            // DW_AT_name, DW_FORM_string.
+            0x03, 0x08,
+            // DW_AT_ranges, DW_FORM_sec_offset.
+            0x55, 0x17,
+            // DW_AT_decl_file, DW_FORM_data1.
+            0x3a, 0x0b,
+            // DW_AT_sibling, DW_FORM_ref4.
+            0x01, 0x13,
+            0x00, 0x00,
+            // DW_TAG_variable, no children.
+            0x03, 0x34, 0x00,
+            // DW_AT_type, DW_FORM_ref4.
+            0x49, 0x13,
+            // DW_AT_location, DW_FORM_exprloc.
+            0x02, 0x18,
+            0x00, 0x00,
+            // DW_TAG_base_type, no children.
+            0x04, 0x24, 0x00,
+            // DW_AT_encoding, DW_FORM_data1.
+            0x3e, 0x0b,
+            // DW_AT_byte_size, DW_FORM_data2.
+            0x0b, 0x05,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_ranges = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let start = Label::new();
+        let end = Label::new();
+        let length = Label::new();
+        let sibling = Label::new();
+        let base_type = Label::new();
+        let sibling_ref = Label::new();
+        let base_type_ref = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            .mark(&start)
+            .L32(&length)
+            .L16(4)
+            .L32(0)
+            .L8(8)
+            // DW_TAG_compile_unit
+            .uleb(1)
+            .L32(0)
+            .append_bytes(b"/tmp\0")
+            .L64(0x1000)
+            .L32(0)
+            // DW_TAG_subprogram
+            .uleb(2)
+            .append_bytes(b"main\0")
+            .L32(0)
+            .L8(2)
+            .L32(&sibling_ref)
+            // DW_TAG_variable
+            .uleb(3)
+            .L32(&base_type_ref)
+            .uleb(9)
+            .L8(constants::DW_OP_addr.0)
+            .L64(0x2000)
+            .uleb(0)
+            // DW_TAG_base_type
+            .mark(&sibling)
+            .mark(&base_type)
+            .uleb(4)
+            .L8(constants::DW_ATE_signed.0)
+            .L16(4)
+            .uleb(0)
+            .mark(&end);
+        length.set_const((&end - &start - 4) as u64);
+        sibling_ref.set_const((&sibling - &start) as u64);
+        base_type_ref.set_const((&base_type - &start) as u64);
+        let debug_info = section.get_contents().unwrap();
+
+        let read_dwarf = ::Dwarf::load(|id| -> ::Result<_> {
+            let data = match id {
+                SectionId::DebugAbbrev => &debug_abbrev[..],
+                SectionId::DebugInfo => &debug_info[..],
+                SectionId::DebugLine => &debug_line[..],
+                SectionId::DebugRanges => &debug_ranges[..],
+                SectionId::DebugStr => &debug_str[..],
+                _ => &[],
+            };
+            Ok(EndianBuf::new(data, LittleEndian))
+        }).unwrap();
+
+        let convert_address = |address| Some(Address::Constant(address + 0x10000));
+        let dwarf = Dwarf::from(&read_dwarf, &convert_address).unwrap();
+        assert_eq!(dwarf.units.count(), 1);
+        let unit = dwarf.units.get(dwarf.units.id(0));
+        assert_eq!(unit.version(), 4);
+        assert_eq!(unit.address_size(), 8);
+        assert_eq!(unit.count(), 4);
+
+        let root = unit.get(unit.root());
+        assert_eq!(root.tag(), constants::DW_TAG_compile_unit);
+        let name = match root.get(constants::DW_AT_name) {
+            Some(&AttributeValue::StringRef(id)) => id,
+            otherwise => panic!("Unexpected name: {:?}", otherwise),
+        };
+        assert_eq!(dwarf.strings.get(name), b"foo.c");
+        assert_eq!(dwarf.strings.count(), 1);
+        assert_eq!(
+            root.get(constants::DW_AT_comp_dir),
+            Some(&AttributeValue::String(b"/tmp".to_vec()))
+        );
+        assert_eq!(
+            root.get(constants::DW_AT_low_pc),
+            Some(&AttributeValue::Address(Address::Constant(0x11000)))
+        );
+        assert_eq!(
+            root.get(constants::DW_AT_stmt_list),
+            Some(&AttributeValue::LineProgramRef)
+        );
+        let line_program = unit.line_program.as_ref().unwrap();
+        assert_eq!(line_program.encoding(), LineEncoding::default());
+
+        let children: Vec<_> = root.children().cloned().collect();
+        assert_eq!(children.len(), 2);
+        let subprogram = unit.get(children[0]);
+        assert_eq!(subprogram.tag(), constants::DW_TAG_subprogram);
+        assert_eq!(subprogram.get(constants::DW_AT_sibling), None);
+        match subprogram.get(constants::DW_AT_decl_file) {
+            Some(&AttributeValue::FileIndex(Some(_))) => {}
+            otherwise => panic!("Unexpected decl_file: {:?}", otherwise),
+        }
+        let ranges = match subprogram.get(constants::DW_AT_ranges) {
+            Some(&AttributeValue::RangeListRef(id)) => id,
+            otherwise => panic!("Unexpected ranges: {:?}", otherwise),
+        };
+        let mut expected_ranges = unit.ranges.clone();
+        assert_eq!(
+            expected_ranges.add(vec![
+                Range {
+                    begin: Address::Constant(0x11000),
+                    end: Address::Constant(0x11008),
+                },
+            ]),
+            ranges
+        );
+
+        let base_type = children[1];
+        let variable_id = *subprogram.children().next().unwrap();
+        let variable = unit.get(variable_id);
+        assert_eq!(variable.parent(), Some(children[0]));
+        assert_eq!(
+            variable.get(constants::DW_AT_type),
+            Some(&AttributeValue::UnitRef(base_type))
+        );
+        let mut expression = Expression::new();
+        expression.op_addr(Address::Constant(0x12000));
+        assert_eq!(
+            variable.get(constants::DW_AT_location),
+            Some(&AttributeValue::Exprloc(expression))
+        );
+
+        let base_type = unit.get(base_type);
+        assert_eq!(
+            base_type.get(constants::DW_AT_encoding),
+            Some(&AttributeValue::Encoding(constants::DW_ATE_signed))
+        );
+        assert_eq!(
+            base_type.get(constants::DW_AT_byte_size),
+            Some(&AttributeValue::Udata(4))
+        );
+
+        let no_address = |_| None;
+        assert_eq!(
+            Dwarf::from(&read_dwarf, &no_address).map(|_| ()),
+            Err(ConvertError::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn test_write_dwarf() {
+        for &version in &[2, 3, 4, 5] {
+            let mut dwarf = Dwarf::new();
+            let producer = dwarf.strings.add(&b"gimli"[..]);
+
+            let unit1 = dwarf.units.add(Unit::new(version, 8));
+            let unit2 = dwarf.units.add(Unit::new(version, 8));
+
+            let (base_type, var1, var2) = {
+                let unit = dwarf.units.get_mut(unit1);
+                let root = unit.root();
+                unit.get_mut(root)
+                    .set(constants::DW_AT_producer, AttributeValue::StringRef(producer));
+                unit.get_mut(root).set(
+                    constants::DW_AT_low_pc,
+                    AttributeValue::Address(Address::Constant(0x1000)),
+                );
+                unit.get_mut(root).set(
+                    constants::DW_AT_language,
+                    AttributeValue::Language(constants::DW_LANG_Rust),
+                );
+                let base_type = unit.add(root, constants::DW_TAG_base_type);
+                unit.get_mut(base_type)
+                    .set(constants::DW_AT_byte_size, AttributeValue::Udata(0x1234));
+                let var1 = unit.add(root, constants::DW_TAG_variable);
+                let var2 = unit.add(root, constants::DW_TAG_variable);
+                for &(var, name) in &[(var1, &b"x"[..]), (var2, &b"y"[..])] {
+                    let entry = unit.get_mut(var);
+                    entry.set(constants::DW_AT_name, AttributeValue::String(name.to_vec()));
+                    entry.set(constants::DW_AT_type, AttributeValue::UnitRef(base_type));
+                    entry.set(constants::DW_AT_external, AttributeValue::Flag(true));
+                }
+                (base_type, var1, var2)
+            };
+
+            {
+                let unit = dwarf.units.get_mut(unit2);
+                let root = unit.root();
+                let var = unit.add(root, constants::DW_TAG_variable);
+                unit.get_mut(var).set(
+                    constants::DW_AT_type,
+                    AttributeValue::DebugInfoRef(unit1, base_type),
+                );
+                let ranges = unit.ranges.add(vec![
+                    Range {
+                        begin: Address::Constant(0x2000),
+                        end: Address::Constant(0x2010),
+                    },
+                ]);
+                unit.get_mut(root)
+                    .set(constants::DW_AT_ranges, AttributeValue::RangeListRef(ranges));
+            }
+
+            let mut sections = Sections::new(EndianVec::new(LittleEndian));
+            let offsets = dwarf.write(&mut sections).unwrap();
+            assert_eq!(offsets.unit(unit1).0, 0);
+            assert_eq!(
+                offsets.entry(unit1, var2).0 - offsets.entry(unit1, var1).0,
+                // Abbreviation code, "x\0", DW_FORM_ref4, no flag data.
+                if version >= 4 { 7 } else { 8 }
+            );
+
+            let debug_abbrev = sections.debug_abbrev.into_vec();
+            let debug_info = sections.debug_info.into_vec();
+            let debug_str = sections.debug_str.into_vec();
+            let debug_ranges = sections.debug_ranges.into_vec();
+            let debug_rnglists = sections.debug_rnglists.into_vec();
+            let read_dwarf = ::Dwarf::load(|id| -> ::Result<_> {
+                let data = match id {
+                    SectionId::DebugAbbrev => &debug_abbrev[..],
+                    SectionId::DebugInfo => &debug_info[..],
+                    SectionId::DebugStr => &debug_str[..],
+                    SectionId::DebugRanges => &debug_ranges[..],
+                    SectionId::DebugRngLists => &debug_rnglists[..],
+                    _ => &[],
+                };
+                Ok(EndianBuf::new(data, LittleEndian))
+            }).unwrap();
+
+            // Converting the written DWARF gives the same entries.
+            let convert_address = |address| Some(Address::Constant(address));
+            let converted = Dwarf::from(&read_dwarf, &convert_address).unwrap();
+            assert_eq!(converted.units.count(), 2);
+
+            let unit = converted.units.get(converted.units.id(0));
+            assert_eq!(unit.version(), version);
+            assert_eq!(unit.count(), 4);
+            let root = unit.get(unit.root());
+            match root.get(constants::DW_AT_producer) {
+                Some(&AttributeValue::StringRef(id)) => {
+                    assert_eq!(converted.strings.get(id), b"gimli")
+                }
+                otherwise => panic!("Unexpected producer: {:?}", otherwise),
+            }
+            assert_eq!(
+                root.get(constants::DW_AT_language),
+                Some(&AttributeValue::Language(constants::DW_LANG_Rust))
+            );
+            let children: Vec<_> = root.children().cloned().collect();
+            assert_eq!(
+                unit.get(children[0]).get(constants::DW_AT_byte_size),
+                Some(&AttributeValue::Udata(0x1234))
+            );
+            let var = unit.get(children[2]);
+            assert_eq!(
+                var.get(constants::DW_AT_name),
+                Some(&AttributeValue::String(b"y".to_vec()))
+            );
+            assert_eq!(
+                var.get(constants::DW_AT_type),
+                Some(&AttributeValue::UnitRef(children[0]))
+            );
+            assert_eq!(
+                var.get(constants::DW_AT_external),
+                Some(&AttributeValue::Flag(true))
+            );
+
+            let unit = converted.units.get(converted.units.id(1));
+            let root = unit.get(unit.root());
+            match root.get(constants::DW_AT_ranges) {
+                Some(&AttributeValue::RangeListRef(_)) => {}
+                otherwise => panic!("Unexpected ranges: {:?}", otherwise),
+            }
+            let var = unit.get(*root.children().next().unwrap());
+            match var.get(constants::DW_AT_type) {
+                Some(&AttributeValue::DebugInfoRef(unit_id, _)) => {
+                    assert_eq!(unit_id, converted.units.id(0))
+                }
+                otherwise => panic!("Unexpected type: {:?}", otherwise),
+            }
+
+            // The two variables share an abbreviation.
+            let mut headers = read_dwarf.units();
+            let header = headers.next().unwrap().unwrap();
+            let abbrevs = read_dwarf.abbreviations(&header).unwrap();
+            assert!(abbrevs.get(6).is_none());
+            assert!(abbrevs.get(5).is_some());
+        }
+    }
+}
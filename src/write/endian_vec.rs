@@ -0,0 +1,96 @@
+use endianity::Endianity;
+use vec::Vec;
+use write::{Error, Result, Writer};
+
+/// A `Vec<u8>` with endianity metadata.
+///
+/// This implements the `Writer` trait, which is used for all writing of DWARF
+/// sections.
+#[derive(Debug, Clone)]
+pub struct EndianVec<Endian>
+where
+    Endian: Endianity,
+{
+    vec: Vec<u8>,
+    endian: Endian,
+}
+
+impl<Endian> EndianVec<Endian>
+where
+    Endian: Endianity,
+{
+    /// Construct an empty `EndianVec` with the given endianity.
+    pub fn new(endian: Endian) -> EndianVec<Endian> {
+        EndianVec {
+            vec: Vec::new(),
+            endian,
+        }
+    }
+
+    /// Return a reference to the raw slice.
+    pub fn slice(&self) -> &[u8] {
+        &self.vec
+    }
+
+    /// Convert into a `Vec<u8>`.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.vec
+    }
+}
+
+impl<Endian> Writer for EndianVec<Endian>
+where
+    Endian: Endianity,
+{
+    type Endian = Endian;
+
+    #[inline]
+    fn endian(&self) -> Self::Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.vec.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        if offset > self.vec.len() {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let to = &mut self.vec[offset..];
+        if bytes.len() > to.len() {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        to[..bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{BigEndian, LittleEndian};
+
+    #[test]
+    fn test_endian_vec() {
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_u8(1).unwrap();
+        w.write_u16(0x0302).unwrap();
+        w.write_u32(0x0706_0504).unwrap();
+        w.write_u32_at(3, 0x0b0a_0908).unwrap();
+        assert_eq!(w.slice(), &[1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(w.write_u32_at(4, 0), Err(Error::OffsetOutOfBounds));
+
+        let mut w = EndianVec::new(BigEndian);
+        w.write_u16(0x0102).unwrap();
+        w.write_uleb128(624_485).unwrap();
+        w.write_sleb128(-123_456).unwrap();
+        assert_eq!(w.into_vec(), vec![1, 2, 0xe5, 0x8e, 0x26, 0xc0, 0xbb, 0x78]);
+    }
+}
@@ -0,0 +1,901 @@
+use constants;
+use line::ColumnType;
+use reader::Reader;
+use vec::Vec;
+use write::{Address, ConvertError, ConvertResult, Error, Result, Writer};
+use write::writer::uleb128;
+
+/// The parameters that control the encoding of a line number program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineEncoding {
+    /// The size in bytes of the smallest target machine instruction.
+    pub minimum_instruction_length: u8,
+
+    /// The initial value of the `is_stmt` register.
+    pub default_is_stmt: bool,
+
+    /// The minimum value which a special opcode can add to the line register.
+    pub line_base: i8,
+
+    /// The range of values which a special opcode can add to the line
+    /// register.
+    pub line_range: u8,
+
+    /// The number assigned to the first special opcode.
+    ///
+    /// This must be at least 10. Standard opcodes that are not available for
+    /// the given opcode base are not used, so the `prologue_end`,
+    /// `epilogue_begin` and `isa` row values may be ignored.
+    pub opcode_base: u8,
+}
+
+impl Default for LineEncoding {
+    fn default() -> Self {
+        // Values from LLVM.
+        LineEncoding {
+            minimum_instruction_length: 1,
+            default_is_stmt: true,
+            line_base: -5,
+            line_range: 14,
+            opcode_base: 13,
+        }
+    }
+}
+
+/// An identifier for a directory in a `LineProgram`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirectoryId(usize);
+
+/// An identifier for a file in a `LineProgram`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(usize);
+
+/// A row in the line number matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRow {
+    /// The address of the machine instruction.
+    pub address: Address,
+
+    /// The source file.
+    pub file: FileId,
+
+    /// The source line number. Lines are numbered beginning at 1, and 0 means
+    /// that the instruction cannot be attributed to any source line.
+    pub line: u64,
+
+    /// The column number within the source line. Columns are numbered
+    /// beginning at 1, and 0 means the left edge of the line.
+    pub column: u64,
+
+    /// The discriminator of the block to which the instruction belongs.
+    pub discriminator: u64,
+
+    /// Whether the instruction is a recommended breakpoint location.
+    pub is_stmt: bool,
+
+    /// Whether the instruction is the beginning of a basic block.
+    pub basic_block: bool,
+
+    /// Whether the instruction is where execution should be suspended for a
+    /// breakpoint at the entry of the function.
+    pub prologue_end: bool,
+
+    /// Whether the instruction is where execution should be suspended for a
+    /// breakpoint just prior to the exit of the function.
+    pub epilogue_begin: bool,
+
+    /// The instruction set architecture of the instruction.
+    pub isa: u64,
+}
+
+impl LineRow {
+    /// Create a new row for a statement at the given location.
+    pub fn new(address: Address, file: FileId, line: u64, column: u64) -> Self {
+        LineRow {
+            address,
+            file,
+            line,
+            column,
+            discriminator: 0,
+            is_stmt: true,
+            basic_block: false,
+            prologue_end: false,
+            epilogue_begin: false,
+            isa: 0,
+        }
+    }
+}
+
+/// The state machine registers that affect which instructions are needed.
+#[derive(Debug, Clone, Copy)]
+struct Registers {
+    address: Address,
+    file: u64,
+    line: u64,
+    column: u64,
+    is_stmt: bool,
+    isa: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineInstruction {
+    Special(u8),
+    AdvancePc(u64),
+    AdvanceLine(i64),
+    SetFile(u64),
+    SetColumn(u64),
+    NegateStatement,
+    SetBasicBlock,
+    ConstAddPc,
+    SetPrologueEnd,
+    SetEpilogueBegin,
+    SetIsa(u64),
+    EndSequence,
+    SetAddress(Address),
+    SetDiscriminator(u64),
+}
+
+/// A line number program builder.
+///
+/// Rows are added in order, and are encoded using special opcodes where
+/// possible. The program is written using the 32-bit DWARF format.
+#[derive(Debug, Clone)]
+pub struct LineProgram {
+    version: u16,
+    address_size: u8,
+    encoding: LineEncoding,
+    directories: Vec<Vec<u8>>,
+    files: Vec<(Vec<u8>, DirectoryId)>,
+    instructions: Vec<LineInstruction>,
+    registers: Option<Registers>,
+}
+
+impl LineProgram {
+    /// Create a new line number program for the given DWARF version, which
+    /// must be between 2 and 5.
+    ///
+    /// `comp_dir` and `comp_file` are the compilation directory and primary
+    /// source file of the unit, and are available as the default directory
+    /// and file. Before version 5, an empty `comp_file` is not written to the
+    /// file names, so file indices start at the first added file.
+    pub fn new(
+        version: u16,
+        address_size: u8,
+        encoding: LineEncoding,
+        comp_dir: Vec<u8>,
+        comp_file: Vec<u8>,
+    ) -> Result<Self> {
+        if version < 2 || version > 5 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        if encoding.minimum_instruction_length == 0 || encoding.line_range == 0 ||
+            encoding.opcode_base < 10
+        {
+            return Err(Error::InvalidLineEncoding);
+        }
+        let line_base = i64::from(encoding.line_base);
+        if line_base > 0 || line_base + i64::from(encoding.line_range) <= 0 ||
+            i64::from(encoding.opcode_base) - line_base > 255
+        {
+            // A line advance of 0 must be encodable in a special opcode.
+            return Err(Error::InvalidLineEncoding);
+        }
+        Ok(LineProgram {
+            version,
+            address_size,
+            encoding,
+            directories: vec![comp_dir],
+            files: vec![(comp_file, DirectoryId(0))],
+            instructions: Vec::new(),
+            registers: None,
+        })
+    }
+
+    /// Return the encoding parameters of this program.
+    pub fn encoding(&self) -> LineEncoding {
+        self.encoding
+    }
+
+    /// Return the id of the compilation directory.
+    pub fn default_directory(&self) -> DirectoryId {
+        DirectoryId(0)
+    }
+
+    /// Return the id of the primary source file.
+    pub fn default_file(&self) -> FileId {
+        FileId(0)
+    }
+
+    /// Add a directory, and return its id.
+    ///
+    /// If the directory was already added, then the existing id is returned.
+    pub fn add_directory(&mut self, directory: Vec<u8>) -> DirectoryId {
+        if let Some(index) = self.directories.iter().position(|d| *d == directory) {
+            return DirectoryId(index);
+        }
+        self.directories.push(directory);
+        DirectoryId(self.directories.len() - 1)
+    }
+
+    /// Add a file within the given directory, and return its id.
+    ///
+    /// If the file was already added, then the existing id is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `directory` was not returned by this program.
+    pub fn add_file(&mut self, name: Vec<u8>, directory: DirectoryId) -> FileId {
+        assert!(directory.0 < self.directories.len());
+        if let Some(index) = self.files
+            .iter()
+            .position(|&(ref n, d)| *n == name && d == directory)
+        {
+            return FileId(index);
+        }
+        self.files.push((name, directory));
+        FileId(self.files.len() - 1)
+    }
+
+    /// Whether the default file is written as the first file name entry of
+    /// a version 2 to 4 header.
+    ///
+    /// An empty name would terminate the file names, so it is omitted.
+    fn writes_default_file(&self) -> bool {
+        self.version < 5 && !self.files[0].0.is_empty()
+    }
+
+    /// The value of the file register for the given file.
+    pub(crate) fn file_index(&self, file: FileId) -> u64 {
+        if self.writes_default_file() {
+            file.0 as u64 + 1
+        } else {
+            file.0 as u64
+        }
+    }
+
+    /// Add a row to the current sequence, starting a new sequence if needed.
+    ///
+    /// Rows within a sequence must have increasing addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row.file` was not returned by this program.
+    pub fn add_row(&mut self, row: LineRow) {
+        assert!(row.file.0 < self.files.len());
+        let mut registers = match self.registers {
+            Some(registers) => registers,
+            None => {
+                self.instructions
+                    .push(LineInstruction::SetAddress(row.address));
+                Registers {
+                    address: row.address,
+                    file: 1,
+                    line: 1,
+                    column: 0,
+                    is_stmt: self.encoding.default_is_stmt,
+                    isa: 0,
+                }
+            }
+        };
+
+        let file = self.file_index(row.file);
+        if file != registers.file {
+            self.instructions.push(LineInstruction::SetFile(file));
+            registers.file = file;
+        }
+        if row.column != registers.column {
+            self.instructions.push(LineInstruction::SetColumn(row.column));
+            registers.column = row.column;
+        }
+        if row.is_stmt != registers.is_stmt {
+            self.instructions.push(LineInstruction::NegateStatement);
+            registers.is_stmt = row.is_stmt;
+        }
+        if row.basic_block {
+            self.instructions.push(LineInstruction::SetBasicBlock);
+        }
+        if row.prologue_end && self.encoding.opcode_base > constants::DW_LNS_set_prologue_end.0 {
+            self.instructions.push(LineInstruction::SetPrologueEnd);
+        }
+        if row.epilogue_begin &&
+            self.encoding.opcode_base > constants::DW_LNS_set_epilogue_begin.0
+        {
+            self.instructions.push(LineInstruction::SetEpilogueBegin);
+        }
+        if row.isa != registers.isa && self.encoding.opcode_base > constants::DW_LNS_set_isa.0 {
+            self.instructions.push(LineInstruction::SetIsa(row.isa));
+            registers.isa = row.isa;
+        }
+        if row.discriminator != 0 {
+            self.instructions
+                .push(LineInstruction::SetDiscriminator(row.discriminator));
+        }
+
+        let operation_advance = self.operation_advance(&mut registers, row.address);
+        let line_advance = row.line.wrapping_sub(registers.line) as i64;
+        registers.line = row.line;
+        self.push_row(operation_advance, line_advance);
+        self.registers = Some(registers);
+    }
+
+    /// End the current sequence. `address` is the address of the first byte
+    /// after the end of the sequence.
+    ///
+    /// If there is no current sequence, then an empty sequence is added.
+    pub fn end_sequence(&mut self, address: Address) {
+        let mut registers = match self.registers.take() {
+            Some(registers) => registers,
+            None => {
+                self.instructions.push(LineInstruction::SetAddress(address));
+                self.instructions.push(LineInstruction::EndSequence);
+                return;
+            }
+        };
+        let operation_advance = self.operation_advance(&mut registers, address);
+        if operation_advance != 0 {
+            self.instructions
+                .push(LineInstruction::AdvancePc(operation_advance));
+        }
+        self.instructions.push(LineInstruction::EndSequence);
+    }
+
+    /// Return the operation advance needed to reach `address`, or set the
+    /// address directly if it cannot be reached with an advance.
+    ///
+    /// An advance is only possible if both addresses are constants, or are
+    /// relative to the same symbol.
+    fn operation_advance(&mut self, registers: &mut Registers, address: Address) -> u64 {
+        let minimum_instruction_length = u64::from(self.encoding.minimum_instruction_length);
+        let advance = match address.offset_from(registers.address) {
+            Some(delta) if delta % minimum_instruction_length == 0 => {
+                Some(delta / minimum_instruction_length)
+            }
+            _ => None,
+        };
+        registers.address = address;
+        match advance {
+            Some(advance) => advance,
+            None => {
+                self.instructions.push(LineInstruction::SetAddress(address));
+                0
+            }
+        }
+    }
+
+    /// Push the instructions to advance the address and line and then append
+    /// a row, using a special opcode if possible.
+    fn push_row(&mut self, operation_advance: u64, line_advance: i64) {
+        let line_base = i64::from(self.encoding.line_base);
+        let line_range = u64::from(self.encoding.line_range);
+        let opcode_base = u64::from(self.encoding.opcode_base);
+
+        // The line advance must be in range for the special opcode, and the
+        // resulting opcode must fit in a byte.
+        let adjusted_line = match line_advance.checked_sub(line_base) {
+            Some(adjusted_line)
+                if adjusted_line >= 0 && (adjusted_line as u64) < line_range &&
+                    adjusted_line as u64 <= 255 - opcode_base =>
+            {
+                adjusted_line as u64
+            }
+            _ => {
+                self.instructions
+                    .push(LineInstruction::AdvanceLine(line_advance));
+                // This is always in range, because `new` checked that 0 is in range.
+                (-line_base) as u64
+            }
+        };
+
+        // The largest operation advance that can be encoded in a special
+        // opcode for this line advance.
+        let max_operation_advance = (255 - opcode_base - adjusted_line) / line_range;
+        let special = |operation_advance: u64| {
+            let opcode = opcode_base + adjusted_line + line_range * operation_advance;
+            LineInstruction::Special(opcode as u8)
+        };
+
+        if operation_advance <= max_operation_advance {
+            self.instructions.push(special(operation_advance));
+            return;
+        }
+
+        let const_add_pc_advance = (255 - opcode_base) / line_range;
+        if operation_advance >= const_add_pc_advance &&
+            operation_advance - const_add_pc_advance <= max_operation_advance
+        {
+            self.instructions.push(LineInstruction::ConstAddPc);
+            self.instructions
+                .push(special(operation_advance - const_add_pc_advance));
+            return;
+        }
+
+        self.instructions
+            .push(LineInstruction::AdvancePc(operation_advance));
+        self.instructions.push(special(0));
+    }
+
+    /// Create a line number program by converting a read line number
+    /// program.
+    ///
+    /// `convert_address` is used to map the address of each row. If the
+    /// encoding parameters of the read program are not supported, then the
+    /// default encoding is used instead.
+    ///
+    /// Returns the program, and the converted file for each file index of
+    /// the read program.
+    pub fn from<R: Reader>(
+        from_program: ::IncompleteLineNumberProgram<R>,
+        address_size: u8,
+        convert_address: &dyn Fn(u64) -> Option<Address>,
+    ) -> ConvertResult<(LineProgram, Vec<FileId>)> {
+        let mut program;
+        let mut files = Vec::new();
+        {
+            let from_header = from_program.header();
+            let to_vec = |r: Option<R>| -> ConvertResult<Vec<u8>> {
+                match r {
+                    Some(r) => Ok(r.to_slice()?.to_vec()),
+                    None => Ok(Vec::new()),
+                }
+            };
+            let comp_dir = to_vec(from_header.directory(0))?;
+            let comp_file = to_vec(from_header.file(0).map(|file| file.path_name()))?;
+
+            let encoding = LineEncoding {
+                minimum_instruction_length: from_header.minimum_instruction_length(),
+                default_is_stmt: from_header.default_is_stmt(),
+                line_base: from_header.line_base(),
+                line_range: from_header.line_range(),
+                opcode_base: from_header.opcode_base(),
+            };
+            let version = from_header.version();
+            program = match LineProgram::new(
+                version,
+                address_size,
+                encoding,
+                comp_dir.clone(),
+                comp_file.clone(),
+            ) {
+                Ok(program) => program,
+                Err(_) => LineProgram::new(
+                    version,
+                    address_size,
+                    LineEncoding::default(),
+                    comp_dir,
+                    comp_file,
+                ).map_err(|_| ::Error::UnknownVersion(version.into()))?,
+            };
+
+            files.push(program.default_file());
+            // In version 5, the primary source file is also the first file
+            // name entry.
+            let skip = if from_header.version() >= 5 { 1 } else { 0 };
+            for from_file in from_header.file_names().iter().skip(skip) {
+                let directory = match from_file.directory_index().0 {
+                    0 => program.default_directory(),
+                    index => {
+                        let directory = from_header
+                            .directory(index)
+                            .ok_or(ConvertError::InvalidFileIndex)?;
+                        program.add_directory(directory.to_slice()?.to_vec())
+                    }
+                };
+                let name = from_file.path_name().to_slice()?.to_vec();
+                files.push(program.add_file(name, directory));
+            }
+        }
+
+        let mut rows = from_program.rows();
+        while let Some((_, row)) = rows.next_row()? {
+            let address = convert_address(row.address()).ok_or(ConvertError::InvalidAddress)?;
+            if row.end_sequence() {
+                program.end_sequence(address);
+                continue;
+            }
+            let file = *files
+                .get(row.file_index().0 as usize)
+                .ok_or(ConvertError::InvalidFileIndex)?;
+            program.add_row(LineRow {
+                address,
+                file,
+                line: row.line().unwrap_or(0),
+                column: match row.column() {
+                    ColumnType::LeftEdge => 0,
+                    ColumnType::Column(column) => column,
+                },
+                discriminator: row.discriminator(),
+                is_stmt: row.is_stmt(),
+                basic_block: row.basic_block(),
+                prologue_end: row.prologue_end(),
+                epilogue_begin: row.epilogue_begin(),
+                isa: row.isa(),
+            });
+        }
+        Ok((program, files))
+    }
+
+    /// Write the line number program.
+    ///
+    /// Returns an error if a sequence has not been ended.
+    pub fn write<W: Writer>(&self, w: &mut W) -> Result<()> {
+        if self.registers.is_some() {
+            return Err(Error::LineSequenceNotEnded);
+        }
+
+        let length_offset = w.len();
+        w.write_u32(0)?;
+        let length_base = w.len();
+
+        w.write_u16(self.version)?;
+        if self.version >= 5 {
+            w.write_u8(self.address_size)?;
+            // Segment selector size.
+            w.write_u8(0)?;
+        }
+
+        let header_length_offset = w.len();
+        w.write_u32(0)?;
+        let header_length_base = w.len();
+
+        w.write_u8(self.encoding.minimum_instruction_length)?;
+        if self.version >= 4 {
+            // Maximum operations per instruction.
+            w.write_u8(1)?;
+        }
+        w.write_u8(if self.encoding.default_is_stmt { 1 } else { 0 })?;
+        w.write_u8(self.encoding.line_base as u8)?;
+        w.write_u8(self.encoding.line_range)?;
+        w.write_u8(self.encoding.opcode_base)?;
+        for opcode in 1..self.encoding.opcode_base {
+            let length = match constants::DwLns(opcode) {
+                constants::DW_LNS_advance_pc |
+                constants::DW_LNS_advance_line |
+                constants::DW_LNS_set_file |
+                constants::DW_LNS_set_column |
+                constants::DW_LNS_fixed_advance_pc |
+                constants::DW_LNS_set_isa => 1,
+                _ => 0,
+            };
+            w.write_u8(length)?;
+        }
+
+        if self.version >= 5 {
+            w.write_u8(1)?;
+            w.write_uleb128(constants::DW_LNCT_path.0.into())?;
+            w.write_uleb128(constants::DW_FORM_string.0.into())?;
+            w.write_uleb128(self.directories.len() as u64)?;
+            for directory in &self.directories {
+                write_string(w, directory)?;
+            }
+
+            w.write_u8(2)?;
+            w.write_uleb128(constants::DW_LNCT_path.0.into())?;
+            w.write_uleb128(constants::DW_FORM_string.0.into())?;
+            w.write_uleb128(constants::DW_LNCT_directory_index.0.into())?;
+            w.write_uleb128(constants::DW_FORM_udata.0.into())?;
+            w.write_uleb128(self.files.len() as u64)?;
+            for &(ref name, directory) in &self.files {
+                write_string(w, name)?;
+                w.write_uleb128(directory.0 as u64)?;
+            }
+        } else {
+            // The compilation directory is implicitly directory 0.
+            for directory in &self.directories[1..] {
+                write_string(w, directory)?;
+            }
+            w.write_u8(0)?;
+
+            let skip = if self.writes_default_file() { 0 } else { 1 };
+            for &(ref name, directory) in self.files.iter().skip(skip) {
+                write_string(w, name)?;
+                w.write_uleb128(directory.0 as u64)?;
+                // Last modification time and length are unknown.
+                w.write_uleb128(0)?;
+                w.write_uleb128(0)?;
+            }
+            w.write_u8(0)?;
+        }
+
+        let header_length = (w.len() - header_length_base) as u64;
+        w.write_u32_at(header_length_offset, header_length as u32)?;
+
+        for instruction in &self.instructions {
+            self.write_instruction(w, *instruction)?;
+        }
+
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(length_offset, length as u32)?;
+        Ok(())
+    }
+
+    fn write_instruction<W: Writer>(&self, w: &mut W, instruction: LineInstruction) -> Result<()> {
+        match instruction {
+            LineInstruction::Special(opcode) => w.write_u8(opcode),
+            LineInstruction::AdvancePc(advance) => {
+                w.write_u8(constants::DW_LNS_advance_pc.0)?;
+                w.write_uleb128(advance)
+            }
+            LineInstruction::AdvanceLine(increment) => {
+                w.write_u8(constants::DW_LNS_advance_line.0)?;
+                w.write_sleb128(increment)
+            }
+            LineInstruction::SetFile(file) => {
+                w.write_u8(constants::DW_LNS_set_file.0)?;
+                w.write_uleb128(file)
+            }
+            LineInstruction::SetColumn(column) => {
+                w.write_u8(constants::DW_LNS_set_column.0)?;
+                w.write_uleb128(column)
+            }
+            LineInstruction::NegateStatement => w.write_u8(constants::DW_LNS_negate_stmt.0),
+            LineInstruction::SetBasicBlock => w.write_u8(constants::DW_LNS_set_basic_block.0),
+            LineInstruction::ConstAddPc => w.write_u8(constants::DW_LNS_const_add_pc.0),
+            LineInstruction::SetPrologueEnd => w.write_u8(constants::DW_LNS_set_prologue_end.0),
+            LineInstruction::SetEpilogueBegin => {
+                w.write_u8(constants::DW_LNS_set_epilogue_begin.0)
+            }
+            LineInstruction::SetIsa(isa) => {
+                w.write_u8(constants::DW_LNS_set_isa.0)?;
+                w.write_uleb128(isa)
+            }
+            LineInstruction::EndSequence => {
+                w.write_u8(0)?;
+                w.write_uleb128(1)?;
+                w.write_u8(constants::DW_LNE_end_sequence.0)
+            }
+            LineInstruction::SetAddress(address) => {
+                w.write_u8(0)?;
+                w.write_uleb128(1 + u64::from(self.address_size))?;
+                w.write_u8(constants::DW_LNE_set_address.0)?;
+                w.write_address(address, self.address_size)
+            }
+            LineInstruction::SetDiscriminator(discriminator) => {
+                let mut bytes = [0u8; 10];
+                let len = uleb128(&mut bytes, discriminator);
+                w.write_u8(0)?;
+                w.write_uleb128(1 + len as u64)?;
+                w.write_u8(constants::DW_LNE_set_discriminator.0)?;
+                w.write(&bytes[..len])
+            }
+        }
+    }
+}
+
+fn write_string<W: Writer>(w: &mut W, string: &[u8]) -> Result<()> {
+    w.write(string)?;
+    w.write_u8(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use line::{ColumnType, DebugLine, DebugLineOffset};
+    use write::EndianVec;
+
+    fn make_program(version: u16) -> (LineProgram, Vec<LineRow>) {
+        let mut program = LineProgram::new(
+            version,
+            8,
+            LineEncoding::default(),
+            b"/comp_dir".to_vec(),
+            b"comp_file.c".to_vec(),
+        ).unwrap();
+        let dir = program.add_directory(b"/inc".to_vec());
+        assert_eq!(program.add_directory(b"/inc".to_vec()), dir);
+        let file = program.add_file(b"file.h".to_vec(), dir);
+        assert_eq!(program.add_file(b"file.h".to_vec(), dir), file);
+
+        let default_file = program.default_file();
+        let mut rows = Vec::new();
+        rows.push(LineRow::new(Address::Constant(0x1000), default_file, 1, 0));
+        rows.push(LineRow::new(Address::Constant(0x1004), default_file, 2, 0));
+        rows.push(LineRow::new(Address::Constant(0x1008), default_file, 10, 4));
+        rows.push(LineRow::new(Address::Constant(0x1100), file, 3, 0));
+        rows.push(LineRow::new(Address::Constant(0x10000), file, 1000, 0));
+        let mut row = LineRow::new(Address::Constant(0x10001), default_file, 100, 0);
+        row.is_stmt = false;
+        row.prologue_end = true;
+        row.discriminator = 3;
+        rows.push(row);
+        for row in &rows {
+            program.add_row(*row);
+        }
+        program.end_sequence(Address::Constant(0x10010));
+        (program, rows)
+    }
+
+    #[test]
+    fn test_line_program_roundtrip() {
+        for &version in &[2, 3, 4, 5] {
+            // File indices are 0-based in version 5, and 1-based before.
+            let base = if version >= 5 { 0 } else { 1 };
+            let (program, rows) = make_program(version);
+            let mut w = EndianVec::new(LittleEndian);
+            program.write(&mut w).unwrap();
+            let buf = w.into_vec();
+
+            let debug_line = DebugLine::new(&buf, LittleEndian);
+            let comp_dir = EndianBuf::new(b"/comp_dir", LittleEndian);
+            let program = debug_line
+                .program(DebugLineOffset(0), 8, Some(comp_dir), None)
+                .unwrap();
+            assert_eq!(program.header().version(), version);
+            assert_eq!(program.header().file_names().len(), 2);
+            let file = program.header().file(base + 1).unwrap();
+            assert_eq!(file.path_name().buf(), b"file.h");
+            assert_eq!(file.directory(program.header()).unwrap().buf(), b"/inc");
+
+            // The converted files are indexed by the file register values.
+            let convert_address = |address| Some(Address::Constant(address));
+            let (converted, files) =
+                LineProgram::from(program.clone(), 8, &convert_address).unwrap();
+            let file_name = |index: u64| &converted.files[files[index as usize].0].0[..];
+            assert_eq!(file_name(base), b"comp_file.c");
+            assert_eq!(file_name(base + 1), b"file.h");
+
+            let mut state_machine = program.rows();
+            for expected in &rows {
+                let (_, row) = state_machine.next_row().unwrap().unwrap();
+                assert_eq!(Address::Constant(row.address()), expected.address);
+                assert_eq!(row.file_index(), ::FileIndex(expected.file.0 as u64 + base));
+                assert_eq!(row.line(), Some(expected.line));
+                let column = if expected.column == 0 {
+                    ColumnType::LeftEdge
+                } else {
+                    ColumnType::Column(expected.column)
+                };
+                assert_eq!(row.column(), column);
+                assert_eq!(row.is_stmt(), expected.is_stmt);
+                assert_eq!(row.prologue_end(), expected.prologue_end);
+                assert_eq!(row.discriminator(), expected.discriminator);
+                assert!(!row.end_sequence());
+            }
+            let (_, row) = state_machine.next_row().unwrap().unwrap();
+            assert!(row.end_sequence());
+            assert_eq!(row.address(), 0x10010);
+            assert!(state_machine.next_row().unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_line_program_roundtrip_no_comp_file() {
+        for &version in &[2, 3, 4] {
+            let mut program = LineProgram::new(
+                version,
+                8,
+                LineEncoding::default(),
+                b"/comp_dir".to_vec(),
+                Vec::new(),
+            ).unwrap();
+            let dir = program.default_directory();
+            let file1 = program.add_file(b"file1.c".to_vec(), dir);
+            let file2 = program.add_file(b"file2.c".to_vec(), dir);
+            program.add_row(LineRow::new(Address::Constant(0x1000), file1, 1, 0));
+            program.add_row(LineRow::new(Address::Constant(0x1004), file2, 2, 0));
+            program.end_sequence(Address::Constant(0x1010));
+
+            // The unit has no DW_AT_name, so there is no primary source file.
+            let mut w = EndianVec::new(LittleEndian);
+            program.write(&mut w).unwrap();
+            let buf = w.into_vec();
+            let debug_line = DebugLine::new(&buf, LittleEndian);
+            let read_program = debug_line
+                .program(DebugLineOffset(0), 8, None, None)
+                .unwrap();
+            assert_eq!(read_program.header().file_names().len(), 2);
+            assert_eq!(read_program.header().file(1).unwrap().path_name().buf(), b"file1.c");
+            assert_eq!(read_program.header().file(2).unwrap().path_name().buf(), b"file2.c");
+
+            let convert_address = |address| Some(Address::Constant(address));
+            let (converted, files) =
+                LineProgram::from(read_program, 8, &convert_address).unwrap();
+            assert_eq!(converted.files[files[1].0].0, b"file1.c");
+            assert_eq!(converted.files[files[2].0].0, b"file2.c");
+
+            let mut w = EndianVec::new(LittleEndian);
+            converted.write(&mut w).unwrap();
+            let buf = w.into_vec();
+            let debug_line = DebugLine::new(&buf, LittleEndian);
+            let read_program = debug_line
+                .program(DebugLineOffset(0), 8, None, None)
+                .unwrap();
+            assert_eq!(read_program.header().file_names().len(), 2);
+            let mut rows = read_program.rows();
+            let (_, row) = rows.next_row().unwrap().unwrap();
+            assert_eq!(row.file_index(), ::FileIndex(1));
+            let (_, row) = rows.next_row().unwrap().unwrap();
+            assert_eq!(row.file_index(), ::FileIndex(2));
+            let (_, row) = rows.next_row().unwrap().unwrap();
+            assert!(row.end_sequence());
+        }
+    }
+
+    #[test]
+    fn test_line_program_special_opcodes() {
+        let mut program = LineProgram::new(
+            4,
+            8,
+            LineEncoding::default(),
+            b"dir".to_vec(),
+            b"file".to_vec(),
+        ).unwrap();
+        let file = program.default_file();
+        program.add_row(LineRow::new(Address::Constant(0x1000), file, 1, 0));
+        let start = program.instructions.len();
+        // Small address and line advances use a single special opcode.
+        program.add_row(LineRow::new(Address::Constant(0x1004), file, 2, 0));
+        assert_eq!(program.instructions.len(), start + 1);
+        // Larger address advances use DW_LNS_const_add_pc.
+        program.add_row(LineRow::new(Address::Constant(0x1004 + 20), file, 2, 0));
+        assert_eq!(
+            &program.instructions[start + 1..],
+            &[LineInstruction::ConstAddPc, LineInstruction::Special(60)]
+        );
+        program.end_sequence(Address::Constant(0x2000));
+
+        // Line advances whose special opcode wouldn't fit in a byte use
+        // DW_LNS_advance_line.
+        let encoding = LineEncoding {
+            line_base: -3,
+            opcode_base: 250,
+            ..LineEncoding::default()
+        };
+        let mut program =
+            LineProgram::new(4, 8, encoding, b"dir".to_vec(), b"file".to_vec()).unwrap();
+        let file = program.default_file();
+        program.add_row(LineRow::new(Address::Constant(0x1000), file, 1, 0));
+        let start = program.instructions.len();
+        program.add_row(LineRow::new(Address::Constant(0x1000), file, 11, 0));
+        assert_eq!(
+            &program.instructions[start..],
+            &[LineInstruction::AdvanceLine(10), LineInstruction::Special(253)]
+        );
+        program.end_sequence(Address::Constant(0x2000));
+    }
+
+    #[test]
+    fn test_line_program_v5_header() {
+        let (program, _) = make_program(5);
+        let mut w = EndianVec::new(LittleEndian);
+        program.write(&mut w).unwrap();
+        let buf = w.into_vec();
+        // Version, address size and segment selector size.
+        assert_eq!(&buf[4..8], &[5, 0, 8, 0]);
+    }
+
+    #[test]
+    fn test_line_program_errors() {
+        let encoding = LineEncoding {
+            line_range: 0,
+            ..LineEncoding::default()
+        };
+        assert_eq!(
+            LineProgram::new(4, 8, encoding, Vec::new(), Vec::new()).map(|_| ()),
+            Err(Error::InvalidLineEncoding)
+        );
+        let encoding = LineEncoding {
+            line_base: -10,
+            opcode_base: 250,
+            ..LineEncoding::default()
+        };
+        assert_eq!(
+            LineProgram::new(4, 8, encoding, Vec::new(), Vec::new()).map(|_| ()),
+            Err(Error::InvalidLineEncoding)
+        );
+        assert_eq!(
+            LineProgram::new(6, 8, LineEncoding::default(), Vec::new(), Vec::new()).map(|_| ()),
+            Err(Error::UnsupportedVersion(6))
+        );
+
+        let mut program =
+            LineProgram::new(4, 8, LineEncoding::default(), Vec::new(), Vec::new()).unwrap();
+        let file = program.default_file();
+        program.add_row(LineRow::new(Address::Constant(0x1000), file, 1, 0));
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(program.write(&mut w), Err(Error::LineSequenceNotEnded));
+    }
+}
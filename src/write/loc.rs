@@ -0,0 +1,294 @@
+use constants;
+use loclists::LocationListsOffset;
+use vec::Vec;
+use write::{Address, Error, Expression, Result, Writer};
+use write::range::{is_empty_range, min_begin, range_offsets, write_lists_header};
+
+/// A location description that is valid for an address range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The beginning address of the range.
+    pub begin: Address,
+
+    /// The first address past the end of the range.
+    pub end: Address,
+
+    /// The location description.
+    pub data: Expression,
+}
+
+/// An identifier for a location list in a `LocationListTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocationListId(usize);
+
+/// A table of location lists that will be written to a `.debug_loc` or
+/// `.debug_loclists` section.
+#[derive(Debug, Default, Clone)]
+pub struct LocationListTable {
+    lists: Vec<Vec<Location>>,
+}
+
+impl LocationListTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        LocationListTable::default()
+    }
+
+    /// Add a location list to the table, and return its id.
+    ///
+    /// If an identical location list was already added, then the existing id
+    /// is returned. Locations with empty ranges are not written.
+    pub fn add(&mut self, locations: Vec<Location>) -> LocationListId {
+        if let Some(index) = self.lists.iter().position(|l| *l == locations) {
+            return LocationListId(index);
+        }
+        self.lists.push(locations);
+        LocationListId(self.lists.len() - 1)
+    }
+
+    /// Write the location lists, using `.debug_loc` for DWARF versions 2 to
+    /// 4, and `.debug_loclists` for version 5.
+    ///
+    /// `base_address` is the base address of the unit that will refer to the
+    /// location lists, which is usually its `DW_AT_low_pc`, or 0 if it has
+    /// none. Ranges are encoded relative to this base address where possible.
+    /// Ranges that are relative to a different symbol are encoded using
+    /// base address selection entries or absolute addresses.
+    ///
+    /// Returns the offsets of the location lists, for use in attributes such
+    /// as `DW_AT_location`.
+    pub fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        version: u16,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<LocationListOffsets> {
+        if version < 2 || version > 5 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        if version < 5 {
+            self.write_loc(w, address_size, base_address)
+        } else {
+            self.write_loclists(w, address_size, base_address)
+        }
+    }
+
+    fn write_loc<W: Writer>(
+        &self,
+        w: &mut W,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<LocationListOffsets> {
+        let max_address = !0 >> (64 - u64::from(address_size) * 8);
+        let mut offsets = Vec::new();
+        for locations in &self.lists {
+            offsets.push(LocationListsOffset(w.len()));
+            let mut base = base_address;
+            if let Some(begin) = min_begin(locations.iter().map(|l| (l.begin, l.end)), base) {
+                if begin.offset_from(base).is_none() {
+                    // Base address selection entry.
+                    w.write_udata(max_address, address_size)?;
+                    w.write_address(begin, address_size)?;
+                    base = begin;
+                }
+            }
+            for location in locations
+                .iter()
+                .filter(|l| !is_empty_range(l.begin, l.end))
+            {
+                let (begin, end) = match range_offsets(location.begin, location.end, base) {
+                    Some(offsets) => offsets,
+                    None => {
+                        // The range is relative to a different symbol.
+                        w.write_udata(max_address, address_size)?;
+                        w.write_address(location.begin, address_size)?;
+                        base = location.begin;
+                        let end = location
+                            .end
+                            .offset_from(base)
+                            .ok_or(Error::InvalidAddressRange)?;
+                        (0, end)
+                    }
+                };
+                w.write_udata(begin, address_size)?;
+                w.write_udata(end, address_size)?;
+                w.write_udata(location.data.size(address_size) as u64, 2)?;
+                location.data.write(w, address_size)?;
+            }
+            // End of list entry.
+            w.write_udata(0, address_size)?;
+            w.write_udata(0, address_size)?;
+        }
+        Ok(LocationListOffsets { offsets })
+    }
+
+    fn write_loclists<W: Writer>(
+        &self,
+        w: &mut W,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<LocationListOffsets> {
+        let length_offset = write_lists_header(w, address_size)?;
+        let length_base = length_offset + 4;
+
+        let mut offsets = Vec::new();
+        for locations in &self.lists {
+            offsets.push(LocationListsOffset(w.len()));
+            let count = locations
+                .iter()
+                .filter(|l| !is_empty_range(l.begin, l.end))
+                .count();
+            let mut base = Some(base_address);
+            if let Some(begin) = min_begin(locations.iter().map(|l| (l.begin, l.end)), base_address)
+            {
+                if begin.offset_from(base_address).is_none() {
+                    base = None;
+                    if count > 1 {
+                        w.write_u8(constants::DW_LLE_base_address.0)?;
+                        w.write_address(begin, address_size)?;
+                        base = Some(begin);
+                    }
+                }
+            }
+            for location in locations
+                .iter()
+                .filter(|l| !is_empty_range(l.begin, l.end))
+            {
+                match base.and_then(|base| range_offsets(location.begin, location.end, base)) {
+                    Some((begin, end)) => {
+                        w.write_u8(constants::DW_LLE_offset_pair.0)?;
+                        w.write_uleb128(begin)?;
+                        w.write_uleb128(end)?;
+                    }
+                    None => match location.end.offset_from(location.begin) {
+                        Some(length) => {
+                            w.write_u8(constants::DW_LLE_start_length.0)?;
+                            w.write_address(location.begin, address_size)?;
+                            w.write_uleb128(length)?;
+                        }
+                        None => {
+                            w.write_u8(constants::DW_LLE_start_end.0)?;
+                            w.write_address(location.begin, address_size)?;
+                            w.write_address(location.end, address_size)?;
+                        }
+                    },
+                }
+                w.write_uleb128(location.data.size(address_size) as u64)?;
+                location.data.write(w, address_size)?;
+            }
+            w.write_u8(constants::DW_LLE_end_of_list.0)?;
+        }
+
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(length_offset, length as u32)?;
+        Ok(LocationListOffsets { offsets })
+    }
+}
+
+/// The section offsets of the location lists in a `LocationListTable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationListOffsets {
+    offsets: Vec<LocationListsOffset>,
+}
+
+impl LocationListOffsets {
+    /// Return the offset of the given location list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not returned by the table that was written.
+    pub fn get(&self, id: LocationListId) -> LocationListsOffset {
+        self.offsets[id.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use loclists::{DebugLoc, DebugLocLists, LocationLists};
+    use write::EndianVec;
+
+    #[test]
+    fn test_location_list_table() {
+        let mut expression = Expression::new();
+        expression.op_addr(Address::Constant(0x1234));
+        let mut table = LocationListTable::new();
+        let list1 = vec![
+            Location {
+                begin: Address::Constant(0x1000),
+                end: Address::Constant(0x1100),
+                data: Expression::raw(vec![0x50]),
+            },
+            Location {
+                begin: Address::Constant(0x1200),
+                end: Address::Constant(0x1200),
+                data: Expression::raw(vec![0x51]),
+            },
+            Location {
+                begin: Address::Constant(0x2000),
+                end: Address::Constant(0x2010),
+                data: expression,
+            },
+        ];
+        let id1 = table.add(list1.clone());
+        assert_eq!(table.add(list1.clone()), id1);
+        let list2 = vec![
+            Location {
+                begin: Address::Constant(0x100),
+                end: Address::Constant(0x200),
+                data: Expression::raw(vec![0x52]),
+            },
+        ];
+        let id2 = table.add(list2.clone());
+        let list3 = vec![
+            Location {
+                begin: Address::Constant(0x100),
+                end: Address::Constant(0x200),
+                data: Expression::raw(vec![0x53]),
+            },
+            Location {
+                begin: Address::Constant(0x3000),
+                end: Address::Constant(0x3004),
+                data: Expression::raw(vec![0x54, 0x55]),
+            },
+        ];
+        let id3 = table.add(list3.clone());
+
+        for &version in &[2, 4, 5] {
+            for &address_size in &[4, 8] {
+                let mut w = EndianVec::new(LittleEndian);
+                let offsets = table.write(&mut w, version, address_size, Address::Constant(0x1000)).unwrap();
+                let buf = w.into_vec();
+                let (debug_loc, debug_loclists) = if version < 5 {
+                    (&buf[..], &[][..])
+                } else {
+                    (&[][..], &buf[..])
+                };
+                let location_lists = LocationLists::new(
+                    DebugLoc::new(debug_loc, LittleEndian),
+                    DebugLocLists::new(debug_loclists, LittleEndian),
+                ).unwrap();
+
+                for &(id, ref list) in &[(id1, &list1), (id2, &list2), (id3, &list3)] {
+                    let mut locations = location_lists
+                        .locations(offsets.get(id), version, address_size, 0x1000)
+                        .unwrap();
+                    for expected in list.iter().filter(|l| !is_empty_range(l.begin, l.end)) {
+                        let location = locations.next().unwrap().unwrap();
+                        assert_eq!(Address::Constant(location.range.begin), expected.begin);
+                        assert_eq!(Address::Constant(location.range.end), expected.end);
+                        let mut data = EndianVec::new(LittleEndian);
+                        expected.data.write(&mut data, address_size).unwrap();
+                        assert_eq!(location.data.0, EndianBuf::new(data.slice(), LittleEndian));
+                    }
+                    assert!(locations.next().unwrap().is_none());
+                }
+            }
+        }
+    }
+}
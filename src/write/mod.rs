@@ -0,0 +1,268 @@
+//! Write DWARF debugging information and unwind information.
+//!
+//! The types in this module are builders: add entries to them, and then write
+//! them out as section data using a [`Writer`](./trait.Writer.html) such as
+//! [`EndianVec`](./struct.EndianVec.html). The size of a section can be
+//! computed without writing its data by using a
+//! [`CountingWriter`](./struct.CountingWriter.html).
+
+use constants;
+use parser::Error as ReadError;
+#[cfg(feature = "std")]
+use std::error;
+use std::fmt;
+use std::result;
+
+/// An address that is written to a section.
+///
+/// Symbol addresses are used when producing relocatable output, where the
+/// final address is not known until link time. Writing a symbol address
+/// requires a `Writer` that can record a relocation for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Address {
+    /// A fixed address that does not require relocation.
+    Constant(u64),
+    /// An address that is relative to a symbol which may be relocated.
+    Symbol {
+        /// The symbol that the address is relative to.
+        ///
+        /// The meaning of this value is decided by the writer, but will
+        /// typically be an index into a symbol table.
+        symbol: usize,
+        /// The offset of the address relative to the symbol.
+        addend: i64,
+    },
+}
+
+impl Address {
+    /// Return the offset of this address from `base`.
+    ///
+    /// Returns `None` if the addresses are not both constants or both
+    /// relative to the same symbol, or if this address is before `base`.
+    pub(crate) fn offset_from(self, base: Address) -> Option<u64> {
+        match (self, base) {
+            (Address::Constant(val), Address::Constant(base)) if val >= base => Some(val - base),
+            (
+                Address::Symbol { symbol, addend },
+                Address::Symbol {
+                    symbol: base_symbol,
+                    addend: base_addend,
+                },
+            ) if symbol == base_symbol && addend >= base_addend =>
+            {
+                Some(addend.wrapping_sub(base_addend) as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+mod endian_vec;
+pub use self::endian_vec::EndianVec;
+
+mod counting_writer;
+pub use self::counting_writer::CountingWriter;
+
+mod writer;
+pub use self::writer::Writer;
+
+mod cfi;
+pub use self::cfi::{CallFrameInstruction, CieId, CommonInformationEntry, EhFrameOffsets,
+                    FrameDescriptionEntry, FrameTable};
+
+mod line;
+pub use self::line::{DirectoryId, FileId, LineEncoding, LineProgram, LineRow};
+
+mod op;
+pub use self::op::Expression;
+
+mod abbrev;
+
+mod section;
+pub use self::section::Sections;
+
+mod unit;
+pub use self::unit::{Attribute, AttributeValue, DebugInfoOffsets, DebuggingInformationEntry,
+                     Unit, UnitEntryId, UnitId, UnitTable};
+
+mod dwarf;
+pub use self::dwarf::Dwarf;
+
+mod range;
+pub use self::range::{Range, RangeListId, RangeListOffsets, RangeListTable};
+
+mod loc;
+pub use self::loc::{Location, LocationListId, LocationListOffsets, LocationListTable};
+
+mod names;
+pub use self::names::{NameEntry, NameIndex, NameUnit};
+
+mod str;
+pub use self::str::{DebugLineStrOffsets, DebugStrOffsets, LineStringId, LineStringTable, StringId,
+                    StringTable};
+
+/// An error that occurred when writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The given offset is out of bounds.
+    OffsetOutOfBounds,
+    /// The given value is too large for the chosen encoding.
+    ValueTooLarge,
+    /// The given size is not supported for an integer.
+    UnsupportedWordSize(u8),
+    /// The given pointer encoding is not supported for writing.
+    UnsupportedPointerEncoding(constants::DwEhPe),
+    /// The code offset of a frame instruction is before the previous
+    /// instruction, or is not a multiple of the code alignment factor.
+    InvalidFrameCodeOffset(u64),
+    /// The offset of a frame register rule is not a multiple of the data
+    /// alignment factor.
+    InvalidFrameDataOffset(i64),
+    /// The given DWARF version is not supported for writing.
+    UnsupportedVersion(u16),
+    /// The line number program encoding parameters are invalid.
+    InvalidLineEncoding,
+    /// A line number sequence was not ended before writing.
+    LineSequenceNotEnded,
+    /// A symbol address was used where it is not supported, either because
+    /// the writer cannot record relocations or because a constant address is
+    /// required.
+    UnsupportedSymbolAddress,
+    /// The end of an address range cannot be encoded relative to its
+    /// beginning.
+    InvalidAddressRange,
+    /// An attribute value cannot be written for its unit, such as a
+    /// reference to a line number program or file when the unit has none.
+    InvalidAttributeValue,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        let description = self.description();
+        match *self {
+            Error::UnsupportedWordSize(size) => write!(f, "{}: {}", description, size),
+            Error::UnsupportedPointerEncoding(encoding) => {
+                write!(f, "{}: {}", description, encoding)
+            }
+            Error::InvalidFrameCodeOffset(offset) => write!(f, "{}: {}", description, offset),
+            Error::InvalidFrameDataOffset(offset) => write!(f, "{}: {}", description, offset),
+            Error::UnsupportedVersion(version) => write!(f, "{}: {}", description, version),
+            _ => write!(f, "{}", description),
+        }
+    }
+}
+
+impl Error {
+    /// A short description of the error.
+    pub fn description(&self) -> &str {
+        match *self {
+            Error::OffsetOutOfBounds => "The given offset is out of bounds.",
+            Error::ValueTooLarge => "The given value is too large for the chosen encoding.",
+            Error::UnsupportedWordSize(_) => "The given size is not supported for an integer.",
+            Error::UnsupportedPointerEncoding(_) => {
+                "The given pointer encoding is not supported for writing."
+            }
+            Error::InvalidFrameCodeOffset(_) => {
+                "The code offset of a frame instruction is invalid."
+            }
+            Error::InvalidFrameDataOffset(_) => {
+                "The offset of a frame register rule is not a multiple of the data alignment factor."
+            }
+            Error::UnsupportedVersion(_) => {
+                "The given DWARF version is not supported for writing."
+            }
+            Error::InvalidLineEncoding => {
+                "The line number program encoding parameters are invalid."
+            }
+            Error::LineSequenceNotEnded => "A line number sequence was not ended before writing.",
+            Error::UnsupportedSymbolAddress => "A symbol address is not supported here.",
+            Error::InvalidAddressRange => {
+                "The end of an address range cannot be encoded relative to its beginning."
+            }
+            Error::InvalidAttributeValue => "An attribute value cannot be written for its unit.",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        Error::description(self)
+    }
+}
+
+/// The result of a write.
+pub type Result<T> = result::Result<T, Error>;
+
+/// An error that occurred when converting a read value into a write value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertError {
+    /// An error occurred when reading.
+    Read(ReadError),
+    /// Writing of this attribute value is not implemented yet.
+    UnsupportedAttributeValue,
+    /// The address conversion function returned `None` for an address.
+    InvalidAddress,
+    /// A `.debug_info` reference does not refer to a valid entry.
+    InvalidDebugInfoOffset,
+    /// A unit reference does not refer to a valid entry.
+    InvalidUnitRef,
+    /// A file index does not refer to a valid file in the line number program.
+    InvalidFileIndex,
+    /// Writing this operation in an expression is not implemented yet.
+    UnsupportedOperation,
+    /// A unit contains more than one top-level entry.
+    MultipleUnitEntries,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        let description = self.description();
+        match *self {
+            ConvertError::Read(ref e) => write!(f, "{}: {}", description, e),
+            _ => write!(f, "{}", description),
+        }
+    }
+}
+
+impl ConvertError {
+    /// A short description of the error.
+    pub fn description(&self) -> &str {
+        match *self {
+            ConvertError::Read(_) => "An error occurred when reading.",
+            ConvertError::UnsupportedAttributeValue => {
+                "Writing of this attribute value is not implemented yet."
+            }
+            ConvertError::InvalidAddress => {
+                "The address conversion function returned None for an address."
+            }
+            ConvertError::InvalidDebugInfoOffset => {
+                "A .debug_info reference does not refer to a valid entry."
+            }
+            ConvertError::InvalidUnitRef => "A unit reference does not refer to a valid entry.",
+            ConvertError::InvalidFileIndex => {
+                "A file index does not refer to a valid file in the line number program."
+            }
+            ConvertError::UnsupportedOperation => {
+                "Writing this operation in an expression is not implemented yet."
+            }
+            ConvertError::MultipleUnitEntries => "A unit contains more than one top-level entry.",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ConvertError {
+    fn description(&self) -> &str {
+        ConvertError::description(self)
+    }
+}
+
+impl From<ReadError> for ConvertError {
+    fn from(e: ReadError) -> Self {
+        ConvertError::Read(e)
+    }
+}
+
+/// The result of a conversion.
+pub type ConvertResult<T> = result::Result<T, ConvertError>;
@@ -0,0 +1,447 @@
+use btree_map::BTreeMap;
+use constants;
+use names::name_hash;
+use str::DebugStrOffset;
+use string::String;
+use unit::{DebugInfoOffset, DebugTypeSignature, UnitOffset};
+use vec::Vec;
+use write::{Error, Result, Writer};
+
+/// A unit in a `NameIndex`.
+///
+/// The index is the position of the unit in the list that it was added to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameUnit {
+    /// A compilation unit that was added using `NameIndex::add_comp_unit`.
+    Comp(usize),
+    /// A type unit that was added using `NameIndex::add_local_type_unit`.
+    LocalType(usize),
+    /// A type unit that was added using `NameIndex::add_foreign_type_unit`.
+    ForeignType(usize),
+}
+
+/// An entry for a debugging information entry that has a given name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NameEntry {
+    /// The unit that contains the debugging information entry.
+    pub unit: NameUnit,
+
+    /// The offset of the debugging information entry within its unit.
+    ///
+    /// This is ignored for entries in foreign type units.
+    pub die_offset: UnitOffset,
+
+    /// The tag of the debugging information entry.
+    pub tag: constants::DwTag,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedName {
+    string_offset: DebugStrOffset,
+    entries: Vec<NameEntry>,
+}
+
+/// A name index that will be written to a `.debug_names` section.
+///
+/// The index is written using the 32-bit DWARF format, and includes a hash
+/// table so that names can be found without a linear search.
+#[derive(Debug, Default, Clone)]
+pub struct NameIndex {
+    comp_units: Vec<DebugInfoOffset>,
+    local_type_units: Vec<DebugInfoOffset>,
+    foreign_type_units: Vec<DebugTypeSignature>,
+    names: BTreeMap<String, IndexedName>,
+}
+
+/// The index attribute that is used to refer to the unit of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnitAttribute {
+    None,
+    CompUnit,
+    TypeUnit,
+}
+
+impl NameIndex {
+    /// Create an empty name index.
+    pub fn new() -> Self {
+        NameIndex::default()
+    }
+
+    /// Add a compilation unit at the given offset in the `.debug_info`
+    /// section.
+    pub fn add_comp_unit(&mut self, offset: DebugInfoOffset) -> NameUnit {
+        self.comp_units.push(offset);
+        NameUnit::Comp(self.comp_units.len() - 1)
+    }
+
+    /// Add a type unit at the given offset in the `.debug_info` section.
+    pub fn add_local_type_unit(&mut self, offset: DebugInfoOffset) -> NameUnit {
+        self.local_type_units.push(offset);
+        NameUnit::LocalType(self.local_type_units.len() - 1)
+    }
+
+    /// Add a type unit that is in a separate object file, such as a split
+    /// DWARF object, and is identified by its type signature.
+    pub fn add_foreign_type_unit(&mut self, signature: DebugTypeSignature) -> NameUnit {
+        self.foreign_type_units.push(signature);
+        NameUnit::ForeignType(self.foreign_type_units.len() - 1)
+    }
+
+    /// Add an entry for the given name.
+    ///
+    /// `string_offset` is the offset of the name in the `.debug_str` section.
+    /// If the name was already added, then the entry is added to the existing
+    /// entries for the name, and the existing string offset is kept.
+    /// Duplicate entries are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the unit of the entry was not added to this index.
+    pub fn add(&mut self, name: &str, string_offset: DebugStrOffset, entry: NameEntry) {
+        let valid = match entry.unit {
+            NameUnit::Comp(index) => index < self.comp_units.len(),
+            NameUnit::LocalType(index) => index < self.local_type_units.len(),
+            NameUnit::ForeignType(index) => index < self.foreign_type_units.len(),
+        };
+        assert!(valid);
+        let name = self.names
+            .entry(String::from(name))
+            .or_insert_with(|| IndexedName {
+                string_offset,
+                entries: Vec::new(),
+            });
+        if !name.entries.contains(&entry) {
+            name.entries.push(entry);
+        }
+    }
+
+    /// Return the number of names in the index.
+    pub fn count(&self) -> usize {
+        self.names.len()
+    }
+
+    fn unit_attribute(&self, unit: NameUnit) -> (UnitAttribute, u64) {
+        match unit {
+            // The compilation unit is implied when there is only one.
+            NameUnit::Comp(_) if self.comp_units.len() == 1 => (UnitAttribute::None, 0),
+            NameUnit::Comp(index) => (UnitAttribute::CompUnit, index as u64),
+            NameUnit::LocalType(index) => (UnitAttribute::TypeUnit, index as u64),
+            // Foreign type units are numbered after the local type units.
+            NameUnit::ForeignType(index) => (
+                UnitAttribute::TypeUnit,
+                (self.local_type_units.len() + index) as u64,
+            ),
+        }
+    }
+
+    /// Write the name index to a `.debug_names` section.
+    pub fn write<W: Writer>(&self, w: &mut W) -> Result<()> {
+        let comp_unit_count = count_u32(self.comp_units.len())?;
+        let local_type_unit_count = count_u32(self.local_type_units.len())?;
+        let foreign_type_unit_count = count_u32(self.foreign_type_units.len())?;
+        let type_unit_count =
+            count_u32(self.local_type_units.len() + self.foreign_type_units.len())?;
+        let name_count = count_u32(self.names.len())?;
+
+        // Sort the names by bucket, as required by the hash table.
+        let mut names: Vec<_> = self.names
+            .iter()
+            .map(|(name, indexed)| (name_hash(name), indexed))
+            .collect();
+        let bucket_count = bucket_count(&names);
+        if bucket_count != 0 {
+            names.sort_by_key(|&(hash, _)| (hash % bucket_count, hash));
+        }
+
+        // Determine the abbreviations that are needed. Entries only differ in
+        // their tag and the attribute that refers to their unit.
+        let mut abbrevs = Vec::new();
+        for &(_, indexed) in &names {
+            for entry in &indexed.entries {
+                let abbrev = (entry.tag, self.unit_attribute(entry.unit).0);
+                if !abbrevs.contains(&abbrev) {
+                    abbrevs.push(abbrev);
+                }
+            }
+        }
+        let comp_unit_form = index_form(comp_unit_count);
+        let type_unit_form = index_form(type_unit_count);
+
+        let length_offset = w.len();
+        w.write_u32(0)?;
+        let length_base = w.len();
+        w.write_u16(5)?;
+        // Padding.
+        w.write_u16(0)?;
+        w.write_u32(comp_unit_count)?;
+        w.write_u32(local_type_unit_count)?;
+        w.write_u32(foreign_type_unit_count)?;
+        w.write_u32(bucket_count)?;
+        w.write_u32(name_count)?;
+        let abbrev_table_size_offset = w.len();
+        w.write_u32(0)?;
+        // Augmentation string size.
+        w.write_u32(0)?;
+
+        for offset in self.comp_units.iter().chain(&self.local_type_units) {
+            w.write_u32(offset_u32(offset.0)?)?;
+        }
+        for signature in &self.foreign_type_units {
+            w.write_u64(signature.0)?;
+        }
+
+        // Buckets contain the 1-based index of their first name, or 0 if
+        // they are empty.
+        let mut index = 0;
+        for bucket in 0..bucket_count {
+            if index < names.len() && names[index].0 % bucket_count == bucket {
+                w.write_u32(index as u32 + 1)?;
+                while index < names.len() && names[index].0 % bucket_count == bucket {
+                    index += 1;
+                }
+            } else {
+                w.write_u32(0)?;
+            }
+        }
+        if bucket_count != 0 {
+            for &(hash, _) in &names {
+                w.write_u32(hash)?;
+            }
+        }
+        for &(_, indexed) in &names {
+            w.write_u32(offset_u32(indexed.string_offset.0)?)?;
+        }
+        // The entry offsets are written after the entry pool.
+        let entry_offsets_offset = w.len();
+        for _ in &names {
+            w.write_u32(0)?;
+        }
+
+        let abbrev_table_offset = w.len();
+        for (index, &(tag, unit_attribute)) in abbrevs.iter().enumerate() {
+            w.write_uleb128(index as u64 + 1)?;
+            w.write_uleb128(tag.0)?;
+            match unit_attribute {
+                UnitAttribute::None => {}
+                UnitAttribute::CompUnit => {
+                    w.write_uleb128(constants::DW_IDX_compile_unit.0.into())?;
+                    w.write_uleb128(comp_unit_form.0)?;
+                }
+                UnitAttribute::TypeUnit => {
+                    w.write_uleb128(constants::DW_IDX_type_unit.0.into())?;
+                    w.write_uleb128(type_unit_form.0)?;
+                }
+            }
+            w.write_uleb128(constants::DW_IDX_die_offset.0.into())?;
+            w.write_uleb128(constants::DW_FORM_ref4.0)?;
+            w.write_u8(0)?;
+            w.write_u8(0)?;
+        }
+        // Null abbreviation code.
+        w.write_u8(0)?;
+        let abbrev_table_size = w.len() - abbrev_table_offset;
+        w.write_u32_at(abbrev_table_size_offset, offset_u32(abbrev_table_size)?)?;
+
+        let entry_pool_offset = w.len();
+        for (index, &(_, indexed)) in names.iter().enumerate() {
+            let entry_offset = offset_u32(w.len() - entry_pool_offset)?;
+            w.write_u32_at(entry_offsets_offset + index * 4, entry_offset)?;
+            for entry in &indexed.entries {
+                let (unit_attribute, unit_index) = self.unit_attribute(entry.unit);
+                let code = abbrevs
+                    .iter()
+                    .position(|&abbrev| abbrev == (entry.tag, unit_attribute))
+                    .unwrap();
+                w.write_uleb128(code as u64 + 1)?;
+                match unit_attribute {
+                    UnitAttribute::None => {}
+                    UnitAttribute::CompUnit => write_index(w, unit_index, comp_unit_form)?,
+                    UnitAttribute::TypeUnit => write_index(w, unit_index, type_unit_form)?,
+                }
+                w.write_u32(offset_u32(entry.die_offset.0)?)?;
+            }
+            // End of the entries for this name.
+            w.write_u8(0)?;
+        }
+
+        let length = w.len() - length_base;
+        w.write_u32_at(length_offset, offset_u32(length)?)?;
+        Ok(())
+    }
+}
+
+/// Choose the number of buckets in the hash table, using the same heuristic
+/// as LLVM.
+fn bucket_count(names: &[(u32, &IndexedName)]) -> u32 {
+    let mut hashes: Vec<_> = names.iter().map(|&(hash, _)| hash).collect();
+    hashes.sort();
+    hashes.dedup();
+    let count = hashes.len() as u32;
+    if count > 1024 {
+        count / 4
+    } else if count > 16 {
+        count / 2
+    } else {
+        count
+    }
+}
+
+/// Choose the smallest form that can encode indexes into a list of `count`
+/// units.
+fn index_form(count: u32) -> constants::DwForm {
+    if count <= 0x100 {
+        constants::DW_FORM_data1
+    } else if count <= 0x1_0000 {
+        constants::DW_FORM_data2
+    } else {
+        constants::DW_FORM_data4
+    }
+}
+
+fn write_index<W: Writer>(w: &mut W, index: u64, form: constants::DwForm) -> Result<()> {
+    match form {
+        constants::DW_FORM_data1 => w.write_u8(index as u8),
+        constants::DW_FORM_data2 => w.write_u16(index as u16),
+        _ => w.write_u32(index as u32),
+    }
+}
+
+fn count_u32(count: usize) -> Result<u32> {
+    if count as u64 > u64::from(u32::max_value()) {
+        return Err(Error::ValueTooLarge);
+    }
+    Ok(count as u32)
+}
+
+fn offset_u32(offset: usize) -> Result<u32> {
+    if offset as u64 > 0xffff_ffff {
+        return Err(Error::ValueTooLarge);
+    }
+    Ok(offset as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+    use fallible_iterator::FallibleIterator;
+    use names::DebugNames;
+    use str::DebugStr;
+    use unit::UnitSectionOffset;
+    use write::{EndianVec, StringTable};
+
+    #[test]
+    fn test_name_index() {
+        let mut strings = StringTable::new();
+        let main_id = strings.add("main");
+        let foo_id = strings.add("foo");
+        let mut debug_str = EndianVec::new(LittleEndian);
+        let str_offsets = strings.write(&mut debug_str).unwrap();
+
+        let mut index = NameIndex::new();
+        let cu0 = index.add_comp_unit(DebugInfoOffset(0));
+        let cu1 = index.add_comp_unit(DebugInfoOffset(0x40));
+        let tu = index.add_local_type_unit(DebugInfoOffset(0x80));
+        let foreign = index.add_foreign_type_unit(DebugTypeSignature(0x1234));
+        let main = NameEntry {
+            unit: cu1,
+            die_offset: UnitOffset(0x20),
+            tag: constants::DW_TAG_subprogram,
+        };
+        index.add("main", str_offsets.get(main_id), main);
+        index.add("main", str_offsets.get(main_id), main);
+        for &unit in &[cu0, tu, foreign] {
+            let entry = NameEntry {
+                unit,
+                die_offset: UnitOffset(0x18),
+                tag: constants::DW_TAG_structure_type,
+            };
+            index.add("foo", str_offsets.get(foo_id), entry);
+        }
+        assert_eq!(index.count(), 2);
+
+        let mut debug_names = EndianVec::new(LittleEndian);
+        index.write(&mut debug_names).unwrap();
+
+        let debug_str = DebugStr::new(debug_str.slice(), LittleEndian);
+        let debug_names = DebugNames::new(debug_names.slice(), LittleEndian);
+        let mut indexes = debug_names.name_indexes();
+        let read_index = indexes.next().unwrap().unwrap();
+        assert_eq!(indexes.next(), Ok(None));
+        assert_eq!(read_index.comp_unit_count(), 2);
+        assert_eq!(read_index.local_type_unit_count(), 1);
+        assert_eq!(read_index.foreign_type_unit_count(), 1);
+        assert_eq!(read_index.name_count(), 2);
+        assert_eq!(read_index.comp_unit(1), Ok(DebugInfoOffset(0x40)));
+        assert_eq!(read_index.local_type_unit(0), Ok(DebugInfoOffset(0x80)));
+        assert_eq!(read_index.foreign_type_unit(0), Ok(DebugTypeSignature(0x1234)));
+
+        let name = read_index.find(&debug_str, "main").unwrap().unwrap();
+        let entries: Vec<_> = read_index.entries(&name).unwrap().collect().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tag(), constants::DW_TAG_subprogram);
+        assert_eq!(
+            read_index.entry_die_offset(&entries[0]),
+            Ok(Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0x60))))
+        );
+
+        let name = read_index.find(&debug_str, "foo").unwrap().unwrap();
+        let entries: Vec<_> = read_index.entries(&name).unwrap().collect().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].compile_unit(), Some(0));
+        assert_eq!(entries[1].type_unit(), Some(0));
+        assert_eq!(entries[2].type_unit(), Some(1));
+        let offsets: Vec<_> = entries
+            .iter()
+            .map(|entry| read_index.entry_die_offset(entry).unwrap())
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0x18))),
+                Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0x98))),
+                None,
+            ]
+        );
+
+        assert_eq!(read_index.find(&debug_str, "bar"), Ok(None));
+    }
+
+    #[test]
+    fn test_name_index_many_names() {
+        let mut strings = StringTable::new();
+        let names: Vec<_> = (0..100).map(|i| format!("name{}", i)).collect();
+        let ids: Vec<_> = names.iter().map(|name| strings.add(name.as_bytes())).collect();
+        let mut debug_str = EndianVec::new(LittleEndian);
+        let str_offsets = strings.write(&mut debug_str).unwrap();
+
+        let mut index = NameIndex::new();
+        let unit = index.add_comp_unit(DebugInfoOffset(0));
+        for (i, name) in names.iter().enumerate() {
+            let entry = NameEntry {
+                unit,
+                die_offset: UnitOffset(i),
+                tag: constants::DW_TAG_variable,
+            };
+            index.add(name, str_offsets.get(ids[i]), entry);
+        }
+        let mut debug_names = EndianVec::new(LittleEndian);
+        index.write(&mut debug_names).unwrap();
+
+        let debug_str = DebugStr::new(debug_str.slice(), LittleEndian);
+        let debug_names = DebugNames::new(debug_names.slice(), LittleEndian);
+        let read_index = debug_names.name_indexes().next().unwrap().unwrap();
+        assert_eq!(read_index.name_count(), 100);
+        assert_eq!(read_index.bucket_count(), 50);
+        for (i, name) in names.iter().enumerate() {
+            let found = read_index.find(&debug_str, name).unwrap().unwrap();
+            let entry = read_index.entries(&found).unwrap().next().unwrap().unwrap();
+            assert_eq!(entry.tag(), constants::DW_TAG_variable);
+            assert_eq!(entry.compile_unit(), None);
+            assert_eq!(
+                read_index.entry_die_offset(&entry),
+                Ok(Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(i))))
+            );
+        }
+    }
+}
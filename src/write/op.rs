@@ -0,0 +1,305 @@
+use arch::Register;
+use constants;
+use op::Operation;
+use parser::Format;
+use reader::{Reader, ReaderOffset};
+use vec::Vec;
+use write::{Address, ConvertError, ConvertResult, Result, Writer};
+use write::writer::{sleb128, uleb128};
+
+/// The bytecode for a DWARF expression or location description.
+///
+/// Operations are appended using the `op_*` methods. Addresses are not
+/// encoded until the expression is written, since their encoding depends on
+/// the address size and endianity of the section. Addresses are written using
+/// `Writer::write_address`, so symbol addresses may be relocated. The offsets
+/// of the encoded addresses can also be obtained with `address_offsets`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Expression {
+    bytecode: Vec<u8>,
+    // The offset in `bytecode` at which each address is inserted.
+    addresses: Vec<(usize, Address)>,
+}
+
+impl Expression {
+    /// Create an empty expression.
+    pub fn new() -> Self {
+        Expression::default()
+    }
+
+    /// Create an expression from raw bytecode.
+    ///
+    /// The bytecode must not contain any address operands, since these
+    /// depend on the address size.
+    pub fn raw(bytecode: Vec<u8>) -> Self {
+        Expression {
+            bytecode,
+            addresses: Vec::new(),
+        }
+    }
+
+    /// Return the size in bytes of the encoded expression.
+    pub fn size(&self, address_size: u8) -> usize {
+        self.bytecode.len() + self.addresses.len() * address_size as usize
+    }
+
+    /// Return true if the expression contains no operations.
+    pub fn is_empty(&self) -> bool {
+        self.bytecode.is_empty()
+    }
+
+    /// Return the offsets within the encoded expression of each address
+    /// operand, along with the address.
+    pub fn address_offsets(&self, address_size: u8) -> Vec<(usize, Address)> {
+        self.addresses
+            .iter()
+            .enumerate()
+            .map(|(i, &(offset, address))| (offset + i * address_size as usize, address))
+            .collect()
+    }
+
+    fn push_uleb128(&mut self, val: u64) {
+        let mut bytes = [0u8; 10];
+        let len = uleb128(&mut bytes, val);
+        self.bytecode.extend_from_slice(&bytes[..len]);
+    }
+
+    fn push_sleb128(&mut self, val: i64) {
+        let mut bytes = [0u8; 10];
+        let len = sleb128(&mut bytes, val);
+        self.bytecode.extend_from_slice(&bytes[..len]);
+    }
+
+    /// Add an operation that has no operands, such as `DW_OP_deref` or
+    /// `DW_OP_stack_value`.
+    pub fn op(&mut self, opcode: constants::DwOp) {
+        self.bytecode.push(opcode.0);
+    }
+
+    /// Add a `DW_OP_addr` operation.
+    pub fn op_addr(&mut self, address: Address) {
+        self.op(constants::DW_OP_addr);
+        self.addresses.push((self.bytecode.len(), address));
+    }
+
+    /// Add an operation that pushes an unsigned constant.
+    ///
+    /// This uses `DW_OP_lit*` for small values, and `DW_OP_constu` otherwise.
+    pub fn op_constu(&mut self, value: u64) {
+        if value < 32 {
+            self.bytecode.push(constants::DW_OP_lit0.0 + value as u8);
+        } else {
+            self.op(constants::DW_OP_constu);
+            self.push_uleb128(value);
+        }
+    }
+
+    /// Add a `DW_OP_consts` operation.
+    pub fn op_consts(&mut self, value: i64) {
+        self.op(constants::DW_OP_consts);
+        self.push_sleb128(value);
+    }
+
+    /// Add an operation that describes a location in a register.
+    ///
+    /// This uses `DW_OP_reg*` for small register numbers, and `DW_OP_regx`
+    /// otherwise.
+    pub fn op_reg(&mut self, register: Register) {
+        if register.0 < 32 {
+            self.bytecode.push(constants::DW_OP_reg0.0 + register.0 as u8);
+        } else {
+            self.op(constants::DW_OP_regx);
+            self.push_uleb128(register.0.into());
+        }
+    }
+
+    /// Add an operation that pushes the value of a register plus an offset.
+    ///
+    /// This uses `DW_OP_breg*` for small register numbers, and `DW_OP_bregx`
+    /// otherwise.
+    pub fn op_breg(&mut self, register: Register, offset: i64) {
+        if register.0 < 32 {
+            self.bytecode.push(constants::DW_OP_breg0.0 + register.0 as u8);
+        } else {
+            self.op(constants::DW_OP_bregx);
+            self.push_uleb128(register.0.into());
+        }
+        self.push_sleb128(offset);
+    }
+
+    /// Add a `DW_OP_fbreg` operation.
+    pub fn op_fbreg(&mut self, offset: i64) {
+        self.op(constants::DW_OP_fbreg);
+        self.push_sleb128(offset);
+    }
+
+    /// Add a `DW_OP_plus_uconst` operation.
+    pub fn op_plus_uconst(&mut self, value: u64) {
+        self.op(constants::DW_OP_plus_uconst);
+        self.push_uleb128(value);
+    }
+
+    /// Add a `DW_OP_piece` operation.
+    pub fn op_piece(&mut self, size_in_bytes: u64) {
+        self.op(constants::DW_OP_piece);
+        self.push_uleb128(size_in_bytes);
+    }
+
+    /// Add a `DW_OP_bit_piece` operation.
+    pub fn op_bit_piece(&mut self, size_in_bits: u64, bit_offset: u64) {
+        self.op(constants::DW_OP_bit_piece);
+        self.push_uleb128(size_in_bits);
+        self.push_uleb128(bit_offset);
+    }
+
+    /// Create an expression by converting a read expression.
+    ///
+    /// `convert_address` is used to map the operands of `DW_OP_addr`
+    /// operations. Operations that refer to debugging information entries or
+    /// to the `.debug_addr` section are not supported.
+    pub fn from<R: Reader>(
+        expression: ::Expression<R>,
+        address_size: u8,
+        format: Format,
+        convert_address: &dyn Fn(u64) -> Option<Address>,
+    ) -> ConvertResult<Expression> {
+        let bytecode = expression.0;
+        let mut pc = bytecode.clone();
+        let mut result = Expression::new();
+        while !pc.is_empty() {
+            let start = pc.clone();
+            match Operation::parse(&mut pc, &bytecode, address_size, format)? {
+                Operation::TextRelativeOffset { offset } => {
+                    let address = convert_address(offset).ok_or(ConvertError::InvalidAddress)?;
+                    result.op_addr(address);
+                }
+                Operation::Deref { base_type, .. } |
+                Operation::Register { base_type, .. } |
+                Operation::TypedLiteral { base_type, .. } |
+                Operation::Convert { base_type } |
+                Operation::Reinterpret { base_type } if base_type.0 != R::Offset::from_u8(0) => {
+                    return Err(ConvertError::UnsupportedOperation);
+                }
+                Operation::Call { .. } |
+                Operation::ImplicitPointer { .. } |
+                Operation::ParameterRef { .. } |
+                Operation::AddressIndex { .. } |
+                Operation::ConstantIndex { .. } => {
+                    return Err(ConvertError::UnsupportedOperation);
+                }
+                _ => {
+                    let mut raw = start;
+                    let len = raw.len() - pc.len();
+                    raw.truncate(len)?;
+                    result.bytecode.extend_from_slice(&*raw.to_slice()?);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Write the expression.
+    pub fn write<W: Writer>(&self, w: &mut W, address_size: u8) -> Result<()> {
+        let mut offset = 0;
+        for &(address_offset, address) in &self.addresses {
+            w.write(&self.bytecode[offset..address_offset])?;
+            w.write_address(address, address_size)?;
+            offset = address_offset;
+        }
+        w.write(&self.bytecode[offset..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use reader::Reader;
+    use unit::UnitOffset;
+    use write::EndianVec;
+
+    #[test]
+    fn test_expression() {
+        let mut expression = Expression::new();
+        assert!(expression.is_empty());
+        expression.op_reg(Register(3));
+        expression.op_piece(4);
+        expression.op_reg(Register(40));
+        expression.op_piece(4);
+        expression.op_breg(Register(7), -8);
+        expression.op_breg(Register(33), 16);
+        expression.op_fbreg(-24);
+        expression.op_addr(Address::Constant(0x1234_5678));
+        expression.op_plus_uconst(200);
+        expression.op_constu(5);
+        expression.op_constu(500);
+        expression.op_consts(-500);
+        expression.op_addr(Address::Constant(0x8765_4321));
+        expression.op(constants::DW_OP_stack_value);
+        expression.op_bit_piece(3, 5);
+
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_u8(0xff).unwrap();
+        expression.write(&mut w, 4).unwrap();
+        let buf = &w.slice()[1..];
+        assert_eq!(buf.len(), expression.size(4));
+
+        for &(offset, address) in &expression.address_offsets(4) {
+            let mut bytes = EndianBuf::new(&buf[offset..offset + 4], LittleEndian);
+            assert_eq!(bytes.read_address(4).map(Address::Constant), Ok(address));
+        }
+
+        let expected = [
+            Operation::Register {
+                base_type: UnitOffset(0),
+                register: Register(3),
+            },
+            Operation::Piece {
+                size_in_bits: 32,
+                bit_offset: None,
+            },
+            Operation::Register {
+                base_type: UnitOffset(0),
+                register: Register(40),
+            },
+            Operation::Piece {
+                size_in_bits: 32,
+                bit_offset: None,
+            },
+            Operation::RegisterOffset {
+                register: Register(7),
+                offset: -8,
+            },
+            Operation::RegisterOffset {
+                register: Register(33),
+                offset: 16,
+            },
+            Operation::FrameOffset { offset: -24 },
+            Operation::TextRelativeOffset {
+                offset: 0x1234_5678,
+            },
+            Operation::PlusConstant { value: 200 },
+            Operation::Literal { value: 5 },
+            Operation::Literal { value: 500 },
+            Operation::Literal {
+                value: -500i64 as u64,
+            },
+            Operation::TextRelativeOffset {
+                offset: 0x8765_4321,
+            },
+            Operation::StackValue,
+            Operation::Piece {
+                size_in_bits: 3,
+                bit_offset: Some(5),
+            },
+        ];
+
+        let bytecode = EndianBuf::new(buf, LittleEndian);
+        let mut pc = bytecode;
+        for operation in &expected {
+            let parsed = Operation::parse(&mut pc, &bytecode, 4, Format::Dwarf32).unwrap();
+            assert_eq!(parsed, *operation);
+        }
+        assert!(pc.is_empty());
+    }
+}
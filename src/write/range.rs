@@ -0,0 +1,423 @@
+use constants;
+use rnglists::RangeListsOffset;
+use vec::Vec;
+use write::{Address, Error, Result, Writer};
+
+/// An address range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Range {
+    /// The beginning address of the range.
+    pub begin: Address,
+
+    /// The first address past the end of the range.
+    pub end: Address,
+}
+
+/// An identifier for a range list in a `RangeListTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RangeListId(usize);
+
+/// A table of range lists that will be written to a `.debug_ranges` or
+/// `.debug_rnglists` section.
+#[derive(Debug, Default, Clone)]
+pub struct RangeListTable {
+    lists: Vec<Vec<Range>>,
+}
+
+impl RangeListTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        RangeListTable::default()
+    }
+
+    /// Add a range list to the table, and return its id.
+    ///
+    /// If an identical range list was already added, then the existing id is
+    /// returned. Empty ranges are not written.
+    pub fn add(&mut self, ranges: Vec<Range>) -> RangeListId {
+        if let Some(index) = self.lists.iter().position(|l| *l == ranges) {
+            return RangeListId(index);
+        }
+        self.lists.push(ranges);
+        RangeListId(self.lists.len() - 1)
+    }
+
+    /// Write the range lists, using `.debug_ranges` for DWARF versions 2 to 4,
+    /// and `.debug_rnglists` for version 5.
+    ///
+    /// `base_address` is the base address of the unit that will refer to the
+    /// range lists, which is usually its `DW_AT_low_pc`, or 0 if it has none.
+    /// Ranges are encoded relative to this base address where possible.
+    /// Ranges that are relative to a different symbol are encoded using
+    /// base address selection entries or absolute addresses.
+    ///
+    /// Returns the offsets of the range lists, for use in `DW_AT_ranges`
+    /// attributes.
+    pub fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        version: u16,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<RangeListOffsets> {
+        if version < 2 || version > 5 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        if version < 5 {
+            self.write_ranges(w, address_size, base_address)
+        } else {
+            self.write_rnglists(w, address_size, base_address)
+        }
+    }
+
+    fn write_ranges<W: Writer>(
+        &self,
+        w: &mut W,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<RangeListOffsets> {
+        let max_address = !0 >> (64 - u64::from(address_size) * 8);
+        let mut offsets = Vec::new();
+        for ranges in &self.lists {
+            offsets.push(RangeListsOffset(w.len()));
+            let mut base = base_address;
+            if let Some(begin) = min_begin(ranges.iter().map(|r| (r.begin, r.end)), base) {
+                if begin.offset_from(base).is_none() {
+                    // Base address selection entry.
+                    w.write_udata(max_address, address_size)?;
+                    w.write_address(begin, address_size)?;
+                    base = begin;
+                }
+            }
+            for range in ranges.iter().filter(|r| !is_empty_range(r.begin, r.end)) {
+                let (begin, end) = match range_offsets(range.begin, range.end, base) {
+                    Some(offsets) => offsets,
+                    None => {
+                        // The range is relative to a different symbol.
+                        w.write_udata(max_address, address_size)?;
+                        w.write_address(range.begin, address_size)?;
+                        base = range.begin;
+                        let end = range
+                            .end
+                            .offset_from(base)
+                            .ok_or(Error::InvalidAddressRange)?;
+                        (0, end)
+                    }
+                };
+                w.write_udata(begin, address_size)?;
+                w.write_udata(end, address_size)?;
+            }
+            // End of list entry.
+            w.write_udata(0, address_size)?;
+            w.write_udata(0, address_size)?;
+        }
+        Ok(RangeListOffsets { offsets })
+    }
+
+    fn write_rnglists<W: Writer>(
+        &self,
+        w: &mut W,
+        address_size: u8,
+        base_address: Address,
+    ) -> Result<RangeListOffsets> {
+        let length_offset = write_lists_header(w, address_size)?;
+        let length_base = length_offset + 4;
+
+        let mut offsets = Vec::new();
+        for ranges in &self.lists {
+            offsets.push(RangeListsOffset(w.len()));
+            let count = ranges
+                .iter()
+                .filter(|r| !is_empty_range(r.begin, r.end))
+                .count();
+            let mut base = Some(base_address);
+            if let Some(begin) = min_begin(ranges.iter().map(|r| (r.begin, r.end)), base_address) {
+                if begin.offset_from(base_address).is_none() {
+                    base = None;
+                    if count > 1 {
+                        w.write_u8(constants::DW_RLE_base_address.0)?;
+                        w.write_address(begin, address_size)?;
+                        base = Some(begin);
+                    }
+                }
+            }
+            for range in ranges.iter().filter(|r| !is_empty_range(r.begin, r.end)) {
+                match base.and_then(|base| range_offsets(range.begin, range.end, base)) {
+                    Some((begin, end)) => {
+                        w.write_u8(constants::DW_RLE_offset_pair.0)?;
+                        w.write_uleb128(begin)?;
+                        w.write_uleb128(end)?;
+                    }
+                    None => match range.end.offset_from(range.begin) {
+                        Some(length) => {
+                            w.write_u8(constants::DW_RLE_start_length.0)?;
+                            w.write_address(range.begin, address_size)?;
+                            w.write_uleb128(length)?;
+                        }
+                        None => {
+                            w.write_u8(constants::DW_RLE_start_end.0)?;
+                            w.write_address(range.begin, address_size)?;
+                            w.write_address(range.end, address_size)?;
+                        }
+                    },
+                }
+            }
+            w.write_u8(constants::DW_RLE_end_of_list.0)?;
+        }
+
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(length_offset, length as u32)?;
+        Ok(RangeListOffsets { offsets })
+    }
+}
+
+/// The section offsets of the range lists in a `RangeListTable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeListOffsets {
+    offsets: Vec<RangeListsOffset>,
+}
+
+impl RangeListOffsets {
+    /// Return the offset of the given range list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not returned by the table that was written.
+    pub fn get(&self, id: RangeListId) -> RangeListsOffset {
+        self.offsets[id.0]
+    }
+}
+
+/// Return true if the range from `begin` to `end` is known to be empty.
+///
+/// Ranges with addresses relative to different symbols are never considered
+/// empty.
+pub(crate) fn is_empty_range(begin: Address, end: Address) -> bool {
+    match end.offset_from(begin) {
+        Some(length) => length == 0,
+        None => begin.offset_from(end).is_some(),
+    }
+}
+
+/// Return the offsets of `begin` and `end` relative to `base`, if both can be
+/// encoded as offsets.
+pub(crate) fn range_offsets(begin: Address, end: Address, base: Address) -> Option<(u64, u64)> {
+    Some((begin.offset_from(base)?, end.offset_from(base)?))
+}
+
+/// Return the lowest beginning address of the non-empty ranges that can be
+/// compared with `base`.
+pub(crate) fn min_begin<I>(ranges: I, base: Address) -> Option<Address>
+where
+    I: Iterator<Item = (Address, Address)>,
+{
+    let mut min = None;
+    for (begin, end) in ranges {
+        if is_empty_range(begin, end) {
+            continue;
+        }
+        if begin.offset_from(base).is_none() && base.offset_from(begin).is_none() {
+            continue;
+        }
+        min = match min {
+            Some(min) if begin.offset_from(min).is_some() => Some(min),
+            _ => Some(begin),
+        };
+    }
+    min
+}
+
+/// Write the header of a `.debug_rnglists` or `.debug_loclists` section,
+/// using the 32-bit DWARF format and no offset table.
+///
+/// Returns the offset of the unit length, which must be filled in later.
+pub(crate) fn write_lists_header<W: Writer>(w: &mut W, address_size: u8) -> Result<usize> {
+    let length_offset = w.len();
+    w.write_u32(0)?;
+    w.write_u16(5)?;
+    w.write_u8(address_size)?;
+    // Segment selector size.
+    w.write_u8(0)?;
+    // Offset entry count.
+    w.write_u32(0)?;
+    Ok(length_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use fallible_iterator::FallibleIterator;
+    use rnglists::{self, DebugRanges, DebugRngLists, RangeLists};
+    use write::EndianVec;
+
+    #[test]
+    fn test_range_list_table() {
+        let mut table = RangeListTable::new();
+        let list1 = vec![
+            Range {
+                begin: Address::Constant(0x1000),
+                end: Address::Constant(0x1100),
+            },
+            Range {
+                begin: Address::Constant(0x1200),
+                end: Address::Constant(0x1200),
+            },
+            Range {
+                begin: Address::Constant(0x2000),
+                end: Address::Constant(0x2010),
+            },
+        ];
+        let id1 = table.add(list1.clone());
+        assert_eq!(table.add(list1), id1);
+        let id2 = table.add(vec![
+            Range {
+                begin: Address::Constant(0x800),
+                end: Address::Constant(0x900),
+            },
+        ]);
+        let id3 = table.add(vec![
+            Range {
+                begin: Address::Constant(0x100),
+                end: Address::Constant(0x200),
+            },
+            Range {
+                begin: Address::Constant(0x3000),
+                end: Address::Constant(0x3004),
+            },
+        ]);
+
+        for &version in &[2, 4, 5] {
+            for &address_size in &[4, 8] {
+                let mut w = EndianVec::new(LittleEndian);
+                let offsets = table.write(&mut w, version, address_size, Address::Constant(0x1000)).unwrap();
+                let buf = w.into_vec();
+                let (debug_ranges, debug_rnglists) = if version < 5 {
+                    (&buf[..], &[][..])
+                } else {
+                    (&[][..], &buf[..])
+                };
+                let range_lists = RangeLists::new(
+                    DebugRanges::new(debug_ranges, LittleEndian),
+                    DebugRngLists::new(debug_rnglists, LittleEndian),
+                ).unwrap();
+                let read = |id| -> Vec<rnglists::Range> {
+                    let ranges: rnglists::RngListIter<EndianBuf<LittleEndian>> = range_lists
+                        .ranges(offsets.get(id), version, address_size, 0x1000)
+                        .unwrap();
+                    ranges.collect().unwrap()
+                };
+                assert_eq!(
+                    read(id1),
+                    vec![
+                        rnglists::Range {
+                            begin: 0x1000,
+                            end: 0x1100,
+                        },
+                        rnglists::Range {
+                            begin: 0x2000,
+                            end: 0x2010,
+                        },
+                    ]
+                );
+                assert_eq!(
+                    read(id2),
+                    vec![
+                        rnglists::Range {
+                            begin: 0x800,
+                            end: 0x900,
+                        },
+                    ]
+                );
+                assert_eq!(
+                    read(id3),
+                    vec![
+                        rnglists::Range {
+                            begin: 0x100,
+                            end: 0x200,
+                        },
+                        rnglists::Range {
+                            begin: 0x3000,
+                            end: 0x3004,
+                        },
+                    ]
+                );
+            }
+        }
+
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write(&mut w, 6, 8, Address::Constant(0)),
+            Err(Error::UnsupportedVersion(6))
+        );
+    }
+
+    #[test]
+    fn test_range_list_symbol_base() {
+        let symbol = |addend| Address::Symbol { symbol: 0, addend };
+        let mut table = RangeListTable::new();
+        let id = table.add(vec![
+            Range {
+                begin: symbol(0x10),
+                end: symbol(0x20),
+            },
+            Range {
+                begin: symbol(0x30),
+                end: symbol(0x30),
+            },
+            Range {
+                begin: Address::Constant(0x5000),
+                end: Address::Constant(0x5008),
+            },
+        ]);
+
+        // The symbol is resolved to 0x1000 when reading.
+        for &version in &[4, 5] {
+            let mut w = EndianVec::new(LittleEndian);
+            let offsets = table.write(&mut w, version, 8, symbol(0)).unwrap();
+            let buf = w.into_vec();
+            let (debug_ranges, debug_rnglists) = if version < 5 {
+                (&buf[..], &[][..])
+            } else {
+                (&[][..], &buf[..])
+            };
+            let range_lists = RangeLists::new(
+                DebugRanges::new(debug_ranges, LittleEndian),
+                DebugRngLists::new(debug_rnglists, LittleEndian),
+            ).unwrap();
+            let ranges: rnglists::RngListIter<EndianBuf<LittleEndian>> = range_lists
+                .ranges(offsets.get(id), version, 8, 0x1000)
+                .unwrap();
+            assert_eq!(
+                ranges.collect::<Vec<_>>().unwrap(),
+                vec![
+                    rnglists::Range {
+                        begin: 0x1010,
+                        end: 0x1020,
+                    },
+                    rnglists::Range {
+                        begin: 0x5000,
+                        end: 0x5008,
+                    },
+                ]
+            );
+        }
+
+        // Writing a symbol address requires a writer that supports it.
+        let mut table = RangeListTable::new();
+        table.add(vec![
+            Range {
+                begin: symbol(0x10),
+                end: symbol(0x20),
+            },
+        ]);
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            table.write(&mut w, 4, 8, Address::Constant(0)),
+            Err(Error::UnsupportedSymbolAddress)
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use write::Writer;
+
+/// The writers for the DWARF sections that are produced from a `Dwarf`.
+///
+/// Range lists and location lists are written to `.debug_ranges` and
+/// `.debug_loc` for units with DWARF versions 2 to 4, and to
+/// `.debug_rnglists` and `.debug_loclists` for version 5.
+#[derive(Debug, Clone)]
+pub struct Sections<W: Writer> {
+    /// The `.debug_abbrev` section.
+    pub debug_abbrev: W,
+    /// The `.debug_info` section.
+    pub debug_info: W,
+    /// The `.debug_line` section.
+    pub debug_line: W,
+    /// The `.debug_line_str` section.
+    pub debug_line_str: W,
+    /// The `.debug_loc` section.
+    pub debug_loc: W,
+    /// The `.debug_loclists` section.
+    pub debug_loclists: W,
+    /// The `.debug_ranges` section.
+    pub debug_ranges: W,
+    /// The `.debug_rnglists` section.
+    pub debug_rnglists: W,
+    /// The `.debug_str` section.
+    pub debug_str: W,
+}
+
+impl<W: Writer + Clone> Sections<W> {
+    /// Create a new `Sections` using clones of the given `section` writer.
+    pub fn new(section: W) -> Self {
+        Sections {
+            debug_abbrev: section.clone(),
+            debug_info: section.clone(),
+            debug_line: section.clone(),
+            debug_line_str: section.clone(),
+            debug_loc: section.clone(),
+            debug_loclists: section.clone(),
+            debug_ranges: section.clone(),
+            debug_rnglists: section.clone(),
+            debug_str: section,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use btree_map::BTreeMap;
+use str::{DebugLineStrOffset, DebugStrOffset};
+use vec::Vec;
+use write::{Result, Writer};
+
+macro_rules! define_string_table {
+    (
+        $name:ident, $name_doc:expr,
+        $id:ident, $id_doc:expr,
+        $offsets:ident, $offsets_doc:expr,
+        $offset:ident
+    ) => {
+        #[doc = $id_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $id(usize);
+
+        #[doc = $name_doc]
+        ///
+        /// Identical strings are only stored once. Offsets are not assigned
+        /// until the table is written.
+        #[derive(Debug, Default)]
+        pub struct $name {
+            strings: Vec<Vec<u8>>,
+            ids: BTreeMap<Vec<u8>, $id>,
+        }
+
+        impl $name {
+            /// Create an empty string table.
+            pub fn new() -> Self {
+                $name::default()
+            }
+
+            /// Add a string to the table, and return its id.
+            ///
+            /// If the string was already added, then the existing id is
+            /// returned.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `bytes` contains a null byte.
+            pub fn add<T>(&mut self, bytes: T) -> $id
+            where
+                T: Into<Vec<u8>>,
+            {
+                let bytes = bytes.into();
+                assert!(!bytes.contains(&0));
+                if let Some(&id) = self.ids.get(&bytes) {
+                    return id;
+                }
+                let id = $id(self.strings.len());
+                self.strings.push(bytes.clone());
+                self.ids.insert(bytes, id);
+                id
+            }
+
+            /// Return the id of a string that was previously added to the
+            /// table, or `None` if it has not been added.
+            ///
+            /// The id can be used with the offsets returned by `write` to find
+            /// the section offset of the string.
+            pub fn find(&self, bytes: &[u8]) -> Option<$id> {
+                self.ids.get(bytes).cloned()
+            }
+
+            /// Return the number of strings in the table.
+            pub fn count(&self) -> usize {
+                self.strings.len()
+            }
+
+            /// Get a reference to a string, not including the null terminator.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `id` is invalid.
+            pub fn get(&self, id: $id) -> &[u8] {
+                &self.strings[id.0]
+            }
+
+            /// Write the strings, and return their offsets.
+            pub fn write<W: Writer>(&self, w: &mut W) -> Result<$offsets> {
+                let mut offsets = Vec::with_capacity(self.strings.len());
+                for string in &self.strings {
+                    offsets.push($offset(w.len()));
+                    w.write(string)?;
+                    w.write_u8(0)?;
+                }
+                Ok($offsets { offsets })
+            }
+        }
+
+        #[doc = $offsets_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $offsets {
+            offsets: Vec<$offset>,
+        }
+
+        impl $offsets {
+            /// Return the offset of the given string.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `id` was not returned by the table that was written.
+            pub fn get(&self, id: $id) -> $offset {
+                self.offsets[id.0]
+            }
+        }
+    };
+}
+
+define_string_table!(
+    StringTable,
+    "A table of strings that will be written to the `.debug_str` section.",
+    StringId,
+    "An identifier for a string in a `StringTable`.",
+    DebugStrOffsets,
+    "The section offsets of the strings in a `StringTable`.",
+    DebugStrOffset
+);
+
+define_string_table!(
+    LineStringTable,
+    "A table of strings that will be written to the `.debug_line_str` section.",
+    LineStringId,
+    "An identifier for a string in a `LineStringTable`.",
+    DebugLineStrOffsets,
+    "The section offsets of the strings in a `LineStringTable`.",
+    DebugLineStrOffset
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::{EndianBuf, LittleEndian};
+    use str::DebugStr;
+    use write::EndianVec;
+
+    #[test]
+    fn test_string_table() {
+        let mut strings = StringTable::new();
+        let id1 = strings.add(&b"one"[..]);
+        let id2 = strings.add(b"two".to_vec());
+        assert_eq!(strings.add(&b"one"[..]), id1);
+        assert_eq!(strings.add(b"two".to_vec()), id2);
+        assert_eq!(strings.count(), 2);
+        assert_eq!(strings.get(id1), b"one");
+        assert_eq!(strings.get(id2), b"two");
+        assert_eq!(strings.find(b"two"), Some(id2));
+        assert_eq!(strings.find(b"three"), None);
+
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_u8(0).unwrap();
+        let offsets = strings.write(&mut w).unwrap();
+        assert_eq!(w.slice(), b"\0one\0two\0");
+        assert_eq!(offsets.get(id1), DebugStrOffset(1));
+        assert_eq!(offsets.get(id2), DebugStrOffset(5));
+
+        let debug_str = DebugStr::new(w.slice(), LittleEndian);
+        assert_eq!(
+            debug_str.get_str(offsets.get(id2)),
+            Ok(EndianBuf::new(b"two", LittleEndian))
+        );
+
+        let mut iter = debug_str.strings();
+        while let Some((offset, string)) = iter.next().unwrap() {
+            if let Some(id) = strings.find(string.buf()) {
+                assert_eq!(offsets.get(id), offset);
+            }
+        }
+        assert_eq!(iter.offset(), DebugStrOffset(w.len()));
+    }
+
+    #[test]
+    fn test_line_string_table() {
+        let mut strings = LineStringTable::new();
+        let id1 = strings.add(&b"/tmp"[..]);
+        let id2 = strings.add(&b"foo.c"[..]);
+        let mut w = EndianVec::new(LittleEndian);
+        let offsets = strings.write(&mut w).unwrap();
+        assert_eq!(w.slice(), b"/tmp\0foo.c\0");
+        assert_eq!(offsets.get(id1), DebugLineStrOffset(0));
+        assert_eq!(offsets.get(id2), DebugLineStrOffset(5));
+    }
+}
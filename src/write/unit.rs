@@ -0,0 +1,1180 @@
+use btree_map::BTreeMap;
+use constants;
+use endianity::Endianity;
+use line::DebugLineOffset;
+use reader::{Reader, ReaderOffset};
+use std::slice;
+use unit::{DebugInfoOffset, DebugTypeSignature, UnitOffset};
+use vec::Vec;
+use write::{Address, ConvertError, ConvertResult, DebugLineStrOffsets, DebugStrOffsets, Error,
+            Expression, FileId, LineProgram, LineStringId, Location, LocationListId,
+            LocationListOffsets, LocationListTable, Range, RangeListId, RangeListOffsets,
+            RangeListTable, Result, Sections, StringId, StringTable, Writer};
+use write::abbrev::{Abbreviation, AbbreviationTable, AttributeSpecification};
+use write::writer::uleb128;
+
+/// An identifier for a unit in a `UnitTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnitId(usize);
+
+/// A table of units that will be written to the `.debug_info` section.
+#[derive(Debug, Default)]
+pub struct UnitTable {
+    units: Vec<Unit>,
+}
+
+impl UnitTable {
+    /// Create a new empty unit table.
+    pub fn new() -> Self {
+        UnitTable::default()
+    }
+
+    /// Add a unit to the table, and return its id.
+    pub fn add(&mut self, unit: Unit) -> UnitId {
+        self.units.push(unit);
+        UnitId(self.units.len() - 1)
+    }
+
+    /// Return the number of units.
+    pub fn count(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Return the id of the unit with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the number of units.
+    pub fn id(&self, index: usize) -> UnitId {
+        assert!(index < self.units.len());
+        UnitId(index)
+    }
+
+    /// Get a reference to a unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid.
+    pub fn get(&self, id: UnitId) -> &Unit {
+        &self.units[id.0]
+    }
+
+    /// Get a mutable reference to a unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid.
+    pub fn get_mut(&mut self, id: UnitId) -> &mut Unit {
+        &mut self.units[id.0]
+    }
+
+    /// Write the units to the given sections.
+    ///
+    /// The `.debug_abbrev` entries are generated from the attributes of each
+    /// entry, and are shared between all of the units. The line number
+    /// program, range lists and location lists of each unit are also written.
+    ///
+    /// `line_strings` and `strings` are the offsets returned when writing the
+    /// string tables that are referred to by `AttributeValue::LineStringRef`
+    /// and `AttributeValue::StringRef`.
+    ///
+    /// Returns the `.debug_info` offsets of the units and their entries.
+    pub fn write<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        line_strings: &DebugLineStrOffsets,
+        strings: &DebugStrOffsets,
+    ) -> Result<DebugInfoOffsets> {
+        let abbrev_offset = sections.debug_abbrev.len();
+        let mut abbrevs = AbbreviationTable::default();
+        let mut offsets = DebugInfoOffsets { units: Vec::new() };
+        let mut debug_info_refs = Vec::new();
+        for unit in &self.units {
+            let unit_offsets = unit.write(
+                sections,
+                abbrev_offset,
+                &mut abbrevs,
+                line_strings,
+                strings,
+                &mut debug_info_refs,
+            )?;
+            offsets.units.push(unit_offsets);
+        }
+        abbrevs.write(&mut sections.debug_abbrev)?;
+
+        // Now that all entry offsets are known, fix up the references
+        // between units.
+        for (offset, size, unit_id, entry_id) in debug_info_refs {
+            let entry_offset = offsets.entry(unit_id, entry_id);
+            sections
+                .debug_info
+                .write_udata_at(offset, entry_offset.0 as u64, size)?;
+        }
+        Ok(offsets)
+    }
+
+    /// Create a unit table by converting the compilation units in the
+    /// `.debug_info` section of a read `Dwarf`.
+    ///
+    /// `convert_address` is used to map each address in the read DWARF to
+    /// an address in the written DWARF. References between entries are
+    /// converted to `UnitEntryId`s. `DW_AT_sibling` attributes, and
+    /// attributes that give the base of offset tables in other sections,
+    /// are not converted.
+    ///
+    /// Strings in the `.debug_str` section are added to `strings`.
+    pub fn from<R: Reader>(
+        dwarf: &::Dwarf<R>,
+        strings: &mut StringTable,
+        convert_address: &dyn Fn(u64) -> Option<Address>,
+    ) -> ConvertResult<UnitTable> {
+        let mut units = UnitTable::new();
+        let mut entry_ids = BTreeMap::new();
+
+        // Create all of the entries first, so that references to entries
+        // that appear later in the section can be converted.
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let unit_id = UnitId(units.count());
+            let read_unit = dwarf.unit(header)?;
+            let unit = Unit::convert_entries(&read_unit, unit_id, &mut entry_ids)?;
+            units.add(unit);
+        }
+
+        let mut headers = dwarf.units();
+        let mut index = 0;
+        while let Some(header) = headers.next()? {
+            let unit_id = UnitId(index);
+            let read_unit = dwarf.unit(header)?;
+            let mut context = ConvertContext {
+                dwarf,
+                read_unit: &read_unit,
+                unit_id,
+                unit: units.get_mut(unit_id),
+                entry_ids: &entry_ids,
+                strings: &mut *strings,
+                files: Vec::new(),
+                convert_address,
+            };
+            context.convert_attributes()?;
+            index += 1;
+        }
+        Ok(units)
+    }
+}
+
+/// An identifier for an entry in a `Unit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnitEntryId(usize);
+
+/// A compilation unit and its debugging information entries.
+#[derive(Debug)]
+pub struct Unit {
+    version: u16,
+    address_size: u8,
+    /// The line number program referenced by `AttributeValue::LineProgramRef`.
+    pub line_program: Option<LineProgram>,
+    /// The range lists referenced by `AttributeValue::RangeListRef`.
+    pub ranges: RangeListTable,
+    /// The location lists referenced by `AttributeValue::LocationListRef`.
+    pub locations: LocationListTable,
+    entries: Vec<DebuggingInformationEntry>,
+    root: UnitEntryId,
+}
+
+impl Unit {
+    /// Create a new unit with a `DW_TAG_compile_unit` root entry.
+    pub fn new(version: u16, address_size: u8) -> Self {
+        Unit::with_root_tag(version, address_size, constants::DW_TAG_compile_unit)
+    }
+
+    /// Create a new unit with a root entry that has the given tag.
+    pub fn with_root_tag(version: u16, address_size: u8, tag: constants::DwTag) -> Self {
+        Unit {
+            version,
+            address_size,
+            line_program: None,
+            ranges: RangeListTable::new(),
+            locations: LocationListTable::new(),
+            entries: vec![DebuggingInformationEntry::new(None, tag)],
+            root: UnitEntryId(0),
+        }
+    }
+
+    /// Return the DWARF version of the unit.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the size in bytes of addresses in the unit.
+    pub fn address_size(&self) -> u8 {
+        self.address_size
+    }
+
+    /// Return the id of the root entry.
+    pub fn root(&self) -> UnitEntryId {
+        self.root
+    }
+
+    /// Return the number of entries in the unit.
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Add a new entry as the last child of `parent`, and return its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is invalid.
+    pub fn add(&mut self, parent: UnitEntryId, tag: constants::DwTag) -> UnitEntryId {
+        let id = UnitEntryId(self.entries.len());
+        self.entries[parent.0].children.push(id);
+        self.entries
+            .push(DebuggingInformationEntry::new(Some(parent), tag));
+        id
+    }
+
+    /// Get a reference to an entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid.
+    pub fn get(&self, id: UnitEntryId) -> &DebuggingInformationEntry {
+        &self.entries[id.0]
+    }
+
+    /// Get a mutable reference to an entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid.
+    pub fn get_mut(&mut self, id: UnitEntryId) -> &mut DebuggingInformationEntry {
+        &mut self.entries[id.0]
+    }
+
+    /// Write the unit and the sections that it refers to.
+    ///
+    /// References to entries in other units are written as zero, and the
+    /// locations that must be fixed up are added to `debug_info_refs`.
+    fn write<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        abbrev_offset: usize,
+        abbrevs: &mut AbbreviationTable,
+        line_strings: &DebugLineStrOffsets,
+        strings: &DebugStrOffsets,
+        debug_info_refs: &mut Vec<(usize, u8, UnitId, UnitEntryId)>,
+    ) -> Result<UnitOffsets> {
+        if self.version < 2 || self.version > 5 {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+
+        let line_program = match self.line_program {
+            Some(ref line_program) => {
+                let offset = DebugLineOffset(sections.debug_line.len());
+                line_program.write(&mut sections.debug_line)?;
+                Some(offset)
+            }
+            None => None,
+        };
+
+        // Range lists and location lists are encoded relative to the base
+        // address of the unit.
+        let base_address = match self.get(self.root).get(constants::DW_AT_low_pc) {
+            Some(&AttributeValue::Address(address)) => address,
+            _ => Address::Constant(0),
+        };
+        let (debug_ranges, debug_loc) = if self.version < 5 {
+            (&mut sections.debug_ranges, &mut sections.debug_loc)
+        } else {
+            (&mut sections.debug_rnglists, &mut sections.debug_loclists)
+        };
+        let range_lists = self.ranges
+            .write(debug_ranges, self.version, self.address_size, base_address)?;
+        let loc_lists = self.locations
+            .write(debug_loc, self.version, self.address_size, base_address)?;
+
+        let w = &mut sections.debug_info;
+        let offset = w.len();
+        w.write_u32(0)?;
+        let length_base = w.len();
+        if abbrev_offset > 0xffff_ffff {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u16(self.version)?;
+        if self.version >= 5 {
+            let unit_type = if self.get(self.root).tag() == constants::DW_TAG_partial_unit {
+                constants::DW_UT_partial
+            } else {
+                constants::DW_UT_compile
+            };
+            w.write_u8(unit_type.0)?;
+            w.write_u8(self.address_size)?;
+            w.write_u32(abbrev_offset as u32)?;
+        } else {
+            w.write_u32(abbrev_offset as u32)?;
+            w.write_u8(self.address_size)?;
+        }
+
+        let mut context = WriteContext {
+            unit: self,
+            unit_offset: offset,
+            line_program,
+            range_lists: &range_lists,
+            loc_lists: &loc_lists,
+            line_strings,
+            strings,
+            entry_offsets: vec![UnitOffset(0); self.entries.len()],
+            unit_refs: Vec::new(),
+            debug_info_refs,
+        };
+        context.write_entry(w, abbrevs, self.root)?;
+
+        let length = (w.len() - length_base) as u64;
+        if length > 0xffff_fff0 {
+            return Err(Error::ValueTooLarge);
+        }
+        w.write_u32_at(offset, length as u32)?;
+
+        for (ref_offset, entry_id) in context.unit_refs {
+            let entry_offset = context.entry_offsets[entry_id.0].0;
+            w.write_u32_at(ref_offset, entry_offset as u32)?;
+        }
+
+        Ok(UnitOffsets {
+            unit: DebugInfoOffset(offset),
+            entries: context.entry_offsets,
+        })
+    }
+
+    /// Create a unit containing the entries of `read_unit`, without their
+    /// attributes.
+    ///
+    /// The ids of the entries are allocated in depth-first order, and the
+    /// mapping from the `.debug_info` offset of each entry is added to
+    /// `entry_ids`.
+    fn convert_entries<R: Reader>(
+        read_unit: &::Unit<R>,
+        unit_id: UnitId,
+        entry_ids: &mut BTreeMap<u64, (UnitId, UnitEntryId)>,
+    ) -> ConvertResult<Unit> {
+        let header = &read_unit.header;
+        let mut unit = None;
+        let mut parents: Vec<UnitEntryId> = Vec::new();
+        let mut depth = 0;
+        let mut entries = read_unit.entries();
+        while let Some((delta, entry)) = entries.next_dfs()? {
+            depth += delta;
+            if depth <= 0 && unit.is_some() {
+                return Err(ConvertError::MultipleUnitEntries);
+            }
+            let unit = unit.get_or_insert_with(|| {
+                Unit::with_root_tag(header.version(), header.address_size(), entry.tag())
+            });
+            let id = if depth <= 0 {
+                unit.root()
+            } else {
+                parents.truncate(depth as usize);
+                let parent = parents[depth as usize - 1];
+                unit.add(parent, entry.tag())
+            };
+            parents.push(id);
+            let offset = entry.offset().to_debug_info_offset(header);
+            entry_ids.insert(offset.0.into_u64(), (unit_id, id));
+        }
+        unit.ok_or_else(|| ::Error::MissingUnitDie.into())
+    }
+}
+
+/// A Debugging Information Entry (DIE) in a `Unit`.
+#[derive(Debug)]
+pub struct DebuggingInformationEntry {
+    parent: Option<UnitEntryId>,
+    tag: constants::DwTag,
+    attrs: Vec<Attribute>,
+    children: Vec<UnitEntryId>,
+}
+
+impl DebuggingInformationEntry {
+    fn new(parent: Option<UnitEntryId>, tag: constants::DwTag) -> Self {
+        DebuggingInformationEntry {
+            parent,
+            tag,
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Return the id of the parent entry, or `None` for the root entry.
+    pub fn parent(&self) -> Option<UnitEntryId> {
+        self.parent
+    }
+
+    /// Return the tag of this entry.
+    pub fn tag(&self) -> constants::DwTag {
+        self.tag
+    }
+
+    /// Iterate over the attributes of this entry.
+    pub fn attrs(&self) -> slice::Iter<'_, Attribute> {
+        self.attrs.iter()
+    }
+
+    /// Get the value of the attribute with the given name.
+    pub fn get(&self, name: constants::DwAt) -> Option<&AttributeValue> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| &attr.value)
+    }
+
+    /// Get a mutable reference to the value of the attribute with the given
+    /// name.
+    pub fn get_mut(&mut self, name: constants::DwAt) -> Option<&mut AttributeValue> {
+        self.attrs
+            .iter_mut()
+            .find(|attr| attr.name == name)
+            .map(|attr| &mut attr.value)
+    }
+
+    /// Set the value of the attribute with the given name, replacing any
+    /// existing value.
+    pub fn set(&mut self, name: constants::DwAt, value: AttributeValue) {
+        if let Some(old) = self.get_mut(name) {
+            *old = value;
+            return;
+        }
+        self.attrs.push(Attribute { name, value });
+    }
+
+    /// Delete the attribute with the given name.
+    pub fn delete(&mut self, name: constants::DwAt) {
+        self.attrs.retain(|attr| attr.name != name);
+    }
+
+    /// Iterate over the ids of the children of this entry.
+    pub fn children(&self) -> slice::Iter<'_, UnitEntryId> {
+        self.children.iter()
+    }
+}
+
+/// An attribute in a `DebuggingInformationEntry`, consisting of a name and
+/// associated value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    name: constants::DwAt,
+    value: AttributeValue,
+}
+
+impl Attribute {
+    /// Get this attribute's name.
+    pub fn name(&self) -> constants::DwAt {
+        self.name
+    }
+
+    /// Get this attribute's value.
+    pub fn get(&self) -> &AttributeValue {
+        &self.value
+    }
+}
+
+/// The value of an attribute in a `DebuggingInformationEntry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeValue {
+    /// "Refers to some location in the address space of the described
+    /// program."
+    Address(Address),
+
+    /// A slice of an arbitrary number of bytes.
+    Block(Vec<u8>),
+
+    /// A one byte constant data value.
+    Data1(u8),
+
+    /// A two byte constant data value.
+    Data2(u16),
+
+    /// A four byte constant data value.
+    Data4(u32),
+
+    /// An eight byte constant data value.
+    Data8(u64),
+
+    /// A signed integer constant.
+    Sdata(i64),
+
+    /// An unsigned integer constant.
+    Udata(u64),
+
+    /// "The information bytes contain a DWARF expression (see Section 2.5) or
+    /// location description (see Section 2.6)."
+    Exprloc(Expression),
+
+    /// A boolean that indicates presence or absence of the attribute.
+    Flag(bool),
+
+    /// A reference to another entry in the same unit.
+    UnitRef(UnitEntryId),
+
+    /// A reference to an entry in any unit.
+    DebugInfoRef(UnitId, UnitEntryId),
+
+    /// A reference to the unit's line number program.
+    LineProgramRef,
+
+    /// A reference to a location list in the unit's location list table.
+    LocationListRef(LocationListId),
+
+    /// A type signature.
+    DebugTypesRef(DebugTypeSignature),
+
+    /// A reference to a range list in the unit's range list table.
+    RangeListRef(RangeListId),
+
+    /// A string, not including the null terminator.
+    String(Vec<u8>),
+
+    /// A reference to a string in the `.debug_str` section.
+    StringRef(StringId),
+
+    /// A reference to a string in the `.debug_line_str` section.
+    LineStringRef(LineStringId),
+
+    /// The value of a `DW_AT_encoding` attribute.
+    Encoding(constants::DwAte),
+
+    /// The value of a `DW_AT_decimal_sign` attribute.
+    DecimalSign(constants::DwDs),
+
+    /// The value of a `DW_AT_endianity` attribute.
+    Endianity(constants::DwEnd),
+
+    /// The value of a `DW_AT_accessibility` attribute.
+    Accessibility(constants::DwAccess),
+
+    /// The value of a `DW_AT_visibility` attribute.
+    Visibility(constants::DwVis),
+
+    /// The value of a `DW_AT_virtuality` attribute.
+    Virtuality(constants::DwVirtuality),
+
+    /// The value of a `DW_AT_language` attribute.
+    Language(constants::DwLang),
+
+    /// The value of a `DW_AT_language_name` attribute.
+    LanguageName(constants::DwLname),
+
+    /// The value of a `DW_AT_address_class` attribute.
+    AddressClass(constants::DwAddr),
+
+    /// The value of a `DW_AT_identifier_case` attribute.
+    IdentifierCase(constants::DwId),
+
+    /// The value of a `DW_AT_calling_convention` attribute.
+    CallingConvention(constants::DwCc),
+
+    /// The value of a `DW_AT_inline` attribute.
+    Inline(constants::DwInl),
+
+    /// The value of a `DW_AT_ordering` attribute.
+    Ordering(constants::DwOrd),
+
+    /// A file in the unit's line number program, or `None` for no file.
+    FileIndex(Option<FileId>),
+}
+
+impl AttributeValue {
+    /// Return the form that will be used to encode this value.
+    ///
+    /// Constants are encoded using the smallest suitable form.
+    fn form(&self, version: u16, address_size: u8) -> Result<constants::DwForm> {
+        Ok(match *self {
+            AttributeValue::Address(_) => constants::DW_FORM_addr,
+            AttributeValue::Block(ref val) => block_form(val.len()),
+            AttributeValue::Data1(_) => constants::DW_FORM_data1,
+            AttributeValue::Data2(_) => constants::DW_FORM_data2,
+            AttributeValue::Data4(_) => constants::DW_FORM_data4,
+            AttributeValue::Data8(_) => constants::DW_FORM_data8,
+            AttributeValue::Sdata(_) => constants::DW_FORM_sdata,
+            AttributeValue::Udata(val) => udata_form(val, version),
+            AttributeValue::Exprloc(_) if version >= 4 => constants::DW_FORM_exprloc,
+            AttributeValue::Exprloc(ref val) => block_form(val.size(address_size)),
+            AttributeValue::Flag(true) if version >= 4 => constants::DW_FORM_flag_present,
+            AttributeValue::Flag(_) => constants::DW_FORM_flag,
+            AttributeValue::UnitRef(_) => constants::DW_FORM_ref4,
+            AttributeValue::DebugInfoRef(..) => constants::DW_FORM_ref_addr,
+            AttributeValue::LineProgramRef |
+            AttributeValue::LocationListRef(_) |
+            AttributeValue::RangeListRef(_) => if version >= 4 {
+                constants::DW_FORM_sec_offset
+            } else {
+                constants::DW_FORM_data4
+            },
+            AttributeValue::DebugTypesRef(_) => constants::DW_FORM_ref_sig8,
+            AttributeValue::String(_) => constants::DW_FORM_string,
+            AttributeValue::StringRef(_) => constants::DW_FORM_strp,
+            AttributeValue::LineStringRef(_) if version >= 5 => constants::DW_FORM_line_strp,
+            AttributeValue::LineStringRef(_) => return Err(Error::InvalidAttributeValue),
+            AttributeValue::Encoding(val) => udata_form(val.0.into(), version),
+            AttributeValue::DecimalSign(val) => udata_form(val.0.into(), version),
+            AttributeValue::Endianity(val) => udata_form(val.0.into(), version),
+            AttributeValue::Accessibility(val) => udata_form(val.0.into(), version),
+            AttributeValue::Visibility(val) => udata_form(val.0.into(), version),
+            AttributeValue::Virtuality(val) => udata_form(val.0.into(), version),
+            AttributeValue::Language(val) => udata_form(val.0.into(), version),
+            AttributeValue::LanguageName(val) => udata_form(val.0.into(), version),
+            AttributeValue::AddressClass(val) => udata_form(val.0, version),
+            AttributeValue::IdentifierCase(val) => udata_form(val.0.into(), version),
+            AttributeValue::CallingConvention(val) => udata_form(val.0.into(), version),
+            AttributeValue::Inline(val) => udata_form(val.0.into(), version),
+            AttributeValue::Ordering(val) => udata_form(val.0.into(), version),
+            // File indices are small, so don't bother finding the index yet.
+            AttributeValue::FileIndex(_) => constants::DW_FORM_udata,
+        })
+    }
+}
+
+/// Return the smallest block form that can encode a block of the given
+/// length.
+fn block_form(len: usize) -> constants::DwForm {
+    if len <= 0xff {
+        constants::DW_FORM_block1
+    } else if len <= 0xffff {
+        constants::DW_FORM_block2
+    } else if len <= 0xffff_ffff {
+        constants::DW_FORM_block4
+    } else {
+        constants::DW_FORM_block
+    }
+}
+
+/// Return the smallest form that can encode an unsigned constant.
+///
+/// `DW_FORM_data4` and `DW_FORM_data8` are not used for DWARF versions
+/// before 4, since they may be interpreted as section offsets.
+fn udata_form(val: u64, version: u16) -> constants::DwForm {
+    let mut bytes = [0u8; 10];
+    let uleb_len = uleb128(&mut bytes, val);
+    if val <= 0xff {
+        constants::DW_FORM_data1
+    } else if val <= 0xffff && uleb_len > 2 {
+        constants::DW_FORM_data2
+    } else if val <= 0xffff_ffff && uleb_len > 4 && version >= 4 {
+        constants::DW_FORM_data4
+    } else {
+        constants::DW_FORM_udata
+    }
+}
+
+/// Write an unsigned constant using a form returned by `udata_form`.
+fn write_udata_form<W: Writer>(w: &mut W, val: u64, form: constants::DwForm) -> Result<()> {
+    match form {
+        constants::DW_FORM_data1 => w.write_udata(val, 1),
+        constants::DW_FORM_data2 => w.write_udata(val, 2),
+        constants::DW_FORM_data4 => w.write_udata(val, 4),
+        _ => w.write_uleb128(val),
+    }
+}
+
+/// Write a block using a form returned by `block_form`.
+fn write_block_form<W: Writer>(
+    w: &mut W,
+    len: usize,
+    form: constants::DwForm,
+) -> Result<()> {
+    match form {
+        constants::DW_FORM_block1 => w.write_udata(len as u64, 1),
+        constants::DW_FORM_block2 => w.write_udata(len as u64, 2),
+        constants::DW_FORM_block4 => w.write_udata(len as u64, 4),
+        _ => w.write_uleb128(len as u64),
+    }
+}
+
+/// The `.debug_info` offsets of a unit and its entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UnitOffsets {
+    unit: DebugInfoOffset,
+    entries: Vec<UnitOffset>,
+}
+
+/// The `.debug_info` offsets of the units and entries in a `UnitTable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugInfoOffsets {
+    units: Vec<UnitOffsets>,
+}
+
+impl DebugInfoOffsets {
+    /// Return the offset of the header of the given unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `unit` was not returned by the table that was written.
+    pub fn unit(&self, unit: UnitId) -> DebugInfoOffset {
+        self.units[unit.0].unit
+    }
+
+    /// Return the offset of the given entry relative to the start of its
+    /// unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `unit` or `entry` are invalid.
+    pub fn unit_offset(&self, unit: UnitId, entry: UnitEntryId) -> UnitOffset {
+        self.units[unit.0].entries[entry.0]
+    }
+
+    /// Return the section offset of the given entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `unit` or `entry` are invalid.
+    pub fn entry(&self, unit: UnitId, entry: UnitEntryId) -> DebugInfoOffset {
+        let unit = &self.units[unit.0];
+        DebugInfoOffset(unit.unit.0 + unit.entries[entry.0].0)
+    }
+}
+
+/// The state for writing the entries of a unit.
+struct WriteContext<'a> {
+    unit: &'a Unit,
+    unit_offset: usize,
+    line_program: Option<DebugLineOffset>,
+    range_lists: &'a RangeListOffsets,
+    loc_lists: &'a LocationListOffsets,
+    line_strings: &'a DebugLineStrOffsets,
+    strings: &'a DebugStrOffsets,
+    entry_offsets: Vec<UnitOffset>,
+    // The offsets of `DW_FORM_ref4` values that must be fixed up.
+    unit_refs: Vec<(usize, UnitEntryId)>,
+    // The offsets and sizes of `DW_FORM_ref_addr` values that must be fixed up.
+    debug_info_refs: &'a mut Vec<(usize, u8, UnitId, UnitEntryId)>,
+}
+
+impl<'a> WriteContext<'a> {
+    fn write_entry<W: Writer>(
+        &mut self,
+        w: &mut W,
+        abbrevs: &mut AbbreviationTable,
+        id: UnitEntryId,
+    ) -> Result<()> {
+        let entry = self.unit.get(id);
+        self.entry_offsets[id.0] = UnitOffset(w.len() - self.unit_offset);
+
+        let version = self.unit.version;
+        let mut specs = Vec::with_capacity(entry.attrs.len());
+        for attr in &entry.attrs {
+            specs.push(AttributeSpecification::new(
+                attr.name,
+                attr.value.form(version, self.unit.address_size)?,
+            ));
+        }
+        let has_children = !entry.children.is_empty();
+        let code = abbrevs.add(Abbreviation::new(entry.tag, has_children, specs.clone()));
+        w.write_uleb128(code)?;
+
+        for (attr, spec) in entry.attrs.iter().zip(specs.iter()) {
+            self.write_attribute_value(w, &attr.value, spec.form())?;
+        }
+
+        if has_children {
+            for &child in &entry.children {
+                self.write_entry(w, abbrevs, child)?;
+            }
+            // Null entry to terminate the children.
+            w.write_u8(0)?;
+        }
+        Ok(())
+    }
+
+    fn write_offset<W: Writer>(&self, w: &mut W, offset: usize) -> Result<()> {
+        w.write_udata(offset as u64, 4)
+    }
+
+    fn write_attribute_value<W: Writer>(
+        &mut self,
+        w: &mut W,
+        value: &AttributeValue,
+        form: constants::DwForm,
+    ) -> Result<()> {
+        let address_size = self.unit.address_size;
+        match *value {
+            AttributeValue::Address(address) => w.write_address(address, address_size),
+            AttributeValue::Block(ref val) => {
+                write_block_form(w, val.len(), form)?;
+                w.write(val)
+            }
+            AttributeValue::Data1(val) => w.write_u8(val),
+            AttributeValue::Data2(val) => w.write_u16(val),
+            AttributeValue::Data4(val) => w.write_u32(val),
+            AttributeValue::Data8(val) => w.write_u64(val),
+            AttributeValue::Sdata(val) => w.write_sleb128(val),
+            AttributeValue::Udata(val) => write_udata_form(w, val, form),
+            AttributeValue::Exprloc(ref val) => {
+                write_block_form(w, val.size(address_size), form)?;
+                val.write(w, address_size)
+            }
+            AttributeValue::Flag(val) => if form == constants::DW_FORM_flag_present {
+                Ok(())
+            } else {
+                w.write_u8(val as u8)
+            },
+            AttributeValue::UnitRef(id) => {
+                self.unit_refs.push((w.len(), id));
+                w.write_u32(0)
+            }
+            AttributeValue::DebugInfoRef(unit_id, entry_id) => {
+                let size = if self.unit.version == 2 {
+                    address_size
+                } else {
+                    4
+                };
+                self.debug_info_refs
+                    .push((w.len(), size, unit_id, entry_id));
+                w.write_udata(0, size)
+            }
+            AttributeValue::LineProgramRef => match self.line_program {
+                Some(offset) => self.write_offset(w, offset.0),
+                None => Err(Error::InvalidAttributeValue),
+            },
+            AttributeValue::LocationListRef(id) => {
+                let offset = self.loc_lists.get(id);
+                self.write_offset(w, offset.0)
+            }
+            AttributeValue::RangeListRef(id) => {
+                let offset = self.range_lists.get(id);
+                self.write_offset(w, offset.0)
+            }
+            AttributeValue::DebugTypesRef(val) => w.write_u64(val.0),
+            AttributeValue::String(ref val) => {
+                w.write(val)?;
+                w.write_u8(0)
+            }
+            AttributeValue::StringRef(id) => {
+                let offset = self.strings.get(id);
+                self.write_offset(w, offset.0)
+            }
+            AttributeValue::LineStringRef(id) => {
+                let offset = self.line_strings.get(id);
+                self.write_offset(w, offset.0)
+            }
+            AttributeValue::Encoding(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::DecimalSign(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Endianity(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Accessibility(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Visibility(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Virtuality(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Language(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::LanguageName(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::AddressClass(val) => write_udata_form(w, val.0, form),
+            AttributeValue::IdentifierCase(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::CallingConvention(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Inline(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::Ordering(val) => write_udata_form(w, val.0.into(), form),
+            AttributeValue::FileIndex(file) => {
+                let index = match (file, self.unit.line_program.as_ref()) {
+                    (None, _) => 0,
+                    (Some(file), Some(line_program)) => line_program.file_index(file),
+                    (Some(_), None) => return Err(Error::InvalidAttributeValue),
+                };
+                w.write_uleb128(index)
+            }
+        }
+    }
+}
+
+/// The state for converting the attributes of a read unit.
+struct ConvertContext<'a, R: Reader + 'a> {
+    dwarf: &'a ::Dwarf<R>,
+    read_unit: &'a ::Unit<R>,
+    unit_id: UnitId,
+    unit: &'a mut Unit,
+    entry_ids: &'a BTreeMap<u64, (UnitId, UnitEntryId)>,
+    strings: &'a mut StringTable,
+    // The converted file for each file index in the line number program.
+    files: Vec<FileId>,
+    convert_address: &'a dyn Fn(u64) -> Option<Address>,
+}
+
+impl<'a, R: Reader + 'a> ConvertContext<'a, R> {
+    fn convert_attributes(&mut self) -> ConvertResult<()> {
+        if let Some(ref program) = self.read_unit.line_program {
+            let (program, files) = LineProgram::from(
+                program.clone(),
+                self.read_unit.header.address_size(),
+                self.convert_address,
+            )?;
+            self.unit.line_program = Some(program);
+            self.files = files;
+        }
+
+        // Entries were allocated in depth-first order.
+        let mut index = 0;
+        let mut entries = self.read_unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            let id = UnitEntryId(index);
+            let mut attrs = entry.attrs();
+            while let Some(attr) = attrs.next()? {
+                match attr.name() {
+                    constants::DW_AT_sibling |
+                    constants::DW_AT_str_offsets_base |
+                    constants::DW_AT_addr_base |
+                    constants::DW_AT_rnglists_base |
+                    constants::DW_AT_loclists_base |
+                    constants::DW_AT_GNU_addr_base |
+                    constants::DW_AT_GNU_ranges_base => continue,
+                    _ => {}
+                }
+                let value = self.convert_attribute_value(attr.value())?;
+                self.unit.get_mut(id).set(attr.name(), value);
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn convert_address(&self, address: u64) -> ConvertResult<Address> {
+        (self.convert_address)(address).ok_or(ConvertError::InvalidAddress)
+    }
+
+    fn convert_debug_info_offset(&self, offset: u64) -> ConvertResult<AttributeValue> {
+        match self.entry_ids.get(&offset) {
+            Some(&(unit_id, entry_id)) => Ok(AttributeValue::DebugInfoRef(unit_id, entry_id)),
+            None => Err(ConvertError::InvalidDebugInfoOffset),
+        }
+    }
+
+    fn convert_attribute_value(
+        &mut self,
+        value: ::AttributeValue<R>,
+    ) -> ConvertResult<AttributeValue> {
+        let read_unit = self.read_unit;
+        let header = &read_unit.header;
+        let version = header.version();
+        let address_size = header.address_size();
+        Ok(match value {
+            ::AttributeValue::Addr(address) => {
+                AttributeValue::Address(self.convert_address(address)?)
+            }
+            ::AttributeValue::DebugAddrIndex(index) => {
+                let address = self.dwarf.address(read_unit, index)?;
+                AttributeValue::Address(self.convert_address(address)?)
+            }
+            ::AttributeValue::Block(data) => AttributeValue::Block(data.to_slice()?.to_vec()),
+            ::AttributeValue::Data1(data) => AttributeValue::Data1(data[0]),
+            ::AttributeValue::Data2((data, endian)) => {
+                AttributeValue::Data2(endian.read_u16(&data))
+            }
+            ::AttributeValue::Data4((data, endian)) => {
+                AttributeValue::Data4(endian.read_u32(&data))
+            }
+            ::AttributeValue::Data8((data, endian)) => {
+                AttributeValue::Data8(endian.read_u64(&data))
+            }
+            ::AttributeValue::Sdata(val) => AttributeValue::Sdata(val),
+            ::AttributeValue::Udata(val) => AttributeValue::Udata(val),
+            ::AttributeValue::Exprloc(expression) => {
+                AttributeValue::Exprloc(Expression::from(
+                    expression,
+                    address_size,
+                    header.format(),
+                    self.convert_address,
+                )?)
+            }
+            ::AttributeValue::Flag(val) => AttributeValue::Flag(val),
+            ::AttributeValue::UnitRef(offset) => {
+                let offset = offset.to_debug_info_offset(header);
+                match self.entry_ids.get(&offset.0.into_u64()) {
+                    Some(&(unit_id, entry_id)) if unit_id == self.unit_id => {
+                        AttributeValue::UnitRef(entry_id)
+                    }
+                    Some(&(unit_id, entry_id)) => AttributeValue::DebugInfoRef(unit_id, entry_id),
+                    None => return Err(ConvertError::InvalidUnitRef),
+                }
+            }
+            ::AttributeValue::DebugInfoRef(offset) => {
+                self.convert_debug_info_offset(offset.0.into_u64())?
+            }
+            ::AttributeValue::DebugLineRef(_) => {
+                if self.unit.line_program.is_none() {
+                    return Err(ConvertError::UnsupportedAttributeValue);
+                }
+                AttributeValue::LineProgramRef
+            }
+            ::AttributeValue::LocationListsRef(offset) => {
+                let mut locations = Vec::new();
+                let mut iter = self.dwarf.locations.locations(
+                    offset,
+                    version,
+                    address_size,
+                    read_unit.low_pc,
+                )?;
+                while let Some(location) = iter.next()? {
+                    locations.push(Location {
+                        begin: self.convert_address(location.range.begin)?,
+                        end: self.convert_address(location.range.end)?,
+                        data: Expression::from(
+                            location.data,
+                            address_size,
+                            header.format(),
+                            self.convert_address,
+                        )?,
+                    });
+                }
+                AttributeValue::LocationListRef(self.unit.locations.add(locations))
+            }
+            ::AttributeValue::DebugLocListsIndex(index) => {
+                let offset = self.dwarf.locations_offset(read_unit, index)?;
+                return self.convert_attribute_value(::AttributeValue::LocationListsRef(offset));
+            }
+            ::AttributeValue::RangeListsRef(offset) => {
+                let offset = self.dwarf.ranges_offset_from_raw(read_unit, offset);
+                let mut ranges = Vec::new();
+                let mut iter = self.dwarf
+                    .ranges
+                    .ranges(offset, version, address_size, read_unit.low_pc)?;
+                while let Some(range) = iter.next()? {
+                    ranges.push(Range {
+                        begin: self.convert_address(range.begin)?,
+                        end: self.convert_address(range.end)?,
+                    });
+                }
+                AttributeValue::RangeListRef(self.unit.ranges.add(ranges))
+            }
+            ::AttributeValue::DebugRngListsIndex(index) => {
+                let offset = self.dwarf.ranges_offset(read_unit, index)?;
+                return self.convert_attribute_value(::AttributeValue::RangeListsRef(offset));
+            }
+            ::AttributeValue::DebugTypesRef(signature) => AttributeValue::DebugTypesRef(signature),
+            ::AttributeValue::DebugStrRef(offset) => {
+                let string = self.dwarf.debug_str.get_str(offset)?;
+                AttributeValue::StringRef(self.strings.add(string.to_slice()?.to_vec()))
+            }
+            ::AttributeValue::DebugLineStrRef(offset) => {
+                let string = self.dwarf.debug_line_str.get_str(offset)?;
+                AttributeValue::StringRef(self.strings.add(string.to_slice()?.to_vec()))
+            }
+            ::AttributeValue::DebugStrOffsetsIndex(index) => {
+                let offset = self.dwarf.string_offset(read_unit, index)?;
+                let string = self.dwarf.debug_str.get_str(offset)?;
+                AttributeValue::StringRef(self.strings.add(string.to_slice()?.to_vec()))
+            }
+            ::AttributeValue::String(string) => AttributeValue::String(string.to_slice()?.to_vec()),
+            ::AttributeValue::Encoding(val) => AttributeValue::Encoding(val),
+            ::AttributeValue::DecimalSign(val) => AttributeValue::DecimalSign(val),
+            ::AttributeValue::Endianity(val) => AttributeValue::Endianity(val),
+            ::AttributeValue::Accessibility(val) => AttributeValue::Accessibility(val),
+            ::AttributeValue::Visibility(val) => AttributeValue::Visibility(val),
+            ::AttributeValue::Virtuality(val) => AttributeValue::Virtuality(val),
+            ::AttributeValue::Language(val) => AttributeValue::Language(val),
+            ::AttributeValue::LanguageName(val) => AttributeValue::LanguageName(val),
+            ::AttributeValue::AddressClass(val) => AttributeValue::AddressClass(val),
+            ::AttributeValue::IdentifierCase(val) => AttributeValue::IdentifierCase(val),
+            ::AttributeValue::CallingConvention(val) => AttributeValue::CallingConvention(val),
+            ::AttributeValue::Inline(val) => AttributeValue::Inline(val),
+            ::AttributeValue::Ordering(val) => AttributeValue::Ordering(val),
+            ::AttributeValue::FileIndex(index) => {
+                if index == 0 && version < 5 {
+                    AttributeValue::FileIndex(None)
+                } else {
+                    match self.files.get(index as usize) {
+                        Some(&file) => AttributeValue::FileIndex(Some(file)),
+                        None => return Err(ConvertError::InvalidFileIndex),
+                    }
+                }
+            }
+            // The writer doesn't support 128 bit constants yet.
+            ::AttributeValue::Data16(_) |
+            ::AttributeValue::Sdata128(_) |
+            ::AttributeValue::Udata128(_) |
+            ::AttributeValue::SecOffset(_) |
+            ::AttributeValue::DebugInfoRefSup(_) |
+            ::AttributeValue::DebugMacinfoRef(_) |
+            ::AttributeValue::DebugMacroRef(_) |
+            ::AttributeValue::DebugStrOffsetsBase(_) |
+            ::AttributeValue::DebugAddrBase(_) |
+            ::AttributeValue::DebugLocListsBase(_) |
+            ::AttributeValue::DebugRngListsBase(_) |
+            ::AttributeValue::DebugStrRefSup(_) => {
+                return Err(ConvertError::UnsupportedAttributeValue)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_entries() {
+        let mut units = UnitTable::new();
+        let unit_id = units.add(Unit::new(4, 8));
+        assert_eq!(units.count(), 1);
+        assert_eq!(units.id(0), unit_id);
+
+        let unit = units.get_mut(unit_id);
+        let root = unit.root();
+        let child1 = unit.add(root, constants::DW_TAG_subprogram);
+        let child2 = unit.add(root, constants::DW_TAG_variable);
+        let grandchild = unit.add(child1, constants::DW_TAG_formal_parameter);
+        assert_eq!(unit.count(), 4);
+
+        assert_eq!(unit.get(root).tag(), constants::DW_TAG_compile_unit);
+        assert_eq!(unit.get(root).parent(), None);
+        assert_eq!(
+            unit.get(root).children().cloned().collect::<Vec<_>>(),
+            vec![child1, child2]
+        );
+        assert_eq!(unit.get(grandchild).parent(), Some(child1));
+
+        let entry = unit.get_mut(child2);
+        entry.set(constants::DW_AT_name, AttributeValue::String(b"x".to_vec()));
+        entry.set(constants::DW_AT_type, AttributeValue::UnitRef(child1));
+        entry.set(constants::DW_AT_name, AttributeValue::String(b"y".to_vec()));
+        assert_eq!(entry.attrs().count(), 2);
+        assert_eq!(
+            entry.get(constants::DW_AT_name),
+            Some(&AttributeValue::String(b"y".to_vec()))
+        );
+        entry.delete(constants::DW_AT_name);
+        assert_eq!(entry.get(constants::DW_AT_name), None);
+        assert_eq!(entry.attrs().count(), 1);
+    }
+
+    #[test]
+    fn test_convert_multiple_unit_entries() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_abbrev = [
+            // Code, tag, children.
+            0x01, 0x11, 0x00,
+            0x00, 0x00,
+            0x00,
+        ];
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let debug_info = [
+            // Unit length, version, abbrev offset, address size.
+            0x09, 0x00, 0x00, 0x00,
+            0x04, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x08,
+            0x01,
+            0x01,
+        ];
+
+        let dwarf = ::Dwarf::load(|id| -> ::Result<_> {
+            let data = match id {
+                ::SectionId::DebugAbbrev => &debug_abbrev[..],
+                ::SectionId::DebugInfo => &debug_info[..],
+                _ => &[],
+            };
+            Ok(::EndianBuf::new(data, ::LittleEndian))
+        }).unwrap();
+        let mut strings = StringTable::new();
+        let convert_address = &|address| Some(Address::Constant(address));
+        assert_eq!(
+            UnitTable::from(&dwarf, &mut strings, convert_address).err(),
+            Some(ConvertError::MultipleUnitEntries)
+        );
+    }
+}
@@ -0,0 +1,484 @@
+use constants;
+use endianity::Endianity;
+use parser::Format;
+use write::{Address, Error, Result};
+
+/// A trait for writing the data to a DWARF section.
+///
+/// All write operations append to the section unless otherwise specified.
+pub trait Writer {
+    /// The endianity of bytes that are written.
+    type Endian: Endianity;
+
+    /// Return the endianity of bytes that are written.
+    fn endian(&self) -> Self::Endian;
+
+    /// Return the current section length.
+    ///
+    /// This may be used as an offset for future `write_at` calls.
+    fn len(&self) -> usize;
+
+    /// Write a slice.
+    fn write(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Write a slice at a given offset.
+    ///
+    /// The write must not extend past the current section length.
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()>;
+
+    /// Write a u8.
+    fn write_u8(&mut self, val: u8) -> Result<()> {
+        let bytes = [val];
+        self.write(&bytes)
+    }
+
+    /// Write a u16.
+    fn write_u16(&mut self, val: u16) -> Result<()> {
+        let mut bytes = [0; 2];
+        self.endian().write_u16(&mut bytes, val);
+        self.write(&bytes)
+    }
+
+    /// Write a u32.
+    fn write_u32(&mut self, val: u32) -> Result<()> {
+        let mut bytes = [0; 4];
+        self.endian().write_u32(&mut bytes, val);
+        self.write(&bytes)
+    }
+
+    /// Write a u64.
+    fn write_u64(&mut self, val: u64) -> Result<()> {
+        let mut bytes = [0; 8];
+        self.endian().write_u64(&mut bytes, val);
+        self.write(&bytes)
+    }
+
+    /// Write a u8 at the given offset.
+    fn write_u8_at(&mut self, offset: usize, val: u8) -> Result<()> {
+        let bytes = [val];
+        self.write_at(offset, &bytes)
+    }
+
+    /// Write a u16 at the given offset.
+    fn write_u16_at(&mut self, offset: usize, val: u16) -> Result<()> {
+        let mut bytes = [0; 2];
+        self.endian().write_u16(&mut bytes, val);
+        self.write_at(offset, &bytes)
+    }
+
+    /// Write a u32 at the given offset.
+    fn write_u32_at(&mut self, offset: usize, val: u32) -> Result<()> {
+        let mut bytes = [0; 4];
+        self.endian().write_u32(&mut bytes, val);
+        self.write_at(offset, &bytes)
+    }
+
+    /// Write a u64 at the given offset.
+    fn write_u64_at(&mut self, offset: usize, val: u64) -> Result<()> {
+        let mut bytes = [0; 8];
+        self.endian().write_u64(&mut bytes, val);
+        self.write_at(offset, &bytes)
+    }
+
+    /// Write an unsigned LEB128 encoded integer.
+    fn write_uleb128(&mut self, val: u64) -> Result<()> {
+        let mut bytes = [0u8; 10];
+        let len = uleb128(&mut bytes, val);
+        self.write(&bytes[..len])
+    }
+
+    /// Write a signed LEB128 encoded integer.
+    fn write_sleb128(&mut self, val: i64) -> Result<()> {
+        let mut bytes = [0u8; 10];
+        let len = sleb128(&mut bytes, val);
+        self.write(&bytes[..len])
+    }
+
+    /// Write an unsigned integer of the given size.
+    ///
+    /// Returns an error if the value does not fit in the size.
+    fn write_udata(&mut self, val: u64, size: u8) -> Result<()> {
+        match size {
+            1 => {
+                let write_val = val as u8;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u8(write_val)
+            }
+            2 => {
+                let write_val = val as u16;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u16(write_val)
+            }
+            4 => {
+                let write_val = val as u32;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u32(write_val)
+            }
+            8 => self.write_u64(val),
+            otherwise => Err(Error::UnsupportedWordSize(otherwise)),
+        }
+    }
+
+    /// Write a signed integer of the given size.
+    ///
+    /// Returns an error if the value does not fit in the size.
+    fn write_sdata(&mut self, val: i64, size: u8) -> Result<()> {
+        match size {
+            1 => {
+                let write_val = val as i8;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u8(write_val as u8)
+            }
+            2 => {
+                let write_val = val as i16;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u16(write_val as u16)
+            }
+            4 => {
+                let write_val = val as i32;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u32(write_val as u32)
+            }
+            8 => self.write_u64(val as u64),
+            otherwise => Err(Error::UnsupportedWordSize(otherwise)),
+        }
+    }
+
+    /// Write an unsigned integer of the given size at the given offset.
+    ///
+    /// Returns an error if the value does not fit in the size.
+    fn write_udata_at(&mut self, offset: usize, val: u64, size: u8) -> Result<()> {
+        match size {
+            1 => {
+                let write_val = val as u8;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u8_at(offset, write_val)
+            }
+            2 => {
+                let write_val = val as u16;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u16_at(offset, write_val)
+            }
+            4 => {
+                let write_val = val as u32;
+                if val != u64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u32_at(offset, write_val)
+            }
+            8 => self.write_u64_at(offset, val),
+            otherwise => Err(Error::UnsupportedWordSize(otherwise)),
+        }
+    }
+
+    /// Write a word of the size given by the DWARF format.
+    ///
+    /// Returns an error if the value does not fit in the word.
+    fn write_word(&mut self, val: u64, format: Format) -> Result<()> {
+        self.write_udata(val, format.word_size())
+    }
+
+    /// Write a word of the size given by the DWARF format at the given
+    /// offset.
+    ///
+    /// Returns an error if the value does not fit in the word.
+    fn write_word_at(&mut self, offset: usize, val: u64, format: Format) -> Result<()> {
+        self.write_udata_at(offset, val, format.word_size())
+    }
+
+    /// Write an address of the given size.
+    ///
+    /// The default implementation only supports constant addresses, and
+    /// returns an error for symbol addresses. Writers that produce
+    /// relocatable output should override this to record a relocation for
+    /// symbol addresses at the current offset.
+    fn write_address(&mut self, address: Address, size: u8) -> Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata(val, size),
+            Address::Symbol { .. } => Err(Error::UnsupportedSymbolAddress),
+        }
+    }
+
+    /// Write an address of the given size at the given offset.
+    ///
+    /// The default implementation only supports constant addresses, and
+    /// returns an error for symbol addresses.
+    fn write_address_at(&mut self, offset: usize, address: Address, size: u8) -> Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata_at(offset, val, size),
+            Address::Symbol { .. } => Err(Error::UnsupportedSymbolAddress),
+        }
+    }
+
+    /// Write an address using the given `DW_EH_PE_*` pointer encoding.
+    ///
+    /// `section_address` is the address of the start of the section, and is
+    /// used for `DW_EH_PE_pcrel`. Other relative applications are not
+    /// supported. The `DW_EH_PE_indirect` flag is ignored, since `address` is
+    /// already the address of the real pointer for indirect encodings.
+    ///
+    /// Symbol addresses are written using `write_address`, and are only
+    /// supported for absolute encodings with the same size as an address or
+    /// a fixed size. Writers that can record PC-relative relocations should
+    /// override this to support `DW_EH_PE_pcrel` for symbol addresses.
+    fn write_eh_pointer(
+        &mut self,
+        address: Address,
+        encoding: constants::DwEhPe,
+        address_size: u8,
+        section_address: u64,
+    ) -> Result<()> {
+        let address = match address {
+            Address::Constant(address) => address,
+            Address::Symbol { .. } => {
+                if encoding.application() != constants::DW_EH_PE_absptr {
+                    return Err(Error::UnsupportedSymbolAddress);
+                }
+                let size = match encoding.format() {
+                    constants::DW_EH_PE_absptr => address_size,
+                    constants::DW_EH_PE_udata2 | constants::DW_EH_PE_sdata2 => 2,
+                    constants::DW_EH_PE_udata4 | constants::DW_EH_PE_sdata4 => 4,
+                    constants::DW_EH_PE_udata8 | constants::DW_EH_PE_sdata8 => 8,
+                    _ => return Err(Error::UnsupportedPointerEncoding(encoding)),
+                };
+                return self.write_address(address, size);
+            }
+        };
+        let address = match encoding.application() {
+            constants::DW_EH_PE_absptr => address,
+            constants::DW_EH_PE_pcrel => {
+                address.wrapping_sub(section_address.wrapping_add(self.len() as u64))
+            }
+            _ => return Err(Error::UnsupportedPointerEncoding(encoding)),
+        };
+        self.write_eh_pointer_data(address, encoding.format(), address_size)
+    }
+
+    /// Write a value using the format of the given `DW_EH_PE_*` pointer
+    /// encoding, without applying any relative application.
+    fn write_eh_pointer_data(
+        &mut self,
+        val: u64,
+        format: constants::DwEhPe,
+        address_size: u8,
+    ) -> Result<()> {
+        match format.format() {
+            constants::DW_EH_PE_absptr => self.write_udata(val, address_size),
+            constants::DW_EH_PE_uleb128 => self.write_uleb128(val),
+            constants::DW_EH_PE_udata2 => self.write_udata(val, 2),
+            constants::DW_EH_PE_udata4 => self.write_udata(val, 4),
+            constants::DW_EH_PE_udata8 => self.write_u64(val),
+            constants::DW_EH_PE_sleb128 => self.write_sleb128(val as i64),
+            constants::DW_EH_PE_sdata2 => self.write_sdata(val as i64, 2),
+            constants::DW_EH_PE_sdata4 => self.write_sdata(val as i64, 4),
+            constants::DW_EH_PE_sdata8 => self.write_u64(val),
+            _ => Err(Error::UnsupportedPointerEncoding(format)),
+        }
+    }
+}
+
+/// Encode `val` as unsigned LEB128 into `buf`, returning the length.
+pub(crate) fn uleb128(buf: &mut [u8; 10], mut val: u64) -> usize {
+    let mut len = 0;
+    loop {
+        let mut byte = (val & 0x7f) as u8;
+        val >>= 7;
+        if val != 0 {
+            byte |= 0x80;
+        }
+        buf[len] = byte;
+        len += 1;
+        if val == 0 {
+            return len;
+        }
+    }
+}
+
+/// Encode `val` as signed LEB128 into `buf`, returning the length.
+pub(crate) fn sleb128(buf: &mut [u8; 10], mut val: i64) -> usize {
+    let mut len = 0;
+    loop {
+        let mut byte = val as u8;
+        // Keep the sign bit for testing.
+        val >>= 6;
+        let done = val == 0 || val == -1;
+        if done {
+            byte &= 0x7f;
+        } else {
+            val >>= 1;
+            byte |= 0x80;
+        }
+        buf[len] = byte;
+        len += 1;
+        if done {
+            return len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endianity::LittleEndian;
+    use leb128;
+    use vec::Vec;
+    use write::EndianVec;
+    use EndianBuf;
+
+    #[test]
+    fn test_write_leb128() {
+        for &val in &[0u64, 1, 63, 64, 127, 128, 0x3fff, 0x4000, u64::max_value()] {
+            let mut w = EndianVec::new(LittleEndian);
+            w.write_uleb128(val).unwrap();
+            let mut r = EndianBuf::new(w.slice(), LittleEndian);
+            assert_eq!(leb128::read::unsigned(&mut r), Ok(val));
+        }
+        for &val in &[0i64, 1, -1, 63, -64, 64, -65, i64::min_value(), i64::max_value()] {
+            let mut w = EndianVec::new(LittleEndian);
+            w.write_sleb128(val).unwrap();
+            let mut r = EndianBuf::new(w.slice(), LittleEndian);
+            assert_eq!(leb128::read::signed(&mut r), Ok(val));
+        }
+    }
+
+    #[test]
+    fn test_write_data() {
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_udata(0xff, 1).unwrap();
+        w.write_sdata(-2, 2).unwrap();
+        assert_eq!(w.write_udata(0x100, 1), Err(Error::ValueTooLarge));
+        assert_eq!(w.write_sdata(0x8000, 2), Err(Error::ValueTooLarge));
+        assert_eq!(w.write_udata(0, 3), Err(Error::UnsupportedWordSize(3)));
+        assert_eq!(w.slice(), &[0xff, 0xfe, 0xff]);
+    }
+
+    #[test]
+    fn test_write_at() {
+        let mut w = EndianVec::new(LittleEndian);
+        w.write(&[0; 16]).unwrap();
+        w.write_u8_at(0, 1).unwrap();
+        w.write_u16_at(1, 0x0302).unwrap();
+        w.write_udata_at(3, 0x04, 1).unwrap();
+        w.write_word_at(4, 0x0807_0605, Format::Dwarf32).unwrap();
+        w.write_word_at(8, 0x100f_0e0d_0c0b_0a09, Format::Dwarf64)
+            .unwrap();
+        assert_eq!(
+            w.write_word_at(0, 0x1_0000_0000, Format::Dwarf32),
+            Err(Error::ValueTooLarge)
+        );
+        assert_eq!(w.write_u64_at(9, 0), Err(Error::OffsetOutOfBounds));
+        assert_eq!(
+            w.slice(),
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+        );
+
+        w.write_word(0x1234, Format::Dwarf64).unwrap();
+        w.write_address_at(16, Address::Constant(0x5678), 4)
+            .unwrap();
+        assert_eq!(&w.slice()[16..], &[0x78, 0x56, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_write_eh_pointer() {
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_u8(0).unwrap();
+        let pcrel_sdata4 =
+            constants::DwEhPe(constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sdata4.0);
+        w.write_eh_pointer(Address::Constant(0x1000), pcrel_sdata4, 8, 0x1010)
+            .unwrap();
+        w.write_eh_pointer(Address::Constant(0x1234), constants::DW_EH_PE_udata2, 8, 0)
+            .unwrap();
+        assert_eq!(
+            w.write_eh_pointer(Address::Constant(0), constants::DW_EH_PE_textrel, 8, 0),
+            Err(Error::UnsupportedPointerEncoding(constants::DW_EH_PE_textrel))
+        );
+        let symbol = Address::Symbol {
+            symbol: 1,
+            addend: 0,
+        };
+        assert_eq!(
+            w.write_eh_pointer(symbol, pcrel_sdata4, 8, 0),
+            Err(Error::UnsupportedSymbolAddress)
+        );
+        assert_eq!(w.slice(), &[0, 0xef, 0xff, 0xff, 0xff, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn test_write_address() {
+        // Records the offset and address of each symbol address.
+        struct RelocVec {
+            data: EndianVec<LittleEndian>,
+            relocs: Vec<(usize, usize, i64)>,
+        }
+
+        impl Writer for RelocVec {
+            type Endian = LittleEndian;
+
+            fn endian(&self) -> LittleEndian {
+                self.data.endian()
+            }
+
+            fn len(&self) -> usize {
+                self.data.len()
+            }
+
+            fn write(&mut self, bytes: &[u8]) -> Result<()> {
+                self.data.write(bytes)
+            }
+
+            fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+                self.data.write_at(offset, bytes)
+            }
+
+            fn write_address(&mut self, address: Address, size: u8) -> Result<()> {
+                match address {
+                    Address::Constant(val) => self.write_udata(val, size),
+                    Address::Symbol { symbol, addend } => {
+                        self.relocs.push((self.data.len(), symbol, addend));
+                        self.write_udata(0, size)
+                    }
+                }
+            }
+        }
+
+        let symbol = Address::Symbol {
+            symbol: 3,
+            addend: 0x10,
+        };
+
+        let mut w = EndianVec::new(LittleEndian);
+        w.write_address(Address::Constant(0x1234), 2).unwrap();
+        assert_eq!(
+            w.write_address(symbol, 4),
+            Err(Error::UnsupportedSymbolAddress)
+        );
+        assert_eq!(w.slice(), &[0x34, 0x12]);
+
+        let mut w = RelocVec {
+            data: EndianVec::new(LittleEndian),
+            relocs: Vec::new(),
+        };
+        w.write_address(Address::Constant(0x1234), 2).unwrap();
+        w.write_address(symbol, 4).unwrap();
+        w.write_eh_pointer(symbol, constants::DW_EH_PE_udata8, 4, 0)
+            .unwrap();
+        assert_eq!(w.relocs, vec![(2, 3, 0x10), (6, 3, 0x10)]);
+        assert_eq!(w.data.len(), 14);
+    }
+}